@@ -1,16 +1,27 @@
-use std::{fs::File, io::Read, process};
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    process,
+};
 
-use bluebell::support::{
-    evm::EvmCompiler,
-    modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+use bluebell::{
+    diagnostics::{render_diagnostics, Diagnostic},
+    intermediate_representation::pass_manager::PassManager,
+    support::{
+        evm::EvmCompiler,
+        modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+    },
 };
 use clap::{Parser, Subcommand, ValueEnum};
-use evm_assembly::types::EvmTypeValue;
-use log::{Log, Metadata, Record};
-use scilla_parser::{
-    ast::nodes::NodeProgram,
-    parser::{lexer, lexer::Lexer, parser, ParserError},
+use evm_assembly::{
+    abi::Abi,
+    control_flow_graph::ControlFlowGraph,
+    executor::EvmExecutor,
+    hardfork::Hardfork,
+    types::{arguments_from_json, EvmTypeValue},
 };
+use log::{Log, Metadata, Record};
 
 // Logger struct to capture logs
 struct CaptureLogger {}
@@ -53,6 +64,15 @@ fn setup_logger() {
 #[derive(Clone, Debug, Subcommand)]
 enum BluebellOutputFormat {
     FormattedScilla,
+    /// Intermediate representation in its textual syntax, before any passes have run
+    Ir,
+}
+
+// Enum to define the formats the control flow graph is output in
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum CfgFormat {
+    Dot,
+    Json,
 }
 
 // Enum to define the backend of Bluebell
@@ -82,10 +102,56 @@ enum BluebellCommand {
         #[arg(short, long)]
         entry_point: String,
 
-        /// Arguments to pass to function
+        /// Arguments to pass to function, either as a JSON array or as a message in the format
+        /// used by `scilla-runner` with the arguments given in `params`
         #[arg(short, long, default_value_t= String::new())]
         args: String,
     },
+    /// Compiles the program and outputs the bytecode as hex
+    Compile {
+        /// Read the input as intermediate representation in its textual syntax rather than
+        /// as Scilla
+        #[arg(long = "from-ir", default_value_t = false)]
+        from_ir: bool,
+
+        /// Filename of output file
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Filename to write the JSON storage layout manifest to
+        #[arg(long = "storage-layout")]
+        storage_layout: Option<String>,
+
+        /// Filename to write the Solidity compatible JSON ABI to
+        #[arg(long = "abi")]
+        abi: Option<String>,
+
+        /// Output the creation bytecode, which takes the ABI encoded contract parameters,
+        /// rather than the runtime bytecode
+        #[arg(long = "deploy", default_value_t = false)]
+        deploy: bool,
+    },
+    /// Labels fields and transition parameters as money or non-money, estimates the gas used by
+    /// each transition and reports suspicious flows of funds as JSON
+    Cashflow {
+        /// Filename of output file
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Recovers the control flow graph of bytecode given in hex, e.g. of a deployed contract
+    Decompile {
+        /// Format to output the graph in
+        #[arg(long, value_enum, default_value_t = CfgFormat::Dot)]
+        format: CfgFormat,
+
+        /// Filename of a JSON ABI to name the functions by
+        #[arg(long = "abi")]
+        abi: Option<String>,
+
+        /// Filename of output file
+        #[arg(short, long)]
+        output: Option<String>,
+    },
 }
 
 // Struct to hold the arguments for Scilla compiler and executor
@@ -103,11 +169,92 @@ struct Args {
     #[arg(long = "runtime-enable")]
     features_raw: Option<String>,
 
+    /// Directories to search for imported libraries
+    #[arg(short = 'L', long = "library-path")]
+    library_paths: Vec<String>,
+
+    /// Options controlling the compiler passes
+    #[command(flatten)]
+    pipeline: PipelineArgs,
+
     /// Command to execute
     #[command(subcommand)]
     mode: BluebellCommand,
 }
 
+// Struct to hold the arguments configuring the pass manager
+#[derive(clap::Args, Debug)]
+struct PipelineArgs {
+    /// Optimisation level, from 0 (none) to 2
+    #[arg(short = 'O', long = "optimisation-level", default_value_t = 0)]
+    optimisation_level: u32,
+
+    /// Comma separated passes to run, e.g. `collect-types,state-alloc,...`, or `@file` to
+    /// read them from a file
+    #[arg(long)]
+    passes: Option<String>,
+
+    /// Print the IR after the given pass
+    #[arg(long = "print-after")]
+    print_after: Vec<String>,
+
+    /// Print the IR after every pass
+    #[arg(long = "print-after-all", default_value_t = false)]
+    print_after_all: bool,
+
+    /// Report the time spent in each pass
+    #[arg(long = "time-passes", default_value_t = false)]
+    time_passes: bool,
+
+    /// Verify the IR after every pass, which debug builds always do
+    #[arg(long = "verify-each", default_value_t = false)]
+    verify_each: bool,
+
+    /// Hardfork to generate code for and run with, e.g. `shanghai` to use PUSH0
+    #[arg(long, default_value_t = Hardfork::Istanbul)]
+    hardfork: Hardfork,
+}
+
+// Implementation of PipelineArgs struct
+impl PipelineArgs {
+    // Method to create the pass manager described by the arguments
+    fn pass_manager(&self) -> Result<PassManager, String> {
+        let mut ret = match &self.passes {
+            Some(spec) => {
+                let spec = match spec.strip_prefix('@') {
+                    Some(filename) => std::fs::read_to_string(filename)
+                        .map_err(|e| format!("Unable to read {}: {}", filename, e))?,
+                    None => spec.clone(),
+                };
+                PassManager::from_spec(&spec)?
+            }
+            None => PassManager::default_pipeline(),
+        };
+
+        ret.set_optimisation_level(self.optimisation_level)
+            .set_print_after_all(self.print_after_all)
+            .set_time_passes(self.time_passes);
+        if self.verify_each {
+            ret.set_verify_each(true);
+        }
+        for name in self.print_after.iter() {
+            ret.set_print_after(name)?;
+        }
+
+        Ok(ret)
+    }
+}
+
+// Function to print the IR dumps and timings collected while running the passes
+fn report_pass_manager(pass_manager: &PassManager) {
+    for (name, ir) in pass_manager.ir_dumps() {
+        eprintln!("; IR after {}\n{}", name, ir);
+    }
+    if !pass_manager.timings().is_empty() {
+        eprint!("{}", pass_manager.timing_report());
+    }
+}
+
 // Implementation of Args struct
 impl Args {
     // Method to get the features
@@ -117,17 +264,41 @@ impl Args {
             _ => Vec::new(),
         }
     }
+
+    // Method to get the library search paths, starting with the directory of the source file
+    fn library_paths(&self) -> Vec<PathBuf> {
+        let mut ret = Vec::new();
+        if let Some(parent) = Path::new(&self.filename).parent() {
+            ret.push(parent.to_path_buf());
+        }
+        ret.extend(self.library_paths.iter().map(PathBuf::from));
+        ret
+    }
 }
 
 // Function to run Bluebell with EVM backend
 fn bluebell_evm_run(
-    ast: &NodeProgram,
+    script: &str,
     entry_point: String,
     args: String,
     features: Vec<String>,
-    _debug: bool,
+    library_paths: Vec<PathBuf>,
+    pipeline: &PipelineArgs,
+    debug: bool,
 ) {
     let mut compiler = EvmCompiler::new();
+    compiler.context.hardfork = pipeline.hardfork;
+    match pipeline.pass_manager() {
+        Ok(pass_manager) => *compiler.pass_manager_mut() = pass_manager,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(-1);
+        }
+    }
+
+    for path in library_paths {
+        compiler.add_library_path(path);
+    }
 
     // Defining capabilities
     let default_types = ScillaDefaultTypes {};
@@ -148,138 +319,282 @@ fn bluebell_evm_run(
         }
     }
 
-    let executable = match compiler.executable_from_ast(ast) {
-        Err(e) => panic!("{:?}", e),
-        Ok(v) => v,
+    // Reporting all errors found in the program at once
+    let result = compiler.compile_with_diagnostics(script.to_string());
+    report_pass_manager(compiler.pass_manager_mut());
+
+    let executable = match result {
+        Err(diagnostics) => {
+            eprint!("{}", render_diagnostics(&diagnostics, script));
+            process::exit(-1);
+        }
+        Ok(v) => {
+            if debug {
+                if let Some(report) = &v.peephole_report {
+                    eprint!("{}", report.to_string());
+                }
+            }
+            EvmExecutor::new(&compiler.context, v)
+        }
     };
 
     let arguments: Vec<EvmTypeValue> = if args == "" {
         [].to_vec()
     } else {
-        serde_json::from_str(&args).expect("Failed to deserialize arguments")
+        match arguments_from_json(&args) {
+            Ok(arguments) => arguments,
+            Err(e) => {
+                eprintln!("Invalid arguments: {}", e);
+                process::exit(-1);
+            }
+        }
     };
 
-    executable.execute(&entry_point, arguments);
+    if let Err(e) = executable.execute(&entry_point, arguments) {
+        eprintln!("{}", e);
+        process::exit(-1);
+    }
 }
 
-// Main function
-fn main() {
-    // Setting up the logger
-    setup_logger();
-    // Parsing the arguments
-    let args = Args::parse();
+// Function to write the output of a command to a file or stdout
+fn write_output(output: Option<String>, value: &str) {
+    match output {
+        Some(filename) => std::fs::write(&filename, value).expect("Unable to write file"),
+        None => println!("{}", value),
+    }
+}
 
-    // Getting the features
-    let features = args.features();
-    // Accessing the values
-    let mut errors: Vec<lexer::ParseError> = [].to_vec();
-    // Opening the file
-    let mut file = File::open(args.filename).expect("Unable to open file");
-    let mut script = String::new();
-    // Reading the file
-    file.read_to_string(&mut script)
-        .expect("Unable to read file");
+// Function to create a compiler with the default modules
+fn create_compiler(library_paths: Vec<PathBuf>, pipeline: &PipelineArgs) -> EvmCompiler {
+    let mut compiler = EvmCompiler::new();
+    compiler.context.hardfork = pipeline.hardfork;
+    match pipeline.pass_manager() {
+        Ok(pass_manager) => *compiler.pass_manager_mut() = pass_manager,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(-1);
+        }
+    }
 
-    // Creating a new lexer
-    let lexer = Lexer::new(&script);
-
-    // Creating a new parser
-    let parser = parser::ProgramParser::new();
-
-    // Parsing the script
-    match parser.parse(&mut errors, lexer) {
-        Ok(ast) => {
-            // Running the appropriate command based on the mode
-            match args.mode {
-                BluebellCommand::Run {
-                    entry_point,
-                    args: arguments,
-                    backend,
-                } => match backend {
-                    // Running with EVM backend
-                    BluebellBackend::Evm => {
-                        bluebell_evm_run(&ast, entry_point, arguments, features, args.debug)
-                    }
-                },
-                _ => unimplemented!(),
-            }
+    for path in library_paths {
+        compiler.add_library_path(path);
+    }
+
+    let default_types = ScillaDefaultTypes {};
+    let default_builtins = ScillaDefaultBuiltins {};
+
+    compiler.attach(&default_types);
+    compiler.attach(&default_builtins);
+
+    compiler
+}
+
+// Function to emit the intermediate representation of the program
+fn bluebell_emit_ir(
+    script: &str,
+    library_paths: Vec<PathBuf>,
+    pipeline: &PipelineArgs,
+    output: Option<String>,
+) {
+    let mut compiler = create_compiler(library_paths, pipeline);
+    match compiler.emit_ir(script.to_string()) {
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(-1);
+        }
+        Ok(ir) => write_output(output, &ir),
+    }
+}
 
-            /*
-            //let _inferred_types = infer_types(&ast).unwrap();
-            let mut formatter = BluebellFormatter::new();
-            let mut ast2 = ast.clone();
-            let formatted_ast = formatter.emit(&mut ast2); // Call to_string on the top-level AST node to get formatted output
+// Function to compile a program, given either as Scilla or as IR, to bytecode
+fn bluebell_compile(
+    script: &str,
+    from_ir: bool,
+    library_paths: Vec<PathBuf>,
+    pipeline: &PipelineArgs,
+    output: Option<String>,
+    storage_layout: Option<String>,
+    abi: Option<String>,
+    deploy: bool,
+) {
+    let mut compiler = create_compiler(library_paths, pipeline);
+    let result = if from_ir {
+        compiler.compile_ir(script).map_err(|e| format!("{}\n", e))
+    } else {
+        compiler
+            .compile_with_diagnostics(script.to_string())
+            .map_err(|diagnostics| render_diagnostics(&diagnostics, script))
+    };
+    report_pass_manager(compiler.pass_manager_mut());
 
-            let mut formatter = BluebellFormatter::new();
-            let mut ast2 = ast.clone();
-            formatter.emit(&mut ast2);
-            */
+    match result {
+        Err(e) => {
+            eprint!("{}", e);
+            process::exit(-1);
         }
-        Err(error) => {
-            // Handling syntax errors
-            let message = format!("Syntax error {:?}", error);
-            let mut pos: Vec<lexer::SourcePosition> = [].to_vec();
-            error.map_location(|l| {
-                pos.push(l.clone());
-                l
-            });
-
-            let mut n = 0;
-            let mut line_counter = 0;
-            let mut char_counter = 0;
-            let mut line_start = 0;
-            let mut line_end = 0;
-            let mut should_stop = false;
-            for ch in script.chars() {
-                if ch == '\n' {
-                    if should_stop {
-                        line_end = n;
-                        break;
-                    } else {
-                        line_start = n + 1;
+        Ok(executable) => {
+            if let Some(filename) = storage_layout {
+                match executable.storage_layout.to_json() {
+                    Ok(json) => write_output(Some(filename), &json),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        process::exit(-1);
                     }
                 }
-                if !should_stop && n == pos[0].position {
-                    should_stop = true;
+            }
+            if let Some(filename) = abi {
+                match executable.abi.to_json() {
+                    Ok(json) => write_output(Some(filename), &json),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        process::exit(-1);
+                    }
                 }
+            }
+            if deploy {
+                write_output(output, &hex::encode(&executable.deploy_bytecode))
+            } else {
+                write_output(output, &hex::encode(&executable.bytecode))
+            }
+        }
+    }
+}
 
-                n += 1;
-                if !should_stop {
-                    char_counter += 1;
-                }
+// Function to run the cashflow analysis and write its JSON report
+fn bluebell_cashflow(script: &str, library_paths: Vec<PathBuf>, output: Option<String>) {
+    let mut compiler = EvmCompiler::new();
 
-                if ch == '\n' {
-                    line_counter += 1;
-                    char_counter = 0;
-                }
-            }
+    for path in library_paths {
+        compiler.add_library_path(path);
+    }
 
-            if line_end < line_start {
-                line_end = script.len();
-            }
+    let default_types = ScillaDefaultTypes {};
+    let default_builtins = ScillaDefaultBuiltins {};
 
-            // Printing the line with the error
-            let line = &script[line_start..line_end];
-            println!("Line {},{}:{}", line_counter, char_counter, line);
-            print!(
-                "{}",
-                " ".repeat(char_counter + format!("Line {},{}:", line_counter, char_counter).len())
-            );
-            if pos.len() > 1 {
-                println!("{}", "^".repeat(pos[1].position - pos[0].position));
-            }
+    compiler.attach(&default_types);
+    compiler.attach(&default_builtins);
+
+    let report = match compiler.cashflow(script.to_string()) {
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(-1);
+        }
+        Ok(v) => v,
+    };
 
-            // Creating a new ParserError
-            let my_error = ParserError {
-                message,
-                line: 0,   //error.location_line(),
-                column: 0, // err.location_column(),
-            };
-            // Printing the error
-            println!("{}", my_error);
+    // Warnings are shown with the source while the report only contains their location
+    let warnings: Vec<Diagnostic> = report.warnings.iter().map(|w| w.to_diagnostic()).collect();
+    eprint!("{}", render_diagnostics(&warnings, script));
 
-            // Exiting the process with an error code
+    let json = report
+        .to_json()
+        .expect("Failed to serialize cashflow report");
+    write_output(output, &json);
+}
+
+// Function to recover the control flow graph of bytecode and write it as DOT or JSON
+fn bluebell_decompile(
+    bytecode: &str,
+    format: CfgFormat,
+    abi: Option<String>,
+    output: Option<String>,
+) {
+    let bytecode = bytecode.trim();
+    let bytecode = match hex::decode(bytecode.strip_prefix("0x").unwrap_or(bytecode)) {
+        Ok(bytecode) => bytecode,
+        Err(e) => {
+            eprintln!("Invalid bytecode: {}", e);
             process::exit(-1);
         }
+    };
+
+    let mut cfg = ControlFlowGraph::from_bytecode(&bytecode);
+    if let Some(filename) = abi {
+        let json = std::fs::read_to_string(filename).expect("Unable to read ABI");
+        match Abi::from_json(&json) {
+            Ok(abi) => cfg.name_functions(&abi),
+            Err(e) => {
+                eprintln!("Invalid ABI: {}", e);
+                process::exit(-1);
+            }
+        }
+    }
+
+    match format {
+        CfgFormat::Dot => write_output(output, &cfg.to_dot()),
+        CfgFormat::Json => match cfg.to_json() {
+            Ok(json) => write_output(output, &json),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(-1);
+            }
+        },
+    }
+}
+
+// Main function
+fn main() {
+    // Setting up the logger
+    setup_logger();
+    // Parsing the arguments
+    let args = Args::parse();
+
+    // Getting the features
+    let features = args.features();
+    // Getting the library search paths
+    let library_paths = args.library_paths();
+    // Opening the file
+    let mut file = File::open(args.filename).expect("Unable to open file");
+    let mut script = String::new();
+    // Reading the file
+    file.read_to_string(&mut script)
+        .expect("Unable to read file");
+
+    // Running the appropriate command based on the mode. Syntax errors are reported together
+    // with the other compilation errors.
+    match args.mode {
+        BluebellCommand::Run {
+            entry_point,
+            args: arguments,
+            backend,
+        } => match backend {
+            // Running with EVM backend
+            BluebellBackend::Evm => bluebell_evm_run(
+                &script,
+                entry_point,
+                arguments,
+                features,
+                library_paths,
+                &args.pipeline,
+                args.debug,
+            ),
+        },
+        BluebellCommand::Emit {
+            format: BluebellOutputFormat::Ir,
+            output,
+        } => bluebell_emit_ir(&script, library_paths, &args.pipeline, output),
+        BluebellCommand::Compile {
+            from_ir,
+            output,
+            storage_layout,
+            abi,
+            deploy,
+        } => bluebell_compile(
+            &script,
+            from_ir,
+            library_paths,
+            &args.pipeline,
+            output,
+            storage_layout,
+            abi,
+            deploy,
+        ),
+        BluebellCommand::Cashflow { output } => bluebell_cashflow(&script, library_paths, output),
+        BluebellCommand::Decompile {
+            format,
+            abi,
+            output,
+        } => bluebell_decompile(&script, format, abi, output),
+        _ => unimplemented!(),
     }
 }
@@ -30,3 +30,40 @@ pub const FUNCTION_RETURN_TYPE_SEPARATOR: &str = "->";
 
 /// prefix for local variables
 pub const LOCAL_VAR_PREFIX: &str = "%";
+
+/// name of the builtin map type, i.e. `Map<Key,Value>`
+pub const MAP_TYPE_NAME: &str = "Map";
+
+/// name of the argument through which a lifted lambda receives its environment record
+pub const CLOSURE_ENVIRONMENT_ARGUMENT: &str = "__closure";
+
+/// recursion primitives of the standard library, e.g. `@list_foldl`, with the number of type
+/// arguments they take
+pub const RECURSION_PRIMITIVES: [(&str, usize); 4] = [
+    ("list_foldl", 2),
+    ("list_foldr", 2),
+    ("list_foldk", 2),
+    ("nat_fold", 1),
+];
+
+/// message entries naming the event, exception or transition, which are hashed together with
+/// the types of the remaining entries into the topic / selector of the message
+pub const MESSAGE_NAME_FIELDS: [&str; 3] = ["_eventname", "_exception", "_tag"];
+
+/// message entry naming the transition a message calls, where an empty tag transfers the amount
+/// without calling a transition
+pub const MESSAGE_TAG_FIELD: &str = "_tag";
+
+/// message entry holding the address a message is sent to
+pub const MESSAGE_RECIPIENT_FIELD: &str = "_recipient";
+
+/// message entry holding the amount sent along with a message
+pub const MESSAGE_AMOUNT_FIELD: &str = "_amount";
+
+/// Returns whether the message entry `field` is passed as an argument, i.e. is neither the name,
+/// the recipient nor the amount of the message.
+pub fn is_message_argument(field: &str) -> bool {
+    !MESSAGE_NAME_FIELDS.contains(&field)
+        && field != MESSAGE_RECIPIENT_FIELD
+        && field != MESSAGE_AMOUNT_FIELD
+}
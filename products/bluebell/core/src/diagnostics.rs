@@ -0,0 +1,278 @@
+use std::fmt;
+
+use lalrpop_util::ParseError;
+use scilla_parser::parser::lexer::SourcePosition;
+
+use crate::errors::{CompilerError, ErrorKind, SourceError};
+
+/// Code of diagnostics reporting syntax errors.
+pub const SYNTAX_ERROR: &str = "E0001";
+/// Code of diagnostics reporting ill typed programs.
+pub const TYPE_ERROR: &str = "E0002";
+/// Code of diagnostics reporting language features the compiler does not support yet.
+pub const UNSUPPORTED: &str = "E0003";
+/// Code of diagnostics reporting failures of the compiler itself.
+pub const INTERNAL_ERROR: &str = "E0004";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// Range of the source code a diagnostic refers to, optionally with a label explaining
+/// what is wrong with it.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub start: SourcePosition,
+    pub end: SourcePosition,
+    pub label: Option<String>,
+}
+
+impl Span {
+    pub fn new(start: SourcePosition, end: SourcePosition) -> Self {
+        Self {
+            start,
+            end,
+            label: None,
+        }
+    }
+
+    /// Span covering a single character.
+    pub fn at(position: SourcePosition) -> Self {
+        Self::new(position.clone(), position)
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+}
+
+/// A message produced while compiling a program. The primary span points at the offending
+/// code while secondary spans point at related code, such as a conflicting declaration.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub message: String,
+    pub primary: Option<Span>,
+    pub secondary: Vec<Span>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: &str) -> Self {
+        Self {
+            severity,
+            code: None,
+            message: message.to_string(),
+            primary: None,
+            secondary: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn error(message: &str) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: &str) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.code = Some(code.to_string());
+        self
+    }
+
+    pub fn with_primary(mut self, span: Span) -> Self {
+        self.primary = Some(span);
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span) -> Self {
+        self.secondary.push(span);
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Creates a diagnostic from an error reported by the parser.
+    pub fn from_parse_error<T: fmt::Debug, E: fmt::Debug>(
+        error: ParseError<SourcePosition, T, E>,
+    ) -> Self {
+        let (message, span) = match error {
+            ParseError::InvalidToken { location } => {
+                ("Invalid token".to_string(), Some(Span::at(location)))
+            }
+            ParseError::UnrecognizedEOF { location, expected } => (
+                format!(
+                    "Unexpected end of file, expected one of {}",
+                    expected.join(", ")
+                ),
+                Some(Span::at(location)),
+            ),
+            ParseError::UnrecognizedToken {
+                token: (start, token, end),
+                expected,
+            } => (
+                format!(
+                    "Unexpected token {:?}, expected one of {}",
+                    token,
+                    expected.join(", ")
+                ),
+                Some(Span::new(start, end)),
+            ),
+            ParseError::ExtraToken {
+                token: (start, token, end),
+            } => (
+                format!("Unexpected token {:?}", token),
+                Some(Span::new(start, end)),
+            ),
+            ParseError::User { error } => (format!("{:?}", error), None),
+        };
+
+        let ret = Diagnostic::error(&format!("Syntax error: {}", message)).with_code(SYNTAX_ERROR);
+        match span {
+            Some(span) => ret.with_primary(span),
+            None => ret,
+        }
+    }
+
+    /// Renders the diagnostic with the offending lines of `source` and the spans underlined.
+    pub fn render(&self, source: &str) -> String {
+        let mut ret = match &self.code {
+            Some(code) => format!("{}[{}]: {}\n", self.severity, code, self.message),
+            None => format!("{}: {}\n", self.severity, self.message),
+        };
+
+        for (span, marker) in self
+            .primary
+            .iter()
+            .map(|span| (span, '^'))
+            .chain(self.secondary.iter().map(|span| (span, '-')))
+        {
+            ret.push_str(&render_span(source, span, marker));
+        }
+
+        for note in self.notes.iter() {
+            ret.push_str(&format!("  = note: {}\n", note));
+        }
+
+        ret
+    }
+}
+
+/// Renders the line containing the start of `span` and underlines the span with `marker`.
+fn render_span(source: &str, span: &Span, marker: char) -> String {
+    let start = span.start.position.min(source.len());
+    let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = source[start..]
+        .find('\n')
+        .map(|i| start + i)
+        .unwrap_or(source.len());
+    let line_number = source[..start].matches('\n').count() + 1;
+    let column = source[line_start..start].chars().count();
+
+    // Spans continuing on the next lines are only underlined up to the end of the first line
+    let end = span.end.position.clamp(start, line_end);
+    let width = source[start..end].chars().count().max(1);
+
+    let gutter = " ".repeat(line_number.to_string().len());
+    let mut ret = format!("{}--> {}:{}\n", gutter, line_number, column + 1);
+    ret.push_str(&format!("{} |\n", gutter));
+    ret.push_str(&format!(
+        "{} | {}\n",
+        line_number,
+        &source[line_start..line_end]
+    ));
+    ret.push_str(&format!(
+        "{} | {}{}",
+        gutter,
+        " ".repeat(column),
+        marker.to_string().repeat(width)
+    ));
+    if let Some(label) = &span.label {
+        ret.push_str(&format!(" {}", label));
+    }
+    ret.push('\n');
+
+    ret
+}
+
+// Without source code diagnostics are displayed as `line:column: message`
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.primary {
+            Some(span) => write!(
+                f,
+                "{}:{}: {}",
+                span.start.line, span.start.column, self.message
+            ),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Errors reported as plain strings carry no source location
+impl From<String> for Diagnostic {
+    fn from(message: String) -> Self {
+        Diagnostic::error(&message)
+    }
+}
+
+impl From<SourceError> for Diagnostic {
+    fn from(error: SourceError) -> Self {
+        Diagnostic::error(&error.message).with_primary(Span::at(error.position))
+    }
+}
+
+impl From<CompilerError> for Diagnostic {
+    fn from(error: CompilerError) -> Self {
+        let code = match error.kind {
+            ErrorKind::Unsupported => UNSUPPORTED,
+            ErrorKind::Internal => INTERNAL_ERROR,
+        };
+        let ret = Diagnostic::error(&error.message).with_code(code);
+        match error.location {
+            Some((start, end)) => ret.with_primary(Span::new(start, end)),
+            None => ret,
+        }
+    }
+}
+
+// Type alias for a list of diagnostics
+pub type DiagnosticList = Vec<Diagnostic>;
+
+/// Returns true if any of the diagnostics is an error.
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|diagnostic| diagnostic.is_error())
+}
+
+/// Renders all diagnostics against `source`, separated by blank lines.
+pub fn render_diagnostics(diagnostics: &[Diagnostic], source: &str) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render(source))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
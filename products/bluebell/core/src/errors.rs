@@ -1,7 +1,10 @@
+use std::fmt;
+
 use scilla_parser::parser::lexer;
 
 // Struct to represent source errors
 // Contains the position of the error in the source code and the error message
+#[derive(Debug, Clone)]
 pub struct SourceError {
     /// Source position of the error
     pub position: lexer::SourcePosition,
@@ -10,5 +13,104 @@ pub struct SourceError {
     pub message: String,
 }
 
+impl SourceError {
+    pub fn new(position: lexer::SourcePosition, message: String) -> Self {
+        SourceError { position, message }
+    }
+}
+
+// Errors are displayed as `line:column: message`
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.position.line, self.position.column, self.message
+        )
+    }
+}
+
 // Type alias for a list of source errors
 pub type ErrorList = Vec<SourceError>;
+
+/// Kind of an error reported by the compiler, which determines the code it is reported with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The program uses a language feature the compiler does not support yet
+    Unsupported,
+    /// The compiler failed on a program it should have been able to handle
+    Internal,
+}
+
+// Struct to represent errors of the emitter, the passes and the code generator
+// Unlike source errors, these do not necessarily refer to a location in the source code
+#[derive(Debug, Clone)]
+pub struct CompilerError {
+    /// What went wrong
+    pub kind: ErrorKind,
+
+    /// Associated error message
+    pub message: String,
+
+    /// Range of the source code causing the error, if known
+    pub location: Option<(lexer::SourcePosition, lexer::SourcePosition)>,
+}
+
+impl CompilerError {
+    pub fn new(kind: ErrorKind, message: String) -> Self {
+        CompilerError {
+            kind,
+            message,
+            location: None,
+        }
+    }
+
+    pub fn unsupported(message: String) -> Self {
+        Self::new(ErrorKind::Unsupported, message)
+    }
+
+    pub fn internal(message: String) -> Self {
+        Self::new(ErrorKind::Internal, message)
+    }
+
+    /// Attaches the location of the offending code, unless it is not a valid location.
+    pub fn at(mut self, location: (lexer::SourcePosition, lexer::SourcePosition)) -> Self {
+        if location.0.is_valid() {
+            self.location = Some(location);
+        }
+        self
+    }
+}
+
+// Errors are displayed as their message, the location is shown when rendered as a diagnostic
+impl fmt::Display for CompilerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// Errors reported as plain strings are failures of the compiler unless stated otherwise
+impl From<String> for CompilerError {
+    fn from(message: String) -> Self {
+        CompilerError::internal(message)
+    }
+}
+
+// Allows compiler errors to be propagated by functions reporting errors as strings
+impl From<CompilerError> for String {
+    fn from(error: CompilerError) -> Self {
+        error.to_string()
+    }
+}
+
+// Type alias for a list of compiler errors
+pub type CompilerErrorList = Vec<CompilerError>;
+
+/// Joins a list of compiler errors into a single message, one error per line.
+pub fn join_errors(errors: &[CompilerError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -1,11 +1,13 @@
-use std::{collections::BTreeSet, mem, str::FromStr};
+use std::{cell::RefCell, collections::{BTreeMap, BTreeSet}, mem, str::FromStr};
 
 use evm_assembly::{
-    block::EvmBlock,
+    abi::{Abi, AbiEntry, AbiParameter, StateMutability},
+    block::{EvmBlock, ALLOCATION_POINTER},
     compiler_context::EvmCompilerContext,
     executable::EvmExecutable,
     instruction::EvmSourcePosition,
-    types::{EvmType, EvmTypeValue},
+    storage_layout::{derived_slot, ImmutableEntry, StorageEncoding, StorageEntry},
+    types::EvmType,
     EvmByteCodeBuilder,
 };
 use primitive_types::U256;
@@ -13,14 +15,1039 @@ use scilla_parser::ast::TreeTraversalMode;
 use sha3::{Digest, Keccak256};
 
 use crate::{
+    constants::{
+        is_message_argument, MESSAGE_AMOUNT_FIELD, MESSAGE_NAME_FIELDS, MESSAGE_RECIPIENT_FIELD,
+        MESSAGE_TAG_FIELD, NAMESPACE_SEPARATOR,
+    },
+    errors::CompilerError,
     intermediate_representation::{
         pass::IrPass,
-        primitives::{IntermediateRepresentation, Operation},
-        symbol_table::StateLayoutEntry,
+        primitives::{
+            FunctionBlock, FunctionKind, IntermediateRepresentation, IrIdentifier, Operation,
+        },
+        symbol_table::{StateLayoutEntry, SymbolTable},
     },
     passes::debug_printer::DebugPrinter,
+    support::{
+        integers::{integer_literal, push_some},
+        recursion::{define_recursion_primitive, recursion_primitive_label},
+        strings::{alloca_object, copy_bytes},
+    },
 };
 
+/// Pushes the storage slot of `base[keys[0]]...[keys[n]]` onto the stack. Slots are derived
+/// Solidity style, `slot(m[k]) = keccak256(k . seed(m))`, using the scratch space at 0x00-0x3f
+/// for hashing. The slot following an entry holds its flags word, whose lowest bit tells
+/// whether the entry exists and whose remaining bits count how often it was deleted. The seed
+/// of a map is its slot for fields and `keccak256(slot . generation)` for nested maps, such
+/// that deleting a nested map also removes its entries. If `mark_existence` is set, every
+/// entry along the path is marked as existing.
+fn push_map_slot(
+    evm_block: &mut EvmBlock,
+    base: U256,
+    keys: &[IrIdentifier],
+    mark_existence: bool,
+) -> Result<(), String> {
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.push_u256(base);
+
+    for (i, key) in keys.iter().enumerate() {
+        let key_name = match &key.resolved {
+            Some(k) => k,
+            None => return Err(format!("Unresolved map key {}", key.unresolved)),
+        };
+
+        if i > 0 {
+            // Stack:
+            // s => keccak256(s . (flags(s) >> 1))
+            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+            evm_block.dup1();
+            evm_block.push1([0x01].to_vec());
+            evm_block.add();
+            evm_block.external_sload();
+            evm_block.push1([0x01].to_vec());
+            evm_block.shr();
+            evm_block.push1([0x20].to_vec());
+            evm_block.mstore();
+            evm_block.push1([0x00].to_vec());
+            evm_block.mstore();
+            evm_block.push1([0x40].to_vec());
+            evm_block.push1([0x00].to_vec());
+            evm_block.external_sha3();
+        }
+
+        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+        evm_block.duplicate_stack_name(key_name)?;
+        evm_block.push1([0x00].to_vec());
+        evm_block.mstore();
+        evm_block.push1([0x20].to_vec());
+        evm_block.mstore();
+        evm_block.push1([0x40].to_vec());
+        evm_block.push1([0x00].to_vec());
+        evm_block.external_sha3();
+
+        if mark_existence {
+            // flags(s) = flags(s) | 1
+            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+            evm_block.dup1();
+            evm_block.push1([0x01].to_vec());
+            evm_block.add();
+            evm_block.dup1();
+            evm_block.external_sload();
+            evm_block.push1([0x01].to_vec());
+            evm_block.or();
+            evm_block.swap1();
+            evm_block.external_sstore();
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the mask selecting the bytes of a field of `size` bytes.
+fn field_mask(size: u64) -> U256 {
+    if size >= 32 {
+        U256::MAX
+    } else {
+        (U256::one() << (8 * size)) - 1
+    }
+}
+
+/// Pushes the value of the field `state` onto the stack, see `StateCollector` for the layout.
+fn push_state_load(evm_block: &mut EvmBlock, state: &StateLayoutEntry) -> Result<(), String> {
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    match state.encoding {
+        StorageEncoding::Inplace => {
+            evm_block.push_u256(state.address_offset);
+            evm_block.external_sload();
+            if state.byte_offset > 0 {
+                evm_block.push_u64(8 * state.byte_offset);
+                evm_block.shr();
+            }
+            if state.size < 32 {
+                if state.signed {
+                    evm_block.push_u64(state.size - 1);
+                    evm_block.signextend();
+                } else {
+                    evm_block.push_u256(field_mask(state.size));
+                    evm_block.and();
+                }
+            }
+        }
+        StorageEncoding::Adt => {
+            evm_block.push_u256(derived_slot(state.address_offset));
+            evm_block.external_sload();
+        }
+        StorageEncoding::Bytes => {
+            let slot = derived_slot(state.address_offset);
+            let lbl_loop = evm_block.generate_label("load_object_loop".to_string());
+            let lbl_done = evm_block.generate_label("load_object_done".to_string());
+
+            // Allocating the object from the length stored in the first word
+            evm_block.push_u256(slot);
+            evm_block.external_sload();
+            evm_block.push1([224].to_vec());
+            evm_block.shr();
+            alloca_object(evm_block);
+
+            // Stack:
+            // p => p
+            //   => i
+            evm_block.push1([0x00].to_vec());
+
+            evm_block.create_label(lbl_loop.clone());
+            evm_block.dup2();
+            evm_block.mload();
+            evm_block.push1([224].to_vec());
+            evm_block.shr();
+            evm_block.push1([0x04].to_vec());
+            evm_block.add();
+            evm_block.dup2();
+            evm_block.lt();
+            evm_block.iszero();
+            evm_block.jump_if_to(&lbl_done);
+
+            // p[i] = sload(slot + i / 32)
+            evm_block.dup1();
+            evm_block.push1([0x05].to_vec());
+            evm_block.shr();
+            evm_block.push_u256(slot);
+            evm_block.add();
+            evm_block.external_sload();
+            evm_block.dup3();
+            evm_block.dup3();
+            evm_block.add();
+            evm_block.mstore();
+
+            evm_block.push1([0x20].to_vec());
+            evm_block.add();
+            evm_block.jump_to(&lbl_loop);
+
+            evm_block.create_label(lbl_done);
+            evm_block.pop();
+        }
+        StorageEncoding::Immutable => {
+            // Copying the word at `address_offset` bytes from the end of the code
+            evm_block.push1([0x20].to_vec());
+            evm_block.push_u256(state.address_offset);
+            evm_block.codesize();
+            evm_block.sub();
+            evm_block.push1([0x00].to_vec());
+            evm_block.codecopy();
+            evm_block.push1([0x00].to_vec());
+            evm_block.mload();
+        }
+        StorageEncoding::Mapping => {
+            return Err("Maps can only be loaded an entry at a time".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores the value on top of the stack in the field `state`, consuming the value.
+fn push_state_store(evm_block: &mut EvmBlock, state: &StateLayoutEntry) -> Result<(), String> {
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    match state.encoding {
+        StorageEncoding::Inplace if state.size >= 32 => {
+            evm_block.push_u256(state.address_offset);
+            evm_block.external_sstore();
+        }
+        StorageEncoding::Inplace => {
+            let mask = field_mask(state.size);
+            let shift = 8 * state.byte_offset;
+
+            evm_block.push_u256(mask);
+            evm_block.and();
+            if shift > 0 {
+                evm_block.push_u64(shift);
+                evm_block.shl();
+            }
+
+            // Keeping the other fields of the slot
+            evm_block.push_u256(state.address_offset);
+            evm_block.external_sload();
+            evm_block.push_u256(!(mask << shift));
+            evm_block.and();
+            evm_block.or();
+            evm_block.push_u256(state.address_offset);
+            evm_block.external_sstore();
+        }
+        StorageEncoding::Adt => {
+            evm_block.push_u256(derived_slot(state.address_offset));
+            evm_block.external_sstore();
+        }
+        StorageEncoding::Bytes => {
+            let slot = derived_slot(state.address_offset);
+            let lbl_loop = evm_block.generate_label("store_object_loop".to_string());
+            let lbl_done = evm_block.generate_label("store_object_done".to_string());
+
+            // Stack:
+            // p => p
+            //   => i
+            evm_block.push1([0x00].to_vec());
+
+            evm_block.create_label(lbl_loop.clone());
+            evm_block.dup2();
+            evm_block.mload();
+            evm_block.push1([224].to_vec());
+            evm_block.shr();
+            evm_block.push1([0x04].to_vec());
+            evm_block.add();
+            evm_block.dup2();
+            evm_block.lt();
+            evm_block.iszero();
+            evm_block.jump_if_to(&lbl_done);
+
+            // sstore(slot + i / 32, p[i])
+            evm_block.dup2();
+            evm_block.dup2();
+            evm_block.add();
+            evm_block.mload();
+            evm_block.dup2();
+            evm_block.push1([0x05].to_vec());
+            evm_block.shr();
+            evm_block.push_u256(slot);
+            evm_block.add();
+            evm_block.external_sstore();
+
+            evm_block.push1([0x20].to_vec());
+            evm_block.add();
+            evm_block.jump_to(&lbl_loop);
+
+            evm_block.create_label(lbl_done);
+            evm_block.pop();
+            evm_block.pop();
+        }
+        StorageEncoding::Immutable => {
+            return Err("Contract parameters cannot be assigned to".to_string());
+        }
+        StorageEncoding::Mapping => {
+            return Err("Maps can only be stored an entry at a time".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the type `typename` is passed as through the ABI. Addresses and byte strings
+/// are passed as `address` and `bytesN` although they are held as unsigned integers, and
+/// byte strings held in memory, which share the layout of strings, as `bytes`.
+fn abi_type(ctx: &EvmCompilerContext, typename: &str) -> Result<EvmType, CompilerError> {
+    let evm_type = match ctx.type_declarations.get(typename) {
+        Some(evm_type) => evm_type.clone(),
+        None => {
+            return Err(CompilerError::unsupported(format!(
+                "Type {} has no ABI representation",
+                typename
+            )))
+        }
+    };
+
+    match typename {
+        "ByStr20" => return Ok(EvmType::Address),
+        "ByStr" => return Ok(EvmType::DynamicBytes),
+        _ => (),
+    }
+    match typename
+        .strip_prefix("ByStr")
+        .and_then(|size| size.parse::<usize>().ok())
+    {
+        Some(size) if size > 0 => Ok(EvmType::Bytes(size)),
+        _ => Ok(evm_type),
+    }
+}
+
+/// Offsets of the packed message, see `push_message`.
+const MESSAGE_RECIPIENT_OFFSET: u8 = 0x20;
+const MESSAGE_AMOUNT_OFFSET: u8 = 0x40;
+const MESSAGE_DATA_SIZE_OFFSET: u8 = 0x60;
+const MESSAGE_SELECTOR_OFFSET: u8 = 0x9c;
+const MESSAGE_DATA_OFFSET: u8 = 0xa0;
+
+fn resolved_message_entry(value: &IrIdentifier) -> Result<&String, String> {
+    match &value.resolved {
+        Some(v) => Ok(v),
+        None => Err(format!("Unresolved message entry {}", value.unresolved)),
+    }
+}
+
+/// Stores the value `value` shifted left by `shift` bits at `offset` relative to the pointer on
+/// the top of the stack.
+fn store_message_word(
+    evm_block: &mut EvmBlock,
+    value: &IrIdentifier,
+    offset: u64,
+    shift: usize,
+) -> Result<(), String> {
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.duplicate_stack_name(resolved_message_entry(value)?)?;
+    if shift > 0 {
+        evm_block.push_u32(shift as u32);
+        evm_block.shl();
+    }
+    evm_block.dup2();
+    evm_block.push_u64(offset);
+    evm_block.add();
+    evm_block.mstore();
+    Ok(())
+}
+
+/// Returns the ABI types of the arguments of a message, i.e. the entries other than the name,
+/// recipient and amount, in order. The type checker ensures that arguments are either held in
+/// a word or are strings and byte strings held in memory.
+fn message_argument_types(
+    ctx: &EvmCompilerContext,
+    entries: &[(String, IrIdentifier)],
+) -> Result<Vec<EvmType>, CompilerError> {
+    entries
+        .iter()
+        .filter(|(field, _)| is_message_argument(field))
+        .map(|(field, value)| match &value.type_reference {
+            Some(typename) => abi_type(ctx, typename),
+            None => Err(CompilerError::internal(format!(
+                "Unable to determine the type of message entry {}",
+                field
+            ))),
+        })
+        .collect()
+}
+
+/// Replaces the pointer to the string `name` on top of the stack with keccak256 of the
+/// signature `name(types)` formed by appending `types` to the string.
+fn hash_message_signature(evm_block: &mut EvmBlock, types: &str) {
+    let types = types.as_bytes();
+
+    // Stack:
+    // p => q
+    //   => len
+    //   => p
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.dup1();
+    evm_block.mload();
+    evm_block.push1([224].to_vec());
+    evm_block.shr();
+    evm_block.dup1();
+    evm_block.push_u64(types.len() as u64);
+    evm_block.add();
+    alloca_object(evm_block);
+
+    // Copying the name to the start of q
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.dup2();
+    evm_block.dup4();
+    evm_block.push1([4].to_vec());
+    evm_block.add();
+    evm_block.dup3();
+    evm_block.push1([4].to_vec());
+    evm_block.add();
+    copy_bytes(evm_block);
+
+    // Appending the types a word at a time, the allocation is padded by a word
+    for (i, chunk) in types.chunks(32).enumerate() {
+        let mut word = chunk.to_vec();
+        word.resize(32, 0);
+
+        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+        evm_block.push(word);
+        evm_block.dup3();
+        evm_block.dup3();
+        evm_block.add();
+        evm_block.push_u64(4 + 32 * i as u64);
+        evm_block.add();
+        evm_block.mstore();
+    }
+
+    // Stack:
+    // q   => hash
+    // len
+    // p
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.swap1();
+    evm_block.push_u64(types.len() as u64);
+    evm_block.add();
+    evm_block.swap1();
+    evm_block.push1([4].to_vec());
+    evm_block.add();
+    evm_block.external_sha3();
+    evm_block.swap1();
+    evm_block.pop();
+}
+
+/// Replaces the pointer to the string or byte string on top of the stack with the size of its
+/// ABI encoding, i.e. the length word followed by the data padded to a multiple of 32 bytes.
+fn push_abi_tail_size(evm_block: &mut EvmBlock) {
+    evm_block.mload();
+    evm_block.push1([224].to_vec());
+    evm_block.shr();
+    evm_block.push1([63].to_vec());
+    evm_block.add();
+    evm_block.push1([5].to_vec());
+    evm_block.shr();
+    evm_block.push1([5].to_vec());
+    evm_block.shl();
+}
+
+/// Writes the ABI encoding of the argument `index` of the message on the stack, held in memory
+/// as an object, at `offset` relative to the start of the data. The head of the argument is set
+/// to `offset`, which is then advanced past the encoding.
+fn store_message_tail(
+    evm_block: &mut EvmBlock,
+    value: &IrIdentifier,
+    index: usize,
+) -> Result<(), String> {
+    let head = MESSAGE_DATA_OFFSET as u64 + 0x20 * index as u64;
+
+    // Stack:
+    // offset => offset
+    // p      => p
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.dup1();
+    evm_block.dup3();
+    evm_block.push_u64(head);
+    evm_block.add();
+    evm_block.mstore();
+
+    // Storing the length at the offset
+    // Stack:
+    // offset => len
+    // p      => s
+    //        => offset
+    //        => p
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.duplicate_stack_name(resolved_message_entry(value)?)?;
+    evm_block.dup1();
+    evm_block.mload();
+    evm_block.push1([224].to_vec());
+    evm_block.shr();
+    evm_block.dup1();
+    evm_block.dup5();
+    evm_block.push1([MESSAGE_DATA_OFFSET].to_vec());
+    evm_block.add();
+    evm_block.dup5();
+    evm_block.add();
+    evm_block.mstore();
+
+    // Copying the data after the length
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.dup1();
+    evm_block.dup3();
+    evm_block.push1([4].to_vec());
+    evm_block.add();
+    evm_block.dup6();
+    evm_block.push1([MESSAGE_DATA_OFFSET + 0x20].to_vec());
+    evm_block.add();
+    evm_block.dup6();
+    evm_block.add();
+    copy_bytes(evm_block);
+
+    // Clearing the padding, which the copy may have filled with the bytes following the data
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.push1([0x00].to_vec());
+    evm_block.dup2();
+    evm_block.dup6();
+    evm_block.add();
+    evm_block.dup5();
+    evm_block.add();
+    evm_block.push1([MESSAGE_DATA_OFFSET + 0x20].to_vec());
+    evm_block.add();
+    evm_block.mstore();
+
+    // Stack:
+    // len    => offset + size
+    // s      => p
+    // offset
+    // p
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.pop();
+    push_abi_tail_size(evm_block);
+    evm_block.add();
+
+    Ok(())
+}
+
+/// Packs a message into newly allocated memory and leaves the pointer on the stack. The layout is
+///
+/// ```plaintext
+/// 0x00 keccak256 of the signature `name(types)` (zero if absent)
+/// 0x20 `_recipient`
+/// 0x40 `_amount`
+/// 0x60 size of the data in bytes
+/// 0x80 selector, i.e. the first four bytes of the hash, right aligned
+/// 0xa0 data, i.e. the ABI encoding of the remaining entries in order
+/// ```
+///
+/// where the name is the `_eventname`, `_exception` or `_tag` string and the types are the ABI
+/// types `argument_types` of the remaining entries, e.g. `Minted(uint64)`. The hash is the
+/// topic of an event as in Solidity, and the selector followed by the data forms the calldata
+/// (or revert payload) of the message. The hash is left zero for an empty `_tag`, marking a
+/// plain transfer.
+fn push_message(
+    evm_block: &mut EvmBlock,
+    entries: &[(String, IrIdentifier)],
+    argument_types: &[EvmType],
+) -> Result<(), String> {
+    let arguments: Vec<&IrIdentifier> = entries
+        .iter()
+        .filter(|(field, _)| is_message_argument(field))
+        .map(|(_, value)| value)
+        .collect();
+    if arguments.len() != argument_types.len() {
+        return Err(format!(
+            "Expected {} argument types, found {}",
+            arguments.len(),
+            argument_types.len()
+        ));
+    }
+    let types = format!(
+        "({})",
+        argument_types
+            .iter()
+            .map(|t| t.signature())
+            .collect::<Vec<String>>()
+            .join(",")
+    );
+    let head_size = 0x20 * arguments.len() as u64;
+
+    // Stack:
+    // => size of the tails
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.push1([0x00].to_vec());
+    for (value, argument_type) in arguments.iter().zip(argument_types) {
+        if argument_type.is_dynamic() {
+            evm_block.duplicate_stack_name(resolved_message_entry(value)?)?;
+            push_abi_tail_size(evm_block);
+            evm_block.add();
+        }
+    }
+
+    // Allocating the message with a word to spare, as copying the data of the last tail may
+    // write past its end
+    // Stack:
+    // tails => p
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.dup1();
+    evm_block.push_u64(MESSAGE_DATA_OFFSET as u64 + head_size + 0x20);
+    evm_block.add();
+    evm_block.push1([ALLOCATION_POINTER].to_vec());
+    evm_block.mload();
+    evm_block.swap1();
+    evm_block.dup2();
+    evm_block.add();
+    evm_block.push1([ALLOCATION_POINTER].to_vec());
+    evm_block.mstore();
+    evm_block.swap1();
+    evm_block.push_u64(head_size);
+    evm_block.add();
+    evm_block.dup2();
+    evm_block.push1([MESSAGE_DATA_SIZE_OFFSET].to_vec());
+    evm_block.add();
+    evm_block.mstore();
+
+    // Freshly allocated memory is zero, so absent entries are not written
+    for (field, value) in entries {
+        if MESSAGE_NAME_FIELDS.contains(&field.as_str()) {
+            let name = resolved_message_entry(value)?;
+            if field == MESSAGE_TAG_FIELD {
+                // Stack:
+                // => len != 0
+                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                evm_block.duplicate_stack_name(name)?;
+                evm_block.mload();
+                evm_block.push1([224].to_vec());
+                evm_block.shr();
+                evm_block.iszero();
+                evm_block.iszero();
+            }
+
+            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+            evm_block.duplicate_stack_name(name)?;
+            hash_message_signature(evm_block, &types);
+            if field == MESSAGE_TAG_FIELD {
+                evm_block.mul();
+            }
+
+            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+            evm_block.dup1();
+            evm_block.dup3();
+            evm_block.mstore();
+            evm_block.push1([224].to_vec());
+            evm_block.shr();
+            evm_block.dup2();
+            evm_block.push1([MESSAGE_SELECTOR_OFFSET - 0x1c].to_vec());
+            evm_block.add();
+            evm_block.mstore();
+        } else if field == MESSAGE_RECIPIENT_FIELD {
+            store_message_word(evm_block, value, MESSAGE_RECIPIENT_OFFSET as u64, 0)?;
+        } else if field == MESSAGE_AMOUNT_FIELD {
+            store_message_word(evm_block, value, MESSAGE_AMOUNT_OFFSET as u64, 0)?;
+        }
+    }
+
+    // Words are encoded as themselves with `bytesN` moved to the left
+    for (i, (value, argument_type)) in arguments.iter().zip(argument_types).enumerate() {
+        if !argument_type.is_dynamic() {
+            store_message_word(
+                evm_block,
+                value,
+                MESSAGE_DATA_OFFSET as u64 + 0x20 * i as u64,
+                argument_type.abi_shift(),
+            )?;
+        }
+    }
+
+    // Strings and byte strings are encoded in the tail, following the heads
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.push_u64(head_size);
+    for (i, (value, argument_type)) in arguments.iter().zip(argument_types).enumerate() {
+        if argument_type.is_dynamic() {
+            store_message_tail(evm_block, value, i)?;
+        }
+    }
+    evm_block.pop();
+
+    Ok(())
+}
+
+/// Pushes the pointer to the message `message`, packed by `push_message`.
+fn duplicate_message(evm_block: &mut EvmBlock, message: &IrIdentifier) -> Result<(), String> {
+    let message_name = match &message.resolved {
+        Some(m) => m,
+        None => return Err(format!("Unresolved message {}", message.unresolved)),
+    };
+
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.duplicate_stack_name(message_name)
+}
+
+/// Emits the message `message` using `LOG1` with the hash of the event signature as the only
+/// topic.
+fn emit_event(evm_block: &mut EvmBlock, message: &IrIdentifier) -> Result<(), String> {
+    duplicate_message(evm_block, message)?;
+    evm_block.dup1();
+    evm_block.mload();
+    evm_block.swap1();
+    evm_block.dup1();
+    evm_block.push1([MESSAGE_DATA_SIZE_OFFSET].to_vec());
+    evm_block.add();
+    evm_block.mload();
+    evm_block.swap1();
+    evm_block.push1([MESSAGE_DATA_OFFSET].to_vec());
+    evm_block.add();
+    evm_block.external_log1();
+
+    Ok(())
+}
+
+/// Reverts with the selector of the exception `exception` followed by its data as payload, or
+/// with an empty payload if no exception is given.
+fn revert_with_exception(
+    evm_block: &mut EvmBlock,
+    exception: &Option<IrIdentifier>,
+) -> Result<(), String> {
+    match exception {
+        Some(exception) => {
+            duplicate_message(evm_block, exception)?;
+            evm_block.dup1();
+            evm_block.push1([MESSAGE_DATA_SIZE_OFFSET].to_vec());
+            evm_block.add();
+            evm_block.mload();
+            evm_block.push1([4].to_vec());
+            evm_block.add();
+            evm_block.swap1();
+            evm_block.push1([MESSAGE_SELECTOR_OFFSET].to_vec());
+            evm_block.add();
+        }
+        None => {
+            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+            evm_block.push1([0x00].to_vec());
+            evm_block.push1([0x00].to_vec());
+        }
+    }
+    evm_block.revert();
+
+    Ok(())
+}
+
+/// Calls `_recipient` of the message on top of the stack with `_amount` attached and the
+/// selector of `_tag` followed by the data of the message as calldata, consuming the pointer.
+/// Messages with an empty `_tag` are sent without calldata, such that the amount is received
+/// as a plain transfer. The transaction is reverted if the call fails.
+fn call_message(evm_block: &mut EvmBlock) {
+    // Return data is ignored
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.push1([0x00].to_vec());
+    evm_block.push1([0x00].to_vec());
+
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.dup3();
+    evm_block.push1([MESSAGE_DATA_SIZE_OFFSET].to_vec());
+    evm_block.add();
+    evm_block.mload();
+    evm_block.push1([4].to_vec());
+    evm_block.add();
+    evm_block.dup4();
+    evm_block.mload();
+    evm_block.iszero();
+    evm_block.iszero();
+    evm_block.mul();
+    evm_block.dup4();
+    evm_block.push1([MESSAGE_SELECTOR_OFFSET].to_vec());
+    evm_block.add();
+
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.dup5();
+    evm_block.push1([MESSAGE_AMOUNT_OFFSET].to_vec());
+    evm_block.add();
+    evm_block.mload();
+    evm_block.dup6();
+    evm_block.push1([MESSAGE_RECIPIENT_OFFSET].to_vec());
+    evm_block.add();
+    evm_block.mload();
+    evm_block.external_gas();
+    evm_block.external_call();
+    evm_block.swap1();
+    evm_block.pop();
+
+    let lbl_sent = evm_block.generate_label("message_sent".to_string());
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.jump_if_to(&lbl_sent);
+    evm_block.push1([0x00].to_vec());
+    evm_block.push1([0x00].to_vec());
+    evm_block.revert();
+    evm_block.create_label(lbl_sent);
+}
+
+/// Sends every message of the list `messages` in order, see `call_message`. Lists are linked
+/// cells `[head, tail]` where the empty list is the null pointer.
+fn send_message(evm_block: &mut EvmBlock, messages: &IrIdentifier) -> Result<(), String> {
+    duplicate_message(evm_block, messages)?;
+
+    let lbl_loop = evm_block.generate_label("send_loop".to_string());
+    let lbl_done = evm_block.generate_label("send_done".to_string());
+
+    // Stack:
+    // cur => cur
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.create_label(lbl_loop.clone());
+    evm_block.dup1();
+    evm_block.iszero();
+    evm_block.jump_if_to(&lbl_done);
+
+    evm_block.dup1();
+    evm_block.mload();
+    call_message(evm_block);
+
+    // Stack:
+    // cur => tail
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.push1([0x20].to_vec());
+    evm_block.add();
+    evm_block.mload();
+    evm_block.jump_to(&lbl_loop);
+
+    evm_block.create_label(lbl_done);
+    evm_block.pop();
+
+    Ok(())
+}
+
+/// Returns the selector of the getter generated for the field `field`, i.e. the selector of
+/// `field()` as it would be for a public Solidity field.
+fn field_getter_selector(field: &str) -> Vec<u8> {
+    let hash = Keccak256::digest(format!("{}()", field).as_bytes());
+    hash[..4].to_vec()
+}
+
+/// Pushes the field `field` of the contract at `contract` onto the stack. Fields are read by
+/// calling the getter generated for every contract field, with the exception of `_balance` which
+/// is read directly. The type checker rejects reads of fields which are not held in a single
+/// word, as these have no getter.
+fn push_remote_field(
+    evm_block: &mut EvmBlock,
+    contract: &IrIdentifier,
+    field: &str,
+) -> Result<(), String> {
+    let contract_name = match &contract.resolved {
+        Some(c) => c,
+        None => {
+            return Err(format!(
+                "Unresolved contract address {}",
+                contract.unresolved
+            ))
+        }
+    };
+
+    if field == "_balance" {
+        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+        evm_block.duplicate_stack_name(contract_name)?;
+        evm_block.external_balance();
+        return Ok(());
+    }
+
+    // Writing the selector to the scratch space and clearing the return value
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.push(field_getter_selector(field));
+    evm_block.push1([224].to_vec());
+    evm_block.shl();
+    evm_block.push1([0x00].to_vec());
+    evm_block.mstore();
+    evm_block.push1([0x00].to_vec());
+    evm_block.push1([0x20].to_vec());
+    evm_block.mstore();
+
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.push1([0x20].to_vec());
+    evm_block.push1([0x20].to_vec());
+    evm_block.push1([0x04].to_vec());
+    evm_block.push1([0x00].to_vec());
+    evm_block.duplicate_stack_name(contract_name)?;
+    evm_block.external_gas();
+    evm_block.external_staticcall();
+
+    let lbl_fetched = evm_block.generate_label("remote_fetched".to_string());
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.jump_if_to(&lbl_fetched);
+    evm_block.push1([0x00].to_vec());
+    evm_block.push1([0x00].to_vec());
+    evm_block.revert();
+    evm_block.create_label(lbl_fetched);
+    evm_block.push1([0x20].to_vec());
+    evm_block.mload();
+
+    Ok(())
+}
+
+/// Returns the number of words of values built by the constructor `name` and the number of
+/// arguments of the constructor. The arguments are the last words of the layout of the type,
+/// which for `Option` starts with the flag telling whether the value is defined.
+fn constructor_layout(
+    ctx: &EvmCompilerContext,
+    symbol_table: &SymbolTable,
+    name: &str,
+) -> Result<(usize, usize), CompilerError> {
+    // Constructors sharing their name with their type, such as `Pair`, are not aliased
+    let typeinfo = [
+        symbol_table.aliases.get(name).cloned(),
+        Some(format!("{}{}{}", name, NAMESPACE_SEPARATOR, name)),
+        Some(name.to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|candidate| {
+        symbol_table
+            .type_of_table
+            .get(&candidate)
+            .filter(|typeinfo| typeinfo.is_constructor())
+    })
+    .ok_or(format!("Unknown constructor {}", name))?;
+
+    let adt = match typeinfo.symbol_name.split(NAMESPACE_SEPARATOR).next() {
+        Some(adt) => adt,
+        None => return Err(CompilerError::internal(format!("Unknown constructor {}", name))),
+    };
+    let words = match ctx.generic_types.get(adt) {
+        Some(declaration) => declaration.layout.len(),
+        None => {
+            return Err(CompilerError::unsupported(format!(
+                "Constructor {} with arguments is not supported",
+                typeinfo.symbol_name
+            )))
+        }
+    };
+    let arity = typeinfo.arguments.len();
+    if arity > words {
+        return Err(CompilerError::internal(format!(
+            "Constructor {} takes more arguments than its type holds",
+            typeinfo.symbol_name
+        )));
+    }
+
+    Ok((words, arity))
+}
+
+/// Allocates the value built by the constructor `name` from `arguments` and leaves a pointer to
+/// it on the stack.
+fn construct_value(
+    ctx: &EvmCompilerContext,
+    symbol_table: &SymbolTable,
+    evm_block: &mut EvmBlock,
+    name: &str,
+    arguments: &[IrIdentifier],
+) -> Result<(), CompilerError> {
+    let (words, arity) = constructor_layout(ctx, symbol_table, name)?;
+    if arity != arguments.len() {
+        return Err(CompilerError::internal(format!(
+            "Constructor {} takes {} argument(s), but {} were given",
+            name,
+            arity,
+            arguments.len()
+        )));
+    }
+
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.alloca_static(32 * words as u64);
+    for word in 0..(words - arity) {
+        evm_block.push1([0x01].to_vec());
+        evm_block.dup2();
+        evm_block.push_u64(32 * word as u64);
+        evm_block.add();
+        evm_block.mstore();
+    }
+
+    for (i, argument) in arguments.iter().enumerate() {
+        let argument = match &argument.resolved {
+            Some(a) => a,
+            None => {
+                return Err(CompilerError::internal(format!(
+                    "Unresolved argument {}",
+                    argument.unresolved
+                )))
+            }
+        };
+        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+        evm_block.duplicate_stack_name(argument)?;
+        evm_block.dup2();
+        evm_block.push_u64(32 * (words - arity + i) as u64);
+        evm_block.add();
+        evm_block.mstore();
+    }
+
+    Ok(())
+}
+
+/// Pushes the argument `index` of `value`, which was built by the constructor `constructor`.
+fn load_constructor_argument(
+    ctx: &EvmCompilerContext,
+    symbol_table: &SymbolTable,
+    evm_block: &mut EvmBlock,
+    value: &IrIdentifier,
+    constructor: &IrIdentifier,
+    index: usize,
+) -> Result<(), CompilerError> {
+    let name = constructor
+        .resolved
+        .as_ref()
+        .unwrap_or(&constructor.unresolved);
+    let (words, arity) = constructor_layout(ctx, symbol_table, name)?;
+    if index >= arity {
+        return Err(CompilerError::internal(format!(
+            "Constructor {} has no argument {}",
+            constructor.unresolved, index
+        )));
+    }
+    let value = match &value.resolved {
+        Some(v) => v,
+        None => {
+            return Err(CompilerError::internal(format!(
+                "Unresolved value {}",
+                value.unresolved
+            )))
+        }
+    };
+
+    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+    evm_block.duplicate_stack_name(value)?;
+    evm_block.push_u64(32 * (words - arity + index) as u64);
+    evm_block.add();
+    evm_block.mload();
+
+    Ok(())
+}
+
+/// Pushes the value selected by the phi node heading `target`, if any, when entering from
+/// `block` and names it after the phi node. The value is the one defined by `block`.
+fn push_phi_value(
+    evm_block: &mut EvmBlock,
+    block: &FunctionBlock,
+    target: Option<&Box<FunctionBlock>>,
+) -> Result<(), String> {
+    let instr = match target.and_then(|target| target.instructions.front()) {
+        Some(instr) => instr,
+        None => return Ok(()),
+    };
+    let (result, values) = match (&instr.ssa_name, &instr.operation) {
+        (Some(result), Operation::PhiNode(values)) => (result, values),
+        _ => return Ok(()),
+    };
+    let result = match &result.resolved {
+        Some(r) => r,
+        None => return Err(format!("Unresolved phi node {}", result.unresolved)),
+    };
+
+    let value = values
+        .iter()
+        .filter_map(|value| value.resolved.as_ref())
+        .find(|value| block.defined_ssas.contains(*value));
+    if let Some(value) = value {
+        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+        evm_block.duplicate_stack_name(value)?;
+        evm_block.register_stack_name(result)?;
+    }
+
+    Ok(())
+}
+
 /// `EvmBytecodeGenerator` is a structure responsible for generating Ethereum Virtual Machine (EVM) bytecode.
 /// It stores an EVM bytecode builder and an intermediate representation (IR) of the program to be compiled.
 ///
@@ -52,27 +1079,50 @@ impl<'ctx> EvmBytecodeGenerator<'ctx> {
         Self { builder, ir }
     }
 
-    /// TODO:
+    /// Enables the peephole optimiser on the generated bytecode.
+    pub fn enable_peephole_optimisation(&mut self) -> &mut Self {
+        self.builder.enable_peephole_optimisation();
+        self
+    }
+
+    /// Makes the storage allocated for the fields available under the names used by the code.
+    /// Storage is allocated by `StateCollector`, which registers the fields under their resolved
+    /// names, whereas loads and stores refer to the fields by their unresolved names. The
+    /// contract parameters are appended to the code in the order they are declared, so their
+    /// offsets are turned into distances from the end of the code.
     pub fn build_state_layout(&mut self) -> Result<(), String> {
-        // TODO: Add support for immutables
-        let mut address_offset: u64 = 4919;
+        let immutables = self
+            .ir
+            .fields_definitions
+            .iter()
+            .filter(|field| field.immutable)
+            .count();
 
         for field in &self.ir.fields_definitions {
             let name = &field.variable.name.unresolved;
-            let address = U256::from(address_offset);
-            let initializer = U256::from(0);
-
-            let state = StateLayoutEntry {
-                address_offset: address,
-                size: 1, // TODO:
-                initializer,
+            let mut state = match field
+                .variable
+                .name
+                .resolved
+                .as_ref()
+                .and_then(|resolved| self.ir.symbol_table.state_layout.get(resolved))
+            {
+                Some(state) => state.clone(),
+                None => {
+                    return Err(format!(
+                        "No storage allocated for field `{}`, was the state allocation pass run?",
+                        name
+                    ))
+                }
             };
+            if state.encoding == StorageEncoding::Immutable {
+                state.address_offset = U256::from(32 * immutables) - state.address_offset;
+            }
 
             self.ir
                 .symbol_table
                 .state_layout
                 .insert(name.to_string(), state);
-            address_offset += 1;
         }
 
         Ok(())
@@ -81,30 +1131,63 @@ impl<'ctx> EvmBytecodeGenerator<'ctx> {
     /// This function writes function definitions from the IR to the EVM module.
     /// It loops over all function definitions in the IR and creates corresponding function definitions
     /// in the EVM module using the byte code builder.
-    pub fn write_function_definitions_to_module(&mut self) -> Result<u32, String> {
-        for func in &mut self.ir.function_definitions {
+    pub fn write_function_definitions_to_module(&mut self) -> Result<u32, CompilerError> {
+        self.write_functions_to_module(false)
+    }
+
+    /// Writes either the functions of the runtime code or those of the creation code, which
+    /// consists of the constructor and the functions it may call.
+    fn write_functions_to_module(&mut self, creation: bool) -> Result<u32, CompilerError> {
+        // Lifted lambdas are written last as the first function is the entry point when the ABI
+        // boilerplate is disabled
+        let (lambdas, functions): (Vec<_>, Vec<_>) = self
+            .ir
+            .function_definitions
+            .iter()
+            .filter(|func| match func.function_kind {
+                FunctionKind::Constructor => creation,
+                FunctionKind::Transition | FunctionKind::Procedure => !creation,
+                FunctionKind::Function | FunctionKind::Lambda => true,
+            })
+            .partition(|func| matches!(func.function_kind, FunctionKind::Lambda));
+
+        // Recursion primitives are defined once after the functions instantiating them
+        let used_primitives: RefCell<BTreeSet<String>> = RefCell::new(BTreeSet::new());
+        let error: RefCell<Option<CompilerError>> = RefCell::new(None);
+        for func in functions.into_iter().chain(lambdas) {
             /*
             let mut ir_printer = DebugPrinter::new();
             let _ = ir_printer.visit_concrete_function(TreeTraversalMode::Enter, func, &mut self.ir.symbol_table);
             info!("{}", format!("IR: {}", ir_printer.value()));
             */
 
-            let arg_types: Vec<&str> = func
+            // Closures and environment records are passed as pointers
+            let is_lambda = matches!(func.function_kind, FunctionKind::Lambda);
+            let type_declarations = &self.builder.context.type_declarations;
+            let evm_typename = |typename: &'_ str| -> String {
+                if is_lambda && !type_declarations.contains_key(typename) {
+                    "Uint256".to_string()
+                } else {
+                    typename.to_string()
+                }
+            };
+
+            let arg_types: Vec<String> = func
                 .arguments
                 .iter()
-                .map(|arg| arg.typename.unresolved.as_str())
+                .map(|arg| evm_typename(&arg.typename.unresolved))
                 .collect();
 
             let arg_names: BTreeSet<String> = func
                 .arguments
                 .iter()
                 .map(|arg| {
-                    arg.name
-                        .resolved
-                        .clone()
-                        .expect("Unresolved function argument name")
+                    arg.name.resolved.clone().ok_or(format!(
+                        "Unresolved function argument name {}",
+                        arg.name.unresolved
+                    ))
                 })
-                .collect();
+                .collect::<Result<_, String>>()?;
 
             let function_name = func
                 .name
@@ -112,604 +1195,1178 @@ impl<'ctx> EvmBytecodeGenerator<'ctx> {
                 .unwrap_or(func.name.unresolved.clone());
 
             let return_type = match func.return_type.as_ref() {
-                Some(return_type) => return_type.as_str(),
-                None => "Uint256", // TODO: panic!("Void type not implemented for EVM")
+                Some(return_type) => evm_typename(return_type),
+                None => "Uint256".to_string(), // TODO: panic!("Void type not implemented for EVM")
             };
 
-            self.builder
-                .define_function(&function_name, arg_types, return_type)
-                .build(|code_builder| {
-                    let mut ret: Vec<EvmBlock> = Vec::new();
-                    let mut symbol_table = self.ir.symbol_table.clone();
+            // Transitions are called through the ABI and therefore take their ABI types
+            let external_arguments = match func.function_kind {
+                FunctionKind::Transition | FunctionKind::Procedure => Some(
+                    func.arguments
+                        .iter()
+                        .map(|arg| abi_type(&self.builder.context, &arg.typename.unresolved))
+                        .collect::<Result<Vec<EvmType>, CompilerError>>()?,
+                ),
+                _ => None,
+            };
 
-                    // TODO: Check that arg_names matches length of the arguments in the first block
-                    if let Some(entry) = func.body.blocks.first() {
-                        if arg_names.len() != entry.block_arguments.len() {
-                            panic!("Internal error: Function argument names differ from block names in length: {:?} vs {:?}", arg_names, entry.block_arguments);
-                        }
-                        if arg_names != entry.block_arguments {
-                            panic!("Internal error: Function argument names differ from block names in order");
+            let built = self
+                .builder
+                .define_function(
+                    &function_name,
+                    arg_types.iter().map(|t| t.as_str()).collect(),
+                    &return_type,
+                )
+                .build(|code_builder| {
+                    let mut build = || -> Result<Vec<EvmBlock>, CompilerError> {
+                        let mut ret: Vec<EvmBlock> = Vec::new();
+                        let mut symbol_table = self.ir.symbol_table.clone();
+
+                        // TODO: Check that arg_names matches length of the arguments in the first block
+                        if let Some(entry) = func.body.blocks.first() {
+                            if arg_names.len() != entry.block_arguments.len() {
+                                return Err(CompilerError::internal(format!("Internal error: Function argument names differ from block names in length: {:?} vs {:?}", arg_names, entry.block_arguments)));
+                            }
+                            if arg_names != entry.block_arguments {
+                                return Err(CompilerError::internal("Internal error: Function argument names differ from block names in order".to_string()));
+                            }
                         }
-                    }
-
-
 
 
-                    // Return PC + Arguments are expected to be on the stack
-                    for block in &func.body.blocks {
-                        let block_name = match block.name.qualified_name() {
-                            Ok(b) => b,
-                            Err(_) => panic!("Failed to get qualified name."),
-                        };
 
-                        // Creating entry function
-                        let block_args : BTreeSet<String> = block.block_arguments.clone();
-                        let mut evm_block =
-                            code_builder.new_evm_block_with_args(&block_name, block_args);
 
-                        for instr in &block.instructions {
-                            let mut instruction_printer = DebugPrinter::new();
-                            let mut instr_copy = instr.clone();
-                            let _ = instruction_printer.visit_instruction(TreeTraversalMode::Enter, &mut instr_copy, &mut symbol_table);
-                            evm_block.set_next_instruction_comment(instruction_printer.value());
+                        // Return PC + Arguments are expected to be on the stack
+                        for block in &func.body.blocks {
+                            let block_name = match block.name.qualified_name() {
+                                Ok(b) => b,
+                                Err(_) => return Err(CompilerError::internal("Failed to get qualified name.".to_string())),
+                            };
 
-                            let (l_pos, r_pos) = &instr.source_location;
-                            if l_pos.is_valid() && r_pos.is_valid() {
-                                let pos = EvmSourcePosition {
-                                    start: l_pos.position,
-                                    end: r_pos.position,
-                                    line: l_pos.line,
-                                    column: l_pos.column,
-                                };
-                                evm_block.set_next_instruction_location(pos);
-                            }
+                            // Creating entry function
+                            let block_args : BTreeSet<String> = block.block_arguments.clone();
+                            let mut evm_block =
+                                code_builder.new_evm_block_with_args(&block_name, block_args);
 
+                            for instr in &block.instructions {
+                                let mut instruction_printer = DebugPrinter::new();
+                                let mut instr_copy = instr.clone();
+                                let _ = instruction_printer.visit_instruction(TreeTraversalMode::Enter, &mut instr_copy, &mut symbol_table);
+                                evm_block.set_next_instruction_comment(instruction_printer.value());
 
-                            match &instr.operation {
-                                Operation::CallFunction {
-                                    ref name,
-                                    ref arguments,
-                                }
-                                | Operation::CallExternalFunction {
-                                    ref name,
-                                    ref arguments,
-                                } => {
-
-                                    // Invoking
-                                    let qualified_name = match &name.resolved {
-                                        Some(n) => n,
-                                        None => {
-                                            // TODO: Fix error propagation
-                                            panic!(
-                                                "Encountered unresolved function name {}",
-                                                name.unresolved
-                                            )
-                                        }
+                                let (l_pos, r_pos) = &instr.source_location;
+                                if l_pos.is_valid() && r_pos.is_valid() {
+                                    let pos = EvmSourcePosition {
+                                        start: l_pos.position,
+                                        end: r_pos.position,
+                                        line: l_pos.line,
+                                        column: l_pos.column,
                                     };
+                                    evm_block.set_next_instruction_location(pos);
+                                }
 
-                                    let mut ctx = &mut code_builder.context;
-                                    // We have three types of calls:
-                                    // - Precompiles / external function
-                                    // - Inline assembler generics
-                                    // - Internal calls
 
-                                    // Copying arguments to stack
-                                    for arg in arguments {
-                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        match &arg.resolved {
-                                            Some(n) => match evm_block.duplicate_stack_name(n) {
-                                                Err(e) => {
-                                                    print!("Block: {:#?}", evm_block);
-                                                    panic!("{} in {}", e, evm_block.name)
-                                                }
-                                                _ => (),
-                                            },
-                                            None => panic!("Argument name was not resolved"),
-                                        }
+                                match &instr.operation {
+                                    Operation::CallFunction {
+                                        ref name,
+                                        ref arguments,
                                     }
-
-                                    let args_types: Vec<String> = arguments
-                                        .iter()
-                                        .map(|arg| arg.type_reference.clone().unwrap())
-                                        .collect();
-
-                                    if ctx.function_declarations.contains_key(qualified_name) {
-                                        let signature = match ctx.get_function(qualified_name) {
-                                            Some(s) => s,
-                                            None => panic!(
-                                                "Internal error: Unable to retrieve function"
-                                            ),
+                                    | Operation::CallExternalFunction {
+                                        ref name,
+                                        ref arguments,
+                                    } => {
+
+                                        // Invoking
+                                        let qualified_name = match &name.resolved {
+                                            Some(n) => n,
+                                            None => {
+                                                return Err(CompilerError::internal(format!("Encountered unresolved function name {}", name.unresolved)))
+                                            }
                                         };
 
-                                        // Precompiled or external function
-                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        let mut ctx = &mut code_builder.context;
+                                        // We have three types of calls:
+                                        // - Precompiles / external function
+                                        // - Inline assembler generics
+                                        // - Internal calls
 
-                                        // TODO: Consider if argument mapping should be managed by the runtime module
-                                        evm_block.call(signature, args_types                            .iter()
-                            .map(|s| EvmType::from_str(s).unwrap())
-                            .collect());
-                                    } else if ctx.inline_generics.contains_key(&name.unresolved) {
-                                        // TODO: This ought to be the resovled name, but it should be resovled without instance parameters - make a or update pass
-                                        // Builtin assembly generator
-
-                                        let block_generator =
-                                            ctx.inline_generics.get(&name.unresolved).unwrap();
-                                        let new_blocks =
-                                            block_generator(&mut ctx, &mut evm_block, args_types);
-                                        match new_blocks {
-                                            Ok(new_blocks) => {
-                                                for block in new_blocks {
-                                                    ret.push(block);
-                                                }
-                                            }
-                                            Err(e) => {
-                                                panic!("Error in external call: {}", e);
-                                            }
-                                        }
-                                    } else {
-                                        // Internal function call
-                                        let exit_block_args: BTreeSet<String> = arguments.iter().map(|arg| {
+                                        // Copying arguments to stack
+                                        for arg in arguments {
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
                                             match &arg.resolved {
-                                                Some(a) =>a.clone(),
-                                                None => panic!("Unable to resolve {}", arg.unresolved),
+                                                Some(n) => match evm_block.duplicate_stack_name(n) {
+                                                    Err(e) => {
+                                                        print!("Block: {:#?}", evm_block);
+                                                        return Err(CompilerError::internal(format!("{} in {}", e, evm_block.name)))
+                                                    }
+                                                    _ => (),
+                                                },
+                                                None => return Err(CompilerError::internal("Argument name was not resolved".to_string())),
                                             }
-                                        }).collect();
-                                        let mut exit_block = code_builder.new_evm_block_with_args("exit_block", exit_block_args);
+                                        }
 
-                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        evm_block.push_label(&exit_block.name);
+                                        let args_types: Vec<String> = arguments
+                                            .iter()
+                                            .map(|arg| arg.type_reference.clone().ok_or(format!("Untyped argument {}", arg.unresolved)))
+                                            .collect::<Result<_, String>>()?;
 
-                                        for arg in arguments {
-                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                            let _ = match &arg.resolved {
-                                                Some(a) => evm_block.duplicate_stack_name(&a),
-                                                None => panic!("Unable to resolve {}", arg.unresolved),
+                                        if ctx.function_declarations.contains_key(qualified_name) {
+                                            let signature = match ctx.get_function(qualified_name) {
+                                                Some(s) => s,
+                                                None => return Err(CompilerError::internal("Internal error: Unable to retrieve function".to_string())),
                                             };
-                                        }
 
-                                        // Jumping to function
-                                        // TODO: Check that internal function is defined and throw an error if not.
-                                        let label = match &name.resolved {
-                                            Some(v) => {
-                                            // TODO: "Rsplit" hack to compensate for that the labels does not contain function parameter types
-                                                if let Some(index) = v.rfind("::<") {
-                                                    &v[..index]
-                                                } else {
-                                                    v
+                                            // Precompiled or external function
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+
+                                            // TODO: Consider if argument mapping should be managed by the runtime module
+                                            let evm_types = args_types
+                                                .iter()
+                                                .map(|s| EvmType::from_str(s).map_err(|_| format!("Unknown EVM type {}", s)))
+                                                .collect::<Result<_, String>>()?;
+                                            evm_block.call(signature, evm_types);
+                                        } else if ctx.inline_generics.contains_key(&name.unresolved) {
+                                            // TODO: This ought to be the resovled name, but it should be resovled without instance parameters - make a or update pass
+                                            // Builtin assembly generator
+
+                                            let block_generator =
+                                                ctx.inline_generics.get(&name.unresolved).unwrap();
+                                            let new_blocks =
+                                                block_generator(&mut ctx, &mut evm_block, args_types);
+                                            match new_blocks {
+                                                Ok(new_blocks) => {
+                                                    for block in new_blocks {
+                                                        ret.push(block);
+                                                    }
+                                                }
+                                                Err(e) => {
+                                                    return Err(CompilerError::internal(format!("Error in external call: {}", e)));
                                                 }
                                             }
-                                            None => panic!(
-                                                "Unresolved function name in function call {:?}",
-                                                name
-                                            ),
-                                        };
+                                        } else {
+                                            // Internal function call
+                                            let exit_block_args: BTreeSet<String> = arguments.iter().map(|arg| {
+                                                match &arg.resolved {
+                                                    Some(a) => Ok(a.clone()),
+                                                    None => Err(format!("Unable to resolve {}", arg.unresolved)),
+                                                }
+                                            }).collect::<Result<_, String>>()?;
+                                            let mut exit_block = code_builder.new_evm_block_with_args("exit_block", exit_block_args);
 
-                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            evm_block.push_label(&exit_block.name);
+
+                                            for arg in arguments {
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                let _ = match &arg.resolved {
+                                                    Some(a) => evm_block.duplicate_stack_name(&a),
+                                                    None => return Err(CompilerError::internal(format!("Unable to resolve {}", arg.unresolved))),
+                                                };
+                                            }
 
-                                        // Note that we do not need to add scopes to function jumps as these are 
-                                        // outside of the function scope                                    
-                                        evm_block.jump_to(&label);
-                                        mem::swap(&mut evm_block, &mut exit_block);
-                                        ret.push(exit_block);
+                                            // Jumping to function
+                                            // TODO: Check that internal function is defined and throw an error if not.
+                                            let label = match &name.resolved {
+                                                Some(v) => {
+                                                // TODO: "Rsplit" hack to compensate for that the labels does not contain function parameter types
+                                                    if let Some(index) = v.rfind("::<") {
+                                                        &v[..index]
+                                                    } else {
+                                                        v
+                                                    }
+                                                }
+                                                None => return Err(CompilerError::internal(format!("Unresolved function name in function call {:?}", name))),
+                                            };
+
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+
+                                            // Note that we do not need to add scopes to function jumps as these are 
+                                            // outside of the function scope                                    
+                                            evm_block.jump_to(&label);
+                                            mem::swap(&mut evm_block, &mut exit_block);
+                                            ret.push(exit_block);
+                                        }
                                     }
-                                }
 
-                                Operation::Literal {
-                                    ref data,
-                                    ref typename,
-                                } => {
-                                    let qualified_name = match typename.qualified_name() {
-                                        Ok(v) => v,
-                                        _ => panic!("Qualified name could not be resolved"),
-                                    };
-                                    let ssa_name = match &instr.ssa_name {
-                                        Some(v) => match &v.resolved {
-                                            Some(x) => x,
-                                            _ => panic!("Literal symbol name was unresolved."),
-                                        },
-                                        _ => panic!("Literals with no SSA name are not supported"),
-                                    };
+                                    Operation::Literal {
+                                        ref data,
+                                        ref typename,
+                                    } => {
+                                        let qualified_name = match typename.qualified_name() {
+                                            Ok(v) => v,
+                                            _ => return Err(CompilerError::internal("Qualified name could not be resolved".to_string())),
+                                        };
+                                        let ssa_name = match &instr.ssa_name {
+                                            Some(v) => match &v.resolved {
+                                                Some(x) => x,
+                                                _ => return Err(CompilerError::internal("Literal symbol name was unresolved.".to_string())),
+                                            },
+                                            _ => return Err(CompilerError::internal("Literals with no SSA name are not supported".to_string())),
+                                        };
 
-                                    match qualified_name.as_str() {
-                                        "String" => {
-                                            let payload = data.clone();
-                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                            let payload = payload[1..payload.len()-1].as_bytes();
-                                            evm_block.allocate_object(payload.to_vec());
-                                            match evm_block.register_stack_name(ssa_name) {
-                                                Err(_) => {
-                                                    panic!("Failed to register SSA stack name.")
+                                        match qualified_name.as_str() {
+                                            // Byte strings of up to a word are held on the stack, longer ones in memory
+                                            "String" if data.starts_with("0x") => {
+                                                let payload = match hex::decode(&data[2..]) {
+                                                    Ok(v) => v,
+                                                    Err(_) => return Err(CompilerError::internal(format!("Invalid hex literal {}", data))),
+                                                };
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                if payload.len() <= 32 {
+                                                    evm_block.push_u256(U256::from_big_endian(&payload));
+                                                } else {
+                                                    evm_block.allocate_object(payload);
+                                                }
+                                                match evm_block.register_stack_name(ssa_name) {
+                                                    Err(_) => {
+                                                        return Err(CompilerError::internal("Failed to register SSA stack name.".to_string()))
+                                                    }
+                                                    _ => (),
                                                 }
-                                                _ => (),
                                             }
+                                            "String" => {
+                                                let payload = data.clone();
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                let payload = payload[1..payload.len()-1].as_bytes();
+                                                evm_block.allocate_object(payload.to_vec());
+                                                match evm_block.register_stack_name(ssa_name) {
+                                                    Err(_) => {
+                                                        return Err(CompilerError::internal("Failed to register SSA stack name.".to_string()))
+                                                    }
+                                                    _ => (),
+                                                }
 
-                                        }
-                                        "Uint64" => {
-                                            let value = EvmTypeValue::Uint64(data.parse().unwrap());
-                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                            evm_block.push(value.to_bytes_unpadded());
-                                            match evm_block.register_stack_name(ssa_name) {
-                                                Err(_) => {
-                                                    panic!("Failed to register SSA stack name.")
+                                            }
+                                            "BNum" => {
+                                                let value = match U256::from_dec_str(data) {
+                                                    Ok(v) => v,
+                                                    Err(_) => return Err(CompilerError::internal(format!("Invalid block number {}", data))),
+                                                };
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                evm_block.push_u256(value);
+                                                match evm_block.register_stack_name(ssa_name) {
+                                                    Err(_) => {
+                                                        return Err(CompilerError::internal("Failed to register SSA stack name.".to_string()))
+                                                    }
+                                                    _ => (),
                                                 }
-                                                _ => (),
                                             }
-                                        }
-                                        // TODO: add cases for other types of literals here if needed
-                                        _ => {
-                                            panic!(
-                                                "{}",
-                                                format!(
+                                            name if name.starts_with("Int") || name.starts_with("Uint") => {
+                                                let value = match integer_literal(&code_builder.context, name, data) {
+                                                    Ok(v) => v,
+                                                    Err(e) => return Err(CompilerError::internal(e)),
+                                                };
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                evm_block.push(value);
+                                                match evm_block.register_stack_name(ssa_name) {
+                                                    Err(_) => {
+                                                        return Err(CompilerError::internal("Failed to register SSA stack name.".to_string()))
+                                                    }
+                                                    _ => (),
+                                                }
+                                            }
+                                            // TODO: add cases for other types of literals here if needed
+                                            _ => {
+                                                return Err(CompilerError::unsupported(format!(
                                                     "Unhandled literal type: {:?}",
                                                     typename.qualified_name()
-                                                )
-                                            );
+                                                )));
+                                            }
                                         }
                                     }
-                                }
-                                Operation::ResolveContextResource {ref symbol} => {
-                                    let mut ctx = &mut code_builder.context;
-
-                                    if ctx.special_variables.contains_key(&symbol.unresolved) {
-                                        // TODO: This ought to be the resovled name, but it should be resovled without instance parameters - make a or update pass
-                                        // Builtin assembly generator
-                                        let block_generator =
-                                            ctx.special_variables.get(&symbol.unresolved).unwrap();
-                                        let new_blocks =
-                                            block_generator(&mut ctx, &mut evm_block );
-                                        match new_blocks {
-                                            Ok(new_blocks) => {
-                                                for block in new_blocks {
-                                                    ret.push(block);
+                                    Operation::ResolveContextResource {ref symbol} => {
+                                        let mut ctx = &mut code_builder.context;
+
+                                        if ctx.special_variables.contains_key(&symbol.unresolved) {
+                                            // TODO: This ought to be the resovled name, but it should be resovled without instance parameters - make a or update pass
+                                            // Builtin assembly generator
+                                            let block_generator =
+                                                ctx.special_variables.get(&symbol.unresolved).unwrap();
+                                            let new_blocks =
+                                                block_generator(&mut ctx, &mut evm_block );
+                                            match new_blocks {
+                                                Ok(new_blocks) => {
+                                                    for block in new_blocks {
+                                                        ret.push(block);
+                                                    }
+                                                }
+                                                Err(e) => {
+                                                    return Err(CompilerError::internal(format!("Error in external call: {}", e)));
                                                 }
-                                            }
-                                            Err(e) => {
-                                                panic!("Error in external call: {}", e);
                                             }
                                         }
+                                        else {
+                                            return Err(CompilerError::internal(format!("Special variable {} not found", symbol.unresolved)));
+                                        }
                                     }
-                                    else {
-                                        panic!("Special variable {} not found", symbol.unresolved);
-                                    }
-                                }
-                                Operation::ResolveSymbol { ref symbol } => {
-                                    let source = match &symbol.resolved {
-                                        Some(v) => v,
-                                        None => panic!("Unresolved symbol: {:?}", symbol),
-                                    };
-                                    let dest = match &instr.ssa_name {
-                                        Some(v) => match &v.resolved {
-                                            Some(x) => x,
-                                            _ => panic!("Alias symbol name was unresolved."),
-                                        },
-                                        _ => panic!("Alias with no SSA name are not supported"),
-                                    };
+                                    Operation::ResolveSymbol { ref symbol } => {
+                                        let source = match &symbol.resolved {
+                                            Some(v) => v,
+                                            None => return Err(CompilerError::internal(format!("Unresolved symbol: {:?}", symbol))),
+                                        };
+                                        let dest = match &instr.ssa_name {
+                                            Some(v) => match &v.resolved {
+                                                Some(x) => x,
+                                                _ => return Err(CompilerError::internal("Alias symbol name was unresolved.".to_string())),
+                                            },
+                                            _ => return Err(CompilerError::internal("Alias with no SSA name are not supported".to_string())),
+                                        };
 
-                                    if let Err(e) = evm_block.register_alias(source, dest) {
-                                        panic!("Failed registering alias: {:?}", e);
+                                        if let Err(e) = evm_block.register_alias(source, dest) {
+                                            return Err(CompilerError::internal(format!("Failed registering alias: {:?}", e)));
+                                        }
                                     }
-                                }
-                                Operation::StateStore {
-                                    ref address,
-                                    ref value,
-                                } => {
-                                    // TODO: Ensure that we used resolved address name
-                                    let binding = &self.ir.symbol_table.state_layout.get(&address.name.unresolved);
-                                    let state = match binding {
-                                        Some(v) => v,
-                                        None => panic!(
-                                            "{}",
-                                            format!(
+                                    Operation::StateStore {
+                                        ref address,
+                                        ref value,
+                                    } => {
+                                        // TODO: Ensure that we used resolved address name
+                                        let binding = &self.ir.symbol_table.state_layout.get(&address.name.unresolved);
+                                        let state = match binding {
+                                            Some(v) => v,
+                                            None => return Err(CompilerError::internal(format!(
                                                 "Unable to find state {} (storing {})",
                                                 address.name.unresolved, value.unresolved
-                                            )
-                                        ),
-                                    };
+                                            ))),
+                                        };
 
-                                    let address = state.address_offset;
+                                        let value_name = match &value.resolved {
+                                            Some(v) => v,
+                                            None => {
+                                                return Err(CompilerError::internal(format!("Unable to resolve {:?}", value)))
+                                            }
+                                        };
 
-                                    let value_name = match &value.resolved {
-                                        Some(v) => v,
-                                        None => {
-                                            panic!("{}", format!("Unable to resolve {:?}", value))
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        if let Err(e) = evm_block.duplicate_stack_name(value_name) {
+                                            return Err(CompilerError::internal(format!("Unable to resolve value to be stored: {:?}", e)));
                                         }
-                                    };
 
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    if let Err(e) = evm_block.duplicate_stack_name(value_name) {
-                                        panic!("Unable to resolve value to be stored: {:?}", e);
+                                        if let Err(e) = push_state_store(&mut evm_block, state) {
+                                            return Err(CompilerError::internal(format!("Unable to store {}: {}", address.name.unresolved, e)));
+                                        }
                                     }
 
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    evm_block.push_u256(address);
-                                    evm_block.external_sstore();
-                                }
-
-                                Operation::StateLoad {
-                                    ref address,
-                                } => {
-                                    // TODO: Ensure that we used resolved address name
-                                    let binding = &self.ir.symbol_table.state_layout.get(&address.name.unresolved);
-                                    let value = match &instr.ssa_name {
-                                        Some(v) => v,
-                                        None => panic!("Load does not assign value")
+                                    Operation::StateLoad {
+                                        ref address,
+                                    } => {
+                                        // TODO: Ensure that we used resolved address name
+                                        let binding = &self.ir.symbol_table.state_layout.get(&address.name.unresolved);
+                                        let value = match &instr.ssa_name {
+                                            Some(v) => v,
+                                            None => return Err(CompilerError::internal("Load does not assign value".to_string()))
 
-                                    };
-                                    let state = match binding {
-                                        Some(v) => v,
-                                        None => panic!(
-                                            "{}",
-                                            format!(
+                                        };
+                                        let state = match binding {
+                                            Some(v) => v,
+                                            None => return Err(CompilerError::internal(format!(
                                                 "Unable to find state {} (loading to {})",
                                                 address.name.unresolved,
                                                 value.unresolved
-                                            )
-                                        ),
-                                    };
+                                            ))),
+                                        };
 
-                                    let address = state.address_offset;
+                                        let value_name = match &value.resolved {
+                                            Some(v) => v,
+                                            None => {
+                                                return Err(CompilerError::internal(format!("Unable to resolve {:?}", value)))
+                                            }
+                                        };
 
-                                    let value_name = match &value.resolved {
-                                        Some(v) => v,
-                                        None => {
-                                            panic!("{}", format!("Unable to resolve {:?}", value))
+                                        if let Err(e) = push_state_load(&mut evm_block, state) {
+                                            return Err(CompilerError::internal(format!("Unable to load {}: {}", address.name.unresolved, e)));
                                         }
-                                    };
+                                        let _ = evm_block.register_stack_name(value_name);
+                                    }
+                                    Operation::StateRemoteLoad { ref contract, ref field } => {
+                                        if let Err(e) = push_remote_field(&mut evm_block, contract, field) {
+                                            return Err(CompilerError::internal(format!("Unable to fetch remote field: {}", e)));
+                                        }
+                                    }
+                                    Operation::StateMapLoad { ref address, ref keys }
+                                    | Operation::StateMapExists { ref address, ref keys }
+                                    | Operation::StateMapDelete { ref address, ref keys }
+                                    | Operation::StateMapStore {
+                                        ref address,
+                                        ref keys,
+                                        value: _,
+                                    } => {
+                                        // TODO: Ensure that we used resolved address name
+                                        let state = match self.ir.symbol_table.state_layout.get(&address.name.unresolved) {
+                                            Some(v) => v,
+                                            None => return Err(CompilerError::internal(format!("Unable to find map {}", address.name.unresolved))),
+                                        };
 
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    evm_block.push_u256(address);
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    evm_block.external_sload();
-                                    let _ = evm_block.register_stack_name(value_name);
-                                }
-                                Operation::Return(ref _value) => {
-                                    // Assumes that the next element on the stack is return pointer
-                                    // TODO: Pop all elements that were not used yet.
-                                    // TODO: Push value if exists and swap1, then jump
+                                        let mark_existence = matches!(instr.operation, Operation::StateMapStore { .. });
+                                        if let Err(e) = push_map_slot(&mut evm_block, state.address_offset, keys, mark_existence) {
+                                            return Err(CompilerError::internal(format!("Unable to compute map slot: {}", e)));
+                                        }
 
-                                    while evm_block.scope.stack_counter > 0 {
-                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        evm_block.pop();
+                                        match &instr.operation {
+                                            Operation::StateMapLoad { .. } => {
+                                                // The entry is read as Some of its value if its existence flag is set
+                                                // and as None otherwise
+                                                let lbl_none = evm_block.generate_label("map_entry_none".to_string());
+                                                let lbl_done = evm_block.generate_label("map_entry_done".to_string());
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                evm_block.dup1();
+                                                evm_block.push1([0x01].to_vec());
+                                                evm_block.add();
+                                                evm_block.external_sload();
+                                                evm_block.push1([0x01].to_vec());
+                                                evm_block.and();
+                                                evm_block.iszero();
+                                                evm_block.jump_if_to(&lbl_none);
+                                                evm_block.external_sload();
+                                                push_some(&mut evm_block);
+                                                evm_block.jump_to(&lbl_done);
+
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                evm_block.create_label(lbl_none);
+                                                evm_block.pop();
+                                                evm_block.push1([0x00].to_vec());
+                                                evm_block.create_label(lbl_done);
+                                            }
+                                            Operation::StateMapExists { .. } => {
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                evm_block.push1([0x01].to_vec());
+                                                evm_block.add();
+                                                evm_block.external_sload();
+                                                evm_block.push1([0x01].to_vec());
+                                                evm_block.and();
+                                            }
+                                            Operation::StateMapStore { value, .. } => {
+                                                let value_name = match &value.resolved {
+                                                    Some(v) => v,
+                                                    None => return Err(CompilerError::internal(format!("Unable to resolve {:?}", value))),
+                                                };
+
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                if let Err(e) = evm_block.duplicate_stack_name(value_name) {
+                                                    return Err(CompilerError::internal(format!("Unable to resolve value to be stored: {:?}", e)));
+                                                }
+                                                evm_block.swap1();
+                                                evm_block.external_sstore();
+                                            }
+                                            _ => {
+                                                // Clearing the value and the existence flag and counting the deletion,
+                                                // which moves the entries of a nested map to fresh slots:
+                                                // flags(s) = (flags(s) | 1) + 1
+                                                evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                                evm_block.push1([0x00].to_vec());
+                                                evm_block.dup2();
+                                                evm_block.external_sstore();
+                                                evm_block.push1([0x01].to_vec());
+                                                evm_block.add();
+                                                evm_block.dup1();
+                                                evm_block.external_sload();
+                                                evm_block.push1([0x01].to_vec());
+                                                evm_block.or();
+                                                evm_block.push1([0x01].to_vec());
+                                                evm_block.add();
+                                                evm_block.swap1();
+                                                evm_block.external_sstore();
+                                            }
+                                        }
                                     }
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    evm_block.jump();
-                                }
-                                Operation::CallStaticFunction {
-                                    // TODO: Poor name
-                                    ref name,
-                                    owner: _,
-                                    ref arguments,
-                                    template_type_arguments: _
-                                } => {
-                                    if arguments.len() > 0 {
-                                        // TODO: Pack data
-                                        unimplemented!();
+                                    Operation::ConstructMessage { ref entries } => {
+                                        let argument_types = message_argument_types(&code_builder.context, entries)?;
+                                        if let Err(e) = push_message(&mut evm_block, entries, &argument_types) {
+                                            return Err(CompilerError::internal(format!("Unable to construct message: {}", e)));
+                                        }
                                     }
-                                    let name = match &name.resolved {
-                                        Some(n) => n,
-                                        None => {
-                                            panic!("Unable to resolve name {:?}", name.unresolved)
+                                    Operation::EmitEvent { ref message } => {
+                                        if let Err(e) = emit_event(&mut evm_block, message) {
+                                            return Err(CompilerError::internal(format!("Unable to emit event: {}", e)));
                                         }
-                                    };
-
-                                    let ctx = &mut code_builder.context;
-                                    if let Some(constructor) = &ctx.default_constructors.get(name) {
-                                        constructor(&mut evm_block);
-                                    } else {
-                                        // Falling back to plain enum type naming with no data associated
-                                        // for custom types.
-                                        let hash = Keccak256::digest(name);
-                                        let mut selector = Vec::new();
-                                        selector.extend_from_slice(&hash[..4]);
-                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        evm_block.push(selector);
                                     }
-                                }
-                                Operation::IsEqual {
-                                    ref left,
-                                    ref right,
-                                } => {
-
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    match &left.resolved {
-                                        Some(l) => match evm_block.duplicate_stack_name(l) {
-                                            Ok(()) => (),
-                                            Err(e) => panic!("{:#?}", e),
-                                        },
-                                        None => panic!("Unresolved left hand side"),
+                                    Operation::SendMessage { ref message } => {
+                                        if let Err(e) = send_message(&mut evm_block, message) {
+                                            return Err(CompilerError::internal(format!("Unable to send message: {}", e)));
+                                        }
                                     }
-                                    match &right.resolved {
-                                        Some(r) => match evm_block.duplicate_stack_name(r) {
-                                            Ok(()) => (),
-                                            Err(e) => panic!("{:#?}", e),
-                                        },
-                                        None => panic!("Unresolved left hand side"),
+                                    Operation::Revert(ref exception) => {
+                                        if let Err(e) = revert_with_exception(&mut evm_block, exception) {
+                                            return Err(CompilerError::internal(format!("Unable to revert: {}", e)));
+                                        }
                                     }
+                                    Operation::Return(Some(ref value)) => {
+                                        // Leaving the return value on top of the return pointer
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        match &value.resolved {
+                                            Some(v) => {
+                                                if let Err(e) = evm_block.duplicate_stack_name(v) {
+                                                    return Err(CompilerError::internal(format!("{} in {}", e, evm_block.name)))
+                                                }
+                                            }
+                                            None => return Err(CompilerError::internal(format!("Unresolved return value {}", value.unresolved))),
+                                        }
 
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    evm_block.eq();
-                                }
-                                Operation::Jump(label) => {
-                                    let label = match &label.resolved {
-                                        Some(l) => l,
-                                        None => panic!("Could not resolve default label"),
-                                    };
-
-                                    let mut pop_count = evm_block.scope.stack_counter;
-                                    let jump_args = block
-                                        .jump_required_arguments
-                                        .get(label)
-                                        .unwrap_or(&BTreeSet::new())
-                                        .clone();
-
-                                    // Preserving the args to the next block
-                                    pop_count -= jump_args.len() as i32;
-
-                                    // Moving arguments
-                                    // Notice the reversing of the arguments, since positions are in relative stack
-                                    // depth and consenquently the first argument becomes the deepest (highest number)
-                                    for (i, arg) in jump_args.iter().rev().enumerate() {
-                                        let pos = pop_count+i as i32;
-                                        evm_block.set_next_instruction_comment(format!("Moving argument {} '{}' behind {}",pos,arg, pop_count).to_string()) ;
+                                        while evm_block.scope.stack_counter > 1 {
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            evm_block.swap1();
+                                            evm_block.pop();
+                                        }
                                         evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        match evm_block.move_stack_name(&arg, pos) {
-                                            Ok(()) => (),
-                                            Err(e) => panic!("{:#?}", e),
+                                        evm_block.swap1();
+                                        evm_block.jump();
+                                    }
+                                    Operation::Return(None) => {
+                                        // Assumes that the next element on the stack is return pointer
+                                        // TODO: Pop all elements that were not used yet.
+
+                                        while evm_block.scope.stack_counter > 0 {
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            evm_block.pop();
                                         }
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        evm_block.jump();
+                                    }
+                                    Operation::CallStaticFunction {
+                                        // TODO: Poor name
+                                        ref name,
+                                        owner: _,
+                                        ref arguments,
+                                        template_type_arguments: _
+                                    } => {
+                                        let name = match &name.resolved {
+                                            Some(n) => n,
+                                            None => {
+                                                return Err(CompilerError::internal(format!("Unable to resolve name {:?}", name.unresolved)))
+                                            }
+                                        };
 
+                                        let ctx = &mut code_builder.context;
+                                        if arguments.len() > 0 {
+                                            // Values with data are allocated in memory
+                                            construct_value(ctx, &symbol_table, &mut evm_block, name, arguments)?;
+                                        } else if let Some(constructor) = &ctx.default_constructors.get(name) {
+                                            constructor(&mut evm_block);
+                                        } else {
+                                            // Falling back to plain enum type naming with no data associated
+                                            // for custom types.
+                                            let hash = Keccak256::digest(name);
+                                            let mut selector = Vec::new();
+                                            selector.extend_from_slice(&hash[..4]);
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            evm_block.push(selector);
+                                        }
                                     }
+                                    Operation::IsEqual {
+                                        ref left,
+                                        ref right,
+                                    } => {
 
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        match &left.resolved {
+                                            Some(l) => match evm_block.duplicate_stack_name(l) {
+                                                Ok(()) => (),
+                                                Err(e) => return Err(CompilerError::internal(format!("{:#?}", e))),
+                                            },
+                                            None => return Err(CompilerError::internal("Unresolved left hand side".to_string())),
+                                        }
+                                        match &right.resolved {
+                                            Some(r) => match evm_block.duplicate_stack_name(r) {
+                                                Ok(()) => (),
+                                                Err(e) => return Err(CompilerError::internal(format!("{:#?}", e))),
+                                            },
+                                            None => return Err(CompilerError::internal("Unresolved left hand side".to_string())),
+                                        }
 
-                                    while pop_count > 0 {
                                         evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        evm_block.pop();
-                                        pop_count -= 1;
+                                        evm_block.eq();
                                     }
+                                    Operation::Jump(label) => {
+                                        let label = match &label.resolved {
+                                            Some(l) => l,
+                                            None => return Err(CompilerError::internal("Could not resolve default label".to_string())),
+                                        };
 
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    evm_block.jump_to(&code_builder.add_scope_to_label(label));
-                                }
-                                Operation::ConditionalJump {
-                                    ref expression,
-                                    ref on_success,
-                                    ref on_failure,
-                                } => {
-
-                                    let _ = match &expression.resolved {
-                                        Some(name) => evm_block.duplicate_stack_name(&name),
-                                        None => panic!("Expression does not have a SSA name"),
-                                    };
+                                        let jump_args = block
+                                            .jump_required_arguments
+                                            .get(label)
+                                            .unwrap_or(&BTreeSet::new())
+                                            .clone();
 
-                                    let mut pop_count = evm_block.scope.stack_counter;
+                                        let target = func.body.blocks.iter().find(|b| b.name.resolved.as_ref() == Some(label));
+                                        if let Err(e) = push_phi_value(&mut evm_block, block, target) {
+                                            return Err(CompilerError::internal(format!("{} in {}", e, evm_block.name)));
+                                        }
 
-                                    let success_label = match &on_success.resolved {
-                                        Some(l) => l,
-                                        None => panic!("Could not resolve on_success label"),
-                                    };
+                                        // Moving the args to the next block to the bottom of the stack
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        let mut pop_count = match evm_block.move_block_arguments(&jump_args) {
+                                            Ok(v) => v,
+                                            Err(e) => return Err(CompilerError::internal(format!("{:#?}", e))),
+                                        };
 
-                                    let failure_label = match &on_failure.resolved {
-                                        Some(l) => l,
-                                        None => panic!("Could not resolve on_failure label"),
-                                    };
-                                    // TODO: Fix this such that it is done properly
-
-                                    let success_jump_args = block
-                                        .jump_required_arguments
-                                        .get(success_label)
-                                        .unwrap_or(&BTreeSet::new())
-                                        .clone();
-                                    let failure_jump_args = block
-                                        .jump_required_arguments
-                                        .get(failure_label)
-                                        .unwrap_or(&BTreeSet::new())
-                                        .clone();
-
-                                    if !success_jump_args.eq(&failure_jump_args) {
-                                        panic!("Block termination must require same number of subsequent variable dependencies.");
-                                    }
+                                        while pop_count > 0 {
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            evm_block.pop();
+                                            pop_count -= 1;
+                                        }
 
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        evm_block.jump_to(&code_builder.add_scope_to_label(label));
+                                    }
+                                    Operation::ConditionalJump {
+                                        ref expression,
+                                        ref on_success,
+                                        ref on_failure,
+                                    } => {
+
+                                        let success_label = match &on_success.resolved {
+                                            Some(l) => l,
+                                            None => return Err(CompilerError::internal("Could not resolve on_success label".to_string())),
+                                        };
 
-                                    // Preserving the args to the next block and the condition                                    
-                                    pop_count -= success_jump_args.len()  as i32;
-                                    assert!(pop_count>=0);
+                                        let failure_label = match &on_failure.resolved {
+                                            Some(l) => l,
+                                            None => return Err(CompilerError::internal("Could not resolve on_failure label".to_string())),
+                                        };
+                                        // TODO: Fix this such that it is done properly
+
+                                        let success_jump_args = block
+                                            .jump_required_arguments
+                                            .get(success_label)
+                                            .unwrap_or(&BTreeSet::new())
+                                            .clone();
+                                        let failure_jump_args = block
+                                            .jump_required_arguments
+                                            .get(failure_label)
+                                            .unwrap_or(&BTreeSet::new())
+                                            .clone();
+
+                                        if !success_jump_args.eq(&failure_jump_args) {
+                                            return Err(CompilerError::internal("Block termination must require same number of subsequent variable dependencies.".to_string()));
+                                        }
 
-                                    // Putting all arguments on the stack and preparing to pop before jumping
-                                    // Notice the reversing of the arguments, since positions are in relative stack
-                                    // depth and consenquently the first argument becomes the deepest (highest number)
-                                    for (i, arg) in success_jump_args.iter().rev().enumerate() {
-                                        let pos = pop_count+i as i32;
+                                        // Moving the args to the next block to the bottom of the stack before
+                                        // putting the condition on top
                                         evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        evm_block.set_next_instruction_comment(format!("Moving argument {} '{}' to {}",i, arg, pos).to_string()) ;
-                                        //assert_eq!(pos, evm_block.scope.stack_counter+1 - (success_jump_args.len() - i) as i32);
+                                        let mut pop_count = match evm_block.move_block_arguments(&success_jump_args) {
+                                            Ok(v) => v,
+                                            Err(e) => return Err(CompilerError::internal(format!("{:#?}", e))),
+                                        };
 
-                                        match evm_block.move_stack_name(&arg, pos) {
-                                            Ok(()) => (),
-                                            Err(e) => panic!("{:#?}", e),
+                                        let _ = match &expression.resolved {
+                                            Some(name) => evm_block.duplicate_stack_name(&name),
+                                            None => return Err(CompilerError::internal("Expression does not have a SSA name".to_string())),
+                                        };
+                                        pop_count += 1;
+
+                                        // Making room for the condition
+                                        assert!(pop_count>0);
+                                        pop_count-= 1;
+
+                                        if pop_count > 0 {
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            evm_block.set_next_instruction_comment(format!("Preserving jump condition and preparing stack deletion {}", pop_count).to_string());
+                                            evm_block.swap(pop_count);
                                         }
-                                    }
 
-                                    // Making room for the condition
-                                    assert!(pop_count>0);
-                                    pop_count-= 1;
+                                        while pop_count > 0 {
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            evm_block.pop();
+                                            pop_count -= 1;
+                                        }
 
-                                    if pop_count > 0 {
                                         evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        evm_block.set_next_instruction_comment(format!("Preserving jump condition and preparing stack deletion {}", pop_count).to_string());
-                                        evm_block.swap(pop_count);
+                                        evm_block.jump_if_to(&code_builder.add_scope_to_label(success_label));
+
+                                        // TODO: manage stack
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        evm_block.jump_to(&code_builder.add_scope_to_label(failure_label));
                                     }
+                                    Operation::CreateClosure {
+                                        ref function,
+                                        ref captures,
+                                    } => {
+                                        let function_name = match &function.resolved {
+                                            Some(f) => f,
+                                            None => return Err(CompilerError::internal(format!("Unresolved anonymous function {}", function.unresolved))),
+                                        };
 
-                                    while pop_count > 0 {
+                                        // The environment record holds the function pointer followed by the captures
                                         evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                        evm_block.pop();
-                                        pop_count -= 1;
+                                        evm_block.alloca_static(32 * (1 + captures.len() as u64));
+                                        evm_block.push_label(function_name);
+                                        evm_block.dup2();
+                                        evm_block.mstore();
+
+                                        for (i, capture) in captures.iter().enumerate() {
+                                            evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                            match &capture.resolved {
+                                                Some(c) => {
+                                                    if let Err(e) = evm_block.duplicate_stack_name(c) {
+                                                        return Err(CompilerError::internal(format!("{} in {}", e, evm_block.name)))
+                                                    }
+                                                }
+                                                None => return Err(CompilerError::internal(format!("Unresolved capture {}", capture.unresolved))),
+                                            }
+                                            evm_block.dup2();
+                                            evm_block.push_u64(32 * (i as u64 + 1));
+                                            evm_block.add();
+                                            evm_block.mstore();
+                                        }
+                                    }
+                                    Operation::LoadCapture {
+                                        ref closure,
+                                        index,
+                                    } => {
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        match &closure.resolved {
+                                            Some(c) => {
+                                                if let Err(e) = evm_block.duplicate_stack_name(c) {
+                                                    return Err(CompilerError::internal(format!("{} in {}", e, evm_block.name)))
+                                                }
+                                            }
+                                            None => return Err(CompilerError::internal(format!("Unresolved closure {}", closure.unresolved))),
+                                        }
+                                        evm_block.push_u64(32 * (*index as u64 + 1));
+                                        evm_block.add();
+                                        evm_block.mload();
                                     }
+                                    Operation::ApplyClosure {
+                                        ref closure,
+                                        ref argument,
+                                    } => {
+                                        let closure = match &closure.resolved {
+                                            Some(c) => c,
+                                            None => return Err(CompilerError::internal(format!("Unresolved closure {}", closure.unresolved))),
+                                        };
+                                        let argument = match &argument.resolved {
+                                            Some(a) => a,
+                                            None => return Err(CompilerError::internal(format!("Unresolved argument {}", argument.unresolved))),
+                                        };
 
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    evm_block.jump_if_to(&code_builder.add_scope_to_label(success_label));
+                                        // The callee consumes the return pointer, the closure and the argument
+                                        // and leaves its return value in their place
+                                        let mut exit_block = code_builder.new_evm_block("closure_exit");
+                                        exit_block.scope = evm_block.scope.clone();
+                                        exit_block.scope.stack_counter += 1;
 
-                                    // TODO: manage stack
-                                    evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
-                                    evm_block.jump_to(&code_builder.add_scope_to_label(failure_label));
-                                }
-                                Operation::TerminatingRef (_) => {
-                                    // Ignore terminating ref as this will just be pop at the end of the block.
-                                }
-                                _ => {
-                                    panic!("Unhandled operation {:#?}",instr);
-                                }
-                            }
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        evm_block.push_label(&exit_block.name);
+                                        for name in [closure, argument, closure] {
+                                            if let Err(e) = evm_block.duplicate_stack_name(name) {
+                                                return Err(CompilerError::internal(format!("{} in {}", e, evm_block.name)))
+                                            }
+                                        }
+                                        evm_block.mload();
+                                        evm_block.jump();
 
-                            // Handling SSA
-                            if let Some(ssa_name) = &instr.ssa_name {
-                                let ssa_name = match &ssa_name.resolved {
-                                    Some(x) => x,
-                                    _ => panic!("SSA symbol name was unresolved."),
-                                };
-
-                                match instr.operation {
-                                    Operation::ResolveSymbol { symbol: _ }
-                                    | Operation::StateStore {
-                                        address: _,
-                                        value: _,
+                                        mem::swap(&mut evm_block, &mut exit_block);
+                                        ret.push(exit_block);
+                                    }
+                                    Operation::LoadConstructorArgument {
+                                        ref value,
+                                        ref constructor,
+                                        index,
+                                    } => {
+                                        load_constructor_argument(&code_builder.context, &symbol_table, &mut evm_block, value, constructor, *index)?;
+                                    }
+                                    Operation::InstantiatePrimitive {
+                                        ref primitive,
+                                        type_arguments: _,
+                                    } => {
+                                        // Types are erased, so every instance shares the closure defined once the
+                                        // functions have been written
+                                        evm_block.set_next_rust_position(file!().to_string(), line!() as usize);
+                                        evm_block.alloca_static(0x20);
+                                        evm_block.push_label(&recursion_primitive_label(&primitive.unresolved));
+                                        evm_block.dup2();
+                                        evm_block.mstore();
+                                        used_primitives.borrow_mut().insert(primitive.unresolved.clone());
+                                    }
+                                    Operation::PhiNode(_) => {
+                                        // The value is put in place by the jump to this block
                                     }
-                                    | Operation::StateLoad {
-                                        address: _,
+                                    Operation::TerminatingRef (_) => {
+                                        // Ignore terminating ref as this will just be pop at the end of the block.
                                     }
-                                    | Operation::Literal {
-                                        data: _,
-                                        typename: _,
-                                    } => (), // Literals are handled in the first match statement
                                     _ => {
-                                        match evm_block.register_stack_name(ssa_name) {
-                                        Err(_) => {
-                                            panic!(
-                                                "Failed to register SSA stack name: {}.",
-                                                ssa_name
-                                            );
-                                        }
-                                        _ => (),
+                                        return Err(CompilerError::unsupported(format!("Unhandled operation {:#?}", instr)));
                                     }
+                                }
+
+                                // Handling SSA
+                                if let Some(ssa_name) = &instr.ssa_name {
+                                    let ssa_name = match &ssa_name.resolved {
+                                        Some(x) => x,
+                                        _ => return Err(CompilerError::internal("SSA symbol name was unresolved.".to_string())),
+                                    };
+
+                                    match instr.operation {
+                                        Operation::ResolveSymbol { symbol: _ }
+                                        | Operation::StateStore {
+                                            address: _,
+                                            value: _,
+                                        }
+                                        | Operation::StateLoad {
+                                            address: _,
+                                        }
+                                        | Operation::Literal {
+                                            data: _,
+                                            typename: _,
+                                        }
+                                        | Operation::PhiNode(_) => (), // Literals are handled in the first match statement
+                                        _ => {
+                                            match evm_block.register_stack_name(ssa_name) {
+                                            Err(_) => {
+                                                return Err(CompilerError::internal(format!("Failed to register SSA stack name: {}.", ssa_name)));
+                                            }
+                                            _ => (),
+                                        }
+                                        }
                                     }
                                 }
                             }
+
+                            ret.push(evm_block);
                         }
 
-                        ret.push(evm_block);
+                        Ok(ret)
+                    };
+
+                    // The builder expects the blocks of the function, so errors are kept until it
+                    // returns and a placeholder body is built in their place
+                    match build() {
+                        Ok(blocks) => blocks,
+                        Err(e) => {
+                            *error.borrow_mut() = Some(e);
+                            let mut placeholder = code_builder.new_evm_block("entry");
+                            placeholder.stop();
+                            [placeholder].to_vec()
+                        }
                     }
-
-                    ret
                 });
+
+            if let Some(e) = error.take() {
+                return Err(e);
+            }
+            built?;
+            if let Some(arguments) = external_arguments {
+                self.builder
+                    .set_external_arguments(&function_name, arguments)?;
+            }
+        }
+
+        for primitive in used_primitives.into_inner() {
+            define_recursion_primitive(&mut self.builder, &primitive)?;
         }
 
         Ok(0)
     }
 
-    pub fn build_executable(&mut self) -> Result<EvmExecutable, String> {
+    /// Writes a getter for every field, except maps and fields stored as objects, to the EVM
+    /// module. The getters allow other contracts to read the fields (see `push_remote_field`)
+    /// and are only generated when the ABI is supported.
+    pub fn write_field_getters_to_module(&mut self) -> Result<u32, String> {
+        if !self.builder.create_abi_boilerplate {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+        for field in &self.ir.fields_definitions {
+            let name = &field.variable.name.unresolved;
+            let typename = &field.variable.typename.unresolved;
+            let state = match self.ir.symbol_table.state_layout.get(name) {
+                Some(state) => state.clone(),
+                None => return Err(format!("Unable to find state {}", name)),
+            };
+            if let StorageEncoding::Mapping | StorageEncoding::Bytes = state.encoding {
+                continue;
+            }
+
+            // Values without an ABI representation are returned as the word holding them and
+            // left out of the ABI
+            let (return_type, shift) = match abi_type(&self.builder.context, typename) {
+                Ok(abi_type) => (typename.as_str(), abi_type.abi_shift()),
+                Err(_) => ("Uint256", 0),
+            };
+
+            let error: RefCell<Option<String>> = RefCell::new(None);
+            let built = self
+                .builder
+                .define_function(name, [].to_vec(), return_type)
+                .build(|code_builder| {
+                    let mut entry = code_builder.new_evm_block("entry");
+                    if let Err(e) = push_state_load(&mut entry, &state) {
+                        *error.borrow_mut() = Some(e);
+                    }
+                    if shift > 0 {
+                        entry.push_u32(shift as u32);
+                        entry.shl();
+                    }
+                    entry.push1([0x00].to_vec());
+                    entry.mstore();
+                    entry.push1([0x20].to_vec());
+                    entry.push1([0x00].to_vec());
+                    entry.r#return();
+                    [entry].to_vec()
+                });
+            if let Some(e) = error.take() {
+                return Err(format!("Unable to load field {}: {}", name, e));
+            }
+            built?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    pub fn build_executable(&mut self) -> Result<EvmExecutable, CompilerError> {
         self.build_state_layout()?;
 
         self.write_function_definitions_to_module()?;
+        self.write_field_getters_to_module()?;
+
+        // Reserving the words holding the contract parameters at the end of the code
+        let immutables = self
+            .ir
+            .fields_definitions
+            .iter()
+            .filter(|field| field.immutable)
+            .count();
+        if immutables > 0 {
+            self.builder
+                .ir
+                .data
+                .push(("immutables".to_string(), vec![0; 32 * immutables]));
+        }
+
+        self.builder.finalize_blocks()?;
+        let mut executable = self.builder.build()?;
+        self.write_storage_layout(&mut executable)?;
+        executable.abi = self.build_abi()?;
+        executable.deploy_bytecode = self.build_deploy_bytecode(&executable.bytecode)?;
+        executable.constructor_arguments = self
+            .ir
+            .fields_definitions
+            .iter()
+            .filter(|field| field.immutable)
+            .map(|field| abi_type(&self.builder.context, &field.variable.typename.unresolved))
+            .collect::<Result<Vec<EvmType>, CompilerError>>()?;
+        Ok(executable)
+    }
 
-        self.builder.finalize_blocks();
-        Ok(self.builder.build())
+    /// Estimates the gas used by each transition of `executable`, keyed by its name, as the
+    /// static gas cost of the code generated for it. Loops are counted once and the functions
+    /// called by a transition are not included.
+    pub fn transition_gas_estimates(&self, executable: &EvmExecutable) -> BTreeMap<String, u64> {
+        self.ir
+            .function_definitions
+            .iter()
+            .filter(|func| matches!(func.function_kind, FunctionKind::Transition))
+            .filter_map(|func| {
+                let function_name = func
+                    .name
+                    .qualified_name()
+                    .unwrap_or(func.name.unresolved.clone());
+                executable
+                    .ir
+                    .functions
+                    .iter()
+                    .find(|function| match &function.signature {
+                        Some(signature) => signature.name == function_name,
+                        None => false,
+                    })
+                    .map(|function| (func.name.unresolved.clone(), function.static_gas()))
+            })
+            .collect()
+    }
+
+    /// Describes the constructor taking the contract parameters, the transitions and the field
+    /// getters. Transitions are nonpayable as calls with value attached are rejected.
+    fn build_abi(&self) -> Result<Abi, CompilerError> {
+        let mut abi = Abi::new();
+
+        let mut parameters = Vec::new();
+        for field in self.ir.fields_definitions.iter().filter(|f| f.immutable) {
+            parameters.push(AbiParameter::new(
+                &field.variable.name.unresolved,
+                &abi_type(&self.builder.context, &field.variable.typename.unresolved)?,
+            ));
+        }
+        abi.entries.push(AbiEntry::constructor(parameters));
+
+        for func in &self.ir.function_definitions {
+            if !matches!(
+                func.function_kind,
+                FunctionKind::Transition | FunctionKind::Procedure
+            ) {
+                continue;
+            }
+
+            let mut inputs = Vec::new();
+            for arg in func.arguments.iter() {
+                inputs.push(AbiParameter::new(
+                    &arg.name.unresolved,
+                    &abi_type(&self.builder.context, &arg.typename.unresolved)?,
+                ));
+            }
+            abi.entries.push(AbiEntry::function(
+                &func.name.unresolved,
+                inputs,
+                Vec::new(),
+                StateMutability::Nonpayable,
+            ));
+        }
+
+        if self.builder.create_abi_boilerplate {
+            for field in &self.ir.fields_definitions {
+                let name = &field.variable.name.unresolved;
+                match self.ir.symbol_table.state_layout.get(name) {
+                    Some(state)
+                        if !matches!(
+                            state.encoding,
+                            StorageEncoding::Mapping | StorageEncoding::Bytes
+                        ) => {}
+                    _ => continue,
+                }
+
+                // Getters of values without an ABI representation are only called internally
+                let output =
+                    match abi_type(&self.builder.context, &field.variable.typename.unresolved) {
+                        Ok(abi_type) => AbiParameter::new("", &abi_type),
+                        Err(_) => continue,
+                    };
+                abi.entries.push(AbiEntry::function(
+                    name,
+                    Vec::new(),
+                    [output].to_vec(),
+                    StateMutability::View,
+                ));
+            }
+        }
+
+        Ok(abi)
+    }
+
+    /// Builds the creation code of the contract. It runs the constructor, which checks the
+    /// contract constraint and initialises the fields, and then returns `runtime` with the
+    /// contract parameters, which are appended to the creation code, copied over the words
+    /// reserved for them at the end.
+    fn build_deploy_bytecode(&mut self, runtime: &[u8]) -> Result<Vec<u8>, CompilerError> {
+        let constructor = match self
+            .ir
+            .function_definitions
+            .iter()
+            .find(|func| matches!(func.function_kind, FunctionKind::Constructor))
+        {
+            Some(func) => func
+                .name
+                .qualified_name()
+                .unwrap_or(func.name.unresolved.clone()),
+            None => return Ok(Vec::new()),
+        };
+        let parameters = self
+            .ir
+            .fields_definitions
+            .iter()
+            .filter(|field| field.immutable)
+            .count();
+        let parameters_size = 32 * parameters as u64;
+        let runtime_size = runtime.len() as u64;
+
+        // Contract parameters passed as `bytesN` are left aligned in the ABI
+        let shifts = self
+            .ir
+            .fields_definitions
+            .iter()
+            .filter(|field| field.immutable)
+            .map(|field| {
+                abi_type(&self.builder.context, &field.variable.typename.unresolved)
+                    .map(|abi_type| abi_type.abi_shift())
+            })
+            .collect::<Result<Vec<usize>, CompilerError>>()?;
+
+        // The main function jumps to the first function when the ABI boilerplate is disabled
+        self.builder.reset(false);
+        self.builder
+            .define_function("__deploy__", [].to_vec(), "Uint256")
+            .build(|code_builder| {
+                let mut entry = code_builder.new_evm_block("entry");
+                let mut copy = code_builder.new_evm_block("copy_runtime");
+
+                entry.set_next_rust_position(file!().to_string(), line!() as usize);
+                entry.push_label(&copy.name);
+                entry.jump_to(&constructor);
+
+                // memory[0..runtime_size] = runtime
+                copy.set_next_rust_position(file!().to_string(), line!() as usize);
+                copy.push_u64(runtime_size);
+                copy.push_label("runtime");
+                copy.push1([0x00].to_vec());
+                copy.codecopy();
+
+                if parameters_size > 0 {
+                    copy.push_u64(parameters_size);
+                    copy.push_u64(parameters_size);
+                    copy.codesize();
+                    copy.sub();
+                    copy.push_u64(runtime_size - parameters_size);
+                    copy.codecopy();
+
+                    // memory[offset] = memory[offset] >> shift
+                    for (i, shift) in shifts.iter().enumerate() {
+                        if *shift == 0 {
+                            continue;
+                        }
+                        let offset = runtime_size - parameters_size + 32 * i as u64;
+                        copy.push_u64(offset);
+                        copy.mload();
+                        copy.push_u32(*shift as u32);
+                        copy.shr();
+                        copy.push_u64(offset);
+                        copy.mstore();
+                    }
+                }
+
+                copy.push_u64(runtime_size);
+                copy.push1([0x00].to_vec());
+                copy.r#return();
+
+                [entry, copy].to_vec()
+            })?;
+        self.write_functions_to_module(true)?;
+        self.builder
+            .ir
+            .data
+            .push(("runtime".to_string(), runtime.to_vec()));
+
+        self.builder.finalize_blocks()?;
+        Ok(self.builder.build()?.bytecode)
+    }
+
+    /// Describes the storage allocated for the fields in the manifest of the executable.
+    fn write_storage_layout(&self, executable: &mut EvmExecutable) -> Result<(), String> {
+        for field in &self.ir.fields_definitions {
+            let label = &field.variable.name.unresolved;
+            let typename = &field.variable.typename.unresolved;
+            let state = match self.ir.symbol_table.state_layout.get(label) {
+                Some(state) => state,
+                None => return Err(format!("Unable to find state {}", label)),
+            };
+
+            if state.encoding == StorageEncoding::Immutable {
+                executable.storage_layout.immutables.push(ImmutableEntry {
+                    label: label.clone(),
+                    typename: typename.clone(),
+                    offset: executable.bytecode.len() as u64 - state.address_offset.as_u64(),
+                });
+            } else {
+                executable.storage_layout.storage.push(StorageEntry {
+                    label: label.clone(),
+                    typename: typename.clone(),
+                    slot: state.address_offset,
+                    offset: state.byte_offset,
+                    size: state.size,
+                    encoding: state.encoding,
+                });
+            }
+        }
+
+        Ok(())
     }
 }
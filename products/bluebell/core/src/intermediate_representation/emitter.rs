@@ -1,4 +1,7 @@
-use std::mem;
+use std::{
+    collections::{BTreeSet, HashMap},
+    mem,
+};
 
 use log::info;
 use scilla_parser::{
@@ -9,6 +12,8 @@ use scilla_parser::{
     parser::lexer::SourcePosition,
 };
 
+use crate::constants::{NAMESPACE_SEPARATOR, RECURSION_PRIMITIVES};
+use crate::errors::{CompilerError, CompilerErrorList};
 use crate::intermediate_representation::ast_queue::AstQueue;
 use crate::intermediate_representation::{primitives::*, symbol_table::SymbolTable};
 
@@ -94,6 +99,15 @@ pub struct IrEmitter<'a> {
 
     /// Queue with imported libraries
     ast_queue: &'a mut dyn AstQueue,
+
+    /// Variables in scope together with their type, if it is known.
+    scope: Vec<(String, Option<IrIdentifier>)>,
+
+    /// Maps the aliases of `import X as Y` to the imported library names.
+    import_aliases: HashMap<String, String>,
+
+    /// Errors found so far. Emission continues with the next component after an error.
+    errors: CompilerErrorList,
 }
 
 impl<'a> IrEmitter<'a> {
@@ -126,6 +140,9 @@ impl<'a> IrEmitter<'a> {
             )]
             .to_vec(), // TODO: this should not be necessary
             ast_queue,
+            scope: Vec::new(),
+            import_aliases: HashMap::new(),
+            errors: Vec::new(),
         }
     }
 
@@ -136,6 +153,57 @@ impl<'a> IrEmitter<'a> {
             .clone()
     }
 
+    /// Records `error` at the location of the node being emitted and returns its message, which
+    /// is propagated to the enclosing component.
+    fn report(&mut self, error: CompilerError) -> String {
+        let error = error.at(self.current_location());
+        let message = error.message.clone();
+        self.errors.push(error);
+        message
+    }
+
+    /// Reports the use of a language feature the emitter does not support yet.
+    fn unsupported(&mut self, message: String) -> String {
+        self.report(CompilerError::unsupported(message))
+    }
+
+    /// Records the error which made the emission fail, unless it was reported where it occurred.
+    fn report_propagated(&mut self, message: String) {
+        if self.errors.last().map(|error| &error.message) != Some(&message) {
+            self.report(CompilerError::internal(message));
+        }
+    }
+
+    /// Emits `component`, reporting the error if it cannot be emitted and discarding the state
+    /// it left behind, such that the components following it are still checked.
+    fn emit_component_reporting<N: AstVisitor>(&mut self, component: &N) {
+        let stack = self.stack.len();
+        let source_positions = self.source_positions.len();
+        let current_block = self.current_block.clone();
+        let current_body = self.current_body.clone();
+
+        if let Err(message) = component.visit(self) {
+            self.report_propagated(message);
+            self.stack.truncate(stack);
+            self.source_positions.truncate(source_positions);
+            self.current_block = current_block;
+            self.current_body = current_body;
+            self.scope.clear();
+        }
+    }
+
+    /// Returns the result of the emission unless errors were reported.
+    fn finish<T>(&mut self, result: Result<T, String>) -> Result<T, CompilerErrorList> {
+        match result {
+            Ok(value) if self.errors.is_empty() => Ok(value),
+            Ok(_) => Err(mem::take(&mut self.errors)),
+            Err(message) => {
+                self.report_propagated(message);
+                Err(mem::take(&mut self.errors))
+            }
+        }
+    }
+
     fn push_namespace(&mut self, mut ns: IrIdentifier) {
         // TODO: Update ns to use nested namespaces
         ns.kind = IrIndentifierKind::Namespace;
@@ -308,16 +376,428 @@ impl<'a> IrEmitter<'a> {
         Ok(ret)
     }
 
-    pub fn emit(&mut self, node: &NodeProgram) -> Result<Box<IntermediateRepresentation>, String> {
+    /// Pops the key and value types of a map from the stack and pushes the resulting map type.
+    fn push_map_type(&mut self) -> Result<(), String> {
+        let value = self.pop_ir_identifier()?;
+        let key = self.pop_ir_identifier()?;
+        let typename = self
+            .ir
+            .symbol_table
+            .declare_map_type(&key.unresolved, &value.unresolved)?;
+
+        self.stack.push(StackObject::IrIdentifier(IrIdentifier::new(
+            typename,
+            IrIndentifierKind::TypeLikeName(Vec::new()),
+            self.current_location(),
+        )));
+        Ok(())
+    }
+
+    /// Returns the type of the value produced by `instruction` if it can be deduced without
+    /// type inference.
+    fn static_type_of(&self, instruction: &Instruction) -> Option<IrIdentifier> {
+        match &instruction.operation {
+            Operation::Literal { typename, .. } => Some(typename.clone()),
+            Operation::ResolveSymbol { symbol } => self
+                .scope
+                .iter()
+                .rev()
+                .find(|(name, _)| *name == symbol.unresolved)
+                .and_then(|(_, typename)| typename.clone()),
+            Operation::StateLoad { address } => self
+                .ir
+                .fields_definitions
+                .iter()
+                .find(|field| field.variable.name.unresolved == address.name.unresolved)
+                .map(|field| field.variable.typename.clone()),
+            _ => None,
+        }
+    }
+
+    /// Emits `expression` as the body of an anonymous function with the value argument
+    /// `argument` or the type argument `type_argument` and pushes the closure creation onto
+    /// the stack. The lambda is lifted into a concrete function by `ClosureConversion`.
+    fn emit_lambda(
+        &mut self,
+        argument: Option<VariableDeclaration>,
+        type_argument: Option<String>,
+        expression: &NodeFullExpression,
+    ) -> Result<(), String> {
+        let (prefix, kind) = if type_argument.is_some() {
+            ("template", IrIndentifierKind::TemplateFunctionName)
+        } else {
+            ("lambda", IrIndentifierKind::FunctionName)
+        };
+        let name = self
+            .ir
+            .symbol_table
+            .name_generator
+            .new_lambda_name(prefix, kind);
+
+        let environment = self.scope.clone();
+        if let Some(argument) = &argument {
+            self.scope.push((
+                argument.name.unresolved.clone(),
+                Some(argument.typename.clone()),
+            ));
+        }
+
+        // Emitting the lambda body in place of the current one
+        let mut body = FunctionBody::new();
+        let mut block = FunctionBlock::new("entry".to_string());
+        mem::swap(&mut body, &mut self.current_body);
+        mem::swap(&mut block, &mut self.current_block);
+
+        let _ = expression.visit(self)?;
+        let value = self.pop_instruction()?;
+        let source_location = value.source_location.clone();
+        let value = self.convert_instruction_to_symbol(value);
+        self.current_block
+            .instructions
+            .push_back(Box::new(Instruction {
+                ssa_name: None,
+                result_type: None,
+                operation: Operation::Return(Some(value)),
+                source_location: source_location.clone(),
+            }));
+        self.current_block.terminated = true;
+
+        mem::swap(&mut block, &mut self.current_block);
+        mem::swap(&mut body, &mut self.current_body);
+        body.blocks.push(block);
+
+        if argument.is_some() {
+            self.scope.pop();
+        }
+
+        self.ir.lambda_functions.push(LambdaFunctionSingleArgument {
+            name: name.clone(),
+            namespace: self.current_namespace.clone(),
+            capture: Box::new(Tuple::new()),
+            environment,
+            argument,
+            type_argument,
+            return_type: None,
+            body,
+        });
+
+        let mut function = name;
+        function.is_definition = false;
+        self.stack
+            .push(StackObject::Instruction(Box::new(Instruction {
+                ssa_name: None,
+                result_type: None,
+                operation: Operation::CreateClosure {
+                    function,
+                    captures: Vec::new(),
+                },
+                source_location,
+            })));
+
+        Ok(())
+    }
+
+    /// Emits the key expressions of a map access and returns the symbols holding the keys.
+    fn emit_map_keys(
+        &mut self,
+        keys: &[WithMetaData<NodeMapAccess>],
+    ) -> Result<Vec<IrIdentifier>, String> {
+        let mut ret: Vec<IrIdentifier> = Vec::new();
+        for key in keys.iter() {
+            let _ = key.visit(self)?;
+            let instruction = self.pop_instruction()?;
+            let symbol = self.convert_instruction_to_symbol(instruction);
+            ret.push(symbol);
+        }
+        Ok(ret)
+    }
+
+    /// Terminates the current block with `operation` and continues in a new block `label`.
+    fn terminate_block(&mut self, operation: Operation, label: IrIdentifier) {
+        self.current_block
+            .instructions
+            .push_back(Box::new(Instruction {
+                ssa_name: None,
+                result_type: None,
+                operation,
+                source_location: self.current_location(),
+            }));
+        self.current_block.terminated = true;
+
+        let mut next_block = FunctionBlock::new_from_symbol(label);
+        mem::swap(&mut next_block, &mut self.current_block);
+        self.current_body.blocks.push(next_block);
+    }
+
+    /// Returns the qualified name and the number of arguments of the constructor `name`
+    /// together with the other constructors of the same type.
+    fn constructor_siblings(
+        &self,
+        name: &str,
+    ) -> Result<(String, usize, Vec<(String, usize)>), CompilerError> {
+        let symbol_table = &self.ir.symbol_table;
+        // Constructors sharing their name with their type, such as `Pair`, are not aliased
+        let candidates = [
+            symbol_table.aliases.get(name).cloned(),
+            Some(format!("{}{}{}", name, NAMESPACE_SEPARATOR, name)),
+            Some(name.to_string()),
+        ];
+        let typeinfo = match candidates
+            .iter()
+            .flatten()
+            .filter_map(|candidate| symbol_table.type_of_table.get(candidate))
+            .find(|typeinfo| typeinfo.is_constructor())
+        {
+            Some(typeinfo) => typeinfo,
+            // User defined types are only collected after the IR has been emitted
+            None => {
+                return Err(CompilerError::unsupported(format!(
+                    "Patterns of constructor {} with arguments are not supported",
+                    name
+                )))
+            }
+        };
+
+        // Constructors are listed under their name as well as their signature
+        let siblings: BTreeSet<(String, usize)> = symbol_table
+            .type_of_table
+            .values()
+            .filter(|other| {
+                other.is_constructor()
+                    && other.return_type == typeinfo.return_type
+                    && other.symbol_name != typeinfo.symbol_name
+            })
+            .map(|other| (other.symbol_name.clone(), other.arguments.len()))
+            .collect();
+
+        Ok((
+            typeinfo.symbol_name.clone(),
+            typeinfo.arguments.len(),
+            siblings.into_iter().collect(),
+        ))
+    }
+
+    /// Emits a test of `value` against the nullary constructor `constructor` which continues
+    /// in a new block if the test succeeds and jumps to `on_failure` otherwise.
+    fn emit_constructor_test(
+        &mut self,
+        value: &IrIdentifier,
+        constructor: IrIdentifier,
+        on_failure: &IrIdentifier,
+    ) {
+        let source_location = constructor.source_location.clone();
+        let condition = self.convert_instruction_to_symbol(Box::new(Instruction {
+            ssa_name: None,
+            result_type: None,
+            operation: Operation::IsEqual {
+                left: value.clone(),
+                right: constructor,
+            },
+            source_location,
+        }));
+
+        let matched = self
+            .ir
+            .symbol_table
+            .name_generator
+            .new_block_label("pattern_matched");
+        self.terminate_block(
+            Operation::ConditionalJump {
+                expression: condition,
+                on_success: matched.clone(),
+                on_failure: on_failure.clone(),
+            },
+            matched,
+        );
+    }
+
+    /// Binds `value` to the variable `name` in the current block.
+    fn emit_binder(&mut self, value: &IrIdentifier, name: &str) {
+        self.current_block
+            .instructions
+            .push_back(Box::new(Instruction {
+                ssa_name: Some(IrIdentifier::new(
+                    name.to_string(),
+                    IrIndentifierKind::VirtualRegister,
+                    self.current_location(),
+                )),
+                result_type: None,
+                operation: Operation::ResolveSymbol {
+                    symbol: value.clone(),
+                },
+                source_location: self.current_location(),
+            }));
+        self.scope.push((name.to_string(), None));
+    }
+
+    /// Emits the test of `value` against `pattern`. Control continues in the current block if
+    /// the value matches and jumps to `on_failure` otherwise. Returns the names bound by the
+    /// pattern, which are pushed onto the scope, and whether the pattern can fail.
+    fn emit_pattern_test(
+        &mut self,
+        value: &IrIdentifier,
+        pattern: &NodePattern,
+        on_failure: &IrIdentifier,
+    ) -> Result<(Vec<String>, bool), String> {
+        let (name, arguments) = match pattern {
+            NodePattern::Wildcard => return Ok((Vec::new(), false)),
+            NodePattern::Binder(name) => {
+                self.emit_binder(value, &name.node);
+                return Ok(([name.node.clone()].to_vec(), false));
+            }
+            NodePattern::Constructor(name, arguments) => (name, arguments),
+        };
+
+        let _ = name.visit(self)?;
+        let constructor = self.pop_ir_identifier()?;
+        if arguments.is_empty() {
+            self.emit_constructor_test(value, constructor, on_failure);
+            return Ok((Vec::new(), true));
+        }
+
+        let (qualified_name, arity, siblings) =
+            match self.constructor_siblings(&constructor.unresolved) {
+                Ok(siblings) => siblings,
+                Err(error) => return Err(self.report(error)),
+            };
+        if arity != arguments.len() {
+            return Err(format!(
+                "Constructor {} takes {} argument(s), but the pattern has {}",
+                constructor.unresolved,
+                arity,
+                arguments.len()
+            ));
+        }
+
+        // Values are distinguished from the only other constructor, which does not carry data
+        let mut refutable = false;
+        match siblings.as_slice() {
+            [] => (),
+            [(sibling, 0)] => {
+                let sibling = IrIdentifier::new(
+                    sibling.clone(),
+                    IrIndentifierKind::TypeLikeName(Vec::new()),
+                    constructor.source_location.clone(),
+                );
+                let source_location = constructor.source_location.clone();
+                let is_sibling = self.convert_instruction_to_symbol(Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::IsEqual {
+                        left: value.clone(),
+                        right: sibling,
+                    },
+                    source_location,
+                }));
+                let matched = self
+                    .ir
+                    .symbol_table
+                    .name_generator
+                    .new_block_label("pattern_matched");
+                self.terminate_block(
+                    Operation::ConditionalJump {
+                        expression: is_sibling,
+                        on_success: on_failure.clone(),
+                        on_failure: matched.clone(),
+                    },
+                    matched,
+                );
+                refutable = true;
+            }
+            _ => {
+                return Err(self.unsupported(format!(
+                    "Patterns of constructor {} with arguments are not supported",
+                    qualified_name
+                )))
+            }
+        }
+
+        let mut bound = Vec::new();
+        for (index, argument) in arguments.iter().enumerate() {
+            if let NodeArgumentPattern::WildcardArgument = &argument.node {
+                continue;
+            }
+
+            let field = self.ir.symbol_table.name_generator.new_intermediate();
+            self.current_block
+                .instructions
+                .push_back(Box::new(Instruction {
+                    ssa_name: Some(field.clone()),
+                    result_type: None,
+                    operation: Operation::LoadConstructorArgument {
+                        value: value.clone(),
+                        constructor: constructor.clone(),
+                        index,
+                    },
+                    source_location: self.current_location(),
+                }));
+
+            match &argument.node {
+                NodeArgumentPattern::WildcardArgument => (),
+                NodeArgumentPattern::BinderArgument(name) => {
+                    self.emit_binder(&field, &name.node);
+                    bound.push(name.node.clone());
+                }
+                NodeArgumentPattern::ConstructorArgument(name) => {
+                    let _ = name.visit(self)?;
+                    let constructor = self.pop_ir_identifier()?;
+                    self.emit_constructor_test(&field, constructor, on_failure);
+                    refutable = true;
+                }
+                NodeArgumentPattern::PatternArgument(pattern) => {
+                    let (names, can_fail) =
+                        self.emit_pattern_test(&field, &pattern.node, on_failure)?;
+                    bound.extend(names);
+                    refutable |= can_fail;
+                }
+            }
+        }
+
+        Ok((bound, refutable))
+    }
+
+    /// Removes the names bound by a pattern from the scope.
+    fn pop_binders(&mut self, bound: &[String]) {
+        for _ in bound.iter() {
+            self.scope.pop();
+        }
+    }
+
+    fn state_field_address(&self, name: String) -> FieldAddress {
+        FieldAddress {
+            name: IrIdentifier {
+                unresolved: name,
+                resolved: None,
+                type_reference: None,
+                kind: IrIndentifierKind::State,
+                is_definition: false,
+                source_location: self.current_location(),
+            },
+            value: None,
+        }
+    }
+
+    fn plain_variable_name(node: &NodeVariableIdentifier) -> Result<String, String> {
+        match node {
+            NodeVariableIdentifier::VariableName(name)
+            | NodeVariableIdentifier::SpecialIdentifier(name) => Ok(name.to_string()),
+            NodeVariableIdentifier::VariableInNamespace(_, name) => {
+                Err(format!("Expected plain variable name, but found {}", name))
+            }
+        }
+    }
+
+    pub fn emit(
+        &mut self,
+        node: &NodeProgram,
+    ) -> Result<Box<IntermediateRepresentation>, CompilerErrorList> {
         // Copying original symbol table to create a new instance of the IR at the end
         // of traversing
         let symbol_table = self.ir.symbol_table.clone();
 
+        // Errors such as unresolvable imports are reported to the caller
         let result = node.visit(self);
-        match result {
-            Err(m) => panic!("{}", m),
-            _ => (),
-        }
+        self.finish(result)?;
 
         // Creating type table
 
@@ -329,6 +809,74 @@ impl<'a> IrEmitter<'a> {
 
         Ok(ret)
     }
+
+    /// Emits the type definitions of an imported library and enqueues the libraries it imports.
+    pub fn emit_library_types(
+        &mut self,
+        node: &NodeProgram,
+    ) -> Result<Box<IntermediateRepresentation>, CompilerErrorList> {
+        let result = match &node.import_declarations {
+            Some(imports) => imports.visit(self).map(|_| ()),
+            None => Ok(()),
+        };
+        self.finish(result)?;
+        let result = self.emit_library(node, None);
+        self.finish(result)
+    }
+
+    /// Emits the value definitions of an imported library which are referred to by `requested`,
+    /// either by their plain name or qualified by the library name. Only definitions which are
+    /// used are emitted, such that importing a library does not require all of it to compile.
+    pub fn emit_library_values(
+        &mut self,
+        node: &NodeProgram,
+        requested: &BTreeSet<String>,
+    ) -> Result<Box<IntermediateRepresentation>, CompilerErrorList> {
+        let result = self.emit_library(node, Some(requested));
+        self.finish(result)
+    }
+
+    fn emit_library(
+        &mut self,
+        node: &NodeProgram,
+        requested: Option<&BTreeSet<String>>,
+    ) -> Result<Box<IntermediateRepresentation>, String> {
+        let symbol_table = self.ir.symbol_table.clone();
+
+        if let Some(library) = &node.library_definition {
+            let _ = library.node.name.visit(self)?;
+            let ns = self.pop_ir_identifier()?;
+            self.push_namespace(ns.clone());
+
+            for def in library.node.definitions.iter() {
+                let selected = match (&def.node, requested) {
+                    (NodeLibrarySingleDefinition::TypeDefinition(..), None) => true,
+                    (
+                        NodeLibrarySingleDefinition::LetDefinition { variable_name, .. },
+                        Some(requested),
+                    ) => {
+                        requested.contains(&variable_name.node)
+                            || requested.contains(&format!(
+                                "{}{}{}",
+                                ns.unresolved, NAMESPACE_SEPARATOR, variable_name.node
+                            ))
+                    }
+                    _ => false,
+                };
+
+                if selected {
+                    let _ = def.visit(self)?;
+                }
+            }
+
+            self.pop_namespace();
+        }
+
+        let mut ret = Box::new(IntermediateRepresentation::new(symbol_table));
+        mem::swap(&mut self.ir, &mut ret);
+
+        Ok(ret)
+    }
 }
 
 impl<'a> AstConverting for IrEmitter<'a> {
@@ -371,7 +919,7 @@ impl<'a> AstConverting for IrEmitter<'a> {
                         "Event".to_string(),
                     )));
                     */
-                    unimplemented!()
+                    return Err(self.unsupported("Event types are not supported".to_string()));
                 }
                 NodeTypeNameIdentifier::TypeOrEnumLikeIdentifier(name) => {
                     let symbol = IrIdentifier::new(
@@ -406,6 +954,8 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 let alias = self.pop_ir_identifier()?;
                 self.ast_queue
                     .enqueue_with_alias(&identifier.unresolved, &alias.unresolved)?;
+                self.import_aliases
+                    .insert(alias.unresolved, identifier.unresolved);
             }
         }
         Ok(TraversalResult::SkipChildren)
@@ -463,8 +1013,28 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 });
                 self.stack.push(StackObject::Instruction(instr));
             }
-            NodeVariableIdentifier::VariableInNamespace(_namespace, _identifier) => {
-                unimplemented!()
+            NodeVariableIdentifier::VariableInNamespace(namespace, identifier) => {
+                namespace.node.visit(self)?;
+                let namespace = self.pop_ir_identifier()?;
+                let library = match self.import_aliases.get(&namespace.unresolved) {
+                    Some(library) => library.clone(),
+                    None => namespace.unresolved,
+                };
+
+                let operation = Operation::ResolveSymbol {
+                    symbol: IrIdentifier::new(
+                        format!("{}{}{}", library, NAMESPACE_SEPARATOR, identifier),
+                        IrIndentifierKind::VirtualRegister,
+                        self.current_location(),
+                    ),
+                };
+                let instr = Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation,
+                    source_location: self.current_location(),
+                });
+                self.stack.push(StackObject::Instruction(instr));
             }
         }
         Ok(TraversalResult::SkipChildren)
@@ -474,21 +1044,52 @@ impl<'a> AstConverting for IrEmitter<'a> {
         _mode: TreeTraversalMode,
         _node: &NodeBuiltinArguments,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Builtin arguments are not supported".to_string()))
     }
     fn emit_type_map_key(
         &mut self,
         _mode: TreeTraversalMode,
-        _node: &NodeTypeMapKey,
+        node: &NodeTypeMapKey,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        match node {
+            NodeTypeMapKey::GenericMapKey(key) | NodeTypeMapKey::EnclosedGenericId(key) => {
+                let _ = key.visit(self)?;
+            }
+            NodeTypeMapKey::EnclosedAddressMapKeyType(_) | NodeTypeMapKey::AddressMapKeyType(_) => {
+                // Addresses are keyed by their 20 byte representation
+                self.stack.push(StackObject::IrIdentifier(IrIdentifier::new(
+                    "ByStr20".to_string(),
+                    IrIndentifierKind::TypeLikeName(Vec::new()),
+                    self.current_location(),
+                )));
+            }
+        }
+        Ok(TraversalResult::SkipChildren)
     }
     fn emit_type_map_value(
         &mut self,
         _mode: TreeTraversalMode,
-        _node: &NodeTypeMapValue,
+        node: &NodeTypeMapValue,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        match node {
+            NodeTypeMapValue::MapValueTypeOrEnumLikeIdentifier(value) => {
+                let _ = value.visit(self)?;
+            }
+            NodeTypeMapValue::MapKeyValue(entry) => {
+                let _ = (*entry).visit(self)?;
+            }
+            NodeTypeMapValue::MapValueParenthesizedType(value) => {
+                let _ = (*value).visit(self)?;
+            }
+            NodeTypeMapValue::MapValueAddressType(_) => {
+                self.stack.push(StackObject::IrIdentifier(IrIdentifier::new(
+                    "ByStr20".to_string(),
+                    IrIndentifierKind::TypeLikeName(Vec::new()),
+                    self.current_location(),
+                )));
+            }
+        }
+        Ok(TraversalResult::SkipChildren)
     }
     fn emit_type_argument(
         &mut self,
@@ -496,20 +1097,30 @@ impl<'a> AstConverting for IrEmitter<'a> {
         node: &NodeTypeArgument,
     ) -> Result<TraversalResult, String> {
         match node {
-            NodeTypeArgument::EnclosedTypeArgument(_) => {
-                unimplemented!();
+            NodeTypeArgument::EnclosedTypeArgument(t) => {
+                let _ = (*t).visit(self)?;
             }
             NodeTypeArgument::GenericTypeArgument(n) => {
                 let _ = n.visit(self)?;
             }
-            NodeTypeArgument::TemplateTypeArgument(_) => {
-                unimplemented!();
+            NodeTypeArgument::TemplateTypeArgument(name) => {
+                self.stack.push(StackObject::IrIdentifier(IrIdentifier::new(
+                    name.to_string(),
+                    IrIndentifierKind::TypeLikeName(Vec::new()),
+                    self.current_location(),
+                )));
             }
             NodeTypeArgument::AddressTypeArgument(_) => {
-                unimplemented!();
+                self.stack.push(StackObject::IrIdentifier(IrIdentifier::new(
+                    "ByStr20".to_string(),
+                    IrIndentifierKind::TypeLikeName(Vec::new()),
+                    self.current_location(),
+                )));
             }
-            NodeTypeArgument::MapTypeArgument(_, _) => {
-                unimplemented!();
+            NodeTypeArgument::MapTypeArgument(key, value) => {
+                let _ = key.visit(self)?;
+                let _ = value.visit(self)?;
+                self.push_map_type()?;
             }
         }
         Ok(TraversalResult::SkipChildren)
@@ -549,20 +1160,35 @@ impl<'a> AstConverting for IrEmitter<'a> {
             NodeScillaType::MapType(key, value) => {
                 let _ = key.visit(self)?;
                 let _ = value.visit(self)?;
-                // TODO: Pop the two and create type Map<X,Y>
-                unimplemented!()
+                self.push_map_type()?;
             }
             NodeScillaType::FunctionType(a, b) => {
                 let _ = (*a).visit(self)?;
                 let _ = (*b).visit(self)?;
-                // TODO: Implement the function type
-                unimplemented!()
+                let return_type = self.pop_ir_identifier()?;
+                let argument = self.pop_ir_identifier()?;
+                let typename = self
+                    .ir
+                    .symbol_table
+                    .declare_closure_type(&argument.unresolved, &return_type.unresolved)?;
+
+                self.stack.push(StackObject::IrIdentifier(IrIdentifier::new(
+                    typename,
+                    IrIndentifierKind::TypeLikeName(Vec::new()),
+                    self.current_location(),
+                )));
             }
 
-            NodeScillaType::PolyFunctionType(_name, a) => {
-                // TODO: What to do with name
+            NodeScillaType::PolyFunctionType(name, a) => {
                 let _ = (*a).visit(self)?;
-                unimplemented!()
+                let body = self.pop_ir_identifier()?;
+                let typename = format!("forall {}. {}", name, body.unresolved);
+
+                self.stack.push(StackObject::IrIdentifier(IrIdentifier::new(
+                    typename,
+                    IrIndentifierKind::TypeLikeName(Vec::new()),
+                    self.current_location(),
+                )));
             }
             NodeScillaType::EnclosedType(a) => {
                 let _ = (*a).visit(self)?;
@@ -570,14 +1196,12 @@ impl<'a> AstConverting for IrEmitter<'a> {
             NodeScillaType::ScillaAddresseType(a) => {
                 let _ = (*a).visit(self)?;
             }
-            NodeScillaType::TypeVarType(_name) => {
-                /*
-                self.stack
-                    .push(StackObject::Identifier(Identifier::TypeName(
-                        name.to_string(),
-                    )));
-                    */
-                unimplemented!()
+            NodeScillaType::TypeVarType(name) => {
+                self.stack.push(StackObject::IrIdentifier(IrIdentifier::new(
+                    name.to_string(),
+                    IrIndentifierKind::TypeLikeName(Vec::new()),
+                    self.current_location(),
+                )));
             }
         };
         Ok(TraversalResult::SkipChildren)
@@ -586,23 +1210,26 @@ impl<'a> AstConverting for IrEmitter<'a> {
     fn emit_type_map_entry(
         &mut self,
         _mode: TreeTraversalMode,
-        _node: &NodeTypeMapEntry,
+        node: &NodeTypeMapEntry,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        let _ = node.key.visit(self)?;
+        let _ = node.value.visit(self)?;
+        self.push_map_type()?;
+        Ok(TraversalResult::SkipChildren)
     }
     fn emit_address_type_field(
         &mut self,
         _mode: TreeTraversalMode,
         _node: &NodeAddressTypeField,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Address type fields are not supported".to_string()))
     }
     fn emit_address_type(
         &mut self,
         _mode: TreeTraversalMode,
         _node: &NodeAddressType,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Address types are not supported".to_string()))
     }
 
     fn emit_full_expression(
@@ -612,31 +1239,99 @@ impl<'a> AstConverting for IrEmitter<'a> {
     ) -> Result<TraversalResult, String> {
         match node {
             NodeFullExpression::LocalVariableDeclaration {
-                identifier_name: _,
+                identifier_name,
                 expression,
-                type_annotation: _,
+                type_annotation,
                 containing_expression,
             } => {
                 expression.visit(self)?;
+                let mut value = self.pop_instruction()?;
+
+                let typename = match type_annotation {
+                    Some(t) => {
+                        t.visit(self)?;
+                        Some(self.pop_ir_identifier()?)
+                    }
+                    None => self.static_type_of(&value),
+                };
+
+                // The value is bound directly, rather than through convert_instruction_to_symbol,
+                // such that aliases of other variables are preserved.
+                let name = identifier_name.to_string();
+                value.ssa_name = Some(IrIdentifier {
+                    unresolved: name.clone(),
+                    resolved: None,
+                    type_reference: None,
+                    kind: IrIndentifierKind::VirtualRegister,
+                    is_definition: false,
+                    source_location: self.current_location(),
+                });
+                self.current_block.instructions.push_back(value);
+
+                self.scope.push((name, typename));
                 containing_expression.visit(self)?;
-                unimplemented!();
+                self.scope.pop();
             }
             NodeFullExpression::FunctionDeclaration {
-                identier_value: _, // TODO: Missing spelling - global replacement
+                identier_value, // TODO: Missing spelling - global replacement
                 type_annotation,
                 expression,
             } => {
-                // identier_value.visit(self)?;
                 type_annotation.visit(self)?;
-                expression.visit(self)?;
+                let mut typename = self.pop_ir_identifier()?;
+                typename.kind = match &typename.kind {
+                    IrIndentifierKind::TypeLikeName(args) if args.len() > 0 => {
+                        IrIndentifierKind::TemplateTypeName(args.clone())
+                    }
+                    _ => IrIndentifierKind::TypeName,
+                };
 
-                unimplemented!();
+                let argument =
+                    VariableDeclaration::new(identier_value.to_string(), false, typename);
+                self.emit_lambda(Some(argument), None, &expression.node)?;
             }
             NodeFullExpression::FunctionCall {
-                function_name: _,
-                argument_list: _,
+                function_name,
+                argument_list,
             } => {
-                unimplemented!();
+                function_name.visit(self)?;
+                let function = self.pop_instruction()?;
+                let mut closure = self.convert_instruction_to_symbol(function);
+
+                let mut arguments: Vec<IrIdentifier> = Vec::new();
+                for arg in argument_list.iter() {
+                    arg.visit(self)?;
+                    let arg = self.pop_instruction()?;
+                    arguments.push(self.convert_instruction_to_symbol(arg));
+                }
+
+                let last_argument = match arguments.pop() {
+                    Some(a) => a,
+                    None => return Err("Function call without arguments.".to_string()),
+                };
+
+                // Functions are curried, so `f a b` is applied as `(f a) b`. This also gives
+                // partial application for free.
+                for argument in arguments {
+                    let partial_application = Box::new(Instruction {
+                        ssa_name: None,
+                        result_type: None,
+                        operation: Operation::ApplyClosure { closure, argument },
+                        source_location: self.current_location(),
+                    });
+                    closure = self.convert_instruction_to_symbol(partial_application);
+                }
+
+                self.stack
+                    .push(StackObject::Instruction(Box::new(Instruction {
+                        ssa_name: None,
+                        result_type: None,
+                        operation: Operation::ApplyClosure {
+                            closure,
+                            argument: last_argument,
+                        },
+                        source_location: self.current_location(),
+                    })));
             }
             NodeFullExpression::ExpressionAtomic(expr) => match &(**expr).node {
                 NodeAtomicExpression::AtomicSid(identifier) => {
@@ -648,7 +1343,8 @@ impl<'a> AstConverting for IrEmitter<'a> {
             },
             NodeFullExpression::ExpressionBuiltin { b, targs, xs } => {
                 if let Some(_targs) = targs {
-                    unimplemented!();
+                    return Err(self
+                        .unsupported("Type arguments of builtins are not supported".to_string()));
                 }
 
                 let mut arguments: Vec<IrIdentifier> = [].to_vec();
@@ -682,160 +1378,118 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 self.stack.push(StackObject::Instruction(instr));
             }
             NodeFullExpression::Message(entries) => {
-                println!("{:#?}", entries);
-                unimplemented!();
-            }
-            NodeFullExpression::Match {
-                match_expression: _,
-                clauses: _,
-            } => {
-                unimplemented!();
-            } /* TODO: {
-
-            info!("Match statement");
-            let _ = match_expression.visit(self)?;
-            let expression = self.pop_instruction()?;
-            let source_location = expression.source_location.clone();
-
-            let main_expression_symbol = self.convert_instruction_to_symbol(expression);
-
-            let finally_exit_label = self
-            .ir
-            .symbol_table
-            .name_generator
-            .new_block_label("match_finally");
-
-            let mut phi_results: Vec<IrIdentifier> = Vec::new();
-
-            for clause in clauses.iter() {
-            info!("Next clause");
-            let fail_label = self
-            .ir
-            .symbol_table
-            .name_generator
-            .new_block_label("match_fail");
-            todo!("Catch all is untested."); //
-
-            match &clause.node.pattern.node {
-            NodePattern::Wildcard => {
-            info!("Dealing with wildcard");
-            // Doing nothing as we will just write the instructions to the current block
-            }
-            NodePattern::Binder(_) => {
-            unimplemented!()
-            }
-            NodePattern::Constructor(name, args) => {
-            info!("Setting {} up", name);
-            clause.node.pattern.visit(self)?;
-
-            // Creating compare instruction
-            // TODO: Pop instruction or symbol
-            let expected_value = self.pop_ir_identifier()?;
-            assert!(match &expected_value.kind {
-
-            IrIndentifierKind::TypeLikeName(args) => args.len() == 0,
-            _ => false,
-
-            });
-
-            let source_location = expected_value.source_location.clone();
-
-            let compare_instr = Box::new(Instruction {
-            ssa_name: None,
-            result_type: None,
-            operation: Operation::IsEqual {
-            left: main_expression_symbol.clone(),
-            right: expected_value,
-            },
-            source_location: source_location.clone(),
-            });
-            let case = self.convert_instruction_to_symbol(compare_instr);
-
-            // Blocks for success
-
-            let success_label = self
-            .ir
-            .symbol_table
-            .name_generator
-            .new_block_label("match_success");
-            let mut success_block =
-            FunctionBlock::new_from_symbol(success_label.clone());
-
-            // Terminating current block
-            let op = Operation::ConditionalJump {
-            expression: case,
-            on_success: success_label,
-            on_failure: fail_label.clone(),
-            };
-            self.current_block
-            .instructions
-            .push_back(Box::new(Instruction {
-            ssa_name: None,
-            result_type: None,
-            operation: op,
-            source_location,
-            }));
-
-            // Finishing current_block and moving it onto
-            // to the current body while preparing the success block
-            // as current
-            mem::swap(&mut success_block, &mut self.current_block);
-            self.current_body.blocks.push(success_block);
-            }
-            }
-
-            let _ = clause.node.expression.visit(self)?;
-            let expr_instr = self.pop_instruction()?;
-            let source_location = expr_instr.source_location.clone();
-
-            let result_sym = self.convert_instruction_to_symbol(expr_instr);
-            phi_results.push(result_sym);
-
-            let exit_instruction = Box::new(Instruction {
-            ssa_name: None,
-            result_type: None,
-            operation: Operation::Jump(finally_exit_label.clone()),
-            source_location,
-            });
-            self.current_block.instructions.push_back(exit_instruction);
-            self.current_block.terminated = true;
-            // Pushing sucess block and creating fail block
+                let mut message_entries = Vec::new();
+                for entry in entries {
+                    let field = match &entry.node {
+                        NodeMessageEntry::MessageLiteral(field, value) => {
+                            let _ = value.visit(self)?;
+                            field
+                        }
+                        NodeMessageEntry::MessageVariable(field, value) => {
+                            let _ = value.visit(self)?;
+                            field
+                        }
+                    };
 
-            let mut fail_block = FunctionBlock::new_from_symbol(fail_label.clone());
-            mem::swap(&mut fail_block, &mut self.current_block);
-            self.current_body.blocks.push(fail_block);
+                    let value = self.pop_instruction()?;
+                    let value = self.convert_instruction_to_symbol(value);
+                    message_entries.push((Self::plain_variable_name(&field.node)?, value));
+                }
 
-            // let fail_label = self.ir.symbol_table.name_generator.new_block_label("match_case");
-            // let fail_block = FunctionBlock::new_from_symbol(fail_label);
+                let instr = Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::ConstructMessage {
+                        entries: message_entries,
+                    },
+                    source_location: self.current_location(),
+                });
+                self.stack.push(StackObject::Instruction(instr));
             }
+            NodeFullExpression::Match {
+                match_expression,
+                clauses,
+            } => {
+                let _ = match_expression.visit(self)?;
+                let expression = self.pop_instruction()?;
+                let source_location = expression.source_location.clone();
+                let value = self.convert_instruction_to_symbol(expression);
 
-            // TODO: Catch all if needed
-
-            // Exiting
-            let exit_instruction = Box::new(Instruction {
-            ssa_name: None,
-            result_type: None,
-            operation: Operation::Jump(finally_exit_label.clone()),
-            source_location: source_location.clone(),
-            });
-            self.current_block.instructions.push_back(exit_instruction);
+                let match_exit = self
+                    .ir
+                    .symbol_table
+                    .name_generator
+                    .new_block_label("match_exit");
+                let match_fail = self
+                    .ir
+                    .symbol_table
+                    .name_generator
+                    .new_block_label("match_fail");
+                let conditions: Vec<IrIdentifier> = (1..clauses.len())
+                    .map(|i| {
+                        self.ir
+                            .symbol_table
+                            .name_generator
+                            .new_block_label(&format!("clause_{}_condition", i))
+                    })
+                    .collect();
+
+                // Each clause passes its value to the exit block which selects it with a phi node
+                let mut clause_values: Vec<IrIdentifier> = Vec::new();
+                let mut exhaustive = false;
+                for (i, clause) in clauses.iter().enumerate() {
+                    let on_failure = conditions.get(i).unwrap_or(&match_fail).clone();
+                    let (bound, refutable) =
+                        self.emit_pattern_test(&value, &clause.node.pattern.node, &on_failure)?;
+
+                    let _ = clause.node.expression.visit(self)?;
+                    let result = self.pop_instruction()?;
+                    let result = self.convert_instruction_to_symbol(result);
+                    let clause_value = self.ir.symbol_table.name_generator.new_intermediate();
+                    self.current_block
+                        .instructions
+                        .push_back(Box::new(Instruction {
+                            ssa_name: Some(clause_value.clone()),
+                            result_type: None,
+                            operation: Operation::ResolveSymbol { symbol: result },
+                            source_location: source_location.clone(),
+                        }));
+                    clause_values.push(clause_value);
+                    self.pop_binders(&bound);
+
+                    // Clauses following one which cannot fail are never reached
+                    if !refutable {
+                        exhaustive = true;
+                        self.terminate_block(
+                            Operation::Jump(match_exit.clone()),
+                            match_exit.clone(),
+                        );
+                        break;
+                    }
+                    self.terminate_block(Operation::Jump(match_exit.clone()), on_failure);
+                }
 
-            // Attaching exit block
-            let mut finally_exit_block =
-            FunctionBlock::new_from_symbol(finally_exit_label.clone());
-            mem::swap(&mut finally_exit_block, &mut self.current_block);
-            self.current_body.blocks.push(finally_exit_block);
+                if !exhaustive {
+                    self.terminate_block(Operation::Revert(None), match_exit.clone());
+                }
 
-            self.stack
-            .push(StackObject::Instruction(Box::new(Instruction {
-            ssa_name: None,
-            result_type: None,
-            operation: Operation::PhiNode(phi_results),
-            source_location: source_location.clone(),
-            })));
-            // unimplemented!();
+                let result = self.ir.symbol_table.name_generator.new_intermediate();
+                self.current_block
+                    .instructions
+                    .push_back(Box::new(Instruction {
+                        ssa_name: Some(result.clone()),
+                        result_type: None,
+                        operation: Operation::PhiNode(clause_values),
+                        source_location: source_location.clone(),
+                    }));
+                self.stack
+                    .push(StackObject::Instruction(Box::new(Instruction {
+                        ssa_name: None,
+                        result_type: None,
+                        operation: Operation::ResolveSymbol { symbol: result },
+                        source_location,
+                    })));
             }
-             */
             NodeFullExpression::ConstructorCall {
                 identifier_name,
                 contract_type_arguments,
@@ -862,12 +1516,13 @@ impl<'a> AstConverting for IrEmitter<'a> {
                         template_type_arguments.push(arg);
                     }
                 }
-                if argument_list.len() > 0 {
-                    unimplemented!()
+                let mut arguments: Vec<IrIdentifier> = Vec::new();
+                for arg in argument_list.iter() {
+                    arg.visit(self)?;
+                    let arg = self.pop_instruction()?;
+                    arguments.push(self.convert_instruction_to_symbol(arg));
                 }
 
-                let arguments: Vec<IrIdentifier> = [].to_vec();
-
                 let operation = Operation::CallStaticFunction {
                     name,
                     owner: None, // We cannot deduce the type from the AST
@@ -885,16 +1540,58 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 self.stack.push(StackObject::Instruction(instr));
             }
             NodeFullExpression::TemplateFunction {
-                identifier_name: _,
-                expression: _,
+                identifier_name,
+                expression,
             } => {
-                unimplemented!();
+                self.emit_lambda(None, Some(identifier_name.to_string()), &expression.node)?;
             }
             NodeFullExpression::TApp {
-                identifier_name: _,
-                type_arguments: _,
+                identifier_name,
+                type_arguments,
             } => {
-                unimplemented!();
+                identifier_name.visit(self)?;
+                let template = self.pop_instruction()?;
+
+                let mut arguments: Vec<IrIdentifier> = Vec::new();
+                for arg in type_arguments.iter() {
+                    arg.visit(self)?;
+                    let mut typename = self.pop_ir_identifier()?;
+                    typename.kind = IrIndentifierKind::TypeName;
+                    arguments.push(typename);
+                }
+
+                // The recursion primitives are provided by the code generator
+                let primitive = match &template.operation {
+                    Operation::ResolveSymbol { symbol }
+                        if RECURSION_PRIMITIVES
+                            .iter()
+                            .any(|(name, _)| *name == symbol.unresolved) =>
+                    {
+                        let mut primitive = symbol.clone();
+                        primitive.kind = IrIndentifierKind::FunctionName;
+                        Some(primitive)
+                    }
+                    _ => None,
+                };
+
+                let operation = match primitive {
+                    Some(primitive) => Operation::InstantiatePrimitive {
+                        primitive,
+                        type_arguments: arguments,
+                    },
+                    None => Operation::InstantiateTemplate {
+                        template: self.convert_instruction_to_symbol(template),
+                        type_arguments: arguments,
+                    },
+                };
+
+                self.stack
+                    .push(StackObject::Instruction(Box::new(Instruction {
+                        ssa_name: None,
+                        result_type: None,
+                        operation,
+                        source_location: self.current_location(),
+                    })));
             }
         }
         Ok(TraversalResult::SkipChildren)
@@ -905,14 +1602,14 @@ impl<'a> AstConverting for IrEmitter<'a> {
         _mode: TreeTraversalMode,
         _node: &NodeMessageEntry,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Message entries are not supported".to_string()))
     }
     fn emit_pattern_match_expression_clause(
         &mut self,
         _mode: TreeTraversalMode,
         _node: &NodePatternMatchExpressionClause,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Match expressions are not supported".to_string()))
     }
     fn emit_atomic_expression(
         &mut self,
@@ -928,7 +1625,7 @@ impl<'a> AstConverting for IrEmitter<'a> {
         _mode: TreeTraversalMode,
         _node: &NodeContractTypeArguments,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Contract type arguments are not supported".to_string()))
     }
     fn emit_value_literal(
         &mut self,
@@ -984,8 +1681,24 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 });
                 self.stack.push(StackObject::Instruction(instr));
             }
-            NodeValueLiteral::LiteralEmptyMap(_key, _value) => {
-                unimplemented!();
+            NodeValueLiteral::LiteralEmptyMap(key, value) => {
+                let _ = key.visit(self)?;
+                let _ = value.visit(self)?;
+                self.push_map_type()?;
+
+                let mut typename = self.pop_ir_identifier()?;
+                typename.kind = IrIndentifierKind::TypeName;
+                let operation = Operation::Literal {
+                    data: "Emp".to_string(),
+                    typename,
+                };
+                let instr = Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation,
+                    source_location: self.current_location(),
+                });
+                self.stack.push(StackObject::Instruction(instr));
             }
         }
         Ok(TraversalResult::SkipChildren)
@@ -993,9 +1706,11 @@ impl<'a> AstConverting for IrEmitter<'a> {
     fn emit_map_access(
         &mut self,
         _mode: TreeTraversalMode,
-        _node: &NodeMapAccess,
+        node: &NodeMapAccess,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        // Leaves the key expression on the stack
+        let _ = node.identifier_name.visit(self)?;
+        Ok(TraversalResult::SkipChildren)
     }
     fn emit_pattern(
         &mut self,
@@ -1008,14 +1723,13 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 // Wild card does not change anything
             }
             NodePattern::Binder(_name) => {
-                unimplemented!()
+                return Err(self.unsupported("Binder patterns are not supported".to_string()));
             }
             NodePattern::Constructor(name, args) => {
                 if args.len() > 0 {
-                    println!("Name: {:?}", name);
-                    println!("Args: {:?}", args);
-
-                    unimplemented!();
+                    return Err(self.unsupported(
+                        "Constructor patterns with arguments are not supported".to_string(),
+                    ));
                 }
 
                 let _ = name.visit(self);
@@ -1029,21 +1743,22 @@ impl<'a> AstConverting for IrEmitter<'a> {
         _mode: TreeTraversalMode,
         _node: &NodeArgumentPattern,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Argument patterns are not supported".to_string()))
     }
     fn emit_pattern_match_clause(
         &mut self,
         _mode: TreeTraversalMode,
         _node: &NodePatternMatchClause,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Pattern match clauses are not supported".to_string()))
     }
     fn emit_blockchain_fetch_arguments(
         &mut self,
         _mode: TreeTraversalMode,
         _node: &NodeBlockchainFetchArguments,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        // The EVM only exposes information about the current block
+        Err(self.unsupported("Blockchain fetch arguments are not supported".to_string()))
     }
 
     fn emit_statement(
@@ -1067,7 +1782,7 @@ impl<'a> AstConverting for IrEmitter<'a> {
 
                 let right_hand_side = match &right_hand_side.node {
                     NodeVariableIdentifier::VariableName(name) => name,
-                    _ => panic!("Load of state {:#?}", right_hand_side.node),
+                    _ => return Err(format!("Unable to load from {:?}", right_hand_side.node)),
                 };
 
                 let ret = Box::new(Instruction {
@@ -1091,8 +1806,9 @@ impl<'a> AstConverting for IrEmitter<'a> {
 
                 Some(ret)
             }
-            NodeStatement::RemoteFetch(_remote_stmt) => {
-                unimplemented!()
+            NodeStatement::RemoteFetch(remote_stmt) => {
+                let _ = remote_stmt.visit(self)?;
+                Some(self.pop_instruction()?)
             }
             NodeStatement::Store {
                 left_hand_side,
@@ -1156,38 +1872,133 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 Some(right_hand_side)
             }
             NodeStatement::ReadFromBC {
-                left_hand_side: _,
-                type_name: _,
-                arguments: _,
+                left_hand_side,
+                type_name,
+                arguments,
             } => {
-                unimplemented!()
+                if let Some(arguments) = arguments {
+                    let _ = arguments.visit(self)?;
+                }
+
+                let _ = type_name.visit(self)?;
+                let resource = self.pop_ir_identifier()?;
+                let symbol = IrIdentifier {
+                    unresolved: left_hand_side.to_string(),
+                    resolved: None,
+                    type_reference: None,
+                    kind: IrIndentifierKind::VirtualRegister,
+                    is_definition: true,
+                    source_location: self.current_location(),
+                };
+
+                Some(Box::new(Instruction {
+                    ssa_name: Some(symbol),
+                    result_type: None,
+                    operation: Operation::ResolveContextResource {
+                        symbol: IrIdentifier::new(
+                            resource.unresolved,
+                            IrIndentifierKind::ContextResource,
+                            self.current_location(),
+                        ),
+                    },
+                    source_location: self.current_location(),
+                }))
             }
             NodeStatement::MapGet {
-                left_hand_side: _,
-                keys: _,
-                right_hand_side: _,
+                left_hand_side,
+                keys,
+                right_hand_side,
             } => {
-                unimplemented!()
+                let keys = self.emit_map_keys(keys)?;
+                let symbol = IrIdentifier {
+                    unresolved: left_hand_side.to_string(),
+                    resolved: None,
+                    type_reference: None,
+                    kind: IrIndentifierKind::VirtualRegister,
+                    is_definition: true,
+                    source_location: self.current_location(),
+                };
+
+                let ret = Box::new(Instruction {
+                    ssa_name: Some(symbol),
+                    result_type: None,
+                    operation: Operation::StateMapLoad {
+                        address: self.state_field_address(right_hand_side.to_string()),
+                        keys,
+                    },
+                    source_location: self.current_location(),
+                });
+
+                Some(ret)
             }
             NodeStatement::MapGetExists {
-                left_hand_side: _,
-                keys: _,
-                right_hand_side: _,
+                left_hand_side,
+                keys,
+                right_hand_side,
             } => {
-                unimplemented!()
+                let keys = self.emit_map_keys(keys)?;
+                let symbol = IrIdentifier {
+                    unresolved: left_hand_side.to_string(),
+                    resolved: None,
+                    type_reference: None,
+                    kind: IrIndentifierKind::VirtualRegister,
+                    is_definition: true,
+                    source_location: self.current_location(),
+                };
+
+                let ret = Box::new(Instruction {
+                    ssa_name: Some(symbol),
+                    result_type: None,
+                    operation: Operation::StateMapExists {
+                        address: self.state_field_address(right_hand_side.to_string()),
+                        keys,
+                    },
+                    source_location: self.current_location(),
+                });
+
+                Some(ret)
             }
             NodeStatement::MapUpdate {
-                left_hand_side: _,
-                keys: _,
-                right_hand_side: _,
+                left_hand_side,
+                keys,
+                right_hand_side,
             } => {
-                unimplemented!()
+                let keys = self.emit_map_keys(keys)?;
+
+                let _ = right_hand_side.visit(self)?;
+                let right_hand_side = self.pop_instruction()?;
+                let value = self.convert_instruction_to_symbol(right_hand_side);
+
+                let ret = Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::StateMapStore {
+                        address: self.state_field_address(left_hand_side.to_string()),
+                        keys,
+                        value,
+                    },
+                    source_location: self.current_location(),
+                });
+
+                Some(ret)
             }
             NodeStatement::MapUpdateDelete {
-                left_hand_side: _,
-                keys: _,
+                left_hand_side,
+                keys,
             } => {
-                unimplemented!()
+                let keys = self.emit_map_keys(keys)?;
+
+                let ret = Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::StateMapDelete {
+                        address: self.state_field_address(left_hand_side.to_string()),
+                        keys,
+                    },
+                    source_location: self.current_location(),
+                });
+
+                Some(ret)
             }
             NodeStatement::Accept => {
                 let arguments: Vec<IrIdentifier> = [].to_vec();
@@ -1211,14 +2022,46 @@ impl<'a> AstConverting for IrEmitter<'a> {
 
                 Some(instr)
             }
-            NodeStatement::Send { identifier_name: _ } => {
-                unimplemented!()
-            }
-            NodeStatement::CreateEvnt { identifier_name: _ } => {
-                unimplemented!()
+            NodeStatement::Send { identifier_name } => {
+                let _ = identifier_name.visit(self)?;
+                let message = self.pop_instruction()?;
+                let message = self.convert_instruction_to_symbol(message);
+
+                Some(Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::SendMessage { message },
+                    source_location: self.current_location(),
+                }))
             }
-            NodeStatement::Throw { error_variable: _ } => {
-                unimplemented!()
+            NodeStatement::CreateEvnt { identifier_name } => {
+                let _ = identifier_name.visit(self)?;
+                let message = self.pop_instruction()?;
+                let message = self.convert_instruction_to_symbol(message);
+
+                Some(Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::EmitEvent { message },
+                    source_location: self.current_location(),
+                }))
+            }
+            NodeStatement::Throw { error_variable } => {
+                let exception = match error_variable {
+                    Some(error_variable) => {
+                        let _ = error_variable.visit(self)?;
+                        let exception = self.pop_instruction()?;
+                        Some(self.convert_instruction_to_symbol(exception))
+                    }
+                    None => None,
+                };
+
+                Some(Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::Revert(exception),
+                    source_location: self.current_location(),
+                }))
             }
             NodeStatement::MatchStmt { variable, clauses } => {
                 let _ = variable.visit(self)?;
@@ -1231,129 +2074,62 @@ impl<'a> AstConverting for IrEmitter<'a> {
                     .symbol_table
                     .name_generator
                     .new_block_label("match_exit");
-
-                // Termingating current block with placeholder label
-                let jump = Box::new(Instruction {
-                    ssa_name: None,
-                    result_type: None,
-                    operation: Operation::Jump(match_exit.clone()),
-                    source_location: source_location.clone(),
-                });
-                self.current_block.instructions.push_back(jump);
+                let conditions: Vec<IrIdentifier> = (1..clauses.len())
+                    .map(|i| {
+                        self.ir
+                            .symbol_table
+                            .name_generator
+                            .new_block_label(&format!("clause_{}_condition", i))
+                    })
+                    .collect();
 
                 for (i, clause) in clauses.iter().enumerate() {
-                    // Terminating previous block
-                    let label_condition = self
-                        .ir
-                        .symbol_table
-                        .name_generator
-                        .new_block_label(&format!("clause_{}_condition", i));
                     let label_block = self
                         .ir
                         .symbol_table
                         .name_generator
                         .new_block_label(&format!("clause_{}_block", i));
 
-                    let next_jump_label = match &clause.node.pattern_expression.node {
-                        NodePattern::Wildcard => label_block.clone(),
-                        NodePattern::Binder(_) => {
-                            unimplemented!()
-                        }
-                        NodePattern::Constructor(_, _) => label_condition.clone(),
-                    };
-
-                    let last_instruction = &mut self.current_block.instructions.back_mut().unwrap();
-
-                    match &mut last_instruction.operation {
-                        Operation::Jump(ref mut value) => {
-                            *value = label_condition.clone();
-                        }
-                        Operation::ConditionalJump {
-                            expression: _,
-                            on_success: _,
-                            ref mut on_failure,
-                        } => {
-                            *on_failure = next_jump_label;
-                        }
-                        _ => {
-                            panic!("Expected previous block to be a terminating jump.");
-                        }
-                    }
-
-                    match &clause.node.pattern_expression.node {
-                        NodePattern::Wildcard => {
-                            // In the event of a wildcard, we jump right to the clause block.
-                            // TODO: Check that the wildcard is last block in the match statement.
-                        }
-                        NodePattern::Binder(_) => {
-                            unimplemented!()
-                        }
-                        NodePattern::Constructor(_, _) => {
-                            // Instating condition checking block as self.current_block
-                            let mut clause_condition_block =
-                                FunctionBlock::new_from_symbol(label_condition);
-                            mem::swap(&mut clause_condition_block, &mut self.current_block);
-                            self.current_body.blocks.push(clause_condition_block);
-
-                            clause.node.pattern_expression.visit(self)?;
-                            let expected_value = self.pop_ir_identifier()?;
-                            assert!(match &expected_value.kind {
-                                IrIndentifierKind::TypeLikeName(args) => args.len() == 0,
-                                _ => false,
-                            });
-                            let source_location = expected_value.source_location.clone();
-
-                            let jump_condition = Box::new(Instruction {
-                                ssa_name: None,
-                                result_type: None,
-                                operation: Operation::IsEqual {
-                                    left: main_expression_symbol.clone(),
-                                    right: expected_value,
-                                },
-                                source_location: source_location.clone(),
-                            });
-
-                            let jump_if = Box::new(Instruction {
-                                ssa_name: None,
-                                result_type: None,
-                                operation: Operation::ConditionalJump {
-                                    expression: self.convert_instruction_to_symbol(jump_condition),
-                                    on_success: label_block.clone(),
-                                    on_failure: match_exit.clone(), // Exit or Placeholder - will be overwritten in next cycle
-                                },
-                                source_location: source_location.clone(),
-                            });
-                            self.current_block.instructions.push_back(jump_if);
-                        }
-                    };
+                    // Values not matching any of the clauses are ignored
+                    let on_failure = conditions.get(i).unwrap_or(&match_exit).clone();
+                    let (bound, _) = self.emit_pattern_test(
+                        &main_expression_symbol,
+                        &clause.node.pattern_expression.node,
+                        &on_failure,
+                    )?;
+                    self.terminate_block(Operation::Jump(label_block.clone()), on_failure);
 
+                    let first_block = self.current_body.blocks.len();
                     let mut clause_block = match &clause.node.statement_block {
                         Some(statement_block) => {
-                            // Condition block
                             statement_block.visit(self)?;
                             self.pop_function_block()?
                         }
                         None => FunctionBlock::new("empty_block".to_string()),
                     };
-                    // TODO: Get source location properly
-                    let source_location = source_location.clone();
-                    clause_block.name = label_block.clone();
+                    // Statements with control flow of their own leave their entry block in
+                    // the body and return the last block
+                    if first_block < self.current_body.blocks.len() {
+                        self.current_body.blocks[first_block].name = label_block;
+                    } else {
+                        clause_block.name = label_block;
+                    }
 
                     let terminator_instr = Box::new(Instruction {
                         ssa_name: None,
                         result_type: None,
                         operation: Operation::Jump(match_exit.clone()),
-                        source_location,
+                        source_location: source_location.clone(),
                     });
                     clause_block.instructions.push_back(terminator_instr);
+                    clause_block.terminated = true;
                     self.current_body.blocks.push(clause_block);
+                    self.pop_binders(&bound);
                 }
 
-                let mut match_exit_block = FunctionBlock::new_from_symbol(match_exit);
-                mem::swap(&mut match_exit_block, &mut self.current_block);
-
-                self.current_body.blocks.push(match_exit_block);
-                // self.current_body.blocks.extend(case_blocks);
+                if clauses.is_empty() {
+                    self.terminate_block(Operation::Jump(match_exit.clone()), match_exit);
+                }
                 None
             }
             NodeStatement::CallProc {
@@ -1373,7 +2149,9 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 }
 
                 let name = match &component_id.node {
-                    NodeComponentId::WithTypeLikeName(_) => unimplemented!(),
+                    NodeComponentId::WithTypeLikeName(_) => {
+                        return Err("Procedure names must be identifiers".to_string())
+                    }
                     NodeComponentId::WithRegularId(n) => n,
                 };
 
@@ -1403,12 +2181,21 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 identifier_name: _,
                 component_id: _,
             } => {
-                unimplemented!()
+                return Err(self.unsupported("forall statements are not supported".to_string()));
             }
         };
 
         match instr {
-            Some(instr) => self.current_block.instructions.push_back(instr),
+            Some(instr) => {
+                // Bound names are visible to anonymous functions defined in later statements
+                if let Some(ssa_name) = &instr.ssa_name {
+                    if ssa_name.kind == IrIndentifierKind::VirtualRegister {
+                        let typename = self.static_type_of(&instr);
+                        self.scope.push((ssa_name.unresolved.clone(), typename));
+                    }
+                }
+                self.current_block.instructions.push_back(instr)
+            }
             None => (),
         }
         Ok(TraversalResult::SkipChildren)
@@ -1417,9 +2204,51 @@ impl<'a> AstConverting for IrEmitter<'a> {
     fn emit_remote_fetch_statement(
         &mut self,
         _mode: TreeTraversalMode,
-        _node: &NodeRemoteFetchStatement,
+        node: &NodeRemoteFetchStatement,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        let (left_hand_side, contract, field) = match node {
+            NodeRemoteFetchStatement::ReadStateMutable(lhs, address, identifier) => (
+                lhs.to_string(),
+                address.to_string(),
+                Self::plain_variable_name(&identifier.node)?,
+            ),
+            NodeRemoteFetchStatement::ReadStateMutableSpecialId(lhs, address, identifier) => {
+                (lhs.to_string(), address.to_string(), identifier.to_string())
+            }
+            NodeRemoteFetchStatement::ReadStateMutableMapAccess(_, _, _, _)
+            | NodeRemoteFetchStatement::ReadStateMutableMapAccessExists(_, _, _, _) => {
+                return Err(self.unsupported("Remote map access is not supported".to_string()));
+            }
+            NodeRemoteFetchStatement::ReadStateMutableCastAddress(_, _, _) => {
+                return Err(self.unsupported("Address casts are not supported".to_string()));
+            }
+        };
+
+        let symbol = IrIdentifier {
+            unresolved: left_hand_side,
+            resolved: None,
+            type_reference: None,
+            kind: IrIndentifierKind::VirtualRegister,
+            is_definition: true,
+            source_location: self.current_location(),
+        };
+
+        let instr = Box::new(Instruction {
+            ssa_name: Some(symbol),
+            result_type: None,
+            operation: Operation::StateRemoteLoad {
+                contract: IrIdentifier::new(
+                    contract,
+                    IrIndentifierKind::VirtualRegister,
+                    self.current_location(),
+                ),
+                field,
+            },
+            source_location: self.current_location(),
+        });
+        self.stack.push(StackObject::Instruction(instr));
+
+        Ok(TraversalResult::SkipChildren)
     }
     fn emit_component_id(
         &mut self,
@@ -1536,7 +2365,7 @@ impl<'a> AstConverting for IrEmitter<'a> {
                     IrIndentifierKind::TypeName
                 }
             }
-            _ => panic!("Expected TypeLikeName"),
+            _ => return Err("Expected a type name".to_string()),
         };
 
         let s = StackObject::VariableDeclaration(VariableDeclaration::new(name, false, typename));
@@ -1648,6 +2477,7 @@ impl<'a> AstConverting for IrEmitter<'a> {
                 let value = self.pop_instruction()?;
 
                 let global_var = GlobalVariableDefition {
+                    namespace: self.current_namespace.clone(),
                     name,
                     typename,
                     value,
@@ -1708,18 +2538,113 @@ impl<'a> AstConverting for IrEmitter<'a> {
 
         self.push_namespace(ns);
 
-        let _ = node.parameters.visit(self)?;
+        // Contract parameters are immutable fields, whose values are set on deployment
+        for parameter in node.parameters.node.parameters.iter() {
+            let _ = parameter.visit(self)?;
+            let mut variable = self.pop_variable_declaration()?;
+            variable.name.kind = IrIndentifierKind::State;
+
+            self.ir.fields_definitions.push(ContractField {
+                namespace: self.current_namespace.clone(),
+                variable,
+                initializer: Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::Noop,
+                    source_location: self.current_location(),
+                }),
+                immutable: true,
+            });
+        }
+
+        // The constraint and the field initialisers are emitted as the body of the constructor
+        let mut body = FunctionBody::new();
+        let mut block = FunctionBlock::new("entry".to_string());
+        mem::swap(&mut body, &mut self.current_body);
+        mem::swap(&mut block, &mut self.current_block);
 
         if let Some(constraint) = &node.constraint {
             let _ = constraint.visit(self)?;
+            let condition = self.pop_instruction()?;
+            let source_location = condition.source_location.clone();
+            let condition = self.convert_instruction_to_symbol(condition);
+
+            let satisfied = self
+                .ir
+                .symbol_table
+                .name_generator
+                .new_block_label("constraint_satisfied");
+            let violated = self
+                .ir
+                .symbol_table
+                .name_generator
+                .new_block_label("constraint_violated");
+
+            self.current_block
+                .instructions
+                .push_back(Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::ConditionalJump {
+                        expression: condition,
+                        on_success: satisfied.clone(),
+                        on_failure: violated.clone(),
+                    },
+                    source_location: source_location.clone(),
+                }));
+            self.current_block.terminated = true;
+
+            let mut violated_block = FunctionBlock::new_from_symbol(violated);
+            violated_block.instructions.push_back(Box::new(Instruction {
+                ssa_name: None,
+                result_type: None,
+                operation: Operation::Revert(None),
+                source_location,
+            }));
+            violated_block.terminated = true;
+
+            let mut satisfied_block = FunctionBlock::new_from_symbol(satisfied);
+            mem::swap(&mut satisfied_block, &mut self.current_block);
+            self.current_body.blocks.push(satisfied_block);
+            self.current_body.blocks.push(violated_block);
         }
 
         for field in node.fields.iter() {
             let _ = field.visit(self)?;
         }
 
+        self.current_block
+            .instructions
+            .push_back(Box::new(Instruction {
+                ssa_name: None,
+                result_type: None,
+                operation: Operation::Return(None),
+                source_location: self.current_location(),
+            }));
+        self.current_block.terminated = true;
+
+        mem::swap(&mut block, &mut self.current_block);
+        mem::swap(&mut body, &mut self.current_body);
+        body.blocks.push(block);
+
+        self.ir.function_definitions.push(ConcreteFunction {
+            name: IrIdentifier {
+                unresolved: "constructor".to_string(),
+                resolved: None,
+                type_reference: None,
+                kind: IrIndentifierKind::TransitionName,
+                is_definition: true,
+                source_location: self.current_location(),
+            },
+            namespace: self.current_namespace.clone(),
+            function_kind: FunctionKind::Constructor,
+            return_type: None,
+            arguments: Vec::new(),
+            body,
+        });
+
         for component in node.components.iter() {
-            let _ = component.visit(self)?;
+            self.emit_component_reporting(component);
         }
 
         self.pop_namespace();
@@ -1738,10 +2663,37 @@ impl<'a> AstConverting for IrEmitter<'a> {
         let initializer = self.pop_instruction()?;
         variable.name.kind = IrIndentifierKind::State;
 
+        // Storing the initial value in the constructor. Maps start out empty.
+        if self
+            .ir
+            .symbol_table
+            .map_key_value_types(&variable.typename.unresolved)
+            .is_none()
+        {
+            let mut value = initializer.clone();
+            let symbol = self.ir.symbol_table.name_generator.new_intermediate();
+            value.ssa_name = Some(symbol.clone());
+            self.current_block.instructions.push_back(value);
+
+            let address = self.state_field_address(variable.name.unresolved.clone());
+            self.current_block
+                .instructions
+                .push_back(Box::new(Instruction {
+                    ssa_name: None,
+                    result_type: None,
+                    operation: Operation::StateStore {
+                        address,
+                        value: symbol,
+                    },
+                    source_location: initializer.source_location.clone(),
+                }));
+        }
+
         let field = ContractField {
             namespace: self.current_namespace.clone(),
             variable,
             initializer,
+            immutable: false,
         };
 
         self.ir.fields_definitions.push(field);
@@ -1753,7 +2705,8 @@ impl<'a> AstConverting for IrEmitter<'a> {
         _mode: TreeTraversalMode,
         _node: &NodeWithConstraint,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        // Pass through, leaving the condition on the stack for the contract definition
+        Ok(TraversalResult::Continue)
     }
     fn emit_component_definition(
         &mut self,
@@ -1767,7 +2720,7 @@ impl<'a> AstConverting for IrEmitter<'a> {
         _mode: TreeTraversalMode,
         _node: &NodeProcedureDefinition,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Procedures are not supported".to_string()))
     }
 
     fn emit_transition_definition(
@@ -1784,8 +2737,14 @@ impl<'a> AstConverting for IrEmitter<'a> {
             let ir_arg = self.pop_variable_declaration()?;
             arguments.push(ir_arg);
         }
+        self.scope = arguments
+            .iter()
+            .map(|arg| (arg.name.unresolved.clone(), Some(arg.typename.clone())))
+            .collect();
+
         // Function body
         let _ = node.body.visit(self)?;
+        self.scope.clear();
 
         // Exit
         let mut body = self.pop_function_body()?;
@@ -1890,13 +2849,14 @@ impl<'a> AstConverting for IrEmitter<'a> {
         _mode: TreeTraversalMode,
         _node: &NodeTypeMapValueArguments,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        Err(self.unsupported("Map value type arguments are not supported".to_string()))
     }
     fn emit_type_map_value_allowing_type_arguments(
         &mut self,
         _mode: TreeTraversalMode,
         _node: &NodeTypeMapValueAllowingTypeArguments,
     ) -> Result<TraversalResult, String> {
-        unimplemented!();
+        // Pass through
+        Ok(TraversalResult::Continue)
     }
 }
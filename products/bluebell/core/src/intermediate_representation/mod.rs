@@ -6,3 +6,4 @@ pub mod pass_executor;
 pub mod pass_manager;
 pub mod primitives;
 pub mod symbol_table;
+pub mod textual;
@@ -9,6 +9,7 @@ pub struct NameGenerator {
     anonymous_type_number: u64,
     intermediate_counter: u64,
     block_counter: u64,
+    lambda_counter: u64,
 }
 
 impl NameGenerator {
@@ -18,9 +19,28 @@ impl NameGenerator {
             anonymous_type_number: 0,
             intermediate_counter: 0,
             block_counter: 0,
+            lambda_counter: 0,
         }
     }
 
+    /// Returns the counters used to generate fresh names, such that they can be restored when
+    /// the IR is read back from its textual form.
+    pub fn counters(&self) -> [u64; 4] {
+        [
+            self.anonymous_type_number,
+            self.intermediate_counter,
+            self.block_counter,
+            self.lambda_counter,
+        ]
+    }
+
+    pub fn set_counters(&mut self, counters: [u64; 4]) {
+        self.anonymous_type_number = counters[0];
+        self.intermediate_counter = counters[1];
+        self.block_counter = counters[2];
+        self.lambda_counter = counters[3];
+    }
+
     pub fn string_type(&self) -> IrIdentifier {
         IrIdentifier {
             unresolved: "String".to_string(),
@@ -73,6 +93,22 @@ impl NameGenerator {
         FunctionBlock::new_label(label)
     }
 
+    pub fn new_lambda_name(&mut self, prefix: &str, kind: IrIndentifierKind) -> IrIdentifier {
+        let n = self.lambda_counter;
+        self.lambda_counter += 1;
+        IrIdentifier {
+            unresolved: format!("{}_{}", prefix, n),
+            resolved: None,
+            type_reference: None,
+            kind,
+            is_definition: true,
+            source_location: (
+                SourcePosition::invalid_position(),
+                SourcePosition::invalid_position(),
+            ),
+        }
+    }
+
     pub fn new_intermediate(&mut self) -> IrIdentifier {
         let n = self.intermediate_counter;
         self.intermediate_counter += 1;
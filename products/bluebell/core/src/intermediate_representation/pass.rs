@@ -1,5 +1,6 @@
 use scilla_parser::ast::{TraversalResult, TreeTraversalMode};
 
+use crate::errors::CompilerError;
 use crate::intermediate_representation::{primitives::*, symbol_table::SymbolTable};
 
 /// `IrPass` is an abstract pass that is used by the `PassManager` to manipulate the Intermediate Representation (IR).
@@ -139,4 +140,10 @@ pub trait IrPass {
 
     /// Finalize the pass.
     fn finalize(&mut self);
+
+    /// Takes the error the pass failed with, if the pass classified it. Errors which are not
+    /// classified are reported as internal errors of the compiler.
+    fn take_error(&mut self) -> Option<CompilerError> {
+        None
+    }
 }
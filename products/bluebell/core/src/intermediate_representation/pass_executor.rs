@@ -209,7 +209,37 @@ impl PassExecutor for Operation {
                     //let ret = value.visit(pass, symbol_table);
                     address.name.visit(pass, symbol_table)
                 }
-                Operation::MemLoad | Operation::MemStore | Operation::PhiNode(_) => {
+                Operation::StateMapStore {
+                    address,
+                    keys,
+                    value,
+                } => {
+                    let ret = value.visit(pass, symbol_table);
+                    address.name.visit(pass, symbol_table)?;
+                    for key in keys {
+                        key.visit(pass, symbol_table)?;
+                    }
+
+                    ret
+                }
+                Operation::StateRemoteLoad { contract, field: _ } => {
+                    contract.visit(pass, symbol_table)?;
+                    Ok(TraversalResult::Continue)
+                }
+                Operation::StateMapLoad { address, keys }
+                | Operation::StateMapExists { address, keys }
+                | Operation::StateMapDelete { address, keys } => {
+                    address.name.visit(pass, symbol_table)?;
+                    for key in keys {
+                        key.visit(pass, symbol_table)?;
+                    }
+                    Ok(TraversalResult::Continue)
+                }
+                Operation::MemLoad | Operation::MemStore => Ok(TraversalResult::Continue),
+                Operation::PhiNode(values) => {
+                    for value in values {
+                        value.visit(pass, symbol_table)?;
+                    }
                     Ok(TraversalResult::Continue)
                 }
                 Operation::ConditionalJump {
@@ -267,6 +297,55 @@ impl PassExecutor for Operation {
                     typename.visit(pass, symbol_table)?;
                     Ok(TraversalResult::Continue)
                 }
+                Operation::ConstructMessage { entries } => {
+                    for (_, value) in entries {
+                        value.visit(pass, symbol_table)?;
+                    }
+                    Ok(TraversalResult::Continue)
+                }
+                Operation::EmitEvent { message } | Operation::SendMessage { message } => {
+                    message.visit(pass, symbol_table)
+                }
+                Operation::CreateClosure { function, captures } => {
+                    function.visit(pass, symbol_table)?;
+                    for capture in captures {
+                        capture.visit(pass, symbol_table)?;
+                    }
+                    Ok(TraversalResult::Continue)
+                }
+                Operation::LoadCapture { closure, index: _ } => closure.visit(pass, symbol_table),
+                Operation::ApplyClosure { closure, argument } => {
+                    closure.visit(pass, symbol_table)?;
+                    argument.visit(pass, symbol_table)
+                }
+                Operation::InstantiateTemplate {
+                    template,
+                    type_arguments,
+                } => {
+                    template.visit(pass, symbol_table)?;
+                    for arg in type_arguments {
+                        arg.visit(pass, symbol_table)?;
+                    }
+                    Ok(TraversalResult::Continue)
+                }
+                Operation::InstantiatePrimitive {
+                    primitive,
+                    type_arguments,
+                } => {
+                    primitive.visit(pass, symbol_table)?;
+                    for arg in type_arguments {
+                        arg.visit(pass, symbol_table)?;
+                    }
+                    Ok(TraversalResult::Continue)
+                }
+                Operation::LoadConstructorArgument {
+                    value,
+                    constructor,
+                    index: _,
+                } => {
+                    value.visit(pass, symbol_table)?;
+                    constructor.visit(pass, symbol_table)
+                }
                 Operation::Noop => Ok(TraversalResult::Continue),
                 Operation::Return(arg) | Operation::Revert(arg) => {
                     match arg {
@@ -1,49 +1,359 @@
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
 use crate::{
+    diagnostics::{self, Diagnostic, DiagnosticList},
+    errors::CompilerError,
     intermediate_representation::{pass::IrPass, primitives::IntermediateRepresentation},
     passes::{
         annotate_base_types::AnnotateBaseTypes, balance_block_args::BalanceBlockArguments,
-        block_dependencies::DeduceBlockDependencies,
-        collect_type_definitions::CollectTypeDefinitionsPass, debug_printer::DebugPrinter,
-        state_allocator::StateCollector,
+        block_dependencies::DeduceBlockDependencies, closure_conversion::ClosureConversion,
+        collect_type_definitions::CollectTypeDefinitionsPass, constant_folding::ConstantFolding,
+        copy_propagation::CopyPropagation, dead_code_elimination::DeadCodeElimination,
+        debug_printer::DebugPrinter, ir_verifier::IrVerifier, jump_threading::JumpThreading,
+        state_allocator::StateCollector, type_checker::TypeChecker,
     },
 };
 
+/// Name of the type checking stage, which runs before any of the passes.
+pub const TYPE_CHECK: &str = "type-check";
+/// Name of the closure conversion stage, which runs after type checking.
+pub const CLOSURE_CONVERSION: &str = "closure-conversion";
+
+/// Passes run by `PassManager::default_pipeline` after closure conversion.
+pub const DEFAULT_PIPELINE: &str =
+    "collect-types,state-alloc,annotate-types,block-deps,balance-block-args,block-deps";
+
+/// Names of the passes which can be used in a pipeline spec.
+pub const PASS_NAMES: [&str; 11] = [
+    "collect-types",
+    "state-alloc",
+    "annotate-types",
+    "block-deps",
+    "balance-block-args",
+    "constant-folding",
+    "copy-propagation",
+    "dead-code-elimination",
+    "jump-threading",
+    "debug-printer",
+    "verify",
+];
+
+/// Reports an error of the emitter, a pass or the code generator as a diagnostic.
+fn to_diagnostics<E: Into<CompilerError>>(error: E) -> DiagnosticList {
+    vec![Diagnostic::from(error.into())]
+}
+
+/// Runs `pass` on `ir`, taking the classification of the error from the pass if it fails.
+fn run_pass(
+    ir: &mut IntermediateRepresentation,
+    pass: &mut dyn IrPass,
+) -> Result<(), CompilerError> {
+    match ir.run_pass(pass) {
+        Ok(_) => Ok(()),
+        Err(message) => Err(pass
+            .take_error()
+            .unwrap_or_else(|| CompilerError::internal(message))),
+    }
+}
+
+/// Creates the pass with the given name.
+pub fn create_pass(name: &str) -> Result<Box<dyn IrPass>, String> {
+    let ret: Box<dyn IrPass> = match name {
+        "collect-types" => Box::new(CollectTypeDefinitionsPass::new()),
+        "state-alloc" => Box::new(StateCollector::new()),
+        "annotate-types" => Box::new(AnnotateBaseTypes::new()),
+        "block-deps" => Box::new(DeduceBlockDependencies::new()),
+        "balance-block-args" => Box::new(BalanceBlockArguments::new()),
+        "constant-folding" => Box::new(ConstantFolding::new()),
+        "copy-propagation" => Box::new(CopyPropagation::new()),
+        "dead-code-elimination" => Box::new(DeadCodeElimination::new()),
+        "jump-threading" => Box::new(JumpThreading::new()),
+        "debug-printer" => Box::new(DebugPrinter::new()),
+        "verify" => Box::new(IrVerifier::new()),
+        _ => {
+            return Err(format!(
+                "Unknown pass `{}`, expected one of {}",
+                name,
+                PASS_NAMES.join(", ")
+            ))
+        }
+    };
+    Ok(ret)
+}
+
 pub struct PassManager {
-    passes: Vec<Box<dyn IrPass>>,
+    passes: Vec<(String, Box<dyn IrPass>)>,
+    /// Whether the program is type checked before it is lowered.
+    type_check: bool,
+    /// Level 0 disables optimisation, level 1 folds constants and removes copies and dead code,
+    /// level 2 additionally threads jumps through empty blocks. From level 1 the generated
+    /// bytecode is also passed through the peephole optimiser.
+    optimisation_level: u32,
+    /// Stages after which the IR is printed.
+    print_after: BTreeSet<String>,
+    print_after_all: bool,
+    time_passes: bool,
+    /// Whether the IR is verified after every stage. Enabled by default in debug builds only, as
+    /// it checks the entire IR each time.
+    verify_each: bool,
+    /// Whether block arguments have been balanced and deduced again in the current run, after
+    /// which the verifier also checks the arguments passed between blocks.
+    block_arguments_balanced: bool,
+    /// IR printed after each stage of the last run, in the order the stages ran.
+    ir_dumps: Vec<(String, String)>,
+    /// Time spent in each stage of the last run, if timing is enabled.
+    timings: Vec<(String, Duration)>,
 }
 
 impl PassManager {
     pub fn new() -> Self {
-        Self { passes: Vec::new() }
+        Self {
+            passes: Vec::new(),
+            type_check: false,
+            optimisation_level: 0,
+            print_after: BTreeSet::new(),
+            print_after_all: false,
+            time_passes: false,
+            verify_each: cfg!(debug_assertions),
+            block_arguments_balanced: false,
+            ir_dumps: Vec::new(),
+            timings: Vec::new(),
+        }
     }
 
     pub fn default_pipeline() -> Self {
+        Self::from_spec(DEFAULT_PIPELINE).expect("Invalid default pipeline")
+    }
+
+    /// Creates a type checking pass manager running the comma separated passes in `spec`,
+    /// e.g. `collect-types,state-alloc,annotate-types`.
+    pub fn from_spec(spec: &str) -> Result<Self, String> {
         let mut ret = Self::new();
+        ret.type_check = true;
 
-        ret.passes.push(Box::new(CollectTypeDefinitionsPass::new()));
-        ret.passes.push(Box::new(StateCollector::new()));
-        ret.passes.push(Box::new(AnnotateBaseTypes::new()));
-        ret.passes.push(Box::new(DeduceBlockDependencies::new()));
-        ret.passes.push(Box::new(BalanceBlockArguments::new()));
-        ret.passes.push(Box::new(DeduceBlockDependencies::new()));
+        for name in spec.split(',').map(|name| name.trim()) {
+            if name.is_empty() {
+                continue;
+            }
+            ret.add_named_pass(name, create_pass(name)?);
+        }
 
-        ret
+        Ok(ret)
+    }
+
+    /// Returns the pipeline as a spec accepted by `from_spec`.
+    pub fn spec(&self) -> String {
+        self.passes
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<String>>()
+            .join(",")
     }
 
     pub fn enable_debug_printer(&mut self) -> &mut Self {
-        self.passes.push(Box::new(DebugPrinter::new()));
+        self.add_named_pass("debug-printer", Box::new(DebugPrinter::new()));
+
+        self
+    }
+
+    pub fn set_type_check(&mut self, type_check: bool) -> &mut Self {
+        self.type_check = type_check;
+
+        self
+    }
+
+    pub fn set_optimisation_level(&mut self, level: u32) -> &mut Self {
+        self.optimisation_level = level;
+
+        self
+    }
+
+    pub fn optimisation_level(&self) -> u32 {
+        self.optimisation_level
+    }
+
+    /// Prints the IR after every stage named `name`.
+    pub fn set_print_after(&mut self, name: &str) -> Result<&mut Self, String> {
+        if name != TYPE_CHECK && name != CLOSURE_CONVERSION && !PASS_NAMES.contains(&name) {
+            return Err(format!("Unknown pass `{}`", name));
+        }
+        self.print_after.insert(name.to_string());
+
+        Ok(self)
+    }
+
+    pub fn set_print_after_all(&mut self, print_after_all: bool) -> &mut Self {
+        self.print_after_all = print_after_all;
+
+        self
+    }
+
+    pub fn set_time_passes(&mut self, time_passes: bool) -> &mut Self {
+        self.time_passes = time_passes;
+
+        self
+    }
+
+    pub fn set_verify_each(&mut self, verify_each: bool) -> &mut Self {
+        self.verify_each = verify_each;
 
         self
     }
 
+    /// Returns the IR printed after each stage of the last run together with the stage name.
+    pub fn ir_dumps(&self) -> &[(String, String)] {
+        &self.ir_dumps
+    }
+
+    /// Returns the time spent in each stage of the last run together with the stage name.
+    pub fn timings(&self) -> &[(String, Duration)] {
+        &self.timings
+    }
+
+    pub fn timing_report(&self) -> String {
+        let total: Duration = self.timings.iter().map(|(_, duration)| *duration).sum();
+        let mut ret = String::new();
+        for (name, duration) in self.timings.iter() {
+            ret.push_str(&format!(
+                "{:<24} {:>10.3} ms\n",
+                name,
+                duration.as_secs_f64() * 1000.0
+            ));
+        }
+        ret.push_str(&format!(
+            "{:<24} {:>10.3} ms\n",
+            "total",
+            total.as_secs_f64() * 1000.0
+        ));
+        ret
+    }
+
     pub fn add_pass(&mut self, pass: Box<dyn IrPass>) {
-        self.passes.push(pass);
+        self.add_named_pass("custom", pass);
+    }
+
+    pub fn add_named_pass(&mut self, name: &str, pass: Box<dyn IrPass>) {
+        self.passes.push((name.to_string(), pass));
+    }
+
+    /// Returns the optimisation passes for the current level. These run on the closure
+    /// converted IR before names are resolved and block dependencies are deduced.
+    fn optimisation_passes(&self) -> Vec<(String, Box<dyn IrPass>)> {
+        let mut names = Vec::new();
+        if self.optimisation_level >= 1 {
+            names.extend([
+                "constant-folding",
+                "copy-propagation",
+                "dead-code-elimination",
+            ]);
+        }
+        if self.optimisation_level >= 2 {
+            names.extend(["jump-threading", "dead-code-elimination"]);
+        }
+        names
+            .into_iter()
+            .map(|name| (name.to_string(), create_pass(name).expect("Unknown pass")))
+            .collect()
+    }
+
+    /// Starts timing a stage if timing is enabled. Timing is opt-in as clocks are not
+    /// available on all targets the compiler is built for.
+    fn start_stage(&self) -> Option<Instant> {
+        if self.time_passes {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    /// Records the time spent in the stage `name`, verifies the IR and prints it if requested.
+    fn finish_stage(
+        &mut self,
+        name: &str,
+        started: Option<Instant>,
+        ir: &mut IntermediateRepresentation,
+    ) -> Result<(), String> {
+        if let Some(started) = started {
+            self.timings.push((name.to_string(), started.elapsed()));
+        }
+
+        if self.verify_each {
+            IrVerifier::new()
+                .set_check_block_arguments(self.block_arguments_balanced)
+                .verify(ir)
+                .map_err(|errors| {
+                    format!("IR verification failed after `{}`:\n{}", name, errors)
+                })?;
+        }
+
+        if self.print_after_all || self.print_after.contains(name) {
+            let mut printer = DebugPrinter::new();
+            ir.run_pass(&mut printer)?;
+            self.ir_dumps.push((name.to_string(), printer.value()));
+        }
+
+        Ok(())
     }
 
     pub fn run(&mut self, ir: &mut IntermediateRepresentation) -> Result<u32, String> {
+        self.run_with_diagnostics(ir).map_err(|diagnostics| {
+            let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+            messages.join("\n")
+        })
+    }
+
+    /// Runs the pipeline and reports every type error found rather than only the first one.
+    /// Lowering stops at the first pass that fails.
+    pub fn run_with_diagnostics(
+        &mut self,
+        ir: &mut IntermediateRepresentation,
+    ) -> Result<u32, DiagnosticList> {
+        self.ir_dumps.clear();
+        self.timings.clear();
+        self.block_arguments_balanced = false;
+        let mut balancing = false;
+
+        if self.type_check {
+            let started = self.start_stage();
+            if let Err(errors) = TypeChecker::new().check(ir) {
+                return Err(errors
+                    .into_iter()
+                    .map(|error| Diagnostic::from(error).with_code(diagnostics::TYPE_ERROR))
+                    .collect());
+            }
+            self.finish_stage(TYPE_CHECK, started, ir)
+                .map_err(to_diagnostics)?;
+        }
+
+        let started = self.start_stage();
+        ClosureConversion::new().run(ir).map_err(to_diagnostics)?;
+        self.finish_stage(CLOSURE_CONVERSION, started, ir)
+            .map_err(to_diagnostics)?;
+
+        for (name, mut pass) in self.optimisation_passes() {
+            let started = self.start_stage();
+            run_pass(ir, pass.as_mut()).map_err(to_diagnostics)?;
+            self.finish_stage(&name, started, ir)
+                .map_err(to_diagnostics)?;
+        }
+
         // TODO: Make self immutable and copy pass before running it on IR
-        for pass in &mut self.passes {
-            ir.run_pass(pass.as_mut())?;
+        for i in 0..self.passes.len() {
+            let started = self.start_stage();
+            let (name, pass) = &mut self.passes[i];
+            run_pass(ir, pass.as_mut()).map_err(to_diagnostics)?;
+
+            let name = name.clone();
+            match &name[..] {
+                "balance-block-args" => balancing = true,
+                "block-deps" if balancing => self.block_arguments_balanced = true,
+                _ => (),
+            }
+            self.finish_stage(&name, started, ir)
+                .map_err(to_diagnostics)?;
         }
         Ok(0)
     }
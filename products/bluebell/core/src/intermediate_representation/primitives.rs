@@ -73,6 +73,16 @@ impl IrIdentifier {
             Ok(format!("[{}]", self.unresolved).to_string())
         }
     }
+
+    /// Whether the identifier refers to a value held in a register or in memory.
+    pub fn is_variable(&self) -> bool {
+        matches!(
+            self.kind,
+            IrIndentifierKind::VirtualRegister
+                | IrIndentifierKind::VirtualRegisterIntermediate
+                | IrIndentifierKind::Memory
+        )
+    }
 }
 
 /// Struct representing an enum value in the intermediate representation.
@@ -211,6 +221,35 @@ pub enum Operation {
         address: FieldAddress,
         value: IrIdentifier,
     },
+    /// Loads the field `field` of the contract at `contract`. The special field `_balance`
+    /// refers to the balance of the contract.
+    StateRemoteLoad {
+        contract: IrIdentifier,
+        field: String,
+    },
+    /// Loads `address[keys[0]]...[keys[n]]` from a map field as `Some` of the entry, or `None`
+    /// if the entry does not exist.
+    StateMapLoad {
+        address: FieldAddress,
+        keys: Vec<IrIdentifier>,
+    },
+    /// Checks whether `address[keys[0]]...[keys[n]]` is present in a map field.
+    StateMapExists {
+        address: FieldAddress,
+        keys: Vec<IrIdentifier>,
+    },
+    /// Stores `value` at `address[keys[0]]...[keys[n]]` in a map field.
+    StateMapStore {
+        address: FieldAddress,
+        keys: Vec<IrIdentifier>,
+        value: IrIdentifier,
+    },
+    /// Removes `address[keys[0]]...[keys[n]]` from a map field. Removing a map from a map
+    /// leaves the entries of the removed map in storage.
+    StateMapDelete {
+        address: FieldAddress,
+        keys: Vec<IrIdentifier>,
+    },
     IsEqual {
         left: IrIdentifier,
         right: IrIdentifier,
@@ -245,12 +284,140 @@ pub enum Operation {
         data: String,
         typename: IrIdentifier,
     },
+    /// Constructs a message from its `(field name, value)` entries.
+    ConstructMessage {
+        entries: Vec<(String, IrIdentifier)>,
+    },
+    /// Emits the message `message` as an event.
+    EmitEvent {
+        message: IrIdentifier,
+    },
+    /// Sends the outgoing messages of the list `message` in order.
+    SendMessage {
+        message: IrIdentifier,
+    },
+    /// Creates a closure of the lifted function `function` whose environment record holds
+    /// `captures`. The captures are filled in by `ClosureConversion`.
+    CreateClosure {
+        function: IrIdentifier,
+        captures: Vec<IrIdentifier>,
+    },
+    /// Loads the captured value at `index` from the environment record of `closure`.
+    LoadCapture {
+        closure: IrIdentifier,
+        index: usize,
+    },
+    /// Applies the closure `closure` to `argument`.
+    ApplyClosure {
+        closure: IrIdentifier,
+        argument: IrIdentifier,
+    },
+    /// Instantiates the type abstraction `template` with `type_arguments`. Type applications
+    /// are monomorphised by `ClosureConversion` and never reach the code generator.
+    InstantiateTemplate {
+        template: IrIdentifier,
+        type_arguments: Vec<IrIdentifier>,
+    },
+    /// Instantiates the recursion primitive `primitive`, i.e. `list_foldl`, `list_foldr`,
+    /// `list_foldk` or `nat_fold`, with `type_arguments`. The result is a closure.
+    InstantiatePrimitive {
+        primitive: IrIdentifier,
+        type_arguments: Vec<IrIdentifier>,
+    },
+    /// Loads the argument at `index` of `value`, which must have been built by the
+    /// constructor `constructor`.
+    LoadConstructorArgument {
+        value: IrIdentifier,
+        constructor: IrIdentifier,
+        index: usize,
+    },
     PhiNode(Vec<IrIdentifier>),
 
     Return(Option<IrIdentifier>),
     Revert(Option<IrIdentifier>),
 }
 
+impl Operation {
+    /// Returns the values read by the operation. Function names, block labels, fields and type
+    /// names are not operands.
+    pub fn operands_mut(&mut self) -> Vec<&mut IrIdentifier> {
+        match self {
+            Operation::Noop
+            | Operation::Jump(_)
+            | Operation::MemLoad
+            | Operation::MemStore
+            | Operation::StateLoad { .. }
+            | Operation::ResolveContextResource { .. }
+            | Operation::Literal { .. }
+            | Operation::InstantiatePrimitive { .. }
+            | Operation::Return(None)
+            | Operation::Revert(None) => Vec::new(),
+            Operation::TerminatingRef(value)
+            | Operation::ResolveSymbol { symbol: value }
+            | Operation::EmitEvent { message: value }
+            | Operation::SendMessage { message: value }
+            | Operation::StateStore { value, .. }
+            | Operation::StateRemoteLoad {
+                contract: value, ..
+            }
+            | Operation::LoadCapture { closure: value, .. }
+            | Operation::LoadConstructorArgument { value, .. }
+            | Operation::InstantiateTemplate {
+                template: value, ..
+            }
+            | Operation::ConditionalJump {
+                expression: value, ..
+            }
+            | Operation::Return(Some(value))
+            | Operation::Revert(Some(value)) => vec![value],
+            Operation::StateMapLoad { keys, .. }
+            | Operation::StateMapExists { keys, .. }
+            | Operation::StateMapDelete { keys, .. } => keys.iter_mut().collect(),
+            Operation::StateMapStore { keys, value, .. } => {
+                let mut ret: Vec<&mut IrIdentifier> = keys.iter_mut().collect();
+                ret.push(value);
+                ret
+            }
+            Operation::IsEqual { left, right } => vec![left, right],
+            Operation::CallExternalFunction { arguments, .. }
+            | Operation::CallFunction { arguments, .. }
+            | Operation::CallStaticFunction { arguments, .. }
+            | Operation::CallMemberFunction { arguments, .. } => arguments.iter_mut().collect(),
+            Operation::ConstructMessage { entries } => {
+                entries.iter_mut().map(|(_, value)| value).collect()
+            }
+            Operation::CreateClosure { captures, .. } => captures.iter_mut().collect(),
+            Operation::ApplyClosure { closure, argument } => vec![closure, argument],
+            Operation::PhiNode(values) => values.iter_mut().collect(),
+        }
+    }
+
+    /// Returns the labels of the blocks the operation jumps to.
+    pub fn jump_targets_mut(&mut self) -> Vec<&mut IrIdentifier> {
+        match self {
+            Operation::Jump(label) => vec![label],
+            Operation::ConditionalJump {
+                on_success,
+                on_failure,
+                ..
+            } => vec![on_success, on_failure],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether the operation has no effect besides producing its value, such that it can be
+    /// removed when the value is unused.
+    pub fn is_pure(&self) -> bool {
+        matches!(
+            self,
+            Operation::ResolveSymbol { .. }
+                | Operation::Literal { .. }
+                | Operation::IsEqual { .. }
+                | Operation::PhiNode(_)
+        )
+    }
+}
+
 /// Struct representing an instruction in the intermediate representation.
 #[derive(Debug, Clone)]
 pub struct Instruction {
@@ -345,6 +512,10 @@ pub enum FunctionKind {
     Procedure,
     Transition,
     Function,
+    Lambda,
+    /// Runs once when the contract is deployed, initialising the fields and checking the
+    /// contract constraint.
+    Constructor,
 }
 
 /// Struct representing a concrete function in the intermediate representation.
@@ -358,14 +529,41 @@ pub struct ConcreteFunction {
     pub body: Box<FunctionBody>,
 }
 
-/// Struct representing a lambda function with a single argument in the intermediate representation.
+impl ConcreteFunction {
+    /// Counts how often each variable is defined, either as an argument or by an instruction.
+    /// Variables defined exactly once hold the same value wherever they are used.
+    pub fn definition_counts(&self) -> HashMap<String, usize> {
+        let mut ret: HashMap<String, usize> = HashMap::new();
+        for arg in self.arguments.iter() {
+            *ret.entry(arg.name.unresolved.clone()).or_insert(0) += 1;
+        }
+        for block in self.body.blocks.iter() {
+            for instr in block.instructions.iter() {
+                if let Some(ssa_name) = &instr.ssa_name {
+                    *ret.entry(ssa_name.unresolved.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// Struct representing an anonymous function, `fun (x: T) => e`, or a type abstraction,
+/// `tfun 'A => e`, in the intermediate representation. Lambda functions are lifted into
+/// concrete functions by `ClosureConversion`.
 #[derive(Debug, Clone)]
 pub struct LambdaFunctionSingleArgument {
     pub name: IrIdentifier,
+    pub namespace: IrIdentifier,
     pub capture: Box<Tuple>,
-    pub argument: VariableDeclaration,
+    /// Variables in scope where the lambda is defined together with their type, if known.
+    pub environment: Vec<(String, Option<IrIdentifier>)>,
+    /// Value argument of `fun`. Type abstractions do not have one.
+    pub argument: Option<VariableDeclaration>,
+    /// Type variable of `tfun`.
+    pub type_argument: Option<String>,
     pub return_type: Option<String>,
-    pub block: FunctionBlock,
+    pub body: Box<FunctionBody>,
 }
 
 /// Struct representing a contract field in the intermediate representation.
@@ -374,10 +572,14 @@ pub struct ContractField {
     pub namespace: IrIdentifier,
     pub variable: VariableDeclaration,
     pub initializer: Box<Instruction>,
+    /// Whether the field is a contract parameter, which is set when the contract is deployed
+    /// and cannot be assigned to.
+    pub immutable: bool,
 }
 
 #[derive(Debug)]
 pub struct GlobalVariableDefition {
+    pub namespace: IrIdentifier,
     pub name: IrIdentifier,
     pub typename: Option<IrIdentifier>,
     pub value: Box<Instruction>,
@@ -413,4 +615,29 @@ impl IntermediateRepresentation {
             symbol_table,
         }
     }
+
+    /// Merges the IR of an imported library into this IR. `other` must be emitted with a symbol
+    /// table derived from the symbol table of this IR, such that generated names do not collide.
+    pub fn merge(&mut self, other: IntermediateRepresentation) -> Result<(), String> {
+        for global in other.global_variables.iter() {
+            let namespace = &global.namespace.unresolved;
+            if !self.symbol_table.imported_namespaces.contains(namespace) {
+                self.symbol_table
+                    .imported_namespaces
+                    .push(namespace.clone());
+            }
+        }
+
+        self.type_definitions.extend(other.type_definitions);
+        self.global_init_block
+            .instructions
+            .extend(other.global_init_block.instructions);
+        self.global_variables.extend(other.global_variables);
+        self.function_definitions.extend(other.function_definitions);
+        self.fields_definitions.extend(other.fields_definitions);
+        self.lambda_functions.extend(other.lambda_functions);
+        self.symbol_table.merge(other.symbol_table);
+
+        Ok(())
+    }
 }
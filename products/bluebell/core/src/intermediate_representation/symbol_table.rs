@@ -1,11 +1,38 @@
 use std::collections::HashMap;
 
+use evm_assembly::storage_layout::{StorageClass, StorageEncoding};
 use primitive_types::U256;
 
 use crate::{
-    constants::NAMESPACE_SEPARATOR, intermediate_representation::name_generator::NameGenerator,
+    constants::{
+        FUNCTION_RETURN_TYPE_SEPARATOR, MAP_TYPE_NAME, NAMESPACE_SEPARATOR,
+        TEMPLATE_PARAMETERS_END, TEMPLATE_PARAMETERS_SEPARATOR, TEMPLATE_PARAMETERS_START,
+    },
+    intermediate_representation::name_generator::NameGenerator,
 };
 
+/// Removes whitespace and parentheses enclosing all of `typename`, i.e. `(A -> B)` becomes
+/// `A -> B` whereas `(A -> B) -> (C -> D)` is left as is.
+pub(crate) fn strip_enclosing_parentheses(typename: &str) -> &str {
+    let mut typename = typename.trim();
+    while let Some(inner) = typename.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        let mut depth = 0;
+        let balanced = inner.chars().all(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => (),
+            }
+            depth >= 0
+        });
+        if !balanced {
+            break;
+        }
+        typename = inner.trim();
+    }
+    typename
+}
+
 /// Struct representing the type information of a symbol.
 #[derive(Debug, Clone)]
 pub struct TypeInfo {
@@ -31,12 +58,18 @@ impl TypeInfo {
     }
 }
 
-/// Struct representing the state layout entry.
+/// Struct representing the state layout entry. For immutables, `address_offset` is the offset
+/// of the value among the contract parameters, which the code generator turns into the
+/// distance of the value from the end of the code.
 #[derive(Debug, Clone)]
 pub struct StateLayoutEntry {
     pub address_offset: U256,
     pub size: u64,
     pub initializer: U256,
+    /// Offset of the value within its slot in bytes, counted from the least significant byte.
+    pub byte_offset: u64,
+    pub encoding: StorageEncoding,
+    pub signed: bool,
 }
 
 /// Struct representing the symbol table.
@@ -46,6 +79,11 @@ pub struct SymbolTable {
     pub type_of_table: HashMap<String, Box<TypeInfo>>,
     pub name_generator: NameGenerator,
     pub state_layout: HashMap<String, StateLayoutEntry>,
+    /// How values of the types known to the backend are stored. Values of other types, apart
+    /// from maps, are stored as algebraic data types.
+    pub storage_classes: HashMap<String, StorageClass>,
+    /// Namespaces of imported libraries, searched when a name cannot be resolved otherwise.
+    pub imported_namespaces: Vec<String>,
 }
 
 /// Trait for constructing a new symbol table.
@@ -95,6 +133,13 @@ impl SymbolTable {
             }
         }
 
+        for namespace in self.imported_namespaces.iter() {
+            let full_name = format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, basename);
+            if let Some(_) = self.typename_of(&full_name) {
+                return Some(full_name);
+            }
+        }
+
         let lookup = if let Some(aliased_name) = self.aliases.get(basename) {
             aliased_name
         } else {
@@ -179,7 +224,7 @@ impl SymbolTable {
             symbol_name: symbol.to_string(),
             typename: signature.clone(),
             return_type: Some(return_type.to_string()),
-            arguments: Vec::new(),
+            arguments: arguments.clone(),
             constructor,
         });
 
@@ -239,6 +284,98 @@ impl SymbolTable {
         self.declare_type_of(symbol, symbol)
     }
 
+    /// Declares the map type `Map<key,value>` and returns its name.
+    pub fn declare_map_type(&mut self, key: &str, value: &str) -> Result<String, String> {
+        let typename = format!(
+            "{}{}{}{}{}{}",
+            MAP_TYPE_NAME,
+            TEMPLATE_PARAMETERS_START,
+            key,
+            TEMPLATE_PARAMETERS_SEPARATOR,
+            value,
+            TEMPLATE_PARAMETERS_END
+        );
+        self.declare_type(&typename)
+    }
+
+    /// Returns the key and value types of a map type, or None if `typename` is not a map.
+    pub fn map_key_value_types(&self, typename: &str) -> Option<(String, String)> {
+        let arguments = typename
+            .strip_prefix(MAP_TYPE_NAME)?
+            .strip_prefix(TEMPLATE_PARAMETERS_START)?
+            .strip_suffix(TEMPLATE_PARAMETERS_END)?;
+
+        // Map keys are never maps themselves, so the first separator splits key and value
+        let (key, value) = arguments.split_once(TEMPLATE_PARAMETERS_SEPARATOR)?;
+        Some((key.to_string(), value.to_string()))
+    }
+
+    /// Declares the closure type `argument -> return_type` and returns its name.
+    pub fn declare_closure_type(
+        &mut self,
+        argument: &str,
+        return_type: &str,
+    ) -> Result<String, String> {
+        // Function typed arguments are parenthesised as the arrow is right associative
+        let argument = if argument.contains(FUNCTION_RETURN_TYPE_SEPARATOR) {
+            format!("({})", argument)
+        } else {
+            argument.to_string()
+        };
+        let typename = format!(
+            "{} {} {}",
+            argument, FUNCTION_RETURN_TYPE_SEPARATOR, return_type
+        );
+        self.declare_type(&typename)
+    }
+
+    /// Returns the argument and return types of a closure type, or None if `typename` is not a
+    /// closure.
+    pub fn closure_argument_return_types(&self, typename: &str) -> Option<(String, String)> {
+        let mut depth = 0;
+        for (i, c) in typename.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ if depth == 0 && typename[i..].starts_with(FUNCTION_RETURN_TYPE_SEPARATOR) => {
+                    let argument = strip_enclosing_parentheses(&typename[..i]);
+                    let return_type = strip_enclosing_parentheses(
+                        &typename[i + FUNCTION_RETURN_TYPE_SEPARATOR.len()..],
+                    );
+                    return Some((argument.to_string(), return_type.to_string()));
+                }
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Merges the symbols of `other` into this table. The name generator of `other` is kept as
+    /// it is assumed to have been derived from the name generator of this table.
+    pub fn merge(&mut self, other: SymbolTable) {
+        self.aliases.extend(other.aliases);
+        self.type_of_table.extend(other.type_of_table);
+        self.state_layout.extend(other.state_layout);
+        self.storage_classes.extend(other.storage_classes);
+        for namespace in other.imported_namespaces {
+            if !self.imported_namespaces.contains(&namespace) {
+                self.imported_namespaces.push(namespace);
+            }
+        }
+        self.name_generator = other.name_generator;
+    }
+
+    /// Returns how values of `typename` are stored.
+    pub fn storage_class(&self, typename: &str) -> StorageClass {
+        if self.map_key_value_types(typename).is_some() {
+            return StorageClass::mapping();
+        }
+        match self.storage_classes.get(typename) {
+            Some(class) => *class,
+            None => StorageClass::adt(),
+        }
+    }
+
     /// Declares an alias for a symbol.
     pub fn declare_alias(&mut self, alias: &str, symbol: &str) -> Result<String, String> {
         self.aliases.insert(alias.to_string(), symbol.to_string());
@@ -0,0 +1,1515 @@
+use std::collections::BTreeSet;
+
+use scilla_parser::parser::lexer::SourcePosition;
+
+use crate::intermediate_representation::{
+    primitives::{
+        ConcreteFunction, ConcreteType, ContractField, EnumValue, FieldAddress, FunctionBlock,
+        FunctionBody, FunctionKind, GlobalVariableDefition, Instruction,
+        IntermediateRepresentation, IrIdentifier, IrIndentifierKind, LambdaFunctionSingleArgument,
+        Operation, Tuple, VariableDeclaration, Variant,
+    },
+    symbol_table::SymbolTable,
+};
+
+// Textual syntax of the intermediate representation. Unlike the output of the `DebugPrinter`,
+// the syntax holds all information needed to reconstruct the IR apart from source locations
+// and the symbol table, which is rebuilt by the passes:
+//
+//     version "0"
+//     names 0 4 2 0
+//     imports BoolUtils
+//     type variant $Bool in @Bool { @True @False }
+//     field %owner{def} : $ByStr20 in @Wallet = literal $ByStr20 "0x1234"
+//     fn transition @Wallet::deposit in @Wallet (%amount{def} : $Uint128) {
+//       block :entry{def} {
+//         .exits_to exit
+//         %x{def} = call_external @builtin__add{kind = external_function} (%amount, %amount)
+//         jmp :exit
+//       }
+//       ...
+//     }
+//
+// Identifiers are written as a sigil giving their kind followed by their name and an optional
+// list of attributes: `@` functions and namespaces, `$` types, `:` block labels, `~` context
+// resources, `%` registers and memory, `#` state and `?` unknown. Names which are not made of
+// alphanumerics, `_`, `'`, `.` and `::` are quoted. Lines starting with `;` are comments.
+
+/// Attributes which may follow an identifier.
+const ATTRIBUTES: [&str; 4] = ["resolved", "type_ref", "kind", "def"];
+
+fn invalid_location() -> (SourcePosition, SourcePosition) {
+    (
+        SourcePosition::invalid_position(),
+        SourcePosition::invalid_position(),
+    )
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '\'' || c == '.'
+}
+
+fn is_sigil(c: char) -> bool {
+    matches!(c, '@' | '$' | ':' | '~' | '%' | '#' | '?')
+}
+
+fn sigil_of(kind: &IrIndentifierKind) -> char {
+    match kind {
+        IrIndentifierKind::FunctionName
+        | IrIndentifierKind::StaticFunctionName
+        | IrIndentifierKind::TransitionName
+        | IrIndentifierKind::ProcedureName
+        | IrIndentifierKind::TemplateFunctionName
+        | IrIndentifierKind::ExternalFunctionName
+        | IrIndentifierKind::ComponentName
+        | IrIndentifierKind::Event
+        | IrIndentifierKind::Namespace => '@',
+        IrIndentifierKind::TypeLikeName(_)
+        | IrIndentifierKind::TypeName
+        | IrIndentifierKind::TemplateTypeName(_) => '$',
+        IrIndentifierKind::BlockLabel => ':',
+        IrIndentifierKind::ContextResource => '~',
+        IrIndentifierKind::VirtualRegister
+        | IrIndentifierKind::VirtualRegisterIntermediate
+        | IrIndentifierKind::Memory => '%',
+        IrIndentifierKind::State => '#',
+        IrIndentifierKind::Unknown => '?',
+    }
+}
+
+/// Kind of an identifier written with `sigil` and without a `kind` attribute.
+fn default_kind(sigil: char) -> IrIndentifierKind {
+    match sigil {
+        '@' => IrIndentifierKind::FunctionName,
+        '$' => IrIndentifierKind::TypeName,
+        ':' => IrIndentifierKind::BlockLabel,
+        '~' => IrIndentifierKind::ContextResource,
+        '%' => IrIndentifierKind::VirtualRegister,
+        '#' => IrIndentifierKind::State,
+        _ => IrIndentifierKind::Unknown,
+    }
+}
+
+/// Writes `name` bare if it can be read back as a single word and quoted otherwise.
+fn format_name(name: &str) -> String {
+    let mut bare = name.chars().next().map_or(false, is_name_char) && !name.starts_with('.');
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c == ':' {
+            bare = bare && chars.next() == Some(':');
+        } else {
+            bare = bare && is_name_char(c);
+        }
+    }
+
+    if bare {
+        name.to_string()
+    } else {
+        format!("{:?}", name)
+    }
+}
+
+fn format_kind(kind: &IrIndentifierKind) -> String {
+    match kind {
+        IrIndentifierKind::FunctionName => "function".to_string(),
+        IrIndentifierKind::StaticFunctionName => "static_function".to_string(),
+        IrIndentifierKind::TransitionName => "transition".to_string(),
+        IrIndentifierKind::ProcedureName => "procedure".to_string(),
+        IrIndentifierKind::TemplateFunctionName => "template_function".to_string(),
+        IrIndentifierKind::ExternalFunctionName => "external_function".to_string(),
+        IrIndentifierKind::TypeLikeName(arguments) => {
+            format!("type_like{}", format_list(arguments, '(', ')'))
+        }
+        IrIndentifierKind::TypeName => "type".to_string(),
+        IrIndentifierKind::TemplateTypeName(arguments) => {
+            format!("template_type{}", format_list(arguments, '(', ')'))
+        }
+        IrIndentifierKind::ComponentName => "component".to_string(),
+        IrIndentifierKind::Event => "event".to_string(),
+        IrIndentifierKind::Namespace => "namespace".to_string(),
+        IrIndentifierKind::BlockLabel => "label".to_string(),
+        IrIndentifierKind::ContextResource => "context_resource".to_string(),
+        IrIndentifierKind::VirtualRegister => "register".to_string(),
+        IrIndentifierKind::VirtualRegisterIntermediate => "intermediate".to_string(),
+        IrIndentifierKind::Memory => "memory".to_string(),
+        IrIndentifierKind::State => "state".to_string(),
+        IrIndentifierKind::Unknown => "unknown".to_string(),
+    }
+}
+
+fn format_identifier(identifier: &IrIdentifier) -> String {
+    let sigil = sigil_of(&identifier.kind);
+    let mut ret = format!("{}{}", sigil, format_name(&identifier.unresolved));
+
+    let mut attributes = Vec::new();
+    if let Some(resolved) = &identifier.resolved {
+        attributes.push(format!("resolved = {}", format_name(resolved)));
+    }
+    if let Some(type_reference) = &identifier.type_reference {
+        attributes.push(format!("type_ref = {}", format_name(type_reference)));
+    }
+    if identifier.kind != default_kind(sigil) {
+        attributes.push(format!("kind = {}", format_kind(&identifier.kind)));
+    }
+    if identifier.is_definition {
+        attributes.push("def".to_string());
+    }
+
+    if !attributes.is_empty() {
+        ret.push_str(&format!("{{{}}}", attributes.join(", ")));
+    }
+    ret
+}
+
+fn format_list(identifiers: &[IrIdentifier], open: char, close: char) -> String {
+    let identifiers: Vec<String> = identifiers.iter().map(format_identifier).collect();
+    format!("{}{}{}", open, identifiers.join(", "), close)
+}
+
+fn format_variable_declaration(declaration: &VariableDeclaration) -> String {
+    format!(
+        "{}{} : {}",
+        if declaration.mutable { "mut " } else { "" },
+        format_identifier(&declaration.name),
+        format_identifier(&declaration.typename)
+    )
+}
+
+fn format_field_address(address: &FieldAddress) -> String {
+    match &address.value {
+        Some(value) => format!(
+            "{} at 0x{}",
+            format_identifier(&address.name),
+            hex::encode(value)
+        ),
+        None => format_identifier(&address.name),
+    }
+}
+
+fn format_operation(operation: &Operation) -> String {
+    match operation {
+        Operation::Noop => "noop".to_string(),
+        Operation::TerminatingRef(value) => {
+            format!("terminating_ref {}", format_identifier(value))
+        }
+        Operation::Jump(label) => format!("jmp {}", format_identifier(label)),
+        Operation::ConditionalJump {
+            expression,
+            on_success,
+            on_failure,
+        } => format!(
+            "jmp_if {} {} {}",
+            format_identifier(expression),
+            format_identifier(on_success),
+            format_identifier(on_failure)
+        ),
+        Operation::MemLoad => "mload".to_string(),
+        Operation::MemStore => "mstore".to_string(),
+        Operation::StateLoad { address } => format!("sload {}", format_field_address(address)),
+        Operation::StateStore { address, value } => format!(
+            "sstore {} {}",
+            format_field_address(address),
+            format_identifier(value)
+        ),
+        Operation::StateRemoteLoad { contract, field } => format!(
+            "sload_remote {} {}",
+            format_identifier(contract),
+            format_name(field)
+        ),
+        Operation::StateMapLoad { address, keys } => format!(
+            "sload_map {} {}",
+            format_field_address(address),
+            format_list(keys, '(', ')')
+        ),
+        Operation::StateMapExists { address, keys } => format!(
+            "sexists_map {} {}",
+            format_field_address(address),
+            format_list(keys, '(', ')')
+        ),
+        Operation::StateMapStore {
+            address,
+            keys,
+            value,
+        } => format!(
+            "sstore_map {} {} {}",
+            format_field_address(address),
+            format_list(keys, '(', ')'),
+            format_identifier(value)
+        ),
+        Operation::StateMapDelete { address, keys } => format!(
+            "sdelete_map {} {}",
+            format_field_address(address),
+            format_list(keys, '(', ')')
+        ),
+        Operation::IsEqual { left, right } => format!(
+            "eq {} {}",
+            format_identifier(left),
+            format_identifier(right)
+        ),
+        Operation::CallExternalFunction { name, arguments } => format!(
+            "call_external {} {}",
+            format_identifier(name),
+            format_list(arguments, '(', ')')
+        ),
+        Operation::CallFunction { name, arguments } => format!(
+            "call {} {}",
+            format_identifier(name),
+            format_list(arguments, '(', ')')
+        ),
+        Operation::CallStaticFunction {
+            name,
+            owner,
+            template_type_arguments,
+            arguments,
+        } => {
+            let mut ret = format!("call_static {}", format_identifier(name));
+            if let Some(owner) = owner {
+                ret.push_str(&format!(" of {}", format_identifier(owner)));
+            }
+            if !template_type_arguments.is_empty() {
+                ret.push_str(&format!(
+                    " {}",
+                    format_list(template_type_arguments, '<', '>')
+                ));
+            }
+            ret.push_str(&format!(" {}", format_list(arguments, '(', ')')));
+            ret
+        }
+        Operation::CallMemberFunction {
+            name,
+            owner,
+            arguments,
+        } => {
+            let mut ret = format!("call_member {}", format_identifier(name));
+            if let Some(owner) = owner {
+                ret.push_str(&format!(" of {}", format_identifier(owner)));
+            }
+            ret.push_str(&format!(" {}", format_list(arguments, '(', ')')));
+            ret
+        }
+        Operation::ResolveSymbol { symbol } => format!("resolve {}", format_identifier(symbol)),
+        Operation::ResolveContextResource { symbol } => {
+            format!("resolve_context {}", format_identifier(symbol))
+        }
+        Operation::Literal { data, typename } => {
+            format!("literal {} {:?}", format_identifier(typename), data)
+        }
+        Operation::ConstructMessage { entries } => {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(field, value)| {
+                    format!("{} = {}", format_name(field), format_identifier(value))
+                })
+                .collect();
+            format!("message ({})", entries.join(", "))
+        }
+        Operation::EmitEvent { message } => format!("event {}", format_identifier(message)),
+        Operation::SendMessage { message } => format!("send {}", format_identifier(message)),
+        Operation::CreateClosure { function, captures } => format!(
+            "closure {} {}",
+            format_identifier(function),
+            format_list(captures, '(', ')')
+        ),
+        Operation::LoadCapture { closure, index } => {
+            format!("capture {} {}", format_identifier(closure), index)
+        }
+        Operation::ApplyClosure { closure, argument } => format!(
+            "apply {} {}",
+            format_identifier(closure),
+            format_identifier(argument)
+        ),
+        Operation::InstantiateTemplate {
+            template,
+            type_arguments,
+        } => format!(
+            "instantiate {} {}",
+            format_identifier(template),
+            format_list(type_arguments, '<', '>')
+        ),
+        Operation::InstantiatePrimitive {
+            primitive,
+            type_arguments,
+        } => format!(
+            "primitive {} {}",
+            format_identifier(primitive),
+            format_list(type_arguments, '<', '>')
+        ),
+        Operation::LoadConstructorArgument {
+            value,
+            constructor,
+            index,
+        } => format!(
+            "argument {} of {} {}",
+            format_identifier(value),
+            format_identifier(constructor),
+            index
+        ),
+        Operation::PhiNode(values) => format!("phi {}", format_list(values, '(', ')')),
+        Operation::Return(value) => match value {
+            Some(value) => format!("return {}", format_identifier(value)),
+            None => "return".to_string(),
+        },
+        Operation::Revert(value) => match value {
+            Some(value) => format!("revert {}", format_identifier(value)),
+            None => "revert".to_string(),
+        },
+    }
+}
+
+fn format_instruction(instr: &Instruction) -> String {
+    let mut ret = String::new();
+    if let Some(ssa_name) = &instr.ssa_name {
+        ret.push_str(&format!("{} = ", format_identifier(ssa_name)));
+    }
+    ret.push_str(&format_operation(&instr.operation));
+    if let Some(result_type) = &instr.result_type {
+        ret.push_str(&format!(" : {}", format_identifier(result_type)));
+    }
+    ret
+}
+
+/// Prints the IR in the textual syntax read by `IrParser`.
+pub struct IrPrinter {
+    script: String,
+}
+
+impl IrPrinter {
+    pub fn new() -> Self {
+        IrPrinter {
+            script: "".to_string(),
+        }
+    }
+
+    pub fn print(&mut self, ir: &IntermediateRepresentation) -> String {
+        self.script = "".to_string();
+
+        self.script.push_str(&format!("version {:?}\n", ir.version));
+        let counters = ir.symbol_table.name_generator.counters();
+        self.script.push_str(&format!(
+            "names {} {} {} {}\n",
+            counters[0], counters[1], counters[2], counters[3]
+        ));
+        if !ir.symbol_table.imported_namespaces.is_empty() {
+            let namespaces: Vec<String> = ir
+                .symbol_table
+                .imported_namespaces
+                .iter()
+                .map(|namespace| format_name(namespace))
+                .collect();
+            self.script
+                .push_str(&format!("imports {}\n", namespaces.join(" ")));
+        }
+
+        for con_type in ir.type_definitions.iter() {
+            self.print_concrete_type(con_type);
+        }
+        for global in ir.global_variables.iter() {
+            self.print_global(global);
+        }
+        if !ir.global_init_block.instructions.is_empty() {
+            self.print_block(&ir.global_init_block, "");
+        }
+        for field in ir.fields_definitions.iter() {
+            self.print_field(field);
+        }
+        for lambda in ir.lambda_functions.iter() {
+            self.print_lambda(lambda);
+        }
+        for function in ir.function_definitions.iter() {
+            self.print_function(function);
+        }
+
+        self.script.clone()
+    }
+
+    fn print_concrete_type(&mut self, con_type: &ConcreteType) {
+        match con_type {
+            ConcreteType::Tuple {
+                name,
+                namespace,
+                data_layout,
+            } => {
+                self.script.push_str(&format!(
+                    "type tuple {} in {} {}\n",
+                    format_identifier(name),
+                    format_identifier(namespace),
+                    format_list(&data_layout.fields, '(', ')')
+                ));
+            }
+            ConcreteType::Variant {
+                name,
+                namespace,
+                data_layout,
+            } => {
+                self.script.push_str(&format!(
+                    "type variant {} in {} {{\n",
+                    format_identifier(name),
+                    format_identifier(namespace)
+                ));
+                for field in data_layout.fields.iter() {
+                    self.script
+                        .push_str(&format!("  {}", format_identifier(&field.name)));
+                    if let Some(data) = &field.data {
+                        self.script
+                            .push_str(&format!(" : {}", format_identifier(data)));
+                    }
+                    self.script.push_str("\n");
+                }
+                self.script.push_str("}\n");
+            }
+        }
+    }
+
+    fn print_global(&mut self, global: &GlobalVariableDefition) {
+        self.script
+            .push_str(&format!("global {}", format_identifier(&global.name)));
+        if let Some(typename) = &global.typename {
+            self.script
+                .push_str(&format!(" : {}", format_identifier(typename)));
+        }
+        self.script.push_str(&format!(
+            " in {} = {}\n",
+            format_identifier(&global.namespace),
+            format_instruction(&global.value)
+        ));
+    }
+
+    fn print_field(&mut self, field: &ContractField) {
+        self.script.push_str(&format!(
+            "field {}{} in {} = {}\n",
+            if field.immutable { "immutable " } else { "" },
+            format_variable_declaration(&field.variable),
+            format_identifier(&field.namespace),
+            format_instruction(&field.initializer)
+        ));
+    }
+
+    fn print_lambda(&mut self, lambda: &LambdaFunctionSingleArgument) {
+        let environment: Vec<String> = lambda
+            .environment
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("{} = {}", format_name(name), format_identifier(value)),
+                None => format_name(name),
+            })
+            .collect();
+
+        self.script.push_str(&format!(
+            "lambda {} in {} capture {} environment ({})",
+            format_identifier(&lambda.name),
+            format_identifier(&lambda.namespace),
+            format_list(&lambda.capture.fields, '(', ')'),
+            environment.join(", ")
+        ));
+        if let Some(argument) = &lambda.argument {
+            self.script.push_str(&format!(
+                " argument {}",
+                format_variable_declaration(argument)
+            ));
+        }
+        if let Some(type_argument) = &lambda.type_argument {
+            self.script
+                .push_str(&format!(" type {}", format_name(type_argument)));
+        }
+        if let Some(return_type) = &lambda.return_type {
+            self.script
+                .push_str(&format!(" -> {}", format_name(return_type)));
+        }
+        self.script.push_str(" {\n");
+        self.print_body(&lambda.body);
+        self.script.push_str("}\n");
+    }
+
+    fn print_function(&mut self, function: &ConcreteFunction) {
+        let kind = match function.function_kind {
+            FunctionKind::Procedure => "procedure",
+            FunctionKind::Transition => "transition",
+            FunctionKind::Function => "function",
+            FunctionKind::Lambda => "lambda",
+            FunctionKind::Constructor => "constructor",
+        };
+        let arguments: Vec<String> = function
+            .arguments
+            .iter()
+            .map(format_variable_declaration)
+            .collect();
+
+        self.script.push_str(&format!(
+            "fn {} {} in {} ({})",
+            kind,
+            format_identifier(&function.name),
+            format_identifier(&function.namespace),
+            arguments.join(", ")
+        ));
+        if let Some(return_type) = &function.return_type {
+            self.script
+                .push_str(&format!(" -> {}", format_name(return_type)));
+        }
+        self.script.push_str(" {\n");
+        self.print_body(&function.body);
+        self.script.push_str("}\n");
+    }
+
+    fn print_body(&mut self, body: &FunctionBody) {
+        for block in body.blocks.iter() {
+            self.print_block(block, "  ");
+        }
+    }
+
+    fn print_block(&mut self, block: &FunctionBlock, indent: &str) {
+        self.script.push_str(&format!(
+            "{}block {} {{\n",
+            indent,
+            format_identifier(&block.name)
+        ));
+
+        let mut directives: Vec<(String, Vec<&String>)> = Vec::new();
+        directives.push((".args".to_string(), block.block_arguments.iter().collect()));
+        directives.push((
+            ".enters_from".to_string(),
+            block.enters_from.iter().collect(),
+        ));
+        directives.push((".exits_to".to_string(), block.exits_to.iter().collect()));
+        directives.push((".defines".to_string(), block.defined_ssas.iter().collect()));
+        for (directive, names) in directives {
+            if names.is_empty() {
+                continue;
+            }
+            let names: Vec<String> = names.into_iter().map(|name| format_name(name)).collect();
+            self.script
+                .push_str(&format!("{}  {} {}\n", indent, directive, names.join(" ")));
+        }
+
+        // Sorted such that the output does not depend on the order of the hash map
+        let mut targets: Vec<&String> = block.jump_required_arguments.keys().collect();
+        targets.sort();
+        for target in targets {
+            self.script
+                .push_str(&format!("{}  .requires {}", indent, format_name(target)));
+            for argument in block.jump_required_arguments[target].iter() {
+                self.script.push_str(&format!(" {}", format_name(argument)));
+            }
+            self.script.push_str("\n");
+        }
+
+        if block.terminated {
+            self.script.push_str(&format!("{}  .terminated\n", indent));
+        }
+
+        for instr in block.instructions.iter() {
+            self.script
+                .push_str(&format!("{}  {}\n", indent, format_instruction(instr)));
+        }
+        self.script.push_str(&format!("{}}}\n", indent));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Str(String),
+    Identifier(char, String),
+    Punctuation(char),
+    Arrow,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match &self.kind {
+            TokenKind::Word(word) => format!("`{}`", word),
+            TokenKind::Str(value) => format!("{:?}", value),
+            TokenKind::Identifier(sigil, name) => format!("`{}{}`", sigil, format_name(name)),
+            TokenKind::Punctuation(c) => format!("`{}`", c),
+            TokenKind::Arrow => "`->`".to_string(),
+        }
+    }
+}
+
+fn read_string(chars: &[char], i: &mut usize, line: usize) -> Result<String, String> {
+    // Skipping the opening quote
+    *i += 1;
+    let mut ret = String::new();
+    loop {
+        let c = match chars.get(*i) {
+            Some(c) => *c,
+            None => return Err(format!("Line {}: Unterminated string", line)),
+        };
+        *i += 1;
+        match c {
+            '"' => return Ok(ret),
+            '\\' => {
+                let escaped = match chars.get(*i) {
+                    Some(c) => *c,
+                    None => return Err(format!("Line {}: Unterminated string", line)),
+                };
+                *i += 1;
+                match escaped {
+                    'n' => ret.push('\n'),
+                    'r' => ret.push('\r'),
+                    't' => ret.push('\t'),
+                    '0' => ret.push('\0'),
+                    '\\' | '"' | '\'' => ret.push(escaped),
+                    'u' => {
+                        let end = chars[*i..]
+                            .iter()
+                            .position(|c| *c == '}')
+                            .ok_or(format!("Line {}: Invalid unicode escape", line))?;
+                        let digits: String = chars[*i + 1..*i + end].iter().collect();
+                        let value = u32::from_str_radix(&digits, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or(format!("Line {}: Invalid unicode escape", line))?;
+                        ret.push(value);
+                        *i += end + 1;
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Line {}: Invalid escape sequence `\\{}`",
+                            line, escaped
+                        ))
+                    }
+                }
+            }
+            _ => ret.push(c),
+        }
+    }
+}
+
+fn read_bare_name(chars: &[char], i: &mut usize) -> String {
+    let mut ret = String::new();
+    while let Some(c) = chars.get(*i) {
+        if is_name_char(*c) {
+            ret.push(*c);
+            *i += 1;
+        } else if *c == ':' && chars.get(*i + 1) == Some(&':') {
+            ret.push_str("::");
+            *i += 2;
+        } else {
+            break;
+        }
+    }
+    ret
+}
+
+fn tokenize(script: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut ret = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while let Some(c) = chars.get(i).copied() {
+        let next = chars.get(i + 1).copied();
+        let kind = if c == '\n' {
+            line += 1;
+            i += 1;
+            continue;
+        } else if c.is_whitespace() {
+            i += 1;
+            continue;
+        } else if c == ';' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        } else if c == '"' {
+            TokenKind::Str(read_string(&chars, &mut i, line)?)
+        } else if is_sigil(c) && next == Some('"') {
+            i += 1;
+            TokenKind::Identifier(c, read_string(&chars, &mut i, line)?)
+        } else if is_sigil(c) && next.map_or(false, is_name_char) {
+            i += 1;
+            TokenKind::Identifier(c, read_bare_name(&chars, &mut i))
+        } else if c == '-' && next == Some('>') {
+            i += 2;
+            TokenKind::Arrow
+        } else if is_name_char(c) {
+            TokenKind::Word(read_bare_name(&chars, &mut i))
+        } else if "(){}<>,=:".contains(c) {
+            i += 1;
+            TokenKind::Punctuation(c)
+        } else {
+            return Err(format!("Line {}: Unexpected character `{}`", line, c));
+        };
+
+        ret.push(Token { kind, line });
+    }
+
+    Ok(ret)
+}
+
+/// Reads the IR from the textual syntax written by `IrPrinter`. Source locations are not part
+/// of the syntax and are left invalid.
+pub struct IrParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl IrParser {
+    pub fn new() -> Self {
+        IrParser {
+            tokens: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Parses `script` into an IR holding `symbol_table`. The counters of the name generator are
+    /// restored from the script, such that fresh names do not collide with existing ones.
+    pub fn parse(
+        &mut self,
+        script: &str,
+        symbol_table: SymbolTable,
+    ) -> Result<IntermediateRepresentation, String> {
+        self.tokens = tokenize(script)?;
+        self.position = 0;
+
+        let mut ir = IntermediateRepresentation::new(symbol_table);
+        while let Some(token) = self.peek() {
+            let keyword = match &token.kind {
+                TokenKind::Word(word) => word.clone(),
+                _ => return Err(self.unexpected("a declaration")),
+            };
+
+            match &keyword[..] {
+                "version" => {
+                    self.advance();
+                    ir.version = self.parse_string()?;
+                }
+                "names" => {
+                    self.advance();
+                    let mut counters = [0u64; 4];
+                    for counter in counters.iter_mut() {
+                        *counter = self.parse_number()?;
+                    }
+                    ir.symbol_table.name_generator.set_counters(counters);
+                }
+                "imports" => {
+                    let line = self.current_line();
+                    self.advance();
+                    while let Some(TokenKind::Word(_)) | Some(TokenKind::Str(_)) =
+                        self.peek_on_line(line)
+                    {
+                        let namespace = self.parse_name()?;
+                        ir.symbol_table.imported_namespaces.push(namespace);
+                    }
+                }
+                "type" => ir.type_definitions.push(self.parse_concrete_type()?),
+                "global" => ir.global_variables.push(self.parse_global()?),
+                "block" => ir.global_init_block = self.parse_block()?,
+                "field" => ir.fields_definitions.push(self.parse_field()?),
+                "lambda" => ir.lambda_functions.push(self.parse_lambda()?),
+                "fn" => ir.function_definitions.push(self.parse_function()?),
+                _ => return Err(self.unexpected("a declaration")),
+            }
+        }
+
+        Ok(ir)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_kind(&self) -> Option<&TokenKind> {
+        self.peek().map(|token| &token.kind)
+    }
+
+    /// Returns the next token if it is on `line`. Used for operands which may be omitted at the
+    /// end of an instruction.
+    fn peek_on_line(&self, line: usize) -> Option<&TokenKind> {
+        match self.peek() {
+            Some(token) if token.line == line => Some(&token.kind),
+            _ => None,
+        }
+    }
+
+    fn current_line(&self) -> usize {
+        match self.peek() {
+            Some(token) => token.line,
+            None => self.tokens.last().map_or(1, |token| token.line),
+        }
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let ret = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        ret
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(token) => format!(
+                "Line {}: Expected {}, found {}",
+                token.line,
+                expected,
+                token.describe()
+            ),
+            None => format!(
+                "Line {}: Expected {}, found end of input",
+                self.current_line(),
+                expected
+            ),
+        }
+    }
+
+    fn is_punctuation(&self, c: char) -> bool {
+        self.peek_kind() == Some(&TokenKind::Punctuation(c))
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek_kind(), Some(TokenKind::Word(w)) if w == word)
+    }
+
+    fn expect_punctuation(&mut self, c: char) -> Result<(), String> {
+        if !self.is_punctuation(c) {
+            return Err(self.unexpected(&format!("`{}`", c)));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<(), String> {
+        if !self.is_word(word) {
+            return Err(self.unexpected(&format!("`{}`", word)));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    /// Parses an optional `-> name`.
+    fn parse_optional_return_type(&mut self) -> Result<Option<String>, String> {
+        if self.peek_kind() == Some(&TokenKind::Arrow) {
+            self.advance();
+            Ok(Some(self.parse_name()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_word(&mut self) -> Result<String, String> {
+        match self.peek_kind() {
+            Some(TokenKind::Word(word)) => {
+                let word = word.clone();
+                self.advance();
+                Ok(word)
+            }
+            _ => Err(self.unexpected("a word")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        match self.peek_kind() {
+            Some(TokenKind::Str(value)) => {
+                let value = value.clone();
+                self.advance();
+                Ok(value)
+            }
+            _ => Err(self.unexpected("a string")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<u64, String> {
+        let line = self.current_line();
+        let word = self.parse_word()?;
+        word.parse::<u64>()
+            .map_err(|_| format!("Line {}: Expected a number, found `{}`", line, word))
+    }
+
+    fn parse_name(&mut self) -> Result<String, String> {
+        match self.peek_kind() {
+            Some(TokenKind::Word(_)) => self.parse_word(),
+            Some(TokenKind::Str(_)) => self.parse_string(),
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<IrIdentifier, String> {
+        let (sigil, name) = match self.peek_kind() {
+            Some(TokenKind::Identifier(sigil, name)) => (*sigil, name.clone()),
+            _ => return Err(self.unexpected("an identifier")),
+        };
+        self.advance();
+
+        let mut ret = IrIdentifier::new(name, default_kind(sigil), invalid_location());
+
+        // Attributes are told apart from bodies following the identifier by their first word
+        let has_attributes = self.is_punctuation('{')
+            && matches!(
+                self.tokens.get(self.position + 1).map(|token| &token.kind),
+                Some(TokenKind::Word(word)) if ATTRIBUTES.contains(&&word[..])
+            );
+        if !has_attributes {
+            return Ok(ret);
+        }
+
+        self.expect_punctuation('{')?;
+        loop {
+            let attribute = self.parse_word()?;
+            match &attribute[..] {
+                "resolved" => {
+                    self.expect_punctuation('=')?;
+                    ret.resolved = Some(self.parse_name()?);
+                }
+                "type_ref" => {
+                    self.expect_punctuation('=')?;
+                    ret.type_reference = Some(self.parse_name()?);
+                }
+                "kind" => {
+                    self.expect_punctuation('=')?;
+                    ret.kind = self.parse_kind()?;
+                }
+                "def" => ret.is_definition = true,
+                _ => {
+                    return Err(format!(
+                        "Line {}: Unknown attribute `{}`",
+                        self.current_line(),
+                        attribute
+                    ))
+                }
+            }
+
+            if self.is_punctuation(',') {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        self.expect_punctuation('}')?;
+
+        Ok(ret)
+    }
+
+    fn parse_kind(&mut self) -> Result<IrIndentifierKind, String> {
+        let line = self.current_line();
+        let kind = self.parse_word()?;
+        let ret = match &kind[..] {
+            "function" => IrIndentifierKind::FunctionName,
+            "static_function" => IrIndentifierKind::StaticFunctionName,
+            "transition" => IrIndentifierKind::TransitionName,
+            "procedure" => IrIndentifierKind::ProcedureName,
+            "template_function" => IrIndentifierKind::TemplateFunctionName,
+            "external_function" => IrIndentifierKind::ExternalFunctionName,
+            "type_like" => IrIndentifierKind::TypeLikeName(self.parse_list('(', ')')?),
+            "type" => IrIndentifierKind::TypeName,
+            "template_type" => IrIndentifierKind::TemplateTypeName(self.parse_list('(', ')')?),
+            "component" => IrIndentifierKind::ComponentName,
+            "event" => IrIndentifierKind::Event,
+            "namespace" => IrIndentifierKind::Namespace,
+            "label" => IrIndentifierKind::BlockLabel,
+            "context_resource" => IrIndentifierKind::ContextResource,
+            "register" => IrIndentifierKind::VirtualRegister,
+            "intermediate" => IrIndentifierKind::VirtualRegisterIntermediate,
+            "memory" => IrIndentifierKind::Memory,
+            "state" => IrIndentifierKind::State,
+            "unknown" => IrIndentifierKind::Unknown,
+            _ => return Err(format!("Line {}: Unknown identifier kind `{}`", line, kind)),
+        };
+        Ok(ret)
+    }
+
+    fn parse_list(&mut self, open: char, close: char) -> Result<Vec<IrIdentifier>, String> {
+        self.expect_punctuation(open)?;
+        let mut ret = Vec::new();
+        while !self.is_punctuation(close) {
+            ret.push(self.parse_identifier()?);
+            if self.is_punctuation(',') {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        self.expect_punctuation(close)?;
+        Ok(ret)
+    }
+
+    fn parse_variable_declaration(&mut self) -> Result<VariableDeclaration, String> {
+        let mutable = self.is_word("mut");
+        if mutable {
+            self.advance();
+        }
+        let name = self.parse_identifier()?;
+        self.expect_punctuation(':')?;
+        let typename = self.parse_identifier()?;
+
+        Ok(VariableDeclaration {
+            name,
+            typename,
+            mutable,
+        })
+    }
+
+    fn parse_field_address(&mut self) -> Result<FieldAddress, String> {
+        let name = self.parse_identifier()?;
+        let value = if self.is_word("at") {
+            self.advance();
+            let line = self.current_line();
+            let word = self.parse_word()?;
+            let digits = word.strip_prefix("0x").unwrap_or(&word);
+            Some(
+                hex::decode(digits)
+                    .map_err(|_| format!("Line {}: Invalid field address `{}`", line, word))?,
+            )
+        } else {
+            None
+        };
+
+        Ok(FieldAddress { name, value })
+    }
+
+    fn parse_instruction(&mut self) -> Result<Box<Instruction>, String> {
+        let is_assignment = matches!(self.peek_kind(), Some(TokenKind::Identifier(_, _)))
+            && matches!(
+                self.tokens.get(self.position + 1).map(|token| &token.kind),
+                Some(TokenKind::Punctuation('='))
+            );
+        let ssa_name = if is_assignment {
+            let ssa_name = self.parse_identifier()?;
+            self.expect_punctuation('=')?;
+            Some(ssa_name)
+        } else {
+            None
+        };
+
+        let line = self.current_line();
+        let operation = self.parse_operation()?;
+        let result_type = if self.peek_on_line(line) == Some(&TokenKind::Punctuation(':')) {
+            self.advance();
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+
+        Ok(Box::new(Instruction {
+            ssa_name,
+            result_type,
+            operation,
+            source_location: invalid_location(),
+        }))
+    }
+
+    /// Parses an optional operand, which must be on the same line as the operation.
+    fn parse_optional_operand(&mut self, line: usize) -> Result<Option<IrIdentifier>, String> {
+        match self.peek_on_line(line) {
+            Some(TokenKind::Identifier(_, _)) => Ok(Some(self.parse_identifier()?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_operation(&mut self) -> Result<Operation, String> {
+        let line = self.current_line();
+        let keyword = match self.peek_kind() {
+            Some(TokenKind::Word(word)) => word.clone(),
+            _ => return Err(self.unexpected("an operation")),
+        };
+        self.advance();
+
+        let ret = match &keyword[..] {
+            "noop" => Operation::Noop,
+            "terminating_ref" => Operation::TerminatingRef(self.parse_identifier()?),
+            "jmp" => Operation::Jump(self.parse_identifier()?),
+            "jmp_if" => Operation::ConditionalJump {
+                expression: self.parse_identifier()?,
+                on_success: self.parse_identifier()?,
+                on_failure: self.parse_identifier()?,
+            },
+            "mload" => Operation::MemLoad,
+            "mstore" => Operation::MemStore,
+            "sload" => Operation::StateLoad {
+                address: self.parse_field_address()?,
+            },
+            "sstore" => Operation::StateStore {
+                address: self.parse_field_address()?,
+                value: self.parse_identifier()?,
+            },
+            "sload_remote" => Operation::StateRemoteLoad {
+                contract: self.parse_identifier()?,
+                field: self.parse_name()?,
+            },
+            "sload_map" => Operation::StateMapLoad {
+                address: self.parse_field_address()?,
+                keys: self.parse_list('(', ')')?,
+            },
+            "sexists_map" => Operation::StateMapExists {
+                address: self.parse_field_address()?,
+                keys: self.parse_list('(', ')')?,
+            },
+            "sstore_map" => Operation::StateMapStore {
+                address: self.parse_field_address()?,
+                keys: self.parse_list('(', ')')?,
+                value: self.parse_identifier()?,
+            },
+            "sdelete_map" => Operation::StateMapDelete {
+                address: self.parse_field_address()?,
+                keys: self.parse_list('(', ')')?,
+            },
+            "eq" => Operation::IsEqual {
+                left: self.parse_identifier()?,
+                right: self.parse_identifier()?,
+            },
+            "call_external" => Operation::CallExternalFunction {
+                name: self.parse_identifier()?,
+                arguments: self.parse_list('(', ')')?,
+            },
+            "call" => Operation::CallFunction {
+                name: self.parse_identifier()?,
+                arguments: self.parse_list('(', ')')?,
+            },
+            "call_static" => {
+                let name = self.parse_identifier()?;
+                let owner = self.parse_optional_owner()?;
+                let template_type_arguments = if self.is_punctuation('<') {
+                    self.parse_list('<', '>')?
+                } else {
+                    Vec::new()
+                };
+                Operation::CallStaticFunction {
+                    name,
+                    owner,
+                    template_type_arguments,
+                    arguments: self.parse_list('(', ')')?,
+                }
+            }
+            "call_member" => Operation::CallMemberFunction {
+                name: self.parse_identifier()?,
+                owner: self.parse_optional_owner()?,
+                arguments: self.parse_list('(', ')')?,
+            },
+            "resolve" => Operation::ResolveSymbol {
+                symbol: self.parse_identifier()?,
+            },
+            "resolve_context" => Operation::ResolveContextResource {
+                symbol: self.parse_identifier()?,
+            },
+            "literal" => Operation::Literal {
+                typename: self.parse_identifier()?,
+                data: self.parse_string()?,
+            },
+            "message" => {
+                self.expect_punctuation('(')?;
+                let mut entries = Vec::new();
+                while !self.is_punctuation(')') {
+                    let field = self.parse_name()?;
+                    self.expect_punctuation('=')?;
+                    entries.push((field, self.parse_identifier()?));
+                    if self.is_punctuation(',') {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect_punctuation(')')?;
+                Operation::ConstructMessage { entries }
+            }
+            "event" => Operation::EmitEvent {
+                message: self.parse_identifier()?,
+            },
+            "send" => Operation::SendMessage {
+                message: self.parse_identifier()?,
+            },
+            "closure" => Operation::CreateClosure {
+                function: self.parse_identifier()?,
+                captures: self.parse_list('(', ')')?,
+            },
+            "capture" => Operation::LoadCapture {
+                closure: self.parse_identifier()?,
+                index: self.parse_number()? as usize,
+            },
+            "apply" => Operation::ApplyClosure {
+                closure: self.parse_identifier()?,
+                argument: self.parse_identifier()?,
+            },
+            "instantiate" => Operation::InstantiateTemplate {
+                template: self.parse_identifier()?,
+                type_arguments: self.parse_list('<', '>')?,
+            },
+            "primitive" => Operation::InstantiatePrimitive {
+                primitive: self.parse_identifier()?,
+                type_arguments: self.parse_list('<', '>')?,
+            },
+            "argument" => {
+                let value = self.parse_identifier()?;
+                self.expect_word("of")?;
+                Operation::LoadConstructorArgument {
+                    value,
+                    constructor: self.parse_identifier()?,
+                    index: self.parse_number()? as usize,
+                }
+            }
+            "phi" => Operation::PhiNode(self.parse_list('(', ')')?),
+            "return" => Operation::Return(self.parse_optional_operand(line)?),
+            "revert" => Operation::Revert(self.parse_optional_operand(line)?),
+            _ => return Err(format!("Line {}: Unknown operation `{}`", line, keyword)),
+        };
+
+        Ok(ret)
+    }
+
+    fn parse_optional_owner(&mut self) -> Result<Option<IrIdentifier>, String> {
+        if self.is_word("of") {
+            self.advance();
+            Ok(Some(self.parse_identifier()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_block(&mut self) -> Result<Box<FunctionBlock>, String> {
+        self.expect_word("block")?;
+        let mut ret = FunctionBlock::new_from_symbol(self.parse_identifier()?);
+        self.expect_punctuation('{')?;
+
+        loop {
+            let directive = match self.peek_kind() {
+                Some(TokenKind::Punctuation('}')) => break,
+                Some(TokenKind::Word(word)) if word.starts_with('.') => word.clone(),
+                _ => {
+                    ret.instructions.push_back(self.parse_instruction()?);
+                    continue;
+                }
+            };
+
+            let line = self.current_line();
+            self.advance();
+            let mut names = Vec::new();
+            while let Some(TokenKind::Word(_)) | Some(TokenKind::Str(_)) = self.peek_on_line(line) {
+                names.push(self.parse_name()?);
+            }
+
+            match &directive[..] {
+                ".args" => ret.block_arguments.extend(names),
+                ".enters_from" => ret.enters_from.extend(names),
+                ".exits_to" => ret.exits_to.extend(names),
+                ".defines" => ret.defined_ssas.extend(names),
+                ".requires" => {
+                    if names.is_empty() {
+                        return Err(format!("Line {}: Expected a block label", line));
+                    }
+                    let target = names.remove(0);
+                    ret.jump_required_arguments
+                        .insert(target, names.into_iter().collect::<BTreeSet<String>>());
+                }
+                ".terminated" => ret.terminated = true,
+                _ => return Err(format!("Line {}: Unknown directive `{}`", line, directive)),
+            }
+        }
+        self.expect_punctuation('}')?;
+
+        Ok(ret)
+    }
+
+    fn parse_body(&mut self) -> Result<Box<FunctionBody>, String> {
+        let mut ret = FunctionBody::new();
+        self.expect_punctuation('{')?;
+        while !self.is_punctuation('}') {
+            ret.blocks.push(self.parse_block()?);
+        }
+        self.expect_punctuation('}')?;
+        Ok(ret)
+    }
+
+    fn parse_concrete_type(&mut self) -> Result<ConcreteType, String> {
+        self.expect_word("type")?;
+        let line = self.current_line();
+        let layout = self.parse_word()?;
+        let name = self.parse_identifier()?;
+        self.expect_word("in")?;
+        let namespace = self.parse_identifier()?;
+
+        match &layout[..] {
+            "tuple" => {
+                let mut data_layout = Box::new(Tuple::new());
+                for field in self.parse_list('(', ')')? {
+                    data_layout.add_field(field);
+                }
+                Ok(ConcreteType::Tuple {
+                    name,
+                    namespace,
+                    data_layout,
+                })
+            }
+            "variant" => {
+                let mut data_layout = Box::new(Variant::new());
+                self.expect_punctuation('{')?;
+                while !self.is_punctuation('}') {
+                    let field_name = self.parse_identifier()?;
+                    let data = if self.is_punctuation(':') {
+                        self.advance();
+                        Some(self.parse_identifier()?)
+                    } else {
+                        None
+                    };
+                    data_layout.add_field(EnumValue::new(field_name, data));
+                }
+                self.expect_punctuation('}')?;
+                Ok(ConcreteType::Variant {
+                    name,
+                    namespace,
+                    data_layout,
+                })
+            }
+            _ => Err(format!(
+                "Line {}: Expected `tuple` or `variant`, found `{}`",
+                line, layout
+            )),
+        }
+    }
+
+    fn parse_global(&mut self) -> Result<GlobalVariableDefition, String> {
+        self.expect_word("global")?;
+        let name = self.parse_identifier()?;
+        let typename = if self.is_punctuation(':') {
+            self.advance();
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+        self.expect_word("in")?;
+        let namespace = self.parse_identifier()?;
+        self.expect_punctuation('=')?;
+        let value = self.parse_instruction()?;
+
+        Ok(GlobalVariableDefition {
+            namespace,
+            name,
+            typename,
+            value,
+        })
+    }
+
+    fn parse_field(&mut self) -> Result<ContractField, String> {
+        self.expect_word("field")?;
+        let immutable = self.is_word("immutable");
+        if immutable {
+            self.advance();
+        }
+        let variable = self.parse_variable_declaration()?;
+        self.expect_word("in")?;
+        let namespace = self.parse_identifier()?;
+        self.expect_punctuation('=')?;
+        let initializer = self.parse_instruction()?;
+
+        Ok(ContractField {
+            namespace,
+            variable,
+            initializer,
+            immutable,
+        })
+    }
+
+    fn parse_lambda(&mut self) -> Result<LambdaFunctionSingleArgument, String> {
+        self.expect_word("lambda")?;
+        let name = self.parse_identifier()?;
+        self.expect_word("in")?;
+        let namespace = self.parse_identifier()?;
+
+        self.expect_word("capture")?;
+        let mut capture = Box::new(Tuple::new());
+        for field in self.parse_list('(', ')')? {
+            capture.add_field(field);
+        }
+
+        self.expect_word("environment")?;
+        self.expect_punctuation('(')?;
+        let mut environment = Vec::new();
+        while !self.is_punctuation(')') {
+            let entry = self.parse_name()?;
+            let value = if self.is_punctuation('=') {
+                self.advance();
+                Some(self.parse_identifier()?)
+            } else {
+                None
+            };
+            environment.push((entry, value));
+            if self.is_punctuation(',') {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        self.expect_punctuation(')')?;
+
+        let argument = if self.is_word("argument") {
+            self.advance();
+            Some(self.parse_variable_declaration()?)
+        } else {
+            None
+        };
+        let type_argument = if self.is_word("type") {
+            self.advance();
+            Some(self.parse_name()?)
+        } else {
+            None
+        };
+        let return_type = self.parse_optional_return_type()?;
+        let body = self.parse_body()?;
+
+        Ok(LambdaFunctionSingleArgument {
+            name,
+            namespace,
+            capture,
+            environment,
+            argument,
+            type_argument,
+            return_type,
+            body,
+        })
+    }
+
+    fn parse_function(&mut self) -> Result<ConcreteFunction, String> {
+        self.expect_word("fn")?;
+        let line = self.current_line();
+        let function_kind = match &self.parse_word()?[..] {
+            "procedure" => FunctionKind::Procedure,
+            "transition" => FunctionKind::Transition,
+            "function" => FunctionKind::Function,
+            "lambda" => FunctionKind::Lambda,
+            "constructor" => FunctionKind::Constructor,
+            kind => return Err(format!("Line {}: Unknown function kind `{}`", line, kind)),
+        };
+        let name = self.parse_identifier()?;
+        self.expect_word("in")?;
+        let namespace = self.parse_identifier()?;
+
+        self.expect_punctuation('(')?;
+        let mut arguments = Vec::new();
+        while !self.is_punctuation(')') {
+            arguments.push(self.parse_variable_declaration()?);
+            if self.is_punctuation(',') {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        self.expect_punctuation(')')?;
+
+        let return_type = self.parse_optional_return_type()?;
+        let body = self.parse_body()?;
+
+        Ok(ConcreteFunction {
+            name,
+            namespace,
+            function_kind,
+            return_type,
+            arguments,
+            body,
+        })
+    }
+}
+
+/// Shorthand for printing `ir` in the textual syntax.
+pub fn print_ir(ir: &IntermediateRepresentation) -> String {
+    IrPrinter::new().print(ir)
+}
+
+/// Shorthand for reading the IR from `script` in the textual syntax.
+pub fn parse_ir(
+    script: &str,
+    symbol_table: SymbolTable,
+) -> Result<IntermediateRepresentation, String> {
+    IrParser::new().parse(script, symbol_table)
+}
@@ -2,6 +2,7 @@
 
 pub mod constants;
 pub mod contract_executor;
+pub mod diagnostics;
 pub mod errors;
 pub mod formatter;
 pub mod intermediate_representation;
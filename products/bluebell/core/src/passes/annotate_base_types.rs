@@ -7,6 +7,7 @@ use scilla_parser::{
 
 use crate::{
     constants::NAMESPACE_SEPARATOR,
+    errors::CompilerError,
     intermediate_representation::{
         pass::IrPass,
         pass_executor::PassExecutor,
@@ -17,12 +18,15 @@ use crate::{
         },
         symbol_table::{SymbolTable, TypeInfo},
     },
+    passes::type_checker::Type,
 };
 
 pub struct AnnotateBaseTypes {
     previous_namespaces: Vec<String>,
     namespace: Option<String>,
     current_block: Option<FunctionBlock>,
+    return_type: Option<String>,
+    error: Option<CompilerError>,
 }
 
 impl AnnotateBaseTypes {
@@ -31,9 +35,18 @@ impl AnnotateBaseTypes {
             previous_namespaces: Vec::new(),
             namespace: None,
             current_block: None,
+            return_type: None,
+            error: None,
         }
     }
 
+    /// Records the use of a language feature the pass does not support yet and returns the
+    /// message to fail with.
+    fn unsupported(&mut self, message: String) -> String {
+        self.error = Some(CompilerError::unsupported(message.clone()));
+        message
+    }
+
     // TODO: Make Symbol table member
     pub fn typename_of(
         &self,
@@ -60,6 +73,34 @@ impl AnnotateBaseTypes {
         }
     }
 
+    /// Returns the type obtained by indexing the map `map` with `depth` keys.
+    pub fn map_value_type(
+        &self,
+        map: &IrIdentifier,
+        depth: usize,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<String, String> {
+        let mut typename = match &map.type_reference {
+            Some(t) => t.clone(),
+            None => return Err(format!("Unable to deduce type of map {}", map.unresolved)),
+        };
+
+        // Each key peels off one level of the map type
+        for _ in 0..depth {
+            typename = match symbol_table.map_key_value_types(&typename) {
+                Some((_, value_type)) => value_type,
+                None => {
+                    return Err(format!(
+                        "Too many keys for {}: {} is not a map",
+                        map.unresolved, typename
+                    ))
+                }
+            };
+        }
+
+        Ok(typename)
+    }
+
     pub fn push_namespace(&mut self, namespace: String) {
         let namespace = if let Some(ns) = &self.namespace {
             self.previous_namespaces.push(ns.clone());
@@ -78,8 +119,13 @@ impl AnnotateBaseTypes {
 // TODO: Rename to AnnotateTypesDeclarations
 
 impl IrPass for AnnotateBaseTypes {
-    fn initiate(&mut self) {}
+    fn initiate(&mut self) {
+        self.error = None;
+    }
     fn finalize(&mut self) {}
+    fn take_error(&mut self) -> Option<CompilerError> {
+        self.error.take()
+    }
     fn visit_concrete_type(
         &mut self,
         _mode: TreeTraversalMode,
@@ -202,11 +248,46 @@ impl IrPass for AnnotateBaseTypes {
             arg.visit(self, symbol_table)?;
         }
 
+        self.return_type = None;
         fnc.body.visit(self, symbol_table)?;
 
         self.pop_namespace();
         self.pop_namespace();
 
+        if let Some(return_type) = self.return_type.take() {
+            fnc.return_type = Some(return_type);
+        }
+
+        // Lifted lambdas are referred to by closure type
+        if let FunctionKind::Lambda = fnc.function_kind {
+            let argument_type = match fnc
+                .arguments
+                .last()
+                .and_then(|arg| arg.typename.resolved.clone())
+            {
+                Some(t) => t,
+                None => {
+                    return Err(format!(
+                        "Unable to deduce the argument type of {}",
+                        fnc.name.unresolved
+                    ))
+                }
+            };
+            let return_type = match &fnc.return_type {
+                Some(t) => t.clone(),
+                None => {
+                    return Err(format!(
+                        "Unable to deduce the return type of {}",
+                        fnc.name.unresolved
+                    ))
+                }
+            };
+            let function_name = fnc.name.qualified_name()?;
+            let typename = symbol_table.declare_closure_type(&argument_type, &return_type)?;
+            symbol_table.declare_type_of(&function_name, &typename)?;
+            fnc.name.type_reference = Some(typename);
+        }
+
         // TODO: collect type of function
         Ok(TraversalResult::SkipChildren)
     }
@@ -231,7 +312,10 @@ impl IrPass for AnnotateBaseTypes {
             IrIndentifierKind::TypeLikeName(dependants) => {
                 // TODO: We do not yet have support for template types
                 if dependants.len() > 0 {
-                    unimplemented!();
+                    return Err(self.unsupported(format!(
+                        "Template type {} is not supported",
+                        symbol.unresolved
+                    )));
                 }
 
                 // TODO: Deal with dependants
@@ -414,7 +498,7 @@ impl IrPass for AnnotateBaseTypes {
                 address.name.visit(self, symbol_table)?;
                 let value = match &mut instr.ssa_name {
                     Some(v) => v,
-                    None => panic!("Load does not assign value"),
+                    None => return Err("Load does not assign value".to_string()),
                 };
                 value.visit(self, symbol_table)?;
                 let symbol_name = match &value.resolved {
@@ -471,6 +555,52 @@ impl IrPass for AnnotateBaseTypes {
                     }
                 }
             }
+            Operation::StateMapLoad { address, keys } => {
+                address.name.visit(self, symbol_table)?;
+                for key in keys.iter_mut() {
+                    key.visit(self, symbol_table)?;
+                }
+
+                // Checking the keys, the entry is read as an option
+                self.map_value_type(&address.name, keys.len(), symbol_table)?;
+                "Option".to_string()
+            }
+            Operation::StateMapStore {
+                address,
+                keys,
+                value,
+            } => {
+                address.name.visit(self, symbol_table)?;
+                for key in keys.iter_mut() {
+                    key.visit(self, symbol_table)?;
+                }
+                value.visit(self, symbol_table)?;
+
+                self.map_value_type(&address.name, keys.len(), symbol_table)?
+            }
+            Operation::StateRemoteLoad { contract, field } => {
+                contract.visit(self, symbol_table)?;
+                if field == "_balance" {
+                    "Uint128".to_string()
+                } else {
+                    // TODO: Requires the contract type of the address
+                    "Uint256".to_string()
+                }
+            }
+            Operation::StateMapExists { address, keys } => {
+                address.name.visit(self, symbol_table)?;
+                for key in keys.iter_mut() {
+                    key.visit(self, symbol_table)?;
+                }
+                "Bool".to_string()
+            }
+            Operation::StateMapDelete { address, keys } => {
+                address.name.visit(self, symbol_table)?;
+                for key in keys.iter_mut() {
+                    key.visit(self, symbol_table)?;
+                }
+                "Void".to_string() // TODO: Fetch from somewhere
+            }
             Operation::MemLoad => "TODO".to_string(),
             Operation::MemStore => "TODO".to_string(),
             Operation::IsEqual { left, right } => {
@@ -495,11 +625,10 @@ impl IrPass for AnnotateBaseTypes {
                     let type_name = match &arg.type_reference {
                         Some(t) => t,
                         None => {
-                            // TODO: Fix error propagation
-                            panic!(
-                                "Unable to resolve type for {:?} in {:?}",
-                                arg.unresolved, name
-                            );
+                            return Err(format!(
+                                "Unable to resolve type for {} in {}",
+                                arg.unresolved, name.unresolved
+                            ));
                         }
                     };
                     if i > 0 {
@@ -520,7 +649,7 @@ impl IrPass for AnnotateBaseTypes {
                 let function_type = if let Some(typeinfo)= symbol_table.type_of(&function_type, &self.namespace) {
                     typeinfo.symbol_name
                 } else {
-                    panic!("Unable to find symbol {}", function_type);
+                    return Err(format!("Unable to find symbol {}", function_type));
                 };
 
                 name.resolved = Some(function_type.clone());
@@ -537,10 +666,7 @@ impl IrPass for AnnotateBaseTypes {
                             None => "Void".to_string() // TODO: Get value from somewhere
                         }
                     }
-                    None => {
-                        println!("{:#?}", symbol_table);
-                        panic!("Undeclared function {}", function_type)
-                    }
+                    None => return Err(format!("Undeclared function {}", function_type)),
                 };
 
                 type_info
@@ -560,7 +686,30 @@ impl IrPass for AnnotateBaseTypes {
                     let function_typeinfo = symbol_table.type_of(function_type, &self.namespace);
 
                     if let Some(function_typeinfo) = function_typeinfo {
-                        function_typeinfo.return_type.expect("").clone()
+                        match function_typeinfo.return_type {
+                            Some(return_type) => return_type,
+                            // Constructors sharing their name with their type, such as `Pair`,
+                            // resolve to the type
+                            None => {
+                                let constructor = format!(
+                                    "{}{}{}",
+                                    function_type, NAMESPACE_SEPARATOR, function_type
+                                );
+                                match symbol_table.type_of(&constructor, &None) {
+                                    Some(typeinfo) if typeinfo.is_constructor() => {
+                                        name.resolved = Some(constructor);
+                                        name.type_reference = Some(typeinfo.typename.clone());
+                                        typeinfo.return_type.unwrap_or(function_type.clone())
+                                    }
+                                    _ => {
+                                        return Err(format!(
+                                            "{} is not a function",
+                                            name.unresolved
+                                        ))
+                                    }
+                                }
+                            }
+                        }
                     } else {
                         return Err(format!("Unable to determine type of {:?}", name.unresolved)
                             .to_string());
@@ -585,7 +734,9 @@ impl IrPass for AnnotateBaseTypes {
                     arg.visit(self, symbol_table)?;
                 }
 
-                unimplemented!()
+                return Err(self.unsupported(
+                    "Static function calls are not supported".to_string(),
+                ));
             }
             Operation::ResolveSymbol { symbol } => {
                 symbol.visit(self, symbol_table)?;
@@ -624,6 +775,16 @@ impl IrPass for AnnotateBaseTypes {
                     }
                 }
             }
+            Operation::ConstructMessage { entries } => {
+                for (_, value) in entries.iter_mut() {
+                    value.visit(self, symbol_table)?;
+                }
+                "Message".to_string()
+            }
+            Operation::EmitEvent { message } | Operation::SendMessage { message } => {
+                message.visit(self, symbol_table)?;
+                "Void".to_string() // TODO: Fetch from somewhere
+            }
             Operation::PhiNode(inputs) => {
                 let mut type_name = None;
                 for input in inputs.iter_mut() {
@@ -643,12 +804,138 @@ impl IrPass for AnnotateBaseTypes {
                     "Void".to_string() // TODO: specify somewhere // TODO: Fetch from somewhere
                 }
             }
-            Operation::Revert(n) | Operation::Return(n) => {
+            Operation::Revert(n) => {
+                if let Some(n) = n {
+                    n.visit(self, symbol_table)?;
+                }
+                "Void".to_string() // TODO: Fetch from somewhere
+            }
+            Operation::Return(n) => {
                 match n {
-                    Some(_) => todo!(),
-                    None => "Void".to_string(), // TODO: specify somewhere // TODO: Fetch from somewhere
+                    Some(value) => {
+                        value.visit(self, symbol_table)?;
+                        let typename = match &value.type_reference {
+                            Some(t) => t.clone(),
+                            None => {
+                                return Err(format!(
+                                    "Unable to deduce the type of return value {}",
+                                    value.unresolved
+                                ))
+                            }
+                        };
+                        match &self.return_type {
+                            Some(previous) if *previous != typename => {
+                                return Err(
+                                    "Different paths given different return types.".to_string()
+                                );
+                            }
+                            _ => self.return_type = Some(typename),
+                        }
+                    }
+                    None => (),
+                }
+                "Void".to_string() // TODO: specify somewhere // TODO: Fetch from somewhere
+            }
+            Operation::CreateClosure { function, captures } => {
+                function.visit(self, symbol_table)?;
+                for capture in captures.iter_mut() {
+                    capture.visit(self, symbol_table)?;
+                }
+                match &function.type_reference {
+                    Some(t) => t.clone(),
+                    None => {
+                        return Err(format!(
+                            "Unable to deduce the type of anonymous function {}",
+                            function.unresolved
+                        ))
+                    }
+                }
+            }
+            Operation::LoadCapture { closure, index: _ } => {
+                closure.visit(self, symbol_table)?;
+                match &instr.result_type {
+                    Some(result_type) => match &result_type.resolved {
+                        Some(t) => t.clone(),
+                        None => result_type.unresolved.clone(),
+                    },
+                    None => return Err("Captured variable without type.".to_string()),
+                }
+            }
+            Operation::ApplyClosure { closure, argument } => {
+                closure.visit(self, symbol_table)?;
+                argument.visit(self, symbol_table)?;
+                let closure_type = match &closure.type_reference {
+                    Some(t) => t.clone(),
+                    None => {
+                        return Err(format!(
+                            "Unable to deduce the type of {}",
+                            closure.unresolved
+                        ))
+                    }
+                };
+                match symbol_table.closure_argument_return_types(&closure_type) {
+                    Some((_, return_type)) => return_type,
+                    None => {
+                        return Err(format!(
+                            "{} of type {} is not a function",
+                            closure.unresolved, closure_type
+                        ))
+                    }
+                }
+            }
+            Operation::InstantiatePrimitive {
+                primitive,
+                type_arguments,
+            } => {
+                let mut arguments = Vec::new();
+                for argument in type_arguments.iter_mut() {
+                    argument.visit(self, symbol_table)?;
+                    arguments.push(Type::parse(&argument.unresolved, symbol_table));
+                }
+                match Type::of_recursion_primitive(&primitive.unresolved, &arguments) {
+                    Some(t) => t.ir_name(),
+                    None => {
+                        return Err(format!(
+                            "Unable to instantiate recursion primitive {}",
+                            primitive.unresolved
+                        ))
+                    }
+                }
+            }
+            Operation::LoadConstructorArgument {
+                value,
+                constructor,
+                index,
+            } => {
+                // The constructor is a pattern rather than a call and is therefore not visited
+                value.visit(self, symbol_table)?;
+                let argument_type = symbol_table
+                    .type_of(&constructor.unresolved, &self.namespace)
+                    .or_else(|| {
+                        symbol_table
+                            .aliases
+                            .get(&constructor.unresolved)
+                            .and_then(|name| symbol_table.type_of(name, &None))
+                    })
+                    .and_then(|typeinfo| typeinfo.arguments.get(*index).cloned())
+                    .filter(|t| !t.contains('\''));
+                match (&instr.result_type, argument_type) {
+                    (Some(result_type), _) => result_type.unresolved.clone(),
+                    (None, Some(t)) => t,
+                    (None, None) => {
+                        return Err(format!(
+                            "Unable to deduce the type of argument {} of {}",
+                            index, constructor.unresolved
+                        ))
+                    }
                 }
             }
+            Operation::InstantiateTemplate { template, .. } => {
+                return Err(format!(
+                    "Internal error: Type application of {} was not monomorphised",
+                    template.unresolved
+                ))
+            }
         };
 
         //instr.operation.type_reference = Some(typename);
@@ -22,6 +22,9 @@ pub struct DeduceBlockDependencies {
     defined_names: BTreeSet<String>,
     used_names: BTreeSet<String>,
     listed_jump_to: BTreeSet<String>,
+    /// Values merged by each phi node of the current function. A phi node is a block argument
+    /// of its block, which is provided by the predecessors defining one of its values.
+    phi_values: HashMap<String, BTreeSet<String>>,
 }
 
 impl DeduceBlockDependencies {
@@ -31,6 +34,7 @@ impl DeduceBlockDependencies {
             defined_names: BTreeSet::new(),
             used_names: BTreeSet::new(),
             listed_jump_to: BTreeSet::new(),
+            phi_values: HashMap::new(),
         }
     }
 }
@@ -105,6 +109,7 @@ impl IrPass for DeduceBlockDependencies {
         match mode {
             TreeTraversalMode::Enter => {
                 self.blocks = HashMap::new();
+                self.phi_values = HashMap::new();
             }
             TreeTraversalMode::Exit => {
                 let mut block_names: Vec<String> = Vec::new();
@@ -113,14 +118,17 @@ impl IrPass for DeduceBlockDependencies {
                 for block in fnc.body.blocks.iter_mut() {
                     let name = match &block.name.resolved {
                         Some(n) => n,
-                        None => panic!("Failed to resolve name of block"),
+                        None => return Err("Failed to resolve name of block".to_string()),
                     };
                     block_names.push(name.clone());
                     for link_to in &block.exits_to {
                         if let Some(target) = self.blocks.get_mut(link_to) {
                             target.enters_from.insert(name.to_string());
                         } else {
-                            panic!("Jump to non-existing block {} in {}", link_to, name);
+                            return Err(format!(
+                                "Jump to non-existing block {} in {}",
+                                link_to, name
+                            ));
                         }
                     }
                 }
@@ -130,7 +138,7 @@ impl IrPass for DeduceBlockDependencies {
                     let block = match self.blocks.get_mut(name) {
                         Some(b) => b.clone(),
                         _ => {
-                            panic!("Unregistered block {}", name);
+                            return Err(format!("Unregistered block {}", name));
                         }
                     };
 
@@ -152,7 +160,7 @@ impl IrPass for DeduceBlockDependencies {
 
                             let required_block_args: BTreeSet<String> = match self.blocks.get(&to) {
                                 Some(to) => to.block_arguments.clone().into_iter().collect(),
-                                None => panic!("Unregistered block {}", to),
+                                None => return Err(format!("Unregistered block {}", to)),
                             };
 
                             match self.blocks.get_mut(&from) {
@@ -160,13 +168,17 @@ impl IrPass for DeduceBlockDependencies {
                                     from_block
                                         .jump_required_arguments
                                         .insert(to, required_block_args);
-                                    if !from_block.defined_ssas.contains(variable) {
+                                    let provided = from_block.defined_ssas.contains(variable)
+                                        || self.phi_values.get(variable).map_or(false, |values| {
+                                            !values.is_disjoint(&from_block.defined_ssas)
+                                        });
+                                    if !provided {
                                         // If the variable is not contained in the arguments already,
                                         // we add it and continue to traverse backwards
                                         if !from_block.block_arguments.contains(variable) {
                                             from_block.block_arguments.insert(variable.to_string());
 
-                                            for name in &block.enters_from {
+                                            for name in &from_block.enters_from {
                                                 edge_queue.push_back((
                                                     name.to_string(),
                                                     from.to_string(),
@@ -176,7 +188,7 @@ impl IrPass for DeduceBlockDependencies {
                                     }
                                 }
                                 _ => {
-                                    panic!("Unregistered block {}", from);
+                                    return Err(format!("Unregistered block {}", from));
                                 }
                             };
                         }
@@ -187,7 +199,7 @@ impl IrPass for DeduceBlockDependencies {
                 for block in fnc.body.blocks.iter_mut() {
                     let name = match &block.name.resolved {
                         Some(n) => n,
-                        None => panic!("Failed to resolve name of block"),
+                        None => return Err("Failed to resolve name of block".to_string()),
                     };
                     if let Some(updated) = self.blocks.get_mut(name) {
                         mem::swap(&mut block.block_arguments, &mut updated.block_arguments);
@@ -199,7 +211,7 @@ impl IrPass for DeduceBlockDependencies {
                             &mut updated.jump_required_arguments,
                         );
                     } else {
-                        panic!("Unregistered block {}", name);
+                        return Err(format!("Unregistered block {}", name));
                     }
                 }
             }
@@ -226,7 +238,7 @@ impl IrPass for DeduceBlockDependencies {
             IrIndentifierKind::VirtualRegister | IrIndentifierKind::VirtualRegisterIntermediate => {
                 match &symbol.resolved {
                     Some(n) => self.used_names.insert(n.clone()),
-                    None => panic!("Unresolved symbol name encountered"),
+                    None => return Err("Unresolved symbol name encountered".to_string()),
                 };
             }
             _ => (),
@@ -276,7 +288,7 @@ impl IrPass for DeduceBlockDependencies {
                 Operation::Jump(label) => {
                     match &label.resolved {
                         Some(l) => self.listed_jump_to.insert(l.clone()),
-                        None => panic!("Unresolved block label encountered"),
+                        None => return Err("Unresolved block label encountered".to_string()),
                     };
                     return Ok(TraversalResult::SkipChildren);
                 }
@@ -287,12 +299,12 @@ impl IrPass for DeduceBlockDependencies {
                 } => {
                     match &on_success.resolved {
                         Some(l) => self.listed_jump_to.insert(l.clone()),
-                        None => panic!("Unresolved block label encountered"),
+                        None => return Err("Unresolved block label encountered".to_string()),
                     };
 
                     match &on_failure.resolved {
                         Some(l) => self.listed_jump_to.insert(l.clone()),
-                        None => panic!("Unresolved block label encountered"),
+                        None => return Err("Unresolved block label encountered".to_string()),
                     };
                     return Ok(TraversalResult::SkipChildren);
                 }
@@ -339,6 +351,23 @@ impl IrPass for DeduceBlockDependencies {
         };
 
         for instr in block.instructions.iter_mut() {
+            if let (Some(id), Operation::PhiNode(values)) = (&instr.ssa_name, &instr.operation) {
+                let name = match &id.resolved {
+                    Some(name) => name.clone(),
+                    None => return Err("Encountered unresolved SSA name".to_string()),
+                };
+                let values = values
+                    .iter()
+                    .map(|value| match &value.resolved {
+                        Some(value) => Ok(value.clone()),
+                        None => Err("Unresolved symbol name encountered".to_string()),
+                    })
+                    .collect::<Result<BTreeSet<String>, String>>()?;
+                self.phi_values.insert(name.clone(), values);
+                self.used_names.insert(name);
+                continue;
+            }
+
             match &instr.ssa_name {
                 Some(id) => {
                     if id.kind == IrIndentifierKind::VirtualRegister
@@ -352,7 +381,7 @@ impl IrPass for DeduceBlockDependencies {
                                     self.defined_names.insert(name.clone());
                                 }
                             }
-                            None => panic!("Encountered unresolved SSA name"),
+                            None => return Err("Encountered unresolved SSA name".to_string()),
                         };
                     }
                 }
@@ -0,0 +1,602 @@
+use std::collections::{BTreeMap, HashMap};
+
+use scilla_parser::{
+    ast::{TraversalResult, TreeTraversalMode},
+    parser::lexer::SourcePosition,
+};
+use serde::Serialize;
+
+use crate::{
+    diagnostics::{Diagnostic, Span},
+    intermediate_representation::{
+        pass::IrPass,
+        primitives::{
+            CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FunctionBlock,
+            FunctionBody, FunctionKind, Instruction, IntermediateRepresentation, IrIdentifier,
+            IrIndentifierKind, Operation, Tuple, VariableDeclaration, Variant,
+        },
+        symbol_table::SymbolTable,
+    },
+    passes::type_checker::Type,
+};
+
+/// Builtins whose result and arguments are all money or all non-money.
+const MONEY_PRESERVING_BUILTINS: [&str; 4] = [
+    "builtin__add",
+    "builtin__sub",
+    "builtin__min",
+    "builtin__max",
+];
+
+/// Builtins comparing their arguments. Only like values are compared.
+const COMPARISON_BUILTINS: [&str; 2] = ["builtin__eq", "builtin__lt"];
+
+/// Name of the intrinsic `accept` is lowered to.
+const ACCEPT_INTRINSIC: &str = "__intrinsic_accept_transfer";
+
+/// Cashflow tag of a field, parameter or value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CashflowTag {
+    /// Nothing is known about the value.
+    NoInfo,
+    Money,
+    NotMoney,
+    /// The value is used both as money and as something else.
+    Inconsistent,
+}
+
+impl CashflowTag {
+    fn join(self, other: CashflowTag) -> CashflowTag {
+        match (self, other) {
+            (CashflowTag::NoInfo, t) | (t, CashflowTag::NoInfo) => t,
+            (a, b) if a == b => a,
+            _ => CashflowTag::Inconsistent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CashflowWarning {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    #[serde(skip)]
+    pub position: SourcePosition,
+}
+
+impl CashflowWarning {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::warning(&self.message).with_primary(Span::at(self.position.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransitionCashflow {
+    pub name: String,
+    pub parameters: BTreeMap<String, CashflowTag>,
+    /// Static estimate of the gas used by the transition, if its code could be generated.
+    pub gas_estimate: Option<u64>,
+}
+
+/// Result of the cashflow analysis. Maps are tagged by the values they hold.
+#[derive(Debug, Clone, Serialize)]
+pub struct CashflowReport {
+    pub fields: BTreeMap<String, CashflowTag>,
+    pub transitions: Vec<TransitionCashflow>,
+    pub warnings: Vec<CashflowWarning>,
+}
+
+impl CashflowReport {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+/// Variables, fields and parameters are nodes which are unified when a value flows from one to
+/// another.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    Field(String),
+    /// Parameter or local variable of the transition with the given index.
+    Local(usize, String),
+}
+
+/// Summary of what a transition does with funds.
+struct TransitionSummary {
+    name: String,
+    location: SourcePosition,
+    parameters: Vec<String>,
+    accepts: Option<SourcePosition>,
+    reads_amount: Option<SourcePosition>,
+    stored_fields: Vec<String>,
+}
+
+/// Labels contract fields and transition parameters as money or non-money and flags
+/// suspicious flows of funds, similar to the cashflow analysis of the Scilla toolchain.
+/// Values are tagged by unifying everything a value flows into: stores, loads, copies and
+/// arithmetic on money, starting from `_amount`, `_balance` and the `_amount` of messages.
+pub struct CashflowAnalysis {
+    parents: Vec<usize>,
+    tags: Vec<CashflowTag>,
+    nodes: HashMap<Node, usize>,
+    /// Fields in declaration order with the location of their initializer.
+    fields: Vec<(String, SourcePosition)>,
+    transitions: Vec<TransitionSummary>,
+}
+
+impl CashflowAnalysis {
+    pub fn new() -> Self {
+        Self {
+            parents: Vec::new(),
+            tags: Vec::new(),
+            nodes: HashMap::new(),
+            fields: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+
+    /// Analyses `ir`, which is expected to be emitted but not yet lowered by the pass manager.
+    pub fn analyse(
+        &mut self,
+        ir: &mut IntermediateRepresentation,
+    ) -> Result<CashflowReport, String> {
+        ir.run_pass(self)?;
+
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, _)| (name.clone(), self.tag(&Node::Field(name.clone()))))
+            .collect();
+
+        let mut transitions = Vec::new();
+        let mut warnings = Vec::new();
+        for (index, transition) in self.transitions.iter().enumerate() {
+            let parameters = transition
+                .parameters
+                .iter()
+                .map(|name| (name.clone(), self.tag(&Node::Local(index, name.clone()))))
+                .collect();
+            transitions.push(TransitionCashflow {
+                name: transition.name.clone(),
+                parameters,
+                gas_estimate: None,
+            });
+
+            let records_funds = transition
+                .stored_fields
+                .iter()
+                .any(|field| self.tag(&Node::Field(field.clone())) == CashflowTag::Money);
+            if let Some(position) = &transition.accepts {
+                if !records_funds {
+                    warnings.push(Self::warning(
+                        position,
+                        format!(
+                            "Transition `{}` accepts funds without recording them in a money field",
+                            transition.name
+                        ),
+                    ));
+                }
+            }
+            if let (Some(position), None) = (&transition.reads_amount, &transition.accepts) {
+                warnings.push(Self::warning(
+                    position,
+                    format!(
+                        "Transition `{}` reads `_amount` but never accepts the funds",
+                        transition.name
+                    ),
+                ));
+            }
+
+            for name in transition.parameters.iter() {
+                if self.tag(&Node::Local(index, name.clone())) == CashflowTag::Inconsistent {
+                    warnings.push(Self::warning(
+                        &transition.location,
+                        format!(
+                            "Parameter `{}` of `{}` is used both as money and as a non-money value",
+                            name, transition.name
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for (name, position) in self.fields.iter() {
+            if self.tag(&Node::Field(name.clone())) == CashflowTag::Inconsistent {
+                warnings.push(Self::warning(
+                    position,
+                    format!(
+                        "Field `{}` is used both as money and as a non-money value",
+                        name
+                    ),
+                ));
+            }
+        }
+
+        Ok(CashflowReport {
+            fields,
+            transitions,
+            warnings,
+        })
+    }
+
+    fn warning(position: &SourcePosition, message: String) -> CashflowWarning {
+        CashflowWarning {
+            line: position.line,
+            column: position.column,
+            message,
+            position: position.clone(),
+        }
+    }
+
+    fn node(&mut self, node: Node) -> usize {
+        if let Some(id) = self.nodes.get(&node) {
+            return *id;
+        }
+        let id = self.parents.len();
+        self.parents.push(id);
+        self.tags.push(CashflowTag::NoInfo);
+        self.nodes.insert(node, id);
+        id
+    }
+
+    fn find(&mut self, id: usize) -> usize {
+        let parent = self.parents[id];
+        if parent == id {
+            return id;
+        }
+        let root = self.find(parent);
+        self.parents[id] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        if a != b {
+            self.parents[b] = a;
+            self.tags[a] = self.tags[a].join(self.tags[b]);
+        }
+    }
+
+    fn mark(&mut self, id: usize, tag: CashflowTag) {
+        let root = self.find(id);
+        self.tags[root] = self.tags[root].join(tag);
+    }
+
+    fn tag(&self, node: &Node) -> CashflowTag {
+        match self.nodes.get(node) {
+            Some(id) => {
+                let mut root = *id;
+                while self.parents[root] != root {
+                    root = self.parents[root];
+                }
+                self.tags[root]
+            }
+            None => CashflowTag::NoInfo,
+        }
+    }
+
+    /// Only integers can hold money. Maps are tagged by the values they hold.
+    fn declared_tag(typename: &IrIdentifier, symbol_table: &SymbolTable) -> CashflowTag {
+        let mut t = Type::parse(&typename.unresolved, symbol_table);
+        while let Type::Map(_, value) = t {
+            t = *value;
+        }
+        match t {
+            Type::Named(name, _) if name.starts_with("Uint") || name.starts_with("Int") => {
+                CashflowTag::NoInfo
+            }
+            Type::Named(..) => CashflowTag::NotMoney,
+            _ => CashflowTag::NoInfo,
+        }
+    }
+
+    fn local(&mut self, transition: usize, symbol: &IrIdentifier) -> usize {
+        self.node(Node::Local(transition, symbol.unresolved.clone()))
+    }
+
+    fn field(&mut self, field: &IrIdentifier) -> usize {
+        self.node(Node::Field(field.unresolved.clone()))
+    }
+
+    fn analyse_instruction(&mut self, transition: usize, instr: &Instruction) {
+        let result = instr
+            .ssa_name
+            .as_ref()
+            .map(|ssa_name| self.local(transition, ssa_name));
+
+        match &instr.operation {
+            Operation::ResolveSymbol { symbol } => {
+                let value = self.local(transition, symbol);
+                if let Some(result) = result {
+                    self.union(result, value);
+                }
+            }
+            Operation::ResolveContextResource { symbol } => {
+                let tag = match symbol.unresolved.as_str() {
+                    "_amount" => {
+                        if self.transitions[transition].reads_amount.is_none() {
+                            self.transitions[transition].reads_amount =
+                                Some(instr.source_location.0.clone());
+                        }
+                        CashflowTag::Money
+                    }
+                    "_balance" => CashflowTag::Money,
+                    _ => CashflowTag::NotMoney,
+                };
+                if let Some(result) = result {
+                    self.mark(result, tag);
+                }
+            }
+            Operation::Literal { typename, .. } => {
+                // Numeric literals take the tag of the values they are combined with
+                if typename.unresolved == "String" {
+                    if let Some(result) = result {
+                        self.mark(result, CashflowTag::NotMoney);
+                    }
+                }
+            }
+            Operation::StateLoad { address } | Operation::StateMapLoad { address, .. } => {
+                let field = self.field(&address.name);
+                if let Some(result) = result {
+                    self.union(result, field);
+                }
+            }
+            Operation::StateRemoteLoad { field, .. } => {
+                if field == "_balance" {
+                    if let Some(result) = result {
+                        self.mark(result, CashflowTag::Money);
+                    }
+                }
+            }
+            Operation::StateStore { address, value }
+            | Operation::StateMapStore { address, value, .. } => {
+                let field = self.field(&address.name);
+                let value = self.local(transition, value);
+                self.union(field, value);
+                self.transitions[transition]
+                    .stored_fields
+                    .push(address.name.unresolved.clone());
+            }
+            Operation::CallExternalFunction { name, arguments } => {
+                let builtin = name.unresolved.as_str();
+                if MONEY_PRESERVING_BUILTINS.contains(&builtin) {
+                    let mut values: Vec<usize> = arguments
+                        .iter()
+                        .map(|argument| self.local(transition, argument))
+                        .collect();
+                    values.extend(result);
+                    for value in values.iter().skip(1) {
+                        self.union(values[0], *value);
+                    }
+                } else if COMPARISON_BUILTINS.contains(&builtin) {
+                    if let [left, right] = &arguments[..] {
+                        let left = self.local(transition, left);
+                        let right = self.local(transition, right);
+                        self.union(left, right);
+                    }
+                    if let Some(result) = result {
+                        self.mark(result, CashflowTag::NotMoney);
+                    }
+                }
+            }
+            Operation::CallFunction { name, .. } => {
+                if name.unresolved == ACCEPT_INTRINSIC
+                    && self.transitions[transition].accepts.is_none()
+                {
+                    self.transitions[transition].accepts = Some(instr.source_location.0.clone());
+                }
+            }
+            Operation::ConstructMessage { entries } => {
+                for (name, value) in entries.iter() {
+                    let tag = match name.as_str() {
+                        "_amount" => CashflowTag::Money,
+                        "_recipient" | "_tag" | "_eventname" | "_exception" => {
+                            CashflowTag::NotMoney
+                        }
+                        _ => continue,
+                    };
+                    let value = self.local(transition, value);
+                    self.mark(value, tag);
+                }
+            }
+            Operation::PhiNode(values) => {
+                if let Some(result) = result {
+                    for value in values.iter() {
+                        let value = self.local(transition, value);
+                        self.union(result, value);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl IrPass for CashflowAnalysis {
+    fn visit_concrete_type(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_type: &mut ConcreteType,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_contract_field(
+        &mut self,
+        _mode: TreeTraversalMode,
+        field: &mut ContractField,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        let name = field.variable.name.unresolved.clone();
+        let node = self.field(&field.variable.name);
+        self.mark(
+            node,
+            Self::declared_tag(&field.variable.typename, symbol_table),
+        );
+        self.fields
+            .push((name, field.initializer.source_location.0.clone()));
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_concrete_function(
+        &mut self,
+        _mode: TreeTraversalMode,
+        fnc: &mut ConcreteFunction,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        if !matches!(fnc.function_kind, FunctionKind::Transition) {
+            return Ok(TraversalResult::SkipChildren);
+        }
+
+        let index = self.transitions.len();
+        self.transitions.push(TransitionSummary {
+            name: fnc.name.unresolved.clone(),
+            location: fnc.name.source_location.0.clone(),
+            parameters: fnc
+                .arguments
+                .iter()
+                .map(|arg| arg.name.unresolved.clone())
+                .collect(),
+            accepts: None,
+            reads_amount: None,
+            stored_fields: Vec::new(),
+        });
+
+        for arg in fnc.arguments.iter() {
+            let node = self.local(index, &arg.name);
+            self.mark(node, Self::declared_tag(&arg.typename, symbol_table));
+        }
+
+        for block in fnc.body.blocks.iter() {
+            for instr in block.instructions.iter() {
+                self.analyse_instruction(index, instr);
+            }
+        }
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_symbol_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_kind: &mut IrIndentifierKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_symbol_name(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_name: &mut IrIdentifier,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_enum_value(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _enum_value: &mut EnumValue,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_tuple(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _tuple: &mut Tuple,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variant(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _variant: &mut Variant,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _var_dec: &mut VariableDeclaration,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_operation(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _operation: &mut Operation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _instruction: &mut Instruction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_block(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_block: &mut FunctionBlock,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_body(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_body: &mut FunctionBody,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_kind: &mut FunctionKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_case_clause(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_function: &mut CaseClause,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_primitives(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _primitives: &mut IntermediateRepresentation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn initiate(&mut self) {}
+
+    fn finalize(&mut self) {}
+}
@@ -0,0 +1,1185 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use scilla_parser::{
+    ast::{TraversalResult, TreeTraversalMode},
+    parser::lexer::SourcePosition,
+};
+
+use crate::{
+    constants::{
+        CLOSURE_ENVIRONMENT_ARGUMENT, FUNCTION_RETURN_TYPE_SEPARATOR, NAMESPACE_SEPARATOR,
+    },
+    errors::CompilerError,
+    intermediate_representation::{
+        pass::IrPass,
+        pass_executor::PassExecutor,
+        primitives::{
+            CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FieldAddress,
+            FunctionBlock, FunctionBody, FunctionKind, Instruction, IntermediateRepresentation,
+            IrIdentifier, IrIndentifierKind, LambdaFunctionSingleArgument, Operation, Tuple,
+            VariableDeclaration, Variant,
+        },
+        symbol_table::SymbolTable,
+    },
+};
+
+/// Replaces the type variable `variable` in `typename` with `replacement`. Function types are
+/// parenthesised when they are substituted in the argument position of another function type.
+fn substitute_type_variable(typename: &str, variable: &str, replacement: &str) -> String {
+    let mut ret = String::new();
+    let mut rest = typename;
+    while let Some(index) = rest.find(variable) {
+        ret.push_str(&rest[..index]);
+        let after = &rest[index + variable.len()..];
+
+        let at_boundary =
+            !after.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '\'');
+        if !at_boundary {
+            ret.push_str(variable);
+        } else if replacement.contains(FUNCTION_RETURN_TYPE_SEPARATOR)
+            && after
+                .trim_start()
+                .starts_with(FUNCTION_RETURN_TYPE_SEPARATOR)
+        {
+            ret.push_str(&format!("({})", replacement));
+        } else {
+            ret.push_str(replacement);
+        }
+
+        rest = after;
+    }
+    ret.push_str(rest);
+    ret
+}
+
+/// Returns the names of the lambda functions for which `body` creates closures.
+fn created_lambdas(body: &FunctionBody) -> Vec<String> {
+    let mut ret = Vec::new();
+    for block in body.blocks.iter() {
+        for instr in block.instructions.iter() {
+            if let Operation::CreateClosure { function, .. } = &instr.operation {
+                if !ret.contains(&function.unresolved) {
+                    ret.push(function.unresolved.clone());
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// Returns the variables which are used, but not defined, by the functions, lambdas and global
+/// values of `ir`. This is used to determine which library definitions a program depends on.
+pub fn free_variables(ir: &mut IntermediateRepresentation) -> Result<BTreeSet<String>, String> {
+    let mut ret = BTreeSet::new();
+
+    for function in ir.function_definitions.iter_mut() {
+        let mut collector = SymbolRewriter::collector();
+        collector.visit_body(&mut function.body, &mut ir.symbol_table)?;
+        for arg in function.arguments.iter() {
+            collector.defined.insert(arg.name.unresolved.clone());
+        }
+        ret.extend(collector.free_variables());
+    }
+
+    for lambda in ir.lambda_functions.iter_mut() {
+        let mut collector = SymbolRewriter::collector();
+        collector.visit_body(&mut lambda.body, &mut ir.symbol_table)?;
+        if let Some(argument) = &lambda.argument {
+            collector.defined.insert(argument.name.unresolved.clone());
+        }
+        ret.extend(collector.free_variables());
+    }
+
+    let values = ir
+        .global_variables
+        .iter_mut()
+        .map(|global| &mut global.value)
+        .chain(ir.global_init_block.instructions.iter_mut());
+    for value in values {
+        let mut collector = SymbolRewriter::collector();
+        value.visit(&mut collector, &mut ir.symbol_table)?;
+        ret.extend(collector.free_variables());
+    }
+
+    Ok(ret)
+}
+
+/// Refers to either a concrete function or a lambda function which is yet to be lifted.
+enum Unit {
+    Function(usize),
+    Lambda(String),
+}
+
+fn body_mut<'a>(
+    functions: &'a mut Vec<ConcreteFunction>,
+    lambdas: &'a mut BTreeMap<String, LambdaFunctionSingleArgument>,
+    unit: &Unit,
+) -> Result<&'a mut FunctionBody, String> {
+    match unit {
+        Unit::Function(index) => match functions.get_mut(*index) {
+            Some(function) => Ok(&mut function.body),
+            None => Err("Internal error: Function index out of bounds".to_string()),
+        },
+        Unit::Lambda(name) => match lambdas.get_mut(name) {
+            Some(lambda) => Ok(&mut lambda.body),
+            None => Err(format!(
+                "Internal error: Lambda function {} not found",
+                name
+            )),
+        },
+    }
+}
+
+/// Collects the variables used and defined by the instructions it visits and renames symbols on
+/// the way. A variable is only considered used if it is not defined by a previous instruction.
+struct SymbolRewriter {
+    renames: HashMap<String, String>,
+    function_renames: HashMap<String, String>,
+    type_substitution: Option<(String, String)>,
+    used: BTreeSet<String>,
+    defined: BTreeSet<String>,
+}
+
+impl SymbolRewriter {
+    fn new(
+        renames: HashMap<String, String>,
+        function_renames: HashMap<String, String>,
+        type_substitution: Option<(String, String)>,
+    ) -> Self {
+        Self {
+            renames,
+            function_renames,
+            type_substitution,
+            used: BTreeSet::new(),
+            defined: BTreeSet::new(),
+        }
+    }
+
+    fn collector() -> Self {
+        Self::new(HashMap::new(), HashMap::new(), None)
+    }
+
+    fn is_variable(kind: &IrIndentifierKind) -> bool {
+        match kind {
+            IrIndentifierKind::VirtualRegister
+            | IrIndentifierKind::VirtualRegisterIntermediate
+            | IrIndentifierKind::Memory => true,
+            _ => false,
+        }
+    }
+
+    fn rename_variable(&self, symbol: &mut IrIdentifier) {
+        if let Some(name) = self.renames.get(&symbol.unresolved) {
+            symbol.unresolved = name.clone();
+        }
+    }
+
+    fn substitute_type(&self, typename: &str) -> String {
+        match &self.type_substitution {
+            Some((variable, replacement)) => {
+                substitute_type_variable(typename, variable, replacement)
+            }
+            None => typename.to_string(),
+        }
+    }
+
+    fn visit_body(
+        &mut self,
+        body: &mut FunctionBody,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<(), String> {
+        for block in body.blocks.iter_mut() {
+            for instr in block.instructions.iter_mut() {
+                instr.visit(self, symbol_table)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the renames and type substitution to the argument, environment and body of
+    /// `lambda`.
+    fn rewrite_lambda(
+        &mut self,
+        lambda: &mut LambdaFunctionSingleArgument,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<(), String> {
+        if let Some(argument) = &mut lambda.argument {
+            self.rename_variable(&mut argument.name);
+            argument.typename.visit(self, symbol_table)?;
+        }
+
+        for (name, typename) in lambda.environment.iter_mut() {
+            if let Some(new_name) = self.renames.get(name) {
+                *name = new_name.clone();
+            }
+            if let Some(typename) = typename {
+                typename.visit(self, symbol_table)?;
+            }
+        }
+
+        if let Some(return_type) = &lambda.return_type {
+            lambda.return_type = Some(self.substitute_type(return_type));
+        }
+
+        self.visit_body(&mut lambda.body, symbol_table)
+    }
+
+    fn free_variables(&self) -> BTreeSet<String> {
+        self.used.difference(&self.defined).cloned().collect()
+    }
+}
+
+impl IrPass for SymbolRewriter {
+    fn initiate(&mut self) {}
+    fn finalize(&mut self) {}
+
+    fn visit_concrete_type(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_type: &mut ConcreteType,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_contract_field(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _field: &mut ContractField,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_enum_value(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _enum_value: &mut EnumValue,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_tuple(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _tuple: &mut Tuple,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variant(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _variant: &mut Variant,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _var_dec: &mut VariableDeclaration,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_concrete_function(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _fnc: &mut ConcreteFunction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_symbol_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _kind: &mut IrIndentifierKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_symbol_name(
+        &mut self,
+        _mode: TreeTraversalMode,
+        symbol: &mut IrIdentifier,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        match &mut symbol.kind {
+            kind if Self::is_variable(kind) => {
+                self.rename_variable(symbol);
+                if !self.defined.contains(&symbol.unresolved) {
+                    self.used.insert(symbol.unresolved.clone());
+                }
+            }
+            IrIndentifierKind::FunctionName | IrIndentifierKind::TemplateFunctionName => {
+                if let Some(name) = self.function_renames.get(&symbol.unresolved) {
+                    symbol.unresolved = name.clone();
+                }
+            }
+            IrIndentifierKind::TypeName
+            | IrIndentifierKind::TypeLikeName(_)
+            | IrIndentifierKind::TemplateTypeName(_) => {
+                if let IrIndentifierKind::TypeLikeName(args)
+                | IrIndentifierKind::TemplateTypeName(args) = &mut symbol.kind
+                {
+                    for arg in args.iter_mut() {
+                        arg.visit(self, symbol_table)?;
+                    }
+                }
+
+                let typename = self.substitute_type(&symbol.unresolved);
+                if typename != symbol.unresolved {
+                    // Instances of composite types, such as `Uint64 -> Uint64`, may not have
+                    // been declared yet
+                    if symbol_table.typename_of(&typename).is_none() {
+                        symbol_table.declare_type(&typename)?;
+                    }
+                    symbol.unresolved = typename;
+                    symbol.resolved = None;
+                }
+            }
+            _ => (),
+        }
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_primitives(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _primitives: &mut IntermediateRepresentation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_body(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_body: &mut FunctionBody,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_kind: &mut FunctionKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_operation(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _operation: &mut Operation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _mode: TreeTraversalMode,
+        instr: &mut Instruction,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        if let Some(result_type) = &mut instr.result_type {
+            result_type.visit(self, symbol_table)?;
+        }
+        instr.operation.visit(self, symbol_table)?;
+
+        // The SSA name is visited last, such that `x = f x` uses the previous `x`
+        if let Some(ssa) = &mut instr.ssa_name {
+            if Self::is_variable(&ssa.kind) {
+                self.rename_variable(ssa);
+                self.defined.insert(ssa.unresolved.clone());
+            }
+        }
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_function_block(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _block: &mut FunctionBlock,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_case_clause(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_function: &mut CaseClause,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+}
+
+/// Lifts the lambda functions of the IR into concrete functions.
+///
+/// The conversion happens in three steps:
+///
+/// 1. Library values and type abstractions referenced from a function body are rematerialised
+///    in the body, such that they do not need to be captured.
+/// 2. Type applications `@f T` are monomorphised by inlining the body of the type abstraction
+///    `f` with `T` substituted for its type variable.
+/// 3. The remaining lambdas are turned into functions which take their environment record as
+///    the first argument and the free variables of each lambda are captured by the
+///    `CreateClosure` instructions referring to it.
+///
+/// Lifted functions are placed before the functions that create them, such that subsequent
+/// passes can deduce the type of a closure before it is used.
+pub struct ClosureConversion {
+    lambdas: BTreeMap<String, LambdaFunctionSingleArgument>,
+}
+
+impl ClosureConversion {
+    pub fn new() -> Self {
+        ClosureConversion {
+            lambdas: BTreeMap::new(),
+        }
+    }
+
+    pub fn run(&mut self, ir: &mut IntermediateRepresentation) -> Result<(), CompilerError> {
+        for lambda in ir.lambda_functions.drain(..) {
+            self.lambdas.insert(lambda.name.unresolved.clone(), lambda);
+        }
+
+        self.rematerialise_globals(ir)?;
+        self.rematerialise_templates(ir)?;
+        self.monomorphise(ir)?;
+        self.lift(ir)?;
+
+        self.lambdas.clear();
+        Ok(())
+    }
+
+    fn is_template(&self, name: &str) -> bool {
+        match self.lambdas.get(name) {
+            Some(lambda) => lambda.type_argument.is_some(),
+            None => false,
+        }
+    }
+
+    /// Prepends the instructions in `candidates` to `body` for every candidate whose name is
+    /// free in `body`, but not in `bound`.
+    fn rematerialise(
+        body: &mut FunctionBody,
+        bound: &BTreeSet<String>,
+        candidates: &HashMap<String, Box<Instruction>>,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<(), String> {
+        loop {
+            let mut collector = SymbolRewriter::collector();
+            collector.visit_body(body, symbol_table)?;
+
+            let missing: Vec<&Box<Instruction>> = collector
+                .free_variables()
+                .iter()
+                .filter(|name| !bound.contains(*name))
+                .filter_map(|name| candidates.get(name))
+                .collect();
+
+            if missing.is_empty() {
+                return Ok(());
+            }
+
+            let entry = match body.blocks.first_mut() {
+                Some(entry) => entry,
+                None => return Err("Internal error: Function body without blocks".to_string()),
+            };
+
+            for value in missing {
+                entry.instructions.push_front(value.clone());
+            }
+        }
+    }
+
+    fn rematerialise_globals(&mut self, ir: &mut IntermediateRepresentation) -> Result<(), String> {
+        // Only values which do not depend on the global initialisation block can be copied.
+        // Globals can be referred to by their plain name or qualified by their library, where
+        // definitions of the main program come first and hence take precedence.
+        let mut globals: HashMap<String, Box<Instruction>> = HashMap::new();
+
+        // Contract parameters shadow library values and are loaded where they are used
+        for field in ir.fields_definitions.iter().filter(|field| field.immutable) {
+            let mut address = field.variable.name.clone();
+            address.is_definition = false;
+            let mut ssa_name = field.variable.name.clone();
+            ssa_name.kind = IrIndentifierKind::VirtualRegister;
+            ssa_name.resolved = None;
+
+            globals.insert(
+                field.variable.name.unresolved.clone(),
+                Box::new(Instruction {
+                    ssa_name: Some(ssa_name),
+                    result_type: None,
+                    operation: Operation::StateLoad {
+                        address: FieldAddress {
+                            name: address,
+                            value: None,
+                        },
+                    },
+                    source_location: field.variable.name.source_location.clone(),
+                }),
+            );
+        }
+
+        for global in ir.global_variables.iter() {
+            match global.value.operation {
+                Operation::Literal { .. }
+                | Operation::CreateClosure { .. }
+                | Operation::InstantiateTemplate { .. }
+                | Operation::InstantiatePrimitive { .. } => (),
+                _ => continue,
+            }
+
+            let qualified_name = format!(
+                "{}{}{}",
+                global.namespace.unresolved, NAMESPACE_SEPARATOR, global.name.unresolved
+            );
+            for name in [global.name.unresolved.clone(), qualified_name] {
+                let mut value = global.value.clone();
+                let mut ssa_name = global.name.clone();
+                ssa_name.unresolved = name.clone();
+                value.ssa_name = Some(ssa_name);
+                globals.entry(name).or_insert(value);
+            }
+        }
+
+        if globals.is_empty() {
+            return Ok(());
+        }
+
+        for function in ir.function_definitions.iter_mut() {
+            let bound = function
+                .arguments
+                .iter()
+                .map(|arg| arg.name.unresolved.clone())
+                .collect();
+            Self::rematerialise(&mut function.body, &bound, &globals, &mut ir.symbol_table)?;
+        }
+
+        for lambda in self.lambdas.values_mut() {
+            // Local variables shadow the library values
+            let mut bound: BTreeSet<String> = lambda
+                .environment
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect();
+            if let Some(argument) = &lambda.argument {
+                bound.insert(argument.name.unresolved.clone());
+            }
+            Self::rematerialise(&mut lambda.body, &bound, &globals, &mut ir.symbol_table)?;
+        }
+
+        Ok(())
+    }
+
+    /// Type abstractions are not values at runtime and hence cannot be captured. Instead, the
+    /// type abstractions which a lambda refers to are copied from the body creating the lambda.
+    fn rematerialise_templates(
+        &mut self,
+        ir: &mut IntermediateRepresentation,
+    ) -> Result<(), String> {
+        let mut creators: HashMap<String, Unit> = HashMap::new();
+        for (index, function) in ir.function_definitions.iter().enumerate() {
+            for name in created_lambdas(&function.body) {
+                creators.entry(name).or_insert(Unit::Function(index));
+            }
+        }
+        for (creator, lambda) in self.lambdas.iter() {
+            for name in created_lambdas(&lambda.body) {
+                creators
+                    .entry(name)
+                    .or_insert(Unit::Lambda(creator.clone()));
+            }
+        }
+
+        // Creators are processed before the lambdas they create
+        let mut order: Vec<(usize, String)> = Vec::new();
+        for name in self.lambdas.keys() {
+            let mut depth = 0;
+            let mut current = name;
+            while let Some(Unit::Lambda(creator)) = creators.get(current) {
+                depth += 1;
+                current = creator;
+                if depth > self.lambdas.len() {
+                    return Err(format!("Internal error: Lambda {} creates itself", name));
+                }
+            }
+            order.push((depth, name.clone()));
+        }
+        order.sort();
+
+        for (_, name) in order {
+            let creator = match creators.get(&name) {
+                Some(creator) => creator,
+                None => continue,
+            };
+
+            let mut templates: HashMap<String, Box<Instruction>> = HashMap::new();
+            let creator_body = body_mut(&mut ir.function_definitions, &mut self.lambdas, creator)?;
+            for block in creator_body.blocks.iter() {
+                for instr in block.instructions.iter() {
+                    if let (Some(ssa), Operation::CreateClosure { function, .. }) =
+                        (&instr.ssa_name, &instr.operation)
+                    {
+                        if self.is_template(&function.unresolved) {
+                            templates.insert(ssa.unresolved.clone(), instr.clone());
+                        }
+                    }
+                }
+            }
+
+            if templates.is_empty() {
+                continue;
+            }
+
+            let lambda = match self.lambdas.get_mut(&name) {
+                Some(lambda) => lambda,
+                None => continue,
+            };
+            let mut bound = BTreeSet::new();
+            if let Some(argument) = &lambda.argument {
+                bound.insert(argument.name.unresolved.clone());
+            }
+            Self::rematerialise(&mut lambda.body, &bound, &templates, &mut ir.symbol_table)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the type abstraction bound to `name` in `body`.
+    fn resolve_template(&self, body: &FunctionBody, name: &str) -> Option<String> {
+        let mut name = name.to_string();
+        // Following aliases, bounded by the number of instructions
+        let instruction_count: usize = body.blocks.iter().map(|b| b.instructions.len()).sum();
+        for _ in 0..=instruction_count {
+            let binding = body
+                .blocks
+                .iter()
+                .flat_map(|block| block.instructions.iter())
+                .find(|instr| match &instr.ssa_name {
+                    Some(ssa) => ssa.unresolved == name,
+                    None => false,
+                })?;
+
+            match &binding.operation {
+                Operation::CreateClosure { function, .. }
+                    if self.is_template(&function.unresolved) =>
+                {
+                    return Some(function.unresolved.clone())
+                }
+                Operation::ResolveSymbol { symbol } => name = symbol.unresolved.clone(),
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Creates copies of the lambdas `names` with the renames and type substitution applied
+    /// and returns a map from the original to the new names.
+    fn clone_lambdas(
+        &mut self,
+        names: Vec<String>,
+        renames: &HashMap<String, String>,
+        type_substitution: &(String, String),
+        symbol_table: &mut SymbolTable,
+    ) -> Result<HashMap<String, String>, String> {
+        let mut function_renames = HashMap::new();
+        for name in names {
+            let mut lambda = match self.lambdas.get(&name) {
+                Some(lambda) => lambda.clone(),
+                None => {
+                    return Err(format!(
+                        "Internal error: Lambda function {} not found",
+                        name
+                    ))
+                }
+            };
+
+            let prefix = if lambda.type_argument.is_some() {
+                "template"
+            } else {
+                "lambda"
+            };
+            let mut new_name = symbol_table
+                .name_generator
+                .new_lambda_name(prefix, lambda.name.kind.clone());
+            new_name.source_location = lambda.name.source_location.clone();
+            lambda.name = new_name.clone();
+
+            let nested = self.clone_lambdas(
+                created_lambdas(&lambda.body),
+                renames,
+                type_substitution,
+                symbol_table,
+            )?;
+            let mut rewriter =
+                SymbolRewriter::new(renames.clone(), nested, Some(type_substitution.clone()));
+            rewriter.rewrite_lambda(&mut lambda, symbol_table)?;
+
+            function_renames.insert(name, new_name.unresolved.clone());
+            self.lambdas.insert(new_name.unresolved, lambda);
+        }
+        Ok(function_renames)
+    }
+
+    /// Instantiates the type abstraction `template` with `type_argument` and returns the
+    /// instructions of its body together with the symbol holding its value.
+    fn instantiate(
+        &mut self,
+        template: &str,
+        type_argument: &IrIdentifier,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<(VecDeque<Box<Instruction>>, IrIdentifier), CompilerError> {
+        let lambda = match self.lambdas.get(template) {
+            Some(lambda) => lambda.clone(),
+            None => {
+                return Err(CompilerError::internal(format!(
+                    "Internal error: Lambda function {} not found",
+                    template
+                )))
+            }
+        };
+
+        let variable = match &lambda.type_argument {
+            Some(variable) => variable.clone(),
+            None => {
+                return Err(CompilerError::internal(format!(
+                    "`{}` is not a type abstraction",
+                    template
+                )))
+            }
+        };
+
+        if lambda.body.blocks.len() != 1 {
+            return Err(CompilerError::unsupported(
+                "Control flow in type abstractions is not supported".to_string(),
+            ));
+        }
+
+        let mut instructions = lambda.body.blocks[0].instructions.clone();
+
+        // Definitions get fresh names, such that the body can be inlined more than once
+        let mut renames: HashMap<String, String> = HashMap::new();
+        for instr in instructions.iter() {
+            if let Some(ssa) = &instr.ssa_name {
+                let fresh = symbol_table.name_generator.new_intermediate();
+                renames.insert(ssa.unresolved.clone(), fresh.unresolved);
+            }
+        }
+
+        let type_substitution = (variable, type_argument.unresolved.clone());
+        let function_renames = self.clone_lambdas(
+            created_lambdas(&lambda.body),
+            &renames,
+            &type_substitution,
+            symbol_table,
+        )?;
+
+        let mut rewriter = SymbolRewriter::new(renames, function_renames, Some(type_substitution));
+        for instr in instructions.iter_mut() {
+            instr.visit(&mut rewriter, symbol_table)?;
+        }
+
+        match instructions.pop_back().map(|instr| instr.operation) {
+            Some(Operation::Return(Some(value))) => Ok((instructions, value)),
+            _ => Err(CompilerError::internal(
+                "Internal error: Type abstraction does not return a value".to_string(),
+            )),
+        }
+    }
+
+    /// Inlines the type applications in `unit`. Returns true if the body was changed.
+    fn monomorphise_body(
+        &mut self,
+        unit: &Unit,
+        ir: &mut IntermediateRepresentation,
+    ) -> Result<bool, CompilerError> {
+        let mut changed = false;
+        loop {
+            let body = body_mut(&mut ir.function_definitions, &mut self.lambdas, unit)?;
+            let mut position = None;
+            'search: for (block_index, block) in body.blocks.iter().enumerate() {
+                for (instr_index, instr) in block.instructions.iter().enumerate() {
+                    if let Operation::InstantiateTemplate { .. } = instr.operation {
+                        position = Some((block_index, instr_index));
+                        break 'search;
+                    }
+                }
+            }
+
+            let (block_index, instr_index) = match position {
+                Some(p) => p,
+                None => return Ok(changed),
+            };
+
+            let instr = body.blocks[block_index].instructions[instr_index].clone();
+            let (template, type_arguments) = match instr.operation {
+                Operation::InstantiateTemplate {
+                    template,
+                    type_arguments,
+                } => (template, type_arguments),
+                _ => {
+                    return Err(CompilerError::internal(
+                        "Internal error: Expected type application".to_string(),
+                    ))
+                }
+            };
+
+            let template_name = match self.resolve_template(body, &template.unresolved) {
+                Some(name) => name,
+                None => {
+                    return Err(CompilerError::internal(format!(
+                        "Unable to resolve the type abstraction `{}`",
+                        template.unresolved
+                    )))
+                }
+            };
+
+            let mut type_arguments = type_arguments.into_iter();
+            let type_argument = match type_arguments.next() {
+                Some(t) => t,
+                None => {
+                    return Err(CompilerError::internal(
+                        "Type application without type arguments".to_string(),
+                    ))
+                }
+            };
+            let remaining_arguments: Vec<IrIdentifier> = type_arguments.collect();
+
+            let (mut instructions, value) =
+                self.instantiate(&template_name, &type_argument, &mut ir.symbol_table)?;
+
+            // `@f A B` is applied as `@(@f A) B`
+            let operation = if remaining_arguments.is_empty() {
+                Operation::ResolveSymbol { symbol: value }
+            } else {
+                Operation::InstantiateTemplate {
+                    template: value,
+                    type_arguments: remaining_arguments,
+                }
+            };
+            if instr.ssa_name.is_some() {
+                instructions.push_back(Box::new(Instruction {
+                    ssa_name: instr.ssa_name.clone(),
+                    result_type: None,
+                    operation,
+                    source_location: instr.source_location.clone(),
+                }));
+            }
+
+            let body = body_mut(&mut ir.function_definitions, &mut self.lambdas, unit)?;
+            let block = &mut body.blocks[block_index];
+            let mut tail = block.instructions.split_off(instr_index);
+            tail.pop_front();
+            block.instructions.extend(instructions);
+            block.instructions.extend(tail);
+
+            changed = true;
+        }
+    }
+
+    fn monomorphise(&mut self, ir: &mut IntermediateRepresentation) -> Result<(), CompilerError> {
+        // Instantiating a template may create new lambdas which in turn need monomorphising
+        loop {
+            let mut changed = false;
+            for index in 0..ir.function_definitions.len() {
+                changed |= self.monomorphise_body(&Unit::Function(index), ir)?;
+            }
+
+            let names: Vec<String> = self
+                .lambdas
+                .iter()
+                .filter(|(_, lambda)| lambda.type_argument.is_none())
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in names {
+                changed |= self.monomorphise_body(&Unit::Lambda(name), ir)?;
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        // Type abstractions have all been inlined at this point
+        let templates: BTreeSet<String> = self
+            .lambdas
+            .iter()
+            .filter(|(_, lambda)| lambda.type_argument.is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        let bodies = ir
+            .function_definitions
+            .iter_mut()
+            .map(|function| &mut function.body)
+            .chain(self.lambdas.values_mut().map(|lambda| &mut lambda.body));
+        for body in bodies {
+            for block in body.blocks.iter_mut() {
+                block.instructions.retain(|instr| match &instr.operation {
+                    Operation::CreateClosure { function, .. } => {
+                        !templates.contains(&function.unresolved)
+                    }
+                    _ => true,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Orders the lambdas created by `name` before `name` itself.
+    fn postorder(
+        &self,
+        name: &str,
+        visited: &mut BTreeSet<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), String> {
+        if !visited.insert(name.to_string()) {
+            return Ok(());
+        }
+
+        let lambda = match self.lambdas.get(name) {
+            Some(lambda) => lambda,
+            None => {
+                return Err(format!(
+                    "Internal error: Lambda function {} not found",
+                    name
+                ))
+            }
+        };
+
+        for child in created_lambdas(&lambda.body) {
+            self.postorder(&child, visited, order)?;
+        }
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    /// Converts the lambda `name` into a function and returns it together with the variables
+    /// it captures.
+    fn convert(
+        &mut self,
+        name: &str,
+        ir: &mut IntermediateRepresentation,
+    ) -> Result<(ConcreteFunction, Vec<IrIdentifier>), String> {
+        let mut lambda = match self.lambdas.remove(name) {
+            Some(lambda) => lambda,
+            None => {
+                return Err(format!(
+                    "Internal error: Lambda function {} not found",
+                    name
+                ))
+            }
+        };
+
+        let argument = match &lambda.argument {
+            Some(argument) => argument.clone(),
+            None => {
+                return Err(format!(
+                    "Internal error: Anonymous function {} does not take an argument",
+                    name
+                ))
+            }
+        };
+
+        // Closures are applied with the environment record as the deepest argument
+        if argument.name.unresolved.as_str() <= CLOSURE_ENVIRONMENT_ARGUMENT {
+            return Err(format!(
+                "Argument name `{}` is reserved for anonymous functions",
+                argument.name.unresolved
+            ));
+        }
+
+        let mut collector = SymbolRewriter::collector();
+        collector.visit_body(&mut lambda.body, &mut ir.symbol_table)?;
+        let mut free = collector.free_variables();
+        free.remove(&argument.name.unresolved);
+
+        let mut captures: Vec<(String, IrIdentifier)> = Vec::new();
+        for variable in free {
+            let typename = match lambda
+                .environment
+                .iter()
+                .rev()
+                .find(|(candidate, _)| *candidate == variable)
+            {
+                Some((_, typename)) => typename,
+                None => continue,
+            };
+
+            match typename {
+                Some(typename) => {
+                    let mut typename = typename.clone();
+                    typename.kind = IrIndentifierKind::TypeName;
+                    typename.is_definition = false;
+                    captures.push((variable, typename));
+                }
+                None => {
+                    return Err(format!(
+                        "Unable to deduce the type of `{}` captured by an anonymous function. Consider adding a type annotation.",
+                        variable
+                    ))
+                }
+            }
+        }
+
+        // Environment record
+        let mut environment = Tuple::new();
+        for (_, typename) in captures.iter() {
+            environment.add_field(typename.clone());
+        }
+        let environment_type = ir
+            .symbol_table
+            .name_generator
+            .generate_anonymous_type_id("ClosureEnvironment".to_string());
+        ir.type_definitions.push(ConcreteType::Tuple {
+            name: environment_type.clone(),
+            namespace: lambda.namespace.clone(),
+            data_layout: Box::new(environment),
+        });
+
+        let source_location = (
+            SourcePosition::invalid_position(),
+            SourcePosition::invalid_position(),
+        );
+        let closure = IrIdentifier::new(
+            CLOSURE_ENVIRONMENT_ARGUMENT.to_string(),
+            IrIndentifierKind::VirtualRegister,
+            source_location.clone(),
+        );
+
+        let entry = match lambda.body.blocks.first_mut() {
+            Some(entry) => entry,
+            None => return Err("Internal error: Function body without blocks".to_string()),
+        };
+
+        for (index, (variable, typename)) in captures.iter().enumerate().rev() {
+            entry.instructions.push_front(Box::new(Instruction {
+                ssa_name: Some(IrIdentifier::new(
+                    variable.clone(),
+                    IrIndentifierKind::VirtualRegister,
+                    source_location.clone(),
+                )),
+                result_type: Some(typename.clone()),
+                operation: Operation::LoadCapture {
+                    closure: closure.clone(),
+                    index,
+                },
+                source_location: source_location.clone(),
+            }));
+        }
+
+        // Both arguments must be referenced for them to become arguments of the entry block
+        let mut unused = Vec::new();
+        if captures.is_empty() {
+            unused.push(closure.clone());
+        }
+        if !collector.used.contains(&argument.name.unresolved) {
+            let mut symbol = argument.name.clone();
+            symbol.is_definition = false;
+            unused.push(symbol);
+        }
+        for symbol in unused {
+            entry.instructions.push_front(Box::new(Instruction {
+                ssa_name: None,
+                result_type: None,
+                operation: Operation::TerminatingRef(symbol),
+                source_location: source_location.clone(),
+            }));
+        }
+
+        let mut environment_typename = environment_type;
+        environment_typename.is_definition = false;
+
+        let function = ConcreteFunction {
+            name: lambda.name,
+            namespace: lambda.namespace,
+            function_kind: FunctionKind::Lambda,
+            return_type: None,
+            arguments: [
+                VariableDeclaration::new(
+                    CLOSURE_ENVIRONMENT_ARGUMENT.to_string(),
+                    false,
+                    environment_typename,
+                ),
+                argument,
+            ]
+            .to_vec(),
+            body: lambda.body,
+        };
+
+        let captures = captures
+            .into_iter()
+            .map(|(variable, _)| {
+                IrIdentifier::new(
+                    variable,
+                    IrIndentifierKind::VirtualRegister,
+                    source_location.clone(),
+                )
+            })
+            .collect();
+
+        Ok((function, captures))
+    }
+
+    fn lift(&mut self, ir: &mut IntermediateRepresentation) -> Result<(), String> {
+        let mut visited = BTreeSet::new();
+        let mut order = Vec::new();
+        for function in ir.function_definitions.iter() {
+            for name in created_lambdas(&function.body) {
+                self.postorder(&name, &mut visited, &mut order)?;
+            }
+        }
+
+        let mut lifted: Vec<ConcreteFunction> = Vec::new();
+        for name in order {
+            let (function, captures) = self.convert(&name, ir)?;
+
+            let bodies = ir
+                .function_definitions
+                .iter_mut()
+                .map(|function| &mut function.body)
+                .chain(self.lambdas.values_mut().map(|lambda| &mut lambda.body));
+            for body in bodies {
+                for block in body.blocks.iter_mut() {
+                    for instr in block.instructions.iter_mut() {
+                        if let Operation::CreateClosure {
+                            function,
+                            captures: closure_captures,
+                        } = &mut instr.operation
+                        {
+                            if function.unresolved == name {
+                                *closure_captures = captures.clone();
+                            }
+                        }
+                    }
+                }
+            }
+
+            lifted.push(function);
+        }
+
+        lifted.extend(ir.function_definitions.drain(..));
+        ir.function_definitions = lifted;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use scilla_parser::ast::{TraversalResult, TreeTraversalMode};
+
+use crate::intermediate_representation::{
+    pass::IrPass,
+    primitives::{
+        CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FunctionBlock,
+        FunctionBody, FunctionKind, Instruction, IntermediateRepresentation, IrIdentifier,
+        IrIndentifierKind, Operation, Tuple, VariableDeclaration, Variant,
+    },
+    symbol_table::SymbolTable,
+};
+
+/// Integer value of a literal. Integers wider than 128 bits are not folded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Integer {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+impl Integer {
+    /// Parses the literal `data` of type `typename` and returns the value together with the
+    /// width of the type.
+    fn parse(typename: &str, data: &str) -> Option<(Integer, u32)> {
+        let (signed, bits) = if let Some(bits) = typename.strip_prefix("Uint") {
+            (false, bits)
+        } else if let Some(bits) = typename.strip_prefix("Int") {
+            (true, bits)
+        } else {
+            return None;
+        };
+        let bits: u32 = bits.parse().ok()?;
+        if bits > 128 {
+            return None;
+        }
+
+        let value = if signed {
+            Integer::Signed(data.trim().parse().ok()?)
+        } else {
+            Integer::Unsigned(data.trim().parse().ok()?)
+        };
+        Some((value, bits))
+    }
+
+    /// Whether the value can be represented with `bits` bits.
+    fn fits(&self, bits: u32) -> bool {
+        match *self {
+            Integer::Signed(v) => {
+                bits == 128 || (v >= -(1i128 << (bits - 1)) && v < (1i128 << (bits - 1)))
+            }
+            Integer::Unsigned(v) => bits == 128 || v < (1u128 << bits),
+        }
+    }
+
+    fn to_literal(&self) -> String {
+        match self {
+            Integer::Signed(v) => v.to_string(),
+            Integer::Unsigned(v) => v.to_string(),
+        }
+    }
+}
+
+/// Evaluates the builtin `name` on two integers of width `bits`. Returns None if the builtin is
+/// not foldable or if it fails, e.g. on overflow or division by zero, in which case the error is
+/// left to be raised at runtime.
+fn evaluate(name: &str, left: Integer, right: Integer, bits: u32) -> Option<Integer> {
+    let ret = match (left, right) {
+        (Integer::Signed(a), Integer::Signed(b)) => Integer::Signed(match name {
+            "builtin__add" => a.checked_add(b)?,
+            "builtin__sub" => a.checked_sub(b)?,
+            "builtin__mul" => a.checked_mul(b)?,
+            "builtin__div" => a.checked_div(b)?,
+            "builtin__rem" => a.checked_rem(b)?,
+            _ => return None,
+        }),
+        (Integer::Unsigned(a), Integer::Unsigned(b)) => Integer::Unsigned(match name {
+            "builtin__add" => a.checked_add(b)?,
+            "builtin__sub" => a.checked_sub(b)?,
+            "builtin__mul" => a.checked_mul(b)?,
+            "builtin__div" => a.checked_div(b)?,
+            "builtin__rem" => a.checked_rem(b)?,
+            _ => return None,
+        }),
+        _ => return None,
+    };
+
+    if ret.fits(bits) {
+        Some(ret)
+    } else {
+        None
+    }
+}
+
+/// Replaces integer arithmetic on literals by the literal holding the result. Only variables
+/// which are defined once are considered constant.
+pub struct ConstantFolding {
+    /// Constant variables with their literal value and type.
+    constants: HashMap<String, (String, IrIdentifier)>,
+}
+
+impl ConstantFolding {
+    pub fn new() -> Self {
+        Self {
+            constants: HashMap::new(),
+        }
+    }
+
+    fn fold(&self, operation: &Operation) -> Option<Operation> {
+        let (name, arguments) = match operation {
+            Operation::CallExternalFunction { name, arguments } => (name, arguments),
+            _ => return None,
+        };
+        let (left, right) = match &arguments[..] {
+            [left, right] => (
+                self.constants.get(&left.unresolved)?,
+                self.constants.get(&right.unresolved)?,
+            ),
+            _ => return None,
+        };
+
+        // Both operands must be of the same type, which is also the type of the result
+        let typename = &left.1;
+        if typename.unresolved != right.1.unresolved {
+            return None;
+        }
+        let (a, bits) = Integer::parse(&typename.unresolved, &left.0)?;
+        let (b, _) = Integer::parse(&typename.unresolved, &right.0)?;
+        let value = evaluate(&name.unresolved, a, b, bits)?;
+
+        Some(Operation::Literal {
+            data: value.to_literal(),
+            typename: typename.clone(),
+        })
+    }
+}
+
+impl IrPass for ConstantFolding {
+    fn visit_concrete_function(
+        &mut self,
+        _mode: TreeTraversalMode,
+        fnc: &mut ConcreteFunction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        let definitions = fnc.definition_counts();
+        self.constants.clear();
+
+        // Folding until no further instructions can be folded, as folded results may be the
+        // operands of instructions earlier in the function
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in fnc.body.blocks.iter_mut() {
+                for instr in block.instructions.iter_mut() {
+                    let ssa_name = match &instr.ssa_name {
+                        Some(ssa_name) if definitions.get(&ssa_name.unresolved) == Some(&1) => {
+                            ssa_name.unresolved.clone()
+                        }
+                        _ => continue,
+                    };
+
+                    if let Some(folded) = self.fold(&instr.operation) {
+                        instr.operation = folded;
+                        changed = true;
+                    }
+
+                    if let Operation::Literal { data, typename } = &instr.operation {
+                        if !self.constants.contains_key(&ssa_name) {
+                            self.constants
+                                .insert(ssa_name, (data.clone(), typename.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_concrete_type(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_type: &mut ConcreteType,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_contract_field(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _field: &mut ContractField,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_symbol_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_kind: &mut IrIndentifierKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_symbol_name(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_name: &mut IrIdentifier,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_enum_value(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _enum_value: &mut EnumValue,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_tuple(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _tuple: &mut Tuple,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variant(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _variant: &mut Variant,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _var_dec: &mut VariableDeclaration,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_operation(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _operation: &mut Operation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _instruction: &mut Instruction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_block(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_block: &mut FunctionBlock,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_body(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_body: &mut FunctionBody,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_kind: &mut FunctionKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_case_clause(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_function: &mut CaseClause,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_primitives(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _primitives: &mut IntermediateRepresentation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn initiate(&mut self) {}
+
+    fn finalize(&mut self) {}
+}
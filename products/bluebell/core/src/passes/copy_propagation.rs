@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use scilla_parser::ast::{TraversalResult, TreeTraversalMode};
+
+use crate::intermediate_representation::{
+    pass::IrPass,
+    primitives::{
+        CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FunctionBlock,
+        FunctionBody, FunctionKind, Instruction, IntermediateRepresentation, IrIdentifier,
+        IrIndentifierKind, Operation, Tuple, VariableDeclaration, Variant,
+    },
+    symbol_table::SymbolTable,
+};
+
+/// Replaces uses of copies, `y = x`, and of phi nodes merging a single value by the original
+/// value. The copies themselves are left for `DeadCodeElimination` to remove. Only variables
+/// which are defined once within the function are propagated, as other variables may hold
+/// different values depending on the path taken.
+pub struct CopyPropagation {
+    copies: HashMap<String, String>,
+}
+
+impl CopyPropagation {
+    pub fn new() -> Self {
+        Self {
+            copies: HashMap::new(),
+        }
+    }
+
+    /// Returns the variable `operation` copies, if any.
+    fn copied_variable(operation: &Operation) -> Option<&IrIdentifier> {
+        match operation {
+            Operation::ResolveSymbol { symbol } => Some(symbol),
+            Operation::PhiNode(values) => match values.first() {
+                Some(first) if values.iter().all(|v| v.unresolved == first.unresolved) => {
+                    Some(first)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Follows chains of copies back to the original variable.
+    fn original(&self, name: &str) -> String {
+        let mut ret = name.to_string();
+        // Bounded by the number of copies to guard against cycles
+        for _ in 0..=self.copies.len() {
+            match self.copies.get(&ret) {
+                Some(source) => ret = source.clone(),
+                None => break,
+            }
+        }
+        ret
+    }
+}
+
+impl IrPass for CopyPropagation {
+    fn visit_concrete_function(
+        &mut self,
+        _mode: TreeTraversalMode,
+        fnc: &mut ConcreteFunction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        let definitions = fnc.definition_counts();
+        let defined_once = |name: &str| definitions.get(name) == Some(&1);
+
+        self.copies.clear();
+        for block in fnc.body.blocks.iter() {
+            for instr in block.instructions.iter() {
+                let ssa_name = match &instr.ssa_name {
+                    Some(ssa_name) if defined_once(&ssa_name.unresolved) => ssa_name,
+                    _ => continue,
+                };
+                if let Some(source) = Self::copied_variable(&instr.operation) {
+                    // Globals and variables defined several times are not propagated
+                    if source.is_variable()
+                        && defined_once(&source.unresolved)
+                        && source.unresolved != ssa_name.unresolved
+                    {
+                        self.copies
+                            .insert(ssa_name.unresolved.clone(), source.unresolved.clone());
+                    }
+                }
+            }
+        }
+
+        for block in fnc.body.blocks.iter_mut() {
+            for instr in block.instructions.iter_mut() {
+                // Each value of a phi node is defined by the predecessor it arrives from, which
+                // need not hold for the original
+                if let Operation::PhiNode(_) = instr.operation {
+                    continue;
+                }
+                for operand in instr.operation.operands_mut() {
+                    if operand.is_variable() && self.copies.contains_key(&operand.unresolved) {
+                        operand.unresolved = self.original(&operand.unresolved);
+                    }
+                }
+            }
+        }
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_concrete_type(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_type: &mut ConcreteType,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_contract_field(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _field: &mut ContractField,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_symbol_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_kind: &mut IrIndentifierKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_symbol_name(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_name: &mut IrIdentifier,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_enum_value(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _enum_value: &mut EnumValue,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_tuple(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _tuple: &mut Tuple,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variant(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _variant: &mut Variant,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _var_dec: &mut VariableDeclaration,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_operation(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _operation: &mut Operation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _instruction: &mut Instruction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_block(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_block: &mut FunctionBlock,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_body(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_body: &mut FunctionBody,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_kind: &mut FunctionKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_case_clause(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_function: &mut CaseClause,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_primitives(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _primitives: &mut IntermediateRepresentation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn initiate(&mut self) {}
+
+    fn finalize(&mut self) {}
+}
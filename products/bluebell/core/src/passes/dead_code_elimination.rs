@@ -0,0 +1,262 @@
+use std::collections::{BTreeSet, HashSet};
+
+use scilla_parser::ast::{TraversalResult, TreeTraversalMode};
+
+use crate::intermediate_representation::{
+    pass::IrPass,
+    primitives::{
+        CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FunctionBlock,
+        FunctionBody, FunctionKind, Instruction, IntermediateRepresentation, IrIdentifier,
+        IrIndentifierKind, Operation, Tuple, VariableDeclaration, Variant,
+    },
+    symbol_table::SymbolTable,
+};
+
+/// Removes blocks which cannot be reached from the entry block and instructions without side
+/// effects whose result is never used.
+pub struct DeadCodeElimination {}
+
+impl DeadCodeElimination {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the names of all variables used as operands in `fnc`.
+    fn used_variables(fnc: &mut ConcreteFunction) -> HashSet<String> {
+        let mut ret = HashSet::new();
+        for block in fnc.body.blocks.iter_mut() {
+            for instr in block.instructions.iter_mut() {
+                for operand in instr.operation.operands_mut() {
+                    ret.insert(operand.unresolved.clone());
+                }
+            }
+        }
+        ret
+    }
+
+    fn remove_unreachable_blocks(fnc: &mut ConcreteFunction) {
+        let entry = match fnc.body.blocks.first() {
+            Some(entry) => entry.name.unresolved.clone(),
+            None => return,
+        };
+
+        let mut reachable: BTreeSet<String> = BTreeSet::new();
+        let mut queue = vec![entry];
+        while let Some(name) = queue.pop() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+            if let Some(block) = fnc
+                .body
+                .blocks
+                .iter_mut()
+                .find(|block| block.name.unresolved == name)
+            {
+                for instr in block.instructions.iter_mut() {
+                    for target in instr.operation.jump_targets_mut() {
+                        queue.push(target.unresolved.clone());
+                    }
+                }
+            }
+        }
+
+        fnc.body
+            .blocks
+            .retain(|block| reachable.contains(&block.name.unresolved));
+    }
+
+    fn remove_unused_instructions(fnc: &mut ConcreteFunction) {
+        // Removing an instruction may leave its operands unused
+        loop {
+            let used = Self::used_variables(fnc);
+            let mut changed = false;
+            for block in fnc.body.blocks.iter_mut() {
+                let before = block.instructions.len();
+                block.instructions.retain(|instr| match &instr.ssa_name {
+                    Some(ssa_name) => {
+                        !instr.operation.is_pure() || used.contains(&ssa_name.unresolved)
+                    }
+                    None => true,
+                });
+                changed = changed || block.instructions.len() != before;
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+}
+
+impl IrPass for DeadCodeElimination {
+    fn visit_concrete_function(
+        &mut self,
+        _mode: TreeTraversalMode,
+        fnc: &mut ConcreteFunction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        let used_before = Self::used_variables(fnc);
+
+        Self::remove_unreachable_blocks(fnc);
+        Self::remove_unused_instructions(fnc);
+
+        // Arguments must remain referenced for them to stay arguments of the entry block
+        let used_after = Self::used_variables(fnc);
+        let source_location = fnc.name.source_location.clone();
+        if let Some(entry) = fnc.body.blocks.first_mut() {
+            for argument in fnc.arguments.iter() {
+                let name = &argument.name.unresolved;
+                if used_before.contains(name) && !used_after.contains(name) {
+                    let mut symbol = argument.name.clone();
+                    symbol.is_definition = false;
+                    entry.instructions.push_front(Box::new(Instruction {
+                        ssa_name: None,
+                        result_type: None,
+                        operation: Operation::TerminatingRef(symbol),
+                        source_location: source_location.clone(),
+                    }));
+                }
+            }
+        }
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_concrete_type(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_type: &mut ConcreteType,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_contract_field(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _field: &mut ContractField,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_symbol_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_kind: &mut IrIndentifierKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_symbol_name(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_name: &mut IrIdentifier,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_enum_value(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _enum_value: &mut EnumValue,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_tuple(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _tuple: &mut Tuple,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variant(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _variant: &mut Variant,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _var_dec: &mut VariableDeclaration,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_operation(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _operation: &mut Operation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _instruction: &mut Instruction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_block(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_block: &mut FunctionBlock,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_body(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_body: &mut FunctionBody,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_kind: &mut FunctionKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_case_clause(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_function: &mut CaseClause,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_primitives(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _primitives: &mut IntermediateRepresentation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn initiate(&mut self) {}
+
+    fn finalize(&mut self) {}
+}
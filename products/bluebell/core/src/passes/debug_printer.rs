@@ -4,9 +4,9 @@ use crate::intermediate_representation::{
     pass::IrPass,
     pass_executor::PassExecutor,
     primitives::{
-        CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FunctionBlock,
-        FunctionBody, FunctionKind, Instruction, IntermediateRepresentation, IrIdentifier,
-        IrIndentifierKind, Operation, Tuple, VariableDeclaration, Variant,
+        CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FieldAddress,
+        FunctionBlock, FunctionBody, FunctionKind, Instruction, IntermediateRepresentation,
+        IrIdentifier, IrIndentifierKind, Operation, Tuple, VariableDeclaration, Variant,
     },
     symbol_table::SymbolTable,
 };
@@ -186,6 +186,34 @@ impl IrPass for DebugPrinter {
                 address: _,
                 value: _,
             } => self.script.push_str("sstore [TODO]"),
+            Operation::StateMapLoad { address, keys } => {
+                self.script.push_str("sload_map ");
+                self.print_map_access(address, keys, symbol_table)?;
+            }
+            Operation::StateMapExists { address, keys } => {
+                self.script.push_str("sexists_map ");
+                self.print_map_access(address, keys, symbol_table)?;
+            }
+            Operation::StateRemoteLoad { contract, field } => {
+                self.script.push_str("sload_remote ");
+                contract.visit(self, symbol_table)?;
+                self.script.push_str(".");
+                self.script.push_str(field);
+            }
+            Operation::StateMapDelete { address, keys } => {
+                self.script.push_str("sdelete_map ");
+                self.print_map_access(address, keys, symbol_table)?;
+            }
+            Operation::StateMapStore {
+                address,
+                keys,
+                value,
+            } => {
+                self.script.push_str("sstore_map ");
+                self.print_map_access(address, keys, symbol_table)?;
+                self.script.push_str(" ");
+                value.visit(self, symbol_table)?;
+            }
 
             Operation::IsEqual { left, right } => {
                 self.script.push_str("eq ");
@@ -244,6 +272,83 @@ impl IrPass for DebugPrinter {
                 self.script.push_str(" ");
                 self.script.push_str(&data);
             }
+            Operation::ConstructMessage { entries } => {
+                self.script.push_str("message {");
+                for (i, (field, value)) in entries.iter_mut().enumerate() {
+                    if i > 0 {
+                        self.script.push_str(",");
+                    }
+                    self.script.push_str(" ");
+                    self.script.push_str(field);
+                    self.script.push_str(": ");
+                    value.visit(self, symbol_table)?;
+                }
+                self.script.push_str(" }");
+            }
+            Operation::EmitEvent { message } => {
+                self.script.push_str("event ");
+                message.visit(self, symbol_table)?;
+            }
+            Operation::SendMessage { message } => {
+                self.script.push_str("send ");
+                message.visit(self, symbol_table)?;
+            }
+            Operation::CreateClosure { function, captures } => {
+                self.script.push_str("closure ");
+                function.visit(self, symbol_table)?;
+                self.script.push_str(" [");
+                for (i, capture) in captures.iter_mut().enumerate() {
+                    if i > 0 {
+                        self.script.push_str(", ");
+                    }
+                    capture.visit(self, symbol_table)?;
+                }
+                self.script.push_str("]");
+            }
+            Operation::LoadCapture { closure, index } => {
+                self.script.push_str("capture ");
+                closure.visit(self, symbol_table)?;
+                self.script.push_str(&format!("[{}]", index));
+            }
+            Operation::ApplyClosure { closure, argument } => {
+                self.script.push_str("apply ");
+                closure.visit(self, symbol_table)?;
+                self.script.push_str(" ");
+                argument.visit(self, symbol_table)?;
+            }
+            Operation::InstantiateTemplate {
+                template,
+                type_arguments,
+            } => {
+                self.script.push_str("@");
+                template.visit(self, symbol_table)?;
+                for arg in type_arguments.iter_mut() {
+                    self.script.push_str(" ");
+                    arg.visit(self, symbol_table)?;
+                }
+            }
+            Operation::InstantiatePrimitive {
+                primitive,
+                type_arguments,
+            } => {
+                self.script.push_str("@");
+                primitive.visit(self, symbol_table)?;
+                for arg in type_arguments.iter_mut() {
+                    self.script.push_str(" ");
+                    arg.visit(self, symbol_table)?;
+                }
+            }
+            Operation::LoadConstructorArgument {
+                value,
+                constructor,
+                index,
+            } => {
+                self.script.push_str("argument ");
+                value.visit(self, symbol_table)?;
+                self.script.push_str(" of ");
+                constructor.visit(self, symbol_table)?;
+                self.script.push_str(&format!("[{}]", index));
+            }
             Operation::PhiNode(arguments) => {
                 self.script.push_str("phi [");
                 for (i, arg) in arguments.iter_mut().enumerate() {
@@ -268,13 +373,10 @@ impl IrPass for DebugPrinter {
             }
             Operation::Revert(arg) => {
                 self.script.push_str("revert");
-                match arg {
-                    Some(r) => {
-                        self.script.push_str(" ");
-                        r.visit(self, symbol_table)?;
-                    }
-                    &mut None => todo!(),
-                };
+                if let Some(r) = arg {
+                    self.script.push_str(" ");
+                    r.visit(self, symbol_table)?;
+                }
             }
         }
         Ok(TraversalResult::SkipChildren)
@@ -421,6 +523,12 @@ impl IrPass for DebugPrinter {
             FunctionKind::Function => {
                 self.script.push_str("function");
             }
+            FunctionKind::Lambda => {
+                self.script.push_str("lambda");
+            }
+            FunctionKind::Constructor => {
+                self.script.push_str("constructor");
+            }
         }
 
         Ok(TraversalResult::SkipChildren)
@@ -495,4 +603,22 @@ impl DebugPrinter {
     pub fn value(&self) -> String {
         self.script.clone()
     }
+
+    fn print_map_access(
+        &mut self,
+        address: &FieldAddress,
+        keys: &mut [IrIdentifier],
+        symbol_table: &mut SymbolTable,
+    ) -> Result<(), String> {
+        match &address.name.resolved {
+            Some(n) => self.script.push_str(&n),
+            None => self.script.push_str("UNRESOLVED"),
+        }
+        for key in keys.iter_mut() {
+            self.script.push_str("[");
+            key.visit(self, symbol_table)?;
+            self.script.push_str("]");
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,448 @@
+use std::collections::{BTreeSet, HashMap};
+
+use scilla_parser::ast::{TraversalResult, TreeTraversalMode};
+
+use crate::intermediate_representation::{
+    pass::IrPass,
+    primitives::{
+        CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FunctionBlock,
+        FunctionBody, FunctionKind, Instruction, IntermediateRepresentation, IrIdentifier,
+        IrIndentifierKind, Operation, Tuple, VariableDeclaration, Variant,
+    },
+    symbol_table::SymbolTable,
+};
+
+/// Resolved name of `identifier`, or its unresolved name if it has not been resolved yet. Block
+/// labels are resolved when they are created.
+fn resolved_name(identifier: &IrIdentifier) -> String {
+    match &identifier.resolved {
+        Some(resolved) => resolved.clone(),
+        None => identifier.unresolved.clone(),
+    }
+}
+
+fn is_terminator(operation: &Operation) -> bool {
+    matches!(
+        operation,
+        Operation::Jump(_)
+            | Operation::ConditionalJump { .. }
+            | Operation::Return(_)
+            | Operation::Revert(_)
+    )
+}
+
+/// Checks the invariants that passes and the code generator rely on:
+///
+/// - every SSA name is defined once and its definition dominates its uses,
+/// - jumps target blocks which exist in the function,
+/// - every block ends with a terminator,
+/// - optionally, the arguments passed along every jump are those expected by the target
+///   block and the entry block expects the function arguments. This only holds once block
+///   arguments have been balanced and their dependencies deduced again.
+///
+/// Blocks which cannot be reached from the entry block are only checked for their structure.
+pub struct IrVerifier {
+    check_block_arguments: bool,
+}
+
+impl IrVerifier {
+    pub fn new() -> Self {
+        Self {
+            check_block_arguments: false,
+        }
+    }
+
+    pub fn set_check_block_arguments(&mut self, check_block_arguments: bool) -> &mut Self {
+        self.check_block_arguments = check_block_arguments;
+
+        self
+    }
+
+    /// Verifies every function of `ir`, reporting all violations found.
+    pub fn verify(&mut self, ir: &IntermediateRepresentation) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for function in ir.function_definitions.iter() {
+            errors.extend(self.verify_function(function));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    fn verify_function(&self, fnc: &ConcreteFunction) -> Vec<String> {
+        let function_name = fnc.name.unresolved.clone();
+        let mut errors = Vec::new();
+        let mut error = |message: String| errors.push(format!("{}: {}", function_name, message));
+
+        let blocks: HashMap<String, usize> = fnc
+            .body
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| (resolved_name(&block.name), i))
+            .collect();
+        if blocks.len() != fnc.body.blocks.len() {
+            error("Block labels are not unique".to_string());
+        }
+
+        // Structure of the blocks and the edges between them
+        let mut successors: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); fnc.body.blocks.len()];
+        for (i, block) in fnc.body.blocks.iter().enumerate() {
+            let name = resolved_name(&block.name);
+            match block.instructions.back() {
+                Some(last) if is_terminator(&last.operation) => (),
+                _ => error(format!("Block `{}` does not end with a terminator", name)),
+            }
+
+            for instr in block.instructions.iter() {
+                let mut operation = instr.operation.clone();
+                for target in operation.jump_targets_mut() {
+                    match blocks.get(&resolved_name(target)) {
+                        Some(j) => {
+                            successors[i].insert(*j);
+                        }
+                        None => error(format!(
+                            "Block `{}` jumps to non-existing block `{}`",
+                            name,
+                            resolved_name(target)
+                        )),
+                    }
+                }
+            }
+        }
+
+        for (name, count) in fnc.definition_counts() {
+            if count > 1 {
+                error(format!("`{}` is defined {} times", name, count));
+            }
+        }
+
+        let dominators = Self::dominators(&successors);
+
+        // Definitions by block and position, where arguments precede all instructions of the
+        // entry block
+        let mut definitions: HashMap<String, (usize, Option<usize>)> = HashMap::new();
+        for arg in fnc.arguments.iter() {
+            definitions.insert(arg.name.unresolved.clone(), (0, None));
+        }
+        for (i, block) in fnc.body.blocks.iter().enumerate() {
+            for (j, instr) in block.instructions.iter().enumerate() {
+                if let Some(ssa_name) = &instr.ssa_name {
+                    definitions
+                        .entry(ssa_name.unresolved.clone())
+                        .or_insert((i, Some(j)));
+                }
+            }
+        }
+
+        for (i, block) in fnc.body.blocks.iter().enumerate() {
+            let dominated_by = match &dominators[i] {
+                Some(dominated_by) => dominated_by,
+                None => continue,
+            };
+
+            for (j, instr) in block.instructions.iter().enumerate() {
+                // Values of phi nodes arrive from different predecessors
+                if let Operation::PhiNode(_) = instr.operation {
+                    continue;
+                }
+
+                let mut operation = instr.operation.clone();
+                for operand in operation.operands_mut() {
+                    if !operand.is_variable() {
+                        continue;
+                    }
+                    // Names defined outside the function, such as globals, are not checked
+                    let (defined_in, position) = match definitions.get(&operand.unresolved) {
+                        Some(definition) => *definition,
+                        None => continue,
+                    };
+
+                    let dominates = if defined_in == i {
+                        match position {
+                            Some(position) => position < j,
+                            None => true,
+                        }
+                    } else {
+                        dominated_by.contains(&defined_in)
+                    };
+                    if !dominates {
+                        error(format!(
+                            "`{}` is used in block `{}` without being defined on every path to it",
+                            operand.unresolved,
+                            resolved_name(&block.name)
+                        ));
+                    }
+                }
+            }
+        }
+
+        if self.check_block_arguments {
+            if let Some(entry) = fnc.body.blocks.first() {
+                let arguments: BTreeSet<String> = fnc
+                    .arguments
+                    .iter()
+                    .map(|arg| resolved_name(&arg.name))
+                    .collect();
+                if entry.block_arguments != arguments {
+                    error(format!(
+                        "Entry block `{}` expects {:?} rather than the function arguments {:?}",
+                        resolved_name(&entry.name),
+                        entry.block_arguments,
+                        arguments
+                    ));
+                }
+            }
+
+            let empty = BTreeSet::new();
+            for (i, block) in fnc.body.blocks.iter().enumerate() {
+                for j in successors[i].iter() {
+                    let target = &fnc.body.blocks[*j];
+                    let target_name = resolved_name(&target.name);
+                    let passed = block
+                        .jump_required_arguments
+                        .get(&target_name)
+                        .unwrap_or(&empty);
+                    if *passed != target.block_arguments {
+                        error(format!(
+                            "Block `{}` passes {:?} to block `{}` which expects {:?}",
+                            resolved_name(&block.name),
+                            passed,
+                            target_name,
+                            target.block_arguments
+                        ));
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Returns the blocks dominating each block, or None if the block cannot be reached from
+    /// the entry block.
+    fn dominators(successors: &[BTreeSet<usize>]) -> Vec<Option<BTreeSet<usize>>> {
+        let count = successors.len();
+        let mut predecessors: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); count];
+        for (i, targets) in successors.iter().enumerate() {
+            for j in targets.iter() {
+                predecessors[*j].insert(i);
+            }
+        }
+
+        let mut reachable = vec![false; count];
+        let mut queue: Vec<usize> = Vec::new();
+        if count > 0 {
+            reachable[0] = true;
+            queue.push(0);
+        }
+        while let Some(i) = queue.pop() {
+            for j in successors[i].iter() {
+                if !reachable[*j] {
+                    reachable[*j] = true;
+                    queue.push(*j);
+                }
+            }
+        }
+
+        let all: BTreeSet<usize> = (0..count).filter(|i| reachable[*i]).collect();
+        let mut ret: Vec<Option<BTreeSet<usize>>> = (0..count)
+            .map(|i| {
+                if !reachable[i] {
+                    None
+                } else if i == 0 {
+                    Some([0].into_iter().collect())
+                } else {
+                    Some(all.clone())
+                }
+            })
+            .collect();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in 1..count {
+                if !reachable[i] {
+                    continue;
+                }
+
+                let mut dominated_by: Option<BTreeSet<usize>> = None;
+                for p in predecessors[i].iter() {
+                    if let Some(p_dominated_by) = &ret[*p] {
+                        dominated_by = Some(match dominated_by {
+                            Some(set) => set.intersection(p_dominated_by).copied().collect(),
+                            None => p_dominated_by.clone(),
+                        });
+                    }
+                }
+                let mut dominated_by = dominated_by.unwrap_or_default();
+                dominated_by.insert(i);
+
+                if ret[i].as_ref() != Some(&dominated_by) {
+                    ret[i] = Some(dominated_by);
+                    changed = true;
+                }
+            }
+        }
+
+        ret
+    }
+}
+
+impl IrPass for IrVerifier {
+    fn visit_concrete_function(
+        &mut self,
+        _mode: TreeTraversalMode,
+        fnc: &mut ConcreteFunction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        let errors = self.verify_function(fnc);
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_concrete_type(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_type: &mut ConcreteType,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_contract_field(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _field: &mut ContractField,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_symbol_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_kind: &mut IrIndentifierKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_symbol_name(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_name: &mut IrIdentifier,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_enum_value(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _enum_value: &mut EnumValue,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_tuple(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _tuple: &mut Tuple,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variant(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _variant: &mut Variant,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _var_dec: &mut VariableDeclaration,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_operation(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _operation: &mut Operation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _instruction: &mut Instruction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_block(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_block: &mut FunctionBlock,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_body(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_body: &mut FunctionBody,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_kind: &mut FunctionKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_case_clause(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_function: &mut CaseClause,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_primitives(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _primitives: &mut IntermediateRepresentation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn initiate(&mut self) {}
+
+    fn finalize(&mut self) {}
+}
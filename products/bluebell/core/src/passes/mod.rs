@@ -1,6 +1,14 @@
 pub mod annotate_base_types;
 pub mod balance_block_args;
 pub mod block_dependencies;
+pub mod cashflow;
+pub mod closure_conversion;
 pub mod collect_type_definitions;
+pub mod constant_folding;
+pub mod copy_propagation;
+pub mod dead_code_elimination;
 pub mod debug_printer;
+pub mod ir_verifier;
+pub mod jump_threading;
 pub mod state_allocator;
+pub mod type_checker;
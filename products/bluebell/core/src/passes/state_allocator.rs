@@ -1,3 +1,4 @@
+use evm_assembly::storage_layout::StorageEncoding;
 use primitive_types::U256;
 use scilla_parser::ast::{TraversalResult, TreeTraversalMode};
 
@@ -12,11 +13,32 @@ use crate::intermediate_representation::{
     symbol_table::{StateLayoutEntry, SymbolTable},
 };
 
+/// Slot of the first field. Fields are allocated from slot zero onwards, as in Solidity, while
+/// the entries of maps and the contents of strings live at keccak256 derived slots which do
+/// not collide with them. It is kept stable such that the storage of deployed contracts
+/// remains readable by newer versions of the compiler.
+pub const FIRST_FIELD_SLOT: u64 = 0;
+
+/// Allocates the storage of the contract fields in the order they are declared:
+///
+/// - values of a fixed size are stored in place, where consecutive fields share a slot as long
+///   as they fit in it. A field which is alone in its slot occupies all of it,
+/// - strings, byte strings and algebraic data types occupy a slot of their own and are stored
+///   from a slot derived from it, as are the entries of maps,
+/// - contract parameters are immutables, which are stored in the code rather than in storage.
+///   Their values are appended to the code, ABI encoded, when the contract is deployed.
+///
+/// See `evm_assembly::storage_layout` for the layout manifest describing the result.
 pub struct StateCollector {
     namespace_stack: Vec<String>,
     current_namespace: Option<String>,
     // current_type: Option<String>,
-    address_offset: u64,
+    address_offset: U256,
+    /// Number of bytes of the slot at `address_offset` used by packed fields.
+    byte_offset: u64,
+    /// Field which is alone in the slot at `address_offset` together with its size, if any.
+    lone_field: Option<(String, u64)>,
+    immutables: u64,
 }
 
 impl StateCollector {
@@ -25,8 +47,103 @@ impl StateCollector {
             namespace_stack: Vec::new(),
             current_namespace: None,
             // current_type: None,
-            address_offset: 4919, // TODO:
+            address_offset: U256::from(FIRST_FIELD_SLOT),
+            byte_offset: 0,
+            lone_field: None,
+            immutables: 0,
+        }
+    }
+
+    /// Moves on to the next slot unless the current one is unused.
+    fn next_slot(&mut self) {
+        if self.byte_offset > 0 {
+            self.address_offset = self.address_offset + 1;
+            self.byte_offset = 0;
+            self.lone_field = None;
+        }
+    }
+
+    /// Allocates storage for the field `name` of type `typename`.
+    fn allocate(
+        &mut self,
+        name: &str,
+        typename: &str,
+        immutable: bool,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<StateLayoutEntry, String> {
+        let class = symbol_table.storage_class(typename);
+
+        if immutable {
+            match class.encoding {
+                StorageEncoding::Inplace | StorageEncoding::Adt => (),
+                _ => {
+                    return Err(format!(
+                        "Contract parameter `{}` of type `{}` is not supported, parameters must be stored in a single word",
+                        name, typename
+                    ))
+                }
+            }
+            let offset = 32 * self.immutables;
+            self.immutables += 1;
+            return Ok(StateLayoutEntry {
+                address_offset: U256::from(offset),
+                size: 32,
+                initializer: U256::from(0),
+                byte_offset: 0,
+                encoding: StorageEncoding::Immutable,
+                signed: class.signed,
+            });
+        }
+
+        if class.encoding != StorageEncoding::Inplace || class.size >= 32 {
+            self.next_slot();
+            let ret = StateLayoutEntry {
+                address_offset: self.address_offset,
+                size: 32,
+                initializer: U256::from(0),
+                byte_offset: 0,
+                encoding: class.encoding,
+                signed: class.signed,
+            };
+            self.byte_offset = 32;
+            self.next_slot();
+            return Ok(ret);
         }
+
+        if self.byte_offset + class.size > 32 {
+            self.next_slot();
+        }
+
+        if self.byte_offset == 0 {
+            // Occupying the entire slot until another field is packed into it
+            self.lone_field = Some((name.to_string(), class.size));
+            self.byte_offset = class.size;
+            return Ok(StateLayoutEntry {
+                address_offset: self.address_offset,
+                size: 32,
+                initializer: U256::from(0),
+                byte_offset: 0,
+                encoding: StorageEncoding::Inplace,
+                signed: class.signed,
+            });
+        }
+
+        if let Some((lone_field, size)) = self.lone_field.take() {
+            if let Some(entry) = symbol_table.state_layout.get_mut(&lone_field) {
+                entry.size = size;
+            }
+        }
+
+        let ret = StateLayoutEntry {
+            address_offset: self.address_offset,
+            size: class.size,
+            initializer: U256::from(0),
+            byte_offset: self.byte_offset,
+            encoding: StorageEncoding::Inplace,
+            signed: class.signed,
+        };
+        self.byte_offset += class.size;
+        Ok(ret)
     }
 
     fn push_namespace(&mut self, namespace: String) {
@@ -45,7 +162,12 @@ impl StateCollector {
 }
 
 impl IrPass for StateCollector {
-    fn initiate(&mut self) {}
+    fn initiate(&mut self) {
+        self.address_offset = U256::from(FIRST_FIELD_SLOT);
+        self.byte_offset = 0;
+        self.lone_field = None;
+        self.immutables = 0;
+    }
     fn finalize(&mut self) {}
 
     fn visit_concrete_type(
@@ -76,16 +198,12 @@ impl IrPass for StateCollector {
             }
         };
 
-        let address = U256::from(self.address_offset);
-        let initializer = U256::from(0);
-        self.address_offset += 1;
-
-        let state = StateLayoutEntry {
-            address_offset: address,
-            size: 1, // TODO:
-            initializer,
-        };
-
+        let state = self.allocate(
+            name,
+            &field.variable.typename.unresolved,
+            field.immutable,
+            symbol_table,
+        )?;
         symbol_table.state_layout.insert(name.to_string(), state);
         // TODO: Register type of.
 
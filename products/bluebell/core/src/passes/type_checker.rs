@@ -0,0 +1,1732 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt, mem,
+};
+
+use evm_assembly::storage_layout::StorageEncoding;
+use scilla_parser::{
+    ast::{TraversalResult, TreeTraversalMode},
+    parser::lexer::SourcePosition,
+};
+
+use crate::{
+    constants::{
+        is_message_argument, FUNCTION_RETURN_TYPE_SEPARATOR, MAP_TYPE_NAME, NAMESPACE_SEPARATOR,
+        TEMPLATE_PARAMETERS_END, TEMPLATE_PARAMETERS_SEPARATOR, TEMPLATE_PARAMETERS_START,
+    },
+    errors::{ErrorList, SourceError},
+    intermediate_representation::{
+        pass::IrPass,
+        primitives::{
+            CaseClause, ConcreteFunction, ConcreteType, ContractField, EnumValue, FieldAddress,
+            FunctionBlock, FunctionBody, FunctionKind, Instruction, IntermediateRepresentation,
+            IrIdentifier, IrIndentifierKind, LambdaFunctionSingleArgument, Operation, Tuple,
+            VariableDeclaration, Variant,
+        },
+        symbol_table::{strip_enclosing_parentheses, SymbolTable},
+    },
+};
+
+/// Prefix of the names under which builtins are called in the IR.
+const BUILTIN_PREFIX: &str = "builtin__";
+
+/// Type of a value as seen by the type checker.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// Type which is not yet known and is determined by unification.
+    Variable(usize),
+    /// Type parameter bound by `tfun` or `forall`, e.g. `'A`.
+    Parameter(String),
+    /// Named type with its type arguments, e.g. `Uint32` or `Option Uint32`. Type arguments are
+    /// not tracked through all of the IR, so a named type without arguments is compatible with
+    /// the same type with any arguments.
+    Named(String, Vec<Type>),
+    Map(Box<Type>, Box<Type>),
+    Function(Box<Type>, Box<Type>),
+    Forall(String, Box<Type>),
+}
+
+impl Type {
+    fn named(name: &str) -> Self {
+        Type::Named(name.to_string(), Vec::new())
+    }
+
+    /// Parses a type as it is named in the IR, e.g. `Map<ByStr20,Uint64>` or `Uint64 -> Bool`.
+    pub fn parse(typename: &str, symbol_table: &SymbolTable) -> Self {
+        let typename = strip_enclosing_parentheses(typename);
+
+        if let Some((parameter, body)) = typename
+            .strip_prefix("forall ")
+            .and_then(|rest| rest.split_once('.'))
+        {
+            return Type::Forall(
+                parameter.trim().to_string(),
+                Box::new(Type::parse(body, symbol_table)),
+            );
+        }
+
+        if let Some((argument, return_type)) = symbol_table.closure_argument_return_types(typename)
+        {
+            return Type::Function(
+                Box::new(Type::parse(&argument, symbol_table)),
+                Box::new(Type::parse(&return_type, symbol_table)),
+            );
+        }
+
+        if let Some((key, value)) = symbol_table.map_key_value_types(typename) {
+            return Type::Map(
+                Box::new(Type::parse(&key, symbol_table)),
+                Box::new(Type::parse(&value, symbol_table)),
+            );
+        }
+
+        if typename.starts_with('\'') {
+            return Type::Parameter(typename.to_string());
+        }
+
+        let mut parts = typename.split_whitespace();
+        match parts.next() {
+            Some(name) => Type::Named(
+                name.to_string(),
+                parts.map(|part| Type::parse(part, symbol_table)).collect(),
+            ),
+            None => Type::named(typename),
+        }
+    }
+
+    /// Returns the name of the type as used by the symbol table, i.e. without type arguments.
+    pub fn ir_name(&self) -> String {
+        match self {
+            Type::Variable(n) => format!("?{}", n),
+            Type::Parameter(name) => name.clone(),
+            Type::Named(name, _) => name.clone(),
+            Type::Map(key, value) => format!(
+                "{}{}{}{}{}{}",
+                MAP_TYPE_NAME,
+                TEMPLATE_PARAMETERS_START,
+                key.ir_name(),
+                TEMPLATE_PARAMETERS_SEPARATOR,
+                value.ir_name(),
+                TEMPLATE_PARAMETERS_END
+            ),
+            Type::Function(argument, return_type) => {
+                let argument = argument.ir_name();
+                // Function typed arguments are parenthesised as the arrow is right associative
+                let argument = if argument.contains(FUNCTION_RETURN_TYPE_SEPARATOR) {
+                    format!("({})", argument)
+                } else {
+                    argument
+                };
+                format!(
+                    "{} {} {}",
+                    argument,
+                    FUNCTION_RETURN_TYPE_SEPARATOR,
+                    return_type.ir_name()
+                )
+            }
+            Type::Forall(parameter, body) => format!("forall {}. {}", parameter, body.ir_name()),
+        }
+    }
+
+    /// Checks whether the type contains type variables.
+    fn contains_variables(&self) -> bool {
+        match self {
+            Type::Variable(_) => true,
+            Type::Parameter(_) => false,
+            Type::Named(_, arguments) => arguments.iter().any(|a| a.contains_variables()),
+            Type::Map(a, b) | Type::Function(a, b) => {
+                a.contains_variables() || b.contains_variables()
+            }
+            Type::Forall(_, body) => body.contains_variables(),
+        }
+    }
+
+    /// Checks whether the type variable `n` occurs in the type.
+    fn contains_variable(&self, n: usize) -> bool {
+        match self {
+            Type::Variable(m) => *m == n,
+            Type::Parameter(_) => false,
+            Type::Named(_, arguments) => arguments.iter().any(|a| a.contains_variable(n)),
+            Type::Map(a, b) | Type::Function(a, b) => {
+                a.contains_variable(n) || b.contains_variable(n)
+            }
+            Type::Forall(_, body) => body.contains_variable(n),
+        }
+    }
+
+    /// Replaces the type parameter `parameter` with `replacement`.
+    fn substitute(&self, parameter: &str, replacement: &Type) -> Type {
+        match self {
+            Type::Parameter(name) if name == parameter => replacement.clone(),
+            Type::Variable(_) | Type::Parameter(_) => self.clone(),
+            Type::Named(name, arguments) => Type::Named(
+                name.clone(),
+                arguments
+                    .iter()
+                    .map(|a| a.substitute(parameter, replacement))
+                    .collect(),
+            ),
+            Type::Map(key, value) => Type::Map(
+                Box::new(key.substitute(parameter, replacement)),
+                Box::new(value.substitute(parameter, replacement)),
+            ),
+            Type::Function(argument, return_type) => Type::Function(
+                Box::new(argument.substitute(parameter, replacement)),
+                Box::new(return_type.substitute(parameter, replacement)),
+            ),
+            // The inner binder shadows the parameter
+            Type::Forall(name, _) if name == parameter => self.clone(),
+            Type::Forall(name, body) => Type::Forall(
+                name.clone(),
+                Box::new(body.substitute(parameter, replacement)),
+            ),
+        }
+    }
+
+    /// Appends the type parameters occurring in the type to `parameters` in order of appearance.
+    fn collect_parameters(&self, parameters: &mut Vec<String>) {
+        match self {
+            Type::Parameter(name) => {
+                if !parameters.contains(name) {
+                    parameters.push(name.clone());
+                }
+            }
+            Type::Variable(_) | Type::Forall(..) => (),
+            Type::Named(_, arguments) => {
+                for argument in arguments.iter() {
+                    argument.collect_parameters(parameters);
+                }
+            }
+            Type::Map(a, b) | Type::Function(a, b) => {
+                a.collect_parameters(parameters);
+                b.collect_parameters(parameters);
+            }
+        }
+    }
+
+    /// Returns the type of the recursion primitive `name`, e.g. `list_foldl`, instantiated with
+    /// `type_arguments`, or None if there is no such primitive or the number of type arguments
+    /// does not match.
+    pub fn of_recursion_primitive(name: &str, type_arguments: &[Type]) -> Option<Self> {
+        let function = |argument: Type, return_type: Type| {
+            Type::Function(Box::new(argument), Box::new(return_type))
+        };
+        let list = |element: &Type| Type::Named("List".to_string(), vec![element.clone()]);
+        let nat = || Type::named("Nat");
+
+        match (name, type_arguments) {
+            ("list_foldl", [a, b]) => Some(function(
+                function(b.clone(), function(a.clone(), b.clone())),
+                function(b.clone(), function(list(a), b.clone())),
+            )),
+            ("list_foldr", [a, b]) => Some(function(
+                function(a.clone(), function(b.clone(), b.clone())),
+                function(b.clone(), function(list(a), b.clone())),
+            )),
+            ("list_foldk", [a, b]) => Some(function(
+                function(
+                    b.clone(),
+                    function(
+                        a.clone(),
+                        function(function(b.clone(), b.clone()), b.clone()),
+                    ),
+                ),
+                function(b.clone(), function(list(a), b.clone())),
+            )),
+            ("nat_fold", [t]) => Some(function(
+                function(t.clone(), function(nat(), t.clone())),
+                function(t.clone(), function(nat(), t.clone())),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the type needs parentheses when it is used as an argument of another type.
+    fn is_compound(&self) -> bool {
+        match self {
+            Type::Named(_, arguments) => !arguments.is_empty(),
+            Type::Map(..) | Type::Function(..) | Type::Forall(..) => true,
+            Type::Variable(_) | Type::Parameter(_) => false,
+        }
+    }
+}
+
+/// Formats a type argument, parenthesising it if needed.
+struct Argument<'a>(&'a Type);
+
+impl<'a> fmt::Display for Argument<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_compound() {
+            write!(f, "({})", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+/// Types are displayed using the Scilla syntax.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Variable(n) => write!(f, "'_{}", n),
+            Type::Parameter(name) => write!(f, "{}", name),
+            Type::Named(name, arguments) => {
+                write!(f, "{}", name)?;
+                for argument in arguments.iter() {
+                    write!(f, " {}", Argument(argument))?;
+                }
+                Ok(())
+            }
+            Type::Map(key, value) => write!(f, "Map {} {}", Argument(key), Argument(value)),
+            Type::Function(argument, return_type) => match **argument {
+                Type::Function(..) | Type::Forall(..) => {
+                    write!(f, "({}) -> {}", argument, return_type)
+                }
+                _ => write!(f, "{} -> {}", argument, return_type),
+            },
+            Type::Forall(parameter, body) => write!(f, "forall {}. {}", parameter, body),
+        }
+    }
+}
+
+/// Global value, or intermediate value of the global initialisation block, whose type is
+/// determined when it is first referred to.
+struct GlobalValue {
+    namespace: Option<String>,
+    annotation: Option<IrIdentifier>,
+    value: Option<Box<Instruction>>,
+    value_type: Option<Type>,
+}
+
+/// `TypeChecker` verifies that the program is well typed before it is lowered. Types are inferred
+/// by unification, so the types of let bindings, lambda bodies and library values need not be
+/// annotated. The checker does not stop at the first error, but collects all errors with their
+/// source positions.
+pub struct TypeChecker {
+    errors: ErrorList,
+    substitution: Vec<Option<Type>>,
+    variables: Vec<(String, Type)>,
+    type_parameters: Vec<String>,
+    known_types: BTreeSet<String>,
+    constructors: HashMap<String, (String, Vec<Type>)>,
+    tuples: HashMap<String, Vec<Type>>,
+    fields: HashMap<String, Type>,
+    /// Contract parameters, which are fields that cannot be assigned to.
+    parameters: BTreeSet<String>,
+    globals: HashMap<String, usize>,
+    global_values: Vec<GlobalValue>,
+    lambdas: HashMap<String, LambdaFunctionSingleArgument>,
+    /// Types of the values bound by patterns, by the SSA name they are loaded into. These are
+    /// written back to the IR, as the types of constructor arguments cannot be deduced from the
+    /// types the IR keeps track of.
+    constructor_arguments: HashMap<String, Type>,
+    /// Fields read from other contracts with the type inferred for them and the position of the
+    /// read. Their types are only known once the whole program has been checked.
+    remote_fields: Vec<(String, Type, SourcePosition)>,
+    return_type: Option<Type>,
+    namespace: Option<String>,
+    location: SourcePosition,
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        TypeChecker {
+            errors: Vec::new(),
+            substitution: Vec::new(),
+            variables: Vec::new(),
+            type_parameters: Vec::new(),
+            known_types: BTreeSet::new(),
+            constructors: HashMap::new(),
+            tuples: HashMap::new(),
+            fields: HashMap::new(),
+            parameters: BTreeSet::new(),
+            globals: HashMap::new(),
+            global_values: Vec::new(),
+            lambdas: HashMap::new(),
+            constructor_arguments: HashMap::new(),
+            remote_fields: Vec::new(),
+            return_type: None,
+            namespace: None,
+            location: SourcePosition::start_position(),
+        }
+    }
+
+    /// Type checks `ir` and returns all errors found.
+    pub fn check(&mut self, ir: &mut IntermediateRepresentation) -> Result<(), ErrorList> {
+        for (name, typeinfo) in ir.symbol_table.type_of_table.iter() {
+            if typeinfo.typename == *name {
+                self.known_types.insert(name.clone());
+            }
+            if typeinfo.is_constructor() {
+                if let Some(return_type) = &typeinfo.return_type {
+                    self.known_types.insert(return_type.clone());
+                }
+            }
+        }
+        // User types may refer to each other regardless of the order of their definitions
+        for type_definition in ir.type_definitions.iter() {
+            if let ConcreteType::Variant { name, .. } = type_definition {
+                self.known_types.insert(name.unresolved.clone());
+            }
+        }
+
+        for lambda in ir.lambda_functions.iter() {
+            self.lambdas
+                .insert(lambda.name.unresolved.clone(), lambda.clone());
+        }
+
+        for instr in ir.global_init_block.instructions.iter() {
+            if let Some(ssa_name) = &instr.ssa_name {
+                self.declare_global(
+                    vec![ssa_name.unresolved.clone()],
+                    GlobalValue {
+                        namespace: None,
+                        annotation: None,
+                        value: Some(instr.clone()),
+                        value_type: None,
+                    },
+                );
+            }
+        }
+        for global in ir.global_variables.iter() {
+            self.declare_global(
+                vec![
+                    global.name.unresolved.clone(),
+                    format!(
+                        "{}{}{}",
+                        global.namespace.unresolved, NAMESPACE_SEPARATOR, global.name.unresolved
+                    ),
+                ],
+                GlobalValue {
+                    namespace: Some(global.namespace.unresolved.clone()),
+                    annotation: global.typename.clone(),
+                    value: Some(global.value.clone()),
+                    value_type: None,
+                },
+            );
+        }
+        // Contract parameters are values in scope of the whole contract, shadowing library
+        // values of the same name
+        for field in ir.fields_definitions.iter().filter(|field| field.immutable) {
+            let load = Instruction {
+                ssa_name: None,
+                result_type: None,
+                operation: Operation::StateLoad {
+                    address: FieldAddress {
+                        name: field.variable.name.clone(),
+                        value: None,
+                    },
+                },
+                source_location: field.variable.name.source_location.clone(),
+            };
+            self.declare_global(
+                vec![field.variable.name.unresolved.clone()],
+                GlobalValue {
+                    namespace: Some(field.namespace.unresolved.clone()),
+                    annotation: None,
+                    value: Some(Box::new(load)),
+                    value_type: None,
+                },
+            );
+        }
+
+        if let Err(message) = ir.run_pass(self) {
+            self.errors
+                .push(SourceError::new(self.location.clone(), message));
+        }
+
+        // Values which are not referred to are checked as well
+        for index in 0..self.global_values.len() {
+            if let Err(message) = self.global_type(index, &mut ir.symbol_table) {
+                self.errors
+                    .push(SourceError::new(self.location.clone(), message));
+            }
+        }
+
+        self.check_remote_field_types(&ir.symbol_table);
+
+        if self.errors.is_empty() {
+            self.annotate_constructor_arguments(ir);
+            Ok(())
+        } else {
+            Err(mem::take(&mut self.errors))
+        }
+    }
+
+    /// Sets the result type of the instructions loading constructor arguments to the type
+    /// inferred for them, unless it is not fully determined.
+    fn annotate_constructor_arguments(&self, ir: &mut IntermediateRepresentation) {
+        let bodies = ir
+            .function_definitions
+            .iter_mut()
+            .map(|function| &mut function.body)
+            .chain(
+                ir.lambda_functions
+                    .iter_mut()
+                    .map(|lambda| &mut lambda.body),
+            );
+        for body in bodies {
+            for block in body.blocks.iter_mut() {
+                for instr in block.instructions.iter_mut() {
+                    let ssa_name = match (&instr.ssa_name, &instr.operation) {
+                        (Some(ssa_name), Operation::LoadConstructorArgument { .. }) => ssa_name,
+                        _ => continue,
+                    };
+                    let t = match self.constructor_arguments.get(&ssa_name.unresolved) {
+                        Some(t) => self.resolve(t),
+                        None => continue,
+                    };
+                    if t.contains_variables() {
+                        continue;
+                    }
+                    instr.result_type = Some(IrIdentifier::new(
+                        t.ir_name(),
+                        IrIndentifierKind::TypeName,
+                        ssa_name.source_location.clone(),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn declare_global(&mut self, names: Vec<String>, value: GlobalValue) {
+        let index = self.global_values.len();
+        self.global_values.push(value);
+        for name in names {
+            self.globals.insert(name, index);
+        }
+    }
+
+    fn error(&mut self, position: &SourcePosition, message: String) {
+        let position = if *position == SourcePosition::invalid_position() {
+            self.location.clone()
+        } else {
+            position.clone()
+        };
+        self.errors.push(SourceError::new(position, message));
+    }
+
+    fn fresh(&mut self) -> Type {
+        self.substitution.push(None);
+        Type::Variable(self.substitution.len() - 1)
+    }
+
+    /// Follows the substitution until `t` is not a bound type variable.
+    fn shallow(&self, t: &Type) -> Type {
+        let mut t = t.clone();
+        while let Type::Variable(n) = t {
+            match &self.substitution[n] {
+                Some(s) => t = s.clone(),
+                None => break,
+            }
+        }
+        t
+    }
+
+    /// Applies the substitution to all of `t`.
+    fn resolve(&self, t: &Type) -> Type {
+        match self.shallow(t) {
+            Type::Named(name, arguments) => {
+                Type::Named(name, arguments.iter().map(|a| self.resolve(a)).collect())
+            }
+            Type::Map(key, value) => {
+                Type::Map(Box::new(self.resolve(&key)), Box::new(self.resolve(&value)))
+            }
+            Type::Function(argument, return_type) => Type::Function(
+                Box::new(self.resolve(&argument)),
+                Box::new(self.resolve(&return_type)),
+            ),
+            Type::Forall(parameter, body) => Type::Forall(parameter, Box::new(self.resolve(&body))),
+            t => t,
+        }
+    }
+
+    fn show(&self, t: &Type) -> String {
+        self.resolve(t).to_string()
+    }
+
+    /// Unifies `a` and `b` and returns whether they agree.
+    fn unify(&mut self, a: &Type, b: &Type) -> bool {
+        match (self.shallow(a), self.shallow(b)) {
+            (Type::Variable(n), Type::Variable(m)) if n == m => true,
+            (Type::Variable(n), t) | (t, Type::Variable(n)) => {
+                if self.resolve(&t).contains_variable(n) {
+                    false
+                } else {
+                    self.substitution[n] = Some(t);
+                    true
+                }
+            }
+            (Type::Parameter(p), Type::Parameter(q)) => p == q,
+            (Type::Named(n, a), Type::Named(m, b)) => {
+                if n != m {
+                    return false;
+                }
+                if a.is_empty() || b.is_empty() {
+                    return true;
+                }
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| self.unify(a, b))
+            }
+            (Type::Map(k1, v1), Type::Map(k2, v2)) => self.unify(&k1, &k2) && self.unify(&v1, &v2),
+            (Type::Function(a1, r1), Type::Function(a2, r2)) => {
+                self.unify(&a1, &a2) && self.unify(&r1, &r2)
+            }
+            (Type::Forall(p, a), Type::Forall(q, b)) => {
+                let b = self.resolve(&b).substitute(&q, &Type::Parameter(p));
+                self.unify(&a, &b)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_known_type(&self, name: &str) -> bool {
+        self.known_types.contains(name)
+            || name
+                .strip_prefix("ByStr")
+                .map_or(false, |n| n.parse::<u32>().is_ok())
+    }
+
+    /// Reports unknown types and unbound type parameters in `t`.
+    fn validate(&mut self, t: &Type, position: &SourcePosition) {
+        match t {
+            Type::Variable(_) => (),
+            Type::Parameter(name) => {
+                if !self.type_parameters.contains(name) {
+                    self.error(position, format!("Unbound type parameter `{}`", name));
+                }
+            }
+            Type::Named(name, arguments) => {
+                if !self.is_known_type(name) {
+                    self.error(position, format!("Unknown type `{}`", name));
+                }
+                for argument in arguments.iter() {
+                    self.validate(argument, position);
+                }
+            }
+            Type::Map(a, b) | Type::Function(a, b) => {
+                self.validate(a, position);
+                self.validate(b, position);
+            }
+            Type::Forall(parameter, body) => {
+                self.type_parameters.push(parameter.clone());
+                self.validate(body, position);
+                self.type_parameters.pop();
+            }
+        }
+    }
+
+    fn type_of_identifier(identifier: &IrIdentifier, symbol_table: &SymbolTable) -> Type {
+        let mut ret = Type::parse(&identifier.unresolved, symbol_table);
+        if let IrIndentifierKind::TemplateTypeName(arguments)
+        | IrIndentifierKind::TypeLikeName(arguments) = &identifier.kind
+        {
+            if let Type::Named(_, ref mut type_arguments) = ret {
+                if type_arguments.is_empty() {
+                    for argument in arguments.iter() {
+                        type_arguments.push(Self::type_of_identifier(argument, symbol_table));
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Returns the type named by the annotation `identifier` and reports it if it is not valid.
+    fn declared_type(&mut self, identifier: &IrIdentifier, symbol_table: &SymbolTable) -> Type {
+        let ret = Self::type_of_identifier(identifier, symbol_table);
+        self.validate(&ret, &identifier.source_location.0);
+        ret
+    }
+
+    fn symbol_type(
+        &mut self,
+        symbol: &IrIdentifier,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<Type, String> {
+        let name = &symbol.unresolved;
+        if let Some((_, t)) = self.variables.iter().rev().find(|(n, _)| n == name) {
+            return Ok(t.clone());
+        }
+
+        let index = self.globals.get(name).copied().or_else(|| {
+            self.namespace.as_ref().and_then(|namespace| {
+                self.globals
+                    .get(&format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, name))
+                    .copied()
+            })
+        });
+        if let Some(index) = index {
+            return self.global_type(index, symbol_table);
+        }
+
+        self.error(
+            &symbol.source_location.0,
+            format!("Undefined variable `{}`", name),
+        );
+        // Declaring the variable avoids reporting every subsequent use
+        let ret = self.fresh();
+        self.variables.push((name.clone(), ret.clone()));
+        Ok(ret)
+    }
+
+    fn global_type(
+        &mut self,
+        index: usize,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<Type, String> {
+        if let Some(value_type) = &self.global_values[index].value_type {
+            return Ok(value_type.clone());
+        }
+        let value = match self.global_values[index].value.take() {
+            Some(value) => value,
+            None => return Ok(self.fresh()),
+        };
+
+        // Recursive references see the type which is being inferred
+        let ret = self.fresh();
+        self.global_values[index].value_type = Some(ret.clone());
+
+        // Globals are checked in the scope of their library
+        let variables = mem::take(&mut self.variables);
+        let type_parameters = mem::take(&mut self.type_parameters);
+        let return_type = self.return_type.take();
+        let location = self.location.clone();
+        let namespace = mem::replace(
+            &mut self.namespace,
+            self.global_values[index].namespace.clone(),
+        );
+
+        let value_type = self.instruction_type(&value, symbol_table)?;
+        if let Some(annotation) = self.global_values[index].annotation.clone() {
+            let declared = self.declared_type(&annotation, symbol_table);
+            if !self.unify(&declared, &value_type) {
+                let message = format!(
+                    "Value of type `{}` cannot be bound to `{}` of type `{}`",
+                    self.show(&value_type),
+                    annotation.unresolved,
+                    self.show(&declared)
+                );
+                self.error(&value.source_location.0, message);
+            }
+        }
+        self.unify(&ret, &value_type);
+
+        self.variables = variables;
+        self.type_parameters = type_parameters;
+        self.return_type = return_type;
+        self.location = location;
+        self.namespace = namespace;
+
+        Ok(ret)
+    }
+
+    /// Checks the lambda `name` and returns its type, or None if there is no such lambda.
+    fn lambda_type(
+        &mut self,
+        name: &str,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<Option<Type>, String> {
+        let lambda = match self.lambdas.remove(name) {
+            Some(lambda) => lambda,
+            None => return Ok(None),
+        };
+
+        let result = self.fresh();
+        if let Some(return_type) = &lambda.return_type {
+            let declared = Type::parse(return_type, symbol_table);
+            self.unify(&result, &declared);
+        }
+        let return_type = mem::replace(&mut self.return_type, Some(result.clone()));
+        let scope = self.variables.len();
+
+        let ret = if let Some(argument) = &lambda.argument {
+            let argument_type = self.declared_type(&argument.typename, symbol_table);
+            self.variables
+                .push((argument.name.unresolved.clone(), argument_type.clone()));
+            self.check_body(&lambda.body, symbol_table)?;
+            Type::Function(Box::new(argument_type), Box::new(result))
+        } else if let Some(parameter) = &lambda.type_argument {
+            self.type_parameters.push(parameter.clone());
+            self.check_body(&lambda.body, symbol_table)?;
+            self.type_parameters.pop();
+            Type::Forall(parameter.clone(), Box::new(result))
+        } else {
+            self.check_body(&lambda.body, symbol_table)?;
+            result
+        };
+
+        self.variables.truncate(scope);
+        self.return_type = return_type;
+        Ok(Some(ret))
+    }
+
+    fn check_body(
+        &mut self,
+        body: &FunctionBody,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<(), String> {
+        for block in body.blocks.iter() {
+            for instr in block.instructions.iter() {
+                let t = self.instruction_type(instr, symbol_table)?;
+                if let Some(ssa_name) = &instr.ssa_name {
+                    self.variables.push((ssa_name.unresolved.clone(), t));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn field_type(&mut self, field: &IrIdentifier) -> Option<Type> {
+        let ret = self.fields.get(&field.unresolved).cloned();
+        if ret.is_none() {
+            self.error(
+                &field.source_location.0,
+                format!("Unknown field `{}`", field.unresolved),
+            );
+        }
+        ret
+    }
+
+    /// Reports map keys and values which are not held in a single word. Entries are stored at a
+    /// slot derived by hashing their key and hold their value in that slot, so keys and values
+    /// held in memory would be hashed and stored as pointers.
+    fn check_map_entry_types(
+        &mut self,
+        t: &Type,
+        position: &SourcePosition,
+        symbol_table: &SymbolTable,
+    ) {
+        if let Type::Map(key, value) = t {
+            for (kind, entry) in [("keys", key), ("values", value)] {
+                if matches!(**entry, Type::Map(..)) {
+                    continue;
+                }
+                if symbol_table.storage_class(&entry.ir_name()).encoding != StorageEncoding::Inplace
+                {
+                    let message = format!(
+                        "Map {} of type `{}` are not supported, map keys and values must be held in a single word",
+                        kind, entry
+                    );
+                    self.error(position, message);
+                }
+            }
+            self.check_map_entry_types(value, position, symbol_table);
+        }
+    }
+
+    /// Reports an error if the message entry `field` of type `t` has no ABI encoding, i.e. is
+    /// neither held in a word nor a string or byte string.
+    fn check_message_argument_type(
+        &mut self,
+        field: &str,
+        t: &Type,
+        value: &IrIdentifier,
+        symbol_table: &SymbolTable,
+    ) {
+        let t = self.resolve(t);
+        if t.contains_variables() {
+            return;
+        }
+        if let StorageEncoding::Adt | StorageEncoding::Mapping =
+            symbol_table.storage_class(&t.ir_name()).encoding
+        {
+            let message = format!(
+                "Message entry `{}` of type `{}` is not supported, message entries must be integers, booleans, strings or byte strings",
+                field, t
+            );
+            self.error(&value.source_location.0, message);
+        }
+    }
+
+    /// Reports fields read from other contracts which are not held in a single word. These are
+    /// read through the getters generated for the fields, which return a single word and are
+    /// not generated for maps, strings and byte strings.
+    fn check_remote_field_types(&mut self, symbol_table: &SymbolTable) {
+        for (field, t, position) in mem::take(&mut self.remote_fields) {
+            let t = self.resolve(&t);
+            if t.contains_variables() {
+                continue;
+            }
+            if symbol_table.storage_class(&t.ir_name()).encoding != StorageEncoding::Inplace {
+                let message = format!(
+                    "Remote field `{}` of type `{}` cannot be read, only fields held in a single word can be read from other contracts",
+                    field, t
+                );
+                self.error(&position, message);
+            }
+        }
+    }
+
+    /// Returns the type of the values found by indexing the map field `address` with `keys`, or
+    /// None if it cannot be determined.
+    fn map_value_type(
+        &mut self,
+        address: &FieldAddress,
+        keys: &[IrIdentifier],
+        symbol_table: &mut SymbolTable,
+    ) -> Result<Option<Type>, String> {
+        let mut current = match self.field_type(&address.name) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        for key in keys.iter() {
+            let key_type = self.symbol_type(key, symbol_table)?;
+            match self.shallow(&current) {
+                Type::Map(expected, value) => {
+                    if !self.unify(&expected, &key_type) {
+                        let message = format!(
+                            "Key of type `{}` does not match the key type `{}` of `{}`",
+                            self.show(&key_type),
+                            self.show(&expected),
+                            address.name.unresolved
+                        );
+                        self.error(&key.source_location.0, message);
+                    }
+                    current = *value;
+                }
+                t => {
+                    let message = format!(
+                        "Too many keys for `{}`: values of type `{}` are not maps",
+                        address.name.unresolved,
+                        self.show(&t)
+                    );
+                    self.error(&key.source_location.0, message);
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(Some(current))
+    }
+
+    /// Returns the signature of the constructor `name`, i.e. the type it constructs and the
+    /// types of its arguments.
+    fn constructor_signature(
+        &self,
+        name: &str,
+        symbol_table: &SymbolTable,
+    ) -> Option<(String, Vec<Type>)> {
+        if let Some(signature) = self.constructors.get(name) {
+            return Some(signature.clone());
+        }
+
+        // Constructors sharing their name with their type, such as `Pair`, are not aliased
+        let candidates = [
+            symbol_table.aliases.get(name).cloned(),
+            Some(format!("{}{}{}", name, NAMESPACE_SEPARATOR, name)),
+            Some(name.to_string()),
+        ];
+        for candidate in candidates.iter().flatten() {
+            if let Some(typeinfo) = symbol_table.type_of_table.get(candidate) {
+                if typeinfo.is_constructor() {
+                    let arguments = typeinfo
+                        .arguments
+                        .iter()
+                        .map(|a| Type::parse(a, symbol_table))
+                        .collect();
+                    return typeinfo
+                        .return_type
+                        .clone()
+                        .map(|return_type| (return_type, arguments));
+                }
+            }
+        }
+        None
+    }
+
+    /// Checks the application of the constructor `name` to `arguments` and returns the type of
+    /// the constructed value.
+    fn constructor_type(
+        &mut self,
+        name: &IrIdentifier,
+        arguments: &[Type],
+        type_arguments: &[Type],
+        symbol_table: &SymbolTable,
+    ) -> Option<Type> {
+        let (adt, parameters) = match self.constructor_signature(&name.unresolved, symbol_table) {
+            Some(signature) => signature,
+            None => {
+                self.error(
+                    &name.source_location.0,
+                    format!("Unknown constructor `{}`", name.unresolved),
+                );
+                return None;
+            }
+        };
+
+        let mut type_parameters = Vec::new();
+        for parameter in parameters.iter() {
+            parameter.collect_parameters(&mut type_parameters);
+        }
+        let mut instantiation = Vec::new();
+        for (i, _) in type_parameters.iter().enumerate() {
+            let t = match type_arguments.get(i) {
+                Some(t) => t.clone(),
+                None => self.fresh(),
+            };
+            instantiation.push(t);
+        }
+        let instantiate = |t: &Type| {
+            type_parameters
+                .iter()
+                .zip(instantiation.iter())
+                .fold(t.clone(), |t, (p, r)| t.substitute(p, r))
+        };
+
+        if parameters.len() != arguments.len() {
+            self.error(
+                &name.source_location.0,
+                format!(
+                    "Constructor `{}` expects {} argument(s), but {} were given",
+                    name.unresolved,
+                    parameters.len(),
+                    arguments.len()
+                ),
+            );
+        } else {
+            for (i, (parameter, argument)) in parameters.iter().zip(arguments.iter()).enumerate() {
+                let expected = instantiate(parameter);
+                if !self.unify(&expected, argument) {
+                    let message = format!(
+                        "Argument {} of `{}` has type `{}`, but `{}` was expected",
+                        i + 1,
+                        name.unresolved,
+                        self.show(argument),
+                        self.show(&expected)
+                    );
+                    self.error(&name.source_location.0, message);
+                }
+            }
+        }
+
+        Some(Type::Named(adt, instantiation))
+    }
+
+    /// Returns the type of the argument at `index` of the value `value` of type `value_type`,
+    /// which was built by the constructor `constructor`.
+    fn constructor_argument_type(
+        &mut self,
+        value: &IrIdentifier,
+        constructor: &IrIdentifier,
+        index: usize,
+        value_type: &Type,
+        symbol_table: &SymbolTable,
+    ) -> Type {
+        let (adt, parameters) =
+            match self.constructor_signature(&constructor.unresolved, symbol_table) {
+                Some(signature) => signature,
+                None => {
+                    self.error(
+                        &constructor.source_location.0,
+                        format!("Unknown constructor `{}`", constructor.unresolved),
+                    );
+                    return self.fresh();
+                }
+            };
+
+        let mut type_parameters = Vec::new();
+        for parameter in parameters.iter() {
+            parameter.collect_parameters(&mut type_parameters);
+        }
+        let instantiation: Vec<Type> = type_parameters.iter().map(|_| self.fresh()).collect();
+        let constructed = Type::Named(adt.clone(), instantiation.clone());
+        if !self.unify(&constructed, value_type) {
+            let message = format!(
+                "Pattern `{}` of type `{}` does not match `{}` of type `{}`",
+                constructor.unresolved,
+                self.show(&constructed),
+                value.unresolved,
+                self.show(value_type)
+            );
+            self.error(&constructor.source_location.0, message);
+        }
+
+        match parameters.get(index) {
+            // Recursive occurrences, such as the tail of a list, are of the constructed type
+            Some(Type::Named(name, arguments)) if *name == adt && arguments.is_empty() => {
+                constructed
+            }
+            Some(parameter) => type_parameters
+                .iter()
+                .zip(instantiation.iter())
+                .fold(parameter.clone(), |t, (p, r)| t.substitute(p, r)),
+            None => {
+                self.error(
+                    &constructor.source_location.0,
+                    format!(
+                        "Constructor `{}` takes {} argument(s), but argument {} was matched",
+                        constructor.unresolved,
+                        parameters.len(),
+                        index + 1
+                    ),
+                );
+                self.fresh()
+            }
+        }
+    }
+
+    /// Looks up the builtin or procedure `name` accepting `arguments` and returns its return type.
+    fn call_type(
+        &mut self,
+        name: &IrIdentifier,
+        arguments: &[Type],
+        symbol_table: &SymbolTable,
+    ) -> Type {
+        let arguments: Vec<Type> = arguments.iter().map(|a| self.resolve(a)).collect();
+        // Overloads can only be selected once the argument types are known
+        if arguments.iter().any(|a| a.contains_variables()) {
+            return self.fresh();
+        }
+
+        let signature = |alias: bool| {
+            let argument_names: Vec<String> = arguments
+                .iter()
+                .map(|a| {
+                    let name = a.ir_name();
+                    match symbol_table.aliases.get(&name) {
+                        Some(aliased_name) if alias => aliased_name.clone(),
+                        _ => name,
+                    }
+                })
+                .collect();
+            format!(
+                "{}::<{}>",
+                name.unresolved,
+                argument_names.join(TEMPLATE_PARAMETERS_SEPARATOR)
+            )
+        };
+
+        let typeinfo = symbol_table
+            .type_of(&signature(false), &self.namespace)
+            .or_else(|| symbol_table.type_of(&signature(true), &self.namespace));
+        match typeinfo {
+            Some(typeinfo) => match &typeinfo.return_type {
+                Some(return_type) => Type::parse(return_type, symbol_table),
+                None => Type::named("Void"),
+            },
+            None => {
+                let argument_types: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+                let message = match name.unresolved.strip_prefix(BUILTIN_PREFIX) {
+                    Some(builtin) => format!(
+                        "No builtin `{}` accepting arguments of type ({})",
+                        builtin,
+                        argument_types.join(", ")
+                    ),
+                    None => format!(
+                        "No procedure `{}` accepting arguments of type ({})",
+                        name.unresolved,
+                        argument_types.join(", ")
+                    ),
+                };
+                self.error(&name.source_location.0, message);
+                self.fresh()
+            }
+        }
+    }
+
+    /// Checks that the value `symbol` of type `t` is of type `expected`, e.g. a message.
+    fn expect_type(&mut self, symbol: &IrIdentifier, t: &Type, expected: &Type) {
+        if !self.unify(t, expected) {
+            let message = format!(
+                "Expected a value of type `{}`, found `{}` of type `{}`",
+                self.show(expected),
+                symbol.unresolved,
+                self.show(t)
+            );
+            self.error(&symbol.source_location.0, message);
+        }
+    }
+
+    /// Returns the type of the value produced by `instr`.
+    fn instruction_type(
+        &mut self,
+        instr: &Instruction,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<Type, String> {
+        if instr.source_location.0 != SourcePosition::invalid_position() {
+            self.location = instr.source_location.0.clone();
+        }
+        let position = self.location.clone();
+
+        let ret = match &instr.operation {
+            Operation::Noop
+            | Operation::Jump(_)
+            | Operation::MemLoad
+            | Operation::MemStore
+            | Operation::Revert(_) => Type::named("Void"),
+            Operation::TerminatingRef(symbol) => {
+                self.symbol_type(symbol, symbol_table)?;
+                Type::named("Void")
+            }
+            Operation::ConditionalJump { expression, .. } => {
+                let t = self.symbol_type(expression, symbol_table)?;
+                if !self.unify(&t, &Type::named("Bool")) {
+                    let message = format!(
+                        "Condition must be of type `Bool`, found `{}`",
+                        self.show(&t)
+                    );
+                    self.error(&expression.source_location.0, message);
+                }
+                Type::named("Void")
+            }
+            Operation::StateLoad { address } => match self.field_type(&address.name) {
+                Some(t) => t,
+                None => self.fresh(),
+            },
+            Operation::StateStore { address, value } => {
+                if self.parameters.contains(&address.name.unresolved) {
+                    let message = format!(
+                        "Contract parameter `{}` cannot be assigned to",
+                        address.name.unresolved
+                    );
+                    self.error(&position, message);
+                }
+                let value_type = self.symbol_type(value, symbol_table)?;
+                if let Some(field_type) = self.field_type(&address.name) {
+                    if !self.unify(&field_type, &value_type) {
+                        let message = format!(
+                            "Cannot store a value of type `{}` in field `{}` of type `{}`",
+                            self.show(&value_type),
+                            address.name.unresolved,
+                            self.show(&field_type)
+                        );
+                        self.error(&position, message);
+                    }
+                }
+                Type::named("Void")
+            }
+            Operation::StateRemoteLoad { contract, field } => {
+                let t = self.symbol_type(contract, symbol_table)?;
+                if !self.unify(&t, &Type::named("ByStr20")) {
+                    let message = format!(
+                        "Remote state can only be read from an address of type `ByStr20`, found `{}`",
+                        self.show(&t)
+                    );
+                    self.error(&contract.source_location.0, message);
+                }
+                if field == "_balance" {
+                    Type::named("Uint128")
+                } else {
+                    let t = self.fresh();
+                    self.remote_fields
+                        .push((field.clone(), t.clone(), position.clone()));
+                    t
+                }
+            }
+            Operation::StateMapLoad { address, keys } => {
+                // Reading an entry yields `None` if it does not exist
+                let value_type = match self.map_value_type(address, keys, symbol_table)? {
+                    Some(t) => t,
+                    None => self.fresh(),
+                };
+                Type::Named("Option".to_string(), [value_type].to_vec())
+            }
+            Operation::StateMapExists { address, keys } => {
+                self.map_value_type(address, keys, symbol_table)?;
+                Type::named("Bool")
+            }
+            Operation::StateMapStore {
+                address,
+                keys,
+                value,
+            } => {
+                let value_type = self.symbol_type(value, symbol_table)?;
+                if let Some(expected) = self.map_value_type(address, keys, symbol_table)? {
+                    if !self.unify(&expected, &value_type) {
+                        let message = format!(
+                            "Cannot store a value of type `{}` in `{}` whose values are of type `{}`",
+                            self.show(&value_type),
+                            address.name.unresolved,
+                            self.show(&expected)
+                        );
+                        self.error(&position, message);
+                    }
+                }
+                Type::named("Void")
+            }
+            Operation::StateMapDelete { address, keys } => {
+                self.map_value_type(address, keys, symbol_table)?;
+                Type::named("Void")
+            }
+            Operation::IsEqual { left, right } => {
+                let left_type = self.symbol_type(left, symbol_table)?;
+                match &right.kind {
+                    // Constructor patterns of match statements
+                    IrIndentifierKind::TypeLikeName(_) => {
+                        if let Some(pattern_type) =
+                            self.constructor_type(right, &[], &[], symbol_table)
+                        {
+                            if !self.unify(&left_type, &pattern_type) {
+                                let message = format!(
+                                    "Pattern `{}` of type `{}` does not match `{}` of type `{}`",
+                                    right.unresolved,
+                                    self.show(&pattern_type),
+                                    left.unresolved,
+                                    self.show(&left_type)
+                                );
+                                self.error(&right.source_location.0, message);
+                            }
+                        }
+                    }
+                    _ => {
+                        let right_type = self.symbol_type(right, symbol_table)?;
+                        if !self.unify(&left_type, &right_type) {
+                            let message = format!(
+                                "Cannot compare a value of type `{}` with a value of type `{}`",
+                                self.show(&left_type),
+                                self.show(&right_type)
+                            );
+                            self.error(&position, message);
+                        }
+                    }
+                }
+                Type::named("Bool")
+            }
+            Operation::CallExternalFunction { name, arguments }
+            | Operation::CallFunction { name, arguments } => {
+                let mut argument_types = Vec::new();
+                for argument in arguments.iter() {
+                    argument_types.push(self.symbol_type(argument, symbol_table)?);
+                }
+                self.call_type(name, &argument_types, symbol_table)
+            }
+            Operation::CallStaticFunction {
+                name,
+                arguments,
+                template_type_arguments,
+                ..
+            } => {
+                let mut argument_types = Vec::new();
+                for argument in arguments.iter() {
+                    argument_types.push(self.symbol_type(argument, symbol_table)?);
+                }
+                let mut type_arguments = Vec::new();
+                for argument in template_type_arguments.iter() {
+                    type_arguments.push(self.declared_type(argument, symbol_table));
+                }
+                match self.constructor_type(name, &argument_types, &type_arguments, symbol_table) {
+                    Some(t) => t,
+                    None => self.fresh(),
+                }
+            }
+            Operation::CallMemberFunction { arguments, .. } => {
+                for argument in arguments.iter() {
+                    self.symbol_type(argument, symbol_table)?;
+                }
+                self.fresh()
+            }
+            Operation::ResolveSymbol { symbol } => self.symbol_type(symbol, symbol_table)?,
+            Operation::ResolveContextResource { symbol } => {
+                match symbol_table.type_of(&symbol.unresolved, &None) {
+                    Some(typeinfo) => Type::parse(&typeinfo.typename, symbol_table),
+                    None => {
+                        self.error(
+                            &symbol.source_location.0,
+                            format!("Unknown blockchain value `{}`", symbol.unresolved),
+                        );
+                        self.fresh()
+                    }
+                }
+            }
+            Operation::Literal { data, typename } => {
+                match data.strip_prefix("0x") {
+                    // Hex literals are byte strings of the length of the literal
+                    Some(hex) if typename.unresolved == "String" => {
+                        Type::named(&format!("ByStr{}", hex.len() / 2))
+                    }
+                    _ => self.declared_type(typename, symbol_table),
+                }
+            }
+            Operation::ConstructMessage { entries } => {
+                for (field, value) in entries.iter() {
+                    let t = self.symbol_type(value, symbol_table)?;
+                    if is_message_argument(field) {
+                        self.check_message_argument_type(field, &t, value, symbol_table);
+                    }
+                }
+                Type::named("Message")
+            }
+            Operation::EmitEvent { message } => {
+                let t = self.symbol_type(message, symbol_table)?;
+                self.expect_type(message, &t, &Type::named("Message"));
+                Type::named("Void")
+            }
+            Operation::SendMessage { message } => {
+                // Messages are sent as a list
+                let t = self.symbol_type(message, symbol_table)?;
+                let expected = Type::Named("List".to_string(), [Type::named("Message")].to_vec());
+                self.expect_type(message, &t, &expected);
+                Type::named("Void")
+            }
+            Operation::CreateClosure { function, .. } => {
+                match self.lambda_type(&function.unresolved, symbol_table)? {
+                    Some(t) => t,
+                    None => self.fresh(),
+                }
+            }
+            Operation::LoadCapture { .. } => self.fresh(),
+            Operation::ApplyClosure { closure, argument } => {
+                let closure_type = self.symbol_type(closure, symbol_table)?;
+                let argument_type = self.symbol_type(argument, symbol_table)?;
+                match self.shallow(&closure_type) {
+                    Type::Function(parameter, result) => {
+                        if !self.unify(&parameter, &argument_type) {
+                            let message = format!(
+                                "Argument of type `{}` does not match the parameter type `{}`",
+                                self.show(&argument_type),
+                                self.show(&parameter)
+                            );
+                            self.error(&argument.source_location.0, message);
+                        }
+                        *result
+                    }
+                    Type::Variable(_) => {
+                        let result = self.fresh();
+                        let expected =
+                            Type::Function(Box::new(argument_type), Box::new(result.clone()));
+                        self.unify(&closure_type, &expected);
+                        result
+                    }
+                    t @ Type::Forall(..) => {
+                        let message = format!(
+                            "Value of polymorphic type `{}` must be instantiated before it is applied",
+                            self.show(&t)
+                        );
+                        self.error(&position, message);
+                        self.fresh()
+                    }
+                    t => {
+                        let message = format!(
+                            "Value of type `{}` is not a function and cannot be applied",
+                            self.show(&t)
+                        );
+                        self.error(&position, message);
+                        self.fresh()
+                    }
+                }
+            }
+            Operation::InstantiateTemplate {
+                template,
+                type_arguments,
+            } => {
+                let mut t = self.symbol_type(template, symbol_table)?;
+                for argument in type_arguments.iter() {
+                    let argument_type = self.declared_type(argument, symbol_table);
+                    t = match self.shallow(&t) {
+                        Type::Forall(parameter, body) => {
+                            self.resolve(&body).substitute(&parameter, &argument_type)
+                        }
+                        Type::Variable(_) => self.fresh(),
+                        other => {
+                            let message = format!(
+                                "Value of type `{}` is not polymorphic and cannot be instantiated",
+                                self.show(&other)
+                            );
+                            self.error(&position, message);
+                            self.fresh()
+                        }
+                    };
+                }
+                t
+            }
+            Operation::InstantiatePrimitive {
+                primitive,
+                type_arguments,
+            } => {
+                let mut arguments = Vec::new();
+                for argument in type_arguments.iter() {
+                    arguments.push(self.declared_type(argument, symbol_table));
+                }
+                match Type::of_recursion_primitive(&primitive.unresolved, &arguments) {
+                    Some(t) => t,
+                    None => {
+                        let message = format!(
+                            "Recursion primitive `{}` cannot be instantiated with {} type argument(s)",
+                            primitive.unresolved,
+                            arguments.len()
+                        );
+                        self.error(&position, message);
+                        self.fresh()
+                    }
+                }
+            }
+            Operation::LoadConstructorArgument {
+                value,
+                constructor,
+                index,
+            } => {
+                let value_type = self.symbol_type(value, symbol_table)?;
+                let ret = self.constructor_argument_type(
+                    value,
+                    constructor,
+                    *index,
+                    &value_type,
+                    symbol_table,
+                );
+                if let Some(ssa_name) = &instr.ssa_name {
+                    self.constructor_arguments
+                        .insert(ssa_name.unresolved.clone(), ret.clone());
+                }
+                ret
+            }
+            Operation::PhiNode(values) => {
+                let ret = self.fresh();
+                for value in values.iter() {
+                    let t = self.symbol_type(value, symbol_table)?;
+                    if !self.unify(&ret, &t) {
+                        let message = format!(
+                            "Branches have different types `{}` and `{}`",
+                            self.show(&ret),
+                            self.show(&t)
+                        );
+                        self.error(&position, message);
+                    }
+                }
+                ret
+            }
+            Operation::Return(value) => {
+                let t = match value {
+                    Some(value) => self.symbol_type(value, symbol_table)?,
+                    None => Type::named("Void"),
+                };
+                if let Some(expected) = self.return_type.clone() {
+                    if !self.unify(&expected, &t) {
+                        let message = format!(
+                            "Returned value of type `{}` does not match the return type `{}`",
+                            self.show(&t),
+                            self.show(&expected)
+                        );
+                        self.error(&position, message);
+                    }
+                }
+                Type::named("Void")
+            }
+        };
+
+        Ok(ret)
+    }
+}
+
+impl IrPass for TypeChecker {
+    fn visit_concrete_type(
+        &mut self,
+        _mode: TreeTraversalMode,
+        con_type: &mut ConcreteType,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        match con_type {
+            ConcreteType::Tuple {
+                name, data_layout, ..
+            } => {
+                let mut fields = Vec::new();
+                for field in data_layout.fields.iter() {
+                    fields.push(self.declared_type(field, symbol_table));
+                }
+                self.tuples.insert(name.unresolved.clone(), fields);
+            }
+            ConcreteType::Variant {
+                name, data_layout, ..
+            } => {
+                for field in data_layout.fields.iter() {
+                    let arguments = match &field.data {
+                        Some(data) => self
+                            .tuples
+                            .get(&data.unresolved)
+                            .cloned()
+                            .unwrap_or_default(),
+                        None => Vec::new(),
+                    };
+                    self.constructors.insert(
+                        field.name.unresolved.clone(),
+                        (name.unresolved.clone(), arguments),
+                    );
+                }
+            }
+        }
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_contract_field(
+        &mut self,
+        _mode: TreeTraversalMode,
+        field: &mut ContractField,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        self.namespace = Some(field.namespace.unresolved.clone());
+        self.variables.clear();
+
+        let declared = self.declared_type(&field.variable.typename, symbol_table);
+        self.fields
+            .insert(field.variable.name.unresolved.clone(), declared.clone());
+        self.check_map_entry_types(
+            &declared,
+            &field.initializer.source_location.0,
+            symbol_table,
+        );
+        if field.immutable {
+            self.parameters
+                .insert(field.variable.name.unresolved.clone());
+            return Ok(TraversalResult::SkipChildren);
+        }
+
+        let value_type = self.instruction_type(&field.initializer, symbol_table)?;
+        if !self.unify(&declared, &value_type) {
+            let message = format!(
+                "Field `{}` of type `{}` cannot be initialised with a value of type `{}`",
+                field.variable.name.unresolved,
+                self.show(&declared),
+                self.show(&value_type)
+            );
+            self.error(&field.initializer.source_location.0, message);
+        }
+
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_concrete_function(
+        &mut self,
+        _mode: TreeTraversalMode,
+        fnc: &mut ConcreteFunction,
+        symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        self.namespace = Some(fnc.namespace.unresolved.clone());
+        self.variables.clear();
+        self.type_parameters.clear();
+        self.return_type = fnc
+            .return_type
+            .as_ref()
+            .map(|return_type| Type::parse(return_type, symbol_table));
+
+        for arg in fnc.arguments.iter() {
+            let t = self.declared_type(&arg.typename, symbol_table);
+            self.variables.push((arg.name.unresolved.clone(), t));
+        }
+
+        self.check_body(&fnc.body, symbol_table)?;
+
+        self.return_type = None;
+        Ok(TraversalResult::SkipChildren)
+    }
+
+    fn visit_symbol_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_kind: &mut IrIndentifierKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_symbol_name(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _symbol_name: &mut IrIdentifier,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_enum_value(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _enum_value: &mut EnumValue,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_tuple(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _tuple: &mut Tuple,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variant(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _variant: &mut Variant,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _var_dec: &mut VariableDeclaration,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_operation(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _operation: &mut Operation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _instruction: &mut Instruction,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_block(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_block: &mut FunctionBlock,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_body(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_body: &mut FunctionBody,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_function_kind(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _function_kind: &mut FunctionKind,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_case_clause(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _con_function: &mut CaseClause,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn visit_primitives(
+        &mut self,
+        _mode: TreeTraversalMode,
+        _primitives: &mut IntermediateRepresentation,
+        _symbol_table: &mut SymbolTable,
+    ) -> Result<TraversalResult, String> {
+        Ok(TraversalResult::Continue)
+    }
+
+    fn initiate(&mut self) {}
+
+    fn finalize(&mut self) {}
+}
@@ -0,0 +1,249 @@
+use evm::{
+    backend::Backend,
+    executor::stack::{PrecompileFailure, PrecompileOutput, PrecompileOutputType},
+    Context as EvmContext, ExitError, ExitSucceed,
+};
+use k256::{
+    ecdsa::{signature::Verifier, RecoveryId, Signature, VerifyingKey},
+    elliptic_curve::{ops::Reduce, sec1::ToEncodedPoint, Field, PrimeField},
+    FieldBytes, ProjectivePoint, PublicKey, Scalar, U256,
+};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+/// Address of the standard SHA256 precompile.
+pub const SHA256_PRECOMPILE: u8 = 0x02;
+/// Address of the standard RIPEMD160 precompile.
+pub const RIPEMD160_PRECOMPILE: u8 = 0x03;
+
+/// Gas charged for verifying a signature or recovering a public key, matching `ecrecover`.
+const SIGNATURE_GAS: u64 = 3000;
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(data).into()
+}
+
+/// Verifies an EC-Schnorr signature over secp256k1 as used by Zilliqa. The signature is the
+/// concatenation of `r` and `s` and is valid if `r = H(s*G + r*P || P || message) mod n` where
+/// `H` is SHA256 and points are compressed.
+pub fn schnorr_verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    if signature.len() != 64 {
+        return false;
+    }
+    let public_key = match PublicKey::from_sec1_bytes(public_key) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+
+    let scalar = |bytes: &[u8]| -> Option<Scalar> {
+        let scalar: Option<Scalar> = Scalar::from_repr(FieldBytes::clone_from_slice(bytes)).into();
+        scalar.filter(|v| !bool::from(v.is_zero()))
+    };
+    let (r, s) = match (scalar(&signature[..32]), scalar(&signature[32..])) {
+        (Some(r), Some(s)) => (r, s),
+        _ => return false,
+    };
+
+    let q = ProjectivePoint::GENERATOR * s + public_key.to_projective() * r;
+    if q == ProjectivePoint::IDENTITY {
+        return false;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(q.to_affine().to_encoded_point(true).as_bytes());
+    hasher.update(public_key.to_encoded_point(true).as_bytes());
+    hasher.update(message);
+    let challenge = <Scalar as Reduce<U256>>::reduce_bytes(&hasher.finalize());
+
+    challenge == r
+}
+
+/// Verifies an ECDSA signature over secp256k1 of the SHA256 hash of `message`.
+pub fn ecdsa_verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let public_key = match VerifyingKey::from_sec1_bytes(public_key) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    let signature = match Signature::from_slice(signature) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+
+    public_key.verify(message, &signature).is_ok()
+}
+
+/// Recovers the uncompressed public key which signed the SHA256 hash of `message`.
+pub fn ecdsa_recover_pk(
+    message: &[u8],
+    signature: &[u8],
+    recovery_id: u32,
+) -> Result<Vec<u8>, String> {
+    let signature = Signature::from_slice(signature).map_err(|e| e.to_string())?;
+    let recovery_id = u8::try_from(recovery_id)
+        .ok()
+        .and_then(RecoveryId::from_byte)
+        .ok_or(format!("Invalid recovery id {}", recovery_id))?;
+
+    let public_key = VerifyingKey::recover_from_msg(message, &signature, recovery_id)
+        .map_err(|e| e.to_string())?;
+    Ok(public_key.to_encoded_point(false).as_bytes().to_vec())
+}
+
+fn failure(message: &'static str) -> PrecompileFailure {
+    PrecompileFailure::Error {
+        exit_status: ExitError::Other(message.into()),
+    }
+}
+
+pub(crate) fn charge(gas_limit: Option<u64>, gas_needed: u64) -> Result<u64, PrecompileFailure> {
+    match gas_limit {
+        Some(gas_limit) if gas_limit < gas_needed => Err(PrecompileFailure::Error {
+            exit_status: ExitError::OutOfGas,
+        }),
+        _ => Ok(gas_needed),
+    }
+}
+
+pub(crate) fn returned(
+    output: Vec<u8>,
+    gas: u64,
+) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+    Ok((
+        PrecompileOutput {
+            output_type: PrecompileOutputType::Exit(ExitSucceed::Returned),
+            output,
+        },
+        gas,
+    ))
+}
+
+pub(crate) fn word_argument(input: &[u8], index: usize) -> Result<&[u8], PrecompileFailure> {
+    input
+        .get(index * 32..(index + 1) * 32)
+        .ok_or(failure("Missing argument"))
+}
+
+/// Returns the content of a memory object passed in the tail of the call data. Its offset is
+/// stored in the head and the object itself is prefixed by its length as a u32.
+pub(crate) fn object_argument(input: &[u8], index: usize) -> Result<&[u8], PrecompileFailure> {
+    let read_u32 = |bytes: &[u8]| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    let offset = read_u32(&word_argument(input, index)?[28..]) as usize;
+    let length = input
+        .get(offset..offset + 4)
+        .map(read_u32)
+        .ok_or(failure("Invalid argument offset"))? as usize;
+    input
+        .get(offset + 4..offset + 4 + length)
+        .ok_or(failure("Invalid argument length"))
+}
+
+/// Returns the message argument which is either a word (`ByStr32`) or an object (`ByStr`).
+fn message_argument<const WORD_MESSAGE: bool>(
+    input: &[u8],
+    index: usize,
+) -> Result<&[u8], PrecompileFailure> {
+    if WORD_MESSAGE {
+        word_argument(input, index)
+    } else {
+        object_argument(input, index)
+    }
+}
+
+fn bool_word(value: bool) -> Vec<u8> {
+    let mut ret = [0u8; 32].to_vec();
+    ret[31] = value as u8;
+    ret
+}
+
+/// Standard precompile computing the SHA256 hash of its input.
+pub fn sha256_precompile(
+    input: &[u8],
+    gas_limit: Option<u64>,
+    _context: &EvmContext,
+    _backend: &dyn Backend,
+    _is_static: bool,
+) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+    let words = (input.len() as u64 + 31) / 32;
+    let gas = charge(gas_limit, 60 + 12 * words)?;
+    returned(sha256(input).to_vec(), gas)
+}
+
+/// Standard precompile computing the RIPEMD160 hash of its input, left padded to a word.
+pub fn ripemd160_precompile(
+    input: &[u8],
+    gas_limit: Option<u64>,
+    _context: &EvmContext,
+    _backend: &dyn Backend,
+    _is_static: bool,
+) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+    let words = (input.len() as u64 + 31) / 32;
+    let gas = charge(gas_limit, 600 + 120 * words)?;
+    let mut output = [0u8; 12].to_vec();
+    output.extend_from_slice(&ripemd160(input));
+    returned(output, gas)
+}
+
+/// Runtime function for `builtin schnorr_verify pubk msg sig`.
+pub fn schnorr_verify_runtime<const WORD_MESSAGE: bool>(
+    input: &[u8],
+    gas_limit: Option<u64>,
+    _context: &EvmContext,
+    _backend: &dyn Backend,
+    _is_static: bool,
+) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+    let gas = charge(gas_limit, SIGNATURE_GAS)?;
+    let public_key = object_argument(input, 0)?;
+    let message = message_argument::<WORD_MESSAGE>(input, 1)?;
+    let signature = object_argument(input, 2)?;
+    returned(
+        bool_word(schnorr_verify(public_key, message, signature)),
+        gas,
+    )
+}
+
+/// Runtime function for `builtin ecdsa_verify pubk msg sig`.
+pub fn ecdsa_verify_runtime<const WORD_MESSAGE: bool>(
+    input: &[u8],
+    gas_limit: Option<u64>,
+    _context: &EvmContext,
+    _backend: &dyn Backend,
+    _is_static: bool,
+) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+    let gas = charge(gas_limit, SIGNATURE_GAS)?;
+    let public_key = object_argument(input, 0)?;
+    let message = message_argument::<WORD_MESSAGE>(input, 1)?;
+    let signature = object_argument(input, 2)?;
+    returned(bool_word(ecdsa_verify(public_key, message, signature)), gas)
+}
+
+/// Runtime function for `builtin ecdsa_recover_pk msg sig recid`. The public key is returned as
+/// a memory object, i.e. prefixed by its length.
+pub fn ecdsa_recover_pk_runtime<const WORD_MESSAGE: bool>(
+    input: &[u8],
+    gas_limit: Option<u64>,
+    _context: &EvmContext,
+    _backend: &dyn Backend,
+    _is_static: bool,
+) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+    let gas = charge(gas_limit, SIGNATURE_GAS)?;
+    let message = message_argument::<WORD_MESSAGE>(input, 0)?;
+    let signature = object_argument(input, 1)?;
+    let recovery_id = word_argument(input, 2)?;
+    let recovery_id = u32::from_be_bytes([
+        recovery_id[28],
+        recovery_id[29],
+        recovery_id[30],
+        recovery_id[31],
+    ]);
+
+    let public_key = ecdsa_recover_pk(message, signature, recovery_id)
+        .map_err(|_| failure("Unable to recover public key"))?;
+    let mut output = (public_key.len() as u32).to_be_bytes().to_vec();
+    output.extend(public_key);
+    returned(output, gas)
+}
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    path::PathBuf,
+};
 
 use evm_assembly::{
     compiler_context::EvmCompilerContext, executable::EvmExecutable, executor::EvmExecutor,
@@ -9,34 +12,63 @@ use scilla_parser::{
 };
 
 use crate::{
+    constants::NAMESPACE_SEPARATOR,
+    diagnostics::{Diagnostic, DiagnosticList},
+    errors::{join_errors, CompilerError, CompilerErrorList, ErrorList},
     evm_bytecode_generator::EvmBytecodeGenerator,
     intermediate_representation::{
-        ast_queue::AstQueue, emitter::IrEmitter, pass_manager::PassManager,
+        ast_queue::AstQueue,
+        emitter::IrEmitter,
+        pass_manager::PassManager,
+        primitives::IntermediateRepresentation,
         symbol_table::SymbolTableConstructor,
+        textual::{parse_ir, print_ir},
+    },
+    passes::{
+        cashflow::{CashflowAnalysis, CashflowReport},
+        closure_conversion::free_variables,
+        type_checker::TypeChecker,
     },
     support::modules::BluebellModule,
 };
 
+/// Scilla sources of the standard library modules which can be imported by name.
+const STANDARD_LIBRARY: [(&str, &str); 5] = [
+    ("BoolUtils", include_str!("stdlib/BoolUtils.scilla")),
+    ("IntUtils", include_str!("stdlib/IntUtils.scilla")),
+    ("ListUtils", include_str!("stdlib/ListUtils.scilla")),
+    ("NatUtils", include_str!("stdlib/NatUtils.scilla")),
+    ("PairUtils", include_str!("stdlib/PairUtils.scilla")),
+];
+
+/// Name under which the program being compiled is registered with the importer.
+const MAIN_PROGRAM: &str = "<main>";
+
 /// Example implementation of AstQueue.
 pub struct SourceImporter {
-    queue: Vec<NodeProgram>,
+    queue: VecDeque<(String, NodeProgram)>,
     preloaded_scripts: HashMap<String, String>,
+    search_paths: Vec<PathBuf>,
+    /// Library currently being converted, i.e. the importer of enqueued libraries.
+    current: Option<String>,
+    /// Loaded libraries together with the libraries they import.
+    imports: BTreeMap<String, BTreeSet<String>>,
 }
 
 impl AstQueue for SourceImporter {
     fn enqueue(&mut self, filename: &str) -> Result<(), String> {
-        let script = self.load_script_from_filename(filename)?;
-        self.load_script(script)
+        self.import(filename)
     }
 
-    fn enqueue_with_alias(&mut self, filename: &str, alias_name: &str) -> Result<(), String> {
-        let script = self.load_script_from_filename(filename)?;
-        println!("TODO: Alias not implemented");
-        self.load_script(script)
+    fn enqueue_with_alias(&mut self, filename: &str, _alias_name: &str) -> Result<(), String> {
+        // Aliases are local to the importing library and resolved by its emitter
+        self.import(filename)
     }
 
     fn pop_front(&mut self) -> Option<NodeProgram> {
-        self.queue.pop()
+        let (name, ast) = self.queue.pop_front()?;
+        self.current = Some(name);
+        Some(ast)
     }
 }
 
@@ -44,69 +76,117 @@ impl SourceImporter {
     fn new() -> Self {
         let mut preloaded_scripts = HashMap::new();
         // TODO: Move this such that it is defined in the module.
-        preloaded_scripts.insert(
-            "ListUtils".to_string(),
-            r#"scilla_version 0
-            library ListUtils
-            contract ListUtils()
-            "#
-            .to_string(),
-        );
-
-        preloaded_scripts.insert(
-            "BoolUtils".to_string(),
-            r#"scilla_version 0
-            library BoolUtils
-            contract BoolUtils()
-            "#
-            .to_string(),
-        );
-
-        preloaded_scripts.insert(
-            "IntUtils".to_string(),
-            r#"scilla_version 0
-            library IntUtils
-            contract IntUtils()
-            "#
-            .to_string(),
-        );
-
-        preloaded_scripts.insert(
-            "IntUtils".to_string(),
-            r#"scilla_version 0
-            library IntUtils
-            contract IntUtils()
-            "#
-            .to_string(),
-        );
+        for (name, script) in STANDARD_LIBRARY {
+            preloaded_scripts.insert(name.to_string(), script.to_string());
+        }
+
         SourceImporter {
-            queue: Vec::new(),
+            queue: VecDeque::new(),
             preloaded_scripts,
+            search_paths: Vec::new(),
+            current: None,
+            imports: BTreeMap::new(),
+        }
+    }
+
+    fn add_search_path(&mut self, path: PathBuf) {
+        self.search_paths.push(path);
+    }
+
+    /// Prepares the importer for compiling a new program.
+    fn begin(&mut self) {
+        self.queue.clear();
+        self.imports.clear();
+        self.imports
+            .insert(MAIN_PROGRAM.to_string(), BTreeSet::new());
+        self.current = Some(MAIN_PROGRAM.to_string());
+    }
+
+    fn import(&mut self, library_name: &str) -> Result<(), String> {
+        if let Some(importer) = &self.current {
+            if let Some(path) = self.import_path(library_name, importer) {
+                return Err(format!(
+                    "Cyclic import: {} -> {}",
+                    importer,
+                    path.join(" -> ")
+                ));
+            }
+
+            self.imports
+                .entry(importer.clone())
+                .or_default()
+                .insert(library_name.to_string());
         }
+
+        // Libraries imported by several others are only loaded once
+        if self.imports.contains_key(library_name) {
+            return Ok(());
+        }
+        self.imports
+            .insert(library_name.to_string(), BTreeSet::new());
+
+        let script = self.load_script_from_filename(library_name)?;
+        let ast = Self::parse_library(library_name, &script)?;
+        self.queue.push_back((library_name.to_string(), ast));
+        Ok(())
+    }
+
+    /// Returns the chain of imports leading from `from` to `to`, if any.
+    fn import_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        if from == to {
+            return Some([from.to_string()].to_vec());
+        }
+
+        for next in self.imports.get(from)?.iter() {
+            if let Some(mut path) = self.import_path(next, to) {
+                path.insert(0, from.to_string());
+                return Some(path);
+            }
+        }
+        None
     }
 
     fn load_script_from_filename(&self, filename: &str) -> Result<String, String> {
         if let Some(script) = self.preloaded_scripts.get(filename) {
-            Ok(script.clone())
-        } else {
-            std::fs::read_to_string(filename).map_err(|err| format!("{}: {}", err, filename))
+            return Ok(script.clone());
+        }
+
+        let mut candidates = [PathBuf::from(filename)].to_vec();
+        for directory in self.search_paths.iter() {
+            let path = directory.join(filename);
+            candidates.push(path.with_extension("scillib"));
+            candidates.push(path.with_extension("scilla"));
+            candidates.push(path);
+        }
+
+        match candidates.iter().find(|candidate| candidate.is_file()) {
+            Some(path) => {
+                std::fs::read_to_string(path).map_err(|err| format!("{}: {}", err, path.display()))
+            }
+            None => Err(format!("Unable to find library {}", filename)),
         }
     }
 
-    fn load_script(&mut self, script: String) -> Result<(), String> {
+    /// Parses the library `name`. Libraries consist of a library definition only while the
+    /// parser expects a contract to follow it, so a placeholder contract is appended to
+    /// libraries which do not define one.
+    fn parse_library(name: &str, script: &str) -> Result<NodeProgram, String> {
+        Self::parse(script).or_else(|error| {
+            Self::parse(&format!("{}\ncontract {}()\n", script, name)).map_err(|_| error)
+        })
+    }
+
+    fn parse(script: &str) -> Result<NodeProgram, String> {
+        Self::parse_with_diagnostics(script).map_err(|diagnostic| diagnostic.to_string())
+    }
+
+    fn parse_with_diagnostics(script: &str) -> Result<NodeProgram, Diagnostic> {
         let mut errors: Vec<lexer::ParseError> = [].to_vec();
-        let lexer = Lexer::new(&script);
+        let lexer = Lexer::new(script);
         let parser = parser::ProgramParser::new();
-        let ast = match parser.parse(&mut errors, lexer) {
-            Ok(ast) => ast,
-            Err(error) => {
-                let message = format!("Syntax error {:?}", error);
-                return Err(message.to_string());
-            }
-        };
-
-        self.queue.push(ast);
-        Ok(())
+        parser
+            .parse(&mut errors, lexer)
+            .map_err(Diagnostic::from_parse_error)
     }
 }
 
@@ -144,35 +224,180 @@ impl EvmCompiler {
         module.attach(&mut self.context);
     }
 
+    /// Adds a directory in which imported libraries are searched for.
+    pub fn add_library_path<P: Into<PathBuf>>(&mut self, path: P) {
+        self.source_importer.add_search_path(path.into());
+    }
+
     pub fn compile(&mut self, script: String) -> Result<EvmExecutable, String> {
-        self.source_importer.load_script(script)?;
+        let ast = SourceImporter::parse(&script)?;
+        self.compile_ast(&ast)
+    }
+
+    /// Compiles `script` reporting all errors found as diagnostics, which can be rendered
+    /// against the source code.
+    pub fn compile_with_diagnostics(
+        &mut self,
+        script: String,
+    ) -> Result<EvmExecutable, DiagnosticList> {
+        let ast = SourceImporter::parse_with_diagnostics(&script).map_err(|d| vec![d])?;
+        let mut ir = self.emit_with_imports(&ast).map_err(|errors| {
+            errors
+                .into_iter()
+                .map(Diagnostic::from)
+                .collect::<DiagnosticList>()
+        })?;
+        self.pass_manager.run_with_diagnostics(&mut ir)?;
+
+        self.generate(ir)
+            .map_err(|error| vec![Diagnostic::from(error)])
+    }
+
+    /// Returns the IR of `script`, including the libraries it depends on, in the textual syntax
+    /// before any of the passes have run.
+    pub fn emit_ir(&mut self, script: String) -> Result<String, String> {
+        let ast = SourceImporter::parse(&script)?;
+        let ir = self
+            .emit_with_imports(&ast)
+            .map_err(|errors| join_errors(&errors))?;
+        Ok(print_ir(&ir))
+    }
+
+    /// Compiles IR written in the textual syntax, as produced by `emit_ir`.
+    pub fn compile_ir(&mut self, script: &str) -> Result<EvmExecutable, String> {
         let symbol_table = self.context.new_symbol_table();
+        let mut ir = Box::new(parse_ir(script, symbol_table)?);
+        self.pass_manager.run(&mut ir)?;
+
+        self.generate(ir).map_err(String::from)
+    }
 
-        // TODO: Change to while loop. This requires that IRs can be merged
-        if let Some(ast) = self.source_importer.pop_front() {
-            let ast_queue = &mut self.source_importer;
-            let mut ir_emitter = IrEmitter::new(symbol_table, ast_queue);
-            let mut ir = ir_emitter.emit(&ast)?;
-            self.pass_manager.run(&mut ir)?;
-            let mut generator = EvmBytecodeGenerator::new(&mut self.context, ir, self.abi_support);
-            generator.build_executable()
-        } else {
-            Err("No AST found.".to_string())
+    /// Type checks `script` without compiling it and returns the errors found. Syntax errors and
+    /// programs which cannot be lowered to the IR are reported as `Err`.
+    pub fn check(&mut self, script: String) -> Result<ErrorList, String> {
+        let ast = SourceImporter::parse(&script)?;
+        let mut ir = self
+            .emit_with_imports(&ast)
+            .map_err(|errors| join_errors(&errors))?;
+        match TypeChecker::new().check(&mut ir) {
+            Ok(()) => Ok(Vec::new()),
+            Err(errors) => Ok(errors),
         }
     }
 
+    /// Runs the cashflow analysis on `script`, labelling fields and transition parameters as
+    /// money or non-money, and estimates the gas used by each transition.
+    pub fn cashflow(&mut self, script: String) -> Result<CashflowReport, String> {
+        let ast = SourceImporter::parse(&script)?;
+        let mut ir = self
+            .emit_with_imports(&ast)
+            .map_err(|errors| join_errors(&errors))?;
+        let mut report = CashflowAnalysis::new().analyse(&mut ir)?;
+
+        // Contracts using features the code generator does not support are still analysed
+        if let Ok(estimates) = self.transition_gas_estimates(&ast) {
+            for transition in report.transitions.iter_mut() {
+                transition.gas_estimate = estimates.get(&transition.name).copied();
+            }
+        }
+        Ok(report)
+    }
+
+    /// Generates the code of `ast` and estimates the gas used by each of its transitions.
+    fn transition_gas_estimates(
+        &mut self,
+        ast: &NodeProgram,
+    ) -> Result<BTreeMap<String, u64>, String> {
+        let mut ir = self
+            .emit_with_imports(ast)
+            .map_err(|errors| join_errors(&errors))?;
+        self.pass_manager.run(&mut ir)?;
+
+        let mut generator = self.generator(ir);
+        let executable = generator.build_executable()?;
+        Ok(generator.transition_gas_estimates(&executable))
+    }
+
     // TODO: Remove &mut self - needs to be removed from a number of places first
     pub fn compile_ast(&mut self, ast: &NodeProgram) -> Result<EvmExecutable, String> {
-        let symbol_table = self.context.new_symbol_table();
-        let ast_queue = &mut self.source_importer;
-
-        let mut ir_emitter = IrEmitter::new(symbol_table, ast_queue);
-        let mut ir = ir_emitter.emit(ast)?;
+        let mut ir = self
+            .emit_with_imports(ast)
+            .map_err(|errors| join_errors(&errors))?;
         self.pass_manager.run(&mut ir)?;
 
+        self.generate(ir).map_err(String::from)
+    }
+
+    /// Generates the bytecode of `ir`, which must have been run through the pass manager.
+    fn generate(
+        &mut self,
+        ir: Box<IntermediateRepresentation>,
+    ) -> Result<EvmExecutable, CompilerError> {
+        self.generator(ir).build_executable()
+    }
+
+    /// Creates the bytecode generator for `ir`, optimising as set by the pass manager.
+    fn generator(&mut self, ir: Box<IntermediateRepresentation>) -> EvmBytecodeGenerator<'_> {
         let mut generator = EvmBytecodeGenerator::new(&mut self.context, ir, self.abi_support);
+        if self.pass_manager.optimisation_level() >= 1 {
+            generator.enable_peephole_optimisation();
+        }
+        generator
+    }
+
+    /// Emits the IR of `ast` and merges the IRs of the libraries it imports, directly or
+    /// indirectly, into it.
+    fn emit_with_imports(
+        &mut self,
+        ast: &NodeProgram,
+    ) -> Result<Box<IntermediateRepresentation>, CompilerErrorList> {
+        let internal = |message: String| vec![CompilerError::internal(message)];
+        self.source_importer.begin();
+
+        let symbol_table = self.context.new_symbol_table();
+        let mut ir = IrEmitter::new(symbol_table, &mut self.source_importer).emit(ast)?;
+
+        // Library values are only emitted when the program depends on them, which both keeps the
+        // executable small and avoids converting definitions the emitter does not support.
+        let mut libraries = Vec::new();
+        loop {
+            while let Some(library) = self.source_importer.pop_front() {
+                // Continuing from the merged symbol table keeps generated names unique
+                let symbol_table = ir.symbol_table.clone();
+                let library_ir = IrEmitter::new(symbol_table, &mut self.source_importer)
+                    .emit_library_types(&library)?;
+                ir.merge(*library_ir).map_err(internal)?;
+                libraries.push(library);
+            }
+
+            let mut defined = BTreeSet::new();
+            for global in ir.global_variables.iter() {
+                defined.insert(global.name.unresolved.clone());
+                defined.insert(format!(
+                    "{}{}{}",
+                    global.namespace.unresolved, NAMESPACE_SEPARATOR, global.name.unresolved
+                ));
+            }
+            let requested: BTreeSet<String> = free_variables(&mut ir)
+                .map_err(internal)?
+                .difference(&defined)
+                .cloned()
+                .collect();
+
+            let global_count = ir.global_variables.len();
+            for library in libraries.iter() {
+                let symbol_table = ir.symbol_table.clone();
+                let library_ir = IrEmitter::new(symbol_table, &mut self.source_importer)
+                    .emit_library_values(library, &requested)?;
+                ir.merge(*library_ir).map_err(internal)?;
+            }
+
+            if ir.global_variables.len() == global_count {
+                break;
+            }
+        }
 
-        generator.build_executable()
+        Ok(ir)
     }
 
     pub fn executable_from_ast(&mut self, ast: &NodeProgram) -> Result<EvmExecutor, String> {
@@ -0,0 +1,590 @@
+use evm_assembly::{block::EvmBlock, compiler_context::EvmCompilerContext, types::EvmType};
+use primitive_types::U256;
+
+use crate::support::strings::equal_objects;
+
+/// Widths of the integer types `IntN` and `UintN`.
+pub const INTEGER_WIDTHS: [usize; 6] = [8, 16, 32, 64, 128, 256];
+
+/// Binary arithmetic builtins. All of these revert if the result does not fit in the type of
+/// the operands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArithmeticOperation {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// Width and signedness of an integer type. Unsigned values are zero extended to a word while
+/// signed values are kept sign extended, such that the 256 bit operations can be used for all
+/// widths.
+#[derive(Debug, Clone, Copy)]
+struct IntegerType {
+    bits: usize,
+    signed: bool,
+}
+
+fn integer_type(ctx: &EvmCompilerContext, typename: &str) -> Result<IntegerType, String> {
+    match ctx.type_declarations.get(typename) {
+        Some(EvmType::Uint(bits)) if typename.starts_with("Uint") => Ok(IntegerType {
+            bits: *bits,
+            signed: false,
+        }),
+        Some(EvmType::Int(bits)) if typename.starts_with("Int") => Ok(IntegerType {
+            bits: *bits,
+            signed: true,
+        }),
+        _ => Err(format!("Expected integer type, found {}", typename)),
+    }
+}
+
+/// Returns the type of the operands, which must all be of the same integer type.
+fn operand_type(
+    ctx: &EvmCompilerContext,
+    arg_types: &Vec<String>,
+    count: usize,
+) -> Result<IntegerType, String> {
+    if arg_types.len() != count {
+        return Err(format!(
+            "Expected {} arguments, found {}",
+            count,
+            arg_types.len()
+        ));
+    }
+    if arg_types.iter().any(|t| *t != arg_types[0]) {
+        return Err(format!(
+            "Expected operands of the same type, found {}",
+            arg_types.join(", ")
+        ));
+    }
+    integer_type(ctx, &arg_types[0])
+}
+
+/// Returns the big endian bytes of the literal `data` of the integer type `typename`. Negative
+/// values are encoded in two's complement over the full word.
+pub fn integer_literal(
+    ctx: &EvmCompilerContext,
+    typename: &str,
+    data: &str,
+) -> Result<Vec<u8>, String> {
+    let ty = integer_type(ctx, typename)?;
+    let invalid = || format!("Invalid {} literal {}", typename, data);
+
+    let (negative, digits) = match data.strip_prefix('-') {
+        Some(digits) if ty.signed => (true, digits),
+        _ => (false, data),
+    };
+    let magnitude = U256::from_dec_str(digits).map_err(|_| invalid())?;
+
+    let in_range = match (ty.signed, negative) {
+        (false, _) => ty.bits >= 256 || (magnitude >> ty.bits).is_zero(),
+        (true, false) => (magnitude >> (ty.bits - 1)).is_zero(),
+        (true, true) => magnitude <= U256::one() << (ty.bits - 1),
+    };
+    if !in_range {
+        return Err(invalid());
+    }
+
+    let value = if negative {
+        U256::zero().overflowing_sub(magnitude).0
+    } else {
+        magnitude
+    };
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+
+    // Non-negative values are pushed with the width of the type
+    let width = if negative { 32 } else { ty.bits / 8 };
+    Ok(bytes[32 - width..].to_vec())
+}
+
+/// Pushes the smallest value of the signed 256 bit integer.
+fn push_int256_min(block: &mut EvmBlock) {
+    block.push1([0x01].to_vec());
+    block.push1([0xff].to_vec());
+    block.shl();
+}
+
+/// Consumes the word on top of the stack and reverts if it is non-zero.
+pub(crate) fn revert_if(block: &mut EvmBlock) {
+    let lbl_ok = block.generate_label("no_revert".to_string());
+    block.iszero();
+    block.jump_if_to(&lbl_ok);
+    block.push1([0x00].to_vec());
+    block.push1([0x00].to_vec());
+    block.revert();
+    block.create_label(lbl_ok);
+}
+
+/// Replaces the value on top of the stack with `Some` of it, i.e. a pointer to the defined flag
+/// followed by the value.
+pub(crate) fn push_some(block: &mut EvmBlock) {
+    // Stack:
+    // v => p
+    block.alloca_static(0x40);
+    block.push1([0x01].to_vec());
+    block.dup2();
+    block.mstore();
+    block.swap1();
+    block.dup2();
+    block.push1([0x20].to_vec());
+    block.add();
+    block.mstore();
+}
+
+/// Reverts unless the value on top of the stack is within the range of `ty`. The value is left
+/// on the stack.
+fn check_range(block: &mut EvmBlock, ty: IntegerType) {
+    if ty.bits >= 256 {
+        return;
+    }
+
+    block.dup1();
+    if ty.signed {
+        // The value is in range if sign extending it from the width leaves it unchanged
+        block.push_u32((ty.bits / 8 - 1) as u32);
+        block.signextend();
+        block.dup2();
+        block.eq();
+        block.iszero();
+    } else {
+        block.push_u32(ty.bits as u32);
+        block.shr();
+    }
+    revert_if(block);
+}
+
+/// Checked addition or subtraction of signed 256 bit integers, which overflows if and only if
+/// the result moves in the wrong direction relative to the first operand.
+fn checked_int256_add_sub(block: &mut EvmBlock, subtract: bool) {
+    // Stack:
+    // a => a
+    // b => b
+    //   => b < 0 (add) or b > 0 (sub)
+    block.dup1();
+    block.push1([0x00].to_vec());
+    block.swap1();
+    if subtract {
+        block.sgt();
+    } else {
+        block.slt();
+    }
+
+    // Stack:
+    // a    => flag
+    // b    => a
+    // flag => r
+    block.swap2();
+    block.dup1();
+    block.swap2();
+    if subtract {
+        block.swap1();
+        block.sub();
+    } else {
+        block.add();
+    }
+
+    // Stack:
+    // flag => r
+    // a    => (r < a) != flag
+    // r
+    block.swap1();
+    block.dup2();
+    block.slt();
+    block.swap2();
+    block.swap1();
+    block.swap2();
+    block.xor();
+    revert_if(block);
+}
+
+/// Checked multiplication of two values of type `ty`.
+fn checked_mul(block: &mut EvmBlock, ty: IntegerType) {
+    if ty.bits <= 128 {
+        // The product of two values of at most 128 bits is exact
+        block.mul();
+        check_range(block, ty);
+        return;
+    }
+
+    // Stack:
+    // a => a
+    // b => b
+    //   => r
+    //   => a != 0 && r / a != b
+    block.dup2();
+    block.dup2();
+    block.mul();
+    block.dup3();
+    block.dup2();
+    if ty.signed {
+        block.sdiv();
+    } else {
+        block.div();
+    }
+    block.dup3();
+    block.eq();
+    block.iszero();
+    block.dup4();
+    block.iszero();
+    block.iszero();
+    block.and();
+
+    if ty.signed {
+        // -1 * MIN is not detected by the division as SDIV(MIN, -1) = MIN
+        block.dup4();
+        block.not();
+        block.iszero();
+        block.dup4();
+        push_int256_min(block);
+        block.eq();
+        block.and();
+        block.or();
+    }
+    revert_if(block);
+
+    // Stack:
+    // a => r
+    // b
+    // r
+    block.swap2();
+    block.pop();
+    block.pop();
+}
+
+/// Lowers `add`, `sub`, `mul`, `div` and `rem` of two values of the same integer type.
+pub fn arithmetic(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+    operation: ArithmeticOperation,
+) -> Result<Vec<EvmBlock>, String> {
+    let ty = operand_type(ctx, &arg_types, 2)?;
+
+    // Stack:
+    // a => a op b
+    // b
+    match operation {
+        ArithmeticOperation::Add | ArithmeticOperation::Sub if ty.bits < 256 => {
+            // Results are exact for narrow types, so only the range needs to be checked
+            if operation == ArithmeticOperation::Sub {
+                block.swap1();
+                block.sub();
+            } else {
+                block.add();
+            }
+            check_range(block, ty);
+        }
+        ArithmeticOperation::Add if ty.signed => checked_int256_add_sub(block, false),
+        ArithmeticOperation::Sub if ty.signed => checked_int256_add_sub(block, true),
+        ArithmeticOperation::Add => {
+            block.dup2();
+            block.add();
+            block.swap1();
+            block.dup2();
+            block.lt();
+            revert_if(block);
+        }
+        ArithmeticOperation::Sub => {
+            block.dup2();
+            block.dup2();
+            block.gt();
+            revert_if(block);
+            block.swap1();
+            block.sub();
+        }
+        ArithmeticOperation::Mul => checked_mul(block, ty),
+        ArithmeticOperation::Div | ArithmeticOperation::Rem => {
+            // Division by zero
+            block.dup1();
+            block.iszero();
+            revert_if(block);
+
+            if operation == ArithmeticOperation::Div && ty.signed && ty.bits == 256 {
+                // MIN / -1 does not fit in the type
+                block.dup1();
+                block.not();
+                block.iszero();
+                block.dup3();
+                push_int256_min(block);
+                block.eq();
+                block.and();
+                revert_if(block);
+            }
+
+            block.swap1();
+            match (operation, ty.signed) {
+                (ArithmeticOperation::Div, false) => block.div(),
+                (ArithmeticOperation::Div, true) => block.sdiv(),
+                (_, false) => block.r#mod(),
+                (_, true) => block.smod(),
+            };
+            if operation == ArithmeticOperation::Div {
+                check_range(block, ty);
+            }
+        }
+    }
+
+    Ok([].to_vec())
+}
+
+/// Lowers `lt` of two values of the same integer type.
+pub fn less_than(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    let ty = operand_type(ctx, &arg_types, 2)?;
+    block.swap1();
+    if ty.signed {
+        block.slt();
+    } else {
+        block.lt();
+    }
+    Ok([].to_vec())
+}
+
+/// Lowers `eq` of two values of the same type. Words are compared directly while strings and
+/// byte strings are compared by content.
+pub fn equal(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    let operand = arg_types.first().and_then(|t| ctx.type_declarations.get(t));
+    match operand {
+        Some(t) if arg_types.len() == 2 && arg_types[0] == arg_types[1] => {
+            if t.is_dynamic() {
+                equal_objects(block);
+            } else {
+                block.eq();
+            }
+        }
+        _ => {
+            return Err(format!(
+                "Unable to compare values of type {}",
+                arg_types.join(", ")
+            ))
+        }
+    }
+    Ok([].to_vec())
+}
+
+/// Lowers `pow x n` where `n` is a `Uint32` by repeated squaring. The result reverts on overflow
+/// like multiplication.
+pub fn pow(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    if arg_types.len() != 2 || arg_types[1] != "Uint32" {
+        return Err(format!(
+            "Expected arguments of type T, Uint32, found {}",
+            arg_types.join(", ")
+        ));
+    }
+    let ty = integer_type(ctx, &arg_types[0])?;
+
+    let lbl_loop = block.generate_label("pow_loop".to_string());
+    let lbl_square = block.generate_label("pow_square".to_string());
+    let lbl_done = block.generate_label("pow_done".to_string());
+
+    // Stack:
+    // x => r = 1
+    // n => base = x
+    //   => n
+    block.push1([0x01].to_vec());
+    block.swap2();
+    block.swap1();
+
+    block.create_label(lbl_loop.clone());
+    block.dup1();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+
+    // r = r * base if the lowest bit of n is set
+    block.dup1();
+    block.push1([0x01].to_vec());
+    block.and();
+    block.iszero();
+    block.jump_if_to(&lbl_square);
+    block.dup2();
+    block.dup4();
+    checked_mul(block, ty);
+    block.swap3();
+    block.pop();
+
+    // Squaring is skipped after the last bit as it may overflow even if the result does not
+    block.create_label(lbl_square);
+    block.push1([0x01].to_vec());
+    block.shr();
+    block.dup1();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+    block.dup2();
+    block.dup1();
+    checked_mul(block, ty);
+    block.swap2();
+    block.pop();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.pop();
+    block.pop();
+
+    Ok([].to_vec())
+}
+
+/// Lowers `isqrt x` for unsigned integers using Newton's method.
+pub fn isqrt(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    let ty = operand_type(ctx, &arg_types, 1)?;
+    if ty.signed {
+        return Err(format!("isqrt is not defined for {}", arg_types[0]));
+    }
+
+    let lbl_loop = block.generate_label("isqrt_loop".to_string());
+    let lbl_done = block.generate_label("isqrt_done".to_string());
+
+    // Stack:
+    // x => x
+    //   => y = x
+    //   => z = ceil(x / 2)
+    block.dup1();
+    block.dup2();
+    block.push1([0x01].to_vec());
+    block.shr();
+    block.dup3();
+    block.push1([0x01].to_vec());
+    block.and();
+    block.add();
+
+    // while z < y: y = z, z = (x / z + z) / 2
+    block.create_label(lbl_loop.clone());
+    block.dup2();
+    block.dup2();
+    block.lt();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+    block.swap1();
+    block.pop();
+    block.dup1();
+    block.dup3();
+    block.div();
+    block.add();
+    block.push1([0x01].to_vec());
+    block.shr();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.pop();
+    block.swap1();
+    block.pop();
+
+    Ok([].to_vec())
+}
+
+/// Lowers `to_intN` and `to_uintN`, which return `Some` of the converted value if it is
+/// representable in the target type and `None` otherwise. `None` is the null pointer and `Some`
+/// points to the fields of `Option`, i.e. the defined flag followed by the value.
+pub fn to_integer<const BITS: usize, const SIGNED: bool>(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    let source = operand_type(ctx, &arg_types, 1)?;
+
+    // Stack:
+    // v => v
+    //   => v is representable
+    block.dup1();
+    match (SIGNED, source.signed) {
+        (false, _) => {
+            block.push_u32(BITS as u32);
+            block.shr();
+            block.iszero();
+            if source.signed {
+                // Negative values are only caught by the shift for narrow targets
+                block.dup2();
+                block.push1([0x00].to_vec());
+                block.swap1();
+                block.slt();
+                block.iszero();
+                block.and();
+            }
+        }
+        (true, true) => {
+            block.push_u32((BITS / 8 - 1) as u32);
+            block.signextend();
+            block.dup2();
+            block.eq();
+        }
+        (true, false) => {
+            block.push_u32((BITS - 1) as u32);
+            block.shr();
+            block.iszero();
+        }
+    }
+
+    let lbl_some = block.generate_label("conversion_some".to_string());
+    let lbl_done = block.generate_label("conversion_done".to_string());
+    block.jump_if_to(&lbl_some);
+    block.pop();
+    block.push1([0x00].to_vec());
+    block.jump_to(&lbl_done);
+
+    block.create_label(lbl_some);
+    push_some(block);
+
+    block.create_label(lbl_done);
+    Ok([].to_vec())
+}
+
+/// Lowers `to_nat n` by constructing `Succ` `n` times. `Zero` is the null pointer and `Succ`
+/// points to its predecessor.
+pub fn to_nat(
+    _ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    if arg_types != ["Uint32".to_string()] {
+        return Err(format!(
+            "Expected argument of type Uint32, found {}",
+            arg_types.join(", ")
+        ));
+    }
+
+    let lbl_loop = block.generate_label("to_nat_loop".to_string());
+    let lbl_done = block.generate_label("to_nat_done".to_string());
+
+    // Stack:
+    // n => n
+    //   => r = Zero
+    block.push1([0x00].to_vec());
+
+    block.create_label(lbl_loop.clone());
+    block.dup2();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+    block.alloca_static(0x20);
+    block.swap1();
+    block.dup2();
+    block.mstore();
+    block.swap1();
+    block.push1([0x01].to_vec());
+    block.swap1();
+    block.sub();
+    block.swap1();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.swap1();
+    block.pop();
+
+    Ok([].to_vec())
+}
@@ -1,5 +1,9 @@
 // pub mod evm;
 
+pub mod crypto;
 pub mod evm;
+pub mod integers;
 // pub mod llvm;
 pub mod modules;
+pub mod recursion;
+pub mod strings;
@@ -9,12 +9,32 @@ use evm::{
     executor::stack::{PrecompileFailure, PrecompileOutput, PrecompileOutputType},
     Context as EvmContext, ExitError, ExitSucceed,
 };
-use evm_assembly::{block::EvmBlock, compiler_context::EvmCompilerContext, types::EvmType};
+use evm_assembly::{
+    block::EvmBlock, compiler_context::EvmCompilerContext, storage_layout::StorageClass,
+    types::EvmType,
+};
 use log::info;
 
-use crate::intermediate_representation::{
-    name_generator::NameGenerator,
-    symbol_table::{SymbolTable, SymbolTableConstructor},
+use crate::{
+    intermediate_representation::{
+        name_generator::NameGenerator,
+        symbol_table::{SymbolTable, SymbolTableConstructor},
+    },
+    support::{
+        crypto::{
+            ecdsa_recover_pk_runtime, ecdsa_verify_runtime, ripemd160_precompile,
+            schnorr_verify_runtime, sha256_precompile, RIPEMD160_PRECOMPILE, SHA256_PRECOMPILE,
+        },
+        integers::{
+            arithmetic, equal, isqrt, less_than, pow, to_integer, to_nat, ArithmeticOperation,
+            INTEGER_WIDTHS,
+        },
+        strings::{
+            bech32_to_bystr20_builtin, bech32_to_bystr20_runtime, bystr20_to_bech32_builtin,
+            bystr20_to_bech32_runtime, concat, strlen, substr, to_ascii, to_bystr, to_bystrx,
+            to_string, BECH32_TO_BYSTR20_RUNTIME, BYSTR20_TO_BECH32_RUNTIME,
+        },
+    },
 };
 
 // TODO: Generalize to support both EVM and LLVM
@@ -30,21 +50,41 @@ impl SymbolTableConstructor for EvmCompilerContext {
             type_of_table: HashMap::new(),
             name_generator: NameGenerator::new(),
             state_layout: HashMap::new(),
+            storage_classes: self
+                .type_declarations
+                .iter()
+                .map(|(name, evm_type)| (name.clone(), StorageClass::of(evm_type)))
+                .collect(),
+            imported_namespaces: Vec::new(),
         };
 
         // TODO: Get types from self
+        let _ = ret.declare_type("BNum");
         let _ = ret.declare_type("Int8");
         let _ = ret.declare_type("Int16");
         let _ = ret.declare_type("Int32");
         let _ = ret.declare_type("Int64");
+        let _ = ret.declare_type("Int128");
+        let _ = ret.declare_type("Int256");
         let _ = ret.declare_type("Uint8");
         let _ = ret.declare_type("Uint16");
         let _ = ret.declare_type("Uint32");
         let _ = ret.declare_type("Uint64");
+        let _ = ret.declare_type("Uint128");
+        let _ = ret.declare_type("Uint256");
         let _ = ret.declare_type("String");
+        let _ = ret.declare_type("ByStr");
         let _ = ret.declare_type("ByStr20");
+        let _ = ret.declare_type("ByStr32");
+        let _ = ret.declare_type("ByStr33");
+        let _ = ret.declare_type("ByStr64");
+        let _ = ret.declare_type("ByStr65");
+        let _ = ret.declare_type("Message");
 
         let _ = ret.declare_special_variable("_sender", "ByStr20");
+        let _ = ret.declare_special_variable("BLOCKNUMBER", "BNum");
+        let _ = ret.declare_special_variable("TIMESTAMP", "Uint64");
+        let _ = ret.declare_special_variable("CHAINID", "Uint32");
 
         ret.aliases
             .insert("True".to_string(), "Bool::True".to_string());
@@ -53,6 +93,204 @@ impl SymbolTableConstructor for EvmCompilerContext {
         let _ = ret.declare_constructor("Bool::True", &[].to_vec(), "Bool");
         let _ = ret.declare_constructor("Bool::False", &[].to_vec(), "Bool");
 
+        // Standard library ADTs
+        let _ = ret.declare_type("List");
+        let _ = ret.declare_type("Pair");
+        let _ = ret.declare_type("Nat");
+        let _ = ret.declare_type("Option");
+        // The constructor `Pair` shares its name with the type and is therefore not aliased
+        for (alias, constructor) in [
+            ("Nil", "List::Nil"),
+            ("Cons", "List::Cons"),
+            ("Zero", "Nat::Zero"),
+            ("Succ", "Nat::Succ"),
+            ("None", "Option::None"),
+            ("Some", "Option::Some"),
+        ] {
+            ret.aliases
+                .insert(alias.to_string(), constructor.to_string());
+        }
+        let _ = ret.declare_constructor("List::Nil", &[].to_vec(), "List");
+        let _ = ret.declare_constructor(
+            "List::Cons",
+            &["'A".to_string(), "List".to_string()].to_vec(),
+            "List",
+        );
+        let _ = ret.declare_constructor(
+            "Pair::Pair",
+            &["'A".to_string(), "'B".to_string()].to_vec(),
+            "Pair",
+        );
+        let _ = ret.declare_constructor("Nat::Zero", &[].to_vec(), "Nat");
+        let _ = ret.declare_constructor("Nat::Succ", &["Nat".to_string()].to_vec(), "Nat");
+        let _ = ret.declare_constructor("Option::None", &[].to_vec(), "Option");
+        let _ = ret.declare_constructor("Option::Some", &["'A".to_string()].to_vec(), "Option");
+
+        // Integer builtins are declared for every pair of operands of the same type
+        let unsigned: Vec<String> = INTEGER_WIDTHS
+            .iter()
+            .map(|b| format!("Uint{}", b))
+            .collect();
+        let signed: Vec<String> = INTEGER_WIDTHS.iter().map(|b| format!("Int{}", b)).collect();
+        for typename in unsigned.iter().chain(signed.iter()) {
+            let binary = [typename.clone(), typename.clone()].to_vec();
+            for builtin in ["add", "sub", "mul", "div", "rem"] {
+                let _ = ret.declare_function_type(
+                    &format!("builtin__{}::<{},{}>", builtin, typename, typename),
+                    &binary,
+                    typename,
+                );
+            }
+            for builtin in ["lt", "eq"] {
+                let _ = ret.declare_function_type(
+                    &format!("builtin__{}::<{},{}>", builtin, typename, typename),
+                    &binary,
+                    "Bool",
+                );
+            }
+            let _ = ret.declare_function_type(
+                &format!("builtin__pow::<{},Uint32>", typename),
+                &[typename.clone(), "Uint32".to_string()].to_vec(),
+                typename,
+            );
+            for target in unsigned.iter().chain(signed.iter()) {
+                let _ = ret.declare_function_type(
+                    &format!("builtin__to_{}::<{}>", target.to_lowercase(), typename),
+                    &[typename.clone()].to_vec(),
+                    "Option",
+                );
+            }
+        }
+        for typename in unsigned.iter() {
+            let _ = ret.declare_function_type(
+                &format!("builtin__isqrt::<{}>", typename),
+                &[typename.clone()].to_vec(),
+                typename,
+            );
+        }
+        let _ = ret.declare_function_type(
+            "builtin__to_nat::<Uint32>",
+            &["Uint32".to_string()].to_vec(),
+            "Nat",
+        );
+
+        // String and byte string builtins
+        for typename in ["String", "ByStr"] {
+            let unary = [typename.to_string()].to_vec();
+            let binary = [typename.to_string(), typename.to_string()].to_vec();
+            let _ = ret.declare_function_type(
+                &format!("builtin__concat::<{},{}>", typename, typename),
+                &binary,
+                typename,
+            );
+            let _ = ret.declare_function_type(
+                &format!("builtin__eq::<{},{}>", typename, typename),
+                &binary,
+                "Bool",
+            );
+            let _ = ret.declare_function_type(
+                &format!("builtin__strlen::<{}>", typename),
+                &unary,
+                "Uint32",
+            );
+            let _ = ret.declare_function_type(
+                &format!("builtin__substr::<{},Uint32,Uint32>", typename),
+                &[
+                    typename.to_string(),
+                    "Uint32".to_string(),
+                    "Uint32".to_string(),
+                ]
+                .to_vec(),
+                typename,
+            );
+        }
+        for builtin in ["to_string", "to_ascii"] {
+            let _ = ret.declare_function_type(
+                &format!("builtin__{}::<ByStr>", builtin),
+                &["ByStr".to_string()].to_vec(),
+                "String",
+            );
+        }
+        for bytes in 1..=32 {
+            let bystrx = format!("ByStr{}", bytes);
+            let unary = [bystrx.clone()].to_vec();
+            for (builtin, return_type) in [
+                ("to_string", "String"),
+                ("to_ascii", "String"),
+                ("to_bystr", "ByStr"),
+            ] {
+                let _ = ret.declare_function_type(
+                    &format!("builtin__{}::<{}>", builtin, bystrx),
+                    &unary,
+                    return_type,
+                );
+            }
+            let _ = ret.declare_function_type(
+                &format!("builtin__eq::<{},{}>", bystrx, bystrx),
+                &[bystrx.clone(), bystrx.clone()].to_vec(),
+                "Bool",
+            );
+            let _ = ret.declare_function_type(
+                &format!("builtin__to_bystr{}::<ByStr>", bytes),
+                &["ByStr".to_string()].to_vec(),
+                "Option",
+            );
+            for other in 1..=(32 - bytes) {
+                let _ = ret.declare_function_type(
+                    &format!("builtin__concat::<{},ByStr{}>", bystrx, other),
+                    &[bystrx.clone(), format!("ByStr{}", other)].to_vec(),
+                    &format!("ByStr{}", bytes + other),
+                );
+            }
+        }
+        for bits in INTEGER_WIDTHS {
+            for typename in [format!("Uint{}", bits), format!("Int{}", bits)] {
+                let _ = ret.declare_function_type(
+                    &format!("builtin__to_string::<{}>", typename),
+                    &[typename.clone()].to_vec(),
+                    "String",
+                );
+            }
+            let _ = ret.declare_function_type(
+                &format!("builtin__to_bystr{}::<Uint{}>", bits / 8, bits),
+                &[format!("Uint{}", bits)].to_vec(),
+                &format!("ByStr{}", bits / 8),
+            );
+        }
+        let _ = ret.declare_function_type(
+            "builtin__bech32_to_bystr20::<String,String>",
+            &["String".to_string(), "String".to_string()].to_vec(),
+            "Option",
+        );
+        let _ = ret.declare_function_type(
+            "builtin__bystr20_to_bech32::<String,ByStr20>",
+            &["String".to_string(), "ByStr20".to_string()].to_vec(),
+            "Option",
+        );
+
+        // Hash builtins accept any primitive value and are lowered to calls to precompiles
+        let mut hashable: Vec<String> = ["Bool", "BNum", "String", "ByStr"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        for bits in [8, 16, 32, 64, 128, 256] {
+            hashable.push(format!("Int{}", bits));
+            hashable.push(format!("Uint{}", bits));
+        }
+        for bytes in (1..=33).chain([64, 65]) {
+            hashable.push(format!("ByStr{}", bytes));
+        }
+        for typename in hashable {
+            for (builtin, return_type) in [("sha256hash", "ByStr32"), ("ripemd160hash", "ByStr20")]
+            {
+                let _ = ret.declare_function_type(
+                    &format!("builtin__{}::<{}>", builtin, typename),
+                    &[typename.clone()].to_vec(),
+                    return_type,
+                );
+            }
+        }
+
         // Adding function types
         for (name, (args, return_type)) in self.raw_function_declarations.iter() {
             // info!("Declaring {:#?}",f);
@@ -67,7 +305,8 @@ pub struct ScillaDefaultTypes;
 impl BluebellModule for ScillaDefaultTypes {
     // TODO: Generalise to support both LLVM and EVM
     fn attach(&self, context: &mut EvmCompilerContext) {
-        context.declare_integer("Bool", 1);
+        context.declare_bool("Bool");
+        context.declare_unsigned_integer("BNum", 256);
         context.declare_integer("Int8", 8);
         context.declare_integer("Int16", 16);
         context.declare_integer("Int32", 32);
@@ -85,7 +324,13 @@ impl BluebellModule for ScillaDefaultTypes {
             context.declare_unsigned_integer(&format!("ByStr{}", i), i * 8);
         }
 
+        // Public keys and signatures
+        for i in [33, 64, 65] {
+            context.declare_byte_string(&format!("ByStr{}", i), i);
+        }
+
         context.declare_dynamic_string("String");
+        context.declare_dynamic_string("ByStr");
 
         context.declare_default_constructor("Bool::False", |block| {
             block.push([0].to_vec());
@@ -103,10 +348,129 @@ impl BluebellModule for ScillaDefaultTypes {
             ]
             .into(),
         );
+
+        // Lists and natural numbers are linked structures in memory where the empty list and
+        // zero are represented by the null pointer
+        context.declare_generic_type(
+            "List",
+            ["A".to_string()].into(),
+            [
+                ("head".to_string(), "A".to_string()),
+                ("tail".to_string(), "Uint256".to_string()),
+            ]
+            .into(),
+        );
+        context.declare_default_constructor("List::Nil", |block| {
+            block.push([0].to_vec());
+        });
+
+        context.declare_generic_type(
+            "Pair",
+            ["A".to_string(), "B".to_string()].into(),
+            [
+                ("first".to_string(), "A".to_string()),
+                ("second".to_string(), "B".to_string()),
+            ]
+            .into(),
+        );
+
+        context.declare_generic_type(
+            "Nat",
+            [].into(),
+            [("previous".to_string(), "Uint256".to_string())].into(),
+        );
+        context.declare_default_constructor("Nat::Zero", |block| {
+            block.push([0].to_vec());
+        });
+        context.declare_default_constructor("Option::None", |block| {
+            block.push([0].to_vec());
+        });
+
         // TODO: Functions to be moved out to another
     }
 }
 
+/// Hashes the value on top of the stack by calling the standard precompile at `address` and
+/// replaces it with the resulting word. Integers and byte strings are hashed as their big endian
+/// bytes while strings are hashed by content.
+fn hash_with_precompile(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+    address: u8,
+) -> Result<Vec<EvmBlock>, String> {
+    let arg = match &arg_types[..] {
+        [arg] => arg,
+        _ => return Err(format!("Expected one argument, found {}", arg_types.len())),
+    };
+    let evm_type = match ctx.type_declarations.get(arg) {
+        Some(v) => v.clone(),
+        None => return Err(format!("Unable to hash values of type {}", arg)),
+    };
+
+    if evm_type.is_dynamic() {
+        // Stack:
+        // p_obj => len
+        //       => p_data
+        block.dup1();
+        block.mload();
+        block.push1([224].to_vec());
+        block.shr();
+        block.swap1();
+        block.push1([0x04].to_vec());
+        block.add();
+
+        // Stack:
+        // len    => len
+        // p_data => p_data
+        //        => p_out
+        //        => 0x20 p_out len p_data address gas
+        block.alloca_static(0x20);
+        block.push1([0x20].to_vec());
+        block.dup2();
+        block.dup5();
+        block.dup5();
+        block.push1([address].to_vec());
+        block.external_gas();
+        block.external_staticcall();
+
+        block.pop(); // Removing success flag
+        block.swap2();
+        block.pop();
+        block.pop();
+    } else {
+        let size = match evm_type {
+            EvmType::Uint(bits) | EvmType::Int(bits) => (bits + 7) / 8,
+            EvmType::Bool => 1,
+            EvmType::Bytes(size) => size,
+            _ => return Err(format!("Unable to hash values of type {}", arg)),
+        };
+
+        // Storing the word such that its value is right aligned at p_out
+        block.alloca_static(0x20);
+        block.swap1();
+        block.dup2();
+        block.mstore();
+
+        // Stack:
+        // p_out => 0x20 p_out size (p_out + 0x20 - size) address gas
+        block.push1([0x20].to_vec());
+        block.dup2();
+        block.push_u64(size as u64);
+        block.dup3();
+        block.push_u64((32 - size) as u64);
+        block.add();
+        block.push1([address].to_vec());
+        block.external_gas();
+        block.external_staticcall();
+
+        block.pop(); // Removing success flag
+    }
+
+    block.mload();
+    Ok([].to_vec())
+}
+
 pub struct ScillaDebugBuiltins;
 impl BluebellModule for ScillaDebugBuiltins {
     fn attach(&self, specification: &mut EvmCompilerContext) {
@@ -498,43 +862,107 @@ impl BluebellModule for ScillaDefaultBuiltins {
             Ok([].to_vec())
         });
 
-        // Assuming you have a 'specification' object available...
-        // Implementing `add`:
-        let _ = specification.declare_inline_generics("builtin__add", |_ctx, block, _arg_types| {
-            // TODO: Check that the number of arguments is two and otherwise return an error
-            block.add();
+        // Blockchain state
+        let _ = specification.declare_special_variable("BLOCKNUMBER", "BNum", |_ctx, block| {
+            block.external_number();
             Ok([].to_vec())
         });
 
-        // Implementing `sub`:
-        let _ = specification.declare_inline_generics("builtin__sub", |_ctx, block, _arg_types| {
-            block.sub();
+        let _ = specification.declare_special_variable("TIMESTAMP", "Uint64", |_ctx, block| {
+            block.external_timestamp();
             Ok([].to_vec())
         });
 
-        // Implementing `mul`:
-        let _ = specification.declare_inline_generics("builtin__mul", |_ctx, block, _arg_types| {
-            block.mul();
+        let _ = specification.declare_special_variable("CHAINID", "Uint32", |_ctx, block| {
+            block.external_chainid();
             Ok([].to_vec())
         });
 
-        // Implementing `div`:
-        let _ = specification.declare_inline_generics("builtin__div", |_ctx, block, _arg_types| {
-            block.div();
-            Ok([].to_vec())
+        // Integer builtins are width aware and revert on overflow
+        let _ = specification.declare_inline_generics("builtin__add", |ctx, block, arg_types| {
+            arithmetic(ctx, block, arg_types, ArithmeticOperation::Add)
         });
-
-        // Implementing `rem`:
-        let _ = specification.declare_inline_generics("builtin__rem", |_ctx, block, _arg_types| {
-            block.smod(); // smod might be the appropriate EVM instruction for remainder, but verify with EVM docs.
-            Ok([].to_vec())
+        let _ = specification.declare_inline_generics("builtin__sub", |ctx, block, arg_types| {
+            arithmetic(ctx, block, arg_types, ArithmeticOperation::Sub)
         });
+        let _ = specification.declare_inline_generics("builtin__mul", |ctx, block, arg_types| {
+            arithmetic(ctx, block, arg_types, ArithmeticOperation::Mul)
+        });
+        let _ = specification.declare_inline_generics("builtin__div", |ctx, block, arg_types| {
+            arithmetic(ctx, block, arg_types, ArithmeticOperation::Div)
+        });
+        let _ = specification.declare_inline_generics("builtin__rem", |ctx, block, arg_types| {
+            arithmetic(ctx, block, arg_types, ArithmeticOperation::Rem)
+        });
+        let _ = specification.declare_inline_generics("builtin__pow", pow);
+        let _ = specification.declare_inline_generics("builtin__isqrt", isqrt);
+
+        // Conversions returning `None` if the value is not representable in the target type
+        let _ = specification.declare_inline_generics("builtin__to_uint8", to_integer::<8, false>);
+        let _ =
+            specification.declare_inline_generics("builtin__to_uint16", to_integer::<16, false>);
+        let _ =
+            specification.declare_inline_generics("builtin__to_uint32", to_integer::<32, false>);
+        let _ =
+            specification.declare_inline_generics("builtin__to_uint64", to_integer::<64, false>);
+        let _ =
+            specification.declare_inline_generics("builtin__to_uint128", to_integer::<128, false>);
+        let _ =
+            specification.declare_inline_generics("builtin__to_uint256", to_integer::<256, false>);
+        let _ = specification.declare_inline_generics("builtin__to_int8", to_integer::<8, true>);
+        let _ = specification.declare_inline_generics("builtin__to_int16", to_integer::<16, true>);
+        let _ = specification.declare_inline_generics("builtin__to_int32", to_integer::<32, true>);
+        let _ = specification.declare_inline_generics("builtin__to_int64", to_integer::<64, true>);
+        let _ =
+            specification.declare_inline_generics("builtin__to_int128", to_integer::<128, true>);
+        let _ =
+            specification.declare_inline_generics("builtin__to_int256", to_integer::<256, true>);
+        let _ = specification.declare_inline_generics("builtin__to_nat", to_nat);
+
+        // String and byte string builtins operate on objects in memory
+        let _ = specification.declare_inline_generics("builtin__concat", concat);
+        let _ = specification.declare_inline_generics("builtin__strlen", strlen);
+        let _ = specification.declare_inline_generics("builtin__substr", substr);
+        let _ = specification.declare_inline_generics("builtin__to_string", to_string);
+        let _ = specification.declare_inline_generics("builtin__to_bystr", to_bystr);
+        let _ = specification.declare_inline_generics("builtin__to_ascii", to_ascii);
+        macro_rules! declare_to_bystrx {
+            ($($bytes:literal),*) => {
+                $(
+                    let _ = specification.declare_inline_generics(
+                        concat!("builtin__to_bystr", $bytes),
+                        to_bystrx::<$bytes>,
+                    );
+                )*
+            };
+        }
+        declare_to_bystrx!(
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+            25, 26, 27, 28, 29, 30, 31, 32
+        );
+
+        let _ = specification
+            .declare_function(
+                BECH32_TO_BYSTR20_RUNTIME,
+                ["String", "String"].to_vec(),
+                "Uint256",
+            )
+            .attach_runtime(|| bech32_to_bystr20_runtime);
+        let _ = specification
+            .declare_function(
+                BYSTR20_TO_BECH32_RUNTIME,
+                ["String", "ByStr20"].to_vec(),
+                "String",
+            )
+            .attach_runtime(|| bystr20_to_bech32_runtime);
+        let _ = specification
+            .declare_inline_generics("builtin__bech32_to_bystr20", bech32_to_bystr20_builtin);
+        let _ = specification
+            .declare_inline_generics("builtin__bystr20_to_bech32", bystr20_to_bech32_builtin);
 
         // Implementing comparison builtins:
-        let _ = specification.declare_inline_generics("builtin__lt", |_ctx, block, _arg_types| {
-            block.lt();
-            Ok([].to_vec())
-        });
+        let _ = specification.declare_inline_generics("builtin__lt", less_than);
+        let _ = specification.declare_inline_generics("builtin__eq", equal);
 
         let _ = specification.declare_inline_generics("builtin__lte", |_ctx, block, _arg_types| {
             block.dup2();
@@ -579,14 +1007,13 @@ impl BluebellModule for ScillaDefaultBuiltins {
             });
 
         // Implementing cryptographic builtins:
-        let _ = specification.declare_inline_generics(
-            "builtin__sha256hash",
-            |_ctx, _block, _arg_types| {
-                // block.external_sha256();
-                // Ok(())
-                unimplemented!()
-            },
-        );
+        let _ = specification.declare_precompile(SHA256_PRECOMPILE.into(), sha256_precompile);
+        let _ = specification.declare_precompile(RIPEMD160_PRECOMPILE.into(), ripemd160_precompile);
+
+        let _ = specification
+            .declare_inline_generics("builtin__sha256hash", |ctx, block, arg_types| {
+                hash_with_precompile(ctx, block, arg_types, SHA256_PRECOMPILE)
+            });
 
         let _ = specification.declare_inline_generics(
             "builtin__keccak256hash",
@@ -598,30 +1025,63 @@ impl BluebellModule for ScillaDefaultBuiltins {
 
         let _ = specification.declare_inline_generics(
             "builtin__ripemd160hash",
-            |_ctx, _block, _arg_types| {
-                // block.external_ripemd160();
-                // Ok(())
-                unimplemented!()
+            |ctx, block, arg_types| {
+                hash_with_precompile(ctx, block, arg_types, RIPEMD160_PRECOMPILE)
             },
         );
 
         let _ = specification.declare_inline_generics(
             "builtin__schnorr_sign",
             |_ctx, _block, _arg_types| {
-                // EVM doesn't natively support Schnorr; you'd need to call a precompiled contract or use an external lib.
-                // For now, just placing a placeholder.
-                // block.schnorr_sign();
-                unimplemented!()
+                // Signing requires the private key which cannot be kept secret on chain
+                Err("schnorr_sign is not supported in contracts".to_string())
             },
         );
 
-        let _ = specification.declare_inline_generics(
-            "builtin__schnorr_verify",
-            |_ctx, _block, _arg_types| {
-                // Same as schnorr_sign, EVM doesn't natively support Schnorr.
-                // block.schnorr_verify();
-                unimplemented!()
-            },
-        );
+        // The message is either a dynamic byte string or a hash
+        let _ = specification
+            .declare_function(
+                "builtin__schnorr_verify::<ByStr33,ByStr,ByStr64>",
+                ["ByStr33", "ByStr", "ByStr64"].to_vec(),
+                "Bool",
+            )
+            .attach_runtime(|| schnorr_verify_runtime::<false>);
+        let _ = specification
+            .declare_function(
+                "builtin__schnorr_verify::<ByStr33,ByStr32,ByStr64>",
+                ["ByStr33", "ByStr32", "ByStr64"].to_vec(),
+                "Bool",
+            )
+            .attach_runtime(|| schnorr_verify_runtime::<true>);
+
+        let _ = specification
+            .declare_function(
+                "builtin__ecdsa_verify::<ByStr33,ByStr,ByStr64>",
+                ["ByStr33", "ByStr", "ByStr64"].to_vec(),
+                "Bool",
+            )
+            .attach_runtime(|| ecdsa_verify_runtime::<false>);
+        let _ = specification
+            .declare_function(
+                "builtin__ecdsa_verify::<ByStr33,ByStr32,ByStr64>",
+                ["ByStr33", "ByStr32", "ByStr64"].to_vec(),
+                "Bool",
+            )
+            .attach_runtime(|| ecdsa_verify_runtime::<true>);
+
+        let _ = specification
+            .declare_function(
+                "builtin__ecdsa_recover_pk::<ByStr,ByStr64,Uint32>",
+                ["ByStr", "ByStr64", "Uint32"].to_vec(),
+                "ByStr65",
+            )
+            .attach_runtime(|| ecdsa_recover_pk_runtime::<false>);
+        let _ = specification
+            .declare_function(
+                "builtin__ecdsa_recover_pk::<ByStr32,ByStr64,Uint32>",
+                ["ByStr32", "ByStr64", "Uint32"].to_vec(),
+                "ByStr65",
+            )
+            .attach_runtime(|| ecdsa_recover_pk_runtime::<true>);
     }
 }
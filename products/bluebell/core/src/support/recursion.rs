@@ -0,0 +1,356 @@
+use std::collections::BTreeSet;
+
+use evm_assembly::{block::EvmBlock, EvmByteCodeBuilder};
+
+use crate::constants::{CLOSURE_ENVIRONMENT_ARGUMENT, RECURSION_PRIMITIVES};
+
+/// Names of the arguments of a closure body. The environment record is passed below the
+/// argument, see `ApplyClosure` in the code generator.
+const ENVIRONMENT: &str = CLOSURE_ENVIRONMENT_ARGUMENT;
+const VALUE: &str = "value";
+
+/// Returns the name of the function implementing the recursion primitive `primitive`. The
+/// function is a closure without captures taking the first argument of the primitive.
+pub fn recursion_primitive_label(primitive: &str) -> String {
+    format!("runtime__{}", primitive)
+}
+
+/// Duplicates the stack element at `depth`, counted from 1 at the top.
+fn dup(block: &mut EvmBlock, depth: usize) {
+    match depth {
+        1 => block.dup1(),
+        2 => block.dup2(),
+        3 => block.dup3(),
+        4 => block.dup4(),
+        5 => block.dup5(),
+        6 => block.dup6(),
+        7 => block.dup7(),
+        8 => block.dup8(),
+        _ => panic!(
+            "Internal error: Unable to duplicate stack element at depth {}",
+            depth
+        ),
+    };
+}
+
+/// Applies the closure at `closure` to the argument at `argument`, both counted from 1 at the
+/// top of the stack, and pushes the result.
+fn apply_closure(block: &mut EvmBlock, closure: usize, argument: usize) {
+    let lbl_return = block.generate_label("apply_return".to_string());
+
+    // Stack:
+    //     => ret
+    //     => closure
+    //     => argument
+    //     => function
+    block.push_label(&lbl_return);
+    dup(block, closure + 1);
+    dup(block, argument + 2);
+    block.dup2();
+    block.mload();
+    block.jump();
+
+    // The callee consumes the return pointer, the closure and the argument and leaves the
+    // result in their place
+    block.create_label(lbl_return);
+    block.scope.stack_counter -= 2;
+}
+
+/// Returns the element on top of the stack to the caller, dropping everything else.
+fn return_top(block: &mut EvmBlock) {
+    while block.scope.stack_counter > 1 {
+        block.swap1();
+        block.pop();
+    }
+    block.swap1();
+    block.jump();
+}
+
+/// Pushes the word `index` of the environment record, which is the second element on the
+/// stack when a closure body is entered.
+fn push_capture(block: &mut EvmBlock, depth: usize, index: u64) {
+    dup(block, depth);
+    block.push_u64(32 * index);
+    block.add();
+    block.mload();
+}
+
+/// Defines the closure body `name`, which returns a closure of `next` capturing the
+/// `captures` words of its own environment followed by its argument. This curries the
+/// arguments of a primitive until the last one is supplied.
+fn define_partial_application(
+    builder: &mut EvmByteCodeBuilder,
+    name: &str,
+    next: &str,
+    captures: u64,
+) -> Result<(), String> {
+    builder
+        .define_function(name, ["Uint256", "Uint256"].to_vec(), "Uint256")
+        .build(|code_builder| {
+            let args: BTreeSet<String> = [ENVIRONMENT.to_string(), VALUE.to_string()].into();
+            let mut entry = code_builder.new_evm_block_with_args("entry", args);
+
+            // Stack:
+            // env   => env
+            // value => value
+            //       => p = [next, env[1], ..., env[captures], value]
+            entry.alloca_static(32 * (captures + 2));
+            entry.push_label(next);
+            entry.dup2();
+            entry.mstore();
+            for i in 1..=captures {
+                push_capture(&mut entry, 3, i);
+                entry.dup2();
+                entry.push_u64(32 * i);
+                entry.add();
+                entry.mstore();
+            }
+            entry.dup2();
+            entry.dup2();
+            entry.push_u64(32 * (captures + 1));
+            entry.add();
+            entry.mstore();
+
+            return_top(&mut entry);
+            [entry].to_vec()
+        })
+}
+
+/// Replaces `cur` on top of the stack with a list of the elements found by following
+/// `next = mload(cur + next_offset)` until zero is reached, in reverse order. The elements are
+/// `mload(cur)`, i.e. the head of a list or the predecessor of a natural number.
+fn collect_reversed(block: &mut EvmBlock, next_offset: u64) {
+    let lbl_loop = block.generate_label("collect_loop".to_string());
+    let lbl_done = block.generate_label("collect_done".to_string());
+
+    // Stack:
+    // cur => rev
+    //     => cur
+    block.push1([0x00].to_vec());
+    block.swap1();
+
+    block.create_label(lbl_loop.clone());
+    block.dup1();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+
+    // p = [mload(cur), rev]
+    block.alloca_static(0x40);
+    block.dup2();
+    block.mload();
+    block.dup2();
+    block.mstore();
+    block.dup3();
+    block.dup2();
+    block.push1([0x20].to_vec());
+    block.add();
+    block.mstore();
+
+    // Stack:
+    // rev => p
+    // cur => next
+    // p
+    block.swap2();
+    block.pop();
+    if next_offset > 0 {
+        block.push_u64(next_offset);
+        block.add();
+    }
+    block.mload();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.pop();
+}
+
+/// Folds the list `cur` into `acc` using the curried closure `f`, which is applied as
+/// `f acc head` or, if `right` is set, as `f head acc`.
+fn fold_loop(block: &mut EvmBlock, right: bool) {
+    let lbl_loop = block.generate_label("fold_loop".to_string());
+    let lbl_done = block.generate_label("fold_done".to_string());
+
+    // Stack:
+    // f   => f
+    // acc => acc
+    // cur => cur
+    block.create_label(lbl_loop.clone());
+    block.dup1();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+
+    // Stack:
+    // f acc cur => f acc cur partial head r
+    //           => f acc cur head partial r if right is set
+    if right {
+        block.dup1();
+        block.mload();
+        apply_closure(block, 4, 1);
+        apply_closure(block, 1, 4);
+    } else {
+        apply_closure(block, 3, 2);
+        block.dup2();
+        block.mload();
+        apply_closure(block, 2, 1);
+    }
+
+    // Stack:
+    // f acc cur partial head r => f r tail
+    block.swap1();
+    block.pop();
+    block.swap1();
+    block.pop();
+    block.swap2();
+    block.pop();
+    block.push1([0x20].to_vec());
+    block.add();
+    block.mload();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.pop();
+}
+
+/// Defines the body of `@list_foldl`, `@list_foldr` or `@nat_fold` receiving the list or
+/// number last, with `f` and the initial value captured in its environment.
+fn define_fold(
+    builder: &mut EvmByteCodeBuilder,
+    name: &str,
+    primitive: &str,
+) -> Result<(), String> {
+    builder
+        .define_function(name, ["Uint256", "Uint256"].to_vec(), "Uint256")
+        .build(|code_builder| {
+            let args: BTreeSet<String> = [ENVIRONMENT.to_string(), VALUE.to_string()].into();
+            let mut entry = code_builder.new_evm_block_with_args("entry", args);
+
+            // Stack:
+            // env   => env
+            // value => value
+            //       => f
+            //       => acc
+            //       => cur
+            push_capture(&mut entry, 2, 1);
+            push_capture(&mut entry, 3, 2);
+            entry.dup3();
+            match primitive {
+                "list_foldl" => fold_loop(&mut entry, false),
+                "list_foldr" => {
+                    collect_reversed(&mut entry, 0x20);
+                    fold_loop(&mut entry, true);
+                }
+                _ => {
+                    // The predecessors of n, starting from zero
+                    collect_reversed(&mut entry, 0);
+                    fold_loop(&mut entry, false);
+                }
+            }
+
+            return_top(&mut entry);
+            [entry].to_vec()
+        })
+}
+
+/// Emits one step of `@list_foldk`: returns `acc` if `cur` is empty and otherwise
+/// `f acc head k`, where `k` is a closure of `continue_label` folding the tail.
+fn foldk_step(block: &mut EvmBlock, continue_label: &str) {
+    let lbl_done = block.generate_label("foldk_done".to_string());
+
+    // Stack:
+    // f acc cur
+    block.dup1();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+    let stack_counter = block.scope.stack_counter;
+
+    // Stack:
+    // f acc cur => f acc cur partial head partial2
+    apply_closure(block, 3, 2);
+    block.dup2();
+    block.mload();
+    apply_closure(block, 2, 1);
+
+    // k = [continue, f, tail]
+    block.alloca_static(0x60);
+    block.push_label(continue_label);
+    block.dup2();
+    block.mstore();
+    block.dup7();
+    block.dup2();
+    block.push1([0x20].to_vec());
+    block.add();
+    block.mstore();
+    push_capture(block, 5, 1);
+    block.dup2();
+    block.push1([0x40].to_vec());
+    block.add();
+    block.mstore();
+
+    apply_closure(block, 2, 1);
+    return_top(block);
+
+    block.create_label(lbl_done);
+    block.scope.stack_counter = stack_counter;
+    block.pop();
+    return_top(block);
+}
+
+/// Defines the bodies of `@list_foldk`. The first receives the list with `f` and the initial
+/// value captured, while the continuation receives the accumulator with `f` and the remaining
+/// list captured.
+fn define_foldk(
+    builder: &mut EvmByteCodeBuilder,
+    name: &str,
+    continue_label: &str,
+) -> Result<(), String> {
+    for (function, continuation) in [(name, false), (continue_label, true)] {
+        builder
+            .define_function(function, ["Uint256", "Uint256"].to_vec(), "Uint256")
+            .build(|code_builder| {
+                let args: BTreeSet<String> = [ENVIRONMENT.to_string(), VALUE.to_string()].into();
+                let mut entry = code_builder.new_evm_block_with_args("entry", args);
+
+                // Stack:
+                // env   => env
+                // value => value
+                //       => f
+                //       => acc
+                //       => cur
+                push_capture(&mut entry, 2, 1);
+                if continuation {
+                    entry.dup2();
+                    push_capture(&mut entry, 4, 2);
+                } else {
+                    push_capture(&mut entry, 3, 2);
+                    entry.dup3();
+                }
+
+                foldk_step(&mut entry, continue_label);
+                [entry].to_vec()
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Defines the functions implementing the recursion primitive `primitive`. Its arguments are
+/// supplied one at a time, each application returning a closure capturing the arguments
+/// received so far, until the list or number is supplied and the fold is run.
+pub fn define_recursion_primitive(
+    builder: &mut EvmByteCodeBuilder,
+    primitive: &str,
+) -> Result<(), String> {
+    if !RECURSION_PRIMITIVES.iter().any(|(p, _)| *p == primitive) {
+        return Err(format!("Unknown recursion primitive {}", primitive));
+    }
+
+    let name = recursion_primitive_label(primitive);
+    let with_function = format!("{}__f", name);
+    let with_initial_value = format!("{}__z", name);
+    define_partial_application(builder, &name, &with_function, 0)?;
+    define_partial_application(builder, &with_function, &with_initial_value, 1)?;
+
+    match primitive {
+        "list_foldk" => define_foldk(builder, &with_initial_value, &format!("{}__continue", name)),
+        _ => define_fold(builder, &with_initial_value, primitive),
+    }
+}
@@ -0,0 +1,876 @@
+use bech32::{FromBase32, ToBase32, Variant};
+use evm::{
+    backend::Backend,
+    executor::stack::{PrecompileFailure, PrecompileOutput},
+    Context as EvmContext,
+};
+use evm_assembly::{
+    block::{EvmBlock, ALLOCATION_POINTER},
+    compiler_context::EvmCompilerContext,
+    types::EvmType,
+};
+
+use crate::support::{
+    crypto::{charge, object_argument, returned, word_argument},
+    integers::{push_some, revert_if},
+};
+
+/// Name of the runtime function backing `bech32_to_bystr20`.
+pub const BECH32_TO_BYSTR20_RUNTIME: &str = "runtime__bech32_to_bystr20";
+/// Name of the runtime function backing `bystr20_to_bech32`.
+pub const BYSTR20_TO_BECH32_RUNTIME: &str = "runtime__bystr20_to_bech32";
+
+/// Gas charged for converting between bech32 addresses and byte strings.
+const BECH32_GAS: u64 = 100;
+
+/// Representation of the values the string builtins operate on. Strings and byte strings of
+/// arbitrary length are objects in memory consisting of a 4 byte length followed by the data,
+/// while `ByStrX` of at most 32 bytes are words holding the bytes right aligned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operand {
+    Object,
+    Word(usize),
+}
+
+fn operand(ctx: &EvmCompilerContext, typename: &str) -> Result<Operand, String> {
+    match ctx.type_declarations.get(typename) {
+        Some(t) if t.is_dynamic() => Ok(Operand::Object),
+        Some(EvmType::Uint(bits)) if typename.starts_with("ByStr") => Ok(Operand::Word(bits / 8)),
+        _ => Err(format!(
+            "Expected string or byte string, found {}",
+            typename
+        )),
+    }
+}
+
+fn single_argument(arg_types: &Vec<String>) -> Result<&String, String> {
+    match &arg_types[..] {
+        [arg] => Ok(arg),
+        _ => Err(format!("Expected one argument, found {}", arg_types.len())),
+    }
+}
+
+/// Replaces the object pointer on top of the stack with the length of the object.
+fn push_object_length(block: &mut EvmBlock) {
+    block.mload();
+    block.push1([224].to_vec());
+    block.shr();
+}
+
+/// Replaces the length on top of the stack with a pointer to a new object of that length. The
+/// allocation is padded by a word such that data can be copied a word at a time.
+pub(crate) fn alloca_object(block: &mut EvmBlock) {
+    // Stack:
+    // len => len
+    //     => p
+    block.push1([ALLOCATION_POINTER].to_vec());
+    block.mload();
+    block.dup1();
+    block.dup3();
+    block.add();
+    block.push1([0x24].to_vec());
+    block.add();
+    block.push1([ALLOCATION_POINTER].to_vec());
+    block.mstore();
+
+    // Stack:
+    // len => p
+    // p
+    block.swap1();
+    block.push1([224].to_vec());
+    block.shl();
+    block.dup2();
+    block.mstore();
+}
+
+/// Copies `len` bytes from `src` to `dest` a word at a time, consuming the three arguments. Up
+/// to 31 bytes past the end of `dest` may be overwritten.
+pub(crate) fn copy_bytes(block: &mut EvmBlock) {
+    let lbl_loop = block.generate_label("copy_bytes_loop".to_string());
+    let lbl_done = block.generate_label("copy_bytes_done".to_string());
+
+    // Stack:
+    // dest => dest
+    // src  => src
+    // len  => len
+    //      => i
+    block.push1([0x00].to_vec());
+
+    block.create_label(lbl_loop.clone());
+    block.dup2();
+    block.dup2();
+    block.lt();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+
+    // dest[i] = src[i]
+    block.dup3();
+    block.dup2();
+    block.add();
+    block.mload();
+    block.dup5();
+    block.dup3();
+    block.add();
+    block.mstore();
+
+    block.push1([0x20].to_vec());
+    block.add();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.pop();
+    block.pop();
+    block.pop();
+    block.pop();
+}
+
+/// Replaces the word on top of the stack with an object holding its `bytes` lowest bytes.
+fn word_to_object(block: &mut EvmBlock, bytes: usize) {
+    // Stack:
+    // v => p
+    block.push_u32(bytes as u32);
+    alloca_object(block);
+    block.swap1();
+    block.push_u32(((32 - bytes) * 8) as u32);
+    block.shl();
+    block.dup2();
+    block.push1([0x04].to_vec());
+    block.add();
+    block.mstore();
+}
+
+/// Replaces the nibble on top of the stack with its lower case hexadecimal digit.
+fn push_hex_digit(block: &mut EvmBlock) {
+    // c = n + '0' + ('a' - '9' - 1) * (n > 9)
+    block.dup1();
+    block.push1([0x09].to_vec());
+    block.lt();
+    block.push1([0x27].to_vec());
+    block.mul();
+    block.add();
+    block.push1([0x30].to_vec());
+    block.add();
+}
+
+/// Replaces the object on top of the stack with a string of its bytes as `0x` prefixed
+/// hexadecimal.
+fn hex_string(block: &mut EvmBlock) {
+    let lbl_loop = block.generate_label("hex_string_loop".to_string());
+    let lbl_done = block.generate_label("hex_string_done".to_string());
+
+    // Stack:
+    // s => s
+    //   => p
+    block.dup1();
+    push_object_length(block);
+    block.push1([0x01].to_vec());
+    block.shl();
+    block.push1([0x02].to_vec());
+    block.add();
+    alloca_object(block);
+
+    block.push1([b'0'].to_vec());
+    block.dup2();
+    block.push1([0x04].to_vec());
+    block.add();
+    block.mstore8();
+    block.push1([b'x'].to_vec());
+    block.dup2();
+    block.push1([0x05].to_vec());
+    block.add();
+    block.mstore8();
+
+    // Stack:
+    // s => s
+    // p => p
+    //   => i
+    block.push1([0x00].to_vec());
+
+    block.create_label(lbl_loop.clone());
+    block.dup3();
+    push_object_length(block);
+    block.dup2();
+    block.lt();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+
+    // Stack:
+    // s => s
+    // p => p
+    // i => i
+    //   => b = s[i]
+    block.dup1();
+    block.dup4();
+    block.add();
+    block.push1([0x04].to_vec());
+    block.add();
+    block.mload();
+    block.push1([248].to_vec());
+    block.shr();
+
+    // p[2 + 2 * i] = hex(b >> 4)
+    block.dup1();
+    block.push1([0x04].to_vec());
+    block.shr();
+    push_hex_digit(block);
+    block.dup3();
+    block.push1([0x01].to_vec());
+    block.shl();
+    block.dup5();
+    block.add();
+    block.push1([0x06].to_vec());
+    block.add();
+    block.mstore8();
+
+    // p[3 + 2 * i] = hex(b & 0xf)
+    block.push1([0x0f].to_vec());
+    block.and();
+    push_hex_digit(block);
+    block.dup2();
+    block.push1([0x01].to_vec());
+    block.shl();
+    block.dup4();
+    block.add();
+    block.push1([0x07].to_vec());
+    block.add();
+    block.mstore8();
+
+    block.push1([0x01].to_vec());
+    block.add();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.pop();
+    block.swap1();
+    block.pop();
+}
+
+/// Replaces the integer on top of the stack with a string of its decimal representation.
+fn decimal_string(block: &mut EvmBlock, signed: bool) {
+    // Stack:
+    // v => negative
+    //   => m = |v|
+    if signed {
+        block.push1([0x00].to_vec());
+        block.dup2();
+        block.slt();
+        block.swap1();
+
+        // m = (v ^ mask) - mask where mask is all ones for negative values
+        block.dup2();
+        block.push1([0x00].to_vec());
+        block.sub();
+        block.swap1();
+        block.dup2();
+        block.xor();
+        block.sub();
+    } else {
+        block.push1([0x00].to_vec());
+        block.swap1();
+    }
+
+    let lbl_count = block.generate_label("decimal_count".to_string());
+    let lbl_counted = block.generate_label("decimal_counted".to_string());
+    let lbl_digit = block.generate_label("decimal_digit".to_string());
+
+    // Counting the characters
+    // Stack:
+    // negative => negative
+    // m        => m
+    //          => n = negative + 1
+    //          => t = m / 10
+    block.dup2();
+    block.push1([0x01].to_vec());
+    block.add();
+    block.push1([0x0a].to_vec());
+    block.dup3();
+    block.div();
+
+    block.create_label(lbl_count.clone());
+    block.dup1();
+    block.iszero();
+    block.jump_if_to(&lbl_counted);
+    block.swap1();
+    block.push1([0x01].to_vec());
+    block.add();
+    block.swap1();
+    block.push1([0x0a].to_vec());
+    block.swap1();
+    block.div();
+    block.jump_to(&lbl_count);
+
+    // Stack:
+    // negative => negative
+    // m        => m
+    // n        => p
+    // t        => pos = p + 4 + n - 1
+    block.create_label(lbl_counted);
+    block.pop();
+    block.dup1();
+    alloca_object(block);
+    block.swap1();
+    block.dup2();
+    block.add();
+    block.push1([0x03].to_vec());
+    block.add();
+
+    // Writing the digits backwards
+    block.create_label(lbl_digit.clone());
+    block.push1([0x0a].to_vec());
+    block.dup4();
+    block.r#mod();
+    block.push1([b'0'].to_vec());
+    block.add();
+    block.dup2();
+    block.mstore8();
+    block.push1([0x01].to_vec());
+    block.swap1();
+    block.sub();
+
+    block.swap2();
+    block.push1([0x0a].to_vec());
+    block.swap1();
+    block.div();
+    block.swap2();
+    block.dup3();
+    block.iszero();
+    block.iszero();
+    block.jump_if_to(&lbl_digit);
+
+    // Stack:
+    // negative => p
+    // m
+    // p
+    // pos
+    block.pop();
+    block.swap1();
+    block.pop();
+    block.swap1();
+    if signed {
+        let lbl_positive = block.generate_label("decimal_positive".to_string());
+        block.iszero();
+        block.jump_if_to(&lbl_positive);
+        block.push1([b'-'].to_vec());
+        block.dup2();
+        block.push1([0x04].to_vec());
+        block.add();
+        block.mstore8();
+        block.create_label(lbl_positive);
+    } else {
+        block.pop();
+    }
+}
+
+/// Replaces the two objects on top of the stack with whether they have the same content.
+pub(crate) fn equal_objects(block: &mut EvmBlock) {
+    let lbl_loop = block.generate_label("equal_objects_loop".to_string());
+    let lbl_done = block.generate_label("equal_objects_done".to_string());
+
+    // Stack:
+    // a => a
+    // b => b
+    //   => r = len(a) == len(b)
+    //   => i
+    block.dup2();
+    push_object_length(block);
+    block.dup2();
+    push_object_length(block);
+    block.eq();
+    block.push1([0x00].to_vec());
+
+    block.create_label(lbl_loop.clone());
+    block.dup2();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+    block.dup4();
+    push_object_length(block);
+    block.dup2();
+    block.lt();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+
+    // Stack:
+    // a => a
+    // b => b
+    // r => r
+    // i => i
+    //   => d = a[i..i + 32] ^ b[i..i + 32]
+    block.dup1();
+    block.dup5();
+    block.add();
+    block.push1([0x04].to_vec());
+    block.add();
+    block.mload();
+    block.dup2();
+    block.dup5();
+    block.add();
+    block.push1([0x04].to_vec());
+    block.add();
+    block.mload();
+    block.xor();
+
+    // Bytes past the end of the objects are discarded from the last word
+    // shift = 8 * (32 - remaining) if remaining < 32 else 0
+    block.dup2();
+    block.dup6();
+    push_object_length(block);
+    block.sub();
+    block.dup1();
+    block.push1([0x20].to_vec());
+    block.swap1();
+    block.lt();
+    block.swap1();
+    block.push1([0x20].to_vec());
+    block.sub();
+    block.mul();
+    block.push1([0x03].to_vec());
+    block.shl();
+    block.shr();
+
+    // r = r && d == 0
+    block.iszero();
+    block.dup3();
+    block.and();
+    block.swap2();
+    block.pop();
+
+    block.push1([0x20].to_vec());
+    block.add();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.pop();
+    block.swap2();
+    block.pop();
+    block.pop();
+}
+
+/// Lowers `strlen` of a string or byte string.
+pub fn strlen(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    match operand(ctx, single_argument(&arg_types)?)? {
+        Operand::Object => push_object_length(block),
+        Operand::Word(_) => return Err(format!("strlen is not defined for {}", arg_types[0])),
+    }
+    Ok([].to_vec())
+}
+
+/// Lowers `concat` of two strings, two byte strings or two `ByStrX` whose total length fits in
+/// a word.
+pub fn concat(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    let operands = match &arg_types[..] {
+        [a, b] => (operand(ctx, a)?, operand(ctx, b)?),
+        _ => return Err(format!("Expected two arguments, found {}", arg_types.len())),
+    };
+
+    match operands {
+        (Operand::Object, Operand::Object) if arg_types[0] == arg_types[1] => {
+            // Stack:
+            // a => a
+            // b => b
+            //   => p
+            block.dup2();
+            push_object_length(block);
+            block.dup2();
+            push_object_length(block);
+            block.add();
+            alloca_object(block);
+
+            // Copying a to the start of p
+            block.dup1();
+            block.push1([0x04].to_vec());
+            block.add();
+            block.dup4();
+            block.push1([0x04].to_vec());
+            block.add();
+            block.dup5();
+            push_object_length(block);
+            copy_bytes(block);
+
+            // Copying b to p after a
+            block.dup3();
+            push_object_length(block);
+            block.dup2();
+            block.add();
+            block.push1([0x04].to_vec());
+            block.add();
+            block.dup3();
+            block.push1([0x04].to_vec());
+            block.add();
+            block.dup4();
+            push_object_length(block);
+            copy_bytes(block);
+
+            block.swap2();
+            block.pop();
+            block.pop();
+        }
+        (Operand::Word(a), Operand::Word(b)) if a + b <= 32 => {
+            // Stack:
+            // a => a << 8 * len(b) | b
+            // b
+            block.swap1();
+            block.push_u32((8 * b) as u32);
+            block.shl();
+            block.or();
+        }
+        _ => {
+            return Err(format!(
+                "Unable to concatenate values of type {}",
+                arg_types.join(", ")
+            ))
+        }
+    }
+    Ok([].to_vec())
+}
+
+/// Lowers `substr s idx len`, which reverts if the range is out of bounds.
+pub fn substr(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    match &arg_types[..] {
+        [s, idx, len]
+            if operand(ctx, s)? == Operand::Object && idx == "Uint32" && len == "Uint32" => {}
+        _ => {
+            return Err(format!(
+                "Expected arguments of type T, Uint32, Uint32, found {}",
+                arg_types.join(", ")
+            ))
+        }
+    }
+
+    // Stack:
+    // s   => s
+    // idx => idx
+    // len => len
+    //     => idx + len > len(s)
+    block.dup3();
+    push_object_length(block);
+    block.dup3();
+    block.dup3();
+    block.add();
+    block.gt();
+    revert_if(block);
+
+    // Stack:
+    // s   => s
+    // idx => idx
+    // len => len
+    //     => p
+    //     => p + 4, s + 4 + idx, len
+    block.dup1();
+    alloca_object(block);
+    block.dup1();
+    block.push1([0x04].to_vec());
+    block.add();
+    block.dup4();
+    block.dup6();
+    block.add();
+    block.push1([0x04].to_vec());
+    block.add();
+    block.dup4();
+    copy_bytes(block);
+
+    block.swap3();
+    block.pop();
+    block.pop();
+    block.pop();
+    Ok([].to_vec())
+}
+
+/// Lowers `to_string` of integers, which are converted to decimal, and of byte strings, which
+/// are converted to `0x` prefixed hexadecimal.
+pub fn to_string(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    let typename = single_argument(&arg_types)?;
+    match ctx.type_declarations.get(typename) {
+        Some(EvmType::Uint(_)) if typename.starts_with("Uint") => decimal_string(block, false),
+        Some(EvmType::Int(_)) if typename.starts_with("Int") => decimal_string(block, true),
+        _ => match operand(ctx, typename)? {
+            Operand::Object if typename != "String" => hex_string(block),
+            Operand::Word(bytes) => {
+                word_to_object(block, bytes);
+                hex_string(block);
+            }
+            _ => return Err(format!("to_string is not defined for {}", typename)),
+        },
+    }
+    Ok([].to_vec())
+}
+
+/// Lowers `to_bystr` which converts a `ByStrX` to a `ByStr`.
+pub fn to_bystr(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    match operand(ctx, single_argument(&arg_types)?)? {
+        Operand::Word(bytes) => word_to_object(block, bytes),
+        Operand::Object => return Err(format!("to_bystr is not defined for {}", arg_types[0])),
+    }
+    Ok([].to_vec())
+}
+
+/// Lowers `to_bystrX`, also known as `bystr_to_bystrx`. A `ByStr` is converted to `Some` of a
+/// `ByStrX` if it is `BYTES` long and `None` otherwise, while an unsigned integer of `BYTES`
+/// bytes is converted to its big endian representation.
+pub fn to_bystrx<const BYTES: usize>(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    let typename = single_argument(&arg_types)?;
+    match ctx.type_declarations.get(typename) {
+        // Integers are already stored as their big endian bytes
+        Some(EvmType::Uint(bits)) if typename.starts_with("Uint") && *bits == 8 * BYTES => {
+            return Ok([].to_vec())
+        }
+        Some(t) if t.is_dynamic() && typename != "String" => (),
+        _ => return Err(format!("to_bystr{} is not defined for {}", BYTES, typename)),
+    }
+
+    let lbl_some = block.generate_label("to_bystrx_some".to_string());
+    let lbl_done = block.generate_label("to_bystrx_done".to_string());
+
+    // Stack:
+    // s => s
+    //   => len(s) == BYTES
+    block.dup1();
+    push_object_length(block);
+    block.push_u32(BYTES as u32);
+    block.eq();
+    block.jump_if_to(&lbl_some);
+    block.pop();
+    block.push1([0x00].to_vec());
+    block.jump_to(&lbl_done);
+
+    // Stack:
+    // s => Some(s[0..BYTES])
+    block.create_label(lbl_some);
+    block.push1([0x04].to_vec());
+    block.add();
+    block.mload();
+    block.push_u32(((32 - BYTES) * 8) as u32);
+    block.shr();
+    push_some(block);
+
+    block.create_label(lbl_done);
+    Ok([].to_vec())
+}
+
+/// Lowers `to_ascii` which converts a byte string to a string, reverting if any of the bytes
+/// is not printable.
+pub fn to_ascii(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    match operand(ctx, single_argument(&arg_types)?)? {
+        Operand::Word(bytes) => word_to_object(block, bytes),
+        Operand::Object if arg_types[0] != "String" => (),
+        Operand::Object => return Err("to_ascii is not defined for String".to_string()),
+    }
+
+    let lbl_loop = block.generate_label("to_ascii_loop".to_string());
+    let lbl_done = block.generate_label("to_ascii_done".to_string());
+
+    // Byte strings share the layout of strings, so only the content needs to be validated
+    // Stack:
+    // s => s
+    //   => i
+    block.push1([0x00].to_vec());
+
+    block.create_label(lbl_loop.clone());
+    block.dup2();
+    push_object_length(block);
+    block.dup2();
+    block.lt();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+
+    // Stack:
+    // s => s
+    // i => i
+    //   => b = s[i]
+    //   => b < 0x20 || 0x7e < b
+    block.dup1();
+    block.dup3();
+    block.add();
+    block.push1([0x04].to_vec());
+    block.add();
+    block.mload();
+    block.push1([248].to_vec());
+    block.shr();
+    block.dup1();
+    block.push1([0x20].to_vec());
+    block.swap1();
+    block.lt();
+    block.swap1();
+    block.push1([0x7e].to_vec());
+    block.lt();
+    block.or();
+    revert_if(block);
+
+    block.push1([0x01].to_vec());
+    block.add();
+    block.jump_to(&lbl_loop);
+
+    block.create_label(lbl_done);
+    block.pop();
+    Ok([].to_vec())
+}
+
+/// Lowers `bech32_to_bystr20 prefix addr` by calling its runtime function, which returns the
+/// address with bit 160 set or zero if the address is invalid.
+pub fn bech32_to_bystr20_builtin(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    if arg_types != ["String".to_string(), "String".to_string()] {
+        return Err(format!(
+            "Expected arguments of type String, String, found {}",
+            arg_types.join(", ")
+        ));
+    }
+    let signature = ctx
+        .get_function(BECH32_TO_BYSTR20_RUNTIME)
+        .cloned()
+        .ok_or(format!(
+            "Runtime function {} not found",
+            BECH32_TO_BYSTR20_RUNTIME
+        ))?;
+    block.call(&signature, [EvmType::String, EvmType::String].to_vec());
+
+    let lbl_done = block.generate_label("bech32_to_bystr20_done".to_string());
+
+    // Stack:
+    // w => None if w is zero, otherwise Some(w & (2^160 - 1))
+    block.dup1();
+    block.iszero();
+    block.jump_if_to(&lbl_done);
+    block.push1([0x01].to_vec());
+    block.push1([160].to_vec());
+    block.shl();
+    block.push1([0x01].to_vec());
+    block.swap1();
+    block.sub();
+    block.and();
+    push_some(block);
+
+    block.create_label(lbl_done);
+    Ok([].to_vec())
+}
+
+/// Lowers `bystr20_to_bech32 prefix addr` by calling its runtime function, which returns an
+/// empty string if the address can not be encoded.
+pub fn bystr20_to_bech32_builtin(
+    ctx: &mut EvmCompilerContext,
+    block: &mut EvmBlock,
+    arg_types: Vec<String>,
+) -> Result<Vec<EvmBlock>, String> {
+    if arg_types != ["String".to_string(), "ByStr20".to_string()] {
+        return Err(format!(
+            "Expected arguments of type String, ByStr20, found {}",
+            arg_types.join(", ")
+        ));
+    }
+    let signature = ctx
+        .get_function(BYSTR20_TO_BECH32_RUNTIME)
+        .cloned()
+        .ok_or(format!(
+            "Runtime function {} not found",
+            BYSTR20_TO_BECH32_RUNTIME
+        ))?;
+    block.call(&signature, [EvmType::String, EvmType::Bytes(20)].to_vec());
+
+    let lbl_none = block.generate_label("bystr20_to_bech32_none".to_string());
+    let lbl_done = block.generate_label("bystr20_to_bech32_done".to_string());
+
+    // Stack:
+    // s => None if s is empty, otherwise Some(s)
+    block.dup1();
+    push_object_length(block);
+    block.iszero();
+    block.jump_if_to(&lbl_none);
+    push_some(block);
+    block.jump_to(&lbl_done);
+
+    block.create_label(lbl_none);
+    block.pop();
+    block.push1([0x00].to_vec());
+
+    block.create_label(lbl_done);
+    Ok([].to_vec())
+}
+
+/// Decodes a bech32 address with the human readable part `prefix` into its 20 bytes.
+pub fn bech32_to_bystr20(prefix: &str, address: &str) -> Option<[u8; 20]> {
+    let (hrp, data, variant) = bech32::decode(address).ok()?;
+    if hrp != prefix || variant != Variant::Bech32 {
+        return None;
+    }
+    Vec::<u8>::from_base32(&data).ok()?.try_into().ok()
+}
+
+/// Encodes 20 bytes as a bech32 address with the human readable part `prefix`.
+pub fn bystr20_to_bech32(prefix: &str, address: &[u8; 20]) -> Option<String> {
+    bech32::encode(prefix, address.to_base32(), Variant::Bech32).ok()
+}
+
+/// Runtime function for `builtin bech32_to_bystr20 prefix addr`.
+pub fn bech32_to_bystr20_runtime(
+    input: &[u8],
+    gas_limit: Option<u64>,
+    _context: &EvmContext,
+    _backend: &dyn Backend,
+    _is_static: bool,
+) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+    let gas = charge(gas_limit, BECH32_GAS)?;
+    let prefix = std::str::from_utf8(object_argument(input, 0)?);
+    let address = std::str::from_utf8(object_argument(input, 1)?);
+
+    let mut output = [0u8; 32];
+    if let (Ok(prefix), Ok(address)) = (prefix, address) {
+        if let Some(bytes) = bech32_to_bystr20(prefix, address) {
+            output[11] = 1;
+            output[12..].copy_from_slice(&bytes);
+        }
+    }
+    returned(output.to_vec(), gas)
+}
+
+/// Runtime function for `builtin bystr20_to_bech32 prefix addr`. The address is returned as a
+/// memory object, i.e. prefixed by its length.
+pub fn bystr20_to_bech32_runtime(
+    input: &[u8],
+    gas_limit: Option<u64>,
+    _context: &EvmContext,
+    _backend: &dyn Backend,
+    _is_static: bool,
+) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+    let gas = charge(gas_limit, BECH32_GAS)?;
+    let prefix = std::str::from_utf8(object_argument(input, 0)?);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&word_argument(input, 1)?[12..]);
+
+    let encoded = match prefix {
+        Ok(prefix) => bystr20_to_bech32(prefix, &address).unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+    let mut output = (encoded.len() as u32).to_be_bytes().to_vec();
+    output.extend(encoded.into_bytes());
+    returned(output, gas)
+}
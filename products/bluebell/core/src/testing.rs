@@ -1,14 +1,22 @@
 use std::{rc::Rc, str::FromStr};
 
 use evm_assembly::{
-    executable::EvmExecutable, observable_machine::ObservableMachine, types::EvmTypeValue,
+    compiler_context::EvmCompilerContext, executable::EvmExecutable,
+    observable_machine::ObservableMachine, types::EvmTypeValue,
 };
 use primitive_types::H256;
 use serde_json;
 
-use crate::support::{
-    evm::EvmCompiler,
-    modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+use crate::{
+    intermediate_representation::{
+        pass_manager::create_pass,
+        symbol_table::SymbolTableConstructor,
+        textual::{parse_ir, print_ir},
+    },
+    support::{
+        evm::EvmCompiler,
+        modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+    },
 };
 
 pub fn create_vm_and_run_code(
@@ -16,9 +24,23 @@ pub fn create_vm_and_run_code(
     args: &str,
     source: String,
     initial_storage: &str,
+) -> (ObservableMachine, EvmExecutable) {
+    create_vm_and_run_code_with_optimisation(function_name, args, source, initial_storage, 0)
+}
+
+/// Same as `create_vm_and_run_code`, compiling the source at the given optimisation level.
+pub fn create_vm_and_run_code_with_optimisation(
+    function_name: &str,
+    args: &str,
+    source: String,
+    initial_storage: &str,
+    optimisation_level: u32,
 ) -> (ObservableMachine, EvmExecutable) {
     let mut compiler = EvmCompiler::new();
-    compiler.pass_manager_mut().enable_debug_printer();
+    compiler
+        .pass_manager_mut()
+        .set_optimisation_level(optimisation_level)
+        .enable_debug_printer();
 
     let default_types = ScillaDefaultTypes {};
     let default_builtins = ScillaDefaultBuiltins {};
@@ -45,11 +67,13 @@ pub fn create_vm_and_run_code(
             .get_function(&function_name)
             .expect(&format!("Function name {} not found", function_name).to_string())
             .generate_transaction_data(arguments)
+            .expect("Failed to encode the arguments")
             .into()
     };
 
     let source_map = executor.executable.get_source_map();
-    let mut vm = ObservableMachine::new(Rc::new(code), data, 1024, 10000, None);
+    let precompiles = executor.context.get_precompiles();
+    let mut vm = ObservableMachine::new(Rc::new(code), data, 1024, 10000, Some(precompiles));
     vm.set_source_map(&source_map);
 
     let initial_storage: Vec<&str> = initial_storage.lines().collect();
@@ -79,6 +103,21 @@ pub fn create_vm_and_run_code(
     (vm, executor.executable)
 }
 
+/// Parses `ir` from its textual syntax, runs the comma separated passes in `spec` on it and
+/// returns the resulting IR in the textual syntax. Allows passes to be tested in isolation.
+pub fn run_passes_on_ir(ir: &str, spec: &str) -> Result<String, String> {
+    let context = EvmCompilerContext::new();
+    let mut ir = parse_ir(ir, context.new_symbol_table())?;
+    for name in spec.split(',').map(|name| name.trim()) {
+        if name.is_empty() {
+            continue;
+        }
+        let mut pass = create_pass(name)?;
+        ir.run_pass(pass.as_mut())?;
+    }
+    Ok(print_ir(&ir))
+}
+
 fn format_hex_string(input: &str) -> Result<String, &'static str> {
     if !input.starts_with("0x") {
         return Err("String does not start with 0x prefix");
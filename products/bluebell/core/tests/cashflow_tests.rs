@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::{
+        passes::cashflow::{CashflowReport, CashflowTag},
+        support::{
+            evm::EvmCompiler,
+            modules::{ScillaDefaultBuiltins, ScillaDefaultTypes},
+        },
+    };
+
+    fn analyse(source: &str) -> CashflowReport {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+
+        compiler
+            .cashflow(source.to_string())
+            .expect("Failed to analyse contract")
+    }
+
+    /// Returns the line and message of each warning in `report`.
+    fn warnings(source: &str, report: &CashflowReport) -> Vec<(usize, String)> {
+        report
+            .warnings
+            .iter()
+            .map(|warning| {
+                let line = source[..warning.position.position].matches('\n').count() + 1;
+                (line, warning.message.clone())
+            })
+            .collect()
+    }
+
+    const WALLET: &str = r#"scilla_version 0
+library Wallet
+contract Wallet()
+field balances : Map ByStr20 Uint128 = Emp ByStr20 Uint128
+field total : Uint128 = Uint128 0
+field count : Uint32 = Uint32 0
+field name : String = "wallet"
+
+transition Deposit ()
+  accept;
+  amount = _amount;
+  sender = _sender;
+  balances[sender] := amount;
+  old <- total;
+  new = builtin add old amount;
+  total := new;
+  c <- count;
+  one = Uint32 1;
+  c2 = builtin add c one;
+  count := c2
+end
+
+transition Withdraw (value : Uint128, to : ByStr20)
+  t <- total;
+  rest = builtin sub t value;
+  total := rest;
+  msg = {_tag : ""; _recipient : to; _amount : value}
+end
+
+transition Donate ()
+  accept
+end
+
+transition Peek ()
+  a = _amount;
+  e = {_eventname : "Peek"; amount : a};
+  event e
+end
+"#;
+
+    #[test]
+    fn test_fields_are_tagged() {
+        let report = analyse(WALLET);
+        assert_eq!(report.fields["balances"], CashflowTag::Money);
+        assert_eq!(report.fields["total"], CashflowTag::Money);
+        assert_eq!(report.fields["count"], CashflowTag::NoInfo);
+        assert_eq!(report.fields["name"], CashflowTag::NotMoney);
+    }
+
+    #[test]
+    fn test_parameters_are_tagged() {
+        let report = analyse(WALLET);
+        let withdraw = report
+            .transitions
+            .iter()
+            .find(|t| t.name == "Withdraw")
+            .expect("Missing transition");
+        assert_eq!(withdraw.parameters["value"], CashflowTag::Money);
+        assert_eq!(withdraw.parameters["to"], CashflowTag::NotMoney);
+    }
+
+    #[test]
+    fn test_suspicious_flows_are_reported() {
+        let report = analyse(WALLET);
+        assert_eq!(
+            warnings(WALLET, &report),
+            vec![
+                (
+                    31,
+                    "Transition `Donate` accepts funds without recording them in a money field"
+                        .to_string()
+                ),
+                (
+                    35,
+                    "Transition `Peek` reads `_amount` but never accepts the funds".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_usage() {
+        let source = r#"scilla_version 0
+library Fees
+contract Fees()
+field total : Uint128 = Uint128 0
+
+transition Pay (fee : Uint128)
+  accept;
+  a = _amount;
+  s = builtin add a fee;
+  total := s;
+  c <- & CHAINID;
+  is_chain = builtin eq fee c
+end
+"#;
+        let report = analyse(source);
+        assert_eq!(
+            report.transitions[0].parameters["fee"],
+            CashflowTag::Inconsistent
+        );
+        assert_eq!(report.fields["total"], CashflowTag::Inconsistent);
+
+        let messages: Vec<String> = warnings(source, &report)
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect();
+        assert!(messages.contains(
+            &"Parameter `fee` of `Pay` is used both as money and as a non-money value".to_string()
+        ));
+        assert!(messages
+            .contains(&"Field `total` is used both as money and as a non-money value".to_string()));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let report = analyse(WALLET);
+        let json: serde_json::Value =
+            serde_json::from_str(&report.to_json().unwrap()).expect("Invalid JSON");
+        assert_eq!(json["fields"]["total"], "Money");
+        assert_eq!(json["transitions"][1]["name"], "Withdraw");
+        assert_eq!(json["transitions"][1]["parameters"]["to"], "NotMoney");
+        assert_eq!(json["warnings"].as_array().unwrap().len(), 2);
+        assert!(json["transitions"][0]["gas_estimate"].is_u64());
+    }
+
+    #[test]
+    fn test_gas_estimates() {
+        let report = analyse(WALLET);
+        let gas = |name: &str| {
+            report
+                .transitions
+                .iter()
+                .find(|t| t.name == name)
+                .and_then(|t| t.gas_estimate)
+                .expect("Missing gas estimate")
+        };
+
+        // Deposit loads two fields and stores three, each store setting a slot in the worst case
+        assert!(gas("Deposit") >= 3 * 22100 + 2 * 2100);
+        assert!(gas("Donate") > 0);
+        assert!(gas("Deposit") > gas("Donate"));
+    }
+}
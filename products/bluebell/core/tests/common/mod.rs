@@ -0,0 +1,67 @@
+//! Helpers shared by the tests compiling and executing contracts.
+// Not every test uses every helper
+#![allow(dead_code)]
+
+use bluebell::support::{
+    evm::EvmCompiler,
+    modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+};
+use evm_assembly::{executor::ExecutorResult, types::EvmTypeValue};
+use serde_json;
+
+/// Returns a compiler with the default types and builtins as well as the debug builtins.
+pub fn new_compiler() -> EvmCompiler {
+    let mut compiler = EvmCompiler::new();
+    let default_types = ScillaDefaultTypes {};
+    let default_builtins = ScillaDefaultBuiltins {};
+    let debug = ScillaDebugBuiltins {};
+
+    compiler.attach(&default_types);
+    compiler.attach(&default_builtins);
+    compiler.attach(&debug);
+    compiler
+}
+
+/// Lists the storage changes, sorted by slot, with `+` marking stored and `-` deleted slots.
+pub fn result_to_string(ret: ExecutorResult) -> String {
+    let mut result = "".to_string();
+    let mut sorted_changeset: Vec<(String, Option<String>)> = ret.changeset.into_iter().collect();
+    sorted_changeset.sort_by_key(|(key, _)| key.clone());
+    for (k, v) in sorted_changeset {
+        match v {
+            Some(v) => {
+                result.push_str("+");
+                result.push_str(&k);
+                result.push_str("=");
+                result.push_str(&v);
+            }
+            None => {
+                result.push_str("-");
+                result.push_str(&k);
+            }
+        }
+        result.push_str("\n");
+    }
+
+    result.trim().to_string()
+}
+
+/// Compiles `script` and executes `entry_point` with the JSON encoded arguments `args`.
+pub fn compile_and_execute_full_evm(
+    entry_point: &str,
+    args: &str,
+    script: &str,
+) -> Result<ExecutorResult, String> {
+    let mut compiler = new_compiler();
+    let executable = compiler.executable_from_script(script.to_string())?;
+
+    let arguments: Vec<EvmTypeValue> = if args == "" {
+        [].to_vec()
+    } else {
+        serde_json::from_str(&args).expect("Failed to deserialize arguments")
+    };
+
+    let ret = executable.execute(&entry_point, arguments)?;
+
+    Ok(ret)
+}
@@ -0,0 +1,218 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::{
+        support::{
+            crypto::{ecdsa_recover_pk, ecdsa_verify, ripemd160, schnorr_verify, sha256},
+            evm::EvmCompiler,
+            modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+        },
+        testing::create_vm_and_run_code,
+    };
+    use evm_assembly::types::EvmTypeValue;
+    use primitive_types::H256;
+    use serde_json;
+
+    const PUBLIC_KEY: &str = "02085fe2ca7a5758957ea811bd8e743d9cee6bc20072f1470a888c43a1091a8e8b";
+    const UNCOMPRESSED_PUBLIC_KEY: &str = "04085fe2ca7a5758957ea811bd8e743d9cee6bc20072f1470a888c43a1091a8e8b6c24b94641fa44c371b757127afcba3652e884413ada780be21d0585190deeac";
+    const MESSAGE: &[u8] = b"Hello, Zilliqa!";
+    const ECDSA_SIGNATURE: &str = "9c5530e4385ebc41cdaf8257edf9a2baaf8506a4099103211e6ed7382103ed6739f2983cb8e3b14f06720e38e4419dd3e493872fcecf520d40a5090ad61989d0";
+    const SCHNORR_SIGNATURE: &str = "fe4ea384a98c994f81f56a29ceeb62bffb5d91138953a8b9b764a069b2ef1d848f5b7e3650c92c9e61219ae8e0d6a501160664d95af37fc979c284c96b862caf";
+
+    fn bytes(value: &str) -> Vec<u8> {
+        hex::decode(value).unwrap()
+    }
+
+    /// Returns `signature` with its last bit flipped.
+    fn tampered(signature: &str) -> String {
+        let mut ret = bytes(signature);
+        ret[63] ^= 1;
+        hex::encode(ret)
+    }
+
+    /// Runs a transition executing `body` and storing the Bool `r`. Returns the stored value or
+    /// `None` if execution reverted before storing it.
+    fn run_in_contract(body: &str) -> Option<H256> {
+        let source = format!(
+            r#"scilla_version 0
+library Crypto
+contract Crypto()
+field result : Bool = False
+
+transition run ()
+  m = 0x{message};
+  msg = builtin to_bystr m;
+  {body};
+  result := r
+end
+"#,
+            message = hex::encode(MESSAGE),
+            body = body
+        );
+
+        let (vm, _executable) = create_vm_and_run_code("Crypto::run", "", source, "");
+        vm.storage.get(&H256::from_low_u64_be(0)).copied()
+    }
+
+    fn verify_in_contract(builtin: &str, signature: &str) -> Option<H256> {
+        run_in_contract(&format!(
+            "pk = 0x{}; sig = 0x{}; r = builtin {} pk msg sig",
+            PUBLIC_KEY, signature, builtin
+        ))
+    }
+
+    /// Recovers the public key with `recovery_id` and compares its hash to the hash of the known
+    /// public key, as values of more than a word can not be compared directly.
+    fn recover_in_contract(signature: &str, recovery_id: u32) -> Option<H256> {
+        let expected = hex::encode(sha256(&bytes(UNCOMPRESSED_PUBLIC_KEY)));
+        run_in_contract(&format!(
+            r#"sig = 0x{}; id = Uint32 {}; pk = builtin ecdsa_recover_pk msg sig id;
+  h = builtin sha256hash pk; expected = 0x{}; r = builtin eq h expected"#,
+            signature, recovery_id, expected
+        ))
+    }
+
+    fn is_true(result: Option<H256>) -> bool {
+        result == Some(H256::from_low_u64_be(1))
+    }
+
+    /// Stores the hash of a Uint64 computed by `builtin` in a field of type `hash_type`.
+    fn hash_in_contract(builtin: &str, hash_type: &str) -> Option<String> {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        let debug = ScillaDebugBuiltins {};
+
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler.attach(&debug);
+
+        let script = format!(
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field result : {hash_type} = 0x{zero}
+
+transition hash (x : Uint64)
+  h = builtin {builtin} x;
+  result := h
+end
+"#,
+            hash_type = hash_type,
+            zero = "0".repeat(if hash_type == "ByStr20" { 40 } else { 64 }),
+            builtin = builtin
+        );
+        let executable = compiler.executable_from_script(script).unwrap();
+        let arguments: Vec<EvmTypeValue> = serde_json::from_str("[42]").unwrap();
+        let ret = executable.execute("HelloWorld::hash", arguments).unwrap();
+
+        ret.changeset
+            .get("0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000")
+            .cloned()
+            .flatten()
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            hex::encode(sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_ripemd160() {
+        assert_eq!(
+            hex::encode(ripemd160(b"abc")),
+            "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+    }
+
+    #[test]
+    fn test_schnorr_verify() {
+        let public_key = bytes(PUBLIC_KEY);
+        let signature = bytes(SCHNORR_SIGNATURE);
+        assert!(schnorr_verify(&public_key, MESSAGE, &signature));
+        assert!(!schnorr_verify(
+            &public_key,
+            b"Hello, Ethereum!",
+            &signature
+        ));
+
+        let mut tampered = signature.clone();
+        tampered[63] ^= 1;
+        assert!(!schnorr_verify(&public_key, MESSAGE, &tampered));
+        assert!(!schnorr_verify(&public_key, MESSAGE, &signature[..63]));
+    }
+
+    #[test]
+    fn test_ecdsa_verify() {
+        let public_key = bytes(PUBLIC_KEY);
+        let signature = bytes(ECDSA_SIGNATURE);
+        assert!(ecdsa_verify(&public_key, MESSAGE, &signature));
+        assert!(!ecdsa_verify(&public_key, b"Hello, Ethereum!", &signature));
+        assert!(!ecdsa_verify(
+            &public_key,
+            MESSAGE,
+            &bytes(SCHNORR_SIGNATURE)
+        ));
+    }
+
+    #[test]
+    fn test_ecdsa_recover_pk() {
+        let signature = bytes(ECDSA_SIGNATURE);
+        assert_eq!(
+            ecdsa_recover_pk(MESSAGE, &signature, 0).unwrap(),
+            bytes(UNCOMPRESSED_PUBLIC_KEY)
+        );
+        assert_ne!(
+            ecdsa_recover_pk(MESSAGE, &signature, 1).ok(),
+            Some(bytes(UNCOMPRESSED_PUBLIC_KEY))
+        );
+        assert!(ecdsa_recover_pk(MESSAGE, &signature, 4).is_err());
+    }
+
+    #[test]
+    fn test_sha256hash_builtin() {
+        // SHA256 of the big endian bytes of Uint64 42
+        assert_eq!(
+            hash_in_contract("sha256hash", "ByStr32"),
+            Some("0xa6bb133cb1e3638ad7b8a3ff0539668e9e56f9b850ef1b2a810f5422eaa6c323".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ripemd160hash_builtin() {
+        assert_eq!(
+            hash_in_contract("ripemd160hash", "ByStr20"),
+            Some("0x0000000000000000000000008aca5395da5ad9bdfe92deeb652d0539aa045ca4".to_string())
+        );
+    }
+
+    #[test]
+    fn test_schnorr_verify_builtin() {
+        assert!(is_true(verify_in_contract(
+            "schnorr_verify",
+            SCHNORR_SIGNATURE
+        )));
+        assert_eq!(
+            verify_in_contract("schnorr_verify", &tampered(SCHNORR_SIGNATURE)),
+            Some(H256::zero())
+        );
+    }
+
+    #[test]
+    fn test_ecdsa_verify_builtin() {
+        assert!(is_true(verify_in_contract("ecdsa_verify", ECDSA_SIGNATURE)));
+        assert_eq!(
+            verify_in_contract("ecdsa_verify", &tampered(ECDSA_SIGNATURE)),
+            Some(H256::zero())
+        );
+    }
+
+    #[test]
+    fn test_ecdsa_recover_pk_builtin() {
+        assert!(is_true(recover_in_contract(ECDSA_SIGNATURE, 0)));
+        // Recovering with the wrong id either fails or gives another key
+        assert!(!is_true(recover_in_contract(ECDSA_SIGNATURE, 1)));
+    }
+}
@@ -0,0 +1,288 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::support::{
+        evm::EvmCompiler,
+        modules::{ScillaDefaultBuiltins, ScillaDefaultTypes},
+    };
+    use evm_assembly::{
+        abi::{AbiEntryKind, StateMutability},
+        executor::EvmExecutor,
+        types::EvmTypeValue,
+    };
+    use primitive_types::{H160, H256, U256};
+
+    fn new_compiler() -> EvmCompiler {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler
+    }
+
+    /// Returns the word stored in `slot` of the deployed contract.
+    fn stored(executor: &EvmExecutor, slot: U256) -> U256 {
+        let mut bytes = [0u8; 32];
+        slot.to_big_endian(&mut bytes);
+        match executor.storage.get(&H256::from(bytes)) {
+            Some(value) => U256::from_big_endian(value.as_bytes()),
+            None => U256::zero(),
+        }
+    }
+
+    const TOKEN: &str = r#"scilla_version 0
+library Token
+contract Token(owner : Uint64, supply : Uint64, active : Bool)
+with
+  builtin lt owner supply
+=>
+field total : Uint64 = supply
+field name : String = "token"
+field last_owner : Uint64 = Uint64 0
+
+transition recordOwner ()
+  last_owner := owner
+end
+"#;
+
+    #[test]
+    fn test_deploy() {
+        let mut compiler = new_compiler();
+        let mut executor = compiler
+            .executable_from_script(TOKEN.to_string())
+            .expect("Failed to compile contract");
+        let runtime = executor.executable.bytecode.clone();
+
+        let result = executor
+            .deploy(
+                [
+                    EvmTypeValue::Uint64(7),
+                    EvmTypeValue::Uint64(1000),
+                    EvmTypeValue::Uint64(1),
+                ]
+                .to_vec(),
+            )
+            .expect("Failed to encode the arguments");
+        assert_eq!(result.revert_reason, None);
+
+        // The runtime code is returned with the contract parameters in place
+        let code = &executor.executable.bytecode;
+        assert_eq!(code.len(), runtime.len());
+        let layout = executor.executable.storage_layout.clone();
+        let owner = layout.get_immutable("owner").unwrap().offset as usize;
+        assert_eq!(code[owner + 31], 7);
+        assert_eq!(code[..owner], runtime[..owner]);
+
+        // Initialisers referring to parameters are evaluated by the constructor
+        let total = layout.get("total").unwrap();
+        assert_eq!(
+            total.decode(stored(&executor, total.slot)),
+            U256::from(1000)
+        );
+        assert_ne!(
+            stored(&executor, layout.get("name").unwrap().data_slot()),
+            U256::zero()
+        );
+
+        let result = executor
+            .execute("Token::recordOwner", [].to_vec())
+            .expect("Failed to encode the arguments");
+        let last_owner = layout.get("last_owner").unwrap();
+        let mut slot = [0u8; 32];
+        last_owner.slot.to_big_endian(&mut slot);
+        let key = format!(
+            "0x1000000000000000000000000000000000000000.0x{}",
+            hex::encode(slot)
+        );
+        let word = result.changeset.get(&key).cloned().flatten().unwrap();
+        let word = U256::from_str_radix(&word[2..], 16).unwrap();
+        assert_eq!(last_owner.decode(word), U256::from(7));
+    }
+
+    #[test]
+    fn test_constraint_violated() {
+        let mut compiler = new_compiler();
+        let mut executor = compiler
+            .executable_from_script(TOKEN.to_string())
+            .expect("Failed to compile contract");
+        let runtime = executor.executable.bytecode.clone();
+
+        let result = executor
+            .deploy(
+                [
+                    EvmTypeValue::Uint64(1000),
+                    EvmTypeValue::Uint64(7),
+                    EvmTypeValue::Uint64(1),
+                ]
+                .to_vec(),
+            )
+            .expect("Failed to encode the arguments");
+        assert!(result.revert_reason.is_some());
+        assert_eq!(executor.executable.bytecode, runtime);
+        assert!(executor.storage.is_empty());
+    }
+
+    #[test]
+    fn test_abi() {
+        let executable = new_compiler()
+            .compile(TOKEN.to_string())
+            .expect("Failed to compile contract");
+        let abi = &executable.abi;
+
+        let constructor = abi.constructor().expect("Missing constructor");
+        let inputs: Vec<(&str, &str)> = constructor
+            .inputs
+            .iter()
+            .map(|input| (input.name.as_str(), input.typename.as_str()))
+            .collect();
+        assert_eq!(
+            inputs,
+            [
+                ("owner", "uint64"),
+                ("supply", "uint64"),
+                ("active", "bool")
+            ]
+        );
+
+        let transition = abi.function("recordOwner").expect("Missing transition");
+        assert_eq!(transition.kind, AbiEntryKind::Function);
+        assert_eq!(transition.state_mutability, StateMutability::Nonpayable);
+        assert_eq!(transition.signature(), "recordOwner()");
+
+        let getter = abi.function("total").expect("Missing getter");
+        assert_eq!(getter.state_mutability, StateMutability::View);
+        assert_eq!(getter.outputs.as_ref().unwrap()[0].typename, "uint64");
+        // Strings and maps are not exposed through getters
+        assert!(abi.function("name").is_none());
+
+        let json = abi.to_json().expect("Failed to serialize ABI");
+        assert!(json.contains("\"type\": \"constructor\""));
+        assert!(json.contains("\"stateMutability\": \"view\""));
+        assert_eq!(&evm_assembly::abi::Abi::from_json(&json).unwrap(), abi);
+    }
+
+    const KEYS: &str = r#"scilla_version 0
+library Keys
+contract Keys(admin : ByStr20)
+field matches : Bool = False
+field last_admin : ByStr20 = admin
+
+transition setKey (key : ByStr4, who : ByStr20)
+  s = builtin to_string key;
+  expected = "0xdeadbeef";
+  r = builtin eq s expected;
+  matches := r
+end
+"#;
+
+    #[test]
+    fn test_byte_string_abi() {
+        let mut compiler = new_compiler();
+        let mut executor = compiler
+            .executable_from_script(KEYS.to_string())
+            .expect("Failed to compile contract");
+
+        let abi = executor.executable.abi.clone();
+        let constructor = abi.constructor().expect("Missing constructor");
+        assert_eq!(constructor.inputs[0].typename, "address");
+        let transition = abi.function("setKey").expect("Missing transition");
+        assert_eq!(transition.signature(), "setKey(bytes4,address)");
+        let getter = abi.function("last_admin").expect("Missing getter");
+        assert_eq!(getter.outputs.as_ref().unwrap()[0].typename, "address");
+
+        let admin = EvmTypeValue::Address(H160::from_low_u64_be(0xff));
+        let result = executor
+            .deploy([admin.clone()].to_vec())
+            .expect("Failed to encode the arguments");
+        assert_eq!(result.revert_reason, None);
+
+        // The key is passed left aligned as `bytes4` and held right aligned
+        let result = executor
+            .execute(
+                "Keys::setKey",
+                [EvmTypeValue::BytesN(vec![0xde, 0xad, 0xbe, 0xef]), admin].to_vec(),
+            )
+            .expect("Failed to encode the arguments");
+        assert_eq!(result.revert_reason, None);
+
+        let matches = executor.executable.storage_layout.get("matches").unwrap();
+        let mut slot = [0u8; 32];
+        matches.slot.to_big_endian(&mut slot);
+        let key = format!(
+            "0x1000000000000000000000000000000000000000.0x{}",
+            hex::encode(slot)
+        );
+        let word = result.changeset.get(&key).cloned().flatten().unwrap();
+        let word = U256::from_str_radix(&word[2..], 16).unwrap();
+        assert_eq!(matches.decode(word), U256::from(1));
+    }
+
+    #[test]
+    fn test_abi_rejects_types_without_representation() {
+        let source = r#"scilla_version 0
+library Options
+contract Options()
+
+transition take (o : Option Uint32)
+  accept
+end
+"#;
+        let error = match new_compiler().compile(source.to_string()) {
+            Ok(_) => panic!("Compiled a transition without ABI representation"),
+            Err(e) => e,
+        };
+        assert!(error.contains("has no ABI representation"), "{}", error);
+    }
+
+    const NAMES: &str = r#"scilla_version 0
+library Names
+contract Names()
+field matches : Bool = False
+
+transition setName (name : String, data : ByStr, key : ByStr33)
+  expected = "a name which does not fit in a single word";
+  r = builtin eq name expected;
+  matches := r
+end
+"#;
+
+    #[test]
+    fn test_dynamic_arguments() {
+        let mut compiler = new_compiler();
+        let executor = compiler
+            .executable_from_script(NAMES.to_string())
+            .expect("Failed to compile contract");
+
+        let transition = executor
+            .executable
+            .abi
+            .function("setName")
+            .expect("Missing transition");
+        assert_eq!(transition.signature(), "setName(string,bytes,bytes)");
+
+        // Strings and byte strings are passed in the tail of the calldata
+        let result = executor
+            .execute(
+                "Names::setName",
+                [
+                    EvmTypeValue::String("a name which does not fit in a single word".to_string()),
+                    EvmTypeValue::Bytes(vec![0xbe, 0xef]),
+                    EvmTypeValue::BytesN(vec![0x02; 33]),
+                ]
+                .to_vec(),
+            )
+            .expect("Failed to encode the arguments");
+        assert_eq!(result.revert_reason, None);
+
+        let matches = executor.executable.storage_layout.get("matches").unwrap();
+        let mut slot = [0u8; 32];
+        matches.slot.to_big_endian(&mut slot);
+        let key = format!(
+            "0x1000000000000000000000000000000000000000.0x{}",
+            hex::encode(slot)
+        );
+        let word = result.changeset.get(&key).cloned().flatten().unwrap();
+        let word = U256::from_str_radix(&word[2..], 16).unwrap();
+        assert_eq!(matches.decode(word), U256::from(1));
+    }
+}
@@ -1,6 +1,63 @@
 #[cfg(test)]
 mod tests {
-    use bluebell::testing::test_execution_path;
+    use bluebell::{
+        diagnostics::{self, Diagnostic, Span},
+        support::{
+            evm::EvmCompiler,
+            modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+        },
+        testing::test_execution_path,
+    };
+    use scilla_parser::parser::lexer::SourcePosition;
+
+    fn new_compiler() -> EvmCompiler {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        let debug = ScillaDebugBuiltins {};
+
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler.attach(&debug);
+        compiler
+    }
+
+    /// Type checks `source` and returns the line and message of each error found.
+    fn check(source: &str) -> Vec<(usize, String)> {
+        let errors = new_compiler()
+            .check(source.to_string())
+            .expect("Failed to emit IR");
+
+        errors
+            .iter()
+            .map(|error| {
+                let line = source[..error.position.position].matches('\n').count() + 1;
+                (line, error.message.clone())
+            })
+            .collect()
+    }
+
+    /// Checks a contract with a `Uint64` field `result`, a map field `balances` and the
+    /// transition `run` executing `body`.
+    fn check_transition(parameters: &str, body: &str) -> Vec<(usize, String)> {
+        check(&format!(
+            r#"scilla_version 0
+library Test
+let id = tfun 'A => fun (a : 'A) => a
+contract Test()
+field result : Uint64 = Uint64 0
+field balances : Map ByStr20 Uint64 = Emp ByStr20 Uint64
+transition run ({})
+{}
+end
+"#,
+            parameters, body
+        ))
+    }
+
+    fn error(line: usize, message: &str) -> Vec<(usize, String)> {
+        vec![(line, message.to_string())]
+    }
 
     #[test]
     fn test_hello_world() {
@@ -21,6 +78,513 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_well_typed_program() {
+        assert_eq!(
+            check_transition(
+                "x : Uint64, a : ByStr20",
+                r#"  f = fun (y : Uint64) => builtin add x y;
+  g = @id Uint64;
+  z = g x;
+  r = f z;
+  balances[a] := r;
+  b <- balances[a];
+  match b with
+  | Some v =>
+    result := v
+  | None =>
+  end;
+  is_zero = builtin eq x r;
+  match is_zero with
+  | True =>
+    e = {_eventname : "Zero"; value : r};
+    event e
+  | False =>
+  end"#
+            ),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_undefined_variable() {
+        assert_eq!(
+            check_transition("x : Uint64", "  result := y"),
+            error(8, "Undefined variable `y`")
+        );
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        assert_eq!(
+            check_transition("x : Unit64", "  accept"),
+            error(7, "Unknown type `Unit64`")
+        );
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        assert_eq!(
+            check_transition("x : Uint64", "  b <- balance"),
+            error(8, "Unknown field `balance`")
+        );
+    }
+
+    #[test]
+    fn test_field_store_mismatch() {
+        assert_eq!(
+            check_transition("x : Uint64", "  s = \"hello\";\n  result := s"),
+            error(
+                9,
+                "Cannot store a value of type `String` in field `result` of type `Uint64`"
+            )
+        );
+    }
+
+    #[test]
+    fn test_field_initializer_mismatch() {
+        assert_eq!(
+            check(
+                r#"scilla_version 0
+library Test
+contract Test()
+field result : Uint64 = Uint32 0
+"#
+            ),
+            error(
+                4,
+                "Field `result` of type `Uint64` cannot be initialised with a value of type `Uint32`"
+            )
+        );
+    }
+
+    #[test]
+    fn test_builtin_argument_mismatch() {
+        assert_eq!(
+            check_transition("x : Uint64", "  a = Uint32 1;\n  r = builtin add x a"),
+            error(
+                9,
+                "No builtin `add` accepting arguments of type (Uint64, Uint32)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_map_errors() {
+        assert_eq!(
+            check_transition("x : Uint64", "  balances[x] := x"),
+            error(
+                8,
+                "Key of type `Uint64` does not match the key type `ByStr20` of `balances`"
+            )
+        );
+        assert_eq!(
+            check_transition("a : ByStr20, s : String", "  balances[a] := s"),
+            error(
+                8,
+                "Cannot store a value of type `String` in `balances` whose values are of type `Uint64`"
+            )
+        );
+        assert_eq!(
+            check_transition("a : ByStr20", "  b <- balances[a];\n  result := b"),
+            error(
+                9,
+                "Cannot store a value of type `Option Uint64` in field `result` of type `Uint64`"
+            )
+        );
+        assert_eq!(
+            check_transition("a : ByStr20", "  b <- balances[a][a]"),
+            error(
+                8,
+                "Too many keys for `balances`: values of type `Uint64` are not maps"
+            )
+        );
+    }
+
+    #[test]
+    fn test_map_entry_type_errors() {
+        let check_field = |field: &str| {
+            check(&format!(
+                "scilla_version 0\nlibrary Test\ncontract Test()\nfield m : {}\n",
+                field
+            ))
+        };
+
+        assert_eq!(
+            check_field("Map String Uint64 = Emp String Uint64"),
+            error(
+                4,
+                "Map keys of type `String` are not supported, map keys and values must be held in a single word"
+            )
+        );
+        assert_eq!(
+            check_field("Map ByStr20 (Map ByStr20 (Option Uint64)) = Emp ByStr20 (Map ByStr20 (Option Uint64))"),
+            error(
+                4,
+                "Map values of type `Option Uint64` are not supported, map keys and values must be held in a single word"
+            )
+        );
+        assert_eq!(
+            check_field("Map ByStr20 (Map Uint32 Bool) = Emp ByStr20 (Map Uint32 Bool)"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_message_entry_type_errors() {
+        assert_eq!(
+            check_transition(
+                "x : Option Uint64",
+                "  e = {_eventname : \"Set\"; value : x};\n  event e"
+            ),
+            error(
+                8,
+                "Message entry `value` of type `Option Uint64` is not supported, message entries must be integers, booleans, strings or byte strings"
+            )
+        );
+        assert_eq!(
+            check_transition(
+                "l : List Uint64",
+                "  e = {_exception : \"Failed\"; values : l};\n  throw e"
+            ),
+            error(
+                8,
+                "Message entry `values` of type `List Uint64` is not supported, message entries must be integers, booleans, strings or byte strings"
+            )
+        );
+        assert_eq!(
+            check_transition(
+                "x : Uint64, a : ByStr20, b : Bool, s : String, d : ByStr",
+                "  e = {_eventname : \"Set\"; x : x; a : a; b : b; s : s; d : d};\n  event e"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_remote_field_type_errors() {
+        assert_eq!(
+            check_transition(
+                "token : ByStr20",
+                "  n <- & token.name;\n  f = fun (s : String) => s;\n  r = f n"
+            ),
+            error(
+                8,
+                "Remote field `name` of type `String` cannot be read, only fields held in a single word can be read from other contracts"
+            )
+        );
+        assert_eq!(
+            check_transition(
+                "token : ByStr20",
+                "  b <- & token.balances;\n  balances := b"
+            ),
+            error(
+                8,
+                "Remote field `balances` of type `Map ByStr20 Uint64` cannot be read, only fields held in a single word can be read from other contracts"
+            )
+        );
+        assert_eq!(
+            check_transition(
+                "token : ByStr20",
+                "  s <- & token.total_supply;\n  result := s;\n  b <- & token._balance"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_application_errors() {
+        assert_eq!(
+            check_transition("x : Uint64", "  r = x x"),
+            error(
+                8,
+                "Value of type `Uint64` is not a function and cannot be applied"
+            )
+        );
+        assert_eq!(
+            check_transition(
+                "x : Uint64",
+                "  f = fun (y : Uint64) => y;\n  s = \"a\";\n  r = f s"
+            ),
+            error(
+                10,
+                "Argument of type `String` does not match the parameter type `Uint64`"
+            )
+        );
+    }
+
+    #[test]
+    fn test_polymorphism_errors() {
+        assert_eq!(
+            check_transition("x : Uint64", "  f = @id String;\n  r = f x"),
+            error(
+                9,
+                "Argument of type `Uint64` does not match the parameter type `String`"
+            )
+        );
+        assert_eq!(
+            check_transition("x : Uint64", "  r = id x"),
+            error(
+                8,
+                "Value of polymorphic type `forall 'A. 'A -> 'A` must be instantiated before it is applied"
+            )
+        );
+        assert_eq!(
+            check_transition("x : Uint64", "  f = @x Uint64"),
+            error(
+                8,
+                "Value of type `Uint64` is not polymorphic and cannot be instantiated"
+            )
+        );
+    }
+
+    #[test]
+    fn test_constructor_errors() {
+        assert_eq!(
+            check_transition("x : Uint64", "  o = Some"),
+            error(
+                8,
+                "Constructor `Some` expects 1 argument(s), but 0 were given"
+            )
+        );
+        assert_eq!(
+            check_transition("x : Uint64", "  o = Sone"),
+            error(8, "Unknown constructor `Sone`")
+        );
+    }
+
+    #[test]
+    fn test_pattern_mismatch() {
+        assert_eq!(
+            check_transition(
+                "x : Uint64",
+                "  match x with\n  | True =>\n    accept\n  end"
+            ),
+            error(
+                9,
+                "Pattern `True` of type `Bool` does not match `x` of type `Uint64`"
+            )
+        );
+    }
+
+    #[test]
+    fn test_send_non_message() {
+        assert_eq!(
+            check_transition("x : Uint64", "  send x"),
+            error(
+                8,
+                "Expected a value of type `List Message`, found `x` of type `Uint64`"
+            )
+        );
+    }
+
+    #[test]
+    fn test_send_requires_list_of_messages() {
+        let message = "  m = {_tag : \"\"; _recipient : _sender; _amount : x};\n";
+        assert_eq!(
+            check_transition("x : Uint128", &format!("{}  send m", message)),
+            error(
+                9,
+                "Expected a value of type `List Message`, found `m` of type `Message`"
+            )
+        );
+        assert_eq!(
+            check_transition(
+                "x : Uint128",
+                &format!(
+                    "{}  nil = Nil {{Message}};\n  ms = Cons {{Message}} m nil;\n  send ms",
+                    message
+                )
+            ),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_event_requires_message() {
+        let message = "  e = {_eventname : \"Sent\"; amount : x};\n";
+        assert_eq!(
+            check_transition("x : Uint64", "  event x"),
+            error(
+                8,
+                "Expected a value of type `Message`, found `x` of type `Uint64`"
+            )
+        );
+        assert_eq!(
+            check_transition(
+                "x : Uint64",
+                &format!(
+                    "{}  nil = Nil {{Message}};\n  es = Cons {{Message}} e nil;\n  event es",
+                    message
+                )
+            ),
+            error(
+                11,
+                "Expected a value of type `Message`, found `es` of type `List Message`"
+            )
+        );
+        assert_eq!(
+            check_transition("x : Uint64", &format!("{}  event e", message)),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_all_errors_are_reported() {
+        assert_eq!(
+            check_transition(
+                "x : Uint64",
+                "  result := y;\n  s = \"hello\";\n  result := s;\n  send x"
+            ),
+            vec![
+                (8, "Undefined variable `y`".to_string()),
+                (
+                    10,
+                    "Cannot store a value of type `String` in field `result` of type `Uint64`"
+                        .to_string()
+                ),
+                (
+                    11,
+                    "Expected a value of type `List Message`, found `x` of type `Uint64`"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compilation_fails_on_type_errors() {
+        let result = new_compiler().executable_from_script(
+            r#"scilla_version 0
+library Test
+contract Test()
+field result : Uint64 = Uint64 0
+transition run (x : Uint64)
+  result := y
+end
+"#
+            .to_string(),
+        );
+
+        match result {
+            Ok(_) => panic!("Expected ill typed program to fail"),
+            Err(err) => assert!(err.ends_with("Undefined variable `y`")),
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_report_all_type_errors() {
+        let source = r#"scilla_version 0
+library Test
+contract Test()
+field result : Uint64 = Uint64 0
+transition run (x : Uint64)
+  result := y;
+  send x
+end
+"#;
+        let diagnostics = match new_compiler().compile_with_diagnostics(source.to_string()) {
+            Ok(_) => panic!("Expected ill typed program to fail"),
+            Err(diagnostics) => diagnostics,
+        };
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics::has_errors(&diagnostics));
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code.as_deref() == Some(diagnostics::TYPE_ERROR)));
+
+        let rendered = diagnostics::render_diagnostics(&diagnostics, source);
+        assert!(rendered.contains("error[E0002]: Undefined variable `y`\n"));
+        assert!(rendered.contains("--> 6:"));
+        assert!(rendered.contains("6 |   result := y;\n"));
+        assert!(rendered.contains("--> 7:"));
+    }
+
+    #[test]
+    fn test_diagnostics_report_syntax_errors() {
+        let source = "scilla_version 0\nlibrary Test\ncontract Test(\n";
+        let diagnostics = match new_compiler().compile_with_diagnostics(source.to_string()) {
+            Ok(_) => panic!("Expected program with syntax error to fail"),
+            Err(diagnostics) => diagnostics,
+        };
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code.as_deref(),
+            Some(diagnostics::SYNTAX_ERROR)
+        );
+        assert!(diagnostics[0].message.starts_with("Syntax error"));
+    }
+
+    #[test]
+    fn test_diagnostics_report_unsupported_features_of_all_components() {
+        let source = r#"scilla_version 0
+library Test
+contract Test()
+field result : Uint64 = Uint64 0
+procedure reset ()
+  result := Uint64 0
+end
+transition run (xs : List Uint64)
+  forall xs reset
+end
+"#;
+        let diagnostics = match new_compiler().compile_with_diagnostics(source.to_string()) {
+            Ok(_) => panic!("Expected program using unsupported features to fail"),
+            Err(diagnostics) => diagnostics,
+        };
+
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Procedures are not supported",
+                "forall statements are not supported"
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code.as_deref() == Some(diagnostics::UNSUPPORTED)));
+
+        let rendered = diagnostics::render_diagnostics(&diagnostics, source);
+        assert!(rendered.contains("9 |   forall xs reset\n"));
+
+        let error = new_compiler()
+            .compile(source.to_string())
+            .err()
+            .expect("Expected program using unsupported features to fail");
+        assert_eq!(error.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_diagnostic_rendering() {
+        let source = "let x = 1\nlet y = foo bar\n";
+        let mut start = SourcePosition::start_position();
+        start.position = 18;
+        start.line = 2;
+        start.column = 8;
+        let mut end = start.clone();
+        end.position = 21;
+
+        let diagnostic = Diagnostic::error("Unknown function `foo`")
+            .with_code(diagnostics::TYPE_ERROR)
+            .with_primary(Span::new(start, end).with_label("not defined"))
+            .with_note("functions must be declared before they are used");
+
+        assert_eq!(
+            diagnostic.render(source),
+            r#"error[E0002]: Unknown function `foo`
+ --> 2:9
+  |
+2 | let y = foo bar
+  |         ^^^ not defined
+  = note: functions must be declared before they are used
+"#
+        );
+        assert_eq!(diagnostic.to_string(), "2:8: Unknown function `foo`");
+    }
+
     // TODO: Add test for stdout.
     // TODO: Work out how to test panic
     // -->   panic msg
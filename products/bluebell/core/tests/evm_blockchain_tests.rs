@@ -0,0 +1,148 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, str::FromStr};
+
+    use evm_assembly::{
+        executor::{EvmExecutor, ExecutorResult},
+        io_interface::{CustomMemoryAccount, EvmBlockInfo},
+        types::EvmTypeValue,
+    };
+    use primitive_types::{H160, H256, U256};
+    use serde_json;
+
+    use crate::common::{new_compiler, result_to_string};
+
+    fn execute(executor: &EvmExecutor, entry_point: &str, args: &str) -> ExecutorResult {
+        let arguments: Vec<EvmTypeValue> =
+            serde_json::from_str(&args).expect("Failed to deserialize arguments");
+        executor
+            .execute(&entry_point, arguments)
+            .expect("Failed to encode the arguments")
+    }
+
+    #[test]
+    fn test_read_block_information() {
+        let mut compiler = new_compiler();
+        let mut executor = compiler
+            .executable_from_script(
+                r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+field last_block : BNum = BNum 0
+field last_timestamp : Uint64 = Uint64 0
+field last_chain_id : Uint32 = Uint32 0
+transition record (dummy : Uint64)
+  blk <- & BLOCKNUMBER;
+  last_block := blk;
+  ts <- & TIMESTAMP;
+  last_timestamp := ts;
+  id <- & CHAINID;
+  last_chain_id := id
+end
+"#
+                .to_string(),
+            )
+            .expect("Failed to compile contract");
+        executor.block = EvmBlockInfo {
+            number: U256::from(42),
+            timestamp: U256::from(1700000000),
+            chain_id: U256::from(33101),
+        };
+
+        assert_eq!(
+            result_to_string(execute(&executor, "HelloWorld::record", "[0]")),
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a\n+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000001=0x00000000000000000000000000000000000000000000814d000000006553f100"
+        );
+    }
+
+    #[test]
+    fn test_read_remote_field() {
+        let mut token_compiler = new_compiler();
+        let token = token_compiler
+            .executable_from_script(
+                r#"scilla_version 0
+library Token
+contract Token()
+
+field total_supply : Uint64 = Uint64 0
+transition mint (amount : Uint64)
+  total_supply := amount
+end
+"#
+                .to_string(),
+            )
+            .expect("Failed to compile token contract");
+
+        let mut compiler = new_compiler();
+        let mut executor = compiler
+            .executable_from_script(
+                r#"scilla_version 0
+library Reader
+contract Reader()
+
+field supply : Uint64 = Uint64 0
+transition read (token : ByStr20)
+  s <- & token.total_supply;
+  supply := s
+end
+"#
+                .to_string(),
+            )
+            .expect("Failed to compile reader contract");
+
+        let mut storage = BTreeMap::new();
+        storage.insert(H256::from_low_u64_be(0), H256::from_low_u64_be(42));
+        executor.add_account(
+            H160::from_str("0x0000000000000000000000000000000000001234").unwrap(),
+            CustomMemoryAccount {
+                nonce: U256::one(),
+                balance: U256::zero(),
+                storage,
+                code: token.executable.bytecode.clone(),
+            },
+        );
+
+        assert_eq!(
+            result_to_string(execute(&executor, "Reader::read", "[4660]")),
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_read_remote_balance() {
+        let mut compiler = new_compiler();
+        let mut executor = compiler
+            .executable_from_script(
+                r#"scilla_version 0
+library Reader
+contract Reader()
+
+field balance : Uint128 = Uint128 0
+transition read (account : ByStr20)
+  b <- & account._balance;
+  balance := b
+end
+"#
+                .to_string(),
+            )
+            .expect("Failed to compile reader contract");
+
+        executor.add_account(
+            H160::from_str("0x0000000000000000000000000000000000001234").unwrap(),
+            CustomMemoryAccount {
+                nonce: U256::one(),
+                balance: U256::from(1000),
+                storage: BTreeMap::new(),
+                code: Vec::new(),
+            },
+        );
+
+        assert_eq!(
+            result_to_string(execute(&executor, "Reader::read", "[4660]")),
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x00000000000000000000000000000000000000000000000000000000000003e8"
+        );
+    }
+}
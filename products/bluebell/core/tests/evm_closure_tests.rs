@@ -0,0 +1,127 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{compile_and_execute_full_evm, result_to_string};
+
+    macro_rules! test_compile_and_execute_full_evm {
+        ($entry:expr, $args:expr, $source:expr, $expected:expr) => {
+            match compile_and_execute_full_evm($entry, $args, $source) {
+                Ok(result) => {
+                    let result_str = result_to_string(result);
+                    assert_eq!($expected.to_string(), result_str);
+                }
+                Err(err) => panic!("{}", err),
+            }
+        };
+    }
+
+    #[test]
+    fn test_apply_lambda() {
+        test_compile_and_execute_full_evm!(
+            "HelloWorld::apply",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition apply (x : Uint64)
+  f = fun (y : Uint64) => y;
+  r = f x;
+  result := r
+end
+"#,
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_lambda_capture() {
+        test_compile_and_execute_full_evm!(
+            "HelloWorld::apply",
+            "[42, 1]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition apply (x : Uint64, y : Uint64)
+  f = fun (z : Uint64) => x;
+  r = f y;
+  result := r
+end
+"#,
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_library_partial_application() {
+        test_compile_and_execute_full_evm!(
+            "HelloWorld::apply",
+            "[42, 1]",
+            r#"scilla_version 0
+library HelloWorld
+let first = fun (a : Uint64) => fun (b : Uint64) => a
+
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition apply (x : Uint64, y : Uint64)
+  f = first x;
+  r = f y;
+  result := r
+end
+"#,
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_type_application() {
+        test_compile_and_execute_full_evm!(
+            "HelloWorld::apply",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+let id = tfun 'A => fun (a : 'A) => a
+
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition apply (x : Uint64)
+  f = @id Uint64;
+  r = f x;
+  result := r
+end
+"#,
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_capture_without_type() {
+        let result = compile_and_execute_full_evm(
+            "HelloWorld::apply",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition apply (x : Uint64)
+  f = fun (a : Uint64) => let v = builtin add a a in fun (b : Uint64) => v;
+  g = f x;
+  r = g x;
+  result := r
+end
+"#,
+        );
+
+        match result {
+            Ok(_) => panic!("Expected capture of untyped variable to fail"),
+            Err(err) => assert!(err.starts_with("Unable to deduce the type of `v`")),
+        }
+    }
+}
@@ -52,7 +52,7 @@ mod tests {
             serde_json::from_str(&args).expect("Failed to deserialize arguments")
         };
 
-        let ret = executable.execute(&entry_point, arguments);
+        let ret = executable.execute(&entry_point, arguments)?;
 
         Ok(ret)
     }
@@ -83,7 +83,7 @@ transition setHello (msg : Uint64)
   welcome_msg := msg   
 end
 "#,
-            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000001337=0x000000000000000000000000000000000000000000000000000000000000002a"
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a"
         );
     }
 
@@ -133,7 +133,7 @@ transition setHello (msg : Uint64)
   end
 end
 "#,
-            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000001337=0x000000000000000000000000000000000000000000000000000000000000002a"
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a"
         );
     }
     /*
@@ -166,7 +166,7 @@ end
                 end
 
     "#,
-                "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000001337=0x000000000000000000000000000000000000000000000000000000000000002a"
+                "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000=0x000000000000000000000000000000000000000000000000000000000000002a"
             );
         }
     */
@@ -59,7 +59,7 @@ mod tests {
             serde_json::from_str(&args).expect("Failed to deserialize arguments")
         };
 
-        let ret = executable.execute(&entry_point, arguments);
+        let ret = executable.execute(&entry_point, arguments)?;
 
         Ok(ret)
     }
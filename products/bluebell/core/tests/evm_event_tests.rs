@@ -0,0 +1,129 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use evm_assembly::executor::ExecutorResult;
+
+    use crate::common::compile_and_execute_full_evm;
+
+    fn logs_to_string(ret: ExecutorResult) -> String {
+        let mut result = "".to_string();
+        for log in ret.logs {
+            result.push_str(&log.address);
+            result.push_str(" [");
+            result.push_str(&log.topics.join(", "));
+            result.push_str("] ");
+            result.push_str(&log.data);
+            result.push_str("\n");
+        }
+
+        result.trim().to_string()
+    }
+
+    macro_rules! test_compile_and_execute_logs {
+        ($entry:expr, $args:expr, $source:expr, $expected:expr) => {
+            match compile_and_execute_full_evm($entry, $args, $source) {
+                Ok(result) => {
+                    let result_str = logs_to_string(result);
+                    assert_eq!($expected.to_string(), result_str);
+                }
+                Err(err) => panic!("{}", err),
+            }
+        };
+    }
+
+    #[test]
+    fn test_event_with_argument() {
+        test_compile_and_execute_logs!(
+            "HelloWorld::mint",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition mint (amount : Uint64)
+  e = {_eventname : "Minted"; amount : amount};
+  event e
+end
+"#,
+            "0x1000000000000000000000000000000000000000 [0x7435534df129ca0086b99eec4c1fb296dddffa59ed49503b1b32983753718fb1] 0x000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_event_without_arguments() {
+        test_compile_and_execute_logs!(
+            "HelloWorld::mint",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition mint (amount : Uint64)
+  e = {_eventname : "Minted"};
+  event e
+end
+"#,
+            "0x1000000000000000000000000000000000000000 [0xe923f1ae2abf95fba64080dca4084b97e57ceddb8f45f6038172e39ac3b987be] 0x"
+        );
+    }
+
+    #[test]
+    fn test_multiple_events() {
+        test_compile_and_execute_logs!(
+            "HelloWorld::transfer",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition transfer (amount : Uint64)
+  e1 = {_eventname : "Minted"; amount : amount};
+  event e1;
+  e2 = {_eventname : "TransferSuccess"; amount : amount};
+  event e2
+end
+"#,
+            "0x1000000000000000000000000000000000000000 [0x7435534df129ca0086b99eec4c1fb296dddffa59ed49503b1b32983753718fb1] 0x000000000000000000000000000000000000000000000000000000000000002a\n0x1000000000000000000000000000000000000000 [0x3bf40d68c270c3e7e5a0dd67f9dbbf4d4accdf11c78bf3ad4602ed68e207185d] 0x000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+    #[test]
+    fn test_event_signature() {
+        // The topic is the hash of the signature and byte strings are left aligned as `bytesN`
+        test_compile_and_execute_logs!(
+            "HelloWorld::mint",
+            r#"[42, "0x12345678"]"#,
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition mint (amount : Uint64, tag : ByStr4)
+  e1 = {_eventname : "Minted"; amount : amount; minter : _sender};
+  event e1;
+  e2 = {_eventname : "Minted"; tag : tag};
+  event e2
+end
+"#,
+            "0x1000000000000000000000000000000000000000 [0x543d7f31f5e4a0f97a95465965b633fe85439080b242b608b388f251baebce98] 0x000000000000000000000000000000000000000000000000000000000000002a000000000000000000000000f000000000000000000000000000000000000000\n0x1000000000000000000000000000000000000000 [0x38602c7186ce751b2adca474d5b0a3b09ba950da88def0df48b36c0431d26b54] 0x1234567800000000000000000000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_event_with_string() {
+        // Strings are ABI encoded in the tail, following the heads of the arguments
+        test_compile_and_execute_logs!(
+            "HelloWorld::mint",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition mint (amount : Uint64)
+  e = {_eventname : "Named"; amount : amount; name : "Alice"};
+  event e
+end
+"#,
+            "0x1000000000000000000000000000000000000000 [0xeb617d9ce549d831f1d6c9d822e8e2ace1b6f07b60496959e935e02d37abbbe2] 0x000000000000000000000000000000000000000000000000000000000000002a00000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000005416c696365000000000000000000000000000000000000000000000000000000"
+        );
+    }
+}
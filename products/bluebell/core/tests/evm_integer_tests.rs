@@ -0,0 +1,278 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::support::{
+        evm::EvmCompiler,
+        modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+    };
+    use evm_assembly::{executor::ExecutorResult, types::EvmTypeValue};
+    use serde_json;
+
+    fn compile_and_execute(
+        entry_point: &str,
+        args: &str,
+        script: &str,
+    ) -> Result<ExecutorResult, String> {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        let debug = ScillaDebugBuiltins {};
+
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler.attach(&debug);
+        let executable = compiler.executable_from_script(script.to_string())?;
+
+        let arguments: Vec<EvmTypeValue> =
+            serde_json::from_str(&args).expect("Failed to deserialize arguments");
+
+        executable.execute(&entry_point, arguments)
+    }
+
+    /// Compiles a contract applying `body` to the parameters `x` and `y` of type `arg_type` and
+    /// storing `r` of type `result_type`. Returns the stored value or `Err` if execution reverted.
+    fn evaluate(
+        arg_type: &str,
+        result_type: &str,
+        body: &str,
+        args: &str,
+    ) -> Result<Option<String>, String> {
+        let initial_value = match result_type {
+            "Bool" => "False".to_string(),
+            _ => format!("{} 0", result_type),
+        };
+        let script = format!(
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field result : {result_type} = {initial_value}
+
+transition evaluate (x : {arg_type}, y : {arg_type})
+  {body};
+  result := r
+end
+"#,
+            arg_type = arg_type,
+            result_type = result_type,
+            initial_value = initial_value,
+            body = body
+        );
+
+        let ret = compile_and_execute("HelloWorld::evaluate", args, &script).unwrap();
+        match ret.revert_reason {
+            Some(reason) => Err(reason),
+            None => Ok(ret
+                .changeset
+                .get("0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000")
+                .cloned()
+                .flatten()),
+        }
+    }
+
+    fn word(value: &str) -> Result<Option<String>, String> {
+        Ok(Some(format!("0x{:0>64}", value)))
+    }
+
+    #[test]
+    fn test_unsigned_arithmetic() {
+        let add = "r = builtin add x y";
+        let sub = "r = builtin sub x y";
+        let mul = "r = builtin mul x y";
+        let div = "r = builtin div x y";
+        let rem = "r = builtin rem x y";
+        assert_eq!(evaluate("Uint64", "Uint64", add, "[40, 2]"), word("2a"));
+        assert_eq!(evaluate("Uint64", "Uint64", sub, "[44, 2]"), word("2a"));
+        assert_eq!(evaluate("Uint64", "Uint64", mul, "[21, 2]"), word("2a"));
+        assert_eq!(evaluate("Uint64", "Uint64", div, "[85, 2]"), word("2a"));
+        assert_eq!(evaluate("Uint64", "Uint64", rem, "[85, 43]"), word("2a"));
+    }
+
+    #[test]
+    fn test_unsigned_overflow() {
+        let add = "r = builtin add x y";
+        let sub = "r = builtin sub x y";
+        let mul = "r = builtin mul x y";
+        assert_eq!(evaluate("Uint8", "Uint8", add, "[200, 55]"), word("ff"));
+        assert!(evaluate("Uint8", "Uint8", add, "[200, 56]").is_err());
+        assert!(evaluate("Uint64", "Uint64", sub, "[1, 2]").is_err());
+        assert!(evaluate("Uint32", "Uint32", mul, "[65536, 65536]").is_err());
+        assert!(evaluate("Uint64", "Uint64", "r = builtin div x y", "[1, 0]").is_err());
+        assert!(evaluate("Uint64", "Uint64", "r = builtin rem x y", "[1, 0]").is_err());
+    }
+
+    #[test]
+    fn test_signed_arithmetic() {
+        let negative_five = format!("{:f>64}", "b");
+        assert_eq!(
+            evaluate("Int64", "Int64", "r = builtin sub x y", "[1, 6]"),
+            Ok(Some(format!("0x{}", negative_five)))
+        );
+
+        // (1 - 7) / 2 = -3
+        assert_eq!(
+            evaluate(
+                "Int64",
+                "Int64",
+                "n = builtin sub x y; two = Int64 2; r = builtin div n two",
+                "[1, 7]"
+            ),
+            Ok(Some(format!("0x{:f>64}", "d")))
+        );
+
+        // The remainder has the sign of the dividend: (1 - 8) % 3 = -1
+        assert_eq!(
+            evaluate(
+                "Int64",
+                "Int64",
+                "n = builtin sub x y; three = Int64 3; r = builtin rem n three",
+                "[1, 8]"
+            ),
+            Ok(Some(format!("0x{:f>64}", "f")))
+        );
+    }
+
+    #[test]
+    fn test_signed_overflow() {
+        assert_eq!(
+            evaluate("Int8", "Int8", "r = builtin add x y", "[100, 27]"),
+            word("7f")
+        );
+        assert!(evaluate("Int8", "Int8", "r = builtin add x y", "[100, 28]").is_err());
+
+        // -128 - 1 does not fit in an Int8
+        assert!(evaluate(
+            "Int8",
+            "Int8",
+            "min = Int8 -128; n = builtin sub min x; r = builtin add n y",
+            "[1, 0]"
+        )
+        .is_err());
+
+        // -128 / -1 does not fit in an Int8
+        assert!(evaluate(
+            "Int8",
+            "Int8",
+            "min = Int8 -128; minus_one = Int8 -1; n = builtin div min minus_one; r = builtin add n x",
+            "[0, 0]"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_signed_comparison() {
+        let lt = "n = builtin sub x y; zero = Int64 0; r = builtin lt n zero";
+        assert_eq!(evaluate("Int64", "Bool", lt, "[1, 2]"), word("1"));
+        assert_eq!(evaluate("Int64", "Bool", lt, "[2, 1]"), word("0"));
+
+        // Operands are compared in order
+        assert_eq!(
+            evaluate("Uint64", "Bool", "r = builtin lt x y", "[1, 2]"),
+            word("1")
+        );
+        assert_eq!(
+            evaluate("Uint64", "Bool", "r = builtin lt x y", "[2, 1]"),
+            word("0")
+        );
+        assert_eq!(
+            evaluate("Int32", "Bool", "r = builtin eq x y", "[7, 7]"),
+            word("1")
+        );
+    }
+
+    #[test]
+    fn test_pow() {
+        let pow = "n = Uint32 4; r = builtin pow x n";
+        assert_eq!(evaluate("Uint64", "Uint64", pow, "[3, 0]"), word("51"));
+        assert_eq!(
+            evaluate(
+                "Uint64",
+                "Uint64",
+                "n = Uint32 0; r = builtin pow x n",
+                "[3, 0]"
+            ),
+            word("1")
+        );
+        assert_eq!(
+            evaluate(
+                "Uint256",
+                "Uint256",
+                "n = Uint32 255; r = builtin pow x n",
+                "[2, 0]"
+            ),
+            Ok(Some(format!("0x8{:0>63}", "")))
+        );
+        assert!(evaluate(
+            "Uint256",
+            "Uint256",
+            "n = Uint32 256; r = builtin pow x n",
+            "[2, 0]"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_isqrt() {
+        let isqrt = "r = builtin isqrt x";
+        assert_eq!(
+            evaluate("Uint64", "Uint64", isqrt, "[1000000, 0]"),
+            word("3e8")
+        );
+        assert_eq!(evaluate("Uint64", "Uint64", isqrt, "[99, 0]"), word("9"));
+        assert_eq!(evaluate("Uint64", "Uint64", isqrt, "[1, 0]"), word("1"));
+        assert_eq!(evaluate("Uint64", "Uint64", isqrt, "[0, 0]"), word("0"));
+    }
+
+    #[test]
+    fn test_conversions() {
+        // `None` is mapped to a default that differs from every converted value
+        let to_uint8 = "o = builtin to_uint8 x; d = Uint8 7; \
+            r = match o with | Some v => v | None => d end";
+        assert_eq!(
+            evaluate("Uint64", "Uint8", to_uint8, "[200, 0]"),
+            word("c8")
+        );
+        assert_eq!(
+            evaluate("Uint64", "Uint8", to_uint8, "[255, 0]"),
+            word("ff")
+        );
+        assert_eq!(evaluate("Uint64", "Uint8", to_uint8, "[256, 0]"), word("7"));
+        assert_eq!(evaluate("Int64", "Uint8", to_uint8, "[0, 1]"), word("0"));
+
+        // Negative values have no unsigned representation
+        let negative_to_uint8 = "n = builtin sub x y; o = builtin to_uint8 n; d = Uint8 7; \
+            r = match o with | Some v => v | None => d end";
+        assert_eq!(
+            evaluate("Int64", "Uint8", negative_to_uint8, "[1, 2]"),
+            word("7")
+        );
+
+        let to_int8 = "o = builtin to_int8 x; d = Int8 7; \
+            r = match o with | Some v => v | None => d end";
+        assert_eq!(evaluate("Uint64", "Int8", to_int8, "[127, 0]"), word("7f"));
+        assert_eq!(evaluate("Uint64", "Int8", to_int8, "[128, 0]"), word("7"));
+
+        let negative_to_int8 = "n = builtin sub x y; o = builtin to_int8 n; d = Int8 7; \
+            r = match o with | Some v => v | None => d end";
+        assert_eq!(
+            evaluate("Int64", "Int8", negative_to_int8, "[1, 129]"),
+            Ok(Some(format!("0x{:f>62}80", "")))
+        );
+        assert_eq!(
+            evaluate("Int64", "Int8", negative_to_int8, "[1, 130]"),
+            word("7")
+        );
+
+        let to_int256 = "o = builtin to_int256 x; d = Int256 7; \
+            r = match o with | Some v => v | None => d end";
+        assert_eq!(
+            evaluate("Uint64", "Int256", to_int256, "[300, 0]"),
+            word("12c")
+        );
+
+        // `to_nat x` has depth `x`: the predecessor of `to_nat 1` is `Zero`
+        let to_nat = "o = builtin to_nat x; p = match o with | Succ q => q | Zero => o end; \
+            r = match p with | Succ q => True | Zero => False end";
+        assert_eq!(evaluate("Uint32", "Bool", to_nat, "[0, 0]"), word("0"));
+        assert_eq!(evaluate("Uint32", "Bool", to_nat, "[1, 0]"), word("0"));
+        assert_eq!(evaluate("Uint32", "Bool", to_nat, "[2, 0]"), word("1"));
+    }
+}
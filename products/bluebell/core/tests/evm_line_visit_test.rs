@@ -38,8 +38,8 @@ mod tests {
 --| end
 
 "#,
-            "0x00...1337:0x00...2c",
-            "0x00...1337:0x00...2a",
+            "0x00...00:0x00...2c",
+            "0x00...00:0x00...2a",
         );
 
         test_execution_path(
@@ -58,9 +58,9 @@ mod tests {
 --| end
 
 "#,
-            "0x00...1338:0x00...2c",
-            r#"0x00...1338:0x00...2c
-            0x00...1337:0x00...2a
+            "0x00...01:0x00...2c",
+            r#"0x00...01:0x00...2c
+            0x00...00:0x00...2a
             "#,
         );
     }
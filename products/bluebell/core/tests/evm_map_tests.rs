@@ -0,0 +1,227 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{compile_and_execute_full_evm, result_to_string};
+
+    /// Returns the value stored in the second field, which follows the map in the first.
+    fn second_field(entry_point: &str, args: &str, script: &str) -> Option<String> {
+        let ret = compile_and_execute_full_evm(entry_point, args, script).unwrap();
+        assert_eq!(ret.revert_reason, None);
+        ret.changeset
+            .get("0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000001")
+            .cloned()
+            .flatten()
+    }
+
+    fn word(value: &str) -> Option<String> {
+        Some(format!("0x{:0>64}", value))
+    }
+
+    macro_rules! test_compile_and_execute_full_evm {
+        ($entry:expr, $args:expr, $source:expr, $expected:expr) => {
+            match compile_and_execute_full_evm($entry, $args, $source) {
+                Ok(result) => {
+                    let result_str = result_to_string(result);
+                    assert_eq!($expected.to_string(), result_str);
+                }
+                Err(err) => panic!("{}", err),
+            }
+        };
+    }
+
+    #[test]
+    fn test_map_update() {
+        test_compile_and_execute_full_evm!(
+            "HelloWorld::setBalance",
+            "[1, 42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field balances : Map Uint64 Uint64 = Emp Uint64 Uint64
+
+transition setBalance (owner : Uint64, amount : Uint64)
+  balances[owner] := amount
+end
+"#,
+            "+0x1000000000000000000000000000000000000000.0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7d=0x000000000000000000000000000000000000000000000000000000000000002a\n+0x1000000000000000000000000000000000000000.0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7e=0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_nested_map_update() {
+        test_compile_and_execute_full_evm!(
+            "HelloWorld::approve",
+            "[1, 2, 42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field allowances : Map Uint64 (Map Uint64 Uint64) = Emp Uint64 (Map Uint64 Uint64)
+
+transition approve (owner : Uint64, spender : Uint64, amount : Uint64)
+  allowances[owner][spender] := amount
+end
+"#,
+            "+0x1000000000000000000000000000000000000000.0x3d9165e60100d9f4675774b585a853d57db7bfe211d44d5e9b23e54ffa400fd9=0x000000000000000000000000000000000000000000000000000000000000002a\n+0x1000000000000000000000000000000000000000.0x3d9165e60100d9f4675774b585a853d57db7bfe211d44d5e9b23e54ffa400fda=0x0000000000000000000000000000000000000000000000000000000000000001\n+0x1000000000000000000000000000000000000000.0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7e=0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_map_load() {
+        test_compile_and_execute_full_evm!(
+            "HelloWorld::setBalance",
+            "[1, 42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field balances : Map Uint64 Uint64 = Emp Uint64 Uint64
+field last_balance : Uint64 = Uint64 0
+
+transition setBalance (owner : Uint64, amount : Uint64)
+  balances[owner] := amount;
+  x <- balances[owner];
+  match x with
+  | Some v =>
+    last_balance := v
+  | None =>
+  end
+end
+"#,
+            "+0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000001=0x000000000000000000000000000000000000000000000000000000000000002a\n+0x1000000000000000000000000000000000000000.0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7d=0x000000000000000000000000000000000000000000000000000000000000002a\n+0x1000000000000000000000000000000000000000.0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7e=0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_map_delete() {
+        test_compile_and_execute_full_evm!(
+            "HelloWorld::setBalance",
+            "[1, 42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field balances : Map Uint64 Uint64 = Emp Uint64 Uint64
+
+transition setBalance (owner : Uint64, amount : Uint64)
+  balances[owner] := amount;
+  delete balances[owner]
+end
+"#,
+            "+0x1000000000000000000000000000000000000000.0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7d=0x0000000000000000000000000000000000000000000000000000000000000000\n+0x1000000000000000000000000000000000000000.0xada5013122d395ba3c54772283fb069b10426056ef8ca54750cb9bb552a59e7e=0x0000000000000000000000000000000000000000000000000000000000000002"
+        );
+    }
+
+    #[test]
+    fn test_map_load_missing_entry() {
+        let script = r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field balances : Map Uint64 Uint64 = Emp Uint64 Uint64
+field last_balance : Uint64 = Uint64 0
+
+transition getBalance (owner : Uint64, amount : Uint64)
+  x <- balances[owner];
+  match x with
+  | Some v =>
+    last_balance := v
+  | None =>
+    missing = Uint64 7;
+    last_balance := missing
+  end
+end
+"#;
+        assert_eq!(
+            second_field("HelloWorld::getBalance", "[1, 42]", script),
+            word("7")
+        );
+    }
+
+    #[test]
+    fn test_map_exists() {
+        let exists = |body: &str| {
+            let script = format!(
+                r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field balances : Map Uint64 Uint64 = Emp Uint64 Uint64
+field result : Bool = False
+
+transition run (owner : Uint64, amount : Uint64)
+  {}
+  e <- exists balances[owner];
+  result := e
+end
+"#,
+                body
+            );
+            second_field("HelloWorld::run", "[1, 42]", &script)
+        };
+
+        // Before inserting, after inserting and after deleting the entry
+        assert_eq!(exists(""), word("0"));
+        assert_eq!(exists("balances[owner] := amount;"), word("1"));
+        assert_eq!(
+            exists("balances[owner] := amount;\n  delete balances[owner];"),
+            word("0")
+        );
+    }
+
+    #[test]
+    fn test_nested_map_delete() {
+        let exists = |body: &str, query: &str| {
+            let script = format!(
+                r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field allowances : Map Uint64 (Map Uint64 Uint64) = Emp Uint64 (Map Uint64 Uint64)
+field result : Bool = False
+
+transition run (owner : Uint64, spender : Uint64, amount : Uint64)
+  allowances[owner][spender] := amount;
+  delete allowances[owner];
+  {}
+  e <- exists {};
+  result := e
+end
+"#,
+                body, query
+            );
+            second_field("HelloWorld::run", "[1, 2, 42]", &script)
+        };
+
+        // Deleting a nested map removes its entries
+        assert_eq!(exists("", "allowances[owner]"), word("0"));
+        assert_eq!(exists("", "allowances[owner][spender]"), word("0"));
+
+        // Entries of the deleted map do not reappear when the map is written to again
+        let recreate = "other = Uint64 3;\n  allowances[owner][other] := amount;";
+        assert_eq!(exists(recreate, "allowances[owner]"), word("1"));
+        assert_eq!(exists(recreate, "allowances[owner][other]"), word("1"));
+        assert_eq!(exists(recreate, "allowances[owner][spender]"), word("0"));
+    }
+
+    #[test]
+    fn test_nested_map_load_after_delete() {
+        let script = r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field allowances : Map Uint64 (Map Uint64 Uint64) = Emp Uint64 (Map Uint64 Uint64)
+field result : Uint64 = Uint64 0
+
+transition run (owner : Uint64, spender : Uint64, amount : Uint64)
+  allowances[owner][spender] := amount;
+  delete allowances[owner];
+  a <- allowances[owner][spender];
+  missing = Uint64 7;
+  r = match a with
+    | Some v => v
+    | None => missing
+    end;
+  result := r
+end
+"#;
+        assert_eq!(
+            second_field("HelloWorld::run", "[1, 2, 42]", script),
+            word("7")
+        );
+    }
+}
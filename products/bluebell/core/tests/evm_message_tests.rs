@@ -0,0 +1,247 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use evm_assembly::executor::ExecutorResult;
+
+    use crate::common::compile_and_execute_full_evm;
+
+    fn revert_reason(ret: ExecutorResult) -> String {
+        match ret.revert_reason {
+            Some(reason) => reason,
+            None => "".to_string(),
+        }
+    }
+
+    fn calls_to_string(ret: ExecutorResult) -> String {
+        let mut result = "".to_string();
+        for call in ret.calls {
+            result.push_str(&call.address);
+            result.push_str(" ");
+            result.push_str(&call.value);
+            result.push_str(" ");
+            result.push_str(&call.data);
+            result.push_str("\n");
+        }
+
+        result.trim().to_string()
+    }
+
+    macro_rules! test_compile_and_execute {
+        ($formatter:expr, $entry:expr, $args:expr, $source:expr, $expected:expr) => {
+            match compile_and_execute_full_evm($entry, $args, $source) {
+                Ok(result) => {
+                    let result_str = $formatter(result);
+                    assert_eq!($expected.to_string(), result_str);
+                }
+                Err(err) => panic!("{}", err),
+            }
+        };
+    }
+
+    #[test]
+    fn test_throw_exception() {
+        test_compile_and_execute!(
+            revert_reason,
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition withdraw (amount : Uint64)
+  e = {_exception : "InsufficientFunds"; amount : amount};
+  throw e
+end
+"#,
+            "0x6500ce9a000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_throw_exception_with_string() {
+        // The payload is the selector of the signature followed by the ABI encoded entries
+        test_compile_and_execute!(
+            revert_reason,
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition withdraw (amount : Uint64)
+  e = {_exception : "Failed"; reason : "Insufficient funds"; amount : amount};
+  throw e
+end
+"#,
+            "0x7b8ece690000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000002a0000000000000000000000000000000000000000000000000000000000000012496e73756666696369656e742066756e64730000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_throw_without_exception() {
+        test_compile_and_execute!(
+            revert_reason,
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition withdraw (amount : Uint64)
+  throw
+end
+"#,
+            "0x"
+        );
+    }
+
+    #[test]
+    fn test_throw_discards_state() {
+        test_compile_and_execute!(
+            |ret: ExecutorResult| ret.changeset.len().to_string(),
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+field balance : Uint64 = Uint64 0
+transition withdraw (amount : Uint64)
+  balance := amount;
+  throw
+end
+"#,
+            "0"
+        );
+    }
+
+    #[test]
+    fn test_send_message() {
+        // An empty tag transfers the amount without calldata
+        test_compile_and_execute!(
+            calls_to_string,
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+
+let one_msg =
+  fun (msg : Message) =>
+    let nil_msg = Nil {Message} in
+    Cons {Message} msg nil_msg
+
+contract HelloWorld()
+
+transition withdraw (amount : Uint64)
+  msg = {_tag : ""; _recipient : _sender; _amount : amount};
+  msgs = one_msg msg;
+  send msgs
+end
+"#,
+            "0xf000000000000000000000000000000000000000 42 0x"
+        );
+    }
+
+    #[test]
+    fn test_send_message_with_arguments() {
+        test_compile_and_execute!(
+            calls_to_string,
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+
+let one_msg =
+  fun (msg : Message) =>
+    let nil_msg = Nil {Message} in
+    Cons {Message} msg nil_msg
+
+contract HelloWorld()
+
+transition withdraw (amount : Uint64)
+  msg = {_tag : "InsufficientFunds"; _recipient : _sender; _amount : amount; amount : amount};
+  msgs = one_msg msg;
+  send msgs
+end
+"#,
+            "0xf000000000000000000000000000000000000000 42 0x6500ce9a000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_send_message_with_string() {
+        test_compile_and_execute!(
+            calls_to_string,
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+
+let one_msg =
+  fun (msg : Message) =>
+    let nil_msg = Nil {Message} in
+    Cons {Message} msg nil_msg
+
+contract HelloWorld()
+
+transition withdraw (amount : Uint64)
+  msg = {_tag : "Transfer"; _recipient : _sender; _amount : amount; to : _sender; memo : "Thanks"};
+  msgs = one_msg msg;
+  send msgs
+end
+"#,
+            "0xf000000000000000000000000000000000000000 42 0xf72a3472000000000000000000000000f000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000000065468616e6b730000000000000000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_send_two_messages() {
+        // Every message of the list results in a call, in the order of the list
+        test_compile_and_execute!(
+            calls_to_string,
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+
+let two_msgs =
+  fun (msg1 : Message) =>
+  fun (msg2 : Message) =>
+    let nil_msg = Nil {Message} in
+    let tail = Cons {Message} msg2 nil_msg in
+    Cons {Message} msg1 tail
+
+contract HelloWorld()
+
+transition withdraw (amount : Uint64)
+  msg1 = {_tag : ""; _recipient : _sender; _amount : amount};
+  zero = Uint64 0;
+  msg2 = {_tag : "InsufficientFunds"; _recipient : _sender; _amount : zero; amount : amount};
+  msgs = two_msgs msg1 msg2;
+  send msgs
+end
+"#,
+            "0xf000000000000000000000000000000000000000 42 0x\n0xf000000000000000000000000000000000000000 0 0x6500ce9a000000000000000000000000000000000000000000000000000000000000002a"
+        );
+    }
+
+    #[test]
+    fn test_send_empty_list() {
+        test_compile_and_execute!(
+            calls_to_string,
+            "HelloWorld::withdraw",
+            "[42]",
+            r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+
+transition withdraw (amount : Uint64)
+  msgs = Nil {Message};
+  send msgs
+end
+"#,
+            ""
+        );
+    }
+}
@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::{
+        support::strings::{bech32_to_bystr20, bystr20_to_bech32},
+        testing::create_vm_and_run_code,
+    };
+    use primitive_types::H256;
+
+    /// Runs a transition executing `body` and storing `r` in a field of type `result_type`.
+    /// Returns the stored value or `None` if execution reverted before storing it.
+    fn run(result_type: &str, body: &str) -> Option<H256> {
+        let initial_value = match result_type {
+            "Bool" => "False".to_string(),
+            _ => format!("{} 0", result_type),
+        };
+        let source = format!(
+            r#"scilla_version 0
+library Strings
+contract Strings()
+field result : {result_type} = {initial_value}
+
+transition run ()
+  {body};
+  result := r
+end
+"#,
+            result_type = result_type,
+            initial_value = initial_value,
+            body = body
+        );
+
+        let (vm, _executable) = create_vm_and_run_code("Strings::run", "", source, "");
+        vm.storage.get(&H256::from_low_u64_be(0)).copied()
+    }
+
+    fn is_true(result: Option<H256>) -> bool {
+        result == Some(H256::from_low_u64_be(1))
+    }
+
+    #[test]
+    fn test_strlen_and_concat() {
+        assert_eq!(
+            run("Uint32", r#"a = "hello"; r = builtin strlen a"#),
+            Some(H256::from_low_u64_be(5))
+        );
+        assert!(is_true(run(
+            "Bool",
+            r#"a = "hello"; b = " world"; c = builtin concat a b;
+  expected = "hello world"; r = builtin eq c expected"#
+        )));
+        assert!(!is_true(run(
+            "Bool",
+            r#"a = "hello"; b = " world"; c = builtin concat a b;
+  expected = "hello there"; r = builtin eq c expected"#
+        )));
+
+        // Strings spanning several words
+        assert!(is_true(run(
+            "Bool",
+            r#"a = "The quick brown fox jumps over"; b = " the lazy dog";
+  c = builtin concat a b;
+  expected = "The quick brown fox jumps over the lazy dog"; r = builtin eq c expected"#
+        )));
+    }
+
+    #[test]
+    fn test_substr() {
+        assert!(is_true(run(
+            "Bool",
+            r#"a = "hello world"; i = Uint32 6; n = Uint32 5; s = builtin substr a i n;
+  expected = "world"; r = builtin eq s expected"#
+        )));
+
+        // Out of bounds ranges revert
+        assert_eq!(
+            run(
+                "Bool",
+                r#"a = "hello world"; i = Uint32 7; n = Uint32 5; s = builtin substr a i n;
+  expected = "world"; r = builtin eq s expected"#
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_to_string() {
+        assert!(is_true(run(
+            "Bool",
+            r#"v = Uint64 1234; s = builtin to_string v; expected = "1234"; r = builtin eq s expected"#
+        )));
+        assert!(is_true(run(
+            "Bool",
+            r#"v = Int32 -42; s = builtin to_string v; expected = "-42"; r = builtin eq s expected"#
+        )));
+        assert!(is_true(run(
+            "Bool",
+            r#"v = Uint32 0; s = builtin to_string v; expected = "0"; r = builtin eq s expected"#
+        )));
+        assert!(is_true(run(
+            "Bool",
+            r#"v = Uint32 1234; b = builtin to_bystr4 v; s = builtin to_string b;
+  expected = "0x000004d2"; r = builtin eq s expected"#
+        )));
+    }
+
+    #[test]
+    fn test_byte_strings() {
+        // Concatenating two ByStr4 gives a ByStr8
+        assert!(is_true(run(
+            "Bool",
+            r#"v = Uint32 1234; b = builtin to_bystr4 v; c = builtin concat b b;
+  s = builtin to_string c; expected = "0x000004d2000004d2"; r = builtin eq s expected"#
+        )));
+
+        assert_eq!(
+            run(
+                "Uint32",
+                r#"v = Uint64 1234; b = builtin to_bystr8 v; c = builtin to_bystr b;
+  r = builtin strlen c"#
+            ),
+            Some(H256::from_low_u64_be(8))
+        );
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        assert!(is_true(run(
+            "Bool",
+            r#"v = Uint32 1094861636; b = builtin to_bystr4 v; s = builtin to_ascii b;
+  expected = "ABCD"; r = builtin eq s expected"#
+        )));
+
+        // Non printable characters revert
+        assert_eq!(
+            run(
+                "Bool",
+                r#"v = Uint32 1094861568; b = builtin to_bystr4 v; s = builtin to_ascii b;
+  expected = "ABC"; r = builtin eq s expected"#
+            ),
+            None
+        );
+    }
+
+    /// Reference vector from the Zilliqa address documentation.
+    const ADDRESS: &str = "7aa7ea9f4534d8d70224b9c2fb165242f321f12b";
+    const BECH32_ADDRESS: &str = "zil102n74869xnvdwq3yh8p0k9jjgtejruft268tg8";
+    /// `BECH32_ADDRESS` with the last character of its checksum changed.
+    const CORRUPTED_ADDRESS: &str = "zil102n74869xnvdwq3yh8p0k9jjgtejruft268tg9";
+
+    #[test]
+    fn test_bech32() {
+        let address: [u8; 20] = hex::decode(ADDRESS).unwrap().try_into().unwrap();
+        assert_eq!(
+            bystr20_to_bech32("zil", &address),
+            Some(BECH32_ADDRESS.to_string())
+        );
+        assert_eq!(bech32_to_bystr20("zil", BECH32_ADDRESS), Some(address));
+
+        // Wrong prefix and corrupted checksum
+        assert_eq!(bech32_to_bystr20("eth", BECH32_ADDRESS), None);
+        assert_eq!(bech32_to_bystr20("zil", CORRUPTED_ADDRESS), None);
+    }
+
+    #[test]
+    fn test_bech32_builtin() {
+        // Decoding gives the address as a ByStr20
+        assert!(is_true(run(
+            "Bool",
+            &format!(
+                r#"prefix = "zil"; a = "{}"; o = builtin bech32_to_bystr20 prefix a;
+  expected = "0x{}";
+  r = match o with
+    | Some v => let s = builtin to_string v in builtin eq s expected
+    | None => False
+    end"#,
+                BECH32_ADDRESS, ADDRESS
+            )
+        )));
+
+        // Encoding the decoded address gives back the bech32 address
+        assert!(is_true(run(
+            "Bool",
+            &format!(
+                r#"prefix = "zil"; a = "{}"; o = builtin bech32_to_bystr20 prefix a;
+  e = match o with
+    | Some v => builtin bystr20_to_bech32 prefix v
+    | None => None {{String}}
+    end;
+  r = match e with
+    | Some s => builtin eq s a
+    | None => False
+    end"#,
+                BECH32_ADDRESS
+            )
+        )));
+
+        // Invalid checksums and prefixes decode to None
+        let decode = |prefix: &str, address: &str| {
+            run(
+                "Uint32",
+                &format!(
+                    r#"prefix = "{}"; a = "{}"; o = builtin bech32_to_bystr20 prefix a;
+  some = Uint32 1; none = Uint32 2;
+  r = match o with
+    | Some v => some
+    | None => none
+    end"#,
+                    prefix, address
+                ),
+            )
+        };
+        assert_eq!(
+            decode("zil", BECH32_ADDRESS),
+            Some(H256::from_low_u64_be(1))
+        );
+        assert_eq!(
+            decode("zil", CORRUPTED_ADDRESS),
+            Some(H256::from_low_u64_be(2))
+        );
+        assert_eq!(
+            decode("eth", BECH32_ADDRESS),
+            Some(H256::from_low_u64_be(2))
+        );
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use bluebell::support::{
+        evm::EvmCompiler,
+        modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+    };
+    use evm_assembly::{executor::ExecutorResult, types::EvmTypeValue};
+    use serde_json;
+
+    const MATH_LIB: &str = r#"scilla_version 0
+library MathLib
+let first = fun (a : Uint64) => fun (b : Uint64) => a
+"#;
+
+    fn library_directory(test_name: &str, libraries: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("bluebell_{}", test_name));
+        fs::create_dir_all(&directory).expect("Failed to create library directory");
+        for (name, source) in libraries {
+            fs::write(directory.join(format!("{}.scillib", name)), source)
+                .expect("Failed to write library");
+        }
+        directory
+    }
+
+    fn compile_and_execute_with_libraries(
+        directory: PathBuf,
+        entry_point: &str,
+        args: &str,
+        script: &str,
+    ) -> Result<ExecutorResult, String> {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        let debug = ScillaDebugBuiltins {};
+
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler.attach(&debug);
+        compiler.add_library_path(directory);
+        let executable = compiler.executable_from_script(script.to_string())?;
+
+        let arguments: Vec<EvmTypeValue> = if args == "" {
+            [].to_vec()
+        } else {
+            serde_json::from_str(&args).expect("Failed to deserialize arguments")
+        };
+
+        executable.execute(&entry_point, arguments)
+    }
+
+    fn result_value(ret: ExecutorResult) -> Option<String> {
+        ret.changeset
+            .get("0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000")
+            .cloned()
+            .flatten()
+    }
+
+    #[test]
+    fn test_library_from_search_path() {
+        let directory = library_directory("search_path", &[("MathLib", MATH_LIB)]);
+        let result = compile_and_execute_with_libraries(
+            directory,
+            "HelloWorld::apply",
+            "[42, 1]",
+            r#"scilla_version 0
+import MathLib
+library HelloWorld
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition apply (x : Uint64, y : Uint64)
+  f = first x;
+  r = f y;
+  result := r
+end
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result_value(result),
+            Some("0x000000000000000000000000000000000000000000000000000000000000002a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_aliased_library_value() {
+        let directory = library_directory("aliased_value", &[("MathLib", MATH_LIB)]);
+        let result = compile_and_execute_with_libraries(
+            directory,
+            "HelloWorld::apply",
+            "[42, 1]",
+            r#"scilla_version 0
+import MathLib as Math
+library HelloWorld
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition apply (x : Uint64, y : Uint64)
+  f = Math.first x;
+  r = f y;
+  result := r
+end
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result_value(result),
+            Some("0x000000000000000000000000000000000000000000000000000000000000002a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cyclic_import() {
+        let directory = library_directory(
+            "cyclic_import",
+            &[
+                ("LibA", "scilla_version 0\nimport LibB\nlibrary LibA\n"),
+                ("LibB", "scilla_version 0\nimport LibA\nlibrary LibB\n"),
+            ],
+        );
+        let result = compile_and_execute_with_libraries(
+            directory,
+            "HelloWorld::setResult",
+            "[1]",
+            r#"scilla_version 0
+import LibA
+library HelloWorld
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition setResult (x : Uint64)
+  result := x
+end
+"#,
+        );
+
+        match result {
+            Ok(_) => panic!("Expected cyclic import to fail"),
+            Err(err) => assert_eq!(err, "Cyclic import: LibB -> LibA -> LibB"),
+        }
+    }
+
+    #[test]
+    fn test_missing_library() {
+        let directory = library_directory("missing_library", &[]);
+        let result = compile_and_execute_with_libraries(
+            directory,
+            "HelloWorld::setResult",
+            "[1]",
+            r#"scilla_version 0
+import NoSuchLib
+library HelloWorld
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition setResult (x : Uint64)
+  result := x
+end
+"#,
+        );
+
+        match result {
+            Ok(_) => panic!("Expected missing library to fail"),
+            Err(err) => assert!(err.starts_with("Unable to find library NoSuchLib")),
+        }
+    }
+}
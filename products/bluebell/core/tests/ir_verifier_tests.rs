@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::{
+        intermediate_representation::{
+            pass_manager::PassManager, primitives::IntermediateRepresentation,
+            symbol_table::SymbolTableConstructor, textual::parse_ir,
+        },
+        passes::ir_verifier::IrVerifier,
+        support::{
+            evm::EvmCompiler,
+            modules::{ScillaDefaultBuiltins, ScillaDefaultTypes},
+        },
+    };
+    use evm_assembly::compiler_context::EvmCompilerContext;
+
+    fn parse(script: &str) -> IntermediateRepresentation {
+        let symbol_table = EvmCompilerContext::new().new_symbol_table();
+        parse_ir(script, symbol_table).expect("Failed to parse IR")
+    }
+
+    #[test]
+    fn test_valid_ir() {
+        let ir = parse(
+            r#"
+fn transition @run in @Verifier (%flag{def} : $Bool) {
+  block :entry{def} {
+    %a{def} = literal $Uint64 "1"
+    jmp_if %flag :left :exit
+  }
+  block :left{def} {
+    %b{def} = call_external @builtin__add{kind = external_function} (%a, %a)
+    jmp :exit
+  }
+  block :exit{def} {
+    return %a
+  }
+}
+"#,
+        );
+        assert!(IrVerifier::new().verify(&ir).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_ir() {
+        let ir = parse(
+            r#"
+fn transition @run in @Verifier (%flag{def} : $Bool) {
+  block :entry{def} {
+    %a{def} = literal $Uint64 "1"
+    jmp_if %flag :left :exit
+  }
+  block :left{def} {
+    %a{def} = literal $Uint64 "2"
+    %b{def} = literal $Uint64 "3"
+    jmp :missing
+  }
+  block :exit{def} {
+    %c{def} = call_external @builtin__add{kind = external_function} (%a, %b)
+  }
+}
+"#,
+        );
+        let errors = IrVerifier::new()
+            .verify(&ir)
+            .err()
+            .expect("Expected invalid IR to be rejected");
+        assert!(errors.contains("run: `a` is defined 2 times"));
+        assert!(errors.contains("run: Block `left` jumps to non-existing block `missing`"));
+        assert!(errors.contains("run: Block `exit` does not end with a terminator"));
+        assert!(errors.contains(
+            "run: `b` is used in block `exit` without being defined on every path to it"
+        ));
+    }
+
+    #[test]
+    fn test_block_arguments() {
+        let ir = parse(
+            r#"
+fn transition @run in @Verifier (%x{def} : $Uint64) {
+  block :entry{def} {
+    .args x
+    .requires exit x
+    jmp :exit
+  }
+  block :exit{def} {
+    return %x
+  }
+}
+"#,
+        );
+        // Block arguments are only checked on request as they are balanced late in the pipeline
+        assert!(IrVerifier::new().verify(&ir).is_ok());
+
+        let errors = IrVerifier::new()
+            .set_check_block_arguments(true)
+            .verify(&ir)
+            .err()
+            .expect("Expected unbalanced block arguments to be rejected");
+        assert_eq!(
+            errors,
+            "run: Block `entry` passes {\"x\"} to block `exit` which expects {}"
+        );
+    }
+
+    #[test]
+    fn test_verify_each() {
+        let mut compiler = EvmCompiler::new();
+        compiler.pass_manager_mut().set_verify_each(true);
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+
+        compiler
+            .compile(
+                r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field welcome_msg : Uint64 = Uint64 0
+
+transition setHello (msg : Uint64)
+  welcome_msg := msg
+end
+"#
+                .to_string(),
+            )
+            .expect("Failed to compile with verification enabled");
+
+        assert!(PassManager::from_spec("collect-types,verify").is_ok());
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bluebell::support::{
+        evm::EvmCompiler,
+        modules::{ScillaDefaultBuiltins, ScillaDefaultTypes},
+    };
+    use evm_assembly::{
+        local_chain::LocalChain, storage_layout::StorageEntry, types::EvmTypeValue,
+    };
+    use primitive_types::{H160, H256, U256};
+
+    fn new_compiler() -> EvmCompiler {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler
+    }
+
+    /// Returns the value of the field stored in `entry` of the contract at `address`.
+    fn read(chain: &LocalChain, address: H160, entry: &StorageEntry) -> U256 {
+        let mut slot = [0u8; 32];
+        entry.slot.to_big_endian(&mut slot);
+        let word = chain.storage(address, H256::from(slot));
+        entry.decode(U256::from_big_endian(word.as_bytes()))
+    }
+
+    const COUNTER: &str = r#"scilla_version 0
+library Counter
+contract Counter(start : Uint64)
+
+field count : Uint64 = start
+
+transition increment ()
+  c <- count;
+  one = Uint64 1;
+  n = builtin add c one;
+  count := n
+end
+"#;
+
+    #[test]
+    fn test_persistent_state() {
+        let mut compiler = new_compiler();
+        let executable = compiler
+            .compile(COUNTER.to_string())
+            .expect("Failed to compile contract");
+        let count = executable.storage_layout.get("count").unwrap();
+
+        let first = H160::from_str("0x1000000000000000000000000000000000000001").unwrap();
+        let second = H160::from_str("0x1000000000000000000000000000000000000002").unwrap();
+
+        let mut chain = LocalChain::new(&compiler.context);
+        let result = chain
+            .deploy(first, &executable, [EvmTypeValue::Uint64(10)].to_vec())
+            .expect("Failed to encode the arguments");
+        assert_eq!(result.revert_reason, None);
+        let result = chain
+            .deploy(second, &executable, [EvmTypeValue::Uint64(20)].to_vec())
+            .expect("Failed to encode the arguments");
+        assert_eq!(result.revert_reason, None);
+        assert_eq!(chain.code(first), chain.code(second));
+
+        // Changes persist across calls and are kept apart per contract
+        chain
+            .execute(first, "Counter::increment", [].to_vec())
+            .expect("Failed to encode the arguments");
+        chain
+            .execute(first, "Counter::increment", [].to_vec())
+            .expect("Failed to encode the arguments");
+        chain
+            .execute(second, "Counter::increment", [].to_vec())
+            .expect("Failed to encode the arguments");
+        assert_eq!(read(&chain, first, count), U256::from(12));
+        assert_eq!(read(&chain, second, count), U256::from(21));
+    }
+
+    #[test]
+    fn test_snapshot_and_revert() {
+        let mut compiler = new_compiler();
+        let executable = compiler
+            .compile(COUNTER.to_string())
+            .expect("Failed to compile contract");
+        let count = executable.storage_layout.get("count").unwrap();
+        let address = H160::from_str("0x1000000000000000000000000000000000000001").unwrap();
+
+        let mut chain = LocalChain::new(&compiler.context);
+        chain
+            .deploy(address, &executable, [EvmTypeValue::Uint64(0)].to_vec())
+            .expect("Failed to encode the arguments");
+        let snapshot = chain.snapshot();
+
+        chain
+            .execute(address, "Counter::increment", [].to_vec())
+            .expect("Failed to encode the arguments");
+        chain.set_balance(address, U256::from(500));
+        assert_eq!(read(&chain, address, count), U256::from(1));
+
+        chain.revert(snapshot).expect("Failed to revert");
+        assert_eq!(read(&chain, address, count), U256::zero());
+        assert_eq!(chain.balance(address), U256::zero());
+        assert!(chain.revert(snapshot).is_err());
+    }
+
+    #[test]
+    fn test_caller() {
+        let mut compiler = new_compiler();
+        let executable = compiler
+            .compile(
+                r#"scilla_version 0
+library Sender
+contract Sender()
+
+field last_sender : ByStr20 = 0x0000000000000000000000000000000000000000
+
+transition record ()
+  last_sender := _sender
+end
+"#
+                .to_string(),
+            )
+            .expect("Failed to compile contract");
+        let last_sender = executable.storage_layout.get("last_sender").unwrap();
+        let address = H160::from_str("0x1000000000000000000000000000000000000001").unwrap();
+        let caller = H160::from_str("0x00000000000000000000000000000000000000ca").unwrap();
+
+        let mut chain = LocalChain::new(&compiler.context);
+        chain
+            .deploy(address, &executable, [].to_vec())
+            .expect("Failed to encode the arguments");
+        chain.set_balance(caller, U256::from(1000));
+        chain.caller = caller;
+        let result = chain
+            .execute(address, "Sender::record", [].to_vec())
+            .expect("Failed to encode the arguments");
+        assert_eq!(result.revert_reason, None);
+        assert_eq!(
+            read(&chain, address, last_sender),
+            U256::from_big_endian(caller.as_bytes())
+        );
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::testing::create_vm_and_run_code_with_optimisation;
+    use primitive_types::H256;
+
+    /// Runs `function_name` on `source` compiled with and without optimisation. Asserts that
+    /// both leave the same value in the first field and that optimisation shrinks the bytecode.
+    /// Returns the value of the first field.
+    fn run_optimised(function_name: &str, args: &str, source: &str) -> Option<H256> {
+        let (unoptimised_vm, unoptimised) = create_vm_and_run_code_with_optimisation(
+            function_name,
+            args,
+            source.to_string(),
+            "",
+            0,
+        );
+        let (optimised_vm, optimised) = create_vm_and_run_code_with_optimisation(
+            function_name,
+            args,
+            source.to_string(),
+            "",
+            2,
+        );
+
+        let key = H256::from_low_u64_be(0);
+        let result = unoptimised_vm.storage.get(&key).copied();
+        assert_eq!(optimised_vm.storage.get(&key).copied(), result);
+        assert!(
+            optimised.bytecode.len() < unoptimised.bytecode.len(),
+            "Expected optimised bytecode ({} bytes) to be smaller than unoptimised bytecode ({} bytes)",
+            optimised.bytecode.len(),
+            unoptimised.bytecode.len()
+        );
+
+        result
+    }
+
+    #[test]
+    fn test_constant_arithmetic_is_folded() {
+        let result = run_optimised(
+            "Arithmetic::run",
+            "",
+            r#"scilla_version 0
+library Arithmetic
+contract Arithmetic()
+field result : Uint64 = Uint64 0
+
+transition run ()
+  a = Uint64 40;
+  b = Uint64 2;
+  c = builtin add a b;
+  d = Uint64 3;
+  e = builtin mul c d;
+  f = builtin sub e b;
+  result := f
+end
+"#,
+        );
+        assert_eq!(result, Some(H256::from_low_u64_be(124)));
+    }
+
+    #[test]
+    fn test_overflow_is_not_folded() {
+        // The overflow must still revert at runtime, leaving the field untouched
+        let result = run_optimised(
+            "Overflow::run",
+            "",
+            r#"scilla_version 0
+library Overflow
+contract Overflow()
+field result : Uint8 = Uint8 0
+
+transition run ()
+  unused = Uint8 7;
+  a = Uint8 200;
+  b = Uint8 100;
+  c = builtin add a b;
+  result := c
+end
+"#,
+        );
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_copies_are_propagated() {
+        let result = run_optimised(
+            "Copies::run",
+            "[42]",
+            r#"scilla_version 0
+library Copies
+contract Copies()
+field result : Uint64 = Uint64 0
+
+transition run (msg : Uint64)
+  x = msg;
+  y = x;
+  z = y;
+  result := z
+end
+"#,
+        );
+        assert_eq!(result, Some(H256::from_low_u64_be(42)));
+    }
+
+    #[test]
+    fn test_empty_clauses_are_threaded() {
+        let source = r#"scilla_version 0
+library Matching
+type Bool =
+  | True
+  | False
+
+contract Matching()
+field result : Uint64 = Uint64 0
+
+transition run (msg : Uint64)
+  is_owner = True;
+  match is_owner with
+    | False =>
+    | True => result := msg
+  end
+end
+"#;
+        assert_eq!(
+            run_optimised("Matching::run", "[42]", source),
+            Some(H256::from_low_u64_be(42))
+        );
+        assert_eq!(
+            run_optimised(
+                "Matching::run",
+                "[42]",
+                &source.replace("is_owner = True", "is_owner = False")
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::{
+        intermediate_representation::pass_manager::{PassManager, DEFAULT_PIPELINE},
+        support::{
+            evm::EvmCompiler,
+            modules::{ScillaDefaultBuiltins, ScillaDefaultTypes},
+        },
+    };
+
+    const SOURCE: &str = r#"scilla_version 0
+library HelloWorld
+contract HelloWorld()
+field welcome_msg : Uint64 = Uint64 0
+
+transition setHello (msg : Uint64)
+  welcome_msg := msg
+end
+"#;
+
+    fn compile_with(pass_manager: PassManager) -> EvmCompiler {
+        let mut compiler = EvmCompiler::new();
+        *compiler.pass_manager_mut() = pass_manager;
+
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+
+        compiler
+            .compile_with_diagnostics(SOURCE.to_string())
+            .expect("Failed to compile source");
+        compiler
+    }
+
+    #[test]
+    fn test_pipeline_spec() {
+        assert_eq!(PassManager::default_pipeline().spec(), DEFAULT_PIPELINE);
+
+        let pass_manager = PassManager::from_spec(" collect-types, state-alloc ,").unwrap();
+        assert_eq!(pass_manager.spec(), "collect-types,state-alloc");
+
+        let error = PassManager::from_spec("collect-types,no-such-pass")
+            .err()
+            .expect("Expected unknown pass to be rejected");
+        assert!(error.starts_with("Unknown pass `no-such-pass`"));
+    }
+
+    #[test]
+    fn test_custom_pipeline_compiles() {
+        let mut pass_manager = PassManager::from_spec(DEFAULT_PIPELINE).unwrap();
+        pass_manager.enable_debug_printer();
+        assert_eq!(
+            pass_manager.spec(),
+            format!("{},debug-printer", DEFAULT_PIPELINE)
+        );
+
+        compile_with(pass_manager);
+    }
+
+    #[test]
+    fn test_print_after() {
+        let mut pass_manager = PassManager::default_pipeline();
+        pass_manager.set_print_after("annotate-types").unwrap();
+        assert!(pass_manager.set_print_after("no-such-pass").is_err());
+
+        let mut compiler = compile_with(pass_manager);
+        let dumps = compiler.pass_manager_mut().ir_dumps().to_vec();
+        assert_eq!(dumps.len(), 1);
+        assert_eq!(dumps[0].0, "annotate-types");
+        assert!(dumps[0].1.contains("setHello"));
+
+        let mut pass_manager = PassManager::default_pipeline();
+        pass_manager.set_print_after_all(true);
+        let mut compiler = compile_with(pass_manager);
+        let stages: Vec<String> = compiler
+            .pass_manager_mut()
+            .ir_dumps()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        assert_eq!(
+            stages.join(","),
+            format!("type-check,closure-conversion,{}", DEFAULT_PIPELINE)
+        );
+    }
+
+    #[test]
+    fn test_time_passes() {
+        // Timing is disabled by default
+        let mut compiler = compile_with(PassManager::default_pipeline());
+        assert!(compiler.pass_manager_mut().timings().is_empty());
+
+        let mut pass_manager = PassManager::default_pipeline();
+        pass_manager.set_optimisation_level(1).set_time_passes(true);
+        let mut compiler = compile_with(pass_manager);
+
+        let stages: Vec<String> = compiler
+            .pass_manager_mut()
+            .timings()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        assert_eq!(
+            stages.join(","),
+            format!(
+                "type-check,closure-conversion,constant-folding,copy-propagation,dead-code-elimination,{}",
+                DEFAULT_PIPELINE
+            )
+        );
+        assert!(compiler
+            .pass_manager_mut()
+            .timing_report()
+            .contains("total"));
+    }
+}
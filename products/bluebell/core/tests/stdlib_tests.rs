@@ -0,0 +1,409 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::support::{
+        evm::EvmCompiler,
+        modules::{ScillaDebugBuiltins, ScillaDefaultBuiltins, ScillaDefaultTypes},
+    };
+
+    fn new_compiler() -> EvmCompiler {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        let debug = ScillaDebugBuiltins {};
+
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler.attach(&debug);
+        compiler
+    }
+
+    fn compile_with_import(module: &str) -> Result<(), String> {
+        let mut compiler = new_compiler();
+
+        let script = format!(
+            r#"scilla_version 0
+import {}
+library HelloWorld
+contract HelloWorld()
+field result : Uint64 = Uint64 0
+
+transition setResult (x : Uint64)
+  result := x
+end
+"#,
+            module
+        );
+        compiler.executable_from_script(script)?;
+        Ok(())
+    }
+
+    /// Compiles a contract importing `module` whose transition `evaluate` runs `body` and
+    /// stores `r` of type `result_type`. Returns the stored value or `Err` if execution
+    /// reverted.
+    fn evaluate(module: &str, result_type: &str, body: &str) -> Result<Option<String>, String> {
+        let initial_value = match result_type {
+            "Bool" => "False".to_string(),
+            _ => format!("{} 0", result_type),
+        };
+        let script = format!(
+            r#"scilla_version 0
+import {module}
+library HelloWorld
+contract HelloWorld()
+field result : {result_type} = {initial_value}
+
+transition evaluate ()
+  {body};
+  result := r
+end
+"#,
+            module = module,
+            result_type = result_type,
+            initial_value = initial_value,
+            body = body
+        );
+
+        let mut compiler = new_compiler();
+        let executable = compiler.executable_from_script(script)?;
+        let ret = executable.execute("HelloWorld::evaluate", [].to_vec())?;
+        match ret.revert_reason {
+            Some(reason) => Err(reason),
+            None => Ok(ret
+                .changeset
+                .get("0x1000000000000000000000000000000000000000.0x0000000000000000000000000000000000000000000000000000000000000000")
+                .cloned()
+                .flatten()),
+        }
+    }
+
+    fn word(value: &str) -> Result<Option<String>, String> {
+        Ok(Some(format!("0x{:0>64}", value)))
+    }
+
+    /// Statements binding `l` to the list `[1, 2, 3]` of `Uint32`.
+    const LIST: &str = "nil = Nil {Uint32}; \
+        one = Uint32 1; two = Uint32 2; three = Uint32 3; \
+        l3 = Cons {Uint32} three nil; l2 = Cons {Uint32} two l3; l = Cons {Uint32} one l2";
+
+    /// Evaluates `body`, which binds the list `m` of `Uint32`, returning the head of `m` or 0.
+    fn list_head(body: &str) -> Result<Option<String>, String> {
+        let body = format!(
+            "{}; {}; head = @list_head Uint32; h = head m; \
+             r = match h with | Some v => v | None => Uint32 0 end",
+            LIST, body
+        );
+        evaluate("ListUtils", "Uint32", &body)
+    }
+
+    /// Evaluates `body`, which binds the list `m` of `Uint32`, returning the length of `m`.
+    fn list_length(body: &str) -> Result<Option<String>, String> {
+        let body = format!(
+            "{}; {}; length = @list_length Uint32; r = length m",
+            LIST, body
+        );
+        evaluate("ListUtils", "Uint32", &body)
+    }
+
+    /// Evaluates `body`, which binds `r` of type `Bool`, with the list `l` and the predicates
+    /// `is_two` and `is_positive` in scope.
+    fn list_predicate(body: &str) -> Result<Option<String>, String> {
+        let body = format!(
+            "{}; zero = Uint32 0; \
+             is_two = fun (x : Uint32) => builtin eq x two; \
+             is_positive = fun (x : Uint32) => builtin lt zero x; {}",
+            LIST, body
+        );
+        evaluate("ListUtils", "Bool", &body)
+    }
+
+    #[test]
+    fn test_import_bool_utils() {
+        compile_with_import("BoolUtils").unwrap();
+    }
+
+    #[test]
+    fn test_import_int_utils() {
+        compile_with_import("IntUtils").unwrap();
+    }
+
+    #[test]
+    fn test_import_list_utils() {
+        compile_with_import("ListUtils").unwrap();
+    }
+
+    #[test]
+    fn test_import_nat_utils() {
+        compile_with_import("NatUtils").unwrap();
+    }
+
+    #[test]
+    fn test_import_pair_utils() {
+        compile_with_import("PairUtils").unwrap();
+    }
+
+    #[test]
+    fn test_import_unknown_module() {
+        assert!(compile_with_import("NoSuchUtils").is_err());
+    }
+
+    #[test]
+    fn test_list_length() {
+        let body = format!("{}; length = @list_length Uint32; r = length l", LIST);
+        assert_eq!(evaluate("ListUtils", "Uint32", &body), word("3"));
+
+        let empty = "l = Nil {Uint32}; length = @list_length Uint32; r = length l";
+        assert_eq!(evaluate("ListUtils", "Uint32", empty), word("0"));
+    }
+
+    #[test]
+    fn test_list_map() {
+        // Mapping x -> x + 1 over [1, 2, 3] preserves the order of the elements
+        let body = format!(
+            "{}; inc = fun (x : Uint32) => builtin add x one; \
+             map = @list_map Uint32 Uint32; m = map inc l; \
+             head = @list_head Uint32; h = head m; \
+             r = match h with | Some v => v | None => Uint32 0 end",
+            LIST
+        );
+        assert_eq!(evaluate("ListUtils", "Uint32", &body), word("2"));
+
+        let body = format!(
+            "{}; inc = fun (x : Uint32) => builtin add x one; \
+             map = @list_map Uint32 Uint32; m = map inc l; \
+             length = @list_length Uint32; r = length m",
+            LIST
+        );
+        assert_eq!(evaluate("ListUtils", "Uint32", &body), word("3"));
+    }
+
+    #[test]
+    fn test_andb() {
+        let cases = [
+            ("True", "True", "1"),
+            ("True", "False", "0"),
+            ("False", "True", "0"),
+            ("False", "False", "0"),
+        ];
+        for (b, c, expected) in cases {
+            let body = format!("b = {}; c = {}; r = andb b c", b, c);
+            assert_eq!(evaluate("BoolUtils", "Bool", &body), word(expected));
+        }
+    }
+
+    #[test]
+    fn test_uint128_le() {
+        let cases = [("1", "2", "1"), ("2", "2", "1"), ("3", "2", "0")];
+        for (a, b, expected) in cases {
+            let body = format!("a = Uint128 {}; b = Uint128 {}; r = uint128_le a b", a, b);
+            assert_eq!(evaluate("IntUtils", "Bool", &body), word(expected));
+        }
+    }
+
+    #[test]
+    fn test_nat_to_int() {
+        let body = "zero = Zero; n1 = Succ zero; n2 = Succ n1; n3 = Succ n2; r = nat_to_int n3";
+        assert_eq!(evaluate("NatUtils", "Uint32", body), word("3"));
+
+        let body = "n = Zero; r = nat_to_int n";
+        assert_eq!(evaluate("NatUtils", "Uint32", body), word("0"));
+    }
+    #[test]
+    fn test_list_head_tail() {
+        let body = "tail = @list_tail Uint32; t = tail l; \
+            m = match t with | Some x => x | None => nil end";
+        assert_eq!(list_head(body), word("2"));
+
+        let body = "tail = @list_tail Uint32; t = tail nil; \
+            m = match t with | Some x => x | None => l end";
+        assert_eq!(list_head(body), word("1"));
+    }
+
+    #[test]
+    fn test_list_filter() {
+        let filter = "is_large = fun (x : Uint32) => builtin lt one x; \
+            filter = @list_filter Uint32; m = filter is_large l";
+        assert_eq!(list_head(filter), word("2"));
+        assert_eq!(list_length(filter), word("2"));
+    }
+
+    #[test]
+    fn test_list_append() {
+        let append = "append = @list_append Uint32; m = append l3 l";
+        assert_eq!(list_head(append), word("3"));
+        assert_eq!(list_length(append), word("4"));
+    }
+
+    #[test]
+    fn test_list_reverse() {
+        let reverse = "reverse = @list_reverse Uint32; m = reverse l";
+        assert_eq!(list_head(reverse), word("3"));
+        assert_eq!(list_length(reverse), word("3"));
+    }
+
+    #[test]
+    fn test_list_flatten() {
+        let flatten = "nil_list = Nil {(List Uint32)}; \
+            ll2 = Cons {(List Uint32)} l nil_list; ll = Cons {(List Uint32)} l3 ll2; \
+            flatten = @list_flatten Uint32; m = flatten ll";
+        assert_eq!(list_head(flatten), word("3"));
+        assert_eq!(list_length(flatten), word("4"));
+    }
+
+    #[test]
+    fn test_list_exists() {
+        let body = "any = @list_exists Uint32; r = any is_two l";
+        assert_eq!(list_predicate(body), word("1"));
+
+        let body = "any = @list_exists Uint32; r = any is_two l3";
+        assert_eq!(list_predicate(body), word("0"));
+    }
+
+    #[test]
+    fn test_list_forall() {
+        let body = "all = @list_forall Uint32; r = all is_positive l";
+        assert_eq!(list_predicate(body), word("1"));
+
+        let body = "all = @list_forall Uint32; r = all is_two l";
+        assert_eq!(list_predicate(body), word("0"));
+    }
+
+    #[test]
+    fn test_list_mem() {
+        let equal = "equal = fun (a : Uint32) => fun (b : Uint32) => builtin eq a b; \
+            mem = @list_mem Uint32";
+        let body = format!("{}; r = mem equal three l", equal);
+        assert_eq!(list_predicate(&body), word("1"));
+
+        let body = format!("{}; r = mem equal one l3", equal);
+        assert_eq!(list_predicate(&body), word("0"));
+    }
+
+    #[test]
+    fn test_list_find() {
+        // The first element satisfying the predicate is found
+        let find = "find = @list_find Uint32; m = find is_positive l; \
+            r = match m with | Some v => v | None => Uint32 0 end";
+        let body = format!(
+            "{}; zero = Uint32 0; is_positive = fun (x : Uint32) => builtin lt zero x; {}",
+            LIST, find
+        );
+        assert_eq!(evaluate("ListUtils", "Uint32", &body), word("1"));
+
+        let body = format!(
+            "{}; zero = Uint32 0; is_positive = fun (x : Uint32) => builtin lt x zero; {}",
+            LIST, find
+        );
+        assert_eq!(evaluate("ListUtils", "Uint32", &body), word("0"));
+    }
+
+    #[test]
+    fn test_orb() {
+        let cases = [
+            ("True", "True", "1"),
+            ("True", "False", "1"),
+            ("False", "True", "1"),
+            ("False", "False", "0"),
+        ];
+        for (b, c, expected) in cases {
+            let body = format!("b = {}; c = {}; r = orb b c", b, c);
+            assert_eq!(evaluate("BoolUtils", "Bool", &body), word(expected));
+        }
+    }
+
+    #[test]
+    fn test_negb() {
+        for (b, expected) in [("True", "0"), ("False", "1")] {
+            let body = format!("b = {}; r = negb b", b);
+            assert_eq!(evaluate("BoolUtils", "Bool", &body), word(expected));
+        }
+    }
+
+    #[test]
+    fn test_bool_to_string() {
+        for b in ["True", "False"] {
+            let body = format!(
+                "b = {}; s = bool_to_string b; t = \"{}\"; r = builtin eq s t",
+                b, b
+            );
+            assert_eq!(evaluate("BoolUtils", "Bool", &body), word("1"));
+        }
+    }
+
+    #[test]
+    fn test_fst_snd() {
+        let pair = "a = Uint32 1; b = Uint32 2; p = Pair {Uint32 Uint32} a b";
+        let body = format!("{}; f = @fst Uint32 Uint32; r = f p", pair);
+        assert_eq!(evaluate("PairUtils", "Uint32", &body), word("1"));
+
+        let body = format!("{}; s = @snd Uint32 Uint32; r = s p", pair);
+        assert_eq!(evaluate("PairUtils", "Uint32", &body), word("2"));
+    }
+
+    #[test]
+    fn test_nat_prev() {
+        let body = "zero = Zero; n1 = Succ zero; n2 = Succ n1; p = nat_prev n2; \
+            m = match p with | Some x => x | None => zero end; r = nat_to_int m";
+        assert_eq!(evaluate("NatUtils", "Uint32", body), word("1"));
+
+        let body = "zero = Zero; n = Succ zero; p = nat_prev zero; \
+            m = match p with | Some x => x | None => n end; r = nat_to_int m";
+        assert_eq!(evaluate("NatUtils", "Uint32", body), word("1"));
+    }
+
+    #[test]
+    fn test_is_some_zero() {
+        let cases = [
+            ("n = Zero; o = Some {Nat} n", "1"),
+            ("zero = Zero; n = Succ zero; o = Some {Nat} n", "0"),
+            ("o = None {Nat}", "0"),
+        ];
+        for (value, expected) in cases {
+            let body = format!("{}; r = is_some_zero o", value);
+            assert_eq!(evaluate("NatUtils", "Bool", &body), word(expected));
+        }
+    }
+
+    #[test]
+    fn test_uint32_to_nat() {
+        let body = "n = Uint32 3; m = uint32_to_nat n; r = nat_to_int m";
+        assert_eq!(evaluate("NatUtils", "Uint32", body), word("3"));
+    }
+
+    #[test]
+    fn test_int_utils() {
+        // Expected results of `neq`, `le`, `gt` and `ge` applied to `a` and `b`
+        let cases = [
+            ("1", "2", ["1", "1", "0", "0"]),
+            ("2", "2", ["0", "1", "0", "1"]),
+            ("3", "2", ["1", "0", "1", "1"]),
+        ];
+        let negative_case = ("-1", "2", ["1", "1", "0", "0"]);
+
+        for typename in [
+            "Int32", "Int64", "Int128", "Int256", "Uint32", "Uint64", "Uint128", "Uint256",
+        ] {
+            let signed = typename.starts_with("Int");
+            for (a, b, expected) in cases
+                .iter()
+                .chain(signed.then_some(&negative_case).into_iter())
+            {
+                for (function, expected) in ["neq", "le", "gt", "ge"].iter().zip(expected) {
+                    let body = format!(
+                        "a = {typename} {a}; b = {typename} {b}; r = {prefix}_{function} a b",
+                        typename = typename,
+                        a = a,
+                        b = b,
+                        prefix = typename.to_lowercase(),
+                        function = function
+                    );
+                    assert_eq!(
+                        evaluate("IntUtils", "Bool", &body),
+                        word(expected),
+                        "{}",
+                        body
+                    );
+                }
+            }
+        }
+    }
+}
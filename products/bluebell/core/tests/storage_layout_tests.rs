@@ -0,0 +1,201 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::support::{
+        evm::EvmCompiler,
+        modules::{ScillaDefaultBuiltins, ScillaDefaultTypes},
+    };
+    use evm_assembly::{
+        executor::ExecutorResult,
+        storage_layout::{derived_slot, StorageEncoding, StorageLayout},
+    };
+    use primitive_types::U256;
+
+    fn new_compiler() -> EvmCompiler {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler
+    }
+
+    /// Returns the value stored in `slot` of the contract after execution, if it changed.
+    fn stored(result: &ExecutorResult, slot: U256) -> Option<String> {
+        let mut bytes = [0u8; 32];
+        slot.to_big_endian(&mut bytes);
+        let key = format!(
+            "0x1000000000000000000000000000000000000000.0x{}",
+            hex::encode(bytes)
+        );
+        result.changeset.get(&key).cloned().flatten()
+    }
+
+    const LAYOUT: &str = r#"scilla_version 0
+library Layout
+contract Layout()
+field total : Uint256 = Uint256 0
+field count : Uint64 = Uint64 0
+field flags : Uint32 = Uint32 0
+field name : String = ""
+field owner : Option Uint64 = None {Uint64}
+field balances : Map Uint64 Uint64 = Emp Uint64 Uint64
+field last : Uint32 = Uint32 0
+
+transition record (c : Uint64, f : Uint32)
+  count := c;
+  flags := f
+end
+"#;
+
+    #[test]
+    fn test_manifest() {
+        let mut compiler = new_compiler();
+        let executable = compiler
+            .compile(LAYOUT.to_string())
+            .expect("Failed to compile contract");
+        let layout = &executable.storage_layout;
+
+        let expected = [
+            ("total", 0, 0, 32, StorageEncoding::Inplace),
+            ("count", 1, 0, 8, StorageEncoding::Inplace),
+            ("flags", 1, 8, 4, StorageEncoding::Inplace),
+            ("name", 2, 0, 32, StorageEncoding::Bytes),
+            ("owner", 3, 0, 32, StorageEncoding::Adt),
+            ("balances", 4, 0, 32, StorageEncoding::Mapping),
+            // Alone in its slot, hence occupying all of it
+            ("last", 5, 0, 32, StorageEncoding::Inplace),
+        ];
+        assert_eq!(layout.storage.len(), expected.len());
+        for (label, slot, offset, size, encoding) in expected {
+            let entry = layout.get(label).expect("Missing field in manifest");
+            assert_eq!(entry.slot, U256::from(slot), "slot of {}", label);
+            assert_eq!(entry.offset, offset, "offset of {}", label);
+            assert_eq!(entry.size, size, "size of {}", label);
+            assert_eq!(entry.encoding, encoding, "encoding of {}", label);
+        }
+        assert_eq!(
+            layout.get("name").unwrap().data_slot(),
+            derived_slot(U256::from(2))
+        );
+        assert!(layout.immutables.is_empty());
+
+        let json = layout.to_json().expect("Failed to serialize manifest");
+        assert!(json.contains("\"type\": \"Uint64\""));
+        assert!(json.contains(
+            "\"slot\": \"0x0000000000000000000000000000000000000000000000000000000000000001\""
+        ));
+        assert_eq!(&StorageLayout::from_json(&json).unwrap(), layout);
+
+        // Compiling again starts over from the first slot
+        let executable = compiler
+            .compile(LAYOUT.to_string())
+            .expect("Failed to compile contract");
+        assert_eq!(&executable.storage_layout, layout);
+    }
+
+    #[test]
+    fn test_packed_fields() {
+        let executor = new_compiler()
+            .executable_from_script(LAYOUT.to_string())
+            .expect("Failed to compile contract");
+        let result = executor
+            .execute("Layout::record", serde_json::from_str("[1000, 7]").unwrap())
+            .expect("Failed to encode the arguments");
+
+        let word = stored(&result, U256::from(1)).expect("Packed slot was not written");
+        assert_eq!(
+            word,
+            "0x00000000000000000000000000000000000000000000000700000000000003e8"
+        );
+
+        let word = U256::from_str_radix(&word[2..], 16).unwrap();
+        let layout = &executor.executable.storage_layout;
+        assert_eq!(layout.get("count").unwrap().decode(word), U256::from(1000));
+        assert_eq!(layout.get("flags").unwrap().decode(word), U256::from(7));
+    }
+
+    #[test]
+    fn test_string_field() {
+        let executor = new_compiler()
+            .executable_from_script(
+                r#"scilla_version 0
+library Strings
+contract Strings()
+field name : String = ""
+field ok : Bool = False
+
+transition run ()
+  a = "hello";
+  name := a;
+  b <- name;
+  expected = "hello";
+  r = builtin eq b expected;
+  ok := r
+end
+"#
+                .to_string(),
+            )
+            .expect("Failed to compile contract");
+        let result = executor
+            .execute("Strings::run", [].to_vec())
+            .expect("Failed to encode the arguments");
+
+        // The length followed by the data, stored from the derived slot
+        assert_eq!(
+            stored(&result, derived_slot(U256::from(0))),
+            Some("0x0000000568656c6c6f0000000000000000000000000000000000000000000000".to_string())
+        );
+        assert_eq!(
+            stored(&result, U256::from(1)),
+            Some("0x0000000000000000000000000000000000000000000000000000000000000001".to_string())
+        );
+    }
+
+    const PARAMETERS: &str = r#"scilla_version 0
+library Token
+contract Token(owner : Uint64)
+field last_owner : Uint64 = Uint64 0
+
+transition readOwner ()
+  last_owner := owner
+end
+"#;
+
+    #[test]
+    fn test_contract_parameters() {
+        let mut executor = new_compiler()
+            .executable_from_script(PARAMETERS.to_string())
+            .expect("Failed to compile contract");
+
+        let layout = executor.executable.storage_layout.clone();
+        assert_eq!(layout.immutables.len(), 1);
+        let immutable = layout.get_immutable("owner").expect("Missing parameter");
+        assert_eq!(immutable.typename, "Uint64");
+        assert_eq!(
+            immutable.offset as usize,
+            executor.executable.bytecode.len() - 32
+        );
+        assert!(layout.get("owner").is_none());
+
+        // Setting the parameter as it would be when deployed
+        let offset = immutable.offset as usize;
+        executor.executable.bytecode[offset + 31] = 42;
+
+        let result = executor
+            .execute("Token::readOwner", [].to_vec())
+            .expect("Failed to encode the arguments");
+        assert_eq!(
+            stored(&result, U256::from(0)),
+            Some("0x000000000000000000000000000000000000000000000000000000000000002a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_assigning_contract_parameter() {
+        let error = new_compiler()
+            .compile(PARAMETERS.replace("last_owner := owner", "owner := last_owner"))
+            .err()
+            .expect("Expected assignment to a parameter to be rejected");
+        assert!(error.contains("Contract parameter `owner` cannot be assigned to"));
+    }
+}
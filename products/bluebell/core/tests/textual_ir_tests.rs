@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use bluebell::{
+        intermediate_representation::{
+            symbol_table::SymbolTableConstructor,
+            textual::{parse_ir, print_ir},
+        },
+        support::{
+            evm::EvmCompiler,
+            modules::{ScillaDefaultBuiltins, ScillaDefaultTypes},
+        },
+        testing::run_passes_on_ir,
+    };
+    use evm_assembly::compiler_context::EvmCompilerContext;
+
+    const SOURCE: &str = r#"scilla_version 0
+library Wallet
+type Bool =
+  | True
+  | False
+
+contract Wallet()
+field balances : Map Uint64 Uint64 = Emp Uint64 Uint64
+
+transition deposit (owner : Uint64, amount : Uint64)
+  is_owner = True;
+  match is_owner with
+    | False =>
+    | True => balances[owner] := amount
+  end
+end
+"#;
+
+    fn create_compiler() -> EvmCompiler {
+        let mut compiler = EvmCompiler::new();
+        let default_types = ScillaDefaultTypes {};
+        let default_builtins = ScillaDefaultBuiltins {};
+        compiler.attach(&default_types);
+        compiler.attach(&default_builtins);
+        compiler
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let printed = create_compiler()
+            .emit_ir(SOURCE.to_string())
+            .expect("Failed to emit IR");
+        assert!(printed.contains("fn transition @"));
+
+        let symbol_table = EvmCompilerContext::new().new_symbol_table();
+        let ir = parse_ir(&printed, symbol_table).expect("Failed to parse IR");
+        assert_eq!(print_ir(&ir), printed);
+    }
+
+    #[test]
+    fn test_compile_from_ir() {
+        let ir = create_compiler()
+            .emit_ir(SOURCE.to_string())
+            .expect("Failed to emit IR");
+
+        let from_source = create_compiler()
+            .compile(SOURCE.to_string())
+            .expect("Failed to compile source");
+        let from_ir = create_compiler()
+            .compile_ir(&ir)
+            .expect("Failed to compile IR");
+        assert_eq!(from_ir.bytecode, from_source.bytecode);
+    }
+
+    #[test]
+    fn test_constant_folding_in_isolation() {
+        let input = r#"
+; Folding stops at the overflowing addition
+fn transition @run in @Arithmetic () {
+  block :entry{def} {
+    %a{def} = literal $Uint8 "40"
+    %b{def} = literal $Uint8 "2"
+    %c{def} = call_external @builtin__add{kind = external_function} (%a, %b)
+    %d{def} = literal $Uint8 "250"
+    %e{def} = call_external @builtin__add{kind = external_function} (%c, %d)
+    return
+  }
+}
+"#;
+        let output = run_passes_on_ir(input, "constant-folding").expect("Failed to run pass");
+        assert!(output.contains("    %c{def} = literal $Uint8 \"42\"\n"));
+        assert!(output.contains(
+            "    %e{def} = call_external @builtin__add{kind = external_function} (%c, %d)\n"
+        ));
+    }
+
+    #[test]
+    fn test_jump_threading_in_isolation() {
+        let input = r#"
+fn transition @run in @Threading (%flag{def} : $Bool) {
+  block :entry{def} {
+    jmp_if %flag :forward :exit
+  }
+  block :forward{def} {
+    jmp :exit
+  }
+  block :exit{def} {
+    return
+  }
+}
+"#;
+        let output = run_passes_on_ir(input, "jump-threading").expect("Failed to run pass");
+        // The forwarding block is left for dead code elimination to remove
+        assert_eq!(
+            &output[output.find("fn ").unwrap()..],
+            r#"fn transition @run in @Threading (%flag{def} : $Bool) {
+  block :entry{def} {
+    jmp :exit
+  }
+  block :forward{def} {
+    jmp :exit
+  }
+  block :exit{def} {
+    return
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let symbol_table = EvmCompilerContext::new().new_symbol_table();
+        let error = parse_ir(
+            "fn transition @run in @Errors () {\n  block :entry {\n    frobnicate %x\n  }\n}\n",
+            symbol_table.clone(),
+        )
+        .err()
+        .expect("Expected unknown operation to be rejected");
+        assert_eq!(error, "Line 3: Unknown operation `frobnicate`");
+
+        let error = parse_ir("fn transition @run in @Errors (", symbol_table)
+            .err()
+            .expect("Expected truncated input to be rejected");
+        assert!(error.contains("end of input"));
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::types::EvmType;
+
+/// Parameter or return value of an entry in the contract ABI.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AbiParameter {
+    pub name: String,
+    /// Type as it appears in the ABI, where tuples are named `tuple` and described by their
+    /// components.
+    #[serde(rename = "type")]
+    pub typename: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<AbiParameter>>,
+}
+
+impl AbiParameter {
+    pub fn new(name: &str, evm_type: &EvmType) -> Self {
+        let (typename, components) = Self::describe(evm_type);
+        Self {
+            name: name.to_string(),
+            typename,
+            components,
+        }
+    }
+
+    fn describe(evm_type: &EvmType) -> (String, Option<Vec<AbiParameter>>) {
+        if let EvmType::Array(element) = evm_type {
+            let (typename, components) = Self::describe(element);
+            return (format!("{}[]", typename), components);
+        }
+
+        match evm_type.components() {
+            Some(components) => (
+                "tuple".to_string(),
+                Some(
+                    components
+                        .iter()
+                        .map(|(name, component)| Self::new(name, component))
+                        .collect(),
+                ),
+            ),
+            None => (evm_type.signature(), None),
+        }
+    }
+
+    /// Returns the type as it appears in function signatures, spelling out tuples.
+    pub fn canonical_type(&self) -> String {
+        match &self.components {
+            Some(components) => format!(
+                "({}){}",
+                components
+                    .iter()
+                    .map(|component| component.canonical_type())
+                    .collect::<Vec<String>>()
+                    .join(","),
+                self.typename.trim_start_matches("tuple")
+            ),
+            None => self.typename.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiEntryKind {
+    Function,
+    Constructor,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateMutability {
+    Pure,
+    View,
+    Nonpayable,
+    Payable,
+}
+
+/// Function or constructor in the contract ABI.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbiEntry {
+    #[serde(rename = "type")]
+    pub kind: AbiEntryKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub inputs: Vec<AbiParameter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<AbiParameter>>,
+    pub state_mutability: StateMutability,
+}
+
+impl AbiEntry {
+    pub fn function(
+        name: &str,
+        inputs: Vec<AbiParameter>,
+        outputs: Vec<AbiParameter>,
+        state_mutability: StateMutability,
+    ) -> Self {
+        Self {
+            kind: AbiEntryKind::Function,
+            name: Some(name.to_string()),
+            inputs,
+            outputs: Some(outputs),
+            state_mutability,
+        }
+    }
+
+    pub fn constructor(inputs: Vec<AbiParameter>) -> Self {
+        Self {
+            kind: AbiEntryKind::Constructor,
+            name: None,
+            inputs,
+            outputs: None,
+            state_mutability: StateMutability::Nonpayable,
+        }
+    }
+
+    pub fn signature(&self) -> String {
+        format!(
+            "{}({})",
+            self.name.as_deref().unwrap_or(""),
+            self.inputs
+                .iter()
+                .map(|input| input.canonical_type())
+                .collect::<Vec<String>>()
+                .join(",")
+        )
+    }
+
+    pub fn selector(&self) -> Vec<u8> {
+        Keccak256::digest(self.signature())[..4].to_vec()
+    }
+}
+
+/// Solidity compatible description of the interface of a contract.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Abi {
+    pub entries: Vec<AbiEntry>,
+}
+
+impl Abi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn function(&self, name: &str) -> Option<&AbiEntry> {
+        self.entries.iter().find(|entry| {
+            entry.kind == AbiEntryKind::Function && entry.name.as_deref() == Some(name)
+        })
+    }
+
+    pub fn constructor(&self) -> Option<&AbiEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.kind == AbiEntryKind::Constructor)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+}
@@ -10,7 +10,9 @@ use primitive_types::U256;
 use crate::{
     function_signature::EvmFunctionSignature,
     instruction::{EvmInstruction, EvmSourcePosition, RustPosition},
-    opcode_spec::{OpcodeSpec, OpcodeSpecification},
+    opcode_spec::{
+        OpcodeSpec, OpcodeSpecification, BLOBBASEFEE, BLOBHASH, MCOPY, PUSH0, TLOAD, TSTORE,
+    },
     types::{EvmType, EvmTypeValue},
 };
 
@@ -37,6 +39,19 @@ impl Scope {
         }
     }
 
+    /// Returns the scope of a block entered with the named arguments on the stack. As the
+    /// names are distinct, registering them cannot fail.
+    pub fn with_arguments(arg_names: &BTreeSet<String>) -> Self {
+        let mut ret = Self::empty(arg_names.len() as i32);
+        for (i, name) in arg_names.iter().enumerate() {
+            ret.name_location.insert(name.to_string(), i as i32);
+            ret.location_name.insert(i as i32, name.to_string());
+        }
+        ret.stack_counter = arg_names.len() as i32;
+
+        ret
+    }
+
     pub fn new(parent: Scope, arg_count: i32) -> Self {
         let mut ret = parent.clone();
         ret.entry_stack_counter = ret.stack_counter + arg_count;
@@ -142,38 +157,34 @@ impl Scope {
     }
 
     fn swap(&mut self, depth: i32) {
-        let position = self.stack_counter - depth;
-
-        let name_at_position: Option<String> = match self.location_name.get(&position) {
-            Some(n) => Some(n.clone()),
-            None => None,
-        };
-
-        let name_at_zero: Option<String> = match self.location_name.get(&self.stack_counter) {
-            Some(n) => Some(n.clone()),
-            None => None,
-        };
-
-        if let Some(name_at_position) = &name_at_position {
-            self.location_name.remove(&position);
-            self.name_location.remove(name_at_position);
+        let top = self.stack_counter - 1;
+        let position = top - depth;
+
+        // Aliases share the location of the value they refer to and move along with it
+        for location in self.name_location.values_mut() {
+            if *location == top {
+                *location = position;
+            } else if *location == position {
+                *location = top;
+            }
         }
 
-        if let Some(name_at_zero) = name_at_zero {
-            self.location_name.remove(&self.stack_counter);
-            self.name_location.remove(&name_at_zero);
-
-            self.name_location
-                .insert(name_at_zero.to_string(), position);
-            self.location_name
-                .insert(position, name_at_zero.to_string());
+        let name_at_top = self.location_name.remove(&top);
+        let name_at_position = self.location_name.remove(&position);
+        if let Some(name) = name_at_top {
+            self.location_name.insert(position, name);
         }
-
-        if let Some(name_at_position) = name_at_position {
-            self.name_location.insert(name_at_position.to_string(), 0);
-            self.location_name.insert(0, name_at_position.to_string());
+        if let Some(name) = name_at_position {
+            self.location_name.insert(top, name);
         }
     }
+
+    /// Returns the depth of `name` relative to the top of the stack.
+    fn depth_of(&self, name: &str) -> Option<i32> {
+        self.name_location
+            .get(name)
+            .map(|location| self.stack_counter - 1 - location)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -226,7 +237,7 @@ impl EvmBlock {
             is_lookup_table: false,
             consumes: 0,
             produces: 0,
-            scope: Scope::empty(arg_names.len() as i32),
+            scope: Scope::with_arguments(&arg_names),
             comment: None,
             source_position: None,
             rust_position: None,
@@ -235,12 +246,6 @@ impl EvmBlock {
             label_counter: 0,
         };
 
-        for (i, name) in arg_names.iter().enumerate() {
-            match ret.register_arg_name(name, i as i32) {
-                Err(e) => panic!("{}", e),
-                _ => (),
-            }
-        }
         ret.jumpdest();
 
         ret
@@ -312,6 +317,71 @@ impl EvmBlock {
         }
     }
 
+    /// Moves the values passed to the next block, `arguments`, to the bottom of the stack with
+    /// the first argument deepest. Values which are not arguments are left above them for the
+    /// caller to pop. Returns the number of values above the arguments.
+    ///
+    /// Each argument is moved by swapping it to the top and then into place, such that an
+    /// argument already on top takes one swap and others two. Values already in place are not
+    /// touched. Arguments which are aliases of the same value are duplicated first.
+    pub fn move_block_arguments(&mut self, arguments: &BTreeSet<String>) -> Result<i32, String> {
+        let mut seen_locations = BTreeSet::new();
+        for arg in arguments.iter() {
+            let location = match self.scope.name_location.get(arg) {
+                Some(location) => *location,
+                None => return Err(format!("Failed to find SSA name {} on stack", arg)),
+            };
+            if !seen_locations.insert(location) {
+                self.duplicate_stack_name(arg)?;
+                self.scope.register_stack_name(arg)?;
+            }
+        }
+
+        let pop_count = self.scope.stack_counter - arguments.len() as i32;
+        if pop_count < 0 {
+            return Err("Not enough values on the stack to pass block arguments".to_string());
+        }
+
+        // Target depth of each argument, the first argument being the deepest
+        let targets: HashMap<String, i32> = arguments
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, arg)| (arg.clone(), pop_count + i as i32))
+            .collect();
+
+        // Each iteration places an argument or brings a misplaced one to the top
+        for _ in 0..=2 * targets.len() {
+            let misplaced: Vec<(i32, i32)> = targets
+                .iter()
+                .filter_map(|(name, target)| {
+                    let depth = self.scope.depth_of(name)?;
+                    if depth != *target {
+                        Some((depth, *target))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            if misplaced.is_empty() {
+                return Ok(pop_count);
+            }
+
+            match misplaced.iter().find(|(depth, _)| *depth == 0) {
+                Some((_, target)) => {
+                    self.swap(*target);
+                }
+                None => {
+                    let shallowest = misplaced.iter().map(|(depth, _)| *depth).min().unwrap();
+                    self.swap(shallowest);
+                }
+            }
+        }
+
+        Err("Failed to move block arguments into place".to_string())
+    }
+
     pub fn duplicate_stack_name(&mut self, name: &str) -> Result<(), String> {
         match self.scope.name_location.get(name) {
             Some(pos) => {
@@ -479,8 +549,8 @@ impl EvmBlock {
     }
 
     pub fn allocate_object(&mut self, value: Vec<u8>) {
-        let chunks = (4 + value.len() + 31) / 32;
-        let padded_length = chunks * 32;
+        let chunks = (value.len() + 31) / 32;
+        let padded_length = 4 + chunks * 32;
 
         self.alloca_static((padded_length).try_into().unwrap());
 
@@ -561,7 +631,7 @@ impl EvmBlock {
 
         for (i, arg) in args.iter().enumerate().rev() {
             match arg {
-                EvmType::String => {
+                arg if arg.is_dynamic() => {
                     // By default we store in head:
                     // p_data - p ->  p + 0x20 * i (p_head)
 
@@ -674,8 +744,7 @@ impl EvmBlock {
         self.swap1();
         self.sub();
 
-        let gas = EvmTypeValue::Uint32(0x1337); // TODO: How to compute this or where to get it from
-        let address = EvmTypeValue::Uint32(address);
+        let gas: u32 = 0x1337; // TODO: How to compute this or where to get it from
 
         // Stack:
         // p         => p
@@ -705,12 +774,43 @@ impl EvmBlock {
         // p            => p
         //              => address
         //              => gas
-        self.push(address.to_bytes_unpadded());
-        self.push(gas.to_bytes_unpadded());
+        self.push(address.to_be_bytes().to_vec());
+        self.push(gas.to_be_bytes().to_vec());
 
         // TODO: How come self.external_call(); does not call the precompile?
         self.external_staticcall();
 
+        // Replacing the success flag with the return value
+        self.pop();
+        if function.return_type.is_dynamic() {
+            // Dynamic values are returned as an object which is copied into newly allocated memory
+            // Stack:
+            //           => p_ret
+            //           => return_size
+            self.push1([ALLOCATION_POINTER].to_vec());
+            self.mload();
+            self.external_returndatasize();
+
+            // Stack:
+            // p_ret       => p_ret
+            // return_size => return_size
+            //             => 0x0
+            //             => p_ret
+            self.dup2();
+            self.dup2();
+            self.add();
+            self.push1([ALLOCATION_POINTER].to_vec());
+            self.mstore();
+            self.push1([0x0].to_vec());
+            self.dup3();
+            self.external_returndatacopy();
+        } else {
+            // The return value was written to the start of the call data
+            self.push1([ALLOCATION_POINTER].to_vec());
+            self.mload();
+            self.mload();
+        }
+
         self
     }
 
@@ -1039,11 +1139,11 @@ impl EvmBlock {
         self.write_instruction(Opcode::JUMPDEST, None)
     }
 
-    /*
+    /// Pushes zero using `PUSH0`, which is lowered to `PUSH1 0x00` for hardforks before
+    /// Shanghai.
     pub fn push0(&mut self) -> &mut Self {
-        self.write_instruction(Opcode::PUSH0, None)
+        self.write_instruction(PUSH0, None)
     }
-    */
 
     pub fn push_u64(&mut self, arg: u64) -> &mut Self {
         self.push(arg.to_be_bytes().to_vec())
@@ -1528,6 +1628,21 @@ impl EvmBlock {
     pub fn external_chainid(&mut self) -> &mut Self {
         self.write_instruction(Opcode::CHAINID, None)
     }
+    pub fn external_tload(&mut self) -> &mut Self {
+        self.write_instruction(TLOAD, None)
+    }
+    pub fn external_tstore(&mut self) -> &mut Self {
+        self.write_instruction(TSTORE, None)
+    }
+    pub fn external_mcopy(&mut self) -> &mut Self {
+        self.write_instruction(MCOPY, None)
+    }
+    pub fn external_blobhash(&mut self) -> &mut Self {
+        self.write_instruction(BLOBHASH, None)
+    }
+    pub fn external_blobbasefee(&mut self) -> &mut Self {
+        self.write_instruction(BLOBBASEFEE, None)
+    }
 }
 
 /*
@@ -10,6 +10,7 @@ use crate::{
     block::EvmBlock,
     evm_bytecode_builder::EvmByteCodeBuilder,
     function_signature::{AssemblyBuilderFn, EvmFunctionSignature},
+    hardfork::Hardfork,
     types::{EvmType, UserType},
 };
 
@@ -36,6 +37,9 @@ pub struct EvmCompilerContext {
     pub user_types: HashMap<String, Box<UserType>>,
     pub generic_types: HashMap<String, GenericDeclaration>,
 
+    /// Hardfork the generated code targets and executors run with.
+    pub hardfork: Hardfork,
+
     /// Scilla types -> EVM types
     precompiles: BTreeMap<H160, PrecompileFn>,
     precompile_addresses: HashMap<String, u32>,
@@ -104,6 +108,8 @@ impl EvmCompilerContext {
             user_types: HashMap::new(),
             generic_types: HashMap::new(),
 
+            hardfork: Hardfork::default(),
+
             precompile_addresses: HashMap::new(),
             precompiles: BTreeMap::new(),
             contract_offset: 5,
@@ -130,11 +136,24 @@ impl EvmCompilerContext {
             .insert(name.to_string(), EvmType::Uint(bits));
     }
 
+    /// Declares a boolean, which is represented by a word holding zero or one.
+    pub fn declare_bool(&mut self, name: &str) {
+        self.type_declarations
+            .insert(name.to_string(), EvmType::Bool);
+    }
+
     pub fn declare_address(&mut self, name: &str) {
         self.type_declarations
             .insert(name.to_string(), EvmType::String);
     }
 
+    /// Declares a byte string of fixed length. Byte strings longer than a word are stored in
+    /// memory in the same way as strings.
+    pub fn declare_byte_string(&mut self, name: &str, bytes: usize) {
+        self.type_declarations
+            .insert(name.to_string(), EvmType::Bytes(bytes));
+    }
+
     pub fn declare_dynamic_string(&mut self, name: &str) {
         self.type_declarations
             .insert(name.to_string(), EvmType::String);
@@ -287,6 +306,22 @@ impl EvmCompilerContext {
         self.function_declarations.get(name).clone()
     }
 
+    /// Installs a precompile at a fixed address. This is used to provide the standard Ethereum
+    /// precompiles, which occupy the addresses below those assigned to runtime functions.
+    pub fn declare_precompile(
+        &mut self,
+        address: u32,
+        precompile: PrecompileFn,
+    ) -> Result<(), String> {
+        let address = H160::from_low_u64_be(address.into());
+        if self.precompiles.contains_key(&address) {
+            return Err(format!("Precompile at {:?} already exists.", address));
+        }
+
+        self.precompiles.insert(address, precompile);
+        Ok(())
+    }
+
     pub fn get_precompiles(&self) -> BTreeMap<H160, PrecompileFn> {
         self.precompiles.clone()
     }
@@ -0,0 +1,535 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use evm::Opcode;
+use primitive_types::U256;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    abi::Abi,
+    opcode_spec::{is_known_opcode, opcode_mnemonic, OpcodeSpec},
+};
+
+/// Most distinct stacks a block is analysed with. Blocks of internal functions are analysed
+/// once for every call site up to this bound, which resolves the jumps returning to them.
+const MAX_CONTEXTS_PER_BLOCK: usize = 64;
+
+/// Number of values tracked at the top of the stack.
+const MAX_TRACKED_STACK: usize = 64;
+
+fn serialize_hex<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+fn deserialize_hex<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    let digits = value.strip_prefix("0x").unwrap_or(&value);
+    hex::decode(digits).map_err(serde::de::Error::custom)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CfgInstruction {
+    pub offset: u32,
+    pub opcode: u8,
+    pub mnemonic: String,
+    /// Bytes pushed by PUSH instructions, which are cut short at the end of the code.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_hex",
+        deserialize_with = "deserialize_hex"
+    )]
+    pub argument: Vec<u8>,
+}
+
+impl CfgInstruction {
+    pub fn to_assembly(&self) -> String {
+        if self.argument.is_empty() {
+            self.mnemonic.clone()
+        } else {
+            format!("{} 0x{}", self.mnemonic, hex::encode(&self.argument))
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CfgBlock {
+    /// Offset of the first instruction, which identifies the block.
+    pub start: u32,
+    /// Offset following the last instruction.
+    pub end: u32,
+    pub instructions: Vec<CfgInstruction>,
+    /// Whether execution can reach the block. Data such as the metadata appended by solc is
+    /// decoded as blocks which are not reachable.
+    pub reachable: bool,
+    /// Whether the block ends with a jump whose target could not be determined.
+    pub unresolved_jump: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CfgEdgeKind {
+    /// JUMP to the target.
+    Jump,
+    /// JUMPI to the target when the condition holds.
+    Branch,
+    /// Execution continuing with the following block, including JUMPI not being taken.
+    FallThrough,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CfgEdge {
+    pub from: u32,
+    pub to: u32,
+    pub kind: CfgEdgeKind,
+    /// Whether the target was pushed outside the block of the jump, e.g. the return address
+    /// pushed by the caller of an internal function.
+    pub dynamic: bool,
+}
+
+/// Function recovered from the code. The entry function is the code from the start up to the
+/// functions dispatched on by their selector.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CfgFunction {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    pub entry: u32,
+    /// Starts of the blocks reachable from the entry, which may be shared with other
+    /// functions.
+    pub blocks: Vec<u32>,
+}
+
+/// Abstract value on the stack during the analysis.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Value {
+    Unknown,
+    /// Constant and whether it was pushed in the block being analysed.
+    Constant(U256, bool),
+    /// Result of comparing a value against what may be a function selector.
+    SelectorMatch(u32),
+}
+
+struct AbstractStack {
+    values: Vec<Value>,
+}
+
+impl AbstractStack {
+    /// Values below the tracked ones are unknown.
+    fn pop(&mut self) -> Value {
+        self.values.pop().unwrap_or(Value::Unknown)
+    }
+
+    fn push(&mut self, value: Value) {
+        self.values.push(value);
+    }
+
+    fn peek(&self, depth: usize) -> Value {
+        match self.values.len().checked_sub(depth + 1) {
+            Some(index) => self.values[index].clone(),
+            None => Value::Unknown,
+        }
+    }
+
+    fn swap(&mut self, depth: usize) {
+        while self.values.len() <= depth {
+            self.values.insert(0, Value::Unknown);
+        }
+        let top = self.values.len() - 1;
+        self.values.swap(top, top - depth);
+    }
+
+    /// Returns the stack as seen by a successor, where no value is pushed in the block.
+    fn successor_state(&self) -> Vec<Value> {
+        let skip = self.values.len().saturating_sub(MAX_TRACKED_STACK);
+        self.values[skip..]
+            .iter()
+            .map(|value| match value {
+                Value::Constant(value, _) => Value::Constant(*value, false),
+                value => value.clone(),
+            })
+            .collect()
+    }
+}
+
+fn ends_block(opcode: Opcode) -> bool {
+    !is_known_opcode(opcode)
+        || [
+            Opcode::JUMP,
+            Opcode::JUMPI,
+            Opcode::STOP,
+            Opcode::RETURN,
+            Opcode::REVERT,
+            Opcode::INVALID,
+            Opcode::SUICIDE,
+            Opcode::EOFMAGIC,
+        ]
+        .contains(&opcode)
+}
+
+fn starts_with_jumpdest(block: &CfgBlock) -> bool {
+    block
+        .instructions
+        .first()
+        .map_or(false, |instr| instr.opcode == Opcode::JUMPDEST.as_u8())
+}
+
+fn binary_operation(opcode: Opcode, a: &Value, b: &Value) -> Value {
+    match (a, b) {
+        (Value::Constant(a, a_local), Value::Constant(b, b_local)) => {
+            let value = match opcode {
+                Opcode::ADD => a.overflowing_add(*b).0,
+                Opcode::SUB => a.overflowing_sub(*b).0,
+                Opcode::AND => *a & *b,
+                Opcode::OR => *a | *b,
+                _ => return Value::Unknown,
+            };
+            Value::Constant(value, *a_local && *b_local)
+        }
+        // Dispatchers compare the selector, which is unknown, against the selector of each
+        // function
+        (Value::Constant(selector, _), Value::Unknown)
+        | (Value::Unknown, Value::Constant(selector, _))
+            if opcode == Opcode::EQ && *selector <= U256::from(u32::MAX) =>
+        {
+            Value::SelectorMatch(selector.as_u32())
+        }
+        _ => Value::Unknown,
+    }
+}
+
+/// Control flow graph of EVM bytecode, recovered by following the values pushed on the stack
+/// to the jumps consuming them. Works on any bytecode, not only the output of the compiler.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<CfgBlock>,
+    pub edges: Vec<CfgEdge>,
+    pub functions: Vec<CfgFunction>,
+}
+
+impl ControlFlowGraph {
+    pub fn from_bytecode(bytecode: &[u8]) -> Self {
+        let mut ret = Self {
+            blocks: Self::split_blocks(bytecode),
+            edges: Vec::new(),
+            functions: Vec::new(),
+        };
+        let selector_targets = ret.analyse();
+        ret.recover_functions(selector_targets);
+        ret
+    }
+
+    /// Decodes the bytecode and splits it into blocks at jump destinations and after
+    /// instructions which end the flow of execution.
+    fn split_blocks(bytecode: &[u8]) -> Vec<CfgBlock> {
+        let mut blocks = Vec::new();
+        let mut instructions: Vec<CfgInstruction> = Vec::new();
+        let mut start = 0;
+
+        let mut i = 0;
+        while i < bytecode.len() {
+            let opcode = Opcode(bytecode[i]);
+            let size = opcode.bytecode_arguments();
+            let argument = bytecode[i + 1..(i + 1 + size).min(bytecode.len())].to_vec();
+
+            if opcode == Opcode::JUMPDEST && !instructions.is_empty() {
+                blocks.push(CfgBlock {
+                    start,
+                    end: i as u32,
+                    instructions: std::mem::take(&mut instructions),
+                    reachable: false,
+                    unresolved_jump: false,
+                });
+            }
+            if instructions.is_empty() {
+                start = i as u32;
+            }
+
+            instructions.push(CfgInstruction {
+                offset: i as u32,
+                opcode: opcode.as_u8(),
+                mnemonic: opcode_mnemonic(opcode),
+                argument,
+            });
+            i += 1 + size;
+
+            if ends_block(opcode) {
+                blocks.push(CfgBlock {
+                    start,
+                    end: i.min(bytecode.len()) as u32,
+                    instructions: std::mem::take(&mut instructions),
+                    reachable: false,
+                    unresolved_jump: false,
+                });
+            }
+        }
+
+        if !instructions.is_empty() {
+            blocks.push(CfgBlock {
+                start,
+                end: bytecode.len() as u32,
+                instructions,
+                reachable: false,
+                unresolved_jump: false,
+            });
+        }
+
+        blocks
+    }
+
+    /// Runs the blocks on abstract stacks from the start of the code, recording the edges
+    /// between them. Returns the targets of jumps taken when the selector matches.
+    fn analyse(&mut self) -> BTreeMap<u32, u32> {
+        let index: HashMap<u32, usize> = self
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| (block.start, i))
+            .collect();
+
+        let mut edges: BTreeMap<(u32, u32, CfgEdgeKind), bool> = BTreeMap::new();
+        let mut selector_targets: BTreeMap<u32, u32> = BTreeMap::new();
+        let mut contexts: HashMap<u32, HashSet<Vec<Value>>> = HashMap::new();
+        let mut queue: VecDeque<(u32, Vec<Value>)> = VecDeque::new();
+        if !self.blocks.is_empty() {
+            queue.push_back((0, Vec::new()));
+        }
+
+        while let Some((start, state)) = queue.pop_front() {
+            let seen = contexts.entry(start).or_default();
+            if seen.len() >= MAX_CONTEXTS_PER_BLOCK || !seen.insert(state.clone()) {
+                continue;
+            }
+
+            let block_index = index[&start];
+            self.blocks[block_index].reachable = true;
+
+            let mut stack = AbstractStack { values: state };
+            let mut jumps: Vec<(CfgEdgeKind, Value, Option<Value>)> = Vec::new();
+            let mut falls_through = true;
+
+            for instr in self.blocks[block_index].instructions.iter() {
+                let opcode = Opcode(instr.opcode);
+                match instr.opcode {
+                    0x5f..=0x7f => stack.push(Value::Constant(
+                        U256::from_big_endian(&instr.argument),
+                        true,
+                    )),
+                    0x80..=0x8f => {
+                        let value = stack.peek((instr.opcode - 0x80) as usize);
+                        stack.push(value);
+                    }
+                    0x90..=0x9f => stack.swap((instr.opcode - 0x8f) as usize),
+                    _ if opcode == Opcode::JUMP => {
+                        let target = stack.pop();
+                        jumps.push((CfgEdgeKind::Jump, target, None));
+                        falls_through = false;
+                    }
+                    _ if opcode == Opcode::JUMPI => {
+                        let target = stack.pop();
+                        let condition = stack.pop();
+                        jumps.push((CfgEdgeKind::Branch, target, Some(condition)));
+                    }
+                    _ if ends_block(opcode) => falls_through = false,
+                    _ if [
+                        Opcode::ADD,
+                        Opcode::SUB,
+                        Opcode::AND,
+                        Opcode::OR,
+                        Opcode::EQ,
+                    ]
+                    .contains(&opcode) =>
+                    {
+                        let a = stack.pop();
+                        let b = stack.pop();
+                        stack.push(binary_operation(opcode, &a, &b));
+                    }
+                    _ => {
+                        for _ in 0..opcode.stack_consumed() {
+                            stack.pop();
+                        }
+                        for _ in 0..opcode.stack_produced() {
+                            stack.push(Value::Unknown);
+                        }
+                    }
+                }
+            }
+
+            let successor_state = stack.successor_state();
+            let end = self.blocks[block_index].end;
+            for (kind, target, condition) in jumps {
+                let resolved = match &target {
+                    Value::Constant(target, local) if *target <= U256::from(u32::MAX) => index
+                        .get(&target.as_u32())
+                        .filter(|i| starts_with_jumpdest(&self.blocks[**i]))
+                        .map(|_| (target.as_u32(), !*local)),
+                    _ => None,
+                };
+
+                match resolved {
+                    Some((to, dynamic)) => {
+                        *edges.entry((start, to, kind)).or_insert(false) |= dynamic;
+                        if let Some(Value::SelectorMatch(selector)) = condition {
+                            selector_targets.insert(to, selector);
+                        }
+                        queue.push_back((to, successor_state.clone()));
+                    }
+                    None => self.blocks[block_index].unresolved_jump = true,
+                }
+            }
+
+            if falls_through && index.contains_key(&end) {
+                edges
+                    .entry((start, end, CfgEdgeKind::FallThrough))
+                    .or_insert(false);
+                queue.push_back((end, successor_state));
+            }
+        }
+
+        self.edges = edges
+            .into_iter()
+            .map(|((from, to, kind), dynamic)| CfgEdge {
+                from,
+                to,
+                kind,
+                dynamic,
+            })
+            .collect();
+
+        selector_targets
+    }
+
+    /// Splits the reachable code into the entry function and the functions the dispatcher
+    /// jumps to when their selector matches.
+    fn recover_functions(&mut self, selector_targets: BTreeMap<u32, u32>) {
+        let mut successors: HashMap<u32, Vec<u32>> = HashMap::new();
+        for edge in &self.edges {
+            successors.entry(edge.from).or_default().push(edge.to);
+        }
+
+        let entries: HashSet<u32> = selector_targets.keys().copied().collect();
+        let reachable_from = |entry: u32| -> Vec<u32> {
+            let mut visited = BTreeSet::from([entry]);
+            let mut queue = VecDeque::from([entry]);
+            while let Some(next) = queue.pop_front() {
+                for to in successors.get(&next).into_iter().flatten() {
+                    if !entries.contains(to) && visited.insert(*to) {
+                        queue.push_back(*to);
+                    }
+                }
+            }
+            visited.into_iter().collect()
+        };
+
+        self.functions = Vec::new();
+        if let Some(first) = self.blocks.first() {
+            self.functions.push(CfgFunction {
+                name: "entry".to_string(),
+                selector: None,
+                entry: first.start,
+                blocks: reachable_from(first.start),
+            });
+        }
+
+        for (entry, selector) in selector_targets {
+            self.functions.push(CfgFunction {
+                name: format!("function_0x{:08x}", selector),
+                selector: Some(format!("0x{:08x}", selector)),
+                entry,
+                blocks: reachable_from(entry),
+            });
+        }
+    }
+
+    /// Names the functions after the entries of `abi` with their selector.
+    pub fn name_functions(&mut self, abi: &Abi) {
+        for function in self.functions.iter_mut() {
+            let selector = match &function.selector {
+                Some(selector) => selector.clone(),
+                None => continue,
+            };
+            if let Some(entry) = abi
+                .entries
+                .iter()
+                .find(|entry| format!("0x{}", hex::encode(entry.selector())) == selector)
+            {
+                function.name = entry.signature();
+            }
+        }
+    }
+
+    pub fn get_block(&self, start: u32) -> Option<&CfgBlock> {
+        self.blocks.iter().find(|block| block.start == start)
+    }
+
+    pub fn get_function(&self, name: &str) -> Option<&CfgFunction> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    /// Returns the graph of the reachable blocks in the Graphviz DOT language.
+    pub fn to_dot(&self) -> String {
+        let entries: HashMap<u32, &str> = self
+            .functions
+            .iter()
+            .map(|function| (function.entry, function.name.as_str()))
+            .collect();
+
+        let mut ret = "digraph cfg {\n  node [shape=box, fontname=\"monospace\"];\n".to_string();
+        for block in self.blocks.iter().filter(|block| block.reachable) {
+            let mut label = String::new();
+            if let Some(name) = entries.get(&block.start) {
+                label.push_str(&format!("{}\\l", name.replace('"', "\\\"")));
+            }
+            label.push_str(&format!("0x{:04x}:\\l", block.start));
+            for instr in &block.instructions {
+                label.push_str(&format!("  {}\\l", instr.to_assembly()));
+            }
+
+            let mut attributes = format!("label=\"{}\"", label);
+            if entries.contains_key(&block.start) {
+                attributes.push_str(", style=bold");
+            }
+            if block.unresolved_jump {
+                attributes.push_str(", color=red");
+            }
+            ret.push_str(&format!(
+                "  block_0x{:04x} [{}];\n",
+                block.start, attributes
+            ));
+        }
+
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            match edge.kind {
+                CfgEdgeKind::Jump => (),
+                CfgEdgeKind::Branch => attributes.push("label=\"true\""),
+                CfgEdgeKind::FallThrough => attributes.push("style=dashed"),
+            }
+            if edge.dynamic {
+                attributes.push("style=dotted");
+            }
+            ret.push_str(&format!(
+                "  block_0x{:04x} -> block_0x{:04x} [{}];\n",
+                edge.from,
+                edge.to,
+                attributes.join(", ")
+            ));
+        }
+        ret.push_str("}\n");
+
+        ret
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+}
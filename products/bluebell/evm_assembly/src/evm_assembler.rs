@@ -0,0 +1,424 @@
+//! Parser for the textual assembly language, which is also what the decompiler outputs:
+//!
+//! ```text
+//! ; Comments run from a semicolon to the end of the line
+//! .macro revert_with code     ; Macros take parameters which are substituted in the body
+//!   PUSH1 $code
+//!   PUSH1 0x00
+//!   REVERT
+//! .endmacro
+//!
+//! .function main              ; Starts a function, which is given a name if omitted
+//!   PUSH1 0x80                ; Code before the first label is not preceded by JUMPDEST
+//!   PUSH @done                ; Pushes the position of a label with PUSH4
+//!   JUMP
+//! done:                       ; Starts a block with JUMPDEST
+//!   PUSH2 @greeting           ; Labels can be pushed with any PUSH wide enough
+//!   %revert_with 0x01         ; Expands a macro
+//!
+//! .data greeting "Hello"      ; Data is placed after the code, given in hex or as a string
+//! ```
+//!
+//! As for compiled programs, the code is followed by STOP and the data sections.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use evm::Opcode;
+use primitive_types::U256;
+
+use crate::{
+    block::EvmBlock,
+    bytecode_ir::EvmBytecodeIr,
+    function::EvmFunction,
+    function_signature::EvmFunctionSignature,
+    instruction::EvmInstruction,
+    opcode_spec::{opcode_from_mnemonic, OpcodeSpec},
+    types::EvmType,
+};
+
+/// Deepest nesting of macro expansions, which stops recursive macros.
+const MAX_MACRO_DEPTH: usize = 32;
+
+struct Macro {
+    parameters: Vec<String>,
+    body: Vec<(usize, String)>,
+}
+
+struct AssemblyParser {
+    ir: EvmBytecodeIr,
+    macros: HashMap<String, Macro>,
+    labels: HashSet<String>,
+    referenced_labels: Vec<(usize, String)>,
+    line_number: usize,
+    function: Option<EvmFunction>,
+    block: Option<EvmBlock>,
+}
+
+/// Removes the comment from `line`, leaving semicolons in strings alone.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ';' if !in_string => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(['.', '@', '%', '$'])
+        && !name.contains(['"', ';'])
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Parses a number given in decimal or in hex, returning its big endian bytes without leading
+/// zeros but at least one byte.
+fn parse_number(value: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = match value.strip_prefix("0x") {
+        Some(digits) => {
+            let digits = if digits.len() % 2 == 1 {
+                format!("0{}", digits)
+            } else {
+                digits.to_string()
+            };
+            hex::decode(digits).map_err(|_| format!("Invalid hex number {}", value))?
+        }
+        None => {
+            let number =
+                U256::from_dec_str(value).map_err(|_| format!("Invalid number {}", value))?;
+            let mut bytes = [0u8; 32];
+            number.to_big_endian(&mut bytes);
+            bytes.to_vec()
+        }
+    };
+
+    let leading_zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    bytes.drain(..leading_zeros.min(bytes.len().saturating_sub(1)));
+    if bytes.is_empty() {
+        bytes.push(0);
+    }
+
+    Ok(bytes)
+}
+
+fn push_opcode(size: usize) -> Opcode {
+    Opcode(Opcode::PUSH1.as_u8() + size as u8 - 1)
+}
+
+impl AssemblyParser {
+    fn new() -> Self {
+        Self {
+            ir: EvmBytecodeIr::new(),
+            macros: HashMap::new(),
+            labels: HashSet::new(),
+            referenced_labels: Vec::new(),
+            line_number: 0,
+            function: None,
+            block: None,
+        }
+    }
+
+    fn parse(mut self, script: &str) -> Result<EvmBytecodeIr, String> {
+        let mut lines = script
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, strip_comment(line).trim().to_string()));
+
+        while let Some((line_number, line)) = lines.next() {
+            self.line_number = line_number;
+            if line.starts_with(".macro") {
+                let mut tokens = line.split_whitespace().skip(1);
+                let name = match tokens.next() {
+                    Some(name) if is_valid_name(name) => name.to_string(),
+                    _ => {
+                        return Err(format!(
+                            "Line {}: Expected the name of the macro",
+                            line_number
+                        ))
+                    }
+                };
+                if self.macros.contains_key(&name) {
+                    return Err(format!(
+                        "Line {}: Macro {} is already defined",
+                        line_number, name
+                    ));
+                }
+
+                let parameters = tokens.map(|p| p.to_string()).collect();
+                let mut body = Vec::new();
+                loop {
+                    match lines.next() {
+                        Some((_, line)) if line == ".endmacro" => break,
+                        Some((_, line)) if line.starts_with(".macro") => {
+                            return Err(format!(
+                                "Line {}: Macros cannot be defined inside macro {}",
+                                line_number, name
+                            ))
+                        }
+                        Some(line) => body.push(line),
+                        None => {
+                            return Err(format!(
+                                "Line {}: Macro {} is missing .endmacro",
+                                line_number, name
+                            ))
+                        }
+                    }
+                }
+                self.macros.insert(name, Macro { parameters, body });
+            } else {
+                self.statement(&line, 0)
+                    .map_err(|e| format!("Line {}: {}", line_number, e))?;
+            }
+        }
+
+        self.finish_function();
+
+        // Functions are only positioned by their first block
+        for function in &self.ir.functions {
+            if function.blocks.is_empty() {
+                if let Some(signature) = &function.signature {
+                    self.labels.remove(&signature.name);
+                }
+            }
+        }
+
+        for (line_number, label) in &self.referenced_labels {
+            if !self.labels.contains(label) {
+                return Err(format!(
+                    "Line {}: Label {} is not defined",
+                    line_number, label
+                ));
+            }
+        }
+
+        Ok(self.ir)
+    }
+
+    fn statement(&mut self, line: &str, depth: usize) -> Result<(), String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let first = match tokens.first() {
+            Some(first) => *first,
+            None => return Ok(()),
+        };
+
+        match first {
+            ".function" => match tokens[1..] {
+                [name] => self.start_function(name),
+                _ => Err("Expected the name of the function".to_string()),
+            },
+            ".data" => {
+                let mut parts = line.splitn(3, char::is_whitespace).skip(1);
+                let name = parts.next().unwrap_or("");
+                let value = parts.next().unwrap_or("").trim();
+                self.data(name, value)
+            }
+            ".endmacro" => Err(".endmacro without .macro".to_string()),
+            _ if first.starts_with('%') => self.expand_macro(&first[1..], &tokens[1..], depth),
+            _ if first.ends_with(':') => {
+                self.start_block(&first[..first.len() - 1])?;
+                self.statement(&tokens[1..].join(" "), depth)
+            }
+            _ if first.starts_with('.') => Err(format!("Unknown directive {}", first)),
+            _ => match tokens[1..] {
+                [] => self.instruction(first, None),
+                [argument] => self.instruction(first, Some(argument)),
+                _ => Err(format!("Too many arguments to {}", first)),
+            },
+        }
+    }
+
+    fn define_label(&mut self, name: &str) -> Result<(), String> {
+        if !is_valid_name(name) {
+            return Err(format!("Invalid name {}", name));
+        }
+        if !self.labels.insert(name.to_string()) {
+            return Err(format!("Label {} is already defined", name));
+        }
+        Ok(())
+    }
+
+    fn finish_block(&mut self) {
+        if let Some(block) = self.block.take() {
+            self.function
+                .as_mut()
+                .expect("Block outside of a function")
+                .blocks
+                .push(block);
+        }
+    }
+
+    fn finish_function(&mut self) {
+        self.finish_block();
+        if let Some(function) = self.function.take() {
+            self.ir.functions.push_back(function);
+        }
+    }
+
+    fn start_function(&mut self, name: &str) -> Result<(), String> {
+        self.finish_function();
+        self.define_label(name)?;
+
+        let signature =
+            EvmFunctionSignature::new(name.to_string(), Vec::new(), &EvmType::Uint(256));
+        self.function = Some(EvmFunction::from_signature(signature));
+        Ok(())
+    }
+
+    fn start_block(&mut self, name: &str) -> Result<(), String> {
+        if self.function.is_none() {
+            let name = format!("function_{}", self.ir.functions.len());
+            self.start_function(&name)?;
+        }
+        self.finish_block();
+        self.define_label(name)?;
+
+        self.block = Some(EvmBlock::new(None, BTreeSet::new(), name));
+        Ok(())
+    }
+
+    fn data(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let payload = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            value[1..value.len() - 1].as_bytes().to_vec()
+        } else if let Some(digits) = value.strip_prefix("0x") {
+            hex::decode(digits).map_err(|_| format!("Invalid data {}", value))?
+        } else {
+            return Err("Expected data in hex or as a string".to_string());
+        };
+
+        self.define_label(name)?;
+        self.ir.data.push((name.to_string(), payload));
+        Ok(())
+    }
+
+    fn expand_macro(&mut self, name: &str, arguments: &[&str], depth: usize) -> Result<(), String> {
+        if depth >= MAX_MACRO_DEPTH {
+            return Err(format!("Macro {} is expanded too deeply", name));
+        }
+        let (parameters, body) = match self.macros.get(name) {
+            Some(m) => (m.parameters.clone(), m.body.clone()),
+            None => return Err(format!("Unknown macro {}", name)),
+        };
+        if parameters.len() != arguments.len() {
+            return Err(format!(
+                "Macro {} expects {} arguments but got {}",
+                name,
+                parameters.len(),
+                arguments.len()
+            ));
+        }
+
+        // Substituting longer names first such that a parameter is not replaced by a prefix of it
+        let mut substitutions: Vec<(String, &str)> = parameters
+            .iter()
+            .map(|p| format!("${}", p))
+            .zip(arguments.iter().copied())
+            .collect();
+        substitutions.sort_by_key(|(parameter, _)| std::cmp::Reverse(parameter.len()));
+
+        for (line_number, line) in body {
+            let mut line = line;
+            for (parameter, argument) in &substitutions {
+                line = line.replace(parameter.as_str(), argument);
+            }
+            self.statement(&line, depth + 1)
+                .map_err(|e| format!("{} (line {} of macro {})", e, line_number, name))?;
+        }
+
+        Ok(())
+    }
+
+    fn instruction(&mut self, mnemonic: &str, argument: Option<&str>) -> Result<(), String> {
+        let label = argument.and_then(|argument| argument.strip_prefix('@'));
+
+        let opcode = if mnemonic.eq_ignore_ascii_case("PUSH") {
+            match (label, argument) {
+                (Some(_), _) => Opcode::PUSH4,
+                (None, Some(argument)) => {
+                    let size = parse_number(argument)?.len();
+                    if size > 32 {
+                        return Err(format!("{} does not fit in a word", argument));
+                    }
+                    push_opcode(size)
+                }
+                (None, None) => return Err("PUSH expects an argument".to_string()),
+            }
+        } else {
+            match opcode_from_mnemonic(mnemonic) {
+                Some(opcode) => opcode,
+                None => return Err(format!("Unknown instruction {}", mnemonic)),
+            }
+        };
+
+        let size = opcode.bytecode_arguments();
+        let arguments = match (argument, label) {
+            (_, Some(label)) if size > 0 => {
+                self.referenced_labels
+                    .push((self.line_number, label.to_string()));
+                vec![0; size]
+            }
+            (Some(argument), None) if size > 0 => {
+                let bytes = parse_number(argument)?;
+                if bytes.len() > size {
+                    return Err(format!("{} does not fit in {}", argument, mnemonic));
+                }
+                let mut arguments = vec![0; size - bytes.len()];
+                arguments.extend(bytes);
+                arguments
+            }
+            (None, _) if size > 0 => return Err(format!("{} expects an argument", mnemonic)),
+            (Some(_), _) => return Err(format!("{} does not take an argument", mnemonic)),
+            (None, _) => Vec::new(),
+        };
+
+        if self.block.is_none() {
+            if self.function.is_none() {
+                let name = format!("function_{}", self.ir.functions.len());
+                self.start_function(&name)?;
+            }
+
+            // Code at the start of a function which is not a jump destination
+            let name = format!(
+                "{}::start",
+                self.function
+                    .as_ref()
+                    .unwrap()
+                    .signature
+                    .as_ref()
+                    .unwrap()
+                    .name
+            );
+            self.define_label(&name)?;
+            let mut block = EvmBlock::new(None, BTreeSet::new(), &name);
+            block.instructions.clear();
+            self.block = Some(block);
+        }
+
+        self.block
+            .as_mut()
+            .unwrap()
+            .instructions
+            .push(EvmInstruction {
+                position: None,
+                opcode,
+                arguments,
+                unresolved_argument_label: label.map(|label| label.to_string()),
+                stack_size: 0,
+                is_terminator: false,
+                comment: None,
+                source_position: None,
+                rust_position: None,
+                label: None,
+            });
+
+        Ok(())
+    }
+}
+
+/// Parses a program in the textual assembly language into functions of blocks, leaving the
+/// labels to be resolved.
+pub fn parse_assembly(script: &str) -> Result<EvmBytecodeIr, String> {
+    AssemblyParser::new().parse(script)
+}
@@ -6,13 +6,17 @@ use std::{
 use evm::Opcode;
 
 use crate::{
-    block::EvmBlock,
+    abi::Abi,
+    block::{EvmBlock, ALLOCATION_POINTER},
     bytecode_ir::EvmBytecodeIr,
     compiler_context::EvmCompilerContext,
+    evm_assembler::parse_assembly,
     evm_decompiler::EvmAssemblyGenerator,
     executable::EvmExecutable,
     function::EvmFunction,
-    opcode_spec::{create_opcode_spec, OpcodeSpec, OpcodeSpecification},
+    opcode_spec::{create_opcode_spec, opcode_mnemonic, OpcodeSpec, OpcodeSpecification},
+    peephole::{PeepholeOptimiser, PeepholeReport},
+    storage_layout::StorageLayout,
     types::EvmType,
 };
 
@@ -22,7 +26,9 @@ pub struct FunctionBuilder<'a, 'ctx> {
 }
 
 impl<'a, 'ctx> FunctionBuilder<'a, 'ctx> {
-    pub fn build<F>(mut self, builder: F)
+    /// Defines the function with the blocks returned by `builder`. Fails if the stack use of
+    /// the blocks is inconsistent or the function returns more than one value.
+    pub fn build<F>(mut self, builder: F) -> Result<(), String>
     where
         F: Fn(&mut EvmByteCodeBuilder<'ctx>) -> Vec<EvmBlock>,
     {
@@ -35,10 +41,9 @@ impl<'a, 'ctx> FunctionBuilder<'a, 'ctx> {
 
         // if first_block.consumes
 
-        match self.function.compute_stack_difference() {
-            Err(e) => panic!("{}", e),
-            _ => (),
-        }
+        self.function
+            .compute_stack_difference()
+            .map_err(|e| format!("Function {}: {}", signature.name, e))?;
 
         /*
         TODO:
@@ -48,16 +53,14 @@ impl<'a, 'ctx> FunctionBuilder<'a, 'ctx> {
         */
 
         if self.function.produces > 1 {
-            panic!(
-                "{}",
-                format!(
-                    "Function produces {} but at the moment we only support 1 return argument",
-                    self.function.produces
-                )
-            )
+            return Err(format!(
+                "Function {} produces {} but at the moment we only support 1 return argument",
+                signature.name, self.function.produces
+            ));
         }
 
         self.builder.ir.functions.push_back(self.function);
+        Ok(())
     }
 }
 
@@ -80,6 +83,9 @@ pub struct EvmByteCodeBuilder<'ctx> {
 
     pub current_function_name: Option<String>,
     pub used_block_names: HashSet<String>,
+
+    pub peephole_optimisation: bool,
+    pub peephole_report: Option<PeepholeReport>,
 }
 
 impl<'ctx> EvmByteCodeBuilder<'ctx> {
@@ -95,19 +101,52 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
             label_positions: HashMap::new(),
             current_function_name: None,
             used_block_names: HashSet::new(),
+            peephole_optimisation: false,
+            peephole_report: None,
         };
-
-        // Reserving the start of the bytecode for the "entry" function
-        ret.define_function("__main__", [].to_vec(), "Uint256")
-            .build(|code_builder| {
-                // Placeholder block for the main function
-                [code_builder.new_evm_block("main_entry")].to_vec()
-                // EvmBlock::new(None, BTreeSet::new(), &code_builder.add_scope_to_label("main_entry"))
-            });
+        ret.reserve_main_function();
 
         ret
     }
 
+    /// Reserves the start of the bytecode for the "entry" function.
+    fn reserve_main_function(&mut self) {
+        let signature = self
+            .context
+            .declare_function("__main__", [].to_vec(), "Uint256")
+            .signature;
+        let mut main = EvmFunction::from_signature(signature);
+
+        // Placeholder block for the main function, which is replaced when the blocks are
+        // finalized
+        self.set_current_function_name("__main__".to_string());
+        main.blocks = [self.new_evm_block("main_entry")].to_vec();
+        self.clear_current_function_name();
+
+        self.ir.functions.push_back(main);
+    }
+
+    /// Discards the functions and data written so far, allowing the builder to be reused for
+    /// another program in the same context, e.g. the creation code of a contract.
+    pub fn reset(&mut self, create_abi_boilerplate: bool) {
+        self.ir = EvmBytecodeIr::new();
+        self.bytecode = Vec::new();
+        self.auxiliary_data = Vec::new();
+        self.was_finalized = false;
+        self.create_abi_boilerplate = create_abi_boilerplate;
+        self.label_positions = HashMap::new();
+        self.current_function_name = None;
+        self.used_block_names = HashSet::new();
+        self.peephole_report = None;
+        self.reserve_main_function();
+    }
+
+    /// Enables the peephole optimiser, which runs on all functions once they are built.
+    pub fn enable_peephole_optimisation(&mut self) -> &mut Self {
+        self.peephole_optimisation = true;
+        self
+    }
+
     pub fn new_evm_block(&mut self, name: &str) -> EvmBlock {
         EvmBlock::new(
             None,
@@ -168,6 +207,40 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
         }
     }
 
+    /// Sets the types the arguments of the defined function `name` are passed as by callers,
+    /// which determine its selector and how its arguments are loaded from the call data.
+    pub fn set_external_arguments(
+        &mut self,
+        name: &str,
+        arguments: Vec<EvmType>,
+    ) -> Result<(), String> {
+        let function = self
+            .ir
+            .functions
+            .iter_mut()
+            .find(|function| match &function.signature {
+                Some(signature) => signature.name == name,
+                None => false,
+            })
+            .ok_or(format!("Function {} not found", name))?;
+        let signature = function.signature.as_mut().unwrap();
+        if signature.arguments.len() != arguments.len() {
+            return Err(format!(
+                "Function {} takes {} arguments, found {} external arguments",
+                name,
+                signature.arguments.len(),
+                arguments.len()
+            ));
+        }
+
+        signature.external_arguments = Some(arguments);
+        function.selector = signature.selector();
+        self.context
+            .function_declarations
+            .insert(name.to_string(), signature.clone());
+        Ok(())
+    }
+
     pub fn from_bytes(context: &'ctx mut EvmCompilerContext, bytes: Vec<u8>) -> Self {
         let opcode_specs = create_opcode_spec();
         let (blocks, auxiliary_data) =
@@ -191,11 +264,60 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
             label_positions: HashMap::new(),
             current_function_name: None,
             used_block_names: HashSet::new(),
+            peephole_optimisation: false,
+            peephole_report: None,
         }
     }
 
-    pub fn from_asm(_script: &str) -> Self {
-        unimplemented!();
+    /// Assembles a program written in the textual assembly language, which is the language
+    /// output by `generate_evm_assembly`. The program is taken as written, i.e. without the
+    /// boilerplate otherwise added to the entry function. Instructions which are not available in
+    /// the hardfork targeted by the context are rejected.
+    pub fn from_asm(context: &'ctx mut EvmCompilerContext, script: &str) -> Result<Self, String> {
+        let ir = parse_assembly(script)?;
+        context.hardfork.check(&ir)?;
+        let used_block_names = ir
+            .functions
+            .iter()
+            .flat_map(|function| function.blocks.iter().map(|block| block.name.clone()))
+            .collect();
+
+        let mut ret = Self {
+            context,
+            ir,
+            bytecode: Vec::new(),
+            opcode_specs: create_opcode_spec(),
+            auxiliary_data: Vec::new(),
+            was_finalized: false,
+            create_abi_boilerplate: false,
+            label_positions: HashMap::new(),
+            current_function_name: None,
+            used_block_names,
+            peephole_optimisation: false,
+            peephole_report: None,
+        };
+
+        ret.resolve_positions()?;
+        ret.was_finalized = true;
+
+        for function in ret.ir.functions.iter() {
+            for block in function.blocks.iter() {
+                for instruction in block.instructions.iter() {
+                    if instruction.arguments.len() != instruction.expected_args_length() {
+                        return Err(format!(
+                            "Position of {} does not fit in {}",
+                            instruction
+                                .unresolved_argument_label
+                                .clone()
+                                .unwrap_or_default(),
+                            opcode_mnemonic(instruction.opcode)
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(ret)
     }
 
     pub fn push_u8(&mut self, opcode: u8) -> &mut Self {
@@ -212,9 +334,9 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
         self
     }
 
-    pub fn build(&mut self) -> EvmExecutable {
+    pub fn build(&mut self) -> Result<EvmExecutable, String> {
         let mut bytecode = Vec::new();
-        self.finalize_blocks();
+        self.finalize_blocks()?;
 
         // Generating bytecode
         for function in self.ir.functions.iter_mut() {
@@ -243,16 +365,23 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
         mem::swap(&mut ir, &mut self.ir);
 
         // TODO: Make block table
-        EvmExecutable {
+        Ok(EvmExecutable {
             bytecode,
             label_positions: self.label_positions.clone(),
             ir,
-        }
+            abi: Abi::new(),
+            deploy_bytecode: Vec::new(),
+            constructor_arguments: Vec::new(),
+            peephole_report: self.peephole_report.take(),
+            storage_layout: StorageLayout::new(),
+        })
     }
 
-    pub fn finalize_blocks(&mut self) {
+    /// Adds the entry function dispatching calls to the functions, lowers the instructions to
+    /// the target hardfork and resolves the positions of the labels.
+    pub fn finalize_blocks(&mut self) -> Result<(), String> {
         if self.was_finalized {
-            return;
+            return Ok(());
         }
 
         // Building entry function
@@ -260,7 +389,7 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
             let mut binding = self.ir.functions.front_mut();
             let main = match binding {
                 Some(ref mut main) => main,
-                _ => panic!("Expected the reserved main function."),
+                _ => return Err("Expected the reserved main function".to_string()),
             };
 
             main.clone()
@@ -269,7 +398,7 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
         let mut binding_block = main.blocks.first_mut();
         let first_block = match binding_block {
             Some(ref mut block) => block,
-            None => panic!("Function does not have a main block."),
+            None => return Err("Function does not have a main block".to_string()),
         };
 
         self.set_current_function_name("__entry_function__".to_string());
@@ -339,8 +468,8 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
 
                         load_data_block.pop(); // Remove the user function selector from the stack
 
-                        // Checking that the size of call args
-                        // TODO: Assumptino is that all arguments are 256-bits
+                        // Checking the size of the head of the arguments, in which every
+                        // argument takes a word
                         let args_size = 0x04 + 0x20 * signature.arguments.len();
                         load_data_block.push_u64(args_size.try_into().unwrap());
                         load_data_block.calldatasize();
@@ -351,37 +480,73 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
                         load_data_block.push_label(&success_block.name);
 
                         // Loading data
-                        for (i, arg) in signature.arguments.iter().enumerate() {
-                            if let EvmType::String = arg {
-                                // TODO: We only support loading of 32 byte strings.
-                                // TODO: Count non-zero characters and push to the stack
-                                let size = 32;
-                                load_data_block.alloca_static(4 + size);
-                                // First four bytes store size
-                                load_data_block.push_u32(size as u32);
-                                load_data_block.push_u32(256 - 32);
+                        for (i, arg) in signature.external_arguments().iter().enumerate() {
+                            if arg.is_dynamic() {
+                                // Copying the bytes referred to by the offset in the head into a
+                                // new object, which is a 4 byte length followed by the data. The
+                                // length is stored at `o`, the position following the selector
+                                // and the offset.
+                                // Stack:
+                                // => o
+                                // => len
+                                load_data_block.push_u64((0x04 + 0x20 * i).try_into().unwrap());
+                                load_data_block.calldataload();
+                                load_data_block.push1([0x04].to_vec());
+                                load_data_block.add();
+                                load_data_block.dup1();
+                                load_data_block.calldataload();
+
+                                // Stack:
+                                // o   => o
+                                // len => len
+                                //     => p
+                                load_data_block.push1([ALLOCATION_POINTER].to_vec());
+                                load_data_block.mload();
+                                load_data_block.dup1();
+                                load_data_block.dup3();
+                                load_data_block.add();
+                                load_data_block.push1([0x24].to_vec());
+                                load_data_block.add();
+                                load_data_block.push1([ALLOCATION_POINTER].to_vec());
+                                load_data_block.mstore();
+
+                                // mstore(p, len << 224)
+                                load_data_block.dup2();
+                                load_data_block.push1([224].to_vec());
                                 load_data_block.shl();
                                 load_data_block.dup2();
                                 load_data_block.mstore();
 
-                                load_data_block.dup1();
+                                // calldatacopy(p + 4, o + 32, len), leaving p
+                                load_data_block.swap2();
+                                load_data_block.push1([0x20].to_vec());
+                                load_data_block.add();
+                                load_data_block.dup3();
                                 load_data_block.push1([0x04].to_vec());
                                 load_data_block.add();
-
-                                load_data_block.push_u64((0x04 + 0x20 * i).try_into().unwrap());
-                                load_data_block.calldataload();
-                                load_data_block.swap1();
-                                load_data_block.mstore();
+                                load_data_block.calldatacopy();
                             } else {
                                 load_data_block.push_u64((0x04 + 0x20 * i).try_into().unwrap());
                                 load_data_block.calldataload();
+
+                                // Byte strings are right aligned on the stack
+                                let shift = arg.abi_shift();
+                                if shift > 0 {
+                                    load_data_block.push_u32(shift as u32);
+                                    load_data_block.shr();
+                                }
                             }
                         }
 
                         load_data_block.jump_to(&block.name);
                         data_loading_blocks.push(load_data_block);
                     }
-                    _ => panic!("Function does not have any blocks."),
+                    _ => {
+                        return Err(format!(
+                            "Function {} does not have any blocks",
+                            signature.name
+                        ))
+                    }
                 };
             }
 
@@ -411,15 +576,25 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
 
         self.ir.functions[0] = main;
 
+        if self.peephole_optimisation {
+            self.peephole_report = Some(PeepholeOptimiser::new().optimise(&mut self.ir));
+        }
+
+        // Using the instructions available in the target hardfork
+        self.context.hardfork.lower(&mut self.ir)?;
+
         // Resolving labels
-        self.resolve_positions();
+        self.resolve_positions()?;
 
         // TODO: Test that all stack positions zero out
 
         self.was_finalized = true;
+        Ok(())
     }
 
-    pub fn resolve_positions(&mut self) {
+    /// Assigns the positions of the instructions and data and resolves the labels referring
+    /// to them.
+    pub fn resolve_positions(&mut self) -> Result<(), String> {
         let mut position: u32 = 0;
         self.label_positions = HashMap::new();
 
@@ -431,7 +606,7 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
                 if i == 0 {
                     let function_name = match &function.signature {
                         Some(v) => v.name.clone(),
-                        None => panic!("Invalid function signature {:?}", function),
+                        None => return Err(format!("Invalid function signature {:?}", function)),
                     };
                     self.label_positions.insert(function_name, position);
                 }
@@ -463,128 +638,19 @@ impl<'ctx> EvmByteCodeBuilder<'ctx> {
                             Some(p) => {
                                 instruction.u32_to_arg_big_endian(*p);
                             }
-                            None => {
-                                println!("Available labels: {:#?}", self.label_positions);
-                                panic!("Label not found {:#?}!", name);
-                            }
+                            None => return Err(format!("Label not found {}", name)),
                         }
                     }
                 }
             }
         }
+
+        Ok(())
     }
 }
 
 impl EvmAssemblyGenerator for EvmByteCodeBuilder<'_> {
     fn generate_evm_assembly(&self) -> String {
-        let mut script = "Unused blocks:\n\n".to_string();
-
-        let unused_blocks = self
-            .ir
-            .unused_blocks
-            .iter()
-            .map(|block| {
-                let code = block
-                    .instructions
-                    .iter()
-                    .map(|instr| {
-                        let position = match instr.position {
-                            Some(v) => v,
-                            None => 0,
-                        };
-                        let instruction_value = if instr.arguments.len() > 0 {
-                            let argument: String = instr
-                                .arguments
-                                .iter()
-                                .map(|byte| format!("{:02x}", byte).to_string())
-                                .collect();
-
-                            format!("{} 0x{}", instr.opcode.to_string(), argument).to_string()
-                        } else {
-                            instr.opcode.to_string()
-                        };
-
-                        format!(
-                            "[0x{:02x}: 0x{:02x}] {:<width$}  ;; Stack: {}, Comment: {}, Source: {:?}",
-                            position,
-                            instr.opcode.as_u8(),
-                            instruction_value,
-                            instr.stack_size,
-                            instr.comment.clone().unwrap_or("".to_string()).trim(),
-                            instr.source_position,
-                            width = 40
-                        )
-                    })
-                    .collect::<Vec<String>>()
-                    .join("\n");
-                let position = match block.position {
-                    Some(v) => v,
-                    None => 0,
-                };
-                format!(
-                    "{}: ;; Starts at 0x{:02x}  u8[{}] \n{}",
-                    block.name, position, block.consumes, code
-                )
-            })
-            .collect::<Vec<String>>()
-            .join("\n\n");
-        script.push_str(&unused_blocks);
-
-        for function in &self.ir.functions {
-            let code_blocks = function
-                .blocks
-                .iter()
-                .map(|block| {
-                    let code = block
-                        .instructions
-                        .iter()
-                        .map(|instr| {
-                            let position = match instr.position {
-                                Some(v) => v,
-                                None => 0,
-                            };
-
-                            let instruction_value = if instr.arguments.len() > 0 {
-                                let argument: String = instr
-                                    .arguments
-                                    .iter()
-                                    .map(|byte| format!("{:02x}", byte).to_string())
-                                    .collect();
-
-                                format!("{} 0x{}", instr.opcode.to_string(), argument).to_string()
-                            } else {
-                                instr.opcode.to_string()
-                            };
-
-                            format!(
-                                "[0x{:02x}: 0x{:02x}] {:<width$} ;; Stack: {}, Comment: {}, Source: {:?}",
-                                position,
-                                instr.opcode.as_u8(),
-                                instruction_value,
-                                instr.stack_size,
-                                instr.comment.clone().unwrap_or("".to_string()).trim(),
-                                instr.source_position,
-                                width = 40
-                            )
-                        })
-                        .collect::<Vec<String>>()
-                        .join("\n");
-                    let position = match block.position {
-                        Some(v) => v,
-                        None => 0,
-                    };
-                    format!(
-                        "{}: ;; Starts at 0x{:02x} u8[{}]\n{}",
-                        block.name, position, block.consumes, code
-                    )
-                })
-                .collect::<Vec<String>>()
-                .join("\n\n");
-
-            script.push_str("\n\nFunction:\n");
-            script.push_str(&code_blocks);
-        }
-
-        script
+        self.ir.generate_evm_assembly()
     }
 }
@@ -1,3 +1,95 @@
+use evm::Opcode;
+
+use crate::{
+    block::EvmBlock, bytecode_ir::EvmBytecodeIr, instruction::EvmInstruction,
+    opcode_spec::opcode_mnemonic,
+};
+
 pub trait EvmAssemblyGenerator {
     fn generate_evm_assembly(&self) -> String;
 }
+
+fn instruction_to_assembly(instr: &EvmInstruction) -> String {
+    let mnemonic = opcode_mnemonic(instr.opcode);
+    if let Some(label) = &instr.unresolved_argument_label {
+        format!("{} @{}", mnemonic, label)
+    } else if instr.arguments.is_empty() {
+        mnemonic
+    } else {
+        format!("{} 0x{}", mnemonic, hex::encode(&instr.arguments))
+    }
+}
+
+/// Writes a block as assembly with the debug information in comments. Blocks start with the
+/// JUMPDEST written by their label, while code at the start of a function has no label.
+fn block_to_assembly(block: &EvmBlock) -> String {
+    let position = block.position.unwrap_or(0);
+    let header = format!("Starts at 0x{:02x} u8[{}]", position, block.consumes);
+
+    let mut instructions = block.instructions.iter().peekable();
+    let mut ret = match instructions.peek() {
+        Some(instr) if instr.opcode == Opcode::JUMPDEST && instr.label.is_none() => {
+            instructions.next();
+            format!("{}: ; {}\n", block.name, header)
+        }
+        _ => format!("; {}: {}\n", block.name, header),
+    };
+
+    for instr in instructions {
+        if let (Opcode::JUMPDEST, Some(label)) = (instr.opcode, &instr.label) {
+            ret.push_str(&format!("{}:\n", label));
+            continue;
+        }
+
+        ret.push_str(&format!(
+            "  {:<40} ; [0x{:02x}] Stack: {}, Comment: {}, Source: {:?}\n",
+            instruction_to_assembly(instr),
+            instr.position.unwrap_or(0),
+            instr.stack_size,
+            instr
+                .comment
+                .clone()
+                .unwrap_or("".to_string())
+                .trim()
+                .replace('\n', " "),
+            instr.source_position,
+        ));
+    }
+
+    ret
+}
+
+impl EvmAssemblyGenerator for EvmBytecodeIr {
+    fn generate_evm_assembly(&self) -> String {
+        let mut script = String::new();
+
+        // Unused blocks are not part of the program and are hence commented out
+        if !self.unused_blocks.is_empty() {
+            script.push_str("; Unused blocks:\n");
+            for block in &self.unused_blocks {
+                for line in block_to_assembly(block).lines() {
+                    script.push_str(&format!("; {}\n", line));
+                }
+            }
+            script.push('\n');
+        }
+
+        for (i, function) in self.functions.iter().enumerate() {
+            let name = match &function.signature {
+                Some(signature) => signature.name.clone(),
+                None => format!("function_{}", i),
+            };
+            script.push_str(&format!(".function {}\n", name));
+            for block in &function.blocks {
+                script.push_str(&block_to_assembly(block));
+            }
+            script.push('\n');
+        }
+
+        for (name, payload) in &self.data {
+            script.push_str(&format!(".data {} 0x{}\n", name, hex::encode(payload)));
+        }
+
+        script
+    }
+}
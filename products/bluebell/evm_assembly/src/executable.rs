@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use crate::bytecode_ir::EvmBytecodeIr;
+use crate::{
+    abi::Abi, bytecode_ir::EvmBytecodeIr, peephole::PeepholeReport, storage_layout::StorageLayout,
+    types::EvmType,
+};
 
 pub type TypeSourceMap = HashMap<usize, (usize, usize, usize, usize)>;
 
@@ -8,7 +11,17 @@ pub type TypeSourceMap = HashMap<usize, (usize, usize, usize, usize)>;
 pub struct EvmExecutable {
     pub bytecode: Vec<u8>,
     pub label_positions: HashMap<String, u32>,
-    pub ir: EvmBytecodeIr, // TODO: add abi
+    pub ir: EvmBytecodeIr,
+    pub abi: Abi,
+    /// Creation code which runs the constructor and returns the runtime code in `bytecode`. The
+    /// ABI encoded contract parameters are appended to it when deploying.
+    pub deploy_bytecode: Vec<u8>,
+    /// Types of the contract parameters as they are passed through the ABI.
+    pub constructor_arguments: Vec<EvmType>,
+    /// Savings of the peephole optimiser, if it was enabled.
+    pub peephole_report: Option<PeepholeReport>,
+    /// Layout of the fields of the contract in storage.
+    pub storage_layout: StorageLayout,
 }
 
 impl EvmExecutable {
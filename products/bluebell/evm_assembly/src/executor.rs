@@ -3,29 +3,56 @@ use std::{
     str::FromStr,
 };
 
-use evm::{
-    backend::Apply,
-    executor::stack::{MemoryStackState, StackExecutor, StackSubstateMetadata},
-    Config,
-};
-use log::info;
-use primitive_types::{H160, U256};
+use primitive_types::{H160, H256, U256};
 
 use crate::{
     compiler_context::EvmCompilerContext,
     executable::EvmExecutable,
-    io_interface::{CustomMemoryAccount, EvmIoInterface},
+    hardfork::Hardfork,
+    io_interface::{CustomMemoryAccount, EvmBlockInfo},
+    local_chain::LocalChain,
     types::EvmTypeValue,
 };
 
+/// Address the executor places the contract at.
+fn contract_address() -> H160 {
+    H160::from_str("0x1000000000000000000000000000000000000000").unwrap()
+}
+
 pub struct EvmExecutor<'a> {
     pub context: &'a EvmCompilerContext,
     pub executable: EvmExecutable,
+    /// Additional accounts, such as other contracts, present in the state at execution.
+    pub accounts: BTreeMap<H160, CustomMemoryAccount>,
+    pub block: EvmBlockInfo,
+    /// Storage of the contract at the start of execution, as left by `deploy`.
+    pub storage: BTreeMap<H256, H256>,
+    /// Hardfork to execute with, by default the one targeted by the context.
+    pub hardfork: Hardfork,
+}
+
+/// A log entry emitted by the contract through one of the `LOGn` instructions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutorLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+}
+
+/// A call made by the contract to another account, i.e. an outgoing message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutorCall {
+    pub address: String,
+    pub value: String,
+    pub data: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct ExecutorResult {
     pub changeset: HashMap<String, Option<String>>,
+    pub logs: Vec<ExecutorLog>,
+    pub calls: Vec<ExecutorCall>,
+    pub revert_reason: Option<String>,
     pub result: String,
 }
 
@@ -34,94 +61,70 @@ impl<'a> EvmExecutor<'a> {
         Self {
             context,
             executable,
+            accounts: BTreeMap::new(),
+            block: EvmBlockInfo::default(),
+            storage: BTreeMap::new(),
+            hardfork: context.hardfork,
         }
     }
 
+    pub fn add_account(&mut self, address: H160, account: CustomMemoryAccount) {
+        self.accounts.insert(address, account);
+    }
+
     pub fn get_label_position(&self, label: &str) -> Option<u32> {
         self.executable.label_positions.get(label).copied()
     }
 
-    pub fn execute(&self, name: &str, args: Vec<EvmTypeValue>) -> ExecutorResult {
-        let input = self
-            .context
-            .get_function(name)
-            .expect(&format!("Function name {} not found", name).to_string())
-            .generate_transaction_data(args);
+    /// Returns a chain holding the additional accounts, on which the executor runs.
+    fn base_chain(&self) -> LocalChain<'a> {
+        let mut chain = LocalChain::new(self.context);
+        chain.block = self.block.clone();
+        chain.hardfork = self.hardfork;
+        for (address, account) in &self.accounts {
+            chain.add_account(*address, account.clone());
+        }
 
-        // Initialized the state of EVM's memory.
-        let config = Config::istanbul();
-        let mut state = BTreeMap::new();
+        chain
+    }
 
-        // Add our contract under the 0x10 address.
-        state.insert(
-            H160::from_str("0x1000000000000000000000000000000000000000").unwrap(),
+    /// Returns a chain with the contract placed at 0x1000000000000000000000000000000000000000
+    /// along with the additional accounts.
+    /// Unlike `execute`, calls made on the chain persist their changes to the state.
+    pub fn chain(&self) -> LocalChain<'a> {
+        let mut chain = self.base_chain();
+        chain.add_account(
+            contract_address(),
             CustomMemoryAccount {
                 nonce: U256::one(),
                 balance: U256::from(10000000),
-                storage: BTreeMap::new(),
+                storage: self.storage.clone(),
                 code: self.executable.bytecode.clone(),
             },
         );
 
-        // Add new user 0xf0 that will be used as the contract caller.
-        state.insert(
-            H160::from_str("0xf000000000000000000000000000000000000000").unwrap(),
-            CustomMemoryAccount {
-                nonce: U256::one(),
-                balance: U256::from(10000000),
-                storage: BTreeMap::new(),
-                code: Vec::new(),
-            },
-        );
+        chain
+    }
 
-        // Prepare the executor.
-        let backend = EvmIoInterface::new(state); //MemoryBackend::new(&vicinity, state);
-        let metadata = StackSubstateMetadata::new(u64::MAX, &config);
-        let mem_state = MemoryStackState::new(metadata, &backend);
-        let precompiles = self.context.get_precompiles();
-        let mut executor = StackExecutor::new_with_precompiles(mem_state, &config, &precompiles);
-
-        // Call the 0x10 contract using the 0xf0 user.
-        // Use the input variable.
-        let (exit_reason, result) = executor.transact_call(
-            H160::from_str("0xf000000000000000000000000000000000000000").unwrap(),
-            H160::from_str("0x1000000000000000000000000000000000000000").unwrap(),
-            U256::zero(),
-            input,
-            u64::MAX,
-            Vec::new(),
-        );
+    /// Calls the function `name` of the contract. The state is left as is, i.e. every call
+    /// starts from the storage left by `deploy`.
+    pub fn execute(&self, name: &str, args: Vec<EvmTypeValue>) -> Result<ExecutorResult, String> {
+        self.chain().execute(contract_address(), name, args)
+    }
 
-        let (state_apply, _logs) = executor.into_state().deconstruct();
-        info!("\n\n\nExit reason: {:#?}", exit_reason);
-        info!("Result: {:#?}", result);
-
-        let mut ret = ExecutorResult {
-            changeset: HashMap::new(),
-            result: format!("{:?}", result),
-        };
-
-        for update in state_apply {
-            match update {
-                Apply::Modify {
-                    address,
-                    basic: _,
-                    code: _,
-                    storage,
-                    reset_storage: _,
-                } => {
-                    for (k, v) in storage {
-                        let key = format!("{:?}.{:?}", address, k);
-                        ret.changeset.insert(key, Some(format!("{:?}", v)));
-                    }
-                }
-                Apply::Delete { address } => {
-                    let key = format!("{:?}", address);
-                    ret.changeset.insert(key, None);
-                }
-            }
+    /// Deploys the contract by running its creation code with the ABI encoded contract
+    /// parameters `args` appended. On success, the runtime code returned by the constructor
+    /// and the storage it initialised replace the executable code and storage, such that
+    /// subsequent calls to `execute` run against the deployed contract.
+    pub fn deploy(&mut self, args: Vec<EvmTypeValue>) -> Result<ExecutorResult, String> {
+        let mut chain = self.base_chain();
+        let ret = chain.deploy(contract_address(), &self.executable, args)?;
+
+        if let Some(account) = chain.get_account(contract_address()) {
+            self.executable.bytecode = account.code.clone();
+            self.storage = account.storage.clone();
         }
 
-        ret
+        Ok(ret)
     }
 }
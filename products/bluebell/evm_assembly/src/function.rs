@@ -2,7 +2,9 @@ use std::collections::{HashMap, HashSet, VecDeque};
 
 use evm::Opcode;
 
-use crate::{block::EvmBlock, function_signature::EvmFunctionSignature};
+use crate::{
+    block::EvmBlock, function_signature::EvmFunctionSignature, opcode_spec::static_gas_cost,
+};
 
 #[derive(Debug, Clone)]
 pub struct EvmFunction {
@@ -111,6 +113,16 @@ impl EvmFunction {
         Ok(())
     }
 
+    /// Sums the static gas cost of every instruction of the function, counting each instruction
+    /// once regardless of how often it is executed.
+    pub fn static_gas(&self) -> u64 {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.instructions.iter())
+            .map(|instr| static_gas_cost(instr.opcode))
+            .sum()
+    }
+
     pub fn empty() -> Self {
         Self {
             signature: None,
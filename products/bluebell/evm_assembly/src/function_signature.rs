@@ -1,6 +1,6 @@
 use crate::{
     block::EvmBlock,
-    types::{EvmType, EvmTypeValue},
+    types::{encode_arguments, EvmType, EvmTypeValue},
 };
 
 pub type AssemblyBuilderFn = fn(&mut EvmBlock);
@@ -13,6 +13,9 @@ pub struct EvmFunctionSignature {
 
     pub inline_assembly_generator: Option<AssemblyBuilderFn>,
     pub external_address: Option<u32>,
+    /// Types of the arguments as seen by callers through the ABI, if they differ from the types
+    /// the function operates on, e.g. `address` for a `ByStr20` held as a `uint160`.
+    pub external_arguments: Option<Vec<EvmType>>,
 }
 
 impl EvmFunctionSignature {
@@ -23,16 +26,32 @@ impl EvmFunctionSignature {
             return_type: return_type.clone(),
             inline_assembly_generator: None,
             external_address: None,
+            external_arguments: None,
         }
     }
 
+    /// Returns the types of the arguments as seen by callers.
+    pub fn external_arguments(&self) -> &Vec<EvmType> {
+        self.external_arguments.as_ref().unwrap_or(&self.arguments)
+    }
+
+    /// Returns the name without the namespace, which is the name the function is called by
+    /// from outside the contract.
+    pub fn external_name(&self) -> &str {
+        match self.name.rfind("::") {
+            Some(index) => &self.name[index + 2..],
+            None => &self.name,
+        }
+    }
+
+    /// Returns the Solidity compatible signature from which the selector is derived.
     pub fn signature(&self) -> String {
         let mut argnames = Vec::new();
-        for arg in &self.arguments {
+        for arg in self.external_arguments() {
             argnames.push(arg.signature());
         }
 
-        format!("{}({})", self.name, argnames.join(",")).to_string()
+        format!("{}({})", self.external_name(), argnames.join(",")).to_string()
     }
 
     pub fn full_signature(&self) -> String {
@@ -60,15 +79,11 @@ impl EvmFunctionSignature {
         selector
     }
 
-    pub fn generate_transaction_data(&self, args: Vec<EvmTypeValue>) -> Vec<u8> {
-        let mut data = Vec::new();
-        data.extend(self.selector());
-
-        // Encode the arguments
-        for arg in args {
-            data.extend(arg.to_bytes());
-        }
-
-        data
+    /// Returns the calldata calling the function with `args`, which are ABI encoded as the
+    /// types the function is called with from outside.
+    pub fn generate_transaction_data(&self, args: Vec<EvmTypeValue>) -> Result<Vec<u8>, String> {
+        let mut data = self.selector();
+        data.extend(encode_arguments(self.external_arguments(), &args)?);
+        Ok(data)
     }
 }
@@ -0,0 +1,148 @@
+use std::{fmt, str::FromStr};
+
+use evm::{Config, Opcode};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bytecode_ir::EvmBytecodeIr,
+    instruction::EvmInstruction,
+    opcode_spec::{opcode_mnemonic, BLOBBASEFEE, BLOBHASH, MCOPY, PUSH0, TLOAD, TSTORE},
+};
+
+/// Version of the EVM the bytecode targets. The hardfork decides which opcodes code
+/// generation may emit and how the executor runs the bytecode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hardfork {
+    #[default]
+    Istanbul,
+    Berlin,
+    London,
+    Shanghai,
+    Cancun,
+}
+
+impl Hardfork {
+    /// Returns the configuration the executor runs bytecode with. The interpreter of the pinned
+    /// evm crate predates Shanghai and would reject `PUSH0` and the Cancun opcodes as invalid
+    /// code, so code targeting those hardforks can be generated but not executed.
+    pub fn config(&self) -> Result<Config, String> {
+        match self {
+            Hardfork::Istanbul => Ok(Config::istanbul()),
+            Hardfork::Berlin => Ok(Config::berlin()),
+            Hardfork::London => Ok(Config::london()),
+            Hardfork::Shanghai | Hardfork::Cancun => Err(format!(
+                "Executing {} code is not supported, the latest executable hardfork is {}",
+                self,
+                Hardfork::London
+            )),
+        }
+    }
+
+    /// Returns the first hardfork in which `opcode` is available.
+    pub fn introducing(opcode: Opcode) -> Hardfork {
+        match opcode {
+            Opcode::BASEFEE => Hardfork::London,
+            PUSH0 => Hardfork::Shanghai,
+            TLOAD | TSTORE | MCOPY | BLOBHASH | BLOBBASEFEE => Hardfork::Cancun,
+            _ => Hardfork::Istanbul,
+        }
+    }
+
+    pub fn supports(&self, opcode: Opcode) -> bool {
+        *self >= Hardfork::introducing(opcode)
+    }
+
+    pub fn has_push0(&self) -> bool {
+        self.supports(PUSH0)
+    }
+
+    pub fn has_transient_storage(&self) -> bool {
+        self.supports(TLOAD)
+    }
+
+    /// Returns an error naming the first instruction in `ir` which is not available in this
+    /// hardfork.
+    pub fn check(&self, ir: &EvmBytecodeIr) -> Result<(), String> {
+        for function in ir.functions.iter() {
+            for block in function.blocks.iter() {
+                for instruction in block.instructions.iter() {
+                    if !self.supports(instruction.opcode) {
+                        return Err(format!(
+                            "{} in {} requires {}, but the target is {}",
+                            opcode_mnemonic(instruction.opcode),
+                            block.name,
+                            Hardfork::introducing(instruction.opcode),
+                            self
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rewrites the instructions of `ir` into their cheapest form in this hardfork. Zeros are
+    /// pushed using `PUSH0` where available and `PUSH1 0x00` otherwise. Before Cancun, `TLOAD`
+    /// and `TSTORE` fall back to `SLOAD` and `SSTORE`, so the values outlive the transaction
+    /// instead of being cleared at its end. Instructions without an equivalent, such as `MCOPY`
+    /// before Cancun, are rejected.
+    pub fn lower(&self, ir: &mut EvmBytecodeIr) -> Result<(), String> {
+        for function in ir.functions.iter_mut() {
+            for block in function.blocks.iter_mut() {
+                for instruction in block.instructions.iter_mut() {
+                    self.lower_instruction(instruction);
+                }
+            }
+        }
+
+        self.check(ir)
+    }
+
+    fn lower_instruction(&self, instruction: &mut EvmInstruction) {
+        let is_zero_push = (0x60..=0x7f).contains(&instruction.opcode.as_u8())
+            && instruction.unresolved_argument_label.is_none()
+            && instruction.arguments.iter().all(|byte| *byte == 0);
+
+        if self.has_push0() && is_zero_push {
+            instruction.opcode = PUSH0;
+            instruction.arguments = Vec::new();
+        } else if !self.has_push0() && instruction.opcode == PUSH0 {
+            instruction.opcode = Opcode::PUSH1;
+            instruction.arguments = [0x00].to_vec();
+        } else if !self.has_transient_storage() && instruction.opcode == TLOAD {
+            instruction.opcode = Opcode::SLOAD;
+        } else if !self.has_transient_storage() && instruction.opcode == TSTORE {
+            instruction.opcode = Opcode::SSTORE;
+        }
+    }
+}
+
+impl fmt::Display for Hardfork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Hardfork::Istanbul => "istanbul",
+            Hardfork::Berlin => "berlin",
+            Hardfork::London => "london",
+            Hardfork::Shanghai => "shanghai",
+            Hardfork::Cancun => "cancun",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Hardfork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "istanbul" => Ok(Hardfork::Istanbul),
+            "berlin" => Ok(Hardfork::Berlin),
+            "london" => Ok(Hardfork::London),
+            "shanghai" => Ok(Hardfork::Shanghai),
+            "cancun" => Ok(Hardfork::Cancun),
+            _ => Err(format!("Unknown hardfork {}", s)),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use evm::Opcode;
 
+use crate::opcode_spec::PUSH0;
+
 #[derive(Debug, Clone)]
 pub struct EvmSourcePosition {
     pub start: usize,
@@ -58,12 +60,7 @@ impl EvmInstruction {
 
         if leading_zeros == 4 {
             // If the u64 value is zero, we still need to ensure that the argument size is correct
-            let leading_zero_bytes = argument_size - 4;
-            if leading_zero_bytes > 0 {
-                self.arguments = vec![0; leading_zero_bytes];
-            } else {
-                self.arguments = vec![];
-            }
+            self.arguments = vec![0; argument_size];
         } else {
             let actual_size = 4 - leading_zeros;
             if actual_size > argument_size {
@@ -119,7 +116,7 @@ impl EvmInstruction {
 
     pub fn push_value_as_u64(&self) -> Option<u64> {
         match self.opcode {
-            // Opcode::PUSH0 => Some(0),
+            PUSH0 => Some(0),
             Opcode::PUSH1
             | Opcode::PUSH2
             | Opcode::PUSH3
@@ -134,8 +131,8 @@ impl EvmInstruction {
 
     pub fn push_value(&self) -> Option<Vec<u8>> {
         match self.opcode {
-            // TODO: Opcode::PUSH0 |
-            Opcode::PUSH1
+            PUSH0
+            | Opcode::PUSH1
             | Opcode::PUSH2
             | Opcode::PUSH3
             | Opcode::PUSH4
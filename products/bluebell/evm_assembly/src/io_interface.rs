@@ -15,15 +15,32 @@ pub struct CustomMemoryAccount {
     pub code: Vec<u8>,
 }
 
+/// Block information exposed to contracts through `NUMBER`, `TIMESTAMP` and `CHAINID`.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+pub struct EvmBlockInfo {
+    pub number: U256,
+    pub timestamp: U256,
+    pub chain_id: U256,
+}
+
 #[derive(Debug)]
 pub struct EvmIoInterface {
     // Backend refers to storage, not execution platform
     state: BTreeMap<H160, CustomMemoryAccount>,
+    block: EvmBlockInfo,
 }
 
 impl EvmIoInterface {
     pub fn new(state: BTreeMap<H160, CustomMemoryAccount>) -> Self {
-        Self { state }
+        Self {
+            state,
+            block: EvmBlockInfo::default(),
+        }
+    }
+
+    pub fn with_block_info(mut self, block: EvmBlockInfo) -> Self {
+        self.block = block;
+        self
     }
 }
 
@@ -41,7 +58,7 @@ impl Backend for EvmIoInterface {
     }
 
     fn block_number(&self) -> U256 {
-        U256::zero()
+        self.block.number
     }
 
     fn block_coinbase(&self) -> H160 {
@@ -49,7 +66,7 @@ impl Backend for EvmIoInterface {
     }
 
     fn block_timestamp(&self) -> U256 {
-        U256::zero()
+        self.block.timestamp
     }
 
     fn block_difficulty(&self) -> U256 {
@@ -69,18 +86,21 @@ impl Backend for EvmIoInterface {
     }
 
     fn chain_id(&self) -> U256 {
-        unimplemented!()
+        self.block.chain_id
     }
 
     fn exists(&self, address: H160) -> bool {
         self.state.contains_key(&address)
     }
 
-    fn basic(&self, _address: H160) -> Basic {
-        Basic {
-            balance: 0.into(),
-            nonce: 0.into(),
-        }
+    fn basic(&self, address: H160) -> Basic {
+        self.state
+            .get(&address)
+            .map(|v| Basic {
+                balance: v.balance,
+                nonce: v.nonce,
+            })
+            .unwrap_or_default()
     }
 
     fn code(&self, address: H160) -> Vec<u8> {
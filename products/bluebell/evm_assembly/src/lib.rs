@@ -1,16 +1,23 @@
+pub mod abi;
 pub mod block;
 pub mod bytecode_ir;
 pub mod compiler_context;
+pub mod control_flow_graph;
+mod evm_assembler;
 mod evm_bytecode_builder;
 mod evm_decompiler;
 pub mod executable;
 pub mod executor;
 pub mod function;
 pub mod function_signature;
+pub mod hardfork;
 pub mod instruction;
 pub mod io_interface;
+pub mod local_chain;
 pub mod observable_machine;
 pub mod opcode_spec;
+pub mod peephole;
+pub mod storage_layout;
 pub mod types;
 
 pub use self::{evm_bytecode_builder::EvmByteCodeBuilder, evm_decompiler::EvmAssemblyGenerator};
@@ -0,0 +1,325 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    str::FromStr,
+};
+
+use evm::{
+    backend::{Apply, Log},
+    executor::stack::{MemoryStackState, StackExecutor, StackSubstateMetadata},
+    tracing::{Event, EventListener},
+    CreateScheme, ExitReason,
+};
+use log::info;
+use primitive_types::{H160, H256, U256};
+
+use crate::{
+    compiler_context::EvmCompilerContext,
+    executable::EvmExecutable,
+    executor::{ExecutorCall, ExecutorLog, ExecutorResult},
+    hardfork::Hardfork,
+    io_interface::{CustomMemoryAccount, EvmBlockInfo, EvmIoInterface},
+    types::{encode_arguments, EvmTypeValue},
+};
+
+/// Selector of the Solidity compatible `Error(string)` revert payload.
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Decodes `Error(string)` payloads into their message. Any other payload, such as custom
+/// exceptions, is returned hex encoded.
+fn decode_revert_reason(data: &[u8]) -> String {
+    if data.len() >= 68 && data[..4] == ERROR_STRING_SELECTOR {
+        let length = U256::from_big_endian(&data[36..68]).low_u64() as usize;
+        if let Some(message) = data.get(68..68 + length) {
+            return String::from_utf8_lossy(message).to_string();
+        }
+    }
+
+    format!("0x{}", hex::encode(data))
+}
+
+/// Records the calls made during execution. Calls to precompiles are considered internal and are
+/// therefore not recorded.
+struct CallListener {
+    precompiles: BTreeSet<H160>,
+    calls: Vec<ExecutorCall>,
+}
+
+impl EventListener for CallListener {
+    fn event(&mut self, event: Event) {
+        if let Event::Call {
+            code_address,
+            transfer,
+            input,
+            ..
+        } = event
+        {
+            if self.precompiles.contains(&code_address) {
+                return;
+            }
+
+            let value = match transfer {
+                Some(transfer) => transfer.value,
+                None => U256::zero(),
+            };
+
+            self.calls.push(ExecutorCall {
+                address: format!("{:?}", code_address),
+                value: value.to_string(),
+                data: format!("0x{}", hex::encode(input)),
+            });
+        }
+    }
+}
+
+enum Transaction {
+    Call { address: H160, input: Vec<u8> },
+    Create { address: H160, init_code: Vec<u8> },
+}
+
+/// In-memory chain holding accounts with their balances, code and storage. Unlike a single
+/// execution, the state persists across transactions, allowing several contracts to be
+/// deployed and to call each other. Transactions are sent by `caller` with `value` attached,
+/// and run in the block described by `block`.
+pub struct LocalChain<'a> {
+    pub context: &'a EvmCompilerContext,
+    pub accounts: BTreeMap<H160, CustomMemoryAccount>,
+    pub block: EvmBlockInfo,
+    pub caller: H160,
+    pub value: U256,
+    pub hardfork: Hardfork,
+    snapshots: Vec<(BTreeMap<H160, CustomMemoryAccount>, EvmBlockInfo)>,
+}
+
+impl<'a> LocalChain<'a> {
+    /// Creates a chain with the funded account 0xf0 sending the transactions.
+    pub fn new(context: &'a EvmCompilerContext) -> Self {
+        let caller = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+        let mut ret = Self {
+            context,
+            accounts: BTreeMap::new(),
+            block: EvmBlockInfo::default(),
+            caller,
+            value: U256::zero(),
+            hardfork: context.hardfork,
+            snapshots: Vec::new(),
+        };
+        ret.add_account(
+            caller,
+            CustomMemoryAccount {
+                nonce: U256::one(),
+                balance: U256::from(10000000),
+                storage: BTreeMap::new(),
+                code: Vec::new(),
+            },
+        );
+
+        ret
+    }
+
+    pub fn add_account(&mut self, address: H160, account: CustomMemoryAccount) {
+        self.accounts.insert(address, account);
+    }
+
+    pub fn get_account(&self, address: H160) -> Option<&CustomMemoryAccount> {
+        self.accounts.get(&address)
+    }
+
+    pub fn set_balance(&mut self, address: H160, balance: U256) {
+        self.accounts.entry(address).or_default().balance = balance;
+    }
+
+    pub fn balance(&self, address: H160) -> U256 {
+        self.get_account(address)
+            .map(|account| account.balance)
+            .unwrap_or_default()
+    }
+
+    pub fn code(&self, address: H160) -> Vec<u8> {
+        self.get_account(address)
+            .map(|account| account.code.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn storage(&self, address: H160, index: H256) -> H256 {
+        self.get_account(address)
+            .and_then(|account| account.storage.get(&index).copied())
+            .unwrap_or_default()
+    }
+
+    /// Places `code` at `address` as is, i.e. without running a constructor.
+    pub fn deploy_code(&mut self, address: H160, code: Vec<u8>) {
+        let account = self.accounts.entry(address).or_default();
+        account.nonce = U256::one();
+        account.code = code;
+    }
+
+    /// Deploys `executable` at `address` by running its creation code with the ABI encoded
+    /// contract parameters `args` appended. The contract is created at the address following
+    /// from the caller and its nonce, and moved to `address` once the constructor succeeded.
+    /// Constructors should therefore not depend on their own address.
+    pub fn deploy(
+        &mut self,
+        address: H160,
+        executable: &EvmExecutable,
+        args: Vec<EvmTypeValue>,
+    ) -> Result<ExecutorResult, String> {
+        let mut init_code = executable.deploy_bytecode.clone();
+        init_code.extend(encode_arguments(&executable.constructor_arguments, &args)?);
+
+        self.transact(Transaction::Create { address, init_code })
+    }
+
+    /// Calls the contract at `address` with the raw transaction data `input`.
+    pub fn call(&mut self, address: H160, input: Vec<u8>) -> Result<ExecutorResult, String> {
+        self.transact(Transaction::Call { address, input })
+    }
+
+    /// Calls the function `name` declared in the context of the chain on the contract at
+    /// `address`.
+    pub fn execute(
+        &mut self,
+        address: H160,
+        name: &str,
+        args: Vec<EvmTypeValue>,
+    ) -> Result<ExecutorResult, String> {
+        let input = match self.context.get_function(name) {
+            Some(function) => function.generate_transaction_data(args)?,
+            None => return Err(format!("Function name {} not found", name)),
+        };
+
+        self.call(address, input)
+    }
+
+    /// Records the current state, returning an identifier to revert to.
+    pub fn snapshot(&mut self) -> usize {
+        self.snapshots
+            .push((self.accounts.clone(), self.block.clone()));
+        self.snapshots.len() - 1
+    }
+
+    /// Restores the state recorded by `snapshot`, discarding it along with the snapshots taken
+    /// after it.
+    pub fn revert(&mut self, snapshot: usize) -> Result<(), String> {
+        if snapshot >= self.snapshots.len() {
+            return Err(format!("Snapshot {} does not exist", snapshot));
+        }
+
+        self.snapshots.truncate(snapshot + 1);
+        let (accounts, block) = self.snapshots.pop().unwrap();
+        self.accounts = accounts;
+        self.block = block;
+        Ok(())
+    }
+
+    /// Runs `transaction` and applies its changes to the state. Fails if the hardfork of the
+    /// chain cannot be executed.
+    fn transact(&mut self, transaction: Transaction) -> Result<ExecutorResult, String> {
+        let config = self.hardfork.config()?;
+        let backend =
+            EvmIoInterface::new(self.accounts.clone()).with_block_info(self.block.clone());
+        let metadata = StackSubstateMetadata::new(u64::MAX, &config);
+        let mem_state = MemoryStackState::new(metadata, &backend);
+        let precompiles = self.context.get_precompiles();
+        let mut executor = StackExecutor::new_with_precompiles(mem_state, &config, &precompiles);
+        let mut listener = CallListener {
+            precompiles: precompiles.keys().copied().collect(),
+            calls: Vec::new(),
+        };
+
+        let (caller, value) = (self.caller, self.value);
+        let mut relocation = None;
+        let (exit_reason, result) = evm::tracing::using(&mut listener, || match transaction {
+            Transaction::Call { address, input } => {
+                executor.transact_call(caller, address, value, input, u64::MAX, Vec::new())
+            }
+            Transaction::Create { address, init_code } => {
+                let created = executor.create_address(CreateScheme::Legacy { caller });
+                relocation = Some((created, address));
+                executor.transact_create(caller, value, init_code, u64::MAX, Vec::new())
+            }
+        });
+
+        let (state_apply, logs) = executor.into_state().deconstruct();
+        info!("\n\n\nExit reason: {:#?}", exit_reason);
+        info!("Result: {:#?}", result);
+
+        let mut ret = ExecutorResult {
+            changeset: HashMap::new(),
+            logs: Vec::new(),
+            calls: listener.calls,
+            revert_reason: match exit_reason {
+                ExitReason::Revert(_) => Some(decode_revert_reason(&result)),
+                _ => None,
+            },
+            result: format!("{:?}", result),
+        };
+
+        self.apply(state_apply, logs, relocation, &mut ret);
+        Ok(ret)
+    }
+
+    /// Applies the state changes and logs of a transaction, moving the account at the first
+    /// address of `relocation` to the second.
+    fn apply<A, I, L>(
+        &mut self,
+        state_apply: A,
+        logs: L,
+        relocation: Option<(H160, H160)>,
+        ret: &mut ExecutorResult,
+    ) where
+        A: IntoIterator<Item = Apply<I>>,
+        I: IntoIterator<Item = (H256, H256)>,
+        L: IntoIterator<Item = Log>,
+    {
+        for log in logs {
+            ret.logs.push(ExecutorLog {
+                address: format!("{:?}", log.address),
+                topics: log.topics.iter().map(|t| format!("{:?}", t)).collect(),
+                data: format!("0x{}", hex::encode(&log.data)),
+            });
+        }
+
+        for update in state_apply {
+            match update {
+                Apply::Modify {
+                    address,
+                    basic,
+                    code,
+                    storage,
+                    reset_storage,
+                } => {
+                    let address = match relocation {
+                        Some((from, to)) if from == address => to,
+                        _ => address,
+                    };
+
+                    let account = self.accounts.entry(address).or_default();
+                    account.balance = basic.balance;
+                    account.nonce = basic.nonce;
+                    if let Some(code) = code {
+                        account.code = code;
+                    }
+                    if reset_storage {
+                        account.storage = BTreeMap::new();
+                    }
+
+                    for (k, v) in storage {
+                        let key = format!("{:?}.{:?}", address, k);
+                        ret.changeset.insert(key, Some(format!("{:?}", v)));
+
+                        if v == H256::zero() {
+                            account.storage.remove(&k);
+                        } else {
+                            account.storage.insert(k, v);
+                        }
+                    }
+                }
+                Apply::Delete { address } => {
+                    let key = format!("{:?}", address);
+                    ret.changeset.insert(key, None);
+                    self.accounts.remove(&address);
+                }
+            }
+        }
+    }
+}
@@ -8,7 +8,7 @@ use evm::{
     executor::stack::PrecompileFn,
     Capture,
     Capture::{Exit, Trap as CaptureTrap},
-    Context, ExitReason, Machine, Opcode, Trap,
+    Context, ExitError, ExitReason, Machine, Opcode, Trap,
 };
 use log::{error, info};
 use primitive_types::{H160, H256, U256};
@@ -27,6 +27,8 @@ pub struct ObservableMachine {
     pub error_message: Option<String>,
     pub storage: HashMap<H256, H256>,
     pub precompile_set: Option<EvmPrecompileSet>,
+    /// Output of the last call made by the contract.
+    pub return_data: Vec<u8>,
 
     pub caller: H160,
 }
@@ -61,6 +63,7 @@ impl ObservableMachine {
             error_message: None,
             storage: HashMap::new(),
             precompile_set,
+            return_data: Vec::new(),
             caller: H160::zero(),
         }
     }
@@ -140,7 +143,7 @@ impl ObservableMachine {
                             // Emulating static call
                             // TODO: Attach runtime!
                             info!("Static call");
-                            let (gas, address, args_offset, args_size, _ret_offset, _ret_size) = {
+                            let (gas, address, args_offset, args_size, ret_offset, ret_size) = {
                                 let stack = self.machine.stack_mut();
                                 let gas: u64 = match stack.pop() {
                                     Ok(g) => h160_to_usize(g.into()) as u64,
@@ -205,12 +208,21 @@ impl ObservableMachine {
                                         f(input, Some(gas), &dummy_context, &dummy_backend, true)
                                     };
 
-                                    if let Ok(_ret) = ret {
-                                        let _mem = self.machine.memory_mut().data();
-                                        // TODO: Write ret to memory
-                                        H256::zero()
+                                    if let Ok((output, _gas)) = ret {
+                                        let size = ret_size.min(output.output.len());
+                                        if self
+                                            .machine
+                                            .memory_mut()
+                                            .set(ret_offset, &output.output[..size], Some(size))
+                                            .is_err()
+                                        {
+                                            panic!("Failed to write return value to memory");
+                                        }
+                                        self.return_data = output.output;
+                                        H256::from_low_u64_be(1)
                                     } else {
                                         error!("Result error in static call");
+                                        self.return_data = Vec::new();
                                         H256::zero()
                                     }
                                 } else {
@@ -228,6 +240,49 @@ impl ObservableMachine {
                             }
                         }
 
+                        Opcode::RETURNDATASIZE => {
+                            let size = H256::from_low_u64_be(self.return_data.len() as u64);
+                            if self.machine.stack_mut().push(size).is_err() {
+                                panic!("Failed to push result to stack");
+                            }
+                        }
+                        Opcode::RETURNDATACOPY => {
+                            let (memory_offset, data_offset, size) = {
+                                let stack = self.machine.stack_mut();
+                                let mut pop = || match stack.pop() {
+                                    Ok(v) => U256::from_big_endian(v.as_bytes()),
+                                    Err(_) => panic!("Stack empty!"),
+                                };
+                                (pop(), pop(), pop())
+                            };
+
+                            // Reading beyond the return data halts the execution
+                            let data = match data_offset.checked_add(size) {
+                                Some(end) if end <= U256::from(self.return_data.len()) => {
+                                    self.return_data[data_offset.as_usize()..end.as_usize()]
+                                        .to_vec()
+                                }
+                                _ => {
+                                    self.failed = true;
+                                    self.error_message =
+                                        Some("Return data out of bounds".to_string());
+                                    return Err(Exit(ExitError::OutOfOffset.into()));
+                                }
+                            };
+                            let copied = memory_offset <= U256::from(usize::MAX)
+                                && self
+                                    .machine
+                                    .memory_mut()
+                                    .set(memory_offset.as_usize(), &data, Some(data.len()))
+                                    .is_ok();
+                            if !data.is_empty() && !copied {
+                                self.failed = true;
+                                self.error_message =
+                                    Some("Failed to copy return data to memory".to_string());
+                                return Err(Exit(ExitError::InvalidRange.into()));
+                            }
+                        }
+
                         _ => {
                             self.failed = true;
                             self.error_message = Some(format!("{:?}", opcode).to_string());
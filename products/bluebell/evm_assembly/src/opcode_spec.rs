@@ -2,6 +2,14 @@ use std::collections::HashMap;
 
 use evm::Opcode;
 
+// Opcodes introduced after London, which the EVM crate has no names for
+pub const PUSH0: Opcode = Opcode(0x5f);
+pub const TLOAD: Opcode = Opcode(0x5c);
+pub const TSTORE: Opcode = Opcode(0x5d);
+pub const MCOPY: Opcode = Opcode(0x5e);
+pub const BLOBHASH: Opcode = Opcode(0x49);
+pub const BLOBBASEFEE: Opcode = Opcode(0x4a);
+
 #[derive(Debug, Clone)]
 pub struct OpcodeSpecification {
     pub opcode: Opcode,
@@ -230,6 +238,12 @@ impl OpcodeSpec for Opcode {
             Opcode::STATICCALL => 6,
             Opcode::SUICIDE => 0,
             Opcode::CHAINID => 0,
+            PUSH0 => 0,
+            TLOAD => 1,
+            TSTORE => 2,
+            MCOPY => 3,
+            BLOBHASH => 1,
+            BLOBBASEFEE => 0,
             _ => todo!(),
         }
     }
@@ -380,19 +394,258 @@ impl OpcodeSpec for Opcode {
             Opcode::STATICCALL => 1,
             Opcode::SUICIDE => 0,
             Opcode::CHAINID => 1,
+            PUSH0 => 1,
+            TLOAD => 1,
+            TSTORE => 0,
+            MCOPY => 0,
+            BLOBHASH => 1,
+            BLOBBASEFEE => 1,
             _ => todo!(),
         }
     }
 }
 
+/// Mnemonics of the opcodes as written in assembly. Alternative names follow the names the
+/// opcodes are printed with.
+const MNEMONICS: &[(&str, Opcode)] = &[
+    ("STOP", Opcode::STOP),
+    ("ADD", Opcode::ADD),
+    ("MUL", Opcode::MUL),
+    ("SUB", Opcode::SUB),
+    ("DIV", Opcode::DIV),
+    ("SDIV", Opcode::SDIV),
+    ("MOD", Opcode::MOD),
+    ("SMOD", Opcode::SMOD),
+    ("ADDMOD", Opcode::ADDMOD),
+    ("MULMOD", Opcode::MULMOD),
+    ("EXP", Opcode::EXP),
+    ("SIGNEXTEND", Opcode::SIGNEXTEND),
+    ("LT", Opcode::LT),
+    ("GT", Opcode::GT),
+    ("SLT", Opcode::SLT),
+    ("SGT", Opcode::SGT),
+    ("EQ", Opcode::EQ),
+    ("ISZERO", Opcode::ISZERO),
+    ("AND", Opcode::AND),
+    ("OR", Opcode::OR),
+    ("XOR", Opcode::XOR),
+    ("NOT", Opcode::NOT),
+    ("BYTE", Opcode::BYTE),
+    ("SHL", Opcode::SHL),
+    ("SHR", Opcode::SHR),
+    ("SAR", Opcode::SAR),
+    ("SHA3", Opcode::SHA3),
+    ("ADDRESS", Opcode::ADDRESS),
+    ("BALANCE", Opcode::BALANCE),
+    ("ORIGIN", Opcode::ORIGIN),
+    ("CALLER", Opcode::CALLER),
+    ("CALLVALUE", Opcode::CALLVALUE),
+    ("CALLDATALOAD", Opcode::CALLDATALOAD),
+    ("CALLDATASIZE", Opcode::CALLDATASIZE),
+    ("CALLDATACOPY", Opcode::CALLDATACOPY),
+    ("CODESIZE", Opcode::CODESIZE),
+    ("CODECOPY", Opcode::CODECOPY),
+    ("GASPRICE", Opcode::GASPRICE),
+    ("EXTCODESIZE", Opcode::EXTCODESIZE),
+    ("EXTCODECOPY", Opcode::EXTCODECOPY),
+    ("RETURNDATASIZE", Opcode::RETURNDATASIZE),
+    ("RETURNDATACOPY", Opcode::RETURNDATACOPY),
+    ("EXTCODEHASH", Opcode::EXTCODEHASH),
+    ("BLOCKHASH", Opcode::BLOCKHASH),
+    ("COINBASE", Opcode::COINBASE),
+    ("TIMESTAMP", Opcode::TIMESTAMP),
+    ("NUMBER", Opcode::NUMBER),
+    ("DIFFICULTY", Opcode::DIFFICULTY),
+    ("GASLIMIT", Opcode::GASLIMIT),
+    ("CHAINID", Opcode::CHAINID),
+    ("SELFBALANCE", Opcode::SELFBALANCE),
+    ("BASEFEE", Opcode::BASEFEE),
+    ("BLOBHASH", BLOBHASH),
+    ("BLOBBASEFEE", BLOBBASEFEE),
+    ("POP", Opcode::POP),
+    ("MLOAD", Opcode::MLOAD),
+    ("MSTORE", Opcode::MSTORE),
+    ("MSTORE8", Opcode::MSTORE8),
+    ("SLOAD", Opcode::SLOAD),
+    ("SSTORE", Opcode::SSTORE),
+    ("JUMP", Opcode::JUMP),
+    ("JUMPI", Opcode::JUMPI),
+    ("PC", Opcode::PC),
+    ("MSIZE", Opcode::MSIZE),
+    ("GAS", Opcode::GAS),
+    ("JUMPDEST", Opcode::JUMPDEST),
+    ("TLOAD", TLOAD),
+    ("TSTORE", TSTORE),
+    ("MCOPY", MCOPY),
+    ("PUSH0", PUSH0),
+    ("PUSH1", Opcode::PUSH1),
+    ("PUSH2", Opcode::PUSH2),
+    ("PUSH3", Opcode::PUSH3),
+    ("PUSH4", Opcode::PUSH4),
+    ("PUSH5", Opcode::PUSH5),
+    ("PUSH6", Opcode::PUSH6),
+    ("PUSH7", Opcode::PUSH7),
+    ("PUSH8", Opcode::PUSH8),
+    ("PUSH9", Opcode::PUSH9),
+    ("PUSH10", Opcode::PUSH10),
+    ("PUSH11", Opcode::PUSH11),
+    ("PUSH12", Opcode::PUSH12),
+    ("PUSH13", Opcode::PUSH13),
+    ("PUSH14", Opcode::PUSH14),
+    ("PUSH15", Opcode::PUSH15),
+    ("PUSH16", Opcode::PUSH16),
+    ("PUSH17", Opcode::PUSH17),
+    ("PUSH18", Opcode::PUSH18),
+    ("PUSH19", Opcode::PUSH19),
+    ("PUSH20", Opcode::PUSH20),
+    ("PUSH21", Opcode::PUSH21),
+    ("PUSH22", Opcode::PUSH22),
+    ("PUSH23", Opcode::PUSH23),
+    ("PUSH24", Opcode::PUSH24),
+    ("PUSH25", Opcode::PUSH25),
+    ("PUSH26", Opcode::PUSH26),
+    ("PUSH27", Opcode::PUSH27),
+    ("PUSH28", Opcode::PUSH28),
+    ("PUSH29", Opcode::PUSH29),
+    ("PUSH30", Opcode::PUSH30),
+    ("PUSH31", Opcode::PUSH31),
+    ("PUSH32", Opcode::PUSH32),
+    ("DUP1", Opcode::DUP1),
+    ("DUP2", Opcode::DUP2),
+    ("DUP3", Opcode::DUP3),
+    ("DUP4", Opcode::DUP4),
+    ("DUP5", Opcode::DUP5),
+    ("DUP6", Opcode::DUP6),
+    ("DUP7", Opcode::DUP7),
+    ("DUP8", Opcode::DUP8),
+    ("DUP9", Opcode::DUP9),
+    ("DUP10", Opcode::DUP10),
+    ("DUP11", Opcode::DUP11),
+    ("DUP12", Opcode::DUP12),
+    ("DUP13", Opcode::DUP13),
+    ("DUP14", Opcode::DUP14),
+    ("DUP15", Opcode::DUP15),
+    ("DUP16", Opcode::DUP16),
+    ("SWAP1", Opcode::SWAP1),
+    ("SWAP2", Opcode::SWAP2),
+    ("SWAP3", Opcode::SWAP3),
+    ("SWAP4", Opcode::SWAP4),
+    ("SWAP5", Opcode::SWAP5),
+    ("SWAP6", Opcode::SWAP6),
+    ("SWAP7", Opcode::SWAP7),
+    ("SWAP8", Opcode::SWAP8),
+    ("SWAP9", Opcode::SWAP9),
+    ("SWAP10", Opcode::SWAP10),
+    ("SWAP11", Opcode::SWAP11),
+    ("SWAP12", Opcode::SWAP12),
+    ("SWAP13", Opcode::SWAP13),
+    ("SWAP14", Opcode::SWAP14),
+    ("SWAP15", Opcode::SWAP15),
+    ("SWAP16", Opcode::SWAP16),
+    ("LOG0", Opcode::LOG0),
+    ("LOG1", Opcode::LOG1),
+    ("LOG2", Opcode::LOG2),
+    ("LOG3", Opcode::LOG3),
+    ("LOG4", Opcode::LOG4),
+    ("CREATE", Opcode::CREATE),
+    ("CALL", Opcode::CALL),
+    ("CALLCODE", Opcode::CALLCODE),
+    ("RETURN", Opcode::RETURN),
+    ("DELEGATECALL", Opcode::DELEGATECALL),
+    ("CREATE2", Opcode::CREATE2),
+    ("STATICCALL", Opcode::STATICCALL),
+    ("REVERT", Opcode::REVERT),
+    ("INVALID", Opcode::INVALID),
+    ("EOFMAGIC", Opcode::EOFMAGIC),
+    ("SUICIDE", Opcode::SUICIDE),
+    ("KECCAK256", Opcode::SHA3),
+    ("PREVRANDAO", Opcode::DIFFICULTY),
+    ("SELFDESTRUCT", Opcode::SUICIDE),
+];
+
+/// Returns the opcode with the mnemonic `name`, ignoring case.
+pub fn opcode_from_mnemonic(name: &str) -> Option<Opcode> {
+    let name = name.to_uppercase();
+    MNEMONICS
+        .iter()
+        .find(|(mnemonic, _)| *mnemonic == name)
+        .map(|(_, opcode)| *opcode)
+}
+
+/// Returns whether `opcode` is an instruction rather than an undefined byte.
+pub fn is_known_opcode(opcode: Opcode) -> bool {
+    MNEMONICS.iter().any(|(_, candidate)| *candidate == opcode)
+}
+
+/// Returns the mnemonic of `opcode`, or its value in hex if it is unknown.
+pub fn opcode_mnemonic(opcode: Opcode) -> String {
+    match MNEMONICS.iter().find(|(_, candidate)| *candidate == opcode) {
+        Some((mnemonic, _)) => mnemonic.to_string(),
+        None => format!("0x{:02x}", opcode.as_u8()),
+    }
+}
+
+/// Returns the static gas cost of `opcode` under the Cancun schedule. State access is charged
+/// as cold and `SSTORE` as setting a zero slot, i.e. the worst case. Dynamic costs such as
+/// memory expansion, copied words and value transfers are not included.
+pub fn static_gas_cost(opcode: Opcode) -> u64 {
+    match opcode.as_u8() {
+        0x00 | 0xf3 | 0xfd | 0xfe => 0,    // STOP, RETURN, REVERT, INVALID
+        0x01 | 0x03 | 0x10..=0x1d => 3,    // ADD, SUB, comparison and bitwise
+        0x02 | 0x04..=0x07 | 0x0b => 5,    // MUL, DIV, SDIV, MOD, SMOD, SIGNEXTEND
+        0x08 | 0x09 => 8,                  // ADDMOD, MULMOD
+        0x0a => 10,                        // EXP
+        0x20 => 30,                        // KECCAK256
+        0x31 | 0x3b | 0x3c | 0x3f => 2600, // BALANCE, EXTCODESIZE, EXTCODECOPY, EXTCODEHASH
+        0x35 | 0x37 | 0x39 | 0x3e => 3,    // CALLDATALOAD and copies
+        0x30..=0x3f => 2,                  // Environment
+        0x40 => 20,                        // BLOCKHASH
+        0x47 => 5,                         // SELFBALANCE
+        0x49 => 3,                         // BLOBHASH
+        0x41..=0x4a => 2,                  // Block information
+        0x50 | 0x58..=0x5a | 0x5f => 2,    // POP, PC, MSIZE, GAS, PUSH0
+        0x51..=0x53 | 0x5e => 3,           // MLOAD, MSTORE, MSTORE8, MCOPY
+        0x54 => 2100,                      // SLOAD
+        0x55 => 22100,                     // SSTORE
+        0x56 => 8,                         // JUMP
+        0x57 => 10,                        // JUMPI
+        0x5b => 1,                         // JUMPDEST
+        0x5c | 0x5d => 100,                // TLOAD, TSTORE
+        0x60..=0x9f => 3,                  // PUSH, DUP and SWAP
+        n @ 0xa0..=0xa4 => 375 * (n as u64 - 0x9f), // LOG0 to LOG4
+        0xf0 | 0xf5 => 32000,              // CREATE, CREATE2
+        0xf1 | 0xf2 | 0xf4 | 0xfa => 2600, // Calls
+        0xff => 5000,                      // SELFDESTRUCT
+        _ => 0,
+    }
+}
+
 // TODO: Finish the spec
 pub fn create_opcode_spec() -> HashMap<u8, OpcodeSpecification> {
     let spec: HashMap<u8, OpcodeSpecification> = [
         (
-            // TODO: Change PUSH0 once the Zilliqa EVM is upgraded
-            // TODO: Ugly hack PUSH0 -> PUSH1
-            0x5f,
-            OpcodeSpecification::new(Opcode::PUSH1, 0, 0, false, 0),
+            PUSH0.as_u8(),
+            OpcodeSpecification::new(PUSH0, 0, 1, false, 0),
+        ),
+        (
+            TLOAD.as_u8(),
+            OpcodeSpecification::new(TLOAD, 1, 1, false, 0),
+        ),
+        (
+            TSTORE.as_u8(),
+            OpcodeSpecification::new(TSTORE, 2, 0, false, 0),
+        ),
+        (
+            MCOPY.as_u8(),
+            OpcodeSpecification::new(MCOPY, 3, 0, false, 0),
+        ),
+        (
+            BLOBHASH.as_u8(),
+            OpcodeSpecification::new(BLOBHASH, 1, 1, false, 0),
+        ),
+        (
+            BLOBBASEFEE.as_u8(),
+            OpcodeSpecification::new(BLOBBASEFEE, 0, 1, false, 0),
         ),
         (
             Opcode::STOP.as_u8(),
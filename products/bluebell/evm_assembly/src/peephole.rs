@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+
+use evm::Opcode;
+use primitive_types::U256;
+
+use crate::{
+    bytecode_ir::EvmBytecodeIr, instruction::EvmInstruction, opcode_spec::static_gas_cost,
+};
+
+/// Bytes and gas saved by the peephole optimiser in a single function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionSavings {
+    pub name: String,
+    pub bytes_saved: i64,
+    pub gas_saved: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PeepholeReport {
+    pub functions: Vec<FunctionSavings>,
+}
+
+impl PeepholeReport {
+    pub fn bytes_saved(&self) -> i64 {
+        self.functions.iter().map(|f| f.bytes_saved).sum()
+    }
+
+    pub fn gas_saved(&self) -> i64 {
+        self.functions.iter().map(|f| f.gas_saved).sum()
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut ret = String::new();
+        for function in self.functions.iter() {
+            ret.push_str(&format!(
+                "{}: {} bytes, {} gas saved\n",
+                function.name, function.bytes_saved, function.gas_saved
+            ));
+        }
+        ret.push_str(&format!(
+            "Total: {} bytes, {} gas saved\n",
+            self.bytes_saved(),
+            self.gas_saved()
+        ));
+        ret
+    }
+}
+
+/// Size of the instruction in the bytecode.
+fn instruction_size(instr: &EvmInstruction) -> i64 {
+    1 + instr.expected_args_length() as i64
+}
+
+/// Static gas cost of the instruction, see `static_gas_cost`.
+fn static_gas(instr: &EvmInstruction) -> i64 {
+    static_gas_cost(instr.opcode) as i64
+}
+
+fn is_push(instr: &EvmInstruction) -> bool {
+    (0x60..=0x7f).contains(&instr.opcode.as_u8())
+}
+
+fn is_dup(instr: &EvmInstruction) -> bool {
+    (0x80..=0x8f).contains(&instr.opcode.as_u8())
+}
+
+fn is_swap(instr: &EvmInstruction) -> bool {
+    (0x90..=0x9f).contains(&instr.opcode.as_u8())
+}
+
+/// Whether the instruction pushes a constant known at compile time, as opposed to a label
+/// whose position is only known once the bytecode is laid out.
+fn is_constant_push(instr: &EvmInstruction) -> bool {
+    is_push(instr) && instr.unresolved_argument_label.is_none()
+}
+
+/// Instructions marking positions which are jumped to can neither be removed nor merged.
+fn is_removable(instr: &EvmInstruction) -> bool {
+    instr.label.is_none()
+}
+
+fn push_instruction(value: U256, template: &EvmInstruction) -> EvmInstruction {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    let width = std::cmp::max(1, (value.bits() + 7) / 8);
+
+    let mut ret = template.clone();
+    ret.opcode = Opcode(0x5f + width as u8);
+    ret.arguments = bytes[32 - width..].to_vec();
+    ret.unresolved_argument_label = None;
+    ret
+}
+
+/// Evaluates `opcode` with `top` being the topmost stack element.
+fn evaluate(opcode: &Opcode, top: U256, second: U256) -> Option<U256> {
+    match *opcode {
+        Opcode::ADD => Some(top.overflowing_add(second).0),
+        Opcode::SUB => Some(top.overflowing_sub(second).0),
+        Opcode::MUL => Some(top.overflowing_mul(second).0),
+        Opcode::AND => Some(top & second),
+        Opcode::OR => Some(top | second),
+        Opcode::XOR => Some(top ^ second),
+        _ => None,
+    }
+}
+
+/// Peephole optimiser rewriting short instruction sequences within blocks:
+///
+/// - `PUSH x; POP` and `DUPn; POP` are removed,
+/// - `SWAPn; SWAPn` is removed,
+/// - `PUSH a; PUSH b; ADD` and similar arithmetic is replaced by a single push,
+/// - constants are pushed using the fewest bytes possible,
+/// - `JUMPDEST`s starting blocks which are never jumped to are removed.
+///
+/// The optimiser must run before positions are resolved.
+pub struct PeepholeOptimiser {}
+
+impl PeepholeOptimiser {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn optimise(&mut self, ir: &mut EvmBytecodeIr) -> PeepholeReport {
+        // Labels which are jumped to or pushed as return addresses
+        let referenced: HashSet<String> = ir
+            .functions
+            .iter()
+            .flat_map(|function| function.blocks.iter())
+            .flat_map(|block| block.instructions.iter())
+            .filter_map(|instr| instr.unresolved_argument_label.clone())
+            .collect();
+
+        let mut report = PeepholeReport::default();
+        for (index, function) in ir.functions.iter_mut().enumerate() {
+            let name = match &function.signature {
+                Some(signature) => signature.name.clone(),
+                None => format!("function_{}", index),
+            };
+
+            let mut bytes_saved = 0;
+            let mut gas_saved = 0;
+            for (i, block) in function.blocks.iter_mut().enumerate() {
+                let before = block.instructions.clone();
+
+                Self::optimise_instructions(&mut block.instructions);
+
+                // The entry block of a function may be called by its name
+                if i > 0 && !referenced.contains(&block.name) {
+                    if let Some(first) = block.instructions.first() {
+                        if first.opcode == Opcode::JUMPDEST && is_removable(first) {
+                            block.instructions.remove(0);
+                        }
+                    }
+                }
+
+                bytes_saved += before.iter().map(instruction_size).sum::<i64>()
+                    - block.instructions.iter().map(instruction_size).sum::<i64>();
+                gas_saved += before.iter().map(static_gas).sum::<i64>()
+                    - block.instructions.iter().map(static_gas).sum::<i64>();
+            }
+
+            report.functions.push(FunctionSavings {
+                name,
+                bytes_saved,
+                gas_saved,
+            });
+        }
+
+        report
+    }
+
+    /// Applies the rewrite rules until none of them matches.
+    fn optimise_instructions(instructions: &mut Vec<EvmInstruction>) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            let mut i = 0;
+            while i < instructions.len() {
+                if Self::rewrite_at(instructions, i) {
+                    changed = true;
+                    // Rewrites may enable a match starting at one of the previous instructions
+                    i = i.saturating_sub(2);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    /// Attempts to rewrite the instructions starting at `i`. Returns true if a rule matched.
+    fn rewrite_at(instructions: &mut Vec<EvmInstruction>, i: usize) -> bool {
+        let first = &instructions[i];
+        if !is_removable(first) {
+            return false;
+        }
+
+        if let Some(second) = instructions.get(i + 1) {
+            if is_removable(second) {
+                // Values pushed only to be removed again
+                if (is_push(first) || is_dup(first)) && second.opcode == Opcode::POP {
+                    instructions.drain(i..i + 2);
+                    return true;
+                }
+
+                // Swaps cancelling each other
+                if is_swap(first) && first.opcode == second.opcode {
+                    instructions.drain(i..i + 2);
+                    return true;
+                }
+
+                if let Some(third) = instructions.get(i + 2) {
+                    if is_constant_push(first) && is_constant_push(second) && is_removable(third) {
+                        let value = evaluate(
+                            &third.opcode,
+                            U256::from_big_endian(&second.arguments),
+                            U256::from_big_endian(&first.arguments),
+                        );
+                        if let Some(value) = value {
+                            let folded = push_instruction(value, third);
+                            instructions.splice(i..i + 3, [folded]);
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Constants padded with leading zeros
+        if is_constant_push(first) {
+            let value = U256::from_big_endian(&first.arguments);
+            let narrowed = push_instruction(value, first);
+            if narrowed.arguments.len() < first.arguments.len() {
+                instructions[i] = narrowed;
+                return true;
+            }
+        }
+
+        false
+    }
+}
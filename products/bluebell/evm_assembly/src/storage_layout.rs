@@ -0,0 +1,176 @@
+use primitive_types::U256;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Keccak256};
+
+use crate::types::EvmType;
+
+/// How a field is laid out in contract storage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageEncoding {
+    /// Stored in `slot` starting at byte `offset`, counted from the least significant byte.
+    /// Fields smaller than a word share slots unless they are alone in their slot, in which
+    /// case they occupy all of it.
+    Inplace,
+    /// Object consisting of a 4 byte length followed by the data, stored a word at a time
+    /// from `keccak256(slot)` as it is laid out in memory.
+    Bytes,
+    /// Word stored at `keccak256(slot)`, leaving the slots following it for the data of the
+    /// constructors.
+    Adt,
+    /// Entries stored at `keccak256(key . slot)`, hashing the key of every level of nested maps
+    /// in turn. The slot following an entry holds whether it exists and how often it was
+    /// deleted, which is hashed into the slots of the entries of a nested map.
+    Mapping,
+    /// Contract parameter stored as a word appended to the code when the contract is
+    /// deployed.
+    Immutable,
+}
+
+/// How the values of a type are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageClass {
+    pub encoding: StorageEncoding,
+    /// Size in bytes of the values stored in place.
+    pub size: u64,
+    /// Whether values are signed integers, which are sign extended when loaded from a slot
+    /// they share with other fields.
+    pub signed: bool,
+}
+
+impl StorageClass {
+    pub fn of(evm_type: &EvmType) -> Self {
+        let (encoding, size, signed) = match evm_type {
+            EvmType::Uint(bits) => (StorageEncoding::Inplace, (bits + 7) / 8, false),
+            EvmType::Int(bits) => (StorageEncoding::Inplace, (bits + 7) / 8, true),
+            EvmType::Bool => (StorageEncoding::Inplace, 1, false),
+            EvmType::Address => (StorageEncoding::Inplace, 20, false),
+            EvmType::Bytes(size) if *size <= 32 => (StorageEncoding::Inplace, *size, false),
+            EvmType::Bytes(_) | EvmType::String | EvmType::DynamicBytes | EvmType::Array(_) => {
+                (StorageEncoding::Bytes, 32, false)
+            }
+            EvmType::UserType(_) => (StorageEncoding::Adt, 32, false),
+        };
+
+        Self {
+            encoding,
+            size: size.max(1) as u64,
+            signed,
+        }
+    }
+
+    pub fn adt() -> Self {
+        Self {
+            encoding: StorageEncoding::Adt,
+            size: 32,
+            signed: false,
+        }
+    }
+
+    pub fn mapping() -> Self {
+        Self {
+            encoding: StorageEncoding::Mapping,
+            size: 32,
+            signed: false,
+        }
+    }
+}
+
+/// Returns the slot from which the data of a field stored outside `slot` begins.
+pub fn derived_slot(slot: U256) -> U256 {
+    let mut bytes = [0u8; 32];
+    slot.to_big_endian(&mut bytes);
+    U256::from_big_endian(&Keccak256::digest(bytes))
+}
+
+fn serialize_u256<S>(value: &U256, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+fn deserialize_u256<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    let digits = value.strip_prefix("0x").unwrap_or(&value);
+    U256::from_str_radix(digits, 16).map_err(serde::de::Error::custom)
+}
+
+/// Field stored in a storage slot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StorageEntry {
+    pub label: String,
+    #[serde(rename = "type")]
+    pub typename: String,
+    #[serde(
+        serialize_with = "serialize_u256",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub slot: U256,
+    pub offset: u64,
+    pub size: u64,
+    pub encoding: StorageEncoding,
+}
+
+impl StorageEntry {
+    /// Returns the slot holding the first word of the value.
+    pub fn data_slot(&self) -> U256 {
+        match self.encoding {
+            StorageEncoding::Bytes | StorageEncoding::Adt => derived_slot(self.slot),
+            _ => self.slot,
+        }
+    }
+
+    /// Extracts the value of an in place field from the word stored in its slot.
+    pub fn decode(&self, word: U256) -> U256 {
+        if self.size >= 32 {
+            return word;
+        }
+        let mask = (U256::one() << (8 * self.size)) - 1;
+        (word >> (8 * self.offset)) & mask
+    }
+}
+
+/// Contract parameter stored in the code of the contract.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImmutableEntry {
+    pub label: String,
+    #[serde(rename = "type")]
+    pub typename: String,
+    /// Position of the word holding the value in the bytecode.
+    pub offset: u64,
+}
+
+/// Storage layout manifest of a contract, which allows external tools to decode its storage.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageEntry>,
+    pub immutables: Vec<ImmutableEntry>,
+}
+
+impl StorageLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, label: &str) -> Option<&StorageEntry> {
+        self.storage.iter().find(|entry| entry.label == label)
+    }
+
+    pub fn get_immutable(&self, label: &str) -> Option<&ImmutableEntry> {
+        self.immutables.iter().find(|entry| entry.label == label)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+}
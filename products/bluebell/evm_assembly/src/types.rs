@@ -1,36 +1,334 @@
 use core::str::FromStr;
 
-use primitive_types::U256;
+use primitive_types::{H160, U256};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+use sha3::{Digest, Keccak256};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum EvmTypeValue {
     Uint32(u32),
     Uint64(u64),
+    Uint128(u128),
     Uint256(U256),
+    Int32(i32),
+    Int64(i64),
+    Int128(i128),
+    /// Signed integer in two's complement.
+    Int256(U256),
+    Bool(bool),
+    Address(H160),
+    /// Byte string of fixed length, i.e. `ByStrN`.
+    BytesN(Vec<u8>),
+    /// Byte string of arbitrary length, i.e. `ByStr`.
+    Bytes(Vec<u8>),
     String(String),
-    // Address(Address),
-    // Add more types as needed
+    /// Value of an algebraic data type. `argtypes` are the type arguments of the type, e.g.
+    /// `Uint32` for `Option Uint32`.
+    Adt {
+        constructor: String,
+        argtypes: Vec<String>,
+        arguments: Vec<EvmTypeValue>,
+    },
     StackReference(u32),
 }
 
+/// Parameter of a message in the JSON format used by the Scilla tooling, such as
+/// `{"vname": "to", "type": "ByStr20", "value": "0x..."}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScillaParameter {
+    pub vname: String,
+    #[serde(rename = "type")]
+    pub typename: String,
+    pub value: Value,
+}
+
+impl ScillaParameter {
+    pub fn to_evm_value(&self) -> Result<EvmTypeValue, String> {
+        EvmTypeValue::from_scilla_json(&self.typename, &self.value)
+            .map_err(|e| format!("Invalid value of parameter `{}`: {}", self.vname, e))
+    }
+}
+
+/// Parses the arguments of a call given either as an array of values or as a message with its
+/// arguments in `params`, as accepted by `scilla-runner`. Values may be given plainly or as
+/// Scilla parameters, which carry their type.
+pub fn arguments_from_json(json: &str) -> Result<Vec<EvmTypeValue>, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let values = match value {
+        Value::Array(values) => values,
+        Value::Object(mut message) => match message.remove("params") {
+            Some(Value::Array(values)) => values,
+            _ => return Err("Expected the arguments of the message in `params`".to_string()),
+        },
+        _ => return Err("Expected an array of arguments or a message".to_string()),
+    };
+
+    values
+        .into_iter()
+        .map(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn to_hex_string(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex_string(value: &str) -> Result<Vec<u8>, String> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(digits).map_err(|e| format!("Invalid byte string {}: {}", value, e))
+}
+
+/// Returns the digits of an integer, which Scilla gives as a string.
+fn integer_text(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(number) => Ok(number.to_string()),
+        _ => Err(format!("Expected an integer, found {}", value)),
+    }
+}
+
+fn parse_integer<T: FromStr>(value: &Value) -> Result<T, String> {
+    let text = integer_text(value)?;
+    text.parse::<T>()
+        .map_err(|_| format!("Integer {} out of range", text))
+}
+
+/// Parses a signed 256 bit integer into its two's complement.
+fn parse_int256(value: &Value) -> Result<U256, String> {
+    let text = integer_text(value)?;
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.as_str()),
+    };
+    let magnitude =
+        U256::from_dec_str(digits).map_err(|_| format!("Integer {} out of range", text))?;
+
+    let limit = U256::one() << 255;
+    if negative && magnitude <= limit {
+        Ok((!magnitude).overflowing_add(U256::one()).0)
+    } else if !negative && magnitude < limit {
+        Ok(magnitude)
+    } else {
+        Err(format!("Integer {} out of range", text))
+    }
+}
+
+/// Returns the 32 byte two's complement of `value`.
+fn signed_word(value: i128) -> Vec<u8> {
+    let mut ret = vec![if value < 0 { 0xff } else { 0x00 }; 16];
+    ret.extend(value.to_be_bytes());
+    ret
+}
+
+/// Returns the argument types of a constructor of the builtin algebraic data types `Bool`,
+/// `Option`, `Pair`, `List` and `Nat` given the type arguments, or `None` for any other
+/// constructor.
+fn constructor_argument_types(constructor: &str, argtypes: &[String]) -> Option<Vec<String>> {
+    let ret = match (constructor, argtypes) {
+        ("True" | "False" | "Zero", []) | ("None" | "Nil", [_]) => Vec::new(),
+        ("Some", [a]) => vec![a.clone()],
+        ("Pair", [a, b]) => vec![a.clone(), b.clone()],
+        ("Cons", [a]) => vec![a.clone(), format!("List ({})", a)],
+        ("Succ", []) => vec!["Nat".to_string()],
+        _ => return None,
+    };
+
+    Some(ret)
+}
+
+impl EvmTypeValue {
+    /// Parses `value` given in the Scilla JSON format as a value of the Scilla type `typename`.
+    pub fn from_scilla_json(typename: &str, value: &Value) -> Result<Self, String> {
+        let mut typename = typename.trim();
+        if typename.starts_with('(') && typename.ends_with(')') {
+            typename = &typename[1..typename.len() - 1];
+        }
+
+        let ret = match typename {
+            "Uint32" => EvmTypeValue::Uint32(parse_integer(value)?),
+            "Uint64" => EvmTypeValue::Uint64(parse_integer(value)?),
+            "Uint128" => EvmTypeValue::Uint128(parse_integer(value)?),
+            "Uint256" | "BNum" => {
+                let text = integer_text(value)?;
+                EvmTypeValue::Uint256(
+                    U256::from_dec_str(&text)
+                        .map_err(|_| format!("Integer {} out of range", text))?,
+                )
+            }
+            "Int32" => EvmTypeValue::Int32(parse_integer(value)?),
+            "Int64" => EvmTypeValue::Int64(parse_integer(value)?),
+            "Int128" => EvmTypeValue::Int128(parse_integer(value)?),
+            "Int256" => EvmTypeValue::Int256(parse_int256(value)?),
+            "String" => match value {
+                Value::String(value) => EvmTypeValue::String(value.clone()),
+                _ => return Err(format!("Expected a string, found {}", value)),
+            },
+            "Bool" => match serde_json::from_value::<EvmTypeValue>(value.clone()) {
+                Ok(EvmTypeValue::Bool(value)) => EvmTypeValue::Bool(value),
+                _ => return Err(format!("Expected True or False, found {}", value)),
+            },
+            "ByStr" | "ByStr20" => {
+                let bytes = match value {
+                    Value::String(value) => from_hex_string(value)?,
+                    _ => return Err(format!("Expected a byte string, found {}", value)),
+                };
+                if typename == "ByStr" {
+                    EvmTypeValue::Bytes(bytes)
+                } else if bytes.len() == 20 {
+                    EvmTypeValue::Address(H160::from_slice(&bytes))
+                } else {
+                    return Err(format!("Expected an address of 20 bytes, found {}", value));
+                }
+            }
+            name if name.starts_with("ByStr") => {
+                let size = name[5..]
+                    .parse::<usize>()
+                    .map_err(|_| format!("Unknown type {}", name))?;
+                let bytes = match value {
+                    Value::String(value) => from_hex_string(value)?,
+                    _ => return Err(format!("Expected a byte string, found {}", value)),
+                };
+                if bytes.len() != size {
+                    return Err(format!("Expected {} bytes, found {}", size, value));
+                }
+                EvmTypeValue::BytesN(bytes)
+            }
+            _ => Self::adt_from_scilla_json(typename, value)?,
+        };
+
+        Ok(ret)
+    }
+
+    /// Parses a value of an algebraic data type. The arguments of the constructors of the builtin
+    /// types are typed from their declarations, while those of other types are parsed untyped.
+    fn adt_from_scilla_json(typename: &str, value: &Value) -> Result<Self, String> {
+        let constructor = match value.get("constructor") {
+            Some(Value::String(constructor)) => constructor.clone(),
+            _ => return Err(format!("Unsupported type {}", typename)),
+        };
+        let argtypes: Vec<String> = match value.get("argtypes") {
+            Some(argtypes) => {
+                serde_json::from_value(argtypes.clone()).map_err(|e| e.to_string())?
+            }
+            None => Vec::new(),
+        };
+        let arguments: Vec<Value> = match value.get("arguments") {
+            Some(arguments) => {
+                serde_json::from_value(arguments.clone()).map_err(|e| e.to_string())?
+            }
+            None => Vec::new(),
+        };
+
+        let arguments = match constructor_argument_types(&constructor, &argtypes) {
+            Some(types) if types.len() != arguments.len() => {
+                return Err(format!(
+                    "Constructor {} expects {} arguments, found {}",
+                    constructor,
+                    types.len(),
+                    arguments.len()
+                ))
+            }
+            Some(types) => arguments
+                .iter()
+                .zip(types.iter())
+                .map(|(argument, argtype)| Self::from_scilla_json(argtype, argument))
+                .collect::<Result<Vec<_>, String>>()?,
+            None => arguments
+                .into_iter()
+                .map(|argument| serde_json::from_value(argument).map_err(|e| e.to_string()))
+                .collect::<Result<Vec<_>, String>>()?,
+        };
+
+        Ok(EvmTypeValue::Adt {
+            constructor,
+            argtypes,
+            arguments,
+        })
+    }
+
+    /// Returns the value in the Scilla JSON format, except for the small integers which are
+    /// given as numbers.
+    pub fn to_json_value(&self) -> Value {
+        match self {
+            EvmTypeValue::Uint32(value) => Value::from(*value),
+            EvmTypeValue::Uint64(value) => Value::from(*value),
+            EvmTypeValue::Int32(value) => Value::from(*value),
+            EvmTypeValue::Int64(value) => Value::from(*value),
+            EvmTypeValue::StackReference(value) => Value::from(*value),
+            EvmTypeValue::Uint128(value) => Value::String(value.to_string()),
+            EvmTypeValue::Int128(value) => Value::String(value.to_string()),
+            EvmTypeValue::Uint256(value) => Value::String(value.to_string()),
+            EvmTypeValue::Int256(value) => {
+                if value.bit(255) {
+                    let magnitude = (!*value).overflowing_add(U256::one()).0;
+                    Value::String(format!("-{}", magnitude))
+                } else {
+                    Value::String(value.to_string())
+                }
+            }
+            EvmTypeValue::Bool(value) => serde_json::json!({
+                "constructor": if *value { "True" } else { "False" },
+                "argtypes": [],
+                "arguments": [],
+            }),
+            EvmTypeValue::Address(address) => Value::String(to_hex_string(address.as_bytes())),
+            EvmTypeValue::BytesN(bytes) | EvmTypeValue::Bytes(bytes) => {
+                Value::String(to_hex_string(bytes))
+            }
+            EvmTypeValue::String(value) => Value::String(value.clone()),
+            EvmTypeValue::Adt {
+                constructor,
+                argtypes,
+                arguments,
+            } => serde_json::json!({
+                "constructor": constructor,
+                "argtypes": argtypes,
+                "arguments": arguments
+                    .iter()
+                    .map(|argument| argument.to_json_value())
+                    .collect::<Vec<Value>>(),
+            }),
+        }
+    }
+
+    /// Converts the value for encoding with `encode_abi`. Values of algebraic data types are
+    /// encoded as tuples of their arguments.
+    pub fn to_abi_value(&self) -> AbiValue {
+        match self {
+            EvmTypeValue::Uint32(value) => AbiValue::Uint(U256::from(*value)),
+            EvmTypeValue::Uint64(value) => AbiValue::Uint(U256::from(*value)),
+            EvmTypeValue::Uint128(value) => AbiValue::Uint(U256::from(*value)),
+            EvmTypeValue::Uint256(value) => AbiValue::Uint(*value),
+            EvmTypeValue::StackReference(value) => AbiValue::Uint(U256::from(*value)),
+            EvmTypeValue::Int32(value) => {
+                AbiValue::Int(U256::from_big_endian(&signed_word(*value as i128)))
+            }
+            EvmTypeValue::Int64(value) => {
+                AbiValue::Int(U256::from_big_endian(&signed_word(*value as i128)))
+            }
+            EvmTypeValue::Int128(value) => {
+                AbiValue::Int(U256::from_big_endian(&signed_word(*value)))
+            }
+            EvmTypeValue::Int256(value) => AbiValue::Int(*value),
+            EvmTypeValue::Bool(value) => AbiValue::Bool(*value),
+            EvmTypeValue::Address(address) => AbiValue::Address(*address),
+            EvmTypeValue::BytesN(bytes) => AbiValue::FixedBytes(bytes.clone()),
+            EvmTypeValue::Bytes(bytes) => AbiValue::Bytes(bytes.clone()),
+            EvmTypeValue::String(value) => AbiValue::String(value.clone()),
+            EvmTypeValue::Adt { arguments, .. } => {
+                AbiValue::Tuple(arguments.iter().map(|a| a.to_abi_value()).collect())
+            }
+        }
+    }
+}
+
 impl Serialize for EvmTypeValue {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        // TODO: Update to match whatever is the standard for Ethereum
-        match self {
-            EvmTypeValue::Uint32(value) => serializer.serialize_u32(*value),
-            EvmTypeValue::Uint64(value) => serializer.serialize_u64(*value),
-            EvmTypeValue::Uint256(value) => {
-                let s = value.to_string();
-                serializer.serialize_str(&s)
-            }
-            EvmTypeValue::String(value) => serializer.serialize_str(value),
-            EvmTypeValue::StackReference(value) => serializer.serialize_u32(*value),
-        }
+        self.to_json_value().serialize(serializer)
     }
 }
 
@@ -39,45 +337,96 @@ impl<'de> Deserialize<'de> for EvmTypeValue {
     where
         D: Deserializer<'de>,
     {
-        // TODO: Update to match whatever is the standard for Ethereum
+        // Without a type, byte strings and large integers given as strings are taken as strings
         let value: Value = Deserialize::deserialize(deserializer)?;
 
         match value {
             Value::Number(num) => {
-                if num.is_u64() {
-                    Ok(EvmTypeValue::Uint64(num.as_u64().unwrap()))
+                if let Some(value) = num.as_u64() {
+                    Ok(EvmTypeValue::Uint64(value))
+                } else if let Some(value) = num.as_i64() {
+                    Ok(EvmTypeValue::Int64(value))
                 } else {
                     Err(serde::de::Error::custom("Invalid integer value"))
                 }
             }
             Value::String(s) => Ok(EvmTypeValue::String(s)),
+            Value::Bool(value) => Ok(EvmTypeValue::Bool(value)),
+            Value::Object(ref object) if object.contains_key("type") => {
+                let parameter: ScillaParameter =
+                    serde_json::from_value(value.clone()).map_err(serde::de::Error::custom)?;
+                parameter.to_evm_value().map_err(serde::de::Error::custom)
+            }
+            Value::Object(ref object) if object.contains_key("constructor") => {
+                match EvmTypeValue::adt_from_scilla_json("ADT", &value)
+                    .map_err(serde::de::Error::custom)?
+                {
+                    EvmTypeValue::Adt {
+                        constructor,
+                        arguments,
+                        ..
+                    } if arguments.is_empty()
+                        && (constructor == "True" || constructor == "False") =>
+                    {
+                        Ok(EvmTypeValue::Bool(constructor == "True"))
+                    }
+                    value => Ok(value),
+                }
+            }
             _ => Err(serde::de::Error::custom("Unsupported type")),
         }
     }
 }
 
 impl EvmTypeValue {
-    fn pad_byte_array(bytes: Vec<u8>) -> Vec<u8> {
-        let padding_size = 32 - bytes.len();
-        let mut ret = vec![0; padding_size];
+    fn pad_byte_array(bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+        if bytes.len() > 32 {
+            return Err(format!(
+                "Value of {} bytes does not fit in a word",
+                bytes.len()
+            ));
+        }
+        let mut ret = vec![0; 32 - bytes.len()];
         ret.extend(bytes);
-        ret
+        Ok(ret)
     }
 
-    pub fn to_bytes(&self) -> Vec<u8> {
-        Self::pad_byte_array(self.to_bytes_unpadded())
+    /// Returns the value as the calldata word passed for it. Signed integers are sign extended
+    /// and byte strings of at most a word are right aligned, as they are held on the stack.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        Self::pad_byte_array(self.to_bytes_unpadded()?)
     }
 
-    pub fn to_bytes_unpadded(&self) -> Vec<u8> {
-        match self {
+    /// Returns the big endian bytes of the value without padding them to a word.
+    pub fn to_bytes_unpadded(&self) -> Result<Vec<u8>, String> {
+        let ret = match self {
             EvmTypeValue::Uint32(value) => value.to_be_bytes().to_vec(),
             EvmTypeValue::Uint64(value) => value.to_be_bytes().to_vec(),
+            EvmTypeValue::Uint128(value) => value.to_be_bytes().to_vec(),
+            EvmTypeValue::Uint256(value) | EvmTypeValue::Int256(value) => {
+                let mut ret = vec![0; 32];
+                value.to_big_endian(&mut ret);
+                ret
+            }
+            EvmTypeValue::Int32(value) => signed_word(*value as i128),
+            EvmTypeValue::Int64(value) => signed_word(*value as i128),
+            EvmTypeValue::Int128(value) => signed_word(*value),
+            EvmTypeValue::Bool(value) => [*value as u8].to_vec(),
+            EvmTypeValue::Address(address) => address.as_bytes().to_vec(),
+            EvmTypeValue::BytesN(bytes) | EvmTypeValue::Bytes(bytes) => bytes.clone(),
             EvmTypeValue::String(value) => value.as_bytes().to_vec(),
-            // EvmTypeValue::Uint256(value) => pad_byte_array(value.to_big_endian(/* &mut [u8] */).to_vec()),
-            // TODO EvmTypeValue::Address(value) => pad_byte_array(value.as_bytes().to_vec()),
-            // Handle other types here
-            _ => panic!("Type conversion not implemented."),
-        }
+            EvmTypeValue::Adt {
+                constructor,
+                arguments,
+                ..
+            } if arguments.is_empty() => {
+                // Constructors without arguments are represented by their selector
+                Keccak256::digest(constructor)[..4].to_vec()
+            }
+            _ => return Err(format!("Unable to convert {:?} to bytes", self)),
+        };
+
+        Ok(ret)
     }
 }
 
@@ -89,6 +438,10 @@ pub enum EvmType {
     Address,
     Bool,
     String,
+    /// Byte string of arbitrary length, `bytes` in the ABI.
+    DynamicBytes,
+    /// Dynamically sized array of the element type.
+    Array(Box<EvmType>),
     UserType(Box<UserType>),
 }
 
@@ -121,13 +474,324 @@ impl EvmType {
         match self {
             EvmType::Uint(size) => format!("uint{}", size).to_string(),
             EvmType::Int(size) => format!("int{}", size).to_string(),
-            EvmType::Bytes(size) => format!("bytes{}", size).to_string(),
+            EvmType::Bytes(size) if *size <= 32 => format!("bytes{}", size).to_string(),
+            EvmType::Bytes(_) | EvmType::DynamicBytes => "bytes".to_string(),
             EvmType::Address => "address".to_string(),
             EvmType::Bool => "bool".to_string(),
             EvmType::String => "string".to_string(),
-            EvmType::UserType(_) => "user_type".to_string(),
+            EvmType::Array(element) => format!("{}[]", element.signature()),
+            EvmType::UserType(user_type) => match self.components() {
+                Some(components) => format!(
+                    "({})",
+                    components
+                        .iter()
+                        .map(|(_, component)| component.signature())
+                        .collect::<Vec<String>>()
+                        .join(",")
+                ),
+                None => match user_type.as_ref() {
+                    UserType::Enum { .. } => "uint8".to_string(),
+                    _ => "uint256".to_string(),
+                },
+            },
+        }
+    }
+
+    /// Returns the number of bits a value is shifted left by in its ABI encoding relative to the
+    /// word it is held in. Only `bytesN` shorter than a word are shifted, as they are left
+    /// aligned in the ABI but held right aligned on the stack.
+    pub fn abi_shift(&self) -> usize {
+        match self {
+            EvmType::Bytes(size) if *size < 32 => 8 * (32 - size),
+            _ => 0,
+        }
+    }
+
+    /// Converts `value` for encoding as a value of the type with `encode_abi`. Strings and byte
+    /// strings held in memory are passed as dynamic values and `bytesN` left aligned, while
+    /// any other value is passed as the word it is held in. Byte strings may be given as hex
+    /// strings.
+    pub fn abi_value(&self, value: &EvmTypeValue) -> Result<AbiValue, String> {
+        let bytes = match value {
+            EvmTypeValue::BytesN(bytes) | EvmTypeValue::Bytes(bytes) => Some(bytes.clone()),
+            EvmTypeValue::String(text)
+                if matches!(self, EvmType::Bytes(_) | EvmType::DynamicBytes) =>
+            {
+                Some(from_hex_string(text)?)
+            }
+            _ => None,
+        };
+
+        let ret = match (self, bytes) {
+            (EvmType::String, _) => match value {
+                EvmTypeValue::String(text) => AbiValue::String(text.clone()),
+                _ => return Err(format!("Expected a string, found {:?}", value)),
+            },
+            (EvmType::Bytes(size), Some(bytes)) if bytes.len() != *size => {
+                return Err(format!("Expected {} bytes, found {}", size, bytes.len()))
+            }
+            (EvmType::Bytes(size), Some(bytes)) if *size <= 32 => AbiValue::FixedBytes(bytes),
+            (EvmType::Bytes(_) | EvmType::DynamicBytes, Some(bytes)) => AbiValue::Bytes(bytes),
+            (evm_type, _) if evm_type.is_dynamic() => {
+                return Err(format!(
+                    "Expected a value of type {}, found {:?}",
+                    evm_type.signature(),
+                    value
+                ))
+            }
+            _ => AbiValue::Uint(U256::from_big_endian(&value.to_bytes()?)),
+        };
+
+        Ok(ret)
+    }
+
+    /// Returns the named components of types which are encoded as ABI tuples. Tagged unions
+    /// are encoded with the tag of the constructor as their first component.
+    pub fn components(&self) -> Option<Vec<(String, EvmType)>> {
+        let user_type = match self {
+            EvmType::UserType(user_type) => user_type,
+            _ => return None,
+        };
+
+        match user_type.as_ref() {
+            UserType::Struct { layout, .. } => Some(layout.clone()),
+            UserType::Tuple { layout, .. } => Some(
+                layout
+                    .iter()
+                    .enumerate()
+                    .map(|(i, component)| (format!("_{}", i), component.clone()))
+                    .collect(),
+            ),
+            UserType::TaggedUnion { layout, .. } => {
+                let mut ret = vec![("tag".to_string(), EvmType::Uint(8))];
+                ret.extend(layout.iter().cloned());
+                Some(ret)
+            }
+            UserType::Union { .. } | UserType::Enum { .. } => None,
+        }
+    }
+
+    /// Checks if values of the type are stored in memory rather than on the stack, i.e. strings
+    /// and byte strings which do not fit in a single word.
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            EvmType::String | EvmType::DynamicBytes | EvmType::Array(_) => true,
+            EvmType::Bytes(size) => *size > 32,
+            _ => false,
+        }
+    }
+
+    /// Checks if values of the type are ABI encoded in the tail of the encoding, referred to
+    /// by their offset from the head.
+    pub fn is_abi_dynamic(&self) -> bool {
+        match self.components() {
+            Some(components) => components
+                .iter()
+                .any(|(_, component)| component.is_abi_dynamic()),
+            None => self.is_dynamic(),
+        }
+    }
+
+    /// Size of the ABI encoding of the type in the head of the encoding.
+    fn abi_head_size(&self) -> usize {
+        match self.components() {
+            Some(components) if !self.is_abi_dynamic() => components
+                .iter()
+                .map(|(_, component)| component.abi_head_size())
+                .sum(),
+            _ => 32,
+        }
+    }
+}
+
+/// Value encoded according to the Ethereum contract ABI.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AbiValue {
+    Uint(U256),
+    /// Signed integer in two's complement.
+    Int(U256),
+    Bool(bool),
+    Address(H160),
+    /// Byte string of at most 32 bytes.
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+}
+
+fn abi_word(value: U256) -> Vec<u8> {
+    let mut ret = vec![0; 32];
+    value.to_big_endian(&mut ret);
+    ret
+}
+
+/// Pads `bytes` with zeros to a multiple of 32 bytes.
+fn abi_padded(bytes: &[u8]) -> Vec<u8> {
+    let mut ret = bytes.to_vec();
+    ret.resize((bytes.len() + 31) / 32 * 32, 0);
+    ret
+}
+
+impl AbiValue {
+    fn is_dynamic(&self) -> bool {
+        match self {
+            AbiValue::Bytes(_) | AbiValue::String(_) | AbiValue::Array(_) => true,
+            AbiValue::Tuple(values) => values.iter().any(|value| value.is_dynamic()),
+            _ => false,
+        }
+    }
+
+    /// Encodes the value itself, which for dynamic values is the part stored in the tail.
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            AbiValue::Uint(value) | AbiValue::Int(value) => abi_word(*value),
+            AbiValue::Bool(value) => abi_word(U256::from(*value as u8)),
+            AbiValue::Address(address) => {
+                let mut ret = vec![0; 12];
+                ret.extend_from_slice(address.as_bytes());
+                ret
+            }
+            AbiValue::FixedBytes(bytes) => abi_padded(bytes),
+            AbiValue::Bytes(bytes) => {
+                let mut ret = abi_word(U256::from(bytes.len()));
+                ret.extend(abi_padded(bytes));
+                ret
+            }
+            AbiValue::String(value) => AbiValue::Bytes(value.as_bytes().to_vec()).encode(),
+            AbiValue::Array(values) => {
+                let mut ret = abi_word(U256::from(values.len()));
+                ret.extend(encode_abi(values));
+                ret
+            }
+            AbiValue::Tuple(values) => encode_abi(values),
+        }
+    }
+}
+
+/// Encodes `values` as arguments of the given types, see `EvmType::abi_value`. Values beyond
+/// the types given are passed as the word they are held in.
+pub fn encode_arguments(types: &[EvmType], values: &[EvmTypeValue]) -> Result<Vec<u8>, String> {
+    let values = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| match types.get(i) {
+            Some(evm_type) => evm_type.abi_value(value),
+            None => Ok(AbiValue::Uint(U256::from_big_endian(&value.to_bytes()?))),
+        })
+        .collect::<Result<Vec<AbiValue>, String>>()?;
+
+    Ok(encode_abi(&values))
+}
+
+/// Encodes `values` as the arguments of a call, i.e. as a tuple with static values in place
+/// and dynamic values referred to by their offset.
+pub fn encode_abi(values: &[AbiValue]) -> Vec<u8> {
+    let encoded: Vec<Vec<u8>> = values.iter().map(|value| value.encode()).collect();
+    let head_size: usize = values
+        .iter()
+        .zip(encoded.iter())
+        .map(|(value, encoding)| {
+            if value.is_dynamic() {
+                32
+            } else {
+                encoding.len()
+            }
+        })
+        .sum();
+
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    for (value, encoding) in values.iter().zip(encoded) {
+        if value.is_dynamic() {
+            head.extend(abi_word(U256::from(head_size + tail.len())));
+            tail.extend(encoding);
+        } else {
+            head.extend(encoding);
         }
     }
+
+    head.extend(tail);
+    head
+}
+
+fn read_abi_word(data: &[u8], offset: usize) -> Result<U256, String> {
+    match data.get(offset..offset + 32) {
+        Some(word) => Ok(U256::from_big_endian(word)),
+        None => Err(format!("ABI data ends before the word at {}", offset)),
+    }
+}
+
+fn read_abi_usize(data: &[u8], offset: usize) -> Result<usize, String> {
+    let value = read_abi_word(data, offset)?;
+    if value > U256::from(data.len()) {
+        return Err(format!("ABI offset or length {} out of bounds", value));
+    }
+    Ok(value.as_usize())
+}
+
+fn read_abi_bytes(data: &[u8], offset: usize) -> Result<Vec<u8>, String> {
+    let length = read_abi_usize(data, offset)?;
+    match data.get(offset + 32..offset + 32 + length) {
+        Some(bytes) => Ok(bytes.to_vec()),
+        None => Err(format!("ABI data ends before the bytes at {}", offset)),
+    }
+}
+
+fn decode_abi_value(evm_type: &EvmType, data: &[u8], offset: usize) -> Result<AbiValue, String> {
+    if let Some(components) = evm_type.components() {
+        let types: Vec<EvmType> = components.into_iter().map(|(_, t)| t).collect();
+        let data = match data.get(offset..) {
+            Some(data) => data,
+            None => return Err(format!("ABI data ends before the tuple at {}", offset)),
+        };
+        return Ok(AbiValue::Tuple(decode_abi(&types, data)?));
+    }
+
+    let ret = match evm_type {
+        EvmType::Uint(_) | EvmType::UserType(_) => AbiValue::Uint(read_abi_word(data, offset)?),
+        EvmType::Int(_) => AbiValue::Int(read_abi_word(data, offset)?),
+        EvmType::Bool => AbiValue::Bool(!read_abi_word(data, offset)?.is_zero()),
+        EvmType::Address => {
+            read_abi_word(data, offset)?;
+            AbiValue::Address(H160::from_slice(&data[offset + 12..offset + 32]))
+        }
+        EvmType::Bytes(size) if *size <= 32 => {
+            read_abi_word(data, offset)?;
+            AbiValue::FixedBytes(data[offset..offset + size].to_vec())
+        }
+        EvmType::Bytes(_) | EvmType::DynamicBytes => AbiValue::Bytes(read_abi_bytes(data, offset)?),
+        EvmType::String => {
+            let bytes = read_abi_bytes(data, offset)?;
+            AbiValue::String(String::from_utf8(bytes).map_err(|e| e.to_string())?)
+        }
+        EvmType::Array(element) => {
+            let length = read_abi_usize(data, offset)?;
+            let types = vec![element.as_ref().clone(); length];
+            AbiValue::Array(decode_abi(&types, &data[offset + 32..])?)
+        }
+    };
+
+    Ok(ret)
+}
+
+/// Decodes values of the given types from their ABI encoding, e.g. the arguments of a call
+/// without the selector or the values returned by it.
+pub fn decode_abi(types: &[EvmType], data: &[u8]) -> Result<Vec<AbiValue>, String> {
+    let mut ret = Vec::new();
+    let mut offset = 0;
+    for evm_type in types {
+        if evm_type.is_abi_dynamic() {
+            let position = read_abi_usize(data, offset)?;
+            ret.push(decode_abi_value(evm_type, data, position)?);
+            offset += 32;
+        } else {
+            ret.push(decode_abi_value(evm_type, data, offset)?);
+            offset += evm_type.abi_head_size();
+        }
+    }
+
+    Ok(ret)
 }
 
 impl FromStr for EvmType {
@@ -140,7 +804,8 @@ impl FromStr for EvmType {
             Ok(EvmType::Address)
         } else if s_lower == "bool" {
             Ok(EvmType::Bool)
-        } else if s_lower == "string" {
+        } else if s_lower == "string" || s_lower == "bystr" {
+            // Byte strings of arbitrary length share the memory layout of strings
             Ok(EvmType::String)
         } else if s_lower.starts_with("uint") {
             s[4..]
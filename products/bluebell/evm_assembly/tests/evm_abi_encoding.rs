@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use evm_assembly::types::{
+        decode_abi, encode_abi, encode_arguments, AbiValue, EvmType, EvmTypeValue, UserType,
+    };
+    use primitive_types::U256;
+
+    #[test]
+    fn test_encode_dynamic_arguments() {
+        // Example of the Solidity ABI specification, f(uint256,uint32[],bytes10,bytes)
+        let values = vec![
+            AbiValue::Uint(U256::from(0x123)),
+            AbiValue::Array(vec![
+                AbiValue::Uint(U256::from(0x456)),
+                AbiValue::Uint(U256::from(0x789)),
+            ]),
+            AbiValue::FixedBytes(b"1234567890".to_vec()),
+            AbiValue::String("Hello, world!".to_string()),
+        ];
+        let encoded = encode_abi(&values);
+        assert_eq!(
+            hex::encode(&encoded),
+            [
+                "0000000000000000000000000000000000000000000000000000000000000123",
+                "0000000000000000000000000000000000000000000000000000000000000080",
+                "3132333435363738393000000000000000000000000000000000000000000000",
+                "00000000000000000000000000000000000000000000000000000000000000e0",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000456",
+                "0000000000000000000000000000000000000000000000000000000000000789",
+                "000000000000000000000000000000000000000000000000000000000000000d",
+                "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+            ]
+            .concat()
+        );
+
+        let types = [
+            EvmType::Uint(256),
+            EvmType::Array(Box::new(EvmType::Uint(32))),
+            EvmType::Bytes(10),
+            EvmType::String,
+        ];
+        assert_eq!(decode_abi(&types, &encoded).unwrap(), values);
+    }
+
+    #[test]
+    fn test_tuples() {
+        let record = EvmType::UserType(Box::new(UserType::Struct {
+            type_id: "user_struct::Record".to_string(),
+            layout: vec![
+                ("id".to_string(), EvmType::Uint(64)),
+                ("active".to_string(), EvmType::Bool),
+                ("name".to_string(), EvmType::String),
+            ],
+        }));
+        assert_eq!(record.signature(), "(uint64,bool,string)");
+        assert!(record.is_abi_dynamic());
+
+        let values = vec![
+            AbiValue::Int(U256::MAX),
+            AbiValue::Tuple(vec![
+                AbiValue::Uint(U256::from(7)),
+                AbiValue::Bool(true),
+                AbiValue::String("bluebell".to_string()),
+            ]),
+        ];
+        let encoded = encode_abi(&values);
+        assert_eq!(encoded.len(), 32 * 7);
+        assert_eq!(
+            decode_abi(&[EvmType::Int(8), record], &encoded).unwrap(),
+            values
+        );
+    }
+
+    #[test]
+    fn test_decode_truncated_data() {
+        let encoded = encode_abi(&[AbiValue::String("Hello".to_string())]);
+        assert!(decode_abi(&[EvmType::String], &encoded[..64]).is_err());
+        assert!(decode_abi(&[EvmType::Uint(256), EvmType::Uint(256)], &encoded[..32]).is_err());
+    }
+
+    #[test]
+    fn test_encode_arguments() {
+        let types = [EvmType::Bytes(4), EvmType::DynamicBytes, EvmType::Uint(64)];
+        let values = [
+            EvmTypeValue::BytesN(vec![0xde, 0xad, 0xbe, 0xef]),
+            EvmTypeValue::String("0xbeef".to_string()),
+            EvmTypeValue::Uint64(7),
+        ];
+        assert_eq!(
+            hex::encode(encode_arguments(&types, &values).unwrap()),
+            [
+                "deadbeef00000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000060",
+                "0000000000000000000000000000000000000000000000000000000000000007",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "beef000000000000000000000000000000000000000000000000000000000000",
+            ]
+            .concat()
+        );
+
+        // Byte strings of the wrong length and values of other types are rejected
+        let short = [EvmTypeValue::BytesN(vec![0xde, 0xad])];
+        assert!(encode_arguments(&types[..1], &short).is_err());
+        let number = [EvmTypeValue::Uint64(7)];
+        assert!(encode_arguments(&[EvmType::String], &number).is_err());
+    }
+}
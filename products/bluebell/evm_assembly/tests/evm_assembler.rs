@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use evm_assembly::{
+        compiler_context::EvmCompilerContext, EvmAssemblyGenerator, EvmByteCodeBuilder,
+    };
+
+    const SNIPPET: &str = r#"
+.macro revert_with code
+  PUSH1 $code
+  PUSH1 0x00
+  REVERT
+.endmacro
+
+  PUSH @done              ; Code before the first label has no JUMPDEST
+  JUMP
+done:
+  PUSH2 @greeting
+  %revert_with 0x01
+
+.data greeting "Hi"       ; Placed after the STOP ending the code
+"#;
+
+    #[test]
+    fn test_assemble_snippet() {
+        let mut context = EvmCompilerContext::new();
+        let mut builder =
+            EvmByteCodeBuilder::from_asm(&mut context, SNIPPET).expect("Failed to assemble");
+        let executable = builder.build().expect("Failed to build");
+
+        assert_eq!(
+            hex::encode(&executable.bytecode),
+            [
+                "6300000006", // PUSH4 @done
+                "56",         // JUMP
+                "5b",         // done:
+                "610010",     // PUSH2 @greeting
+                "6001",
+                "6000",
+                "fd",
+                "00",   // STOP
+                "4869", // greeting
+            ]
+            .concat()
+        );
+        assert_eq!(executable.get_label_position("done"), Some(6));
+        assert_eq!(executable.get_label_position("greeting"), Some(16));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut context = EvmCompilerContext::new();
+        context.declare_unsigned_integer("Uint256", 256);
+
+        let (bytecode, script) = {
+            let mut builder = context.create_builder_no_abi_support();
+            builder
+                .define_function("hello", [].to_vec(), "Uint256")
+                .build(|code_builder| {
+                    let mut entry = code_builder.new_evm_block("entry");
+                    let mut done = code_builder.new_evm_block("done");
+
+                    let inner = entry.generate_label("inner".to_string());
+                    entry.push1([0x2a].to_vec());
+                    entry.pop();
+                    entry.create_label(inner.clone());
+                    entry.jump_to(&done.name);
+
+                    done.push_label(&inner);
+                    done.pop();
+                    done.push1([0x00].to_vec());
+                    done.dup1();
+                    done.r#return();
+                    [entry, done].to_vec()
+                })
+                .expect("Failed to build function");
+            builder
+                .ir
+                .data
+                .push(("greeting".to_string(), b"Hello".to_vec()));
+            builder.finalize_blocks().expect("Failed to finalize");
+
+            let script = builder.generate_evm_assembly();
+            (builder.build().expect("Failed to build").bytecode, script)
+        };
+        assert!(script.contains(".function hello"));
+        assert!(script.contains("PUSH4 @hello::done"));
+
+        let mut builder =
+            EvmByteCodeBuilder::from_asm(&mut context, &script).expect("Failed to assemble");
+        assert_eq!(builder.build().expect("Failed to build").bytecode, bytecode);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut context = EvmCompilerContext::new();
+        let mut error = |script: &str| {
+            EvmByteCodeBuilder::from_asm(&mut context, script)
+                .err()
+                .expect("Expected the program to be rejected")
+        };
+
+        assert_eq!(
+            error("  PUSH1 0x01\n  FOO"),
+            "Line 2: Unknown instruction FOO"
+        );
+        assert_eq!(
+            error("  PUSH1 0x0100"),
+            "Line 1: 0x0100 does not fit in PUSH1"
+        );
+        assert_eq!(
+            error("  PUSH @nowhere"),
+            "Line 1: Label nowhere is not defined"
+        );
+        assert_eq!(error("a:\na:"), "Line 2: Label a is already defined");
+        assert_eq!(error("  POP 0x01"), "Line 1: POP does not take an argument");
+        assert!(error(".macro loop\n  %loop\n.endmacro\n  %loop").contains("too deeply"));
+
+        // The label is past the first 256 bytes
+        let script = format!("{}  PUSH1 @end\nend:", "  PUSH32 0x01\n".repeat(8));
+        assert_eq!(error(&script), "Position of end does not fit in PUSH1");
+    }
+}
@@ -111,12 +111,15 @@ mod tests {
 
                 finally.r#return();
                 [entry, success, finally].to_vec()
-            });
+            })
+            .expect("Failed to build function");
 
-        let executable = builder.build();
+        let executable = builder.build().expect("Failed to build");
 
         let executor = EvmExecutor::new(&specification, executable);
-        executor.execute("hello", [EvmTypeValue::Uint32(10)].to_vec());
+        executor
+            .execute("hello", [EvmTypeValue::Uint32(10)].to_vec())
+            .expect("Failed to encode the arguments");
 
         // assert!(false);
     }
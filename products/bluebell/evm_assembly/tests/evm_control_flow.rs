@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use evm_assembly::{
+        abi::{Abi, AbiEntry, StateMutability},
+        compiler_context::EvmCompilerContext,
+        control_flow_graph::{CfgEdgeKind, ControlFlowGraph},
+        EvmByteCodeBuilder,
+    };
+
+    /// Dispatcher calling an internal helper, which returns through the label pushed by its caller.
+    fn dispatcher(selector: &str) -> String {
+        format!(
+            r#"
+  PUSH1 0x00
+  CALLDATALOAD
+  PUSH1 0xe0
+  SHR
+  PUSH4 {}
+  EQ
+  PUSH @hello
+  JUMPI
+  PUSH1 0x00
+  DUP1
+  REVERT
+hello:
+  PUSH @after
+  PUSH @helper
+  JUMP
+after:
+  STOP
+helper:
+  JUMP
+"#,
+            selector
+        )
+    }
+
+    #[test]
+    fn test_assembled_dispatcher() {
+        let mut context = EvmCompilerContext::new();
+        let mut builder = EvmByteCodeBuilder::from_asm(&mut context, &dispatcher("0x3a19a7c6"))
+            .expect("Failed to assemble");
+        let executable = builder.build().expect("Failed to build");
+        let hello = executable.get_label_position("hello").unwrap();
+        let after = executable.get_label_position("after").unwrap();
+        let helper = executable.get_label_position("helper").unwrap();
+        // The revert block follows the JUMPI, 18 bytes into the code
+        let revert = 18;
+
+        let cfg = ControlFlowGraph::from_bytecode(&executable.bytecode);
+        let edges: Vec<(u32, u32, CfgEdgeKind, bool)> = cfg
+            .edges
+            .iter()
+            .map(|edge| (edge.from, edge.to, edge.kind, edge.dynamic))
+            .collect();
+        assert!(edges.contains(&(0, hello, CfgEdgeKind::Branch, false)));
+        assert!(edges.contains(&(0, revert, CfgEdgeKind::FallThrough, false)));
+        assert!(edges.contains(&(hello, helper, CfgEdgeKind::Jump, false)));
+        assert!(edges.contains(&(helper, after, CfgEdgeKind::Jump, true)));
+        assert!(cfg.get_block(after).unwrap().reachable);
+        assert!(cfg.blocks.iter().all(|block| !block.unresolved_jump));
+        // The STOP appended after the code is never reached
+        assert!(!cfg.blocks.last().unwrap().reachable);
+
+        let function = cfg
+            .get_function("function_0x3a19a7c6")
+            .expect("Missing function");
+        assert_eq!(function.entry, hello);
+        assert_eq!(function.blocks, [hello, after, helper].to_vec());
+        assert!(!cfg.get_function("entry").unwrap().blocks.contains(&hello));
+    }
+
+    #[test]
+    fn test_name_functions() {
+        let entry = AbiEntry::function("answer", [].to_vec(), [].to_vec(), StateMutability::View);
+        let selector = format!("0x{}", hex::encode(entry.selector()));
+        let abi = Abi {
+            entries: [entry].to_vec(),
+        };
+
+        let mut context = EvmCompilerContext::new();
+        let mut builder = EvmByteCodeBuilder::from_asm(&mut context, &dispatcher(&selector))
+            .expect("Failed to assemble");
+        let executable = builder.build().expect("Failed to build");
+
+        let mut cfg = ControlFlowGraph::from_bytecode(&executable.bytecode);
+        cfg.name_functions(&abi);
+        let function = cfg.get_function("answer()").expect("Missing function");
+        assert_eq!(function.selector, Some(selector));
+    }
+
+    #[test]
+    fn test_solidity_bytecode() {
+        let bytes = hex::decode("608060405234801561001057600080fd5b506004361061002b5760003560e01c80633a19a7c614610030575b600080fd5b61003861004e565b6040516100459190610107565b60405180910390f35b60606000604051806101400160405280610114815260200161012a610114913990508091505090565b600081519050919050565b600082825260208201905092915050565b60005b838110156100b1578082015181840152602081019050610096565b60008484015250505050565b6000601f19601f8301169050919050565b60006100d982610077565b6100e38185610082565b93506100f3818560208601610093565b6100fc816100bd565b840191505092915050565b6000602082019050818103600083015261012181846100ce565b90509291505056fe48656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c642048656c6c6f20576f726c6420a26469706673582212209e44d7f3c5ad5ed44f2d09f524e9aea6f2a72997367b5def3f0952f557cf658864736f6c63430008140033").unwrap();
+        let cfg = ControlFlowGraph::from_bytecode(&bytes);
+
+        let function = cfg
+            .functions
+            .iter()
+            .find(|function| function.selector.as_deref() == Some("0x3a19a7c6"))
+            .expect("Missing function");
+        assert_eq!(function.entry, 0x30);
+        // The getter returns to its caller through a label pushed before the call
+        assert!(cfg.edges.iter().any(|edge| edge.dynamic && edge.to == 0x38));
+        // Metadata following the INVALID is never executed
+        assert!(!cfg.blocks.last().unwrap().reachable);
+
+        let dot = cfg.to_dot();
+        assert!(dot.starts_with("digraph"));
+        assert!(dot.contains("block_0x0030"));
+
+        let json = cfg.to_json().expect("Failed to serialize graph");
+        assert_eq!(ControlFlowGraph::from_json(&json).unwrap(), cfg);
+    }
+    #[test]
+    fn test_missing_label() {
+        let mut context = EvmCompilerContext::new();
+        context.declare_unsigned_integer("Uint256", 256);
+        let mut builder = context.create_builder_no_abi_support();
+
+        // Jumps are checked when the function is built
+        let error = builder
+            .define_function("jump", [].to_vec(), "Uint256")
+            .build(|code_builder| {
+                let mut entry = code_builder.new_evm_block("entry");
+                entry.jump_to("missing");
+                [entry].to_vec()
+            })
+            .unwrap_err();
+        assert!(error.contains("Label 'missing' not found"), "{}", error);
+
+        // Pushed labels are resolved once the blocks are finalized
+        builder
+            .define_function("push", [].to_vec(), "Uint256")
+            .build(|code_builder| {
+                let mut entry = code_builder.new_evm_block("entry");
+                entry.push_label("missing");
+                entry.pop();
+                entry.push1([0x00].to_vec());
+                entry.dup1();
+                entry.r#return();
+                [entry].to_vec()
+            })
+            .expect("Failed to build function");
+        let error = builder.finalize_blocks().unwrap_err();
+        assert!(error.contains("Label not found missing"), "{}", error);
+    }
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use evm::Opcode;
+    use evm_assembly::{
+        block::EvmBlock,
+        compiler_context::EvmCompilerContext,
+        executable::EvmExecutable,
+        executor::ExecutorResult,
+        hardfork::Hardfork,
+        local_chain::LocalChain,
+        opcode_spec::{MCOPY, PUSH0, TLOAD, TSTORE},
+        EvmByteCodeBuilder,
+    };
+    use primitive_types::H160;
+
+    fn push_zero(block: &mut EvmBlock, explicit_push0: bool) {
+        if explicit_push0 {
+            block.push0();
+        } else {
+            block.push1([0x00].to_vec());
+        }
+    }
+
+    /// Builds a function returning 42. With `transient` set, the value is passed through
+    /// transient storage before being returned.
+    fn build(hardfork: Hardfork, explicit_push0: bool, transient: bool) -> EvmExecutable {
+        let mut context = EvmCompilerContext::new();
+        context.declare_unsigned_integer("Uint256", 256);
+        context.hardfork = hardfork;
+
+        let mut builder = context.create_builder_no_abi_support();
+        builder
+            .define_function("answer", [].to_vec(), "Uint256")
+            .build(|code_builder| {
+                let mut entry = code_builder.new_evm_block("entry");
+                entry.push1([0x2a].to_vec());
+                if transient {
+                    push_zero(&mut entry, explicit_push0);
+                    entry.external_tstore();
+                    push_zero(&mut entry, explicit_push0);
+                    entry.external_tload();
+                }
+                push_zero(&mut entry, explicit_push0);
+                entry.mstore();
+                entry.push1([0x20].to_vec());
+                push_zero(&mut entry, explicit_push0);
+                entry.r#return();
+                [entry].to_vec()
+            })
+            .expect("Failed to build function");
+        builder.build().expect("Failed to build")
+    }
+
+    /// Runs `executable` without calldata on a chain following `hardfork`.
+    fn run(hardfork: Hardfork, executable: &EvmExecutable) -> Result<ExecutorResult, String> {
+        let mut context = EvmCompilerContext::new();
+        context.hardfork = hardfork;
+        let address = H160::from_str("0x1000000000000000000000000000000000000000").unwrap();
+
+        let mut chain = LocalChain::new(&context);
+        chain.deploy_code(address, executable.bytecode.clone());
+        chain.call(address, [].to_vec())
+    }
+
+    fn opcodes(executable: &EvmExecutable) -> Vec<Opcode> {
+        executable
+            .ir
+            .functions
+            .iter()
+            .flat_map(|function| function.blocks.iter())
+            .flat_map(|block| block.instructions.iter())
+            .map(|instruction| instruction.opcode)
+            .collect()
+    }
+
+    #[test]
+    fn test_push0() {
+        let istanbul = build(Hardfork::Istanbul, false, false);
+        let shanghai = build(Hardfork::Shanghai, false, false);
+        assert!(!opcodes(&istanbul).contains(&PUSH0));
+        assert!(opcodes(&shanghai).contains(&PUSH0));
+        assert!(shanghai.bytecode.len() < istanbul.bytecode.len());
+
+        // Explicit PUSH0 falls back to PUSH1 0x00 before Shanghai
+        let fallback = build(Hardfork::London, true, false);
+        assert!(!opcodes(&fallback).contains(&PUSH0));
+        assert_eq!(
+            fallback.bytecode,
+            build(Hardfork::London, false, false).bytecode
+        );
+    }
+
+    #[test]
+    fn test_transient_storage() {
+        let cancun = opcodes(&build(Hardfork::Cancun, false, true));
+        assert!(cancun.contains(&TLOAD) && cancun.contains(&TSTORE));
+        assert!(!cancun.contains(&Opcode::SLOAD) && !cancun.contains(&Opcode::SSTORE));
+
+        // Transient storage falls back to storage before Cancun
+        for hardfork in [
+            Hardfork::Istanbul,
+            Hardfork::Berlin,
+            Hardfork::London,
+            Hardfork::Shanghai,
+        ] {
+            let fallback = opcodes(&build(hardfork, false, true));
+            assert!(
+                fallback.contains(&Opcode::SLOAD) && fallback.contains(&Opcode::SSTORE),
+                "{}",
+                hardfork
+            );
+            assert!(
+                !fallback.contains(&TLOAD) && !fallback.contains(&TSTORE),
+                "{}",
+                hardfork
+            );
+        }
+    }
+
+    #[test]
+    fn test_execution() {
+        let mut expected = [0u8; 32].to_vec();
+        expected[31] = 0x2a;
+
+        for hardfork in [Hardfork::Istanbul, Hardfork::Berlin, Hardfork::London] {
+            for transient in [false, true] {
+                let result =
+                    run(hardfork, &build(hardfork, true, transient)).expect("Failed to execute");
+                assert_eq!(result.revert_reason, None, "{}", hardfork);
+                assert_eq!(result.result, format!("{:?}", expected), "{}", hardfork);
+            }
+        }
+    }
+
+    #[test]
+    fn test_execution_requires_executable_hardfork() {
+        for hardfork in [Hardfork::Shanghai, Hardfork::Cancun] {
+            let error = run(hardfork, &build(hardfork, true, true))
+                .err()
+                .expect("Expected the execution to be rejected");
+            assert!(
+                error.contains("the latest executable hardfork is london"),
+                "{}: {}",
+                hardfork,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_assembler_targets_hardfork() {
+        let script = "  PUSH0\n  PUSH0\n  PUSH0\n  MCOPY\n";
+
+        let mut context = EvmCompilerContext::new();
+        context.hardfork = Hardfork::Shanghai;
+        let error = EvmByteCodeBuilder::from_asm(&mut context, script)
+            .err()
+            .expect("Expected MCOPY to be rejected");
+        assert!(error.contains("MCOPY"), "{}", error);
+        assert!(error.contains("requires cancun"), "{}", error);
+
+        context.hardfork = Hardfork::Cancun;
+        let mut builder =
+            EvmByteCodeBuilder::from_asm(&mut context, script).expect("Failed to assemble");
+        assert_eq!(
+            hex::encode(builder.build().expect("Failed to build").bytecode),
+            "5f5f5f5e00"
+        );
+    }
+
+    #[test]
+    fn test_hardfork_names() {
+        assert_eq!(Hardfork::default(), Hardfork::Istanbul);
+        assert_eq!(Hardfork::from_str("Shanghai"), Ok(Hardfork::Shanghai));
+        assert_eq!(Hardfork::Cancun.to_string(), "cancun");
+        assert!(Hardfork::from_str("frontier").is_err());
+
+        assert!(!Hardfork::Istanbul.supports(Opcode::BASEFEE));
+        assert!(Hardfork::London.supports(Opcode::BASEFEE));
+        assert!(!Hardfork::London.has_push0());
+        assert!(!Hardfork::Shanghai.supports(MCOPY));
+        assert!(Hardfork::Cancun.has_transient_storage());
+    }
+}
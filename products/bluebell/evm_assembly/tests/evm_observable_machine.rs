@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use evm_assembly::observable_machine::ObservableMachine;
+
+    /// Runs `code` without call data and returns the machine after it stopped.
+    fn run(code: Vec<u8>) -> ObservableMachine {
+        let mut vm = ObservableMachine::new(Rc::new(code), Rc::new(Vec::new()), 1024, 10000, None);
+        vm.run();
+        vm
+    }
+
+    /// Copies `size` bytes of the return data from `data_offset` to memory, then stops.
+    fn return_data_copy(data_offset: &[u8], size: &[u8]) -> Vec<u8> {
+        let mut code = Vec::new();
+        code.push(0x60 + size.len() as u8 - 1); // PUSHn size
+        code.extend(size);
+        code.push(0x60 + data_offset.len() as u8 - 1); // PUSHn data_offset
+        code.extend(data_offset);
+        code.extend([0x60, 0x00]); // PUSH1 memory_offset
+        code.push(0x3e); // RETURNDATACOPY
+        code.push(0x00); // STOP
+        code
+    }
+
+    #[test]
+    fn test_return_data_copy_in_bounds() {
+        let vm = run(return_data_copy(&[0x00], &[0x00]));
+        assert!(!vm.failed);
+        assert_eq!(vm.error_message, None);
+    }
+
+    #[test]
+    fn test_return_data_copy_out_of_bounds() {
+        let vm = run(return_data_copy(&[0x00], &[0x01]));
+        assert!(vm.failed);
+        assert_eq!(
+            vm.error_message,
+            Some("Return data out of bounds".to_string())
+        );
+
+        // The end of the copied range overflows
+        let vm = run(return_data_copy(&[0xff; 32], &[0x02]));
+        assert!(vm.failed);
+        assert_eq!(
+            vm.error_message,
+            Some("Return data out of bounds".to_string())
+        );
+    }
+}
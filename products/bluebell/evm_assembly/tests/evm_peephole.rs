@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use evm::Opcode;
+    use evm_assembly::{
+        block::EvmBlock, bytecode_ir::EvmBytecodeIr, function::EvmFunction,
+        peephole::PeepholeOptimiser,
+    };
+
+    fn ir_from_blocks(blocks: Vec<EvmBlock>) -> EvmBytecodeIr {
+        let mut function = EvmFunction::empty();
+        function.blocks = blocks;
+
+        let mut ir = EvmBytecodeIr::new();
+        ir.functions.push_back(function);
+        ir
+    }
+
+    fn opcodes(block: &EvmBlock) -> Vec<(Opcode, Vec<u8>)> {
+        block
+            .instructions
+            .iter()
+            .map(|instr| (instr.opcode, instr.arguments.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_redundant_stack_operations_are_removed() {
+        let mut entry = EvmBlock::new(None, BTreeSet::new(), "entry");
+        entry.push1([0x2a].to_vec());
+        entry.push1([0x07].to_vec());
+        entry.pop();
+        entry.dup1();
+        entry.pop();
+        entry.push1([0x03].to_vec());
+        entry.swap1();
+        entry.swap1();
+        entry.pop();
+        entry.stop();
+
+        let mut ir = ir_from_blocks([entry].to_vec());
+        let report = PeepholeOptimiser::new().optimise(&mut ir);
+
+        assert_eq!(
+            opcodes(&ir.functions[0].blocks[0]),
+            [
+                (Opcode::JUMPDEST, [].to_vec()),
+                (Opcode::PUSH1, [0x2a].to_vec()),
+                (Opcode::STOP, [].to_vec()),
+            ]
+            .to_vec()
+        );
+        assert_eq!(report.functions[0].name, "function_0");
+        assert_eq!(report.bytes_saved(), 10);
+        assert_eq!(report.gas_saved(), 3 + 2 + 3 + 2 + 3 + 3 + 3 + 2);
+    }
+
+    #[test]
+    fn test_constant_arithmetic_is_folded() {
+        let mut entry = EvmBlock::new(None, BTreeSet::new(), "entry");
+        entry.push_u64(40);
+        entry.push_u64(2);
+        entry.add();
+        entry.push1([0x02].to_vec());
+        entry.push1([0x28].to_vec());
+        entry.sub();
+        entry.push_label("entry");
+        entry.stop();
+
+        let mut ir = ir_from_blocks([entry].to_vec());
+        PeepholeOptimiser::new().optimise(&mut ir);
+
+        let block = &ir.functions[0].blocks[0];
+        assert_eq!(
+            opcodes(block)[1..3].to_vec(),
+            [
+                (Opcode::PUSH1, [42].to_vec()),
+                (Opcode::PUSH1, [38].to_vec()),
+            ]
+            .to_vec()
+        );
+
+        // Labels keep their width as their position is not known yet
+        assert_eq!(block.instructions[3].opcode, Opcode::PUSH4);
+        assert_eq!(
+            block.instructions[3].unresolved_argument_label,
+            Some("entry".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unreferenced_jumpdests_are_removed() {
+        let mut entry = EvmBlock::new(None, BTreeSet::new(), "entry");
+        let mut fallthrough = EvmBlock::new(None, BTreeSet::new(), "fallthrough");
+        let mut target = EvmBlock::new(None, BTreeSet::new(), "target");
+
+        entry.push1([0x01].to_vec());
+        entry.jump_if_to(&target.name);
+        fallthrough.stop();
+        target.stop();
+
+        let mut ir = ir_from_blocks([entry, fallthrough, target].to_vec());
+        let report = PeepholeOptimiser::new().optimise(&mut ir);
+
+        let blocks = &ir.functions[0].blocks;
+        assert_eq!(blocks[0].instructions[0].opcode, Opcode::JUMPDEST);
+        assert_eq!(blocks[1].instructions[0].opcode, Opcode::STOP);
+        assert_eq!(blocks[2].instructions[0].opcode, Opcode::JUMPDEST);
+        assert_eq!(report.bytes_saved(), 1);
+        assert_eq!(report.gas_saved(), 1);
+    }
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use evm_assembly::types::{arguments_from_json, EvmTypeValue};
+    use primitive_types::{H160, U256};
+
+    #[test]
+    fn test_message_params() {
+        let message = r#"{
+            "_tag": "transfer",
+            "_amount": "0",
+            "_sender": "0x1234567890123456789012345678901234567890",
+            "params": [
+                { "vname": "to", "type": "ByStr20", "value": "0x00000000000000000000000000000000000000ff" },
+                { "vname": "amount", "type": "Uint128", "value": "1000" },
+                { "vname": "delta", "type": "Int32", "value": "-5" },
+                { "vname": "hash", "type": "ByStr4", "value": "0xdeadbeef" },
+                { "vname": "paused", "type": "Bool",
+                  "value": { "constructor": "False", "argtypes": [], "arguments": [] } },
+                { "vname": "limit", "type": "Option (Uint32)",
+                  "value": { "constructor": "Some", "argtypes": ["Uint32"], "arguments": ["7"] } }
+            ]
+        }"#;
+
+        let arguments = arguments_from_json(message).expect("Failed to parse message");
+        assert_eq!(
+            arguments,
+            [
+                EvmTypeValue::Address(H160::from_low_u64_be(0xff)),
+                EvmTypeValue::Uint128(1000),
+                EvmTypeValue::Int32(-5),
+                EvmTypeValue::BytesN(vec![0xde, 0xad, 0xbe, 0xef]),
+                EvmTypeValue::Bool(false),
+                EvmTypeValue::Adt {
+                    constructor: "Some".to_string(),
+                    argtypes: vec!["Uint32".to_string()],
+                    arguments: vec![EvmTypeValue::Uint32(7)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        assert!(arguments_from_json(
+            r#"[{ "vname": "to", "type": "ByStr20", "value": "0x1234" }]"#
+        )
+        .is_err());
+        assert!(
+            arguments_from_json(r#"[{ "vname": "x", "type": "Uint32", "value": "-1" }]"#).is_err()
+        );
+        assert!(arguments_from_json(r#"{ "_tag": "transfer" }"#).is_err());
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(EvmTypeValue::Int64(-1).to_bytes(), Ok(vec![0xff; 32]));
+        assert_eq!(EvmTypeValue::Bool(true).to_bytes().unwrap()[31], 1);
+
+        let address = EvmTypeValue::Address(H160::from_low_u64_be(0xff));
+        let word = address.to_bytes().unwrap();
+        assert_eq!(&word[..12], &[0; 12]);
+        assert_eq!(word[31], 0xff);
+
+        let value = EvmTypeValue::Uint256(U256::MAX);
+        assert_eq!(value.to_bytes(), Ok(vec![0xff; 32]));
+
+        assert!(EvmTypeValue::Bytes(vec![0; 33]).to_bytes().is_err());
+        assert!(EvmTypeValue::String("a".repeat(33)).to_bytes().is_err());
+        let value = EvmTypeValue::Adt {
+            constructor: "Some".to_string(),
+            argtypes: vec!["Uint32".to_string()],
+            arguments: vec![EvmTypeValue::Uint32(7)],
+        };
+        assert!(value.to_bytes_unpadded().is_err());
+
+        let value = EvmTypeValue::from_scilla_json("Int256", &serde_json::json!("-2")).unwrap();
+        assert_eq!(value, EvmTypeValue::Int256(U256::MAX - 1));
+        assert!(EvmTypeValue::from_scilla_json(
+            "Int256",
+            &serde_json::json!(
+                "57896044618658097711785492504343953926634992332820282019728792003956564819968"
+            )
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_adt_arguments() {
+        let list = serde_json::json!({
+            "constructor": "Cons",
+            "argtypes": ["Int32"],
+            "arguments": [
+                "-1",
+                { "constructor": "Nil", "argtypes": ["Int32"], "arguments": [] }
+            ]
+        });
+        assert_eq!(
+            EvmTypeValue::from_scilla_json("List (Int32)", &list),
+            Ok(EvmTypeValue::Adt {
+                constructor: "Cons".to_string(),
+                argtypes: vec!["Int32".to_string()],
+                arguments: vec![
+                    EvmTypeValue::Int32(-1),
+                    EvmTypeValue::Adt {
+                        constructor: "Nil".to_string(),
+                        argtypes: vec!["Int32".to_string()],
+                        arguments: vec![],
+                    },
+                ],
+            })
+        );
+
+        let nat = serde_json::json!({
+            "constructor": "Succ",
+            "argtypes": [],
+            "arguments": [{ "constructor": "Zero", "argtypes": [], "arguments": [] }]
+        });
+        let value = EvmTypeValue::from_scilla_json("Nat", &nat).unwrap();
+        assert_eq!(
+            value,
+            EvmTypeValue::Adt {
+                constructor: "Succ".to_string(),
+                argtypes: vec![],
+                arguments: vec![EvmTypeValue::Adt {
+                    constructor: "Zero".to_string(),
+                    argtypes: vec![],
+                    arguments: vec![],
+                }],
+            }
+        );
+
+        let pair = serde_json::json!({
+            "constructor": "Pair",
+            "argtypes": ["Uint128", "ByStr4"],
+            "arguments": ["1", "0xdeadbeef"]
+        });
+        assert_eq!(
+            EvmTypeValue::from_scilla_json("Pair (Uint128) (ByStr4)", &pair),
+            Ok(EvmTypeValue::Adt {
+                constructor: "Pair".to_string(),
+                argtypes: vec!["Uint128".to_string(), "ByStr4".to_string()],
+                arguments: vec![
+                    EvmTypeValue::Uint128(1),
+                    EvmTypeValue::BytesN(vec![0xde, 0xad, 0xbe, 0xef]),
+                ],
+            })
+        );
+
+        let invalid = serde_json::json!({
+            "constructor": "Some",
+            "argtypes": ["Uint32"],
+            "arguments": []
+        });
+        assert!(EvmTypeValue::from_scilla_json("Option (Uint32)", &invalid).is_err());
+        let invalid = serde_json::json!({
+            "constructor": "Cons",
+            "argtypes": ["Uint32"],
+            "arguments": ["1", "2"]
+        });
+        assert!(EvmTypeValue::from_scilla_json("List (Uint32)", &invalid).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let values = vec![
+            EvmTypeValue::Int64(-3),
+            EvmTypeValue::Bool(true),
+            EvmTypeValue::Adt {
+                constructor: "Pair".to_string(),
+                argtypes: vec!["Uint32".to_string(), "String".to_string()],
+                arguments: vec![
+                    EvmTypeValue::Uint32(1),
+                    EvmTypeValue::String("one".to_string()),
+                ],
+            },
+        ];
+        let json = serde_json::to_string(&values).expect("Failed to serialize");
+        assert!(json.contains(r#"{"argtypes":[],"arguments":[],"constructor":"True"}"#));
+
+        let deserialized: Vec<EvmTypeValue> =
+            serde_json::from_str(&json).expect("Failed to deserialize");
+        assert_eq!(deserialized, values);
+
+        let value = EvmTypeValue::Int256(U256::MAX);
+        assert_eq!(serde_json::to_string(&value).unwrap(), r#""-1""#);
+    }
+}
@@ -171,22 +171,22 @@ impl Reducer<State> for StateMessage {
 
                 state.data = "0x00".to_string();
 
-                let result = compiler.executable_from_script(source_code.to_string());
-                if let Ok(exec) = result {
-                    state.functions = exec
+                let result = compiler.compile_with_diagnostics(source_code.to_string());
+                if let Ok(executable) = result {
+                    state.functions = compiler
                         .context
                         .function_declarations
                         .iter()
                         .map(|(_k, v)| v.clone())
                         .collect();
                     state.source_code = source_code.clone();
-                    state.bytecode_hex = hex::encode(&exec.executable.bytecode.clone());
-                    let code: Vec<u8> = (&*exec.executable.bytecode).to_vec();
+                    state.bytecode_hex = hex::encode(&executable.bytecode.clone());
+                    let code: Vec<u8> = (&*executable.bytecode).to_vec();
 
                     // Creating PC to source map
-                    state.pc_to_position = exec.executable.get_source_map();
+                    state.pc_to_position = executable.get_source_map();
 
-                    state.executable = Some(Rc::new(RefCell::new(exec.executable)));
+                    state.executable = Some(Rc::new(RefCell::new(executable)));
                     let mut observable_machine = ObservableMachine::new(
                         code.into(),
                         [].to_vec().into(),
@@ -207,8 +207,10 @@ impl Reducer<State> for StateMessage {
                     }
                     state.context = Some(context);
                 } else {
-                    if let Err(e) = result {
-                        console::error!(format!("{:#?}", e));
+                    if let Err(diagnostics) = result {
+                        for diagnostic in diagnostics.iter() {
+                            console::error!(diagnostic.render(&source_code));
+                        }
                     }
                     console::error!("Compilation failed!");
                 }
@@ -239,6 +241,7 @@ impl Reducer<State> for StateMessage {
                         .get_function(&function_name)
                         .expect(&format!("Function name {} not found", function_name).to_string())
                         .generate_transaction_data(args)
+                        .expect("Failed to encode the arguments")
                         .into()
                 } else {
                     Rc::new([].to_vec())